@@ -14,6 +14,39 @@
 #include "concrete-num_complex.h"
 
 
+#define COMPARISON_EQUAL 1
+
+#define COMPARISON_GREATER 2
+
+#define COMPARISON_LESS 0
+
+enum IntegrityStatus
+#ifdef __cplusplus
+  : uint32_t
+#endif // __cplusplus
+ {
+  Consistent = 0,
+  ParameterMismatch = 1,
+  KeyMismatch = 2,
+  KeyCorrupted = 3,
+};
+#ifndef __cplusplus
+typedef uint32_t IntegrityStatus;
+#endif // __cplusplus
+
+enum ModulusSwitching
+#ifdef __cplusplus
+  : uint32_t
+#endif // __cplusplus
+ {
+  StandardModulusSwitching = 0,
+  CenteredMeanModulusSwitching = 1,
+  ShiftedKeyModulusSwitching = 2,
+};
+#ifndef __cplusplus
+typedef uint32_t ModulusSwitching;
+#endif // __cplusplus
+
 enum Parallelism
 #ifdef __cplusplus
   : uint32_t
@@ -26,6 +59,18 @@ enum Parallelism
 typedef uint32_t Parallelism;
 #endif // __cplusplus
 
+enum RadixStatus
+#ifdef __cplusplus
+  : uint32_t
+#endif // __cplusplus
+ {
+  Computed = 0,
+  EmptyInteger = 1,
+};
+#ifndef __cplusplus
+typedef uint32_t RadixStatus;
+#endif // __cplusplus
+
 enum ScratchStatus
 #ifdef __cplusplus
   : uint32_t
@@ -38,14 +83,43 @@ enum ScratchStatus
 typedef uint32_t ScratchStatus;
 #endif // __cplusplus
 
+enum SecurityCurvesStatus
+#ifdef __cplusplus
+  : uint32_t
+#endif // __cplusplus
+ {
+  Loaded = 0,
+  Unreadable = 1,
+  Invalid = 2,
+};
+#ifndef __cplusplus
+typedef uint32_t SecurityCurvesStatus;
+#endif // __cplusplus
+
 typedef struct Csprng Csprng;
 
 typedef struct EncCsprng EncCsprng;
 
 typedef struct Fft Fft;
 
+typedef struct ScratchArena ScratchArena;
+
 typedef struct SecCsprng SecCsprng;
 
+typedef struct SecurityCurves SecurityCurves;
+
+typedef struct CiphertextFingerprint {
+  uint64_t parameter_hash;
+  uint64_t key_id;
+} CiphertextFingerprint;
+
+typedef struct KeyFingerprint {
+  uint64_t parameter_hash;
+  uint64_t input_key_id;
+  uint64_t output_key_id;
+  uint64_t content_hash;
+} KeyFingerprint;
+
 typedef struct Uint128 {
   uint8_t little_endian_bytes[16];
 } Uint128;
@@ -58,6 +132,10 @@ extern const size_t CONCRETE_FFT_ALIGN;
 
 extern const size_t CONCRETE_FFT_SIZE;
 
+extern const size_t CONCRETE_SCRATCH_ARENA_ALIGN;
+
+extern const size_t CONCRETE_SCRATCH_ARENA_SIZE;
+
 extern const size_t CSPRNG_ALIGN;
 
 extern const size_t CSPRNG_SIZE;
@@ -80,6 +158,14 @@ void concrete_cpu_add_plaintext_lwe_ciphertext_u64(uint64_t *ct_out,
                                                    uint64_t plaintext,
                                                    size_t lwe_dimension);
 
+void concrete_cpu_batched_dot_lwe_ciphertext_u64(uint64_t *ct_out,
+                                                 const uint64_t *ct_in,
+                                                 const uint64_t *weights,
+                                                 size_t batch_count,
+                                                 size_t weight_count,
+                                                 size_t lwe_dimension,
+                                                 Parallelism parallelism);
+
 void concrete_cpu_bootstrap_key_convert_u64_to_fourier(const uint64_t *standard_bsk,
                                                        c64 *fourier_bsk,
                                                        size_t decomposition_level_count,
@@ -113,12 +199,58 @@ void concrete_cpu_bootstrap_lwe_ciphertext_u64(uint64_t *ct_out,
                                                uint8_t *stack,
                                                size_t stack_size);
 
+IntegrityStatus concrete_cpu_bootstrap_lwe_ciphertext_u64_checked(uint64_t *ct_out,
+                                                                  struct CiphertextFingerprint *ct_out_fingerprint,
+                                                                  const uint64_t *ct_in,
+                                                                  const struct CiphertextFingerprint *ct_in_fingerprint,
+                                                                  const uint64_t *accumulator,
+                                                                  const c64 *fourier_bsk,
+                                                                  const struct KeyFingerprint *bsk_fingerprint,
+                                                                  size_t decomposition_level_count,
+                                                                  size_t decomposition_base_log,
+                                                                  size_t glwe_dimension,
+                                                                  size_t polynomial_size,
+                                                                  size_t input_lwe_dimension,
+                                                                  const struct Fft *fft,
+                                                                  uint8_t *stack,
+                                                                  size_t stack_size);
+
 ScratchStatus concrete_cpu_bootstrap_lwe_ciphertext_u64_scratch(size_t *stack_size,
                                                                 size_t *stack_align,
                                                                 size_t glwe_dimension,
                                                                 size_t polynomial_size,
                                                                 const struct Fft *fft);
 
+void concrete_cpu_bootstrap_lwe_ciphertext_with_modulus_switching_u64(uint64_t *ct_out,
+                                                                      const uint64_t *ct_in,
+                                                                      const uint64_t *accumulator,
+                                                                      const c64 *fourier_bsk,
+                                                                      size_t decomposition_level_count,
+                                                                      size_t decomposition_base_log,
+                                                                      size_t glwe_dimension,
+                                                                      size_t polynomial_size,
+                                                                      size_t input_lwe_dimension,
+                                                                      ModulusSwitching modulus_switching,
+                                                                      double key_mean,
+                                                                      const struct Fft *fft,
+                                                                      uint8_t *stack,
+                                                                      size_t stack_size);
+
+ScratchStatus concrete_cpu_bootstrap_lwe_ciphertext_with_modulus_switching_u64_scratch(size_t *stack_size,
+                                                                                       size_t *stack_align,
+                                                                                       size_t glwe_dimension,
+                                                                                       size_t polynomial_size,
+                                                                                       size_t input_lwe_dimension,
+                                                                                       const struct Fft *fft);
+
+void concrete_cpu_broadcast_dot_lwe_ciphertext_u64(uint64_t *ct_out,
+                                                   const uint64_t *ct_in,
+                                                   const uint64_t *weights,
+                                                   size_t weight_count,
+                                                   size_t ct_in_len,
+                                                   size_t lwe_dimension,
+                                                   Parallelism parallelism);
+
 void concrete_cpu_circuit_bootstrap_boolean_vertical_packing_lwe_ciphertext_u64(uint64_t *ct_out_vec,
                                                                                 const uint64_t *ct_in_vec,
                                                                                 const uint64_t *lut,
@@ -143,6 +275,8 @@ void concrete_cpu_circuit_bootstrap_boolean_vertical_packing_lwe_ciphertext_u64(
                                                                                 size_t _fpksk_count,
                                                                                 size_t cbs_decomposition_level_count,
                                                                                 size_t cbs_decomposition_base_log,
+                                                                                Parallelism parallelism,
+                                                                                size_t worker_count,
                                                                                 const struct Fft *fft,
                                                                                 uint8_t *stack,
                                                                                 size_t stack_size);
@@ -158,14 +292,22 @@ ScratchStatus concrete_cpu_circuit_bootstrap_boolean_vertical_packing_lwe_cipher
                                                                                                  size_t bsk_polynomial_size,
                                                                                                  size_t fpksk_output_polynomial_size,
                                                                                                  size_t cbs_decomposition_level_count,
+                                                                                                 Parallelism parallelism,
+                                                                                                 size_t worker_count,
                                                                                                  const struct Fft *fft);
 
+size_t concrete_cpu_circuit_bootstrap_boolean_vertical_packing_worker_count(void);
+
 void concrete_cpu_construct_concrete_fft(struct Fft *mem, size_t polynomial_size);
 
 void concrete_cpu_construct_csprng(struct Csprng *mem, struct Uint128 seed);
 
 void concrete_cpu_construct_encryption_csprng(struct EncCsprng *mem, struct Uint128 seed);
 
+void concrete_cpu_construct_scratch_arena(struct ScratchArena *mem,
+                                          size_t stack_size,
+                                          size_t stack_align);
+
 void concrete_cpu_construct_secret_csprng(struct SecCsprng *mem, struct Uint128 seed);
 
 int concrete_cpu_crypto_secure_random_128(struct Uint128 *u128);
@@ -211,8 +353,18 @@ void concrete_cpu_destroy_csprng(struct Csprng *mem);
 
 void concrete_cpu_destroy_encryption_csprng(struct EncCsprng *mem);
 
+void concrete_cpu_destroy_scratch_arena(struct ScratchArena *mem);
+
 void concrete_cpu_destroy_secret_csprng(struct SecCsprng *mem);
 
+void concrete_cpu_destroy_security_curves(struct SecurityCurves *curves);
+
+void concrete_cpu_dot_lwe_ciphertext_u64(uint64_t *ct_out,
+                                         const uint64_t *ct_in,
+                                         const uint64_t *weights,
+                                         size_t ct_in_count,
+                                         size_t lwe_dimension);
+
 void concrete_cpu_encrypt_ggsw_ciphertext_u64(const uint64_t *glwe_sk,
                                               uint64_t *ggsw_out,
                                               uint64_t input,
@@ -355,8 +507,45 @@ void concrete_cpu_keyswitch_lwe_ciphertext_u64(uint64_t *ct_out,
                                                size_t input_dimension,
                                                size_t output_dimension);
 
+IntegrityStatus concrete_cpu_keyswitch_lwe_ciphertext_u64_checked(uint64_t *ct_out,
+                                                                  struct CiphertextFingerprint *ct_out_fingerprint,
+                                                                  const uint64_t *ct_in,
+                                                                  const struct CiphertextFingerprint *ct_in_fingerprint,
+                                                                  const uint64_t *keyswitch_key,
+                                                                  const struct KeyFingerprint *ksk_fingerprint,
+                                                                  size_t decomposition_level_count,
+                                                                  size_t decomposition_base_log,
+                                                                  size_t input_dimension,
+                                                                  size_t output_dimension);
+
+SecurityCurvesStatus concrete_cpu_load_security_curves(const char *path,
+                                                       struct SecurityCurves **curves);
+
+void concrete_cpu_lwe_bootstrap_key_fingerprint_u64(struct KeyFingerprint *fingerprint,
+                                                    const c64 *fourier_bsk,
+                                                    uint64_t input_key_id,
+                                                    uint64_t output_key_id,
+                                                    size_t decomposition_level_count,
+                                                    size_t decomposition_base_log,
+                                                    size_t glwe_dimension,
+                                                    size_t polynomial_size,
+                                                    size_t input_lwe_dimension);
+
+void concrete_cpu_lwe_ciphertext_fingerprint_u64(struct CiphertextFingerprint *fingerprint,
+                                                 uint64_t key_id,
+                                                 size_t lwe_dimension);
+
 size_t concrete_cpu_lwe_ciphertext_size_u64(size_t lwe_dimension);
 
+void concrete_cpu_lwe_keyswitch_key_fingerprint_u64(struct KeyFingerprint *fingerprint,
+                                                    const uint64_t *keyswitch_key,
+                                                    uint64_t input_key_id,
+                                                    uint64_t output_key_id,
+                                                    size_t decomposition_level_count,
+                                                    size_t decomposition_base_log,
+                                                    size_t input_dimension,
+                                                    size_t output_dimension);
+
 size_t concrete_cpu_lwe_packing_keyswitch_key_size(size_t output_glwe_dimension,
                                                    size_t polynomial_size,
                                                    size_t decomposition_level_count,
@@ -364,6 +553,14 @@ size_t concrete_cpu_lwe_packing_keyswitch_key_size(size_t output_glwe_dimension,
 
 size_t concrete_cpu_lwe_secret_key_size_u64(size_t lwe_dimension);
 
+void concrete_cpu_matmul_cleartext_lwe_ciphertext_u64(uint64_t *ct_out,
+                                                      const uint64_t *weights,
+                                                      const uint64_t *ct_in,
+                                                      size_t row_count,
+                                                      size_t col_count,
+                                                      size_t lwe_dimension,
+                                                      Parallelism parallelism);
+
 void concrete_cpu_mul_cleartext_lwe_ciphertext_u64(uint64_t *ct_out,
                                                    const uint64_t *ct_in,
                                                    uint64_t cleartext,
@@ -373,6 +570,99 @@ void concrete_cpu_negate_lwe_ciphertext_u64(uint64_t *ct_out,
                                             const uint64_t *ct_in,
                                             size_t lwe_dimension);
 
+void concrete_cpu_radix_bivariate_lut_lwe_ciphertext_u64(uint64_t *ct_out,
+                                                         const uint64_t *ct_lhs,
+                                                         const uint64_t *ct_rhs,
+                                                         const uint64_t *lut,
+                                                         const c64 *fourier_bsk,
+                                                         size_t bsk_decomposition_level_count,
+                                                         size_t bsk_decomposition_base_log,
+                                                         size_t glwe_dimension,
+                                                         size_t polynomial_size,
+                                                         const uint64_t *keyswitch_key,
+                                                         size_t ksk_decomposition_level_count,
+                                                         size_t ksk_decomposition_base_log,
+                                                         size_t small_lwe_dimension,
+                                                         uint64_t message_modulus,
+                                                         uint64_t carry_modulus,
+                                                         const struct Fft *fft,
+                                                         uint8_t *stack,
+                                                         size_t stack_size);
+
+RadixStatus concrete_cpu_radix_compare_lwe_ciphertext_u64(uint64_t *ct_out,
+                                                          const uint64_t *ct_lhs,
+                                                          const uint64_t *ct_rhs,
+                                                          size_t block_count,
+                                                          const c64 *fourier_bsk,
+                                                          size_t bsk_decomposition_level_count,
+                                                          size_t bsk_decomposition_base_log,
+                                                          size_t glwe_dimension,
+                                                          size_t polynomial_size,
+                                                          const uint64_t *keyswitch_key,
+                                                          size_t ksk_decomposition_level_count,
+                                                          size_t ksk_decomposition_base_log,
+                                                          size_t small_lwe_dimension,
+                                                          uint64_t message_modulus,
+                                                          uint64_t carry_modulus,
+                                                          const struct Fft *fft,
+                                                          uint8_t *stack,
+                                                          size_t stack_size);
+
+ScratchStatus concrete_cpu_radix_lwe_ciphertext_u64_scratch(size_t *stack_size,
+                                                            size_t *stack_align,
+                                                            size_t glwe_dimension,
+                                                            size_t polynomial_size,
+                                                            const struct Fft *fft);
+
+RadixStatus concrete_cpu_radix_propagate_carry_lwe_ciphertext_u64(uint64_t *ct_in_out,
+                                                                  size_t block_count,
+                                                                  const c64 *fourier_bsk,
+                                                                  size_t bsk_decomposition_level_count,
+                                                                  size_t bsk_decomposition_base_log,
+                                                                  size_t glwe_dimension,
+                                                                  size_t polynomial_size,
+                                                                  const uint64_t *keyswitch_key,
+                                                                  size_t ksk_decomposition_level_count,
+                                                                  size_t ksk_decomposition_base_log,
+                                                                  size_t small_lwe_dimension,
+                                                                  uint64_t message_modulus,
+                                                                  uint64_t carry_modulus,
+                                                                  const struct Fft *fft,
+                                                                  uint8_t *stack,
+                                                                  size_t stack_size);
+
+RadixStatus concrete_cpu_radix_sign_lwe_ciphertext_u64(uint64_t *ct_out,
+                                                       const uint64_t *ct_in,
+                                                       size_t block_count,
+                                                       const c64 *fourier_bsk,
+                                                       size_t bsk_decomposition_level_count,
+                                                       size_t bsk_decomposition_base_log,
+                                                       size_t glwe_dimension,
+                                                       size_t polynomial_size,
+                                                       const uint64_t *keyswitch_key,
+                                                       size_t ksk_decomposition_level_count,
+                                                       size_t ksk_decomposition_base_log,
+                                                       size_t small_lwe_dimension,
+                                                       uint64_t message_modulus,
+                                                       uint64_t carry_modulus,
+                                                       const struct Fft *fft,
+                                                       uint8_t *stack,
+                                                       size_t stack_size);
+
+ScratchStatus concrete_cpu_scratch_arena_requirement(size_t *stack_size,
+                                                     size_t *stack_align,
+                                                     size_t bsk_glwe_dimension,
+                                                     size_t bsk_polynomial_size,
+                                                     size_t ksk_output_dimension,
+                                                     size_t wop_ct_in_count,
+                                                     size_t wop_lut_count,
+                                                     size_t fpksk_output_polynomial_size,
+                                                     size_t cbs_decomposition_level_count,
+                                                     size_t worker_count,
+                                                     const struct Fft *fft);
+
+uint8_t *concrete_cpu_scratch_arena_stack(struct ScratchArena *arena, size_t *stack_size);
+
 size_t concrete_cpu_seeded_bootstrap_key_size_u64(size_t decomposition_level_count,
                                                   size_t glwe_dimension,
                                                   size_t polynomial_size,
@@ -398,7 +688,8 @@ void simulation_circuit_bootstrap_boolean_vertical_packing_lwe_ciphertext_u64(co
                                                                               uint64_t pp_level,
                                                                               uint64_t pp_log_base,
                                                                               uint32_t ciphertext_modulus_log,
-                                                                              uint64_t security_level);
+                                                                              uint64_t security_level,
+                                                                              const struct SecurityCurves *security_curves);
 
 void simulation_extract_bit_lwe_ciphertext_u64(uint64_t *lwe_list_out,
                                                uint64_t lwe_in,
@@ -412,7 +703,8 @@ void simulation_extract_bit_lwe_ciphertext_u64(uint64_t *lwe_list_out,
                                                uint64_t br_log_base,
                                                uint64_t br_level,
                                                uint32_t ciphertext_modulus_log,
-                                               uint64_t security_level);
+                                               uint64_t security_level,
+                                               const struct SecurityCurves *security_curves);
 
 #ifdef __cplusplus
 } // extern "C"
@@ -3,9 +3,13 @@ pub mod bootstrap;
 pub mod csprng;
 pub mod encrypt;
 pub mod fft;
+pub mod integrity;
 pub mod keyswitch;
 pub mod linear_op;
+pub mod radix;
+pub mod scratch;
 pub mod secret_key;
+pub mod security_curves;
 pub mod types;
 pub mod wop_pbs;
 pub mod wop_pbs_simulation;
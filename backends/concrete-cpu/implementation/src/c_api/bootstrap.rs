@@ -3,7 +3,10 @@ use concrete_fft::c64;
 use tfhe::core_crypto::commons::math::random::{CompressionSeed, Seed};
 use tfhe::core_crypto::prelude::*;
 
-use crate::c_api::types::{EncCsprng, Parallelism, ScratchStatus, Uint128};
+use crate::c_api::types::{EncCsprng, ModulusSwitching, Parallelism, ScratchStatus, Uint128};
+use crate::implementation::modulus_switching::{
+    bootstrap_with_modulus_switching, bootstrap_with_modulus_switching_requirement,
+};
 use core::slice;
 use dyn_stack::PodStack;
 
@@ -413,6 +416,111 @@ pub unsafe extern "C" fn concrete_cpu_bootstrap_lwe_ciphertext_u64(
     })
 }
 
+#[no_mangle]
+#[must_use]
+pub unsafe extern "C" fn concrete_cpu_bootstrap_lwe_ciphertext_with_modulus_switching_u64_scratch(
+    stack_size: *mut usize,
+    stack_align: *mut usize,
+    // bootstrap parameters
+    glwe_dimension: usize,
+    polynomial_size: usize,
+    input_lwe_dimension: usize,
+    // side resources
+    fft: *const Fft,
+) -> ScratchStatus {
+    nounwind(|| {
+        if let Ok(scratch) = bootstrap_with_modulus_switching_requirement(
+            GlweDimension(glwe_dimension).to_glwe_size(),
+            PolynomialSize(polynomial_size),
+            LweDimension(input_lwe_dimension),
+            (*fft).as_view(),
+        ) {
+            *stack_size = scratch.size_bytes();
+            *stack_align = scratch.align_bytes();
+            ScratchStatus::Valid
+        } else {
+            ScratchStatus::SizeOverflow
+        }
+    })
+}
+
+/// Bootstrap correcting the body of a copy of `ct_in` for the rounding of its mask, `key_mean`
+/// being the mean of the coefficients of the input key.
+#[no_mangle]
+pub unsafe extern "C" fn concrete_cpu_bootstrap_lwe_ciphertext_with_modulus_switching_u64(
+    // ciphertexts
+    ct_out: *mut u64,
+    ct_in: *const u64,
+    // accumulator
+    accumulator: *const u64,
+    // bootstrap key
+    fourier_bsk: *const c64,
+    // bootstrap parameters
+    decomposition_level_count: usize,
+    decomposition_base_log: usize,
+    glwe_dimension: usize,
+    polynomial_size: usize,
+    input_lwe_dimension: usize,
+    // modulus switching
+    modulus_switching: ModulusSwitching,
+    key_mean: f64,
+    // side resources
+    fft: *const Fft,
+    stack: *mut u8,
+    stack_size: usize,
+) {
+    nounwind(|| {
+        let output_lwe_dimension = glwe_dimension * polynomial_size;
+
+        let fourier = FourierLweBootstrapKey::from_container(
+            slice::from_raw_parts(
+                fourier_bsk,
+                concrete_cpu_fourier_bootstrap_key_size_u64(
+                    decomposition_level_count,
+                    glwe_dimension,
+                    polynomial_size,
+                    input_lwe_dimension,
+                ),
+            ),
+            LweDimension(input_lwe_dimension),
+            GlweDimension(glwe_dimension).to_glwe_size(),
+            PolynomialSize(polynomial_size),
+            DecompositionBaseLog(decomposition_base_log),
+            DecompositionLevelCount(decomposition_level_count),
+        );
+
+        let lwe_in = LweCiphertext::from_container(
+            slice::from_raw_parts(ct_in, input_lwe_dimension + 1),
+            CiphertextModulus::new_native(),
+        );
+
+        let mut lwe_out = LweCiphertext::from_container(
+            slice::from_raw_parts_mut(ct_out, output_lwe_dimension + 1),
+            CiphertextModulus::new_native(),
+        );
+
+        let accumulator = GlweCiphertext::from_container(
+            slice::from_raw_parts(
+                accumulator,
+                concrete_cpu_glwe_ciphertext_size_u64(glwe_dimension, polynomial_size),
+            ),
+            PolynomialSize(polynomial_size),
+            CiphertextModulus::new_native(),
+        );
+
+        bootstrap_with_modulus_switching(
+            &lwe_in,
+            &mut lwe_out,
+            &accumulator,
+            &fourier,
+            key_mean,
+            modulus_switching.into(),
+            (*fft).as_view(),
+            PodStack::new(slice::from_raw_parts_mut(stack as _, stack_size)),
+        );
+    })
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn concrete_cpu_bootstrap_key_size_u64(
     decomposition_level_count: usize,
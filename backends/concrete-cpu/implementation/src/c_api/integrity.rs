@@ -0,0 +1,588 @@
+use concrete_fft::c64;
+use tfhe::core_crypto::prelude::*;
+
+use super::bootstrap::{
+    concrete_cpu_bootstrap_lwe_ciphertext_u64, concrete_cpu_fourier_bootstrap_key_size_u64,
+};
+use super::keyswitch::{
+    concrete_cpu_keyswitch_key_size_u64, concrete_cpu_keyswitch_lwe_ciphertext_u64,
+};
+use super::types::{CiphertextFingerprint, IntegrityStatus, KeyFingerprint};
+use super::utils::nounwind;
+use crate::implementation::integrity::{
+    check, check_content, content_hash, parameter_hash, IntegrityError, ParameterDomain,
+};
+use core::slice;
+
+/// Whether the `_checked` operations verify fingerprints: always in debug builds, and in release
+/// builds with the `checked` feature.
+const CHECKS_ENABLED: bool = cfg!(any(debug_assertions, feature = "checked"));
+
+fn status(result: Result<(), IntegrityError>) -> IntegrityStatus {
+    match result {
+        Ok(()) => IntegrityStatus::Consistent,
+        Err(IntegrityError::ParameterMismatch) => IntegrityStatus::ParameterMismatch,
+        Err(IntegrityError::KeyMismatch) => IntegrityStatus::KeyMismatch,
+        Err(IntegrityError::KeyCorrupted) => IntegrityStatus::KeyCorrupted,
+    }
+}
+
+unsafe fn fourier_bootstrap_key_content_hash(
+    fourier_bsk: *const c64,
+    decomposition_level_count: usize,
+    glwe_dimension: usize,
+    polynomial_size: usize,
+    input_lwe_dimension: usize,
+) -> u64 {
+    let fourier_bsk = slice::from_raw_parts(
+        fourier_bsk,
+        concrete_cpu_fourier_bootstrap_key_size_u64(
+            decomposition_level_count,
+            glwe_dimension,
+            polynomial_size,
+            input_lwe_dimension,
+        ),
+    );
+    content_hash(
+        fourier_bsk
+            .iter()
+            .flat_map(|c| [c.re.to_bits(), c.im.to_bits()]),
+    )
+}
+
+unsafe fn keyswitch_key_content_hash(
+    keyswitch_key: *const u64,
+    decomposition_level_count: usize,
+    input_dimension: usize,
+    output_dimension: usize,
+) -> u64 {
+    content_hash(
+        slice::from_raw_parts(
+            keyswitch_key,
+            concrete_cpu_keyswitch_key_size_u64(
+                decomposition_level_count,
+                input_dimension,
+                output_dimension,
+            ),
+        )
+        .iter()
+        .copied(),
+    )
+}
+
+fn lwe_ciphertext_parameter_hash(lwe_dimension: usize) -> u64 {
+    parameter_hash(ParameterDomain::LweCiphertext, &[lwe_dimension])
+}
+
+fn bootstrap_key_parameter_hash(
+    decomposition_level_count: usize,
+    decomposition_base_log: usize,
+    glwe_dimension: usize,
+    polynomial_size: usize,
+    input_lwe_dimension: usize,
+) -> u64 {
+    parameter_hash(
+        ParameterDomain::BootstrapKey,
+        &[
+            decomposition_level_count,
+            decomposition_base_log,
+            glwe_dimension,
+            polynomial_size,
+            input_lwe_dimension,
+        ],
+    )
+}
+
+fn keyswitch_key_parameter_hash(
+    decomposition_level_count: usize,
+    decomposition_base_log: usize,
+    input_dimension: usize,
+    output_dimension: usize,
+) -> u64 {
+    parameter_hash(
+        ParameterDomain::KeyswitchKey,
+        &[
+            decomposition_level_count,
+            decomposition_base_log,
+            input_dimension,
+            output_dimension,
+        ],
+    )
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn concrete_cpu_lwe_ciphertext_fingerprint_u64(
+    fingerprint: *mut CiphertextFingerprint,
+    // secret key
+    key_id: u64,
+    // ciphertext dimension
+    lwe_dimension: usize,
+) {
+    nounwind(|| {
+        *fingerprint = CiphertextFingerprint {
+            parameter_hash: lwe_ciphertext_parameter_hash(lwe_dimension),
+            key_id,
+        };
+    })
+}
+
+/// Fingerprints the Fourier bootstrap key `fourier_bsk`, as given to the checked bootstrap.
+///
+/// # Safety
+///
+/// `fourier_bsk` must be a valid range for reads of the Fourier bootstrap key size.
+#[no_mangle]
+pub unsafe extern "C" fn concrete_cpu_lwe_bootstrap_key_fingerprint_u64(
+    fingerprint: *mut KeyFingerprint,
+    // bootstrap key
+    fourier_bsk: *const c64,
+    // secret keys
+    input_key_id: u64,
+    output_key_id: u64,
+    // bootstrap parameters
+    decomposition_level_count: usize,
+    decomposition_base_log: usize,
+    glwe_dimension: usize,
+    polynomial_size: usize,
+    input_lwe_dimension: usize,
+) {
+    nounwind(|| {
+        *fingerprint = KeyFingerprint {
+            parameter_hash: bootstrap_key_parameter_hash(
+                decomposition_level_count,
+                decomposition_base_log,
+                glwe_dimension,
+                polynomial_size,
+                input_lwe_dimension,
+            ),
+            input_key_id,
+            output_key_id,
+            content_hash: fourier_bootstrap_key_content_hash(
+                fourier_bsk,
+                decomposition_level_count,
+                glwe_dimension,
+                polynomial_size,
+                input_lwe_dimension,
+            ),
+        };
+    })
+}
+
+/// Fingerprints the keyswitch key `keyswitch_key`.
+///
+/// # Safety
+///
+/// `keyswitch_key` must be a valid range for reads of the keyswitch key size.
+#[no_mangle]
+pub unsafe extern "C" fn concrete_cpu_lwe_keyswitch_key_fingerprint_u64(
+    fingerprint: *mut KeyFingerprint,
+    // keyswitch key
+    keyswitch_key: *const u64,
+    // secret keys
+    input_key_id: u64,
+    output_key_id: u64,
+    // keyswitch parameters
+    decomposition_level_count: usize,
+    decomposition_base_log: usize,
+    input_dimension: usize,
+    output_dimension: usize,
+) {
+    nounwind(|| {
+        *fingerprint = KeyFingerprint {
+            parameter_hash: keyswitch_key_parameter_hash(
+                decomposition_level_count,
+                decomposition_base_log,
+                input_dimension,
+                output_dimension,
+            ),
+            input_key_id,
+            output_key_id,
+            content_hash: keyswitch_key_content_hash(
+                keyswitch_key,
+                decomposition_level_count,
+                input_dimension,
+                output_dimension,
+            ),
+        };
+    })
+}
+
+/// Same as `concrete_cpu_bootstrap_lwe_ciphertext_u64`, after checking that `fourier_bsk` was
+/// generated with the given parameters, that its contents match its fingerprint and that `ct_in` is
+/// encrypted under its input key.
+///
+/// On mismatch, nothing is computed and the error is returned. On success `ct_out_fingerprint` is
+/// set to the output key of the bootstrap key. Checks are skipped in release builds without the
+/// `checked` feature.
+#[no_mangle]
+pub unsafe extern "C" fn concrete_cpu_bootstrap_lwe_ciphertext_u64_checked(
+    // ciphertexts
+    ct_out: *mut u64,
+    ct_out_fingerprint: *mut CiphertextFingerprint,
+    ct_in: *const u64,
+    ct_in_fingerprint: *const CiphertextFingerprint,
+    // accumulator
+    accumulator: *const u64,
+    // bootstrap key
+    fourier_bsk: *const c64,
+    bsk_fingerprint: *const KeyFingerprint,
+    // bootstrap parameters
+    decomposition_level_count: usize,
+    decomposition_base_log: usize,
+    glwe_dimension: usize,
+    polynomial_size: usize,
+    input_lwe_dimension: usize,
+    // side resources
+    fft: *const Fft,
+    stack: *mut u8,
+    stack_size: usize,
+) -> IntegrityStatus {
+    nounwind(|| {
+        let bsk_fingerprint = *bsk_fingerprint;
+        let ct_in_fingerprint = *ct_in_fingerprint;
+
+        if CHECKS_ENABLED {
+            let result = check(
+                bsk_fingerprint.parameter_hash,
+                bootstrap_key_parameter_hash(
+                    decomposition_level_count,
+                    decomposition_base_log,
+                    glwe_dimension,
+                    polynomial_size,
+                    input_lwe_dimension,
+                ),
+                bsk_fingerprint.input_key_id,
+                ct_in_fingerprint.parameter_hash,
+                lwe_ciphertext_parameter_hash(input_lwe_dimension),
+                ct_in_fingerprint.key_id,
+            )
+            .and_then(|()| {
+                check_content(
+                    bsk_fingerprint.content_hash,
+                    fourier_bootstrap_key_content_hash(
+                        fourier_bsk,
+                        decomposition_level_count,
+                        glwe_dimension,
+                        polynomial_size,
+                        input_lwe_dimension,
+                    ),
+                )
+            });
+            if result.is_err() {
+                return status(result);
+            }
+        }
+
+        concrete_cpu_bootstrap_lwe_ciphertext_u64(
+            ct_out,
+            ct_in,
+            accumulator,
+            fourier_bsk,
+            decomposition_level_count,
+            decomposition_base_log,
+            glwe_dimension,
+            polynomial_size,
+            input_lwe_dimension,
+            fft,
+            stack,
+            stack_size,
+        );
+
+        *ct_out_fingerprint = CiphertextFingerprint {
+            parameter_hash: lwe_ciphertext_parameter_hash(glwe_dimension * polynomial_size),
+            key_id: bsk_fingerprint.output_key_id,
+        };
+        IntegrityStatus::Consistent
+    })
+}
+
+/// Same as `concrete_cpu_keyswitch_lwe_ciphertext_u64`, after checking that `keyswitch_key` was
+/// generated with the given parameters, that its contents match its fingerprint and that `ct_in`
+/// is encrypted under its input key.
+///
+/// On mismatch, nothing is computed and the error is returned. On success `ct_out_fingerprint` is
+/// set to the output key of the keyswitch key. Checks are skipped in release builds without the
+/// `checked` feature.
+#[no_mangle]
+pub unsafe extern "C" fn concrete_cpu_keyswitch_lwe_ciphertext_u64_checked(
+    // ciphertexts
+    ct_out: *mut u64,
+    ct_out_fingerprint: *mut CiphertextFingerprint,
+    ct_in: *const u64,
+    ct_in_fingerprint: *const CiphertextFingerprint,
+    // keyswitch key
+    keyswitch_key: *const u64,
+    ksk_fingerprint: *const KeyFingerprint,
+    // keyswitch parameters
+    decomposition_level_count: usize,
+    decomposition_base_log: usize,
+    input_dimension: usize,
+    output_dimension: usize,
+) -> IntegrityStatus {
+    nounwind(|| {
+        let ksk_fingerprint = *ksk_fingerprint;
+        let ct_in_fingerprint = *ct_in_fingerprint;
+
+        if CHECKS_ENABLED {
+            let result = check(
+                ksk_fingerprint.parameter_hash,
+                keyswitch_key_parameter_hash(
+                    decomposition_level_count,
+                    decomposition_base_log,
+                    input_dimension,
+                    output_dimension,
+                ),
+                ksk_fingerprint.input_key_id,
+                ct_in_fingerprint.parameter_hash,
+                lwe_ciphertext_parameter_hash(input_dimension),
+                ct_in_fingerprint.key_id,
+            )
+            .and_then(|()| {
+                check_content(
+                    ksk_fingerprint.content_hash,
+                    keyswitch_key_content_hash(
+                        keyswitch_key,
+                        decomposition_level_count,
+                        input_dimension,
+                        output_dimension,
+                    ),
+                )
+            });
+            if result.is_err() {
+                return status(result);
+            }
+        }
+
+        concrete_cpu_keyswitch_lwe_ciphertext_u64(
+            ct_out,
+            ct_in,
+            keyswitch_key,
+            decomposition_level_count,
+            decomposition_base_log,
+            input_dimension,
+            output_dimension,
+        );
+
+        *ct_out_fingerprint = CiphertextFingerprint {
+            parameter_hash: lwe_ciphertext_parameter_hash(output_dimension),
+            key_id: ksk_fingerprint.output_key_id,
+        };
+        IntegrityStatus::Consistent
+    })
+}
+
+#[cfg(all(test, any(debug_assertions, feature = "checked")))]
+mod tests {
+    use super::*;
+
+    const KS_LEVEL: usize = 2;
+    const KS_BASE_LOG: usize = 4;
+    const INPUT_DIMENSION: usize = 8;
+    const OUTPUT_DIMENSION: usize = 4;
+    const SENTINEL: u64 = 0xdead_beef;
+
+    struct Keyswitch {
+        ksk: Vec<u64>,
+        ksk_fingerprint: KeyFingerprint,
+        ct_in: Vec<u64>,
+        ct_in_fingerprint: CiphertextFingerprint,
+    }
+
+    impl Keyswitch {
+        fn new() -> Self {
+            let size = unsafe {
+                concrete_cpu_keyswitch_key_size_u64(KS_LEVEL, INPUT_DIMENSION, OUTPUT_DIMENSION)
+            };
+            let ksk: Vec<u64> = (0..size as u64)
+                .map(|i| i.wrapping_mul(0x9e37_79b9))
+                .collect();
+            let mut ksk_fingerprint = KeyFingerprint {
+                parameter_hash: 0,
+                input_key_id: 0,
+                output_key_id: 0,
+                content_hash: 0,
+            };
+            let mut ct_in_fingerprint = CiphertextFingerprint {
+                parameter_hash: 0,
+                key_id: 0,
+            };
+            unsafe {
+                concrete_cpu_lwe_keyswitch_key_fingerprint_u64(
+                    &mut ksk_fingerprint,
+                    ksk.as_ptr(),
+                    1,
+                    2,
+                    KS_LEVEL,
+                    KS_BASE_LOG,
+                    INPUT_DIMENSION,
+                    OUTPUT_DIMENSION,
+                );
+                concrete_cpu_lwe_ciphertext_fingerprint_u64(
+                    &mut ct_in_fingerprint,
+                    1,
+                    INPUT_DIMENSION,
+                );
+            }
+            Self {
+                ksk,
+                ksk_fingerprint,
+                ct_in: (0..=INPUT_DIMENSION as u64).collect(),
+                ct_in_fingerprint,
+            }
+        }
+
+        /// Runs the checked keyswitch on an output prefilled with `SENTINEL`.
+        fn run(&self) -> (IntegrityStatus, Vec<u64>, CiphertextFingerprint) {
+            let mut ct_out = vec![SENTINEL; OUTPUT_DIMENSION + 1];
+            let mut ct_out_fingerprint = CiphertextFingerprint {
+                parameter_hash: SENTINEL,
+                key_id: SENTINEL,
+            };
+            let status = unsafe {
+                concrete_cpu_keyswitch_lwe_ciphertext_u64_checked(
+                    ct_out.as_mut_ptr(),
+                    &mut ct_out_fingerprint,
+                    self.ct_in.as_ptr(),
+                    &self.ct_in_fingerprint,
+                    self.ksk.as_ptr(),
+                    &self.ksk_fingerprint,
+                    KS_LEVEL,
+                    KS_BASE_LOG,
+                    INPUT_DIMENSION,
+                    OUTPUT_DIMENSION,
+                )
+            };
+            (status, ct_out, ct_out_fingerprint)
+        }
+    }
+
+    fn assert_untouched(
+        (status, ct_out, ct_out_fingerprint): (IntegrityStatus, Vec<u64>, CiphertextFingerprint),
+        expected: IntegrityStatus,
+    ) {
+        assert_eq!(status as u32, expected as u32);
+        assert!(ct_out.iter().all(|&c| c == SENTINEL));
+        assert_eq!(ct_out_fingerprint.key_id, SENTINEL);
+    }
+
+    #[test]
+    fn keyswitch_checks_fingerprints_and_contents() {
+        let keyswitch = Keyswitch::new();
+        let (status, ct_out, ct_out_fingerprint) = keyswitch.run();
+        assert_eq!(status as u32, IntegrityStatus::Consistent as u32);
+        assert!(ct_out.iter().any(|&c| c != SENTINEL));
+        assert_eq!(ct_out_fingerprint.key_id, 2);
+
+        let mut wrong_key = Keyswitch::new();
+        wrong_key.ct_in_fingerprint.key_id = 3;
+        assert_untouched(wrong_key.run(), IntegrityStatus::KeyMismatch);
+
+        let mut wrong_parameters = Keyswitch::new();
+        wrong_parameters.ksk_fingerprint.parameter_hash ^= 1;
+        assert_untouched(wrong_parameters.run(), IntegrityStatus::ParameterMismatch);
+
+        // the fingerprint is the expected one, the buffer is not
+        let mut corrupted = Keyswitch::new();
+        corrupted.ksk[5] ^= 1 << 40;
+        assert_untouched(corrupted.run(), IntegrityStatus::KeyCorrupted);
+    }
+
+    #[test]
+    fn bootstrap_checks_fingerprints_and_contents() {
+        let (level, base_log, glwe_dimension, polynomial_size) = (1, 10, 1, 256);
+        let input_lwe_dimension = OUTPUT_DIMENSION;
+        let size = unsafe {
+            concrete_cpu_fourier_bootstrap_key_size_u64(
+                level,
+                glwe_dimension,
+                polynomial_size,
+                input_lwe_dimension,
+            )
+        };
+        let mut fourier_bsk: Vec<_> = (0..size).map(|i| c64::new(i as f64, 1.0)).collect();
+        let mut bsk_fingerprint = KeyFingerprint {
+            parameter_hash: 0,
+            input_key_id: 0,
+            output_key_id: 0,
+            content_hash: 0,
+        };
+        let mut ct_in_fingerprint = CiphertextFingerprint {
+            parameter_hash: 0,
+            key_id: 0,
+        };
+        unsafe {
+            concrete_cpu_lwe_bootstrap_key_fingerprint_u64(
+                &mut bsk_fingerprint,
+                fourier_bsk.as_ptr(),
+                2,
+                1,
+                level,
+                base_log,
+                glwe_dimension,
+                polynomial_size,
+                input_lwe_dimension,
+            );
+            concrete_cpu_lwe_ciphertext_fingerprint_u64(
+                &mut ct_in_fingerprint,
+                2,
+                input_lwe_dimension,
+            );
+        }
+        let ct_in = vec![0; input_lwe_dimension + 1];
+        let accumulator = vec![0; (glwe_dimension + 1) * polynomial_size];
+        let fft = Fft::new(PolynomialSize(polynomial_size));
+
+        let run = |fourier_bsk: &[c64],
+                   bsk_fingerprint: &KeyFingerprint,
+                   ct_in_fingerprint: &CiphertextFingerprint| {
+            let mut ct_out = vec![SENTINEL; glwe_dimension * polynomial_size + 1];
+            let mut ct_out_fingerprint = CiphertextFingerprint {
+                parameter_hash: SENTINEL,
+                key_id: SENTINEL,
+            };
+            let status = unsafe {
+                concrete_cpu_bootstrap_lwe_ciphertext_u64_checked(
+                    ct_out.as_mut_ptr(),
+                    &mut ct_out_fingerprint,
+                    ct_in.as_ptr(),
+                    ct_in_fingerprint,
+                    accumulator.as_ptr(),
+                    fourier_bsk.as_ptr(),
+                    bsk_fingerprint,
+                    level,
+                    base_log,
+                    glwe_dimension,
+                    polynomial_size,
+                    input_lwe_dimension,
+                    &fft,
+                    core::ptr::null_mut(),
+                    0,
+                )
+            };
+            (status, ct_out, ct_out_fingerprint)
+        };
+
+        // nothing is computed on mismatch, the missing stack is never used
+        let wrong_key = CiphertextFingerprint {
+            key_id: 1,
+            ..ct_in_fingerprint
+        };
+        assert_untouched(
+            run(&fourier_bsk, &bsk_fingerprint, &wrong_key),
+            IntegrityStatus::KeyMismatch,
+        );
+        let wrong_parameters = CiphertextFingerprint {
+            parameter_hash: ct_in_fingerprint.parameter_hash ^ 1,
+            ..ct_in_fingerprint
+        };
+        assert_untouched(
+            run(&fourier_bsk, &bsk_fingerprint, &wrong_parameters),
+            IntegrityStatus::ParameterMismatch,
+        );
+        fourier_bsk[7].im = -1.0;
+        assert_untouched(
+            run(&fourier_bsk, &bsk_fingerprint, &ct_in_fingerprint),
+            IntegrityStatus::KeyCorrupted,
+        );
+    }
+}
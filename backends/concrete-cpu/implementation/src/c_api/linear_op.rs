@@ -1,6 +1,47 @@
+use super::types::Parallelism;
 use super::utils::nounwind;
 use core::slice;
 
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// Adds `weight * ct_in` to `ct_out`, `weight` being taken modulo 2^64.
+#[inline]
+fn mul_add_assign(ct_out: &mut [u64], ct_in: &[u64], weight: u64) {
+    for (out, &c) in ct_out.iter_mut().zip(ct_in) {
+        *out = out.wrapping_add(c.wrapping_mul(weight));
+    }
+}
+
+/// Sets `ct_out` to the sum of the weighted ciphertexts.
+#[inline]
+fn weighted_sum<'a>(ct_out: &mut [u64], terms: impl Iterator<Item = (&'a [u64], u64)>) {
+    ct_out.fill(0);
+    for (ct_in, weight) in terms {
+        mul_add_assign(ct_out, ct_in, weight);
+    }
+}
+
+/// Runs `f` on each output ciphertext with its index, in parallel if requested.
+fn for_each_output(
+    ct_out: &mut [u64],
+    lwe_size: usize,
+    parallelism: Parallelism,
+    f: impl Fn(usize, &mut [u64]) + Sync,
+) {
+    let f = |(i, ct_out): (usize, &mut [u64])| pulp::Arch::new().dispatch(|| f(i, ct_out));
+    match parallelism {
+        Parallelism::No => ct_out.chunks_exact_mut(lwe_size).enumerate().for_each(f),
+        #[cfg(feature = "parallel")]
+        Parallelism::Rayon => ct_out
+            .par_chunks_exact_mut(lwe_size)
+            .enumerate()
+            .for_each(f),
+        #[cfg(not(feature = "parallel"))]
+        Parallelism::Rayon => ct_out.chunks_exact_mut(lwe_size).enumerate().for_each(f),
+    }
+}
+
 /// # Safety
 ///
 /// `[ct_out, ct_out + lwe_dimension + 1[` must be a valid mutable range, and must not alias
@@ -122,3 +163,346 @@ pub unsafe extern "C" fn concrete_cpu_negate_lwe_ciphertext_u64(
         });
     })
 }
+
+/// Computes `sum(weights[i] * ct_in[i])` for `i` in `[0, ct_in_count[`.
+///
+/// This covers the `Simple`, `Tensor` and `CompatibleTensor` dot kinds, where inputs and weights
+/// have the same number of elements.
+///
+/// # Safety
+///
+/// `[ct_out, ct_out + lwe_dimension + 1[` must be a valid mutable range, and must not alias
+/// `[ct_in, ct_in + ct_in_count * (lwe_dimension + 1)[`, which must be a valid range for reads, as
+/// well as `[weights, weights + ct_in_count[`.
+#[no_mangle]
+pub unsafe extern "C" fn concrete_cpu_dot_lwe_ciphertext_u64(
+    ct_out: *mut u64,
+    ct_in: *const u64,
+    weights: *const u64,
+    ct_in_count: usize,
+    lwe_dimension: usize,
+) {
+    nounwind(|| {
+        let lwe_size = lwe_dimension + 1;
+        let ct_in = slice::from_raw_parts(ct_in, ct_in_count * lwe_size);
+        let weights = slice::from_raw_parts(weights, ct_in_count);
+
+        pulp::Arch::new().dispatch(|| {
+            weighted_sum(
+                slice::from_raw_parts_mut(ct_out, lwe_size),
+                ct_in.chunks_exact(lwe_size).zip(weights.iter().copied()),
+            )
+        });
+    })
+}
+
+/// Computes the product of the cleartext matrix `weights`, of `row_count` rows and `col_count`
+/// columns stored row by row, with the encrypted vector `ct_in` of `col_count` ciphertexts.
+///
+/// # Safety
+///
+/// `[ct_out, ct_out + row_count * (lwe_dimension + 1)[` must be a valid mutable range, and must not
+/// alias `[ct_in, ct_in + col_count * (lwe_dimension + 1)[`, which must be a valid range for reads,
+/// as well as `[weights, weights + row_count * col_count[`.
+#[no_mangle]
+pub unsafe extern "C" fn concrete_cpu_matmul_cleartext_lwe_ciphertext_u64(
+    ct_out: *mut u64,
+    weights: *const u64,
+    ct_in: *const u64,
+    row_count: usize,
+    col_count: usize,
+    lwe_dimension: usize,
+    parallelism: Parallelism,
+) {
+    nounwind(|| {
+        let lwe_size = lwe_dimension + 1;
+        let ct_in = slice::from_raw_parts(ct_in, col_count * lwe_size);
+        let weights = slice::from_raw_parts(weights, row_count * col_count);
+
+        for_each_output(
+            slice::from_raw_parts_mut(ct_out, row_count * lwe_size),
+            lwe_size,
+            parallelism,
+            |row, ct_out| {
+                let row_weights = &weights[row * col_count..(row + 1) * col_count];
+                weighted_sum(
+                    ct_out,
+                    ct_in
+                        .chunks_exact(lwe_size)
+                        .zip(row_weights.iter().copied()),
+                );
+            },
+        );
+    })
+}
+
+/// Computes `sum(weights[i] * ct_in[i][j])` for each `j` in `[0, ct_in_len[`, where `ct_in` holds
+/// `weight_count` tensors of `ct_in_len` ciphertexts each.
+///
+/// This covers the `Broadcast` dot kinds where a single weight multiplies a whole tensor, or a
+/// vector of weights combines tensors elementwise. The `Broadcast` of a single tensor whose trailing
+/// dimensions match the weights is computed by [`concrete_cpu_batched_dot_lwe_ciphertext_u64`].
+///
+/// # Safety
+///
+/// `[ct_out, ct_out + ct_in_len * (lwe_dimension + 1)[` must be a valid mutable range, and must not
+/// alias `[ct_in, ct_in + weight_count * ct_in_len * (lwe_dimension + 1)[`, which must be a valid
+/// range for reads, as well as `[weights, weights + weight_count[`.
+#[no_mangle]
+pub unsafe extern "C" fn concrete_cpu_broadcast_dot_lwe_ciphertext_u64(
+    ct_out: *mut u64,
+    ct_in: *const u64,
+    weights: *const u64,
+    weight_count: usize,
+    ct_in_len: usize,
+    lwe_dimension: usize,
+    parallelism: Parallelism,
+) {
+    nounwind(|| {
+        let lwe_size = lwe_dimension + 1;
+        let ct_in = slice::from_raw_parts(ct_in, weight_count * ct_in_len * lwe_size);
+        let weights = slice::from_raw_parts(weights, weight_count);
+
+        for_each_output(
+            slice::from_raw_parts_mut(ct_out, ct_in_len * lwe_size),
+            lwe_size,
+            parallelism,
+            |j, ct_out| {
+                weighted_sum(
+                    ct_out,
+                    ct_in
+                        .chunks_exact(ct_in_len * lwe_size)
+                        .map(|tensor| &tensor[j * lwe_size..(j + 1) * lwe_size])
+                        .zip(weights.iter().copied()),
+                );
+            },
+        );
+    })
+}
+
+/// Computes `sum(weights[i] * ct_in[j][i])` for each `j` in `[0, batch_count[`, where `ct_in` holds
+/// `batch_count` tensors of `weight_count` ciphertexts each.
+///
+/// This covers the `Broadcast` dot kind of a single tensor whose trailing dimensions match the
+/// weights, a dot per slice of its first dimensions.
+///
+/// # Safety
+///
+/// `[ct_out, ct_out + batch_count * (lwe_dimension + 1)[` must be a valid mutable range, and must
+/// not alias `[ct_in, ct_in + batch_count * weight_count * (lwe_dimension + 1)[`, which must be a
+/// valid range for reads, as well as `[weights, weights + weight_count[`.
+#[no_mangle]
+pub unsafe extern "C" fn concrete_cpu_batched_dot_lwe_ciphertext_u64(
+    ct_out: *mut u64,
+    ct_in: *const u64,
+    weights: *const u64,
+    batch_count: usize,
+    weight_count: usize,
+    lwe_dimension: usize,
+    parallelism: Parallelism,
+) {
+    nounwind(|| {
+        let lwe_size = lwe_dimension + 1;
+        let ct_in = slice::from_raw_parts(ct_in, batch_count * weight_count * lwe_size);
+        let weights = slice::from_raw_parts(weights, weight_count);
+
+        for_each_output(
+            slice::from_raw_parts_mut(ct_out, batch_count * lwe_size),
+            lwe_size,
+            parallelism,
+            |j, ct_out| {
+                let tensor = &ct_in[j * weight_count * lwe_size..(j + 1) * weight_count * lwe_size];
+                weighted_sum(
+                    ct_out,
+                    tensor.chunks_exact(lwe_size).zip(weights.iter().copied()),
+                );
+            },
+        );
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use concrete_csprng::seeders::Seed;
+    use tfhe::core_crypto::commons::generators::DeterministicSeeder;
+    use tfhe::core_crypto::prelude::*;
+
+    const LWE_DIMENSION: usize = 16;
+    const LWE_SIZE: usize = LWE_DIMENSION + 1;
+    const PRECISION: u32 = 6;
+
+    struct Context {
+        sk: LweSecretKeyOwned<u64>,
+        encryption_generator: EncryptionRandomGenerator<ActivatedRandomGenerator>,
+    }
+
+    impl Context {
+        fn new() -> Self {
+            let mut secret_generator =
+                SecretRandomGenerator::<ActivatedRandomGenerator>::new(Seed(0));
+            let mut seeder = DeterministicSeeder::<ActivatedRandomGenerator>::new(Seed(1));
+            let encryption_generator =
+                EncryptionRandomGenerator::<ActivatedRandomGenerator>::new(Seed(2), &mut seeder);
+            let sk = allocate_and_generate_new_binary_lwe_secret_key(
+                LweDimension(LWE_DIMENSION),
+                &mut secret_generator,
+            );
+            Self {
+                sk,
+                encryption_generator,
+            }
+        }
+
+        fn encrypt(&mut self, messages: &[u64]) -> Vec<u64> {
+            let mut cts = vec![0; messages.len() * LWE_SIZE];
+            for (ct, &message) in cts.chunks_exact_mut(LWE_SIZE).zip(messages) {
+                let mut ct = LweCiphertext::from_container(ct, CiphertextModulus::new_native());
+                // negligible noise, the decryptions are exact
+                encrypt_lwe_ciphertext(
+                    &self.sk,
+                    &mut ct,
+                    Plaintext(message << (64 - PRECISION)),
+                    Variance(2_f64.powi(-120)),
+                    &mut self.encryption_generator,
+                );
+            }
+            cts
+        }
+
+        fn decrypt(&self, cts: &[u64]) -> Vec<u64> {
+            cts.chunks_exact(LWE_SIZE)
+                .map(|ct| {
+                    let ct = LweCiphertext::from_container(ct, CiphertextModulus::new_native());
+                    let plaintext = decrypt_lwe_ciphertext(&self.sk, &ct).0;
+                    plaintext.wrapping_add(1 << (63 - PRECISION)) >> (64 - PRECISION)
+                })
+                .collect()
+        }
+    }
+
+    fn clear_dot(messages: &[u64], weights: &[u64]) -> u64 {
+        let sum = messages
+            .iter()
+            .zip(weights)
+            .fold(0_u64, |sum, (&m, &w)| sum.wrapping_add(m.wrapping_mul(w)));
+        sum % (1 << PRECISION)
+    }
+
+    #[test]
+    fn dot_decrypts_to_the_cleartext_dot() {
+        let mut context = Context::new();
+        let messages = [3, 1, 4, 1, 5];
+        let weights = [2, (-3_i64) as u64, 1, 7, 0];
+        let ct_in = context.encrypt(&messages);
+        let mut ct_out = vec![0; LWE_SIZE];
+        unsafe {
+            concrete_cpu_dot_lwe_ciphertext_u64(
+                ct_out.as_mut_ptr(),
+                ct_in.as_ptr(),
+                weights.as_ptr(),
+                messages.len(),
+                LWE_DIMENSION,
+            );
+        }
+        assert_eq!(context.decrypt(&ct_out), [clear_dot(&messages, &weights)]);
+    }
+
+    #[test]
+    fn matmul_decrypts_to_the_cleartext_product() {
+        let mut context = Context::new();
+        let messages = [3, 1, 4];
+        let weights = [1, 2, 3, (-1_i64) as u64, 0, 5];
+        let ct_in = context.encrypt(&messages);
+        let expected: Vec<_> = weights
+            .chunks_exact(messages.len())
+            .map(|row| clear_dot(&messages, row))
+            .collect();
+        for parallelism in [Parallelism::No, Parallelism::Rayon] {
+            let mut ct_out = vec![0; 2 * LWE_SIZE];
+            unsafe {
+                concrete_cpu_matmul_cleartext_lwe_ciphertext_u64(
+                    ct_out.as_mut_ptr(),
+                    weights.as_ptr(),
+                    ct_in.as_ptr(),
+                    2,
+                    messages.len(),
+                    LWE_DIMENSION,
+                    parallelism,
+                );
+            }
+            assert_eq!(context.decrypt(&ct_out), expected);
+        }
+    }
+
+    #[test]
+    fn broadcast_dot_decrypts_to_the_cleartext_elementwise_sum() {
+        let mut context = Context::new();
+        // two tensors of three elements
+        let messages = [3, 1, 4, 1, 5, 9];
+        let weights = [2, (-1_i64) as u64];
+        let ct_in = context.encrypt(&messages);
+        let expected: Vec<_> = (0..3)
+            .map(|j| clear_dot(&[messages[j], messages[3 + j]], &weights))
+            .collect();
+        for parallelism in [Parallelism::No, Parallelism::Rayon] {
+            let mut ct_out = vec![0; 3 * LWE_SIZE];
+            unsafe {
+                concrete_cpu_broadcast_dot_lwe_ciphertext_u64(
+                    ct_out.as_mut_ptr(),
+                    ct_in.as_ptr(),
+                    weights.as_ptr(),
+                    weights.len(),
+                    3,
+                    LWE_DIMENSION,
+                    parallelism,
+                );
+            }
+            assert_eq!(context.decrypt(&ct_out), expected);
+        }
+
+        // a single weight multiplies the whole tensor
+        let mut ct_out = vec![0; messages.len() * LWE_SIZE];
+        unsafe {
+            concrete_cpu_broadcast_dot_lwe_ciphertext_u64(
+                ct_out.as_mut_ptr(),
+                ct_in.as_ptr(),
+                [3].as_ptr(),
+                1,
+                messages.len(),
+                LWE_DIMENSION,
+                Parallelism::No,
+            );
+        }
+        let expected: Vec<_> = messages.iter().map(|&m| clear_dot(&[m], &[3])).collect();
+        assert_eq!(context.decrypt(&ct_out), expected);
+    }
+
+    #[test]
+    fn batched_dot_decrypts_to_the_cleartext_dot_per_slice() {
+        let mut context = Context::new();
+        // a tensor of shape [2, 3] with weights of shape [3]
+        let messages = [3, 1, 4, 1, 5, 9];
+        let weights = [2, (-1_i64) as u64, 7];
+        let ct_in = context.encrypt(&messages);
+        let expected: Vec<_> = messages
+            .chunks_exact(weights.len())
+            .map(|slice| clear_dot(slice, &weights))
+            .collect();
+        for parallelism in [Parallelism::No, Parallelism::Rayon] {
+            let mut ct_out = vec![0; 2 * LWE_SIZE];
+            unsafe {
+                concrete_cpu_batched_dot_lwe_ciphertext_u64(
+                    ct_out.as_mut_ptr(),
+                    ct_in.as_ptr(),
+                    weights.as_ptr(),
+                    2,
+                    weights.len(),
+                    LWE_DIMENSION,
+                    parallelism,
+                );
+            }
+            assert_eq!(context.decrypt(&ct_out), expected);
+        }
+    }
+}
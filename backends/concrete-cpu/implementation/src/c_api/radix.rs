@@ -0,0 +1,378 @@
+use concrete_fft::c64;
+use core::slice;
+use dyn_stack::PodStack;
+use tfhe::core_crypto::prelude::*;
+
+use super::bootstrap::concrete_cpu_fourier_bootstrap_key_size_u64;
+use super::keyswitch::concrete_cpu_keyswitch_key_size_u64;
+use super::types::{RadixStatus, ScratchStatus};
+use super::utils::nounwind;
+use crate::implementation::radix::{RadixContext, RadixError};
+
+fn status(result: Result<(), RadixError>) -> RadixStatus {
+    match result {
+        Ok(()) => RadixStatus::Computed,
+        Err(RadixError::EmptyInteger) => RadixStatus::EmptyInteger,
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+unsafe fn radix_context<'a>(
+    fourier_bsk: *const c64,
+    bsk_decomposition_level_count: usize,
+    bsk_decomposition_base_log: usize,
+    glwe_dimension: usize,
+    polynomial_size: usize,
+    keyswitch_key: *const u64,
+    ksk_decomposition_level_count: usize,
+    ksk_decomposition_base_log: usize,
+    small_lwe_dimension: usize,
+    message_modulus: u64,
+    carry_modulus: u64,
+    fft: *const Fft,
+) -> RadixContext<'a> {
+    let big_lwe_dimension = glwe_dimension * polynomial_size;
+
+    let fourier_bsk = FourierLweBootstrapKey::from_container(
+        slice::from_raw_parts(
+            fourier_bsk,
+            concrete_cpu_fourier_bootstrap_key_size_u64(
+                bsk_decomposition_level_count,
+                glwe_dimension,
+                polynomial_size,
+                small_lwe_dimension,
+            ),
+        ),
+        LweDimension(small_lwe_dimension),
+        GlweDimension(glwe_dimension).to_glwe_size(),
+        PolynomialSize(polynomial_size),
+        DecompositionBaseLog(bsk_decomposition_base_log),
+        DecompositionLevelCount(bsk_decomposition_level_count),
+    );
+
+    let ksk = LweKeyswitchKey::from_container(
+        slice::from_raw_parts(
+            keyswitch_key,
+            concrete_cpu_keyswitch_key_size_u64(
+                ksk_decomposition_level_count,
+                big_lwe_dimension,
+                small_lwe_dimension,
+            ),
+        ),
+        DecompositionBaseLog(ksk_decomposition_base_log),
+        DecompositionLevelCount(ksk_decomposition_level_count),
+        LweDimension(small_lwe_dimension).to_lwe_size(),
+        CiphertextModulus::new_native(),
+    );
+
+    RadixContext::new(
+        fourier_bsk,
+        ksk,
+        (*fft).as_view(),
+        message_modulus,
+        carry_modulus,
+    )
+}
+
+/// Scratch requirement of every `concrete_cpu_radix_*` operation.
+#[no_mangle]
+#[must_use]
+pub unsafe extern "C" fn concrete_cpu_radix_lwe_ciphertext_u64_scratch(
+    stack_size: *mut usize,
+    stack_align: *mut usize,
+    // bootstrap parameters
+    glwe_dimension: usize,
+    polynomial_size: usize,
+    // side resources
+    fft: *const Fft,
+) -> ScratchStatus {
+    nounwind(|| {
+        if let Ok(scratch) = programmable_bootstrap_lwe_ciphertext_mem_optimized_requirement::<u64>(
+            GlweDimension(glwe_dimension).to_glwe_size(),
+            PolynomialSize(polynomial_size),
+            (*fft).as_view(),
+        ) {
+            *stack_size = scratch.size_bytes();
+            *stack_align = scratch.align_bytes();
+            ScratchStatus::Valid
+        } else {
+            ScratchStatus::SizeOverflow
+        }
+    })
+}
+
+/// Encrypts in `ct_out` the most significant message bit of the radix integer `ct_in`.
+///
+/// Returns `EmptyInteger`, leaving `ct_out` untouched, if `block_count` is 0.
+#[no_mangle]
+#[must_use]
+pub unsafe extern "C" fn concrete_cpu_radix_sign_lwe_ciphertext_u64(
+    // ciphertexts
+    ct_out: *mut u64,
+    ct_in: *const u64,
+    block_count: usize,
+    // bootstrap key
+    fourier_bsk: *const c64,
+    // bootstrap parameters
+    bsk_decomposition_level_count: usize,
+    bsk_decomposition_base_log: usize,
+    glwe_dimension: usize,
+    polynomial_size: usize,
+    // keyswitch key
+    keyswitch_key: *const u64,
+    // keyswitch parameters
+    ksk_decomposition_level_count: usize,
+    ksk_decomposition_base_log: usize,
+    small_lwe_dimension: usize,
+    // encoding
+    message_modulus: u64,
+    carry_modulus: u64,
+    // side resources
+    fft: *const Fft,
+    stack: *mut u8,
+    stack_size: usize,
+) -> RadixStatus {
+    nounwind(|| {
+        let ctx = radix_context(
+            fourier_bsk,
+            bsk_decomposition_level_count,
+            bsk_decomposition_base_log,
+            glwe_dimension,
+            polynomial_size,
+            keyswitch_key,
+            ksk_decomposition_level_count,
+            ksk_decomposition_base_log,
+            small_lwe_dimension,
+            message_modulus,
+            carry_modulus,
+            fft,
+        );
+        let block_size = ctx.block_size();
+
+        let mut ct_out = LweCiphertext::from_container(
+            slice::from_raw_parts_mut(ct_out, block_size.0),
+            CiphertextModulus::new_native(),
+        );
+        let ct_in = LweCiphertextList::from_container(
+            slice::from_raw_parts(ct_in, block_count * block_size.0),
+            block_size,
+            CiphertextModulus::new_native(),
+        );
+
+        status(ctx.sign(
+            &mut ct_out,
+            &ct_in,
+            PodStack::new(slice::from_raw_parts_mut(stack as _, stack_size)),
+        ))
+    })
+}
+
+/// Compares the radix integers `ct_lhs` and `ct_rhs`, `ct_out` encrypts 0 if `lhs < rhs`, 1 if
+/// `lhs == rhs` and 2 if `lhs > rhs`.
+///
+/// Blocks must have empty carries, `carry_modulus` must be at least `message_modulus` and
+/// `message_modulus` at least 4. Returns `EmptyInteger`, leaving `ct_out` untouched, if
+/// `block_count` is 0.
+#[no_mangle]
+#[must_use]
+pub unsafe extern "C" fn concrete_cpu_radix_compare_lwe_ciphertext_u64(
+    // ciphertexts
+    ct_out: *mut u64,
+    ct_lhs: *const u64,
+    ct_rhs: *const u64,
+    block_count: usize,
+    // bootstrap key
+    fourier_bsk: *const c64,
+    // bootstrap parameters
+    bsk_decomposition_level_count: usize,
+    bsk_decomposition_base_log: usize,
+    glwe_dimension: usize,
+    polynomial_size: usize,
+    // keyswitch key
+    keyswitch_key: *const u64,
+    // keyswitch parameters
+    ksk_decomposition_level_count: usize,
+    ksk_decomposition_base_log: usize,
+    small_lwe_dimension: usize,
+    // encoding
+    message_modulus: u64,
+    carry_modulus: u64,
+    // side resources
+    fft: *const Fft,
+    stack: *mut u8,
+    stack_size: usize,
+) -> RadixStatus {
+    nounwind(|| {
+        let ctx = radix_context(
+            fourier_bsk,
+            bsk_decomposition_level_count,
+            bsk_decomposition_base_log,
+            glwe_dimension,
+            polynomial_size,
+            keyswitch_key,
+            ksk_decomposition_level_count,
+            ksk_decomposition_base_log,
+            small_lwe_dimension,
+            message_modulus,
+            carry_modulus,
+            fft,
+        );
+        let block_size = ctx.block_size();
+
+        let mut ct_out = LweCiphertext::from_container(
+            slice::from_raw_parts_mut(ct_out, block_size.0),
+            CiphertextModulus::new_native(),
+        );
+        let ct_lhs = LweCiphertextList::from_container(
+            slice::from_raw_parts(ct_lhs, block_count * block_size.0),
+            block_size,
+            CiphertextModulus::new_native(),
+        );
+        let ct_rhs = LweCiphertextList::from_container(
+            slice::from_raw_parts(ct_rhs, block_count * block_size.0),
+            block_size,
+            CiphertextModulus::new_native(),
+        );
+
+        status(ctx.compare(
+            &mut ct_out,
+            &ct_lhs,
+            &ct_rhs,
+            PodStack::new(slice::from_raw_parts_mut(stack as _, stack_size)),
+        ))
+    })
+}
+
+/// Propagates the carries of the radix integer `ct_in_out` in place.
+///
+/// Returns `EmptyInteger` if `block_count` is 0.
+#[no_mangle]
+#[must_use]
+pub unsafe extern "C" fn concrete_cpu_radix_propagate_carry_lwe_ciphertext_u64(
+    // ciphertexts
+    ct_in_out: *mut u64,
+    block_count: usize,
+    // bootstrap key
+    fourier_bsk: *const c64,
+    // bootstrap parameters
+    bsk_decomposition_level_count: usize,
+    bsk_decomposition_base_log: usize,
+    glwe_dimension: usize,
+    polynomial_size: usize,
+    // keyswitch key
+    keyswitch_key: *const u64,
+    // keyswitch parameters
+    ksk_decomposition_level_count: usize,
+    ksk_decomposition_base_log: usize,
+    small_lwe_dimension: usize,
+    // encoding
+    message_modulus: u64,
+    carry_modulus: u64,
+    // side resources
+    fft: *const Fft,
+    stack: *mut u8,
+    stack_size: usize,
+) -> RadixStatus {
+    nounwind(|| {
+        let ctx = radix_context(
+            fourier_bsk,
+            bsk_decomposition_level_count,
+            bsk_decomposition_base_log,
+            glwe_dimension,
+            polynomial_size,
+            keyswitch_key,
+            ksk_decomposition_level_count,
+            ksk_decomposition_base_log,
+            small_lwe_dimension,
+            message_modulus,
+            carry_modulus,
+            fft,
+        );
+        let block_size = ctx.block_size();
+
+        let mut ct_in_out = LweCiphertextList::from_container(
+            slice::from_raw_parts_mut(ct_in_out, block_count * block_size.0),
+            block_size,
+            CiphertextModulus::new_native(),
+        );
+
+        status(ctx.propagate_carry(
+            &mut ct_in_out,
+            PodStack::new(slice::from_raw_parts_mut(stack as _, stack_size)),
+        ))
+    })
+}
+
+/// Evaluates a lookup table of two blocks with empty carries.
+///
+/// `lut` has `message_modulus * message_modulus` entries, the output message for `(lhs, rhs)`
+/// being `lut[lhs * message_modulus + rhs]`.
+#[no_mangle]
+pub unsafe extern "C" fn concrete_cpu_radix_bivariate_lut_lwe_ciphertext_u64(
+    // ciphertexts
+    ct_out: *mut u64,
+    ct_lhs: *const u64,
+    ct_rhs: *const u64,
+    // lookup table
+    lut: *const u64,
+    // bootstrap key
+    fourier_bsk: *const c64,
+    // bootstrap parameters
+    bsk_decomposition_level_count: usize,
+    bsk_decomposition_base_log: usize,
+    glwe_dimension: usize,
+    polynomial_size: usize,
+    // keyswitch key
+    keyswitch_key: *const u64,
+    // keyswitch parameters
+    ksk_decomposition_level_count: usize,
+    ksk_decomposition_base_log: usize,
+    small_lwe_dimension: usize,
+    // encoding
+    message_modulus: u64,
+    carry_modulus: u64,
+    // side resources
+    fft: *const Fft,
+    stack: *mut u8,
+    stack_size: usize,
+) {
+    nounwind(|| {
+        let ctx = radix_context(
+            fourier_bsk,
+            bsk_decomposition_level_count,
+            bsk_decomposition_base_log,
+            glwe_dimension,
+            polynomial_size,
+            keyswitch_key,
+            ksk_decomposition_level_count,
+            ksk_decomposition_base_log,
+            small_lwe_dimension,
+            message_modulus,
+            carry_modulus,
+            fft,
+        );
+        let block_size = ctx.block_size();
+
+        let mut ct_out = LweCiphertext::from_container(
+            slice::from_raw_parts_mut(ct_out, block_size.0),
+            CiphertextModulus::new_native(),
+        );
+        let ct_lhs = LweCiphertext::from_container(
+            slice::from_raw_parts(ct_lhs, block_size.0),
+            CiphertextModulus::new_native(),
+        );
+        let ct_rhs = LweCiphertext::from_container(
+            slice::from_raw_parts(ct_rhs, block_size.0),
+            CiphertextModulus::new_native(),
+        );
+        let lut = slice::from_raw_parts(lut, (message_modulus * message_modulus) as usize);
+
+        ctx.apply_bivariate(
+            &mut ct_out,
+            &ct_lhs,
+            &ct_rhs,
+            |lhs, rhs| lut[(lhs * message_modulus + rhs) as usize],
+            PodStack::new(slice::from_raw_parts_mut(stack as _, stack_size)),
+        );
+    })
+}
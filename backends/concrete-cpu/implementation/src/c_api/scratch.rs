@@ -0,0 +1,104 @@
+use tfhe::core_crypto::prelude::*;
+
+use crate::c_api::types::ScratchStatus;
+use crate::c_api::utils::nounwind;
+use crate::implementation::scratch::ScratchParameters;
+use dyn_stack::StackReq;
+
+type ScratchArenaImpl = crate::implementation::scratch::ScratchArena;
+
+pub struct ScratchArena {
+    _private: (),
+}
+
+#[no_mangle]
+pub static CONCRETE_SCRATCH_ARENA_SIZE: usize = core::mem::size_of::<ScratchArenaImpl>();
+
+#[no_mangle]
+pub static CONCRETE_SCRATCH_ARENA_ALIGN: usize = core::mem::align_of::<ScratchArenaImpl>();
+
+/// Computes the stack requirement covering the bootstrap, the bootstrap key Fourier conversion,
+/// the bit extraction and the circuit bootstrap + vertical packing for a parameter set.
+///
+/// The vertical packing part is skipped when `wop_ct_in_count` is 0. Its parallel version, with
+/// `worker_count` workers as given to the operation, is covered unless `worker_count` is 0.
+#[no_mangle]
+#[must_use]
+pub unsafe extern "C" fn concrete_cpu_scratch_arena_requirement(
+    stack_size: *mut usize,
+    stack_align: *mut usize,
+    // bootstrap parameters
+    bsk_glwe_dimension: usize,
+    bsk_polynomial_size: usize,
+    // keyswitch parameters
+    ksk_output_dimension: usize,
+    // wop parameters
+    wop_ct_in_count: usize,
+    wop_lut_count: usize,
+    fpksk_output_polynomial_size: usize,
+    cbs_decomposition_level_count: usize,
+    worker_count: usize,
+    // side resources
+    fft: *const Fft,
+) -> ScratchStatus {
+    nounwind(|| {
+        let params = ScratchParameters {
+            bsk_glwe_dimension,
+            bsk_polynomial_size,
+            ksk_output_dimension,
+            wop_ct_in_count,
+            wop_lut_count,
+            fpksk_output_polynomial_size,
+            cbs_decomposition_level_count,
+            worker_count,
+        };
+
+        if let Ok(scratch) = params.requirement((*fft).as_view()) {
+            *stack_size = scratch.size_bytes();
+            *stack_align = scratch.align_bytes();
+            ScratchStatus::Valid
+        } else {
+            ScratchStatus::SizeOverflow
+        }
+    })
+}
+
+/// # Safety
+///
+/// `mem` must be valid for writes of `CONCRETE_SCRATCH_ARENA_SIZE` bytes and aligned to
+/// `CONCRETE_SCRATCH_ARENA_ALIGN`. `stack_align` must be a power of two.
+#[no_mangle]
+pub unsafe extern "C" fn concrete_cpu_construct_scratch_arena(
+    mem: *mut ScratchArena,
+    stack_size: usize,
+    stack_align: usize,
+) {
+    nounwind(|| {
+        let mem = mem as *mut ScratchArenaImpl;
+        mem.write(ScratchArenaImpl::new(StackReq::new_aligned::<u8>(
+            stack_size,
+            stack_align,
+        )));
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn concrete_cpu_destroy_scratch_arena(mem: *mut ScratchArena) {
+    nounwind(|| core::ptr::drop_in_place(mem as *mut ScratchArenaImpl))
+}
+
+/// Returns the arena memory, to be passed as the `stack` argument of an operation along with the
+/// size written to `stack_size`.
+///
+/// The memory is reused by every call: an arena must only be used by one operation at a time.
+#[no_mangle]
+pub unsafe extern "C" fn concrete_cpu_scratch_arena_stack(
+    arena: *mut ScratchArena,
+    stack_size: *mut usize,
+) -> *mut u8 {
+    nounwind(|| {
+        let arena = &mut *(arena as *mut ScratchArenaImpl);
+        *stack_size = arena.size_bytes();
+        arena.as_mut_ptr()
+    })
+}
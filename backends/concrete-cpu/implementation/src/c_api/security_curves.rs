@@ -0,0 +1,50 @@
+use core::ffi::{c_char, CStr};
+
+use crate::c_api::types::SecurityCurvesStatus;
+use crate::c_api::utils::nounwind;
+use concrete_security_curves::gaussian::curves::CurvesError;
+
+type SecurityCurvesImpl = concrete_security_curves::gaussian::curves::SecurityCurves;
+
+pub struct SecurityCurves {
+    _private: (),
+}
+
+/// Loads and validates security curves from a file in the `curves.json` format.
+///
+/// On success `curves` is set to a new handle, to be released with
+/// `concrete_cpu_destroy_security_curves`. On failure it is left untouched.
+#[no_mangle]
+#[must_use]
+pub unsafe extern "C" fn concrete_cpu_load_security_curves(
+    path: *const c_char,
+    curves: *mut *mut SecurityCurves,
+) -> SecurityCurvesStatus {
+    nounwind(|| {
+        let Ok(path) = CStr::from_ptr(path).to_str() else {
+            return SecurityCurvesStatus::Unreadable;
+        };
+        match SecurityCurvesImpl::from_json_file(path) {
+            Ok(loaded) => {
+                *curves = Box::into_raw(Box::new(loaded)) as *mut SecurityCurves;
+                SecurityCurvesStatus::Loaded
+            }
+            Err(CurvesError::Io(_)) => SecurityCurvesStatus::Unreadable,
+            Err(_) => SecurityCurvesStatus::Invalid,
+        }
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn concrete_cpu_destroy_security_curves(curves: *mut SecurityCurves) {
+    nounwind(|| drop(Box::from_raw(curves as *mut SecurityCurvesImpl)))
+}
+
+/// The curves behind `curves`, or the compiled ones when it is null.
+pub(crate) unsafe fn curves_or_compiled<'a>(curves: *const SecurityCurves) -> &'a SecurityCurvesImpl {
+    if curves.is_null() {
+        SecurityCurvesImpl::compiled()
+    } else {
+        &*(curves as *const SecurityCurvesImpl)
+    }
+}
@@ -1,3 +1,5 @@
+use concrete_cpu_noise_model::gaussian_noise::noise::modulus_switching::ModulusSwitchingKind;
+
 #[repr(C)]
 #[derive(Copy, Clone)]
 pub struct Uint128 {
@@ -30,9 +32,70 @@ pub enum ScratchStatus {
     SizeOverflow = 1,
 }
 
+#[repr(u32)]
+#[derive(Copy, Clone, Debug)]
+pub enum RadixStatus {
+    Computed = 0,
+    EmptyInteger = 1,
+}
+
 #[repr(u32)]
 #[derive(Copy, Clone, Debug)]
 pub enum Parallelism {
     No = 0,
     Rayon = 1,
 }
+
+/// Correction of the body for the rounding of the mask before a blind rotate.
+#[repr(u32)]
+#[derive(Copy, Clone, Debug)]
+pub enum ModulusSwitching {
+    StandardModulusSwitching = 0,
+    CenteredMeanModulusSwitching = 1,
+    ShiftedKeyModulusSwitching = 2,
+}
+
+impl From<ModulusSwitching> for ModulusSwitchingKind {
+    fn from(modulus_switching: ModulusSwitching) -> Self {
+        match modulus_switching {
+            ModulusSwitching::StandardModulusSwitching => Self::Standard,
+            ModulusSwitching::CenteredMeanModulusSwitching => Self::CenteredMean,
+            ModulusSwitching::ShiftedKeyModulusSwitching => Self::ShiftedKey,
+        }
+    }
+}
+
+/// Parameters, keys and contents a bootstrap or keyswitch key was generated with.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct KeyFingerprint {
+    pub parameter_hash: u64,
+    pub input_key_id: u64,
+    pub output_key_id: u64,
+    pub content_hash: u64,
+}
+
+/// Dimension and secret key of an LWE ciphertext.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CiphertextFingerprint {
+    pub parameter_hash: u64,
+    pub key_id: u64,
+}
+
+#[repr(u32)]
+#[derive(Copy, Clone, Debug)]
+pub enum IntegrityStatus {
+    Consistent = 0,
+    ParameterMismatch = 1,
+    KeyMismatch = 2,
+    KeyCorrupted = 3,
+}
+
+#[repr(u32)]
+#[derive(Copy, Clone, Debug)]
+pub enum SecurityCurvesStatus {
+    Loaded = 0,
+    Unreadable = 1,
+    Invalid = 2,
+}
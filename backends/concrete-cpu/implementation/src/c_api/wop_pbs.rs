@@ -6,6 +6,7 @@ use crate::c_api::bootstrap::concrete_cpu_fourier_bootstrap_key_size_u64;
 use crate::c_api::keyswitch::concrete_cpu_keyswitch_key_size_u64;
 use crate::c_api::types::*;
 use crate::c_api::utils::nounwind;
+use crate::implementation::wop_pbs;
 use core::slice;
 use dyn_stack::PodStack;
 
@@ -211,6 +212,22 @@ pub unsafe extern "C" fn concrete_cpu_extract_bit_lwe_ciphertext_u64(
     })
 }
 
+/// Default number of workers of a [`Parallelism::Rayon`] circuit bootstrap + vertical packing.
+///
+/// The scratch holds one slot per worker, so the same count must be given to the scratch and to
+/// the operation.
+#[no_mangle]
+pub extern "C" fn concrete_cpu_circuit_bootstrap_boolean_vertical_packing_worker_count() -> usize {
+    #[cfg(feature = "parallel")]
+    {
+        rayon::current_num_threads()
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        1
+    }
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn concrete_cpu_circuit_bootstrap_boolean_vertical_packing_lwe_ciphertext_u64_scratch(
     stack_size: *mut usize,
@@ -228,6 +245,9 @@ pub unsafe extern "C" fn concrete_cpu_circuit_bootstrap_boolean_vertical_packing
     fpksk_output_polynomial_size: usize,
     // circuit bootstrap parameters
     cbs_decomposition_level_count: usize,
+    // parallelism, the worker count is ignored by Parallelism::No
+    parallelism: Parallelism,
+    worker_count: usize,
     // side resources
     fft: *const Fft,
 ) -> ScratchStatus {
@@ -239,10 +259,23 @@ pub unsafe extern "C" fn concrete_cpu_circuit_bootstrap_boolean_vertical_packing
 
         assert_ne!(cbs_decomposition_level_count, 0);
 
-        if let Ok(scratch) =
-            circuit_bootstrap_boolean_vertical_packing_lwe_ciphertext_list_mem_optimized_requirement::<
-                u64,
-            >(
+        let scratch = match parallelism {
+            Parallelism::No => {
+                circuit_bootstrap_boolean_vertical_packing_lwe_ciphertext_list_mem_optimized_requirement::<
+                    u64,
+                >(
+                    LweCiphertextCount(ct_in_count),
+                    LweCiphertextCount(ct_out_count),
+                    LweDimension(ct_in_dimension).to_lwe_size(),
+                    PolynomialCount(lut_count),
+                    LweDimension(bsk_output_lwe_dimension).to_lwe_size(),
+                    GlweDimension(bsk_glwe_dimension).to_glwe_size(),
+                    PolynomialSize(fpksk_output_polynomial_size.max(lut_size)),
+                    DecompositionLevelCount(cbs_decomposition_level_count),
+                    (*fft).as_view(),
+                )
+            }
+            Parallelism::Rayon => wop_pbs::circuit_bootstrap_boolean_vertical_packing_scratch(
                 LweCiphertextCount(ct_in_count),
                 LweCiphertextCount(ct_out_count),
                 LweDimension(ct_in_dimension).to_lwe_size(),
@@ -251,9 +284,12 @@ pub unsafe extern "C" fn concrete_cpu_circuit_bootstrap_boolean_vertical_packing
                 GlweDimension(bsk_glwe_dimension).to_glwe_size(),
                 PolynomialSize(fpksk_output_polynomial_size.max(lut_size)),
                 DecompositionLevelCount(cbs_decomposition_level_count),
+                worker_count,
                 (*fft).as_view(),
-            )
-        {
+            ),
+        };
+
+        if let Ok(scratch) = scratch {
             *stack_size = scratch.size_bytes();
             *stack_align = scratch.align_bytes();
             ScratchStatus::Valid
@@ -297,6 +333,9 @@ pub unsafe extern "C" fn concrete_cpu_circuit_bootstrap_boolean_vertical_packing
     // circuit bootstrap parameters
     cbs_decomposition_level_count: usize,
     cbs_decomposition_base_log: usize,
+    // parallelism, the worker count must be the one given to the scratch
+    parallelism: Parallelism,
+    worker_count: usize,
     // side resources
     fft: *const Fft,
     stack: *mut u8,
@@ -382,17 +421,34 @@ pub unsafe extern "C" fn concrete_cpu_circuit_bootstrap_boolean_vertical_packing
             CiphertextModulus::new_native(),
         );
 
-        circuit_bootstrap_boolean_vertical_packing_lwe_ciphertext_list_mem_optimized(
-            &lwe_list_in,
-            &mut lwe_list_out,
-            &luts,
-            &fourier_bsk,
-            &fpksk_list,
-            DecompositionBaseLog(cbs_decomposition_base_log),
-            DecompositionLevelCount(cbs_decomposition_level_count),
-            (*fft).as_view(),
-            PodStack::new(slice::from_raw_parts_mut(stack as _, stack_size)),
-        );
+        let stack = PodStack::new(slice::from_raw_parts_mut(stack as _, stack_size));
+        match parallelism {
+            Parallelism::No => {
+                circuit_bootstrap_boolean_vertical_packing_lwe_ciphertext_list_mem_optimized(
+                    &lwe_list_in,
+                    &mut lwe_list_out,
+                    &luts,
+                    &fourier_bsk,
+                    &fpksk_list,
+                    DecompositionBaseLog(cbs_decomposition_base_log),
+                    DecompositionLevelCount(cbs_decomposition_level_count),
+                    (*fft).as_view(),
+                    stack,
+                )
+            }
+            Parallelism::Rayon => wop_pbs::circuit_bootstrap_boolean_vertical_packing(
+                luts,
+                fourier_bsk.as_view(),
+                lwe_list_out,
+                lwe_list_in,
+                fpksk_list,
+                DecompositionLevelCount(cbs_decomposition_level_count),
+                DecompositionBaseLog(cbs_decomposition_base_log),
+                worker_count,
+                (*fft).as_view(),
+                stack,
+            ),
+        }
     })
 }
 
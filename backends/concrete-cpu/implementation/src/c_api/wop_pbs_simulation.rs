@@ -1,3 +1,4 @@
+use crate::c_api::security_curves::{curves_or_compiled, SecurityCurves};
 use crate::c_api::utils::nounwind;
 use crate::implementation::wop_simulation::{
     circuit_bootstrap_boolean_vertical_packing, extract_bits,
@@ -19,6 +20,8 @@ pub unsafe extern "C" fn simulation_extract_bit_lwe_ciphertext_u64(
     br_level: u64,
     ciphertext_modulus_log: u32,
     security_level: u64,
+    // null for the compiled curves
+    security_curves: *const SecurityCurves,
 ) {
     nounwind(|| {
         assert!(64 <= number_of_bits_to_extract + delta_log);
@@ -36,6 +39,7 @@ pub unsafe extern "C" fn simulation_extract_bit_lwe_ciphertext_u64(
             br_log_base,
             br_level,
             ciphertext_modulus_log,
+            curves_or_compiled(security_curves),
             security_level,
         );
     })
@@ -61,6 +65,8 @@ pub unsafe extern "C" fn simulation_circuit_bootstrap_boolean_vertical_packing_l
     pp_log_base: u64,
     ciphertext_modulus_log: u32,
     security_level: u64,
+    // null for the compiled curves
+    security_curves: *const SecurityCurves,
 ) {
     nounwind(|| {
         assert_ne!(cb_log_base, 0);
@@ -87,6 +93,7 @@ pub unsafe extern "C" fn simulation_circuit_bootstrap_boolean_vertical_packing_l
             pp_level,
             pp_log_base,
             ciphertext_modulus_log,
+            curves_or_compiled(security_curves),
             security_level,
         );
     })
@@ -0,0 +1,110 @@
+//! Fingerprints tying keys and ciphertexts to the parameters and keys they were created with.
+//!
+//! A fingerprint does not authenticate anything: it is a cheap way to catch a buffer passed with
+//! the wrong parameters or under the wrong key before computing garbage from it.
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Kind of object a parameter hash is computed for, so that two kinds with the same parameter
+/// values never share a hash.
+#[derive(Copy, Clone, Debug)]
+#[repr(u64)]
+pub enum ParameterDomain {
+    LweCiphertext = 1,
+    BootstrapKey = 2,
+    KeyswitchKey = 3,
+}
+
+/// Stable 64 bits FNV-1a hash of `parameters` within `domain`.
+pub fn parameter_hash(domain: ParameterDomain, parameters: &[usize]) -> u64 {
+    core::iter::once(domain as u64)
+        .chain(parameters.iter().map(|&p| p as u64))
+        .flat_map(u64::to_le_bytes)
+        .fold(FNV_OFFSET_BASIS, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(FNV_PRIME)
+        })
+}
+
+/// Stable 64 bits FNV-1a hash of the words of a key, one word at a time to keep large keys cheap.
+pub fn content_hash(words: impl IntoIterator<Item = u64>) -> u64 {
+    words.into_iter().fold(FNV_OFFSET_BASIS, |hash, word| {
+        (hash ^ word).wrapping_mul(FNV_PRIME)
+    })
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum IntegrityError {
+    /// The object was created with different parameters than the ones it is used with.
+    ParameterMismatch,
+    /// The ciphertext is not encrypted under the input key of the key it is used with.
+    KeyMismatch,
+    /// The key contents changed since it was fingerprinted.
+    KeyCorrupted,
+}
+
+/// Checks a key fingerprint against the parameters of the operation, and an input ciphertext
+/// fingerprint against the key.
+pub fn check(
+    key_parameter_hash: u64,
+    expected_key_parameter_hash: u64,
+    key_input_key_id: u64,
+    ct_in_parameter_hash: u64,
+    expected_ct_in_parameter_hash: u64,
+    ct_in_key_id: u64,
+) -> Result<(), IntegrityError> {
+    if key_parameter_hash != expected_key_parameter_hash
+        || ct_in_parameter_hash != expected_ct_in_parameter_hash
+    {
+        return Err(IntegrityError::ParameterMismatch);
+    }
+    if key_input_key_id != ct_in_key_id {
+        return Err(IntegrityError::KeyMismatch);
+    }
+    Ok(())
+}
+
+/// Checks the contents of a key against its fingerprint.
+pub fn check_content(
+    key_content_hash: u64,
+    actual_content_hash: u64,
+) -> Result<(), IntegrityError> {
+    if key_content_hash != actual_content_hash {
+        return Err(IntegrityError::KeyCorrupted);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hash_depends_on_domain_and_order() {
+        let ct = parameter_hash(ParameterDomain::LweCiphertext, &[1, 2]);
+        assert_eq!(ct, parameter_hash(ParameterDomain::LweCiphertext, &[1, 2]));
+        assert_ne!(ct, parameter_hash(ParameterDomain::KeyswitchKey, &[1, 2]));
+        assert_ne!(ct, parameter_hash(ParameterDomain::LweCiphertext, &[2, 1]));
+    }
+
+    #[test]
+    fn content_hash_depends_on_each_word() {
+        let key = content_hash([1, 2, 3]);
+        assert_eq!(check_content(key, content_hash([1, 2, 3])), Ok(()));
+        assert_eq!(
+            check_content(key, content_hash([1, 2, 4])),
+            Err(IntegrityError::KeyCorrupted)
+        );
+        assert_ne!(key, content_hash([3, 2, 1]));
+    }
+
+    #[test]
+    fn parameter_mismatch_is_reported_first() {
+        assert_eq!(check(1, 1, 7, 2, 2, 7), Ok(()));
+        assert_eq!(check(1, 1, 7, 2, 2, 8), Err(IntegrityError::KeyMismatch));
+        assert_eq!(
+            check(1, 3, 7, 2, 2, 8),
+            Err(IntegrityError::ParameterMismatch)
+        );
+    }
+}
@@ -1,3 +1,8 @@
+pub mod integrity;
+pub mod modulus_switching;
+pub mod radix;
+pub mod scratch;
+pub mod wop_pbs;
 pub mod wop_simulation;
 
 #[inline]
@@ -0,0 +1,189 @@
+//! Body corrections applied before the blind rotate, for the modulus switching variants of the
+//! noise model.
+//!
+//! The blind rotate rounds every coefficient of its input to the `2N` modulus. The corrections only
+//! change the body of a copy of the input, the blind rotate itself is unchanged.
+
+use aligned_vec::CACHELINE_ALIGN;
+use concrete_cpu_noise_model::gaussian_noise::noise::modulus_switching::ModulusSwitchingKind;
+use concrete_fft::c64;
+use dyn_stack::{PodStack, SizeOverflow, StackReq};
+use tfhe::core_crypto::fft_impl::fft64::math::fft::FftView;
+use tfhe::core_crypto::prelude::*;
+
+use super::from_torus;
+
+/// Bits below the `2N` modulus.
+fn switched_shift(polynomial_size: PolynomialSize) -> usize {
+    u64::BITS as usize - polynomial_size.log2().0 - 1
+}
+
+/// `coefficient` on the `2N` modulus, rounded half up as the blind rotate does.
+///
+/// The result is not reduced: `2N` stands for the coefficients closer to 1 than to the last step,
+/// so that the sums of the shifted key stay consistent with the sum of the unswitched mask.
+fn switched(coefficient: u64, polynomial_size: PolynomialSize) -> u64 {
+    let shift = switched_shift(polynomial_size);
+    ((coefficient >> (shift - 1)) + 1) >> 1
+}
+
+/// Difference between `coefficient` and its rounding to the `2N` modulus.
+fn rounding_error(coefficient: u64, polynomial_size: PolynomialSize) -> i64 {
+    let shift = switched_shift(polynomial_size);
+    coefficient.wrapping_sub(switched(coefficient, polynomial_size) << shift) as i64
+}
+
+/// Corrects the body of `lwe` for the rounding of its mask by the blind rotate.
+///
+/// `key_mean` is the mean of the coefficients of the key `lwe` is encrypted under, 1/2 for a
+/// uniform binary key or the density of a fixed weight key.
+pub fn correct_modulus_switching(
+    lwe: &mut [u64],
+    polynomial_size: PolynomialSize,
+    key_mean: f64,
+    kind: ModulusSwitchingKind,
+) {
+    let (body, mask) = lwe.split_last_mut().unwrap();
+    match kind {
+        ModulusSwitchingKind::Standard => {}
+        ModulusSwitchingKind::CenteredMean => {
+            let rounding_errors: i128 = mask
+                .iter()
+                .map(|&a| rounding_error(a, polynomial_size) as i128)
+                .sum();
+            let correction = (key_mean * rounding_errors as f64).round() as i64;
+            *body = body.wrapping_sub(correction as u64);
+        }
+        ModulusSwitchingKind::ShiftedKey => {
+            let mask_sum: u128 = mask.iter().map(|&a| a as u128).sum();
+            let switched_sum: u64 = mask.iter().map(|&a| switched(a, polynomial_size)).sum();
+            let before = from_torus(key_mean * mask_sum as f64 * 2.0_f64.powi(-(u64::BITS as i32)));
+            let after = ((key_mean * switched_sum as f64).round_ties_even() as u64)
+                << switched_shift(polynomial_size);
+            *body = body.wrapping_sub(before).wrapping_add(after);
+        }
+    }
+}
+
+/// Scratch of a bootstrap correcting a copy of its input.
+pub fn bootstrap_with_modulus_switching_requirement(
+    glwe_size: GlweSize,
+    polynomial_size: PolynomialSize,
+    input_lwe_dimension: LweDimension,
+    fft: FftView<'_>,
+) -> Result<StackReq, SizeOverflow> {
+    StackReq::try_new_aligned::<u64>(input_lwe_dimension.to_lwe_size().0, CACHELINE_ALIGN)?.try_and(
+        programmable_bootstrap_lwe_ciphertext_mem_optimized_requirement::<u64>(
+            glwe_size,
+            polynomial_size,
+            fft,
+        )?,
+    )
+}
+
+/// Programmable bootstrap of `lwe_in` with the modulus switching `kind`.
+#[allow(clippy::too_many_arguments)]
+pub fn bootstrap_with_modulus_switching(
+    lwe_in: &LweCiphertext<&[u64]>,
+    lwe_out: &mut LweCiphertext<&mut [u64]>,
+    accumulator: &GlweCiphertext<&[u64]>,
+    fourier_bsk: &FourierLweBootstrapKey<&[c64]>,
+    key_mean: f64,
+    kind: ModulusSwitchingKind,
+    fft: FftView<'_>,
+    stack: PodStack<'_>,
+) {
+    let (mut corrected, stack) =
+        stack.collect_aligned(CACHELINE_ALIGN, lwe_in.as_ref().iter().copied());
+    correct_modulus_switching(
+        &mut corrected,
+        fourier_bsk.polynomial_size(),
+        key_mean,
+        kind,
+    );
+    let corrected = LweCiphertext::from_container(&*corrected, lwe_in.ciphertext_modulus());
+    programmable_bootstrap_lwe_ciphertext_mem_optimized(
+        &corrected,
+        lwe_out,
+        accumulator,
+        fourier_bsk,
+        fft,
+        stack,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use concrete_cpu_noise_model::gaussian_noise::noise::modulus_switching::estimate_modulus_switching_noise_with_kind;
+    use concrete_security_curves::key_distribution::KeyMoments;
+
+    use super::*;
+
+    struct XorShift(u64);
+
+    impl XorShift {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+    }
+
+    /// Variance on the torus of the phase of noiseless ciphertexts once switched to `2N`.
+    fn switched_phase_variance(kind: ModulusSwitchingKind, polynomial_size: PolynomialSize) -> f64 {
+        let lwe_dimension = 256;
+        let samples = 4000;
+        let mut rng = XorShift(0x2545_f491_4f6c_dd1d);
+        let key: Vec<u64> = (0..lwe_dimension).map(|_| rng.next() >> 63).collect();
+        let two_n = 2 * polynomial_size.0 as i64;
+        let mut sum_squares = 0.0;
+        for _ in 0..samples {
+            let mut lwe: Vec<u64> = (0..lwe_dimension).map(|_| rng.next()).collect();
+            let body = lwe
+                .iter()
+                .zip(&key)
+                .fold(0_u64, |acc, (a, s)| acc.wrapping_add(a.wrapping_mul(*s)));
+            lwe.push(body);
+            correct_modulus_switching(&mut lwe, polynomial_size, 0.5, kind);
+            let (body, mask) = lwe.split_last().unwrap();
+            let phase = mask
+                .iter()
+                .zip(&key)
+                .fold(switched(*body, polynomial_size) as i64, |acc, (a, s)| {
+                    acc - (switched(*a, polynomial_size) * s) as i64
+                });
+            let centered = (phase.rem_euclid(two_n) + two_n / 2).rem_euclid(two_n) - two_n / 2;
+            sum_squares += (centered as f64 / two_n as f64).powi(2);
+        }
+        sum_squares / samples as f64
+    }
+
+    #[test]
+    fn corrections_follow_the_noise_model() {
+        let polynomial_size = PolynomialSize(1024);
+        for kind in ModulusSwitchingKind::ALL {
+            let expected =
+                estimate_modulus_switching_noise_with_kind(256, 10, 64, KeyMoments::BINARY, kind);
+            let actual = switched_phase_variance(kind, polynomial_size);
+            assert!(
+                (actual / expected - 1.0).abs() < 0.1,
+                "{kind:?}: {actual} against {expected}"
+            );
+        }
+    }
+
+    #[test]
+    fn switched_coefficients_round_half_up() {
+        let polynomial_size = PolynomialSize(1024);
+        let step = 1_u64 << 53;
+        assert_eq!(switched(step / 2 - 1, polynomial_size), 0);
+        assert_eq!(switched(step / 2, polynomial_size), 1);
+        assert_eq!(switched(u64::MAX, polynomial_size), 2048);
+        assert_eq!(
+            rounding_error(step / 2, polynomial_size),
+            -(step as i64) / 2
+        );
+        assert_eq!(rounding_error(step + 3, polynomial_size), 3);
+    }
+}
@@ -0,0 +1,557 @@
+//! Radix-decomposed integer operations built on programmable bootstrapping.
+//!
+//! An integer is split in blocks, least significant block first. Each block is an LWE ciphertext
+//! under the big (GLWE) key encoding a value in `[0, message_modulus * carry_modulus[` with one bit
+//! of padding, i.e. scaled by `2^63 / (message_modulus * carry_modulus)`. The message part of a
+//! block is its value modulo `message_modulus`, the rest is carry.
+
+use concrete_fft::c64;
+use dyn_stack::{PodStack, ReborrowMut};
+use tfhe::core_crypto::fft_impl::fft64::math::fft::FftView;
+use tfhe::core_crypto::prelude::*;
+
+/// Encoding of the comparison result: `lhs < rhs`.
+pub const COMPARISON_LESS: u64 = 0;
+/// Encoding of the comparison result: `lhs == rhs`.
+pub const COMPARISON_EQUAL: u64 = 1;
+/// Encoding of the comparison result: `lhs > rhs`.
+pub const COMPARISON_GREATER: u64 = 2;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RadixError {
+    /// The integer has no block.
+    EmptyInteger,
+}
+
+/// Keys and encoding needed to evaluate a lookup table on a block.
+pub struct RadixContext<'a> {
+    pub fourier_bsk: FourierLweBootstrapKey<&'a [c64]>,
+    pub ksk: LweKeyswitchKey<&'a [u64]>,
+    pub fft: FftView<'a>,
+    pub message_modulus: u64,
+    pub carry_modulus: u64,
+}
+
+impl<'a> RadixContext<'a> {
+    pub fn new(
+        fourier_bsk: FourierLweBootstrapKey<&'a [c64]>,
+        ksk: LweKeyswitchKey<&'a [u64]>,
+        fft: FftView<'a>,
+        message_modulus: u64,
+        carry_modulus: u64,
+    ) -> Self {
+        assert!(message_modulus.is_power_of_two());
+        assert!(carry_modulus.is_power_of_two());
+        assert!(
+            (message_modulus * carry_modulus) as usize <= fourier_bsk.polynomial_size().0,
+            "message_modulus * carry_modulus must not exceed the polynomial size",
+        );
+        assert_eq!(
+            ksk.output_key_lwe_dimension(),
+            fourier_bsk.input_lwe_dimension()
+        );
+        assert_eq!(
+            ksk.input_key_lwe_dimension(),
+            fourier_bsk.output_lwe_dimension()
+        );
+        Self {
+            fourier_bsk,
+            ksk,
+            fft,
+            message_modulus,
+            carry_modulus,
+        }
+    }
+
+    pub fn block_size(&self) -> LweSize {
+        self.fourier_bsk.output_lwe_dimension().to_lwe_size()
+    }
+
+    fn modulus_sup(&self) -> u64 {
+        self.message_modulus * self.carry_modulus
+    }
+
+    /// Builds the trivially encrypted accumulator evaluating `f` on a block.
+    pub fn accumulator(&self, f: impl Fn(u64) -> u64) -> GlweCiphertextOwned<u64> {
+        let polynomial_size = self.fourier_bsk.polynomial_size();
+        let modulus_sup = self.modulus_sup();
+        let box_size = polynomial_size.0 / modulus_sup as usize;
+        let delta = (1_u64 << 63) / modulus_sup;
+
+        let mut accumulator = GlweCiphertext::new(
+            0_u64,
+            self.fourier_bsk.glwe_size(),
+            polynomial_size,
+            CiphertextModulus::new_native(),
+        );
+        let mut body = accumulator.get_mut_body();
+        let body = body.as_mut();
+
+        for (value, box_) in body.chunks_exact_mut(box_size).enumerate() {
+            box_.fill((f(value as u64) % modulus_sup).wrapping_mul(delta));
+        }
+
+        // Center each box on its value, the first half box wraps around negacyclically
+        let half_box_size = box_size / 2;
+        for coefficient in &mut body[..half_box_size] {
+            *coefficient = coefficient.wrapping_neg();
+        }
+        body.rotate_left(half_box_size);
+
+        accumulator
+    }
+
+    /// Keyswitches `input` to the bootstrap input key and bootstraps it with `accumulator`.
+    pub fn apply_accumulator(
+        &self,
+        output: &mut LweCiphertext<&mut [u64]>,
+        input: &LweCiphertext<&[u64]>,
+        accumulator: &GlweCiphertextOwned<u64>,
+        stack: PodStack<'_>,
+    ) {
+        let mut small = LweCiphertext::new(
+            0_u64,
+            self.fourier_bsk.input_lwe_dimension().to_lwe_size(),
+            CiphertextModulus::new_native(),
+        );
+        keyswitch_lwe_ciphertext(&self.ksk, input, &mut small);
+        programmable_bootstrap_lwe_ciphertext_mem_optimized(
+            &small,
+            output,
+            accumulator,
+            &self.fourier_bsk,
+            self.fft,
+            stack,
+        );
+    }
+
+    /// Packs two blocks as `lhs * message_modulus + rhs` so that one lookup sees both messages.
+    ///
+    /// Both blocks must have an empty carry, and the carry space must hold a full message.
+    pub fn pack(
+        &self,
+        lhs: &LweCiphertext<&[u64]>,
+        rhs: &LweCiphertext<&[u64]>,
+    ) -> LweCiphertextOwned<u64> {
+        assert!(self.carry_modulus >= self.message_modulus);
+        let mut packed =
+            LweCiphertext::new(0_u64, self.block_size(), CiphertextModulus::new_native());
+        lwe_ciphertext_cleartext_mul(&mut packed, lhs, Cleartext(self.message_modulus));
+        lwe_ciphertext_add_assign(&mut packed, rhs);
+        packed
+    }
+
+    /// Evaluates `f(lhs_message, rhs_message)` on two blocks with empty carries.
+    pub fn apply_bivariate(
+        &self,
+        output: &mut LweCiphertext<&mut [u64]>,
+        lhs: &LweCiphertext<&[u64]>,
+        rhs: &LweCiphertext<&[u64]>,
+        f: impl Fn(u64, u64) -> u64,
+        stack: PodStack<'_>,
+    ) {
+        let message_modulus = self.message_modulus;
+        let accumulator = self.accumulator(|packed| {
+            f(
+                (packed / message_modulus) % message_modulus,
+                packed % message_modulus,
+            )
+        });
+        let packed = self.pack(lhs, rhs);
+        self.apply_accumulator(output, &packed.as_view(), &accumulator, stack);
+    }
+
+    /// Extracts the most significant bit of the message of the most significant block, i.e. the
+    /// sign of the integer in two's complement.
+    pub fn sign(
+        &self,
+        output: &mut LweCiphertext<&mut [u64]>,
+        blocks: &LweCiphertextList<&[u64]>,
+        stack: PodStack<'_>,
+    ) -> Result<(), RadixError> {
+        let msb_block = blocks.iter().next_back().ok_or(RadixError::EmptyInteger)?;
+        let half_message_modulus = self.message_modulus / 2;
+        let message_modulus = self.message_modulus;
+        let accumulator =
+            self.accumulator(|value| (value % message_modulus) / half_message_modulus.max(1));
+        self.apply_accumulator(output, &msb_block, &accumulator, stack);
+        Ok(())
+    }
+
+    /// Compares two integers, the output block encodes one of [`COMPARISON_LESS`],
+    /// [`COMPARISON_EQUAL`] or [`COMPARISON_GREATER`].
+    ///
+    /// Blocks are compared pairwise, then the block results are reduced from the least to the most
+    /// significant block, a non-equal block overriding lower blocks.
+    pub fn compare(
+        &self,
+        output: &mut LweCiphertext<&mut [u64]>,
+        lhs: &LweCiphertextList<&[u64]>,
+        rhs: &LweCiphertextList<&[u64]>,
+        mut stack: PodStack<'_>,
+    ) -> Result<(), RadixError> {
+        assert_eq!(lhs.lwe_ciphertext_count(), rhs.lwe_ciphertext_count());
+        assert!(self.message_modulus > COMPARISON_GREATER);
+
+        let compare_block = |a: u64, b: u64| match a.cmp(&b) {
+            core::cmp::Ordering::Less => COMPARISON_LESS,
+            core::cmp::Ordering::Equal => COMPARISON_EQUAL,
+            core::cmp::Ordering::Greater => COMPARISON_GREATER,
+        };
+        let reduce = |msb: u64, lsb: u64| {
+            if msb == COMPARISON_EQUAL {
+                lsb
+            } else {
+                msb
+            }
+        };
+
+        let mut reduced: Option<LweCiphertextOwned<u64>> = None;
+        let mut block_cmp =
+            LweCiphertext::new(0_u64, self.block_size(), CiphertextModulus::new_native());
+        for (lhs_block, rhs_block) in lhs.iter().zip(rhs.iter()) {
+            self.apply_bivariate(
+                &mut block_cmp.as_mut_view(),
+                &lhs_block,
+                &rhs_block,
+                compare_block,
+                stack.rb_mut(),
+            );
+            reduced = Some(match reduced {
+                None => block_cmp.clone(),
+                Some(lower) => {
+                    let mut result = LweCiphertext::new(
+                        0_u64,
+                        self.block_size(),
+                        CiphertextModulus::new_native(),
+                    );
+                    self.apply_bivariate(
+                        &mut result.as_mut_view(),
+                        &block_cmp.as_view(),
+                        &lower.as_view(),
+                        reduce,
+                        stack.rb_mut(),
+                    );
+                    result
+                }
+            });
+        }
+
+        output
+            .as_mut()
+            .copy_from_slice(reduced.ok_or(RadixError::EmptyInteger)?.as_ref());
+        Ok(())
+    }
+
+    /// Propagates the carries from the least to the most significant block, leaving every block
+    /// with an empty carry. The carry of the most significant block is dropped.
+    pub fn propagate_carry(
+        &self,
+        blocks: &mut LweCiphertextList<&mut [u64]>,
+        mut stack: PodStack<'_>,
+    ) -> Result<(), RadixError> {
+        if blocks.lwe_ciphertext_count().0 == 0 {
+            return Err(RadixError::EmptyInteger);
+        }
+        let message_modulus = self.message_modulus;
+        let carry_accumulator = self.accumulator(|value| value / message_modulus);
+        let message_accumulator = self.accumulator(|value| value % message_modulus);
+
+        let block_count = blocks.lwe_ciphertext_count().0;
+        let mut carry: Option<LweCiphertextOwned<u64>> = None;
+        for (i, mut block) in blocks.iter_mut().enumerate() {
+            if let Some(carry) = &carry {
+                lwe_ciphertext_add_assign(&mut block, carry);
+            }
+            let input = LweCiphertext::from_container(
+                block.as_ref().to_vec(),
+                CiphertextModulus::new_native(),
+            );
+            if i + 1 < block_count {
+                let mut next_carry =
+                    LweCiphertext::new(0_u64, self.block_size(), CiphertextModulus::new_native());
+                self.apply_accumulator(
+                    &mut next_carry.as_mut_view(),
+                    &input.as_view(),
+                    &carry_accumulator,
+                    stack.rb_mut(),
+                );
+                carry = Some(next_carry);
+            }
+            self.apply_accumulator(
+                &mut block,
+                &input.as_view(),
+                &message_accumulator,
+                stack.rb_mut(),
+            );
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use concrete_csprng::seeders::Seed;
+    use tfhe::core_crypto::commons::generators::DeterministicSeeder;
+
+    const MESSAGE_MODULUS: u64 = 4;
+    const CARRY_MODULUS: u64 = 4;
+
+    struct Keys {
+        big_sk: LweSecretKeyOwned<u64>,
+        fourier_bsk: FourierLweBootstrapKeyOwned,
+        ksk: LweKeyswitchKeyOwned<u64>,
+        fft: Fft,
+        encryption_generator: EncryptionRandomGenerator<ActivatedRandomGenerator>,
+    }
+
+    impl Keys {
+        fn new() -> Self {
+            let polynomial_size = PolynomialSize(512);
+            let ciphertext_modulus = CiphertextModulus::new_native();
+            // negligible noise, the decryptions are exact
+            let variance = Variance(2_f64.powi(-120));
+
+            let mut secret_generator =
+                SecretRandomGenerator::<ActivatedRandomGenerator>::new(Seed(0));
+            let mut seeder = DeterministicSeeder::<ActivatedRandomGenerator>::new(Seed(1));
+            let mut encryption_generator =
+                EncryptionRandomGenerator::<ActivatedRandomGenerator>::new(Seed(2), &mut seeder);
+
+            let glwe_sk = allocate_and_generate_new_binary_glwe_secret_key(
+                GlweDimension(1),
+                polynomial_size,
+                &mut secret_generator,
+            );
+            let small_sk = allocate_and_generate_new_binary_lwe_secret_key(
+                LweDimension(10),
+                &mut secret_generator,
+            );
+            let big_sk = glwe_sk.clone().into_lwe_secret_key();
+
+            let std_bsk: LweBootstrapKeyOwned<u64> = allocate_and_generate_new_lwe_bootstrap_key(
+                &small_sk,
+                &glwe_sk,
+                DecompositionBaseLog(15),
+                DecompositionLevelCount(2),
+                variance,
+                ciphertext_modulus,
+                &mut encryption_generator,
+            );
+            let mut fourier_bsk = FourierLweBootstrapKeyOwned::new(
+                std_bsk.input_lwe_dimension(),
+                std_bsk.glwe_size(),
+                std_bsk.polynomial_size(),
+                std_bsk.decomposition_base_log(),
+                std_bsk.decomposition_level_count(),
+            );
+            convert_standard_lwe_bootstrap_key_to_fourier(&std_bsk, &mut fourier_bsk);
+
+            let ksk = allocate_and_generate_new_lwe_keyswitch_key(
+                &big_sk,
+                &small_sk,
+                DecompositionBaseLog(15),
+                DecompositionLevelCount(3),
+                variance,
+                ciphertext_modulus,
+                &mut encryption_generator,
+            );
+
+            Self {
+                big_sk,
+                fourier_bsk,
+                ksk,
+                fft: Fft::new(polynomial_size),
+                encryption_generator,
+            }
+        }
+
+        fn context(&self) -> RadixContext<'_> {
+            RadixContext::new(
+                self.fourier_bsk.as_view(),
+                self.ksk.as_view(),
+                self.fft.as_view(),
+                MESSAGE_MODULUS,
+                CARRY_MODULUS,
+            )
+        }
+
+        fn delta() -> u64 {
+            (1_u64 << 63) / (MESSAGE_MODULUS * CARRY_MODULUS)
+        }
+
+        /// Encrypts `value` in blocks of empty carries, least significant block first.
+        fn encrypt(&mut self, mut value: u64, block_count: usize) -> LweCiphertextListOwned<u64> {
+            let mut blocks = LweCiphertextList::new(
+                0_u64,
+                self.big_sk.lwe_dimension().to_lwe_size(),
+                LweCiphertextCount(block_count),
+                CiphertextModulus::new_native(),
+            );
+            for mut block in blocks.iter_mut() {
+                encrypt_lwe_ciphertext(
+                    &self.big_sk,
+                    &mut block,
+                    Plaintext((value % MESSAGE_MODULUS) * Self::delta()),
+                    Variance(2_f64.powi(-120)),
+                    &mut self.encryption_generator,
+                );
+                value /= MESSAGE_MODULUS;
+            }
+            blocks
+        }
+
+        fn decrypt(&self, block: &LweCiphertext<&[u64]>) -> u64 {
+            let decrypted = decrypt_lwe_ciphertext(&self.big_sk, block).0;
+            decrypted.wrapping_add(Self::delta() / 2) / Self::delta()
+        }
+
+        /// Decrypts blocks with empty carries, least significant block first.
+        fn decrypt_integer(&self, blocks: &LweCiphertextList<&[u64]>) -> u64 {
+            blocks.iter().rev().fold(0, |value, block| {
+                let block = self.decrypt(&block);
+                assert!(block < MESSAGE_MODULUS, "non empty carry {block}");
+                value * MESSAGE_MODULUS + block
+            })
+        }
+    }
+
+    fn buffers(keys: &Keys) -> ComputationBuffers {
+        let mut buffers = ComputationBuffers::new();
+        buffers.resize(
+            programmable_bootstrap_lwe_ciphertext_mem_optimized_requirement::<u64>(
+                keys.fourier_bsk.glwe_size(),
+                keys.fourier_bsk.polynomial_size(),
+                keys.fft.as_view(),
+            )
+            .unwrap()
+            .unaligned_bytes_required(),
+        );
+        buffers
+    }
+
+    #[test]
+    fn compare_and_sign_decrypt_to_the_cleartext_results() {
+        let mut keys = Keys::new();
+        let mut buffers = buffers(&keys);
+        let block_count = 3;
+        let mut output = LweCiphertext::new(
+            0_u64,
+            keys.context().block_size(),
+            CiphertextModulus::new_native(),
+        );
+
+        for (lhs, rhs, expected) in [
+            (37, 12, COMPARISON_GREATER),
+            (12, 37, COMPARISON_LESS),
+            (45, 45, COMPARISON_EQUAL),
+            (33, 34, COMPARISON_LESS),
+        ] {
+            let lhs = keys.encrypt(lhs, block_count);
+            let rhs = keys.encrypt(rhs, block_count);
+            keys.context()
+                .compare(
+                    &mut output.as_mut_view(),
+                    &lhs.as_view(),
+                    &rhs.as_view(),
+                    buffers.stack(),
+                )
+                .unwrap();
+            assert_eq!(keys.decrypt(&output.as_view()), expected);
+        }
+
+        // 2 in the most significant block of 3 has its most significant message bit set
+        for (value, expected) in [(0b10_00_01, 1), (0b01_11_11, 0)] {
+            let blocks = keys.encrypt(value, block_count);
+            keys.context()
+                .sign(
+                    &mut output.as_mut_view(),
+                    &blocks.as_view(),
+                    buffers.stack(),
+                )
+                .unwrap();
+            assert_eq!(keys.decrypt(&output.as_view()), expected);
+        }
+    }
+
+    #[test]
+    fn propagate_carry_decrypts_to_the_cleartext_sum() {
+        let mut keys = Keys::new();
+        let mut buffers = buffers(&keys);
+        let block_count = 3;
+        let modulus = MESSAGE_MODULUS.pow(block_count as u32);
+
+        // 0b11_11_11 + 1 carries across every block, the last carry is dropped
+        for (lhs, rhs) in [(27, 22), (63, 1), (0b10_01_11, 0b01_10_01), (0, 0)] {
+            let mut sum = keys.encrypt(lhs, block_count);
+            let rhs_blocks = keys.encrypt(rhs, block_count);
+            for (mut block, rhs_block) in sum.iter_mut().zip(rhs_blocks.iter()) {
+                lwe_ciphertext_add_assign(&mut block, &rhs_block);
+            }
+            keys.context()
+                .propagate_carry(&mut sum.as_mut_view(), buffers.stack())
+                .unwrap();
+            assert_eq!(keys.decrypt_integer(&sum.as_view()), (lhs + rhs) % modulus);
+        }
+    }
+
+    #[test]
+    fn bivariate_lut_decrypts_to_the_cleartext_results() {
+        let mut keys = Keys::new();
+        let mut buffers = buffers(&keys);
+        let f = |lhs: u64, rhs: u64| (lhs * 3 + rhs * rhs) % MESSAGE_MODULUS;
+        let mut output = LweCiphertext::new(
+            0_u64,
+            keys.context().block_size(),
+            CiphertextModulus::new_native(),
+        );
+
+        for lhs in 0..MESSAGE_MODULUS {
+            for rhs in 0..MESSAGE_MODULUS {
+                let lhs_block = keys.encrypt(lhs, 1);
+                let rhs_block = keys.encrypt(rhs, 1);
+                keys.context().apply_bivariate(
+                    &mut output.as_mut_view(),
+                    &lhs_block.get(0),
+                    &rhs_block.get(0),
+                    f,
+                    buffers.stack(),
+                );
+                assert_eq!(keys.decrypt(&output.as_view()), f(lhs, rhs), "{lhs} {rhs}");
+            }
+        }
+    }
+
+    #[test]
+    fn empty_integers_are_rejected() {
+        let mut keys = Keys::new();
+        let mut buffers = buffers(&keys);
+        let mut empty = keys.encrypt(0, 0);
+        let mut output = LweCiphertext::new(
+            0_u64,
+            keys.context().block_size(),
+            CiphertextModulus::new_native(),
+        );
+
+        assert_eq!(
+            keys.context().compare(
+                &mut output.as_mut_view(),
+                &empty.as_view(),
+                &empty.as_view(),
+                buffers.stack(),
+            ),
+            Err(RadixError::EmptyInteger)
+        );
+        assert_eq!(
+            keys.context()
+                .sign(&mut output.as_mut_view(), &empty.as_view(), buffers.stack()),
+            Err(RadixError::EmptyInteger)
+        );
+        assert_eq!(
+            keys.context()
+                .propagate_carry(&mut empty.as_mut_view(), buffers.stack()),
+            Err(RadixError::EmptyInteger)
+        );
+    }
+}
@@ -0,0 +1,210 @@
+use dyn_stack::{GlobalPodBuffer, PodStack, SizeOverflow, StackReq};
+use tfhe::core_crypto::fft_impl::fft64::math::fft::FftView;
+use tfhe::core_crypto::prelude::*;
+
+use super::modulus_switching::bootstrap_with_modulus_switching_requirement;
+use super::wop_pbs::circuit_bootstrap_boolean_vertical_packing_scratch;
+
+/// Parameters sizing a [`ScratchArena`].
+///
+/// The arena is sized from the maximum requirement of every operation that can be run with these
+/// parameters, so that a single buffer can be reused across all of them.
+#[derive(Copy, Clone, Debug)]
+pub struct ScratchParameters {
+    pub bsk_glwe_dimension: usize,
+    pub bsk_polynomial_size: usize,
+    pub ksk_output_dimension: usize,
+    /// Maximum number of input bits of a circuit bootstrap + vertical packing, 0 if unused.
+    pub wop_ct_in_count: usize,
+    /// Maximum number of lookup tables of a vertical packing, 0 if unused.
+    pub wop_lut_count: usize,
+    pub fpksk_output_polynomial_size: usize,
+    pub cbs_decomposition_level_count: usize,
+    /// Number of workers of a parallel circuit bootstrap + vertical packing, 0 if only run
+    /// sequentially.
+    pub worker_count: usize,
+}
+
+impl ScratchParameters {
+    pub fn requirement(&self, fft: FftView<'_>) -> Result<StackReq, SizeOverflow> {
+        let glwe_size = GlweDimension(self.bsk_glwe_dimension).to_glwe_size();
+        let polynomial_size = PolynomialSize(self.bsk_polynomial_size);
+
+        let bootstrap = bootstrap_with_modulus_switching_requirement(
+            glwe_size,
+            polynomial_size,
+            LweDimension(self.ksk_output_dimension),
+            fft,
+        )?;
+        let convert = convert_standard_lwe_bootstrap_key_to_fourier_mem_optimized_requirement(fft)?;
+        let extract_bits = extract_bits_from_lwe_ciphertext_mem_optimized_requirement::<u64>(
+            LweDimension(self.bsk_glwe_dimension * self.bsk_polynomial_size),
+            LweDimension(self.ksk_output_dimension + 1),
+            glwe_size,
+            polynomial_size,
+            fft,
+        )?;
+
+        let vertical_packing = if self.wop_ct_in_count == 0 {
+            StackReq::empty()
+        } else {
+            let lut_size = 1 << self.wop_ct_in_count;
+            let fpksk_output_polynomial_size =
+                PolynomialSize(self.fpksk_output_polynomial_size.max(lut_size));
+            let sequential =
+                circuit_bootstrap_boolean_vertical_packing_lwe_ciphertext_list_mem_optimized_requirement::<
+                    u64,
+                >(
+                    LweCiphertextCount(self.wop_ct_in_count),
+                    LweCiphertextCount(self.wop_lut_count),
+                    LweDimension(self.ksk_output_dimension).to_lwe_size(),
+                    PolynomialCount(self.wop_lut_count),
+                    LweDimension(self.bsk_glwe_dimension * self.bsk_polynomial_size).to_lwe_size(),
+                    glwe_size,
+                    fpksk_output_polynomial_size,
+                    DecompositionLevelCount(self.cbs_decomposition_level_count),
+                    fft,
+                )?;
+            let parallel = if self.worker_count == 0 {
+                StackReq::empty()
+            } else {
+                circuit_bootstrap_boolean_vertical_packing_scratch(
+                    LweCiphertextCount(self.wop_ct_in_count),
+                    LweCiphertextCount(self.wop_lut_count),
+                    LweDimension(self.ksk_output_dimension).to_lwe_size(),
+                    PolynomialCount(self.wop_lut_count),
+                    LweDimension(self.bsk_glwe_dimension * self.bsk_polynomial_size).to_lwe_size(),
+                    glwe_size,
+                    fpksk_output_polynomial_size,
+                    DecompositionLevelCount(self.cbs_decomposition_level_count),
+                    self.worker_count,
+                    fft,
+                )?
+            };
+            StackReq::try_any_of([sequential, parallel])?
+        };
+
+        StackReq::try_any_of([bootstrap, convert, extract_bits, vertical_packing])
+    }
+}
+
+/// Reusable scratch memory shared by the operations of one thread.
+///
+/// The arena is allocated once and its memory is handed out as the `stack` of each operation,
+/// avoiding an allocation per call. It is not meant to be shared between threads: each worker
+/// should own its arena.
+pub struct ScratchArena {
+    buffer: GlobalPodBuffer,
+    req: StackReq,
+}
+
+impl ScratchArena {
+    pub fn new(req: StackReq) -> Self {
+        Self {
+            buffer: GlobalPodBuffer::new(req),
+            req,
+        }
+    }
+
+    pub fn size_bytes(&self) -> usize {
+        self.req.size_bytes()
+    }
+
+    pub fn align_bytes(&self) -> usize {
+        self.req.align_bytes()
+    }
+
+    pub fn as_mut_ptr(&mut self) -> *mut u8 {
+        self.buffer.as_mut_ptr()
+    }
+
+    pub fn stack(&mut self) -> PodStack<'_> {
+        PodStack::new(&mut self.buffer)
+    }
+
+    /// Whether the arena can hold the given requirement.
+    pub fn fits(&self, req: StackReq) -> bool {
+        req.size_bytes() <= self.req.size_bytes() && req.align_bytes() <= self.req.align_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arena_covers_each_operation() {
+        let params = ScratchParameters {
+            bsk_glwe_dimension: 1,
+            bsk_polynomial_size: 1024,
+            ksk_output_dimension: 600,
+            wop_ct_in_count: 4,
+            wop_lut_count: 2,
+            fpksk_output_polynomial_size: 1024,
+            cbs_decomposition_level_count: 2,
+            worker_count: 3,
+        };
+        let fft = Fft::new(PolynomialSize(params.bsk_polynomial_size));
+        let fft = fft.as_view();
+
+        let arena = ScratchArena::new(params.requirement(fft).unwrap());
+
+        let bootstrap = programmable_bootstrap_lwe_ciphertext_mem_optimized_requirement::<u64>(
+            GlweDimension(params.bsk_glwe_dimension).to_glwe_size(),
+            PolynomialSize(params.bsk_polynomial_size),
+            fft,
+        )
+        .unwrap();
+        let convert =
+            convert_standard_lwe_bootstrap_key_to_fourier_mem_optimized_requirement(fft).unwrap();
+        let bootstrap_with_modulus_switching = bootstrap_with_modulus_switching_requirement(
+            GlweDimension(params.bsk_glwe_dimension).to_glwe_size(),
+            PolynomialSize(params.bsk_polynomial_size),
+            LweDimension(params.ksk_output_dimension),
+            fft,
+        )
+        .unwrap();
+
+        let extract_bits = extract_bits_from_lwe_ciphertext_mem_optimized_requirement::<u64>(
+            LweDimension(params.bsk_glwe_dimension * params.bsk_polynomial_size),
+            LweDimension(params.ksk_output_dimension + 1),
+            GlweDimension(params.bsk_glwe_dimension).to_glwe_size(),
+            PolynomialSize(params.bsk_polynomial_size),
+            fft,
+        )
+        .unwrap();
+        let vertical_packing = |worker_count| {
+            circuit_bootstrap_boolean_vertical_packing_scratch(
+                LweCiphertextCount(params.wop_ct_in_count),
+                LweCiphertextCount(params.wop_lut_count),
+                LweDimension(params.ksk_output_dimension).to_lwe_size(),
+                PolynomialCount(params.wop_lut_count),
+                LweDimension(params.bsk_glwe_dimension * params.bsk_polynomial_size).to_lwe_size(),
+                GlweDimension(params.bsk_glwe_dimension).to_glwe_size(),
+                PolynomialSize(params.fpksk_output_polynomial_size),
+                DecompositionLevelCount(params.cbs_decomposition_level_count),
+                worker_count,
+                fft,
+            )
+            .unwrap()
+        };
+
+        assert!(arena.fits(bootstrap));
+        assert!(arena.fits(bootstrap_with_modulus_switching));
+        assert!(arena.fits(convert));
+        assert!(arena.fits(extract_bits));
+        for worker_count in 1..=params.worker_count {
+            assert!(arena.fits(vertical_packing(worker_count)));
+        }
+        // the parallel requirement grows with the workers, it must be part of the arena
+        assert!(!ScratchArena::new(
+            ScratchParameters {
+                worker_count: 0,
+                ..params
+            }
+            .requirement(fft)
+            .unwrap()
+        )
+        .fits(vertical_packing(params.worker_count)));
+    }
+}
@@ -0,0 +1,472 @@
+//! Circuit bootstrap + vertical packing split across workers.
+//!
+//! The circuit bootstraps of the input bits are independent, and so are the vertical packings of
+//! the output lookup tables once every GGSW is computed. The caller-provided stack holds the shared
+//! Fourier GGSW list followed by one scratch slot per worker, each worker processing a contiguous
+//! group of items with its own slot.
+
+use aligned_vec::CACHELINE_ALIGN;
+use concrete_fft::c64;
+use dyn_stack::{PodStack, ReborrowMut, SizeOverflow, StackReq};
+use tfhe::core_crypto::fft_impl::fft64::crypto::bootstrap::FourierLweBootstrapKeyView;
+use tfhe::core_crypto::fft_impl::fft64::crypto::ggsw::{
+    fill_with_forward_fourier_scratch, FourierGgswCiphertext, FourierGgswCiphertextList,
+};
+use tfhe::core_crypto::fft_impl::fft64::crypto::wop_pbs::{
+    circuit_bootstrap_boolean, circuit_bootstrap_boolean_scratch, vertical_packing,
+    vertical_packing_scratch,
+};
+use tfhe::core_crypto::fft_impl::fft64::math::fft::FftView;
+use tfhe::core_crypto::prelude::*;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+fn ggsw_list_len(
+    ggsw_count: usize,
+    glwe_size: GlweSize,
+    polynomial_size: PolynomialSize,
+    level_cbs: DecompositionLevelCount,
+) -> usize {
+    ggsw_count
+        * polynomial_size.to_fourier_polynomial_size().0
+        * glwe_size.0
+        * glwe_size.0
+        * level_cbs.0
+}
+
+/// Scratch of a single worker, covering one circuit bootstrap and one vertical packing.
+#[allow(clippy::too_many_arguments)]
+fn worker_scratch(
+    lwe_list_in_count: LweCiphertextCount,
+    lwe_in_size: LweSize,
+    small_lut_polynomial_count: PolynomialCount,
+    bsk_output_lwe_size: LweSize,
+    glwe_size: GlweSize,
+    fpksk_output_polynomial_size: PolynomialSize,
+    level_cbs: DecompositionLevelCount,
+    fft: FftView<'_>,
+) -> Result<StackReq, SizeOverflow> {
+    StackReq::try_any_of([
+        StackReq::try_new_aligned::<u64>(
+            fpksk_output_polynomial_size.0 * glwe_size.0 * glwe_size.0 * level_cbs.0,
+            CACHELINE_ALIGN,
+        )?
+        .try_and(StackReq::try_any_of([
+            circuit_bootstrap_boolean_scratch::<u64>(
+                lwe_in_size,
+                bsk_output_lwe_size,
+                glwe_size,
+                fpksk_output_polynomial_size,
+                fft,
+            )?,
+            fill_with_forward_fourier_scratch(fft)?,
+        ])?)?,
+        vertical_packing_scratch::<u64>(
+            glwe_size,
+            fpksk_output_polynomial_size,
+            small_lut_polynomial_count,
+            lwe_list_in_count.0,
+            fft,
+        )?,
+    ])
+}
+
+/// Size of a worker slot, rounded up so that consecutive slots stay aligned.
+fn worker_slot_size(worker: StackReq) -> usize {
+    worker.size_bytes().next_multiple_of(worker.align_bytes())
+}
+
+/// Stack requirement of [`circuit_bootstrap_boolean_vertical_packing`] with `worker_count`
+/// workers.
+#[allow(clippy::too_many_arguments)]
+pub fn circuit_bootstrap_boolean_vertical_packing_scratch(
+    lwe_list_in_count: LweCiphertextCount,
+    lwe_list_out_count: LweCiphertextCount,
+    lwe_in_size: LweSize,
+    big_lut_polynomial_count: PolynomialCount,
+    bsk_output_lwe_size: LweSize,
+    glwe_size: GlweSize,
+    fpksk_output_polynomial_size: PolynomialSize,
+    level_cbs: DecompositionLevelCount,
+    worker_count: usize,
+    fft: FftView<'_>,
+) -> Result<StackReq, SizeOverflow> {
+    assert_ne!(worker_count, 0);
+    let small_lut_polynomial_count =
+        PolynomialCount(big_lut_polynomial_count.0 / lwe_list_out_count.0);
+
+    let worker = worker_scratch(
+        lwe_list_in_count,
+        lwe_in_size,
+        small_lut_polynomial_count,
+        bsk_output_lwe_size,
+        glwe_size,
+        fpksk_output_polynomial_size,
+        level_cbs,
+        fft,
+    )?;
+    let workers_size = worker_slot_size(worker)
+        .checked_mul(worker_count)
+        .ok_or(SizeOverflow)?;
+
+    StackReq::try_all_of([
+        StackReq::try_new_aligned::<c64>(
+            ggsw_list_len(
+                lwe_list_in_count.0,
+                glwe_size,
+                fpksk_output_polynomial_size,
+                level_cbs,
+            ),
+            CACHELINE_ALIGN,
+        )?,
+        StackReq::try_new_aligned::<u8>(workers_size, worker.align_bytes())?,
+    ])
+}
+
+/// Runs `f` on each group of `items` with the scratch slot of its worker.
+fn for_each_worker<T: Send>(
+    items: Vec<T>,
+    slots: &mut [u8],
+    slot_size: usize,
+    f: impl Fn(T, PodStack<'_>) + Sync,
+) {
+    let worker_count = slots.len() / slot_size;
+    let group_size = items.len().div_ceil(worker_count);
+    let mut groups = Vec::with_capacity(worker_count);
+    let mut items = items.into_iter();
+    for _ in 0..worker_count {
+        groups.push(items.by_ref().take(group_size).collect::<Vec<_>>());
+    }
+
+    let run = |(group, slot): (Vec<T>, &mut [u8])| {
+        let mut stack = PodStack::new(slot);
+        for item in group {
+            f(item, stack.rb_mut());
+        }
+    };
+
+    #[cfg(feature = "parallel")]
+    groups
+        .into_par_iter()
+        .zip(slots.par_chunks_exact_mut(slot_size))
+        .for_each(run);
+    #[cfg(not(feature = "parallel"))]
+    groups
+        .into_iter()
+        .zip(slots.chunks_exact_mut(slot_size))
+        .for_each(run);
+}
+
+/// Circuit bootstrap of every input bit then vertical packing of every output lookup table, each
+/// step spread over `worker_count` workers.
+///
+/// The stack must hold [`circuit_bootstrap_boolean_vertical_packing_scratch`] for the same
+/// `worker_count`. The output is the same as the sequential tfhe implementation.
+#[allow(clippy::too_many_arguments)]
+pub fn circuit_bootstrap_boolean_vertical_packing(
+    big_lut_as_polynomial_list: PolynomialList<&[u64]>,
+    fourier_bsk: FourierLweBootstrapKeyView<'_>,
+    mut lwe_list_out: LweCiphertextList<&mut [u64]>,
+    lwe_list_in: LweCiphertextList<&[u64]>,
+    pfpksk_list: LwePrivateFunctionalPackingKeyswitchKeyList<&[u64]>,
+    level_cbs: DecompositionLevelCount,
+    base_log_cbs: DecompositionBaseLog,
+    worker_count: usize,
+    fft: FftView<'_>,
+    stack: PodStack<'_>,
+) {
+    assert_ne!(worker_count, 0);
+    let glwe_size = pfpksk_list.output_key_glwe_dimension().to_glwe_size();
+    let polynomial_size = pfpksk_list.output_polynomial_size();
+    let lwe_list_in_count = lwe_list_in.lwe_ciphertext_count();
+    let number_of_luts = lwe_list_out.lwe_ciphertext_count().0;
+    let small_lut_size = big_lut_as_polynomial_list.polynomial_count().0 / number_of_luts;
+
+    let worker = worker_scratch(
+        lwe_list_in_count,
+        lwe_list_in.lwe_size(),
+        PolynomialCount(small_lut_size),
+        fourier_bsk.output_lwe_dimension().to_lwe_size(),
+        fourier_bsk.glwe_size(),
+        polynomial_size,
+        level_cbs,
+        fft,
+    )
+    .unwrap();
+    let slot_size = worker_slot_size(worker);
+
+    let (mut ggsw_list_data, stack) = stack.make_aligned_with(
+        ggsw_list_len(lwe_list_in_count.0, glwe_size, polynomial_size, level_cbs),
+        CACHELINE_ALIGN,
+        |_| c64::default(),
+    );
+    let (mut slots, _) =
+        stack.make_aligned_raw::<u8>(slot_size * worker_count, worker.align_bytes());
+
+    let ggsw_len = ggsw_list_len(1, glwe_size, polynomial_size, level_cbs);
+    let ggsw_res_len = polynomial_size.0 * glwe_size.0 * glwe_size.0 * level_cbs.0;
+    let ciphertext_modulus = pfpksk_list.ciphertext_modulus();
+
+    let circuit_bootstraps: Vec<_> = lwe_list_in
+        .iter()
+        .zip(ggsw_list_data.chunks_exact_mut(ggsw_len))
+        .collect();
+    for_each_worker(
+        circuit_bootstraps,
+        &mut slots,
+        slot_size,
+        |(lwe_in, ggsw_data), stack| {
+            let (mut ggsw_res_data, mut stack) =
+                stack.make_aligned_with(ggsw_res_len, CACHELINE_ALIGN, |_| 0_u64);
+            let mut ggsw_res = GgswCiphertext::from_container(
+                &mut *ggsw_res_data,
+                glwe_size,
+                polynomial_size,
+                base_log_cbs,
+                ciphertext_modulus,
+            );
+
+            circuit_bootstrap_boolean(
+                fourier_bsk,
+                lwe_in,
+                ggsw_res.as_mut_view(),
+                DeltaLog(u64::BITS as usize - 1),
+                pfpksk_list.as_view(),
+                fft,
+                stack.rb_mut(),
+            );
+
+            FourierGgswCiphertext::from_container(
+                ggsw_data,
+                glwe_size,
+                polynomial_size,
+                base_log_cbs,
+                level_cbs,
+            )
+            .fill_with_forward_fourier(ggsw_res.as_view(), fft, stack);
+        },
+    );
+
+    let ggsw_list = FourierGgswCiphertextList::new(
+        &*ggsw_list_data,
+        lwe_list_in_count.0,
+        glwe_size,
+        polynomial_size,
+        base_log_cbs,
+        level_cbs,
+    );
+
+    let vertical_packings: Vec<_> = big_lut_as_polynomial_list
+        .chunks_exact(small_lut_size)
+        .zip(lwe_list_out.iter_mut())
+        .collect();
+    for_each_worker(
+        vertical_packings,
+        &mut slots,
+        slot_size,
+        |(lut, lwe_out), stack| {
+            vertical_packing(lut, lwe_out, ggsw_list.as_view(), fft, stack);
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use concrete_csprng::seeders::Seed;
+    use tfhe::core_crypto::commons::generators::DeterministicSeeder;
+
+    #[test]
+    fn scratch_grows_with_workers() {
+        let polynomial_size = PolynomialSize(512);
+        let fft = Fft::new(polynomial_size);
+        let fft = fft.as_view();
+
+        let scratch = |worker_count| {
+            circuit_bootstrap_boolean_vertical_packing_scratch(
+                LweCiphertextCount(4),
+                LweCiphertextCount(2),
+                LweSize(601),
+                PolynomialCount(2),
+                LweSize(1025),
+                GlweSize(2),
+                polynomial_size,
+                DecompositionLevelCount(2),
+                worker_count,
+                fft,
+            )
+            .unwrap()
+        };
+
+        let one = scratch(1);
+        let four = scratch(4);
+        assert!(four.size_bytes() > one.size_bytes());
+        assert_eq!(four.align_bytes(), one.align_bytes());
+    }
+
+    #[test]
+    fn same_output_as_sequential() {
+        let polynomial_size = PolynomialSize(512);
+        let glwe_dimension = GlweDimension(1);
+        let lwe_dimension = LweDimension(10);
+        let ciphertext_modulus = CiphertextModulus::new_native();
+        // negligible noise, the decryptions are exact
+        let variance = Variance(2_f64.powi(-120));
+
+        let mut secret_generator = SecretRandomGenerator::<ActivatedRandomGenerator>::new(Seed(0));
+        let mut seeder = DeterministicSeeder::<ActivatedRandomGenerator>::new(Seed(1));
+        let mut encryption_generator =
+            EncryptionRandomGenerator::<ActivatedRandomGenerator>::new(Seed(2), &mut seeder);
+
+        let glwe_sk = allocate_and_generate_new_binary_glwe_secret_key(
+            glwe_dimension,
+            polynomial_size,
+            &mut secret_generator,
+        );
+        let lwe_small_sk =
+            allocate_and_generate_new_binary_lwe_secret_key(lwe_dimension, &mut secret_generator);
+        let lwe_big_sk = glwe_sk.clone().into_lwe_secret_key();
+
+        let std_bsk: LweBootstrapKeyOwned<u64> = allocate_and_generate_new_lwe_bootstrap_key(
+            &lwe_small_sk,
+            &glwe_sk,
+            DecompositionBaseLog(15),
+            DecompositionLevelCount(2),
+            variance,
+            ciphertext_modulus,
+            &mut encryption_generator,
+        );
+        let mut fourier_bsk = FourierLweBootstrapKeyOwned::new(
+            std_bsk.input_lwe_dimension(),
+            std_bsk.glwe_size(),
+            std_bsk.polynomial_size(),
+            std_bsk.decomposition_base_log(),
+            std_bsk.decomposition_level_count(),
+        );
+        convert_standard_lwe_bootstrap_key_to_fourier(&std_bsk, &mut fourier_bsk);
+
+        let pfpksk_list = allocate_and_generate_new_circuit_bootstrap_lwe_pfpksk_list(
+            &lwe_big_sk,
+            &glwe_sk,
+            DecompositionBaseLog(15),
+            DecompositionLevelCount(2),
+            variance,
+            ciphertext_modulus,
+            &mut encryption_generator,
+        );
+
+        // 3 bits of 5, most significant first
+        let bits = [1_u64, 0, 1];
+        let mut lwe_list_in = LweCiphertextList::new(
+            0_u64,
+            lwe_dimension.to_lwe_size(),
+            LweCiphertextCount(bits.len()),
+            ciphertext_modulus,
+        );
+        for (mut lwe, bit) in lwe_list_in.iter_mut().zip(bits) {
+            encrypt_lwe_ciphertext(
+                &lwe_small_sk,
+                &mut lwe,
+                Plaintext(bit << 63),
+                variance,
+                &mut encryption_generator,
+            );
+        }
+
+        // 2 tables of 8 entries, padded to the polynomial size
+        let lut_count = 2;
+        let lut_size = 1 << bits.len();
+        let mut luts = vec![0_u64; polynomial_size.0 * lut_count];
+        for (i, lut) in luts.chunks_exact_mut(polynomial_size.0).enumerate() {
+            for (j, entry) in lut[..lut_size].iter_mut().enumerate() {
+                *entry = ((i * lut_size + j) as u64) << 59;
+            }
+        }
+        let luts = PolynomialList::from_container(luts.as_slice(), polynomial_size);
+
+        let level_cbs = DecompositionLevelCount(4);
+        let base_log_cbs = DecompositionBaseLog(6);
+        let fft = Fft::new(polynomial_size);
+        let fft = fft.as_view();
+        let new_output = || {
+            LweCiphertextList::new(
+                0_u64,
+                lwe_big_sk.lwe_dimension().to_lwe_size(),
+                LweCiphertextCount(lut_count),
+                ciphertext_modulus,
+            )
+        };
+
+        let mut sequential = new_output();
+        let mut buffers = ComputationBuffers::new();
+        buffers.resize(
+            circuit_bootstrap_boolean_vertical_packing_lwe_ciphertext_list_mem_optimized_requirement::<
+                u64,
+            >(
+                lwe_list_in.lwe_ciphertext_count(),
+                sequential.lwe_ciphertext_count(),
+                lwe_list_in.lwe_size(),
+                PolynomialCount(lut_count),
+                fourier_bsk.output_lwe_dimension().to_lwe_size(),
+                fourier_bsk.glwe_size(),
+                polynomial_size,
+                level_cbs,
+                fft,
+            )
+            .unwrap()
+            .unaligned_bytes_required(),
+        );
+        circuit_bootstrap_boolean_vertical_packing_lwe_ciphertext_list_mem_optimized(
+            &lwe_list_in,
+            &mut sequential,
+            &luts,
+            &fourier_bsk,
+            &pfpksk_list,
+            base_log_cbs,
+            level_cbs,
+            fft,
+            buffers.stack(),
+        );
+
+        // more workers than vertical packings, some work on nothing
+        let worker_count = 3;
+        let mut parallel = new_output();
+        buffers.resize(
+            circuit_bootstrap_boolean_vertical_packing_scratch(
+                lwe_list_in.lwe_ciphertext_count(),
+                parallel.lwe_ciphertext_count(),
+                lwe_list_in.lwe_size(),
+                PolynomialCount(lut_count),
+                fourier_bsk.output_lwe_dimension().to_lwe_size(),
+                fourier_bsk.glwe_size(),
+                polynomial_size,
+                level_cbs,
+                worker_count,
+                fft,
+            )
+            .unwrap()
+            .unaligned_bytes_required(),
+        );
+        circuit_bootstrap_boolean_vertical_packing(
+            luts,
+            fourier_bsk.as_view(),
+            parallel.as_mut_view(),
+            lwe_list_in.as_view(),
+            pfpksk_list.as_view(),
+            level_cbs,
+            base_log_cbs,
+            worker_count,
+            fft,
+            buffers.stack(),
+        );
+
+        assert_eq!(parallel, sequential);
+        let decomposer = SignedDecomposer::new(DecompositionBaseLog(5), DecompositionLevelCount(1));
+        for (i, lwe) in parallel.iter().enumerate() {
+            let decrypted = decrypt_lwe_ciphertext(&lwe_big_sk, &lwe);
+            let decoded = decomposer.closest_representable(decrypted.0) >> 59;
+            assert_eq!(decoded, (i * lut_size + 5) as u64);
+        }
+    }
+}
@@ -12,7 +12,7 @@ use tfhe::core_crypto::commons::math::random::RandomGenerator;
 use tfhe::core_crypto::commons::parameters::*;
 
 use concrete_csprng::seeders::Seed;
-use concrete_security_curves::gaussian::security::{minimal_variance_glwe, minimal_variance_lwe};
+use concrete_security_curves::gaussian::curves::SecurityCurves;
 
 use tfhe::core_crypto::entities::{Polynomial, PolynomialList};
 
@@ -70,6 +70,7 @@ pub fn extract_bits(
     br_log_base: u64,
     br_level: u64,
     ciphertext_modulus_log: u32,
+    security_curves: &SecurityCurves,
     security_level: u64,
 ) {
     let mut csprng = RandomGenerator::<SoftwareRandomGenerator>::new(Seed(0));
@@ -92,8 +93,11 @@ pub fn extract_bits(
     // We iterate on the list in reverse as we want to store the extracted MSB at index 0
     for (bit_idx, output_ct) in lwe_list_out.iter_mut().rev().enumerate() {
         let shifted_lwe = temp_lwe << (ciphertext_n_bits - delta_log - bit_idx - 1);
-        let variance_ksk =
-            minimal_variance_lwe(lwe_dimension, ciphertext_modulus_log, security_level);
+        let variance_ksk = security_curves.minimal_variance_lwe(
+            lwe_dimension,
+            ciphertext_modulus_log,
+            security_level,
+        );
         let keyswitch_variance = variance_keyswitch(
             glwe_dimension * polynomial_size,
             ks_log_base,
@@ -148,7 +152,7 @@ pub fn extract_bits(
         );
 
         // blind rotate + sample extract
-        let variance_bsk = minimal_variance_glwe(
+        let variance_bsk = security_curves.minimal_variance_glwe(
             glwe_dimension,
             polynomial_size,
             ciphertext_modulus_log,
@@ -399,6 +403,7 @@ pub fn blind_rotate(
     lookup_table: &mut [u64],
     ggsw_list: &[u64],
     ciphertext_modulus_log: u32,
+    security_curves: &SecurityCurves,
     security_level: u64,
     csprng: &mut RandomGenerator<SoftwareRandomGenerator>,
 ) -> u64 {
@@ -417,7 +422,7 @@ pub fn blind_rotate(
     // The Vertical packing (VP) selected a lut `lookup_table` using part of the the GGSWs
     // The blind rotate outputs: L[exponent] + noise_blindrotate(n= number of GGSWs, var_bsk=var_circuit_bootstrap)
 
-    let variance_bsk = minimal_variance_glwe(
+    let variance_bsk = security_curves.minimal_variance_glwe(
         glwe_dimension,
         polynomial_size,
         ciphertext_modulus_log,
@@ -483,6 +488,7 @@ pub fn vertical_packing(
     pbs_log_base: u64,
     pbs_level: u64,
     ciphertext_modulus_log: u32,
+    security_curves: &SecurityCurves,
     security_level: u64,
     csprng: &mut RandomGenerator<SoftwareRandomGenerator>,
 ) -> u64 {
@@ -550,6 +556,7 @@ pub fn vertical_packing(
         &mut cmux_tree_lut_res,
         br_ggsw,
         ciphertext_modulus_log,
+        security_curves,
         security_level,
         csprng,
     )
@@ -576,6 +583,7 @@ pub fn circuit_bootstrap_boolean_vertical_packing(
     pp_level: u64,
     pp_log_base: u64,
     ciphertext_modulus_log: u32,
+    security_curves: &SecurityCurves,
     security_level: u64,
 ) {
     let sw_csprng = &mut RandomGenerator::<SoftwareRandomGenerator>::new(Seed(0));
@@ -607,6 +615,7 @@ pub fn circuit_bootstrap_boolean_vertical_packing(
             pbs_log_base,
             pbs_level,
             ciphertext_modulus_log,
+            security_curves,
             security_level,
             sw_csprng,
         );
@@ -15,6 +15,8 @@
 
 #define FFT_SCALING_WEIGHT -2.57722494
 
+#define GAUSSIAN_TAIL_LOG2_PROBABILITY -128
+
 #ifdef __cplusplus
 extern "C" {
 #endif // __cplusplus
@@ -23,6 +25,35 @@ double concrete_cpu_estimate_modulus_switching_noise_with_binary_key(uint64_t in
                                                                      uint64_t glwe_log2_polynomial_size,
                                                                      uint32_t ciphertext_modulus_log);
 
+double concrete_cpu_estimate_packing_private_keyswitch(double var_glwe,
+                                                       double var_ggsw,
+                                                       uint64_t log2_base,
+                                                       uint64_t level,
+                                                       uint64_t output_glwe_dimension,
+                                                       uint64_t output_polynomial_size,
+                                                       uint32_t ciphertext_modulus_log);
+
+double concrete_cpu_minimal_variance_glwe(uint64_t glwe_dimension,
+                                          uint64_t polynomial_size,
+                                          uint32_t ciphertext_modulus_log,
+                                          uint64_t security_level);
+
+double concrete_cpu_minimal_variance_lwe(uint64_t lwe_dimension,
+                                         uint32_t ciphertext_modulus_log,
+                                         uint64_t security_level);
+
+double concrete_cpu_modular_variance_to_variance(double modular_variance,
+                                                 uint32_t ciphertext_modulus_log);
+
+double concrete_cpu_variance_atomic_pattern_output(double input_variance,
+                                                   uint64_t input_lwe_dimension,
+                                                   uint64_t ks_log2_base,
+                                                   uint64_t ks_level,
+                                                   double variance_ksk,
+                                                   uint64_t internal_ks_output_lwe_dimension,
+                                                   uint64_t glwe_log2_polynomial_size,
+                                                   uint32_t ciphertext_modulus_log);
+
 double concrete_cpu_variance_blind_rotate(uint64_t in_lwe_dimension,
                                           uint64_t out_glwe_dimension,
                                           uint64_t out_polynomial_size,
@@ -32,12 +63,62 @@ double concrete_cpu_variance_blind_rotate(uint64_t in_lwe_dimension,
                                           uint32_t fft_precision,
                                           double variance_bsk);
 
+double concrete_cpu_variance_cmux(uint64_t glwe_dimension,
+                                  uint64_t polynomial_size,
+                                  uint64_t log2_base,
+                                  uint64_t level,
+                                  uint32_t ciphertext_modulus_log,
+                                  uint32_t fft_precision,
+                                  double variance_ggsw);
+
+double concrete_cpu_variance_external_product_glwe(uint64_t glwe_dimension,
+                                                   uint64_t polynomial_size,
+                                                   uint64_t log2_base,
+                                                   uint64_t level,
+                                                   uint32_t ciphertext_modulus_log,
+                                                   uint32_t fft_precision,
+                                                   double variance_ggsw);
+
+double concrete_cpu_variance_fast_keyswitch(uint64_t input_glwe_dimension,
+                                            uint64_t input_polynomial_size,
+                                            uint64_t output_polynomial_size,
+                                            uint64_t log2_base,
+                                            uint64_t level,
+                                            uint32_t ciphertext_modulus_log,
+                                            uint32_t fft_precision,
+                                            double variance_ksk);
+
 double concrete_cpu_variance_keyswitch(uint64_t input_lwe_dimension,
                                        uint64_t log2_base,
                                        uint64_t level,
                                        uint32_t ciphertext_modulus_log,
                                        double variance_ksk);
 
+double concrete_cpu_variance_multi_bit_blind_rotate(uint64_t in_lwe_dimension,
+                                                    uint64_t out_glwe_dimension,
+                                                    uint64_t out_polynomial_size,
+                                                    uint64_t log2_base,
+                                                    uint64_t level,
+                                                    uint32_t ciphertext_modulus_log,
+                                                    uint32_t fft_precision,
+                                                    double variance_bsk,
+                                                    uint32_t grouping_factor,
+                                                    bool jit_fft);
+
+double concrete_cpu_variance_multi_bit_external_product_glwe(uint64_t glwe_dimension,
+                                                             uint64_t polynomial_size,
+                                                             uint64_t log2_base,
+                                                             uint64_t level,
+                                                             uint32_t ciphertext_modulus_log,
+                                                             uint32_t fft_precision,
+                                                             double variance_ggsw,
+                                                             uint32_t grouping_factor,
+                                                             bool jit_fft);
+
+double concrete_cpu_variance_to_modular_variance(double variance, uint32_t ciphertext_modulus_log);
+
+double concrete_cpu_variance_to_std_dev(double variance);
+
 #ifdef __cplusplus
 } // extern "C"
 #endif // __cplusplus
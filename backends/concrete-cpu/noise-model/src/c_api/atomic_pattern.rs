@@ -0,0 +1,29 @@
+use crate::gaussian_noise::noise::atomic_pattern::breakdown_atomic_pattern;
+
+/// Variance at the blind rotate input of an atomic pattern with binary keys.
+///
+/// That is the variance of a dot product of variance `input_variance` after the keyswitch and the
+/// modulus switching, the one the bootstrap error probability depends on.
+#[no_mangle]
+pub extern "C" fn concrete_cpu_variance_atomic_pattern_output(
+    input_variance: f64,
+    input_lwe_dimension: u64,
+    ks_log2_base: u64,
+    ks_level: u64,
+    variance_ksk: f64,
+    internal_ks_output_lwe_dimension: u64,
+    glwe_log2_polynomial_size: u64,
+    ciphertext_modulus_log: u32,
+) -> f64 {
+    breakdown_atomic_pattern(
+        input_variance,
+        input_lwe_dimension,
+        ks_log2_base,
+        ks_level,
+        variance_ksk,
+        internal_ks_output_lwe_dimension,
+        glwe_log2_polynomial_size,
+        ciphertext_modulus_log,
+    )
+    .total()
+}
@@ -0,0 +1,22 @@
+use crate::gaussian_noise::noise::cmux::variance_cmux;
+
+#[no_mangle]
+pub extern "C" fn concrete_cpu_variance_cmux(
+    glwe_dimension: u64,
+    polynomial_size: u64,
+    log2_base: u64,
+    level: u64,
+    ciphertext_modulus_log: u32,
+    fft_precision: u32,
+    variance_ggsw: f64,
+) -> f64 {
+    variance_cmux(
+        glwe_dimension,
+        polynomial_size,
+        log2_base,
+        level,
+        ciphertext_modulus_log,
+        fft_precision,
+        variance_ggsw,
+    )
+}
@@ -0,0 +1,24 @@
+use crate::gaussian_noise::conversion::{
+    modular_variance_to_variance, variance_to_modular_variance, variance_to_std_dev,
+};
+
+#[no_mangle]
+pub extern "C" fn concrete_cpu_modular_variance_to_variance(
+    modular_variance: f64,
+    ciphertext_modulus_log: u32,
+) -> f64 {
+    modular_variance_to_variance(modular_variance, ciphertext_modulus_log)
+}
+
+#[no_mangle]
+pub extern "C" fn concrete_cpu_variance_to_modular_variance(
+    variance: f64,
+    ciphertext_modulus_log: u32,
+) -> f64 {
+    variance_to_modular_variance(variance, ciphertext_modulus_log)
+}
+
+#[no_mangle]
+pub extern "C" fn concrete_cpu_variance_to_std_dev(variance: f64) -> f64 {
+    variance_to_std_dev(variance)
+}
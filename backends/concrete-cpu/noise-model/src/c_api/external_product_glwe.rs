@@ -0,0 +1,22 @@
+use crate::gaussian_noise::noise::external_product_glwe::variance_external_product_glwe;
+
+#[no_mangle]
+pub extern "C" fn concrete_cpu_variance_external_product_glwe(
+    glwe_dimension: u64,
+    polynomial_size: u64,
+    log2_base: u64,
+    level: u64,
+    ciphertext_modulus_log: u32,
+    fft_precision: u32,
+    variance_ggsw: f64,
+) -> f64 {
+    variance_external_product_glwe(
+        glwe_dimension,
+        polynomial_size,
+        log2_base,
+        level,
+        ciphertext_modulus_log,
+        fft_precision,
+        variance_ggsw,
+    )
+}
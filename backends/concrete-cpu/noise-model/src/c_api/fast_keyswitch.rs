@@ -0,0 +1,24 @@
+use crate::gaussian_noise::noise::fast_keyswitch::variance_fast_keyswitch;
+
+#[no_mangle]
+pub extern "C" fn concrete_cpu_variance_fast_keyswitch(
+    input_glwe_dimension: u64,
+    input_polynomial_size: u64,
+    output_polynomial_size: u64,
+    log2_base: u64,
+    level: u64,
+    ciphertext_modulus_log: u32,
+    fft_precision: u32,
+    variance_ksk: f64,
+) -> f64 {
+    variance_fast_keyswitch(
+        input_glwe_dimension,
+        input_polynomial_size,
+        output_polynomial_size,
+        log2_base,
+        level,
+        ciphertext_modulus_log,
+        fft_precision,
+        variance_ksk,
+    )
+}
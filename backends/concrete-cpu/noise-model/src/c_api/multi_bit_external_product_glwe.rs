@@ -0,0 +1,26 @@
+use crate::gaussian_noise::noise::multi_bit_external_product_glwe::variance_multi_bit_external_product_glwe;
+
+#[no_mangle]
+pub extern "C" fn concrete_cpu_variance_multi_bit_external_product_glwe(
+    glwe_dimension: u64,
+    polynomial_size: u64,
+    log2_base: u64,
+    level: u64,
+    ciphertext_modulus_log: u32,
+    fft_precision: u32,
+    variance_ggsw: f64,
+    grouping_factor: u32,
+    jit_fft: bool,
+) -> f64 {
+    variance_multi_bit_external_product_glwe(
+        glwe_dimension,
+        polynomial_size,
+        log2_base,
+        level,
+        ciphertext_modulus_log,
+        fft_precision,
+        variance_ggsw,
+        grouping_factor,
+        jit_fft,
+    )
+}
@@ -0,0 +1,22 @@
+use crate::gaussian_noise::noise::private_packing_keyswitch::estimate_packing_private_keyswitch;
+
+#[no_mangle]
+pub extern "C" fn concrete_cpu_estimate_packing_private_keyswitch(
+    var_glwe: f64,
+    var_ggsw: f64,
+    log2_base: u64,
+    level: u64,
+    output_glwe_dimension: u64,
+    output_polynomial_size: u64,
+    ciphertext_modulus_log: u32,
+) -> f64 {
+    estimate_packing_private_keyswitch(
+        var_glwe,
+        var_ggsw,
+        log2_base,
+        level,
+        output_glwe_dimension,
+        output_polynomial_size,
+        ciphertext_modulus_log,
+    )
+}
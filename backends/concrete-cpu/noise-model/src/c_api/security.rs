@@ -0,0 +1,49 @@
+use concrete_security_curves::gaussian::curves::SecurityCurves;
+
+/// Minimal variance of an LWE key encryption according to the compiled security curves.
+///
+/// Returns NaN for an unsupported security level or ciphertext modulus. Dimensions below the
+/// curve minimum get a variance covering the whole torus, as in the optimizer.
+#[no_mangle]
+pub extern "C" fn concrete_cpu_minimal_variance_lwe(
+    lwe_dimension: u64,
+    ciphertext_modulus_log: u32,
+    security_level: u64,
+) -> f64 {
+    concrete_cpu_minimal_variance_glwe(lwe_dimension, 1, ciphertext_modulus_log, security_level)
+}
+
+/// Minimal variance of a GLWE key encryption, see `concrete_cpu_minimal_variance_lwe`.
+#[no_mangle]
+pub extern "C" fn concrete_cpu_minimal_variance_glwe(
+    glwe_dimension: u64,
+    polynomial_size: u64,
+    ciphertext_modulus_log: u32,
+    security_level: u64,
+) -> f64 {
+    let curves = SecurityCurves::compiled();
+    if curves
+        .check(security_level, ciphertext_modulus_log)
+        .is_err()
+    {
+        return f64::NAN;
+    }
+    curves.minimal_variance_glwe(
+        glwe_dimension,
+        polynomial_size,
+        ciphertext_modulus_log,
+        security_level,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unsupported_queries_are_nan() {
+        assert!(concrete_cpu_minimal_variance_lwe(1024, 64, 128).is_finite());
+        assert!(concrete_cpu_minimal_variance_lwe(1024, 64, 100).is_nan());
+        assert!(concrete_cpu_minimal_variance_glwe(1, 1024, 256, 128).is_nan());
+    }
+}
@@ -1,2 +1,5 @@
+pub mod breakdown;
 pub mod conversion;
+pub mod error_probability;
 pub mod noise;
+pub mod worst_case;
@@ -0,0 +1,289 @@
+//! Variances split into named contributions, to tell which term dominates a noise budget.
+//!
+//! Every primitive of [`super::noise`] has a `breakdown_*` counterpart to its variance function,
+//! the variance being the [`NoiseBreakdown::total`] of the breakdown. Breakdowns compose with
+//! [`NoiseBreakdown::then`] along an atomic pattern. Each term also tells its [`NoiseShape`], for
+//! the error probabilities of [`super::error_probability`].
+
+use std::fmt;
+
+/// What a noise term comes from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum NoiseSource {
+    /// Noise already present in the input ciphertexts
+    Input,
+    /// Encryption noise of the key material (ksk, bsk, private functional ksk)
+    KeyNoise,
+    /// Decomposition rounding errors, multiplied by the key coefficients
+    DecompositionRounding,
+    /// Floating point error of the FFT polynomial products
+    Fft,
+    /// Rounding of the mask and body to the 2N modulus
+    ModulusSwitching,
+}
+
+impl NoiseSource {
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Input => "input",
+            Self::KeyNoise => "key noise",
+            Self::DecompositionRounding => "decomposition rounding",
+            Self::Fft => "fft",
+            Self::ModulusSwitching => "modulus switching",
+        }
+    }
+}
+
+impl fmt::Display for NoiseSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Distribution of a noise term, given its variance.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum NoiseShape {
+    /// Key material encryption noise, or a sum of enough independent errors to be one.
+    #[default]
+    Gaussian,
+    /// Sum of `count` independent products of a uniform rounding error and a key coefficient,
+    /// the coefficient being 0 or a fixed value of random sign, non zero with probability
+    /// `density`. A single uniform rounding has a `count` and a `density` of 1.
+    SparseUniform { count: u64, density: f64 },
+}
+
+/// A variance added by one source during one primitive.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NoiseTerm {
+    pub primitive: &'static str,
+    pub source: NoiseSource,
+    pub variance: f64,
+    pub shape: NoiseShape,
+}
+
+/// An ordered sum of noise terms.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NoiseBreakdown {
+    terms: Vec<NoiseTerm>,
+}
+
+impl NoiseBreakdown {
+    pub fn term(primitive: &'static str, source: NoiseSource, variance: f64) -> Self {
+        Self::shaped_term(primitive, source, variance, NoiseShape::Gaussian)
+    }
+
+    pub fn shaped_term(
+        primitive: &'static str,
+        source: NoiseSource,
+        variance: f64,
+        shape: NoiseShape,
+    ) -> Self {
+        Self {
+            terms: vec![NoiseTerm {
+                primitive,
+                source,
+                variance,
+                shape,
+            }],
+        }
+    }
+
+    /// Noise of the ciphertexts entering a sequence of primitives.
+    pub fn input(variance: f64) -> Self {
+        Self::term("input", NoiseSource::Input, variance)
+    }
+
+    /// Adds the terms of `next`, applied after `self`.
+    #[must_use]
+    pub fn then(mut self, next: Self) -> Self {
+        self.terms.extend(next.terms);
+        self
+    }
+
+    /// Every term multiplied by `factor`, e.g. for a primitive repeated `factor` times.
+    ///
+    /// Repeated gaussian terms stay gaussian, a sparse uniform term sums `factor` times more
+    /// independent errors, so `factor` must then be a whole number.
+    #[must_use]
+    pub fn scaled(mut self, factor: f64) -> Self {
+        for term in &mut self.terms {
+            term.variance *= factor;
+            if let NoiseShape::SparseUniform { count, .. } = &mut term.shape {
+                let repetitions = factor.round();
+                debug_assert!(
+                    repetitions >= 0.0 && (factor - repetitions).abs() < f64::EPSILON,
+                    "a sparse uniform term is repeated a whole number of times"
+                );
+                #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
+                {
+                    *count *= repetitions as u64;
+                }
+            }
+        }
+        self
+    }
+
+    /// The same terms attributed to `primitive`, for a primitive built from another one.
+    #[must_use]
+    pub fn renamed(mut self, primitive: &'static str) -> Self {
+        for term in &mut self.terms {
+            term.primitive = primitive;
+        }
+        self
+    }
+
+    pub fn terms(&self) -> &[NoiseTerm] {
+        &self.terms
+    }
+
+    /// Sum of the terms, in order.
+    pub fn total(&self) -> f64 {
+        self.terms
+            .iter()
+            .fold(0.0, |total, term| total + term.variance)
+    }
+
+    /// Total variance per source, in order of first appearance.
+    pub fn by_source(&self) -> Vec<(NoiseSource, f64)> {
+        let mut sums: Vec<(NoiseSource, f64)> = vec![];
+        for term in &self.terms {
+            match sums.iter_mut().find(|(source, _)| *source == term.source) {
+                Some((_, variance)) => *variance += term.variance,
+                None => sums.push((term.source, term.variance)),
+            }
+        }
+        sums
+    }
+
+    /// The largest term.
+    pub fn dominant(&self) -> Option<&NoiseTerm> {
+        self.terms
+            .iter()
+            .max_by(|a, b| a.variance.total_cmp(&b.variance))
+    }
+}
+
+/// One line per term with its log2 standard deviation and share of the total, then the total.
+impl fmt::Display for NoiseBreakdown {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let total = self.total();
+        let primitive_width = self
+            .terms
+            .iter()
+            .map(|term| term.primitive.len())
+            .max()
+            .unwrap_or(0)
+            .max("total".len());
+        let source_width = self
+            .terms
+            .iter()
+            .map(|term| term.source.name().len())
+            .max()
+            .unwrap_or(0);
+        for term in &self.terms {
+            writeln!(
+                f,
+                "{:<primitive_width$}  {:<source_width$}  log2(std) {:>7.2}  {:>5.1}%",
+                term.primitive,
+                term.source.name(),
+                0.5 * term.variance.log2(),
+                100.0 * term.variance / total,
+            )?;
+        }
+        write!(
+            f,
+            "{:<primitive_width$}  {:<source_width$}  log2(std) {:>7.2}",
+            "total",
+            "",
+            0.5 * total.log2(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example() -> NoiseBreakdown {
+        NoiseBreakdown::input(1.0)
+            .then(NoiseBreakdown::term(
+                "keyswitch",
+                NoiseSource::KeyNoise,
+                2.0,
+            ))
+            .then(
+                NoiseBreakdown::term("keyswitch", NoiseSource::DecompositionRounding, 0.5)
+                    .then(NoiseBreakdown::term(
+                        "bootstrap",
+                        NoiseSource::KeyNoise,
+                        0.25,
+                    ))
+                    .scaled(2.0),
+            )
+    }
+
+    #[test]
+    fn scaling_repeats_sparse_uniform_terms() {
+        let shape = NoiseShape::SparseUniform {
+            count: 2,
+            density: 0.5,
+        };
+        let breakdown = NoiseBreakdown::shaped_term(
+            "modulus switching",
+            NoiseSource::ModulusSwitching,
+            1.0,
+            shape,
+        )
+        .then(NoiseBreakdown::term(
+            "bootstrap",
+            NoiseSource::KeyNoise,
+            1.0,
+        ))
+        .scaled(3.0);
+        let terms = breakdown.terms();
+        approx::assert_relative_eq!(terms[0].variance, 3.0);
+        assert_eq!(
+            terms[0].shape,
+            NoiseShape::SparseUniform {
+                count: 6,
+                density: 0.5
+            }
+        );
+        approx::assert_relative_eq!(terms[1].variance, 3.0);
+        assert_eq!(terms[1].shape, NoiseShape::Gaussian);
+    }
+
+    #[test]
+    fn composition_keeps_every_term() {
+        let breakdown = example();
+        assert_eq!(breakdown.terms().len(), 4);
+        approx::assert_relative_eq!(breakdown.total(), 4.5);
+        assert_eq!(
+            breakdown.by_source(),
+            vec![
+                (NoiseSource::Input, 1.0),
+                (NoiseSource::KeyNoise, 2.5),
+                (NoiseSource::DecompositionRounding, 1.0),
+            ]
+        );
+        let dominant = breakdown.dominant().unwrap();
+        assert_eq!(
+            (dominant.primitive, dominant.source),
+            ("keyswitch", NoiseSource::KeyNoise)
+        );
+        assert!(NoiseBreakdown::default().terms().is_empty());
+    }
+
+    #[test]
+    fn pretty_print_aligns_terms() {
+        let printed = example().to_string();
+        let lines: Vec<&str> = printed.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert_eq!(
+            lines[1],
+            "keyswitch  key noise               log2(std)    0.50   44.4%"
+        );
+        assert!(lines[4].starts_with("total "));
+        assert!(lines[4].ends_with("log2(std)    1.08"));
+    }
+}
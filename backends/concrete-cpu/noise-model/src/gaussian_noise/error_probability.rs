@@ -0,0 +1,444 @@
+//! Error probabilities from the distributions of the noise terms, not only from their variances.
+//!
+//! A gaussian of the total variance misestimates the tail of a noise dominated by a few non
+//! gaussian terms, e.g. the uniform roundings of a modulus switching from a small dimension. The
+//! probability for the sum of the terms of a [`NoiseBreakdown`] to exceed a bound is computed from
+//! its cumulant generating function `K`, the sum of the ones of the terms given their
+//! [`NoiseShape`]. The Laplace transform of the tail is inverted along the vertical line through
+//! the saddle point `K'(theta) = bound`, where the integrand neither oscillates nor cancels, so that
+//! the probability keeps its relative precision far in the tail.
+
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+use super::breakdown::{NoiseBreakdown, NoiseShape, NoiseSource};
+
+/// How the error probability of a noise is computed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ErrorProbabilityModel {
+    /// The noise is a gaussian of its total variance.
+    #[default]
+    Gaussian,
+    /// The noise is the sum of its terms, each with its own distribution.
+    ExactTail,
+}
+
+/// Complex numbers, for the cumulant generating function off the real axis.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Complex {
+    re: f64,
+    im: f64,
+}
+
+impl Complex {
+    const ZERO: Self = Self::new(0.0, 0.0);
+    const ONE: Self = Self::new(1.0, 0.0);
+
+    const fn new(re: f64, im: f64) -> Self {
+        Self { re, im }
+    }
+
+    fn scale(self, factor: f64) -> Self {
+        Self::new(self.re * factor, self.im * factor)
+    }
+
+    fn abs(self) -> f64 {
+        self.re.hypot(self.im)
+    }
+
+    fn exp(self) -> Self {
+        Self::new(self.im.cos(), self.im.sin()).scale(self.re.exp())
+    }
+
+    fn ln(self) -> Self {
+        Self::new(self.abs().ln(), self.im.atan2(self.re))
+    }
+
+    fn sinh(self) -> Self {
+        Self::new(
+            self.re.sinh() * self.im.cos(),
+            self.re.cosh() * self.im.sin(),
+        )
+    }
+}
+
+impl Add for Complex {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self::new(self.re + rhs.re, self.im + rhs.im)
+    }
+}
+
+impl Sub for Complex {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self::new(self.re - rhs.re, self.im - rhs.im)
+    }
+}
+
+impl Neg for Complex {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::new(-self.re, -self.im)
+    }
+}
+
+impl Mul for Complex {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Self::new(
+            self.re * rhs.re - self.im * rhs.im,
+            self.re * rhs.im + self.im * rhs.re,
+        )
+    }
+}
+
+impl Div for Complex {
+    type Output = Self;
+
+    fn div(self, rhs: Self) -> Self {
+        let norm = rhs.re * rhs.re + rhs.im * rhs.im;
+        Self::new(
+            self.re * rhs.re + self.im * rhs.im,
+            self.im * rhs.re - self.re * rhs.im,
+        )
+        .scale(1.0 / norm)
+    }
+}
+
+/// `ln(sinh(w) / w)`, the cumulant generating function of a uniform error in `[-1, 1]`.
+fn ln_sinhc(w: Complex) -> Complex {
+    if w.abs() < 1e-2 {
+        let w2 = w * w;
+        // w^2 / 6 - w^4 / 180 + w^6 / 2835
+        w2 * (Complex::new(1. / 6., 0.0)
+            - w2 * (Complex::new(1. / 180., 0.0) - w2.scale(1. / 2835.)))
+    } else if w.re > 20.0 {
+        // sinh(w) = e^w (1 - e^-2w) / 2, without overflowing
+        w - Complex::new(std::f64::consts::LN_2, 0.0) - w.ln()
+            + (Complex::ONE - w.scale(-2.0).exp()).ln()
+    } else {
+        (w.sinh() / w).ln()
+    }
+}
+
+/// `ln(1 - density + density sinh(w) / w)`, for a uniform error in `[-1, 1]` kept with
+/// probability `density`.
+fn ln_sparse_sinhc(w: Complex, density: f64) -> Complex {
+    let ln_sinhc = ln_sinhc(w);
+    if density >= 1.0 {
+        ln_sinhc
+    } else if ln_sinhc.re > 0.0 {
+        ln_sinhc
+            + Complex::new(density.ln(), 0.0)
+            + (Complex::ONE + (-ln_sinhc).exp().scale((1.0 - density) / density)).ln()
+    } else {
+        (Complex::new(1.0 - density, 0.0) + ln_sinhc.exp().scale(density)).ln()
+    }
+}
+
+/// Largest absolute value of a non gaussian term, its products being at most `half_width`.
+fn sparse_uniform_half_width(variance: f64, count: u64, density: f64) -> f64 {
+    // a product is uniform in [-half_width, half_width] with probability density
+    (3.0 * variance / (count as f64 * density)).sqrt()
+}
+
+fn cumulant(breakdown: &NoiseBreakdown, z: Complex) -> Complex {
+    breakdown
+        .terms()
+        .iter()
+        .filter(|term| term.variance > 0.0)
+        .fold(Complex::ZERO, |sum, term| {
+            sum + match term.shape {
+                NoiseShape::Gaussian => (z * z).scale(term.variance / 2.0),
+                NoiseShape::SparseUniform { count, density } => {
+                    let half_width = sparse_uniform_half_width(term.variance, count, density);
+                    ln_sparse_sinhc(z.scale(half_width), density).scale(count as f64)
+                }
+            }
+        })
+}
+
+/// `K'(theta)` by complex step differentiation, as precise as `K` itself.
+fn cumulant_derivative(breakdown: &NoiseBreakdown, theta: f64) -> f64 {
+    let step = theta * 1e-8;
+    cumulant(breakdown, Complex::new(theta, step)).im / step
+}
+
+fn cumulant_second_derivative(breakdown: &NoiseBreakdown, theta: f64) -> f64 {
+    let step = theta * 1e-4;
+    (cumulant_derivative(breakdown, theta + step) - cumulant_derivative(breakdown, theta - step))
+        / (2.0 * step)
+}
+
+fn gaussian_variance(breakdown: &NoiseBreakdown) -> f64 {
+    breakdown
+        .terms()
+        .iter()
+        .filter(|term| term.shape == NoiseShape::Gaussian)
+        .map(|term| term.variance)
+        .sum()
+}
+
+/// Largest absolute value of the sum of the non gaussian terms.
+fn bounded_max_error(breakdown: &NoiseBreakdown) -> f64 {
+    breakdown
+        .terms()
+        .iter()
+        .filter(|term| term.variance > 0.0)
+        .map(|term| match term.shape {
+            NoiseShape::Gaussian => 0.0,
+            NoiseShape::SparseUniform { count, density } => {
+                count as f64 * sparse_uniform_half_width(term.variance, count, density)
+            }
+        })
+        .sum()
+}
+
+/// Doublings of the upper end of a bisection, enough to cross the exponent range of `f64`.
+const BRACKETING_STEPS: u32 = 2048;
+/// Halvings of a bisection interval, down to the precision of `f64`.
+const BISECTION_STEPS: u32 = 64;
+/// Relative precision of a [`gaussian_noise_budget`], far below the one of the noise formulas.
+const BUDGET_PRECISION: f64 = 1e-6;
+
+/// `theta` such that `K'(theta) = bound`, `K'` increasing from 0, by Newton steps kept within a
+/// bisection bracket.
+fn saddle_point(breakdown: &NoiseBreakdown, bound: f64) -> f64 {
+    // the saddle point of a gaussian of the same variance
+    let mut high = bound / breakdown.total();
+    for _ in 0..BRACKETING_STEPS {
+        if cumulant_derivative(breakdown, high) >= bound {
+            break;
+        }
+        high *= 2.0;
+    }
+    let mut low = 0.0;
+    let mut theta = high;
+    for _ in 0..BISECTION_STEPS {
+        let derivative = cumulant_derivative(breakdown, theta);
+        if (derivative - bound).abs() <= bound * 1e-13 {
+            break;
+        }
+        if derivative < bound {
+            low = theta;
+        } else {
+            high = theta;
+        }
+        let newton = theta - (derivative - bound) / cumulant_second_derivative(breakdown, theta);
+        theta = if low < newton && newton < high {
+            newton
+        } else {
+            0.5 * (low + high)
+        };
+    }
+    theta
+}
+
+/// Steps of the integral along the saddle point line before giving up on its convergence.
+const MAX_INTEGRATION_STEPS: u64 = 1 << 20;
+
+/// Probability for the sum of the terms of `breakdown` to exceed `bound` in absolute value.
+///
+/// `P(X > bound)` is `exp(K(theta) - theta bound) / pi` times the integral over `t > 0` of the
+/// real part of `exp(K(z) - K(theta) - i t bound) / z`, `z = theta + i t`, integrated with the
+/// trapezoidal rule whose error vanishes exponentially for such analytic integrands. The terms are
+/// symmetric, the probability is twice that.
+pub fn error_probability(breakdown: &NoiseBreakdown, bound: f64) -> f64 {
+    if bound <= 0.0 {
+        return 1.0;
+    }
+    if gaussian_variance(breakdown) == 0.0 && bound >= bounded_max_error(breakdown) {
+        return 0.0;
+    }
+    let theta = saddle_point(breakdown, bound);
+    let cumulant_theta = cumulant(breakdown, Complex::new(theta, 0.0)).re;
+    let width = cumulant_second_derivative(breakdown, theta).sqrt().recip();
+    // far enough from the pole of 1 / z and fine enough for the width of the integrand
+    let step = width.min(theta) / 8.0;
+    let integrand_modulus_and_value = |t: f64| {
+        let z = Complex::new(theta, t);
+        let exponent = cumulant(breakdown, z) - Complex::new(cumulant_theta, t * bound);
+        let value = exponent.exp() / z;
+        (exponent.re.exp() / z.abs(), value.re)
+    };
+    let negligible = 1e-17 / theta;
+    let mut integral = 0.5 / theta;
+    for k in 1..MAX_INTEGRATION_STEPS {
+        let t = k as f64 * step;
+        let (modulus, value) = integrand_modulus_and_value(t);
+        integral += value;
+        if modulus < negligible && t > 10.0 * width {
+            break;
+        }
+    }
+    let upper_tail =
+        (cumulant_theta - theta * bound).exp() * integral * step / std::f64::consts::PI;
+    (2.0 * upper_tail).clamp(0.0, 1.0)
+}
+
+/// [`error_probability`] of the terms of `breakdown` plus an independent gaussian noise of variance
+/// `gaussian_variance`.
+pub fn error_probability_with_gaussian(
+    breakdown: &NoiseBreakdown,
+    gaussian_variance: f64,
+    bound: f64,
+) -> f64 {
+    error_probability(
+        &breakdown.clone().then(NoiseBreakdown::term(
+            "gaussian noise",
+            NoiseSource::Input,
+            gaussian_variance,
+        )),
+        bound,
+    )
+}
+
+/// Largest variance of a gaussian noise that can be added to `breakdown` keeping its
+/// [`error_probability`] at `bound` below `p_error`, `None` when `breakdown` alone exceeds it.
+pub fn gaussian_noise_budget(breakdown: &NoiseBreakdown, bound: f64, p_error: f64) -> Option<f64> {
+    let p_error_with = |variance| error_probability_with_gaussian(breakdown, variance, bound);
+    if error_probability(breakdown, bound) > p_error {
+        return None;
+    }
+    // a gaussian alone of standard deviation bound already fails a third of the time
+    let mut high = bound * bound;
+    for _ in 0..BRACKETING_STEPS {
+        if p_error_with(high) > p_error {
+            break;
+        }
+        high *= 2.0;
+    }
+    let mut low = 0.0;
+    for _ in 0..BISECTION_STEPS {
+        if high - low <= high * BUDGET_PRECISION {
+            break;
+        }
+        let middle = 0.5 * (low + high);
+        if p_error_with(middle) <= p_error {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+    Some(low)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gaussian_noise::noise::modulus_switching::{
+        breakdown_modulus_switching_with_kind, ModulusSwitchingKind,
+    };
+    use concrete_security_curves::key_distribution::KeyMoments;
+
+    fn uniforms(count: u64, half_width: f64) -> NoiseBreakdown {
+        NoiseBreakdown::shaped_term(
+            "uniforms",
+            NoiseSource::ModulusSwitching,
+            count as f64 * half_width * half_width / 3.0,
+            NoiseShape::SparseUniform {
+                count,
+                density: 1.0,
+            },
+        )
+    }
+
+    #[test]
+    fn gaussian_tails_match_erfc() {
+        // https://en.wikipedia.org/wiki/Normal_distribution#Standard_deviation_and_coverage
+        let reference = [
+            (1.0, 0.317_310_507_862_914),
+            (2.0, 0.045_500_263_896_358),
+            (4.0, 6.334_248_366_623_984e-5),
+            // far in the tail
+            (10.0, 1.523_970_604_832_105e-23),
+            (30.0, 9.813_427_854_296_374e-198),
+        ];
+        let breakdown = NoiseBreakdown::input(4.0);
+        for (sigma_scale, expected) in reference {
+            approx::assert_relative_eq!(
+                error_probability(&breakdown, 2.0 * sigma_scale),
+                expected,
+                max_relative = 1e-9
+            );
+        }
+    }
+
+    #[test]
+    fn uniform_sums_match_irwin_hall() {
+        // 4 uniforms in [-1, 1] exceed 3 with probability 2 (1 / 2)^4 / 4!
+        let breakdown = uniforms(4, 1.0);
+        approx::assert_relative_eq!(
+            error_probability(&breakdown, 3.0),
+            2.0 / 16.0 / 24.0,
+            max_relative = 1e-9
+        );
+        // and 2 with probability 2 / 4!
+        approx::assert_relative_eq!(
+            error_probability(&breakdown, 2.0),
+            2.0 / 24.0,
+            max_relative = 1e-9
+        );
+        assert!(error_probability(&breakdown, 4.0).abs() < f64::MIN_POSITIVE);
+        approx::assert_relative_eq!(error_probability(&breakdown, 0.0), 1.0);
+    }
+
+    #[test]
+    fn uniform_plus_gaussian_matches_the_convolution() {
+        let half_width = 3.0;
+        let breakdown = uniforms(1, half_width).then(NoiseBreakdown::input(1.0));
+        let bound = 8.0;
+        // midpoint rule over the uniform of the gaussian tails
+        let steps = 10_000;
+        let expected = (0..steps)
+            .map(|i| {
+                let u = -half_width + (i as f64 + 0.5) * 2.0 * half_width / steps as f64;
+                error_probability(&NoiseBreakdown::input(1.0), bound - u) / 2.0
+                    + error_probability(&NoiseBreakdown::input(1.0), bound + u) / 2.0
+            })
+            .sum::<f64>()
+            / steps as f64;
+        approx::assert_relative_eq!(
+            error_probability(&breakdown, bound),
+            expected,
+            max_relative = 1e-6
+        );
+    }
+
+    #[test]
+    fn modulus_switching_tails_differ_from_the_gaussian() {
+        let tail_ratio = |kind| {
+            let breakdown =
+                breakdown_modulus_switching_with_kind(64, 10, 64, KeyMoments::BINARY, kind);
+            let bound = 6.0 * breakdown.total().sqrt();
+            error_probability(&breakdown, bound)
+                / error_probability(&NoiseBreakdown::input(breakdown.total()), bound)
+        };
+        // half of the rounding errors are dropped by a binary key, a heavier tail than a gaussian
+        assert!(tail_ratio(ModulusSwitchingKind::Standard) > 1.2);
+        // the centered key keeps them all, uniform errors have a lighter tail
+        assert!(tail_ratio(ModulusSwitchingKind::CenteredMean) < 0.6);
+    }
+
+    #[test]
+    fn budget_reaches_the_target_probability() {
+        let breakdown = breakdown_modulus_switching_with_kind(
+            512,
+            10,
+            64,
+            KeyMoments::BINARY,
+            ModulusSwitchingKind::Standard,
+        );
+        let bound = 2_f64.powi(-5);
+        let p_error = 2_f64.powi(-40);
+        let budget = gaussian_noise_budget(&breakdown, bound, p_error).unwrap();
+        let p_error_at_budget = error_probability_with_gaussian(&breakdown, budget, bound);
+        assert!(p_error_at_budget <= p_error);
+        approx::assert_relative_eq!(p_error_at_budget, p_error, max_relative = 1e-3);
+        assert!(gaussian_noise_budget(&breakdown, bound / 4.0, p_error).is_none());
+    }
+}
@@ -1,9 +1,12 @@
+pub mod atomic_pattern;
 pub mod blind_rotate;
 pub mod cmux;
 pub mod external_product_glwe;
+pub mod fast_keyswitch;
 pub mod keyswitch;
 pub mod keyswitch_one_bit;
 pub mod modulus_switching;
 pub mod multi_bit_blind_rotate;
 pub mod multi_bit_external_product_glwe;
 pub mod private_packing_keyswitch;
+pub mod radix;
@@ -0,0 +1,112 @@
+use concrete_security_curves::key_distribution::KeyMoments;
+
+use super::keyswitch::breakdown_keyswitch_with_key;
+use super::modulus_switching::breakdown_modulus_switching_with_key;
+use crate::gaussian_noise::breakdown::NoiseBreakdown;
+
+/// Noise entering the blind rotate of an atomic pattern, i.e. the noise the bootstrap corrects.
+///
+/// `input_variance` is the variance of the dot product, then come the keyswitch to the internal key
+/// and the modulus switching.
+#[allow(clippy::too_many_arguments)]
+pub fn breakdown_atomic_pattern(
+    input_variance: f64,
+    input_lwe_dimension: u64,
+    ks_log2_base: u64,
+    ks_level: u64,
+    variance_ksk: f64,
+    internal_ks_output_lwe_dimension: u64,
+    glwe_log2_polynomial_size: u64,
+    ciphertext_modulus_log: u32,
+) -> NoiseBreakdown {
+    breakdown_atomic_pattern_with_key(
+        input_variance,
+        input_lwe_dimension,
+        ks_log2_base,
+        ks_level,
+        variance_ksk,
+        internal_ks_output_lwe_dimension,
+        glwe_log2_polynomial_size,
+        ciphertext_modulus_log,
+        KeyMoments::BINARY,
+        KeyMoments::BINARY,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn breakdown_atomic_pattern_with_key(
+    input_variance: f64,
+    input_lwe_dimension: u64,
+    ks_log2_base: u64,
+    ks_level: u64,
+    variance_ksk: f64,
+    internal_ks_output_lwe_dimension: u64,
+    glwe_log2_polynomial_size: u64,
+    ciphertext_modulus_log: u32,
+    input_key: KeyMoments,
+    internal_key: KeyMoments,
+) -> NoiseBreakdown {
+    NoiseBreakdown::input(input_variance)
+        .then(breakdown_keyswitch_with_key(
+            input_lwe_dimension,
+            ks_log2_base,
+            ks_level,
+            ciphertext_modulus_log,
+            variance_ksk,
+            input_key,
+        ))
+        .then(breakdown_modulus_switching_with_key(
+            internal_ks_output_lwe_dimension,
+            glwe_log2_polynomial_size,
+            ciphertext_modulus_log,
+            internal_key,
+        ))
+}
+
+#[cfg(test)]
+mod tests {
+    use concrete_security_curves::gaussian::security::minimal_variance_lwe;
+
+    use super::*;
+    use crate::gaussian_noise::breakdown::NoiseSource;
+    use crate::gaussian_noise::noise::keyswitch::variance_keyswitch;
+    use crate::gaussian_noise::noise::modulus_switching::estimate_modulus_switching_noise_with_binary_key;
+
+    #[test]
+    fn breakdown_sums_the_primitive_variances() {
+        let ciphertext_modulus_log = 64;
+        let variance_ksk = minimal_variance_lwe(800, ciphertext_modulus_log, 128).unwrap();
+        let input_variance = 2_f64.powi(-40);
+
+        let breakdown = breakdown_atomic_pattern(
+            input_variance,
+            2048,
+            4,
+            4,
+            variance_ksk,
+            800,
+            11,
+            ciphertext_modulus_log,
+        );
+
+        let expected = input_variance
+            + variance_keyswitch(2048, 4, 4, ciphertext_modulus_log, variance_ksk)
+            + estimate_modulus_switching_noise_with_binary_key(800, 11, ciphertext_modulus_log);
+        approx::assert_relative_eq!(breakdown.total(), expected, max_relative = 1e-12);
+
+        let sources: Vec<NoiseSource> = breakdown
+            .by_source()
+            .into_iter()
+            .map(|(source, _)| source)
+            .collect();
+        assert_eq!(
+            sources,
+            [
+                NoiseSource::Input,
+                NoiseSource::DecompositionRounding,
+                NoiseSource::KeyNoise,
+                NoiseSource::ModulusSwitching,
+            ]
+        );
+    }
+}
@@ -1,4 +1,7 @@
-use super::cmux::variance_cmux;
+use concrete_security_curves::key_distribution::KeyMoments;
+
+use super::cmux::{breakdown_cmux_with_key, max_error_cmux, variance_cmux_with_key};
+use crate::gaussian_noise::breakdown::NoiseBreakdown;
 
 pub const FFT_SCALING_WEIGHT: f64 = -2.577_224_94;
 
@@ -14,9 +17,34 @@ pub fn variance_blind_rotate(
     ciphertext_modulus_log: u32,
     fft_precision: u32,
     variance_bsk: f64,
+) -> f64 {
+    variance_blind_rotate_with_key(
+        in_lwe_dimension,
+        out_glwe_dimension,
+        out_polynomial_size,
+        log2_base,
+        level,
+        ciphertext_modulus_log,
+        fft_precision,
+        variance_bsk,
+        KeyMoments::BINARY,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn variance_blind_rotate_with_key(
+    in_lwe_dimension: u64,
+    out_glwe_dimension: u64,
+    out_polynomial_size: u64,
+    log2_base: u64,
+    level: u64,
+    ciphertext_modulus_log: u32,
+    fft_precision: u32,
+    variance_bsk: f64,
+    out_glwe_key: KeyMoments,
 ) -> f64 {
     in_lwe_dimension as f64
-        * variance_cmux(
+        * variance_cmux_with_key(
             out_glwe_dimension,
             out_polynomial_size,
             log2_base,
@@ -24,6 +52,59 @@ pub fn variance_blind_rotate(
             ciphertext_modulus_log,
             fft_precision,
             variance_bsk,
+            out_glwe_key,
+        )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn breakdown_blind_rotate_with_key(
+    in_lwe_dimension: u64,
+    out_glwe_dimension: u64,
+    out_polynomial_size: u64,
+    log2_base: u64,
+    level: u64,
+    ciphertext_modulus_log: u32,
+    fft_precision: u32,
+    variance_bsk: f64,
+    out_glwe_key: KeyMoments,
+) -> NoiseBreakdown {
+    breakdown_cmux_with_key(
+        out_glwe_dimension,
+        out_polynomial_size,
+        log2_base,
+        level,
+        ciphertext_modulus_log,
+        fft_precision,
+        variance_bsk,
+        out_glwe_key,
+    )
+    .renamed("blind rotate")
+    .scaled(in_lwe_dimension as f64)
+}
+
+/// Largest error of the blind rotate, the errors of the `in_lwe_dimension` cmuxes adding up.
+#[allow(clippy::too_many_arguments)]
+pub fn max_error_blind_rotate(
+    in_lwe_dimension: u64,
+    out_glwe_dimension: u64,
+    out_polynomial_size: u64,
+    log2_base: u64,
+    level: u64,
+    ciphertext_modulus_log: u32,
+    fft_precision: u32,
+    variance_bsk: f64,
+    out_glwe_key_bound: f64,
+) -> f64 {
+    in_lwe_dimension as f64
+        * max_error_cmux(
+            out_glwe_dimension,
+            out_polynomial_size,
+            log2_base,
+            level,
+            ciphertext_modulus_log,
+            fft_precision,
+            variance_bsk,
+            out_glwe_key_bound,
         )
 }
 
@@ -49,7 +130,8 @@ mod tests {
             polynomial_size,
             ciphertext_modulus_log,
             security,
-        );
+        )
+        .unwrap();
 
         let fft_precision = 53;
 
@@ -86,7 +168,8 @@ mod tests {
             polynomial_size,
             ciphertext_modulus_log,
             security,
-        );
+        )
+        .unwrap();
 
         let fft_precision = 53;
 
@@ -107,4 +190,44 @@ mod tests {
             max_relative = 1e-8
         );
     }
+
+    #[test]
+    fn breakdown_matches_the_variance() {
+        let polynomial_size = 1 << 11;
+        let glwe_dimension = 1;
+        let variance_bsk = minimal_variance_glwe(glwe_dimension, polynomial_size, 64, 128).unwrap();
+
+        let breakdown = breakdown_blind_rotate_with_key(
+            800,
+            glwe_dimension,
+            polynomial_size,
+            23,
+            1,
+            64,
+            53,
+            variance_bsk,
+            KeyMoments::BINARY,
+        );
+        let variance = variance_blind_rotate(
+            800,
+            glwe_dimension,
+            polynomial_size,
+            23,
+            1,
+            64,
+            53,
+            variance_bsk,
+        );
+
+        approx::assert_relative_eq!(breakdown.total(), variance, max_relative = 1e-12);
+        assert!(breakdown
+            .terms()
+            .iter()
+            .all(|term| term.primitive == "blind rotate"));
+        // a single level with a large base is dominated by the fft error
+        assert_eq!(
+            breakdown.dominant().map(|term| term.source),
+            Some(crate::gaussian_noise::breakdown::NoiseSource::Fft)
+        );
+    }
 }
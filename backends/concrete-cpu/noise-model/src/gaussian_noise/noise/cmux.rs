@@ -1,4 +1,10 @@
-use super::external_product_glwe::variance_external_product_glwe;
+use concrete_security_curves::key_distribution::KeyMoments;
+
+use super::external_product_glwe::{
+    breakdown_external_product_glwe_with_key, max_error_external_product_glwe,
+    variance_external_product_glwe_with_key,
+};
+use crate::gaussian_noise::breakdown::NoiseBreakdown;
 
 // only valid in the blind rotate case
 pub fn variance_cmux(
@@ -10,7 +16,77 @@ pub fn variance_cmux(
     fft_precision: u32,
     variance_ggsw: f64,
 ) -> f64 {
-    variance_external_product_glwe(
+    variance_cmux_with_key(
+        glwe_dimension,
+        polynomial_size,
+        log2_base,
+        level,
+        ciphertext_modulus_log,
+        fft_precision,
+        variance_ggsw,
+        KeyMoments::BINARY,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn variance_cmux_with_key(
+    glwe_dimension: u64,
+    polynomial_size: u64,
+    log2_base: u64,
+    level: u64,
+    ciphertext_modulus_log: u32,
+    fft_precision: u32,
+    variance_ggsw: f64,
+    glwe_key: KeyMoments,
+) -> f64 {
+    variance_external_product_glwe_with_key(
+        glwe_dimension,
+        polynomial_size,
+        log2_base,
+        level,
+        ciphertext_modulus_log,
+        fft_precision,
+        variance_ggsw,
+        glwe_key,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn breakdown_cmux_with_key(
+    glwe_dimension: u64,
+    polynomial_size: u64,
+    log2_base: u64,
+    level: u64,
+    ciphertext_modulus_log: u32,
+    fft_precision: u32,
+    variance_ggsw: f64,
+    glwe_key: KeyMoments,
+) -> NoiseBreakdown {
+    breakdown_external_product_glwe_with_key(
+        glwe_dimension,
+        polynomial_size,
+        log2_base,
+        level,
+        ciphertext_modulus_log,
+        fft_precision,
+        variance_ggsw,
+        glwe_key,
+    )
+    .renamed("cmux")
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn max_error_cmux(
+    glwe_dimension: u64,
+    polynomial_size: u64,
+    log2_base: u64,
+    level: u64,
+    ciphertext_modulus_log: u32,
+    fft_precision: u32,
+    variance_ggsw: f64,
+    key_bound: f64,
+) -> f64 {
+    max_error_external_product_glwe(
         glwe_dimension,
         polynomial_size,
         log2_base,
@@ -18,5 +94,6 @@ pub fn variance_cmux(
         ciphertext_modulus_log,
         fft_precision,
         variance_ggsw,
+        key_bound,
     )
 }
@@ -1,4 +1,8 @@
+use concrete_security_curves::key_distribution::KeyMoments;
+
+use crate::gaussian_noise::breakdown::{NoiseBreakdown, NoiseSource};
 use crate::gaussian_noise::conversion::modular_variance_to_variance;
+use crate::gaussian_noise::worst_case::{decomposition_rounding_max_error, gaussian_max_error};
 use crate::utils::square;
 
 pub fn variance_external_product_glwe(
@@ -10,36 +14,90 @@ pub fn variance_external_product_glwe(
     fft_precision: u32,
     variance_ggsw: f64,
 ) -> f64 {
-    theoretical_variance_external_product_glwe(
+    variance_external_product_glwe_with_key(
         glwe_dimension,
         polynomial_size,
         log2_base,
         level,
         ciphertext_modulus_log,
+        fft_precision,
         variance_ggsw,
-    ) + fft_noise_variance_external_product_glwe(
+        KeyMoments::BINARY,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn variance_external_product_glwe_with_key(
+    glwe_dimension: u64,
+    polynomial_size: u64,
+    log2_base: u64,
+    level: u64,
+    ciphertext_modulus_log: u32,
+    fft_precision: u32,
+    variance_ggsw: f64,
+    glwe_key: KeyMoments,
+) -> f64 {
+    breakdown_external_product_glwe_with_key(
         glwe_dimension,
         polynomial_size,
         log2_base,
         level,
         ciphertext_modulus_log,
         fft_precision,
+        variance_ggsw,
+        glwe_key,
     )
+    .total()
 }
 
-fn theoretical_variance_external_product_glwe(
+#[allow(clippy::too_many_arguments)]
+pub fn breakdown_external_product_glwe_with_key(
     glwe_dimension: u64,
     polynomial_size: u64,
     log2_base: u64,
     level: u64,
     ciphertext_modulus_log: u32,
+    fft_precision: u32,
     variance_ggsw: f64,
-) -> f64 {
-    let variance_key_coefficient_binary: f64 =
-        modular_variance_to_variance(1. / 4., ciphertext_modulus_log);
+    glwe_key: KeyMoments,
+) -> NoiseBreakdown {
+    theoretical_breakdown_external_product_glwe(
+        glwe_dimension,
+        polynomial_size,
+        log2_base,
+        level,
+        ciphertext_modulus_log,
+        variance_ggsw,
+        glwe_key,
+    )
+    .then(NoiseBreakdown::term(
+        "external product",
+        NoiseSource::Fft,
+        fft_noise_variance_external_product_glwe(
+            glwe_dimension,
+            polynomial_size,
+            log2_base,
+            level,
+            ciphertext_modulus_log,
+            fft_precision,
+        ),
+    ))
+}
+
+fn theoretical_breakdown_external_product_glwe(
+    glwe_dimension: u64,
+    polynomial_size: u64,
+    log2_base: u64,
+    level: u64,
+    ciphertext_modulus_log: u32,
+    variance_ggsw: f64,
+    glwe_key: KeyMoments,
+) -> NoiseBreakdown {
+    let variance_key_coefficient: f64 =
+        modular_variance_to_variance(glwe_key.variance, ciphertext_modulus_log);
 
-    let square_expectation_key_coefficient_binary: f64 =
-        modular_variance_to_variance(square(1. / 2.), ciphertext_modulus_log);
+    let square_expectation_key_coefficient: f64 =
+        modular_variance_to_variance(square(glwe_key.mean), ciphertext_modulus_log);
 
     let k = glwe_dimension as f64;
     let b = 2_f64.powi(log2_base as i32);
@@ -51,18 +109,56 @@ fn theoretical_variance_external_product_glwe(
     let res_1 = l * (k + 1.) * big_n * (square(b) + 2.) / 12. * variance_ggsw;
     let res_2 = (q_square - b2l) / (24. * b2l)
         * (modular_variance_to_variance(1., ciphertext_modulus_log)
-            + k * big_n
-                * (variance_key_coefficient_binary + square_expectation_key_coefficient_binary))
-        + k * big_n / 8. * variance_key_coefficient_binary
-        + 1. / 16. * square(1. - k * big_n) * square_expectation_key_coefficient_binary;
+            + k * big_n * (variance_key_coefficient + square_expectation_key_coefficient))
+        + k * big_n / 8. * variance_key_coefficient
+        + 1. / 16. * square(1. - k * big_n) * square_expectation_key_coefficient;
+
+    NoiseBreakdown::term("external product", NoiseSource::KeyNoise, res_1).then(
+        NoiseBreakdown::term(
+            "external product",
+            NoiseSource::DecompositionRounding,
+            res_2,
+        ),
+    )
+}
+
+/// Largest error added by the external product with a ggsw of a bit.
+///
+/// It sums `level` balanced digits of the `(k + 1) N` decomposed coefficients times the ggsw error,
+/// the rounding of the body and of the `k N` mask coefficients times a key coefficient at
+/// `key_bound`, and the fft error tail.
+#[allow(clippy::too_many_arguments)]
+pub fn max_error_external_product_glwe(
+    glwe_dimension: u64,
+    polynomial_size: u64,
+    log2_base: u64,
+    level: u64,
+    ciphertext_modulus_log: u32,
+    fft_precision: u32,
+    variance_ggsw: f64,
+    key_bound: f64,
+) -> f64 {
+    let k = glwe_dimension as f64;
+    let big_n = polynomial_size as f64;
+    let half_base = 2_f64.powi(log2_base as i32 - 1);
 
-    res_1 + res_2
+    let key_noise = (k + 1.) * level as f64 * big_n * half_base * gaussian_max_error(variance_ggsw);
+    let rounding = (1. + k * big_n * key_bound)
+        * decomposition_rounding_max_error(log2_base, level, ciphertext_modulus_log);
+    let fft = gaussian_max_error(fft_noise_variance_external_product_glwe(
+        glwe_dimension,
+        polynomial_size,
+        log2_base,
+        level,
+        ciphertext_modulus_log,
+        fft_precision,
+    ));
+    key_noise + rounding + fft
 }
 
 const FFT_SCALING_WEIGHT: f64 = -2.577_224_94;
 
 /// Additional noise generated by fft computation
-
 fn fft_noise_variance_external_product_glwe(
     glwe_dimension: u64,
     polynomial_size: u64,
@@ -70,14 +166,36 @@ fn fft_noise_variance_external_product_glwe(
     level: u64,
     ciphertext_modulus_log: u32,
     fft_precision: u32,
+) -> f64 {
+    let k = glwe_dimension;
+    assert!(k > 0, "k = {k}");
+    assert!(k < 7, "k = {k}");
+
+    fft_noise_variance_glwe_products(
+        glwe_dimension,
+        polynomial_size,
+        log2_base,
+        level,
+        ciphertext_modulus_log,
+        fft_precision,
+    )
+}
+
+/// Fft error of multiplying `level` decomposed polynomials by `glwe_dimension + 1` polynomials and
+/// summing, without checking that the weight was fitted for `glwe_dimension`.
+pub(crate) fn fft_noise_variance_glwe_products(
+    glwe_dimension: u64,
+    polynomial_size: u64,
+    log2_base: u64,
+    level: u64,
+    ciphertext_modulus_log: u32,
+    fft_precision: u32,
 ) -> f64 {
     // https://github.com/zama-ai/concrete-optimizer/blob/prototype/python/optimizer/noise_formulas/bootstrap.py#L25
     let b = 2_f64.powi(log2_base as i32);
     let l = level as f64;
     let big_n = polynomial_size as f64;
     let k = glwe_dimension;
-    assert!(k > 0, "k = {k}");
-    assert!(k < 7, "k = {k}");
 
     let lost_bits = ciphertext_modulus_log as i32 - fft_precision as i32;
 
@@ -0,0 +1,251 @@
+//! GLWE to GLWE keyswitch towards a key with smaller polynomials.
+//!
+//! The `k1 * N1` coefficients of the input key are cut into as many polynomials of the output size
+//! `N0`, each switched through an external product like decomposition with the FFT.
+
+use concrete_security_curves::key_distribution::KeyMoments;
+
+use super::external_product_glwe::fft_noise_variance_glwe_products;
+use super::keyswitch::{
+    breakdown_keyswitch_with_key, max_error_keyswitch, variance_keyswitch_with_key,
+};
+use crate::gaussian_noise::breakdown::{NoiseBreakdown, NoiseSource};
+use crate::gaussian_noise::worst_case::gaussian_max_error;
+
+/// Number of output size polynomials covering the input key.
+pub const fn fast_keyswitch_input_polynomial_count(
+    input_glwe_dimension: u64,
+    input_polynomial_size: u64,
+    output_polynomial_size: u64,
+) -> u64 {
+    (input_glwe_dimension * input_polynomial_size).div_ceil(output_polynomial_size)
+}
+
+/// Additional noise generated by the fft products of the fast keyswitch.
+pub fn fft_noise_variance_fast_keyswitch(
+    input_glwe_dimension: u64,
+    input_polynomial_size: u64,
+    output_polynomial_size: u64,
+    log2_base: u64,
+    level: u64,
+    ciphertext_modulus_log: u32,
+    fft_precision: u32,
+) -> f64 {
+    fft_noise_variance_glwe_products(
+        fast_keyswitch_input_polynomial_count(
+            input_glwe_dimension,
+            input_polynomial_size,
+            output_polynomial_size,
+        ),
+        output_polynomial_size,
+        log2_base,
+        level,
+        ciphertext_modulus_log,
+        fft_precision,
+    )
+}
+
+/// Additional noise generated by the fast keyswitch.
+#[allow(clippy::too_many_arguments)]
+pub fn variance_fast_keyswitch(
+    input_glwe_dimension: u64,
+    input_polynomial_size: u64,
+    output_polynomial_size: u64,
+    log2_base: u64,
+    level: u64,
+    ciphertext_modulus_log: u32,
+    fft_precision: u32,
+    variance_ksk: f64,
+) -> f64 {
+    variance_fast_keyswitch_with_key(
+        input_glwe_dimension,
+        input_polynomial_size,
+        output_polynomial_size,
+        log2_base,
+        level,
+        ciphertext_modulus_log,
+        fft_precision,
+        variance_ksk,
+        KeyMoments::BINARY,
+    )
+}
+
+/// The decomposition noise is the one of a lwe keyswitch from the sample extracted input key.
+#[allow(clippy::too_many_arguments)]
+pub fn variance_fast_keyswitch_with_key(
+    input_glwe_dimension: u64,
+    input_polynomial_size: u64,
+    output_polynomial_size: u64,
+    log2_base: u64,
+    level: u64,
+    ciphertext_modulus_log: u32,
+    fft_precision: u32,
+    variance_ksk: f64,
+    input_key: KeyMoments,
+) -> f64 {
+    variance_keyswitch_with_key(
+        input_glwe_dimension * input_polynomial_size,
+        log2_base,
+        level,
+        ciphertext_modulus_log,
+        variance_ksk,
+        input_key,
+    ) + fft_noise_variance_fast_keyswitch(
+        input_glwe_dimension,
+        input_polynomial_size,
+        output_polynomial_size,
+        log2_base,
+        level,
+        ciphertext_modulus_log,
+        fft_precision,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn breakdown_fast_keyswitch_with_key(
+    input_glwe_dimension: u64,
+    input_polynomial_size: u64,
+    output_polynomial_size: u64,
+    log2_base: u64,
+    level: u64,
+    ciphertext_modulus_log: u32,
+    fft_precision: u32,
+    variance_ksk: f64,
+    input_key: KeyMoments,
+) -> NoiseBreakdown {
+    breakdown_keyswitch_with_key(
+        input_glwe_dimension * input_polynomial_size,
+        log2_base,
+        level,
+        ciphertext_modulus_log,
+        variance_ksk,
+        input_key,
+    )
+    .renamed("fast keyswitch")
+    .then(NoiseBreakdown::term(
+        "fast keyswitch",
+        NoiseSource::Fft,
+        fft_noise_variance_fast_keyswitch(
+            input_glwe_dimension,
+            input_polynomial_size,
+            output_polynomial_size,
+            log2_base,
+            level,
+            ciphertext_modulus_log,
+            fft_precision,
+        ),
+    ))
+}
+
+/// Largest error of the fast keyswitch, the one of the lwe keyswitch plus the fft error tail.
+#[allow(clippy::too_many_arguments)]
+pub fn max_error_fast_keyswitch(
+    input_glwe_dimension: u64,
+    input_polynomial_size: u64,
+    output_polynomial_size: u64,
+    log2_base: u64,
+    level: u64,
+    ciphertext_modulus_log: u32,
+    fft_precision: u32,
+    variance_ksk: f64,
+    input_key_bound: f64,
+) -> f64 {
+    max_error_keyswitch(
+        input_glwe_dimension * input_polynomial_size,
+        log2_base,
+        level,
+        ciphertext_modulus_log,
+        variance_ksk,
+        input_key_bound,
+    ) + gaussian_max_error(fft_noise_variance_fast_keyswitch(
+        input_glwe_dimension,
+        input_polynomial_size,
+        output_polynomial_size,
+        log2_base,
+        level,
+        ciphertext_modulus_log,
+        fft_precision,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use concrete_security_curves::gaussian::security::minimal_variance_glwe;
+
+    use crate::gaussian_noise::conversion::variance_to_modular_variance;
+
+    use super::*;
+
+    #[test]
+    fn golden_optimizer_fast_keyswitch_1() {
+        // reference values from the optimizer implementation
+        let golden_modular_variance = 5.418_187_662_522_443e31;
+        let ciphertext_modulus_log = 64;
+        let variance_ksk = minimal_variance_glwe(1, 1 << 10, ciphertext_modulus_log, 128).unwrap();
+
+        let actual = variance_fast_keyswitch(
+            2,
+            1 << 11,
+            1 << 10,
+            3,
+            5,
+            ciphertext_modulus_log,
+            53,
+            variance_ksk,
+        );
+
+        approx::assert_relative_eq!(
+            variance_to_modular_variance(actual, ciphertext_modulus_log),
+            golden_modular_variance,
+            max_relative = 1e-8
+        );
+    }
+
+    #[test]
+    fn golden_optimizer_fast_keyswitch_2() {
+        // reference values from the optimizer implementation
+        let golden_modular_variance = 1.382_010_775_995_633_1e31;
+        let ciphertext_modulus_log = 64;
+        let variance_ksk = minimal_variance_glwe(2, 1 << 9, ciphertext_modulus_log, 128).unwrap();
+
+        let actual = variance_fast_keyswitch(
+            1,
+            1 << 12,
+            1 << 9,
+            4,
+            4,
+            ciphertext_modulus_log,
+            53,
+            variance_ksk,
+        );
+
+        approx::assert_relative_eq!(
+            variance_to_modular_variance(actual, ciphertext_modulus_log),
+            golden_modular_variance,
+            max_relative = 1e-8
+        );
+    }
+
+    #[test]
+    fn breakdown_matches_the_variance() {
+        let variance_ksk = minimal_variance_glwe(1, 1 << 10, 64, 128).unwrap();
+        let variance = variance_fast_keyswitch(2, 1 << 11, 1 << 10, 3, 5, 64, 53, variance_ksk);
+        let breakdown = breakdown_fast_keyswitch_with_key(
+            2,
+            1 << 11,
+            1 << 10,
+            3,
+            5,
+            64,
+            53,
+            variance_ksk,
+            KeyMoments::BINARY,
+        );
+        approx::assert_relative_eq!(breakdown.total(), variance, max_relative = 1e-12);
+        assert_eq!(
+            fast_keyswitch_input_polynomial_count(2, 1 << 11, 1 << 10),
+            4
+        );
+        assert_eq!(fast_keyswitch_input_polynomial_count(3, 1 << 9, 1 << 10), 2);
+    }
+}
@@ -1,4 +1,10 @@
-use super::keyswitch_one_bit::variance_keyswitch_one_bit;
+use concrete_security_curves::key_distribution::KeyMoments;
+
+use super::keyswitch_one_bit::{
+    breakdown_keyswitch_one_bit_with_key, max_error_keyswitch_one_bit,
+    variance_keyswitch_one_bit_with_key,
+};
+use crate::gaussian_noise::breakdown::NoiseBreakdown;
 
 /// Additional noise generated by the keyswitch step.
 pub fn variance_keyswitch(
@@ -7,9 +13,70 @@ pub fn variance_keyswitch(
     level: u64,
     ciphertext_modulus_log: u32,
     variance_ksk: f64,
+) -> f64 {
+    variance_keyswitch_with_key(
+        input_lwe_dimension,
+        log2_base,
+        level,
+        ciphertext_modulus_log,
+        variance_ksk,
+        KeyMoments::BINARY,
+    )
+}
+
+pub fn variance_keyswitch_with_key(
+    input_lwe_dimension: u64,
+    log2_base: u64,
+    level: u64,
+    ciphertext_modulus_log: u32,
+    variance_ksk: f64,
+    input_key: KeyMoments,
+) -> f64 {
+    input_lwe_dimension as f64
+        * variance_keyswitch_one_bit_with_key(
+            log2_base,
+            level,
+            ciphertext_modulus_log,
+            variance_ksk,
+            input_key,
+        )
+}
+
+pub fn breakdown_keyswitch_with_key(
+    input_lwe_dimension: u64,
+    log2_base: u64,
+    level: u64,
+    ciphertext_modulus_log: u32,
+    variance_ksk: f64,
+    input_key: KeyMoments,
+) -> NoiseBreakdown {
+    breakdown_keyswitch_one_bit_with_key(
+        log2_base,
+        level,
+        ciphertext_modulus_log,
+        variance_ksk,
+        input_key,
+    )
+    .scaled(input_lwe_dimension as f64)
+}
+
+/// Largest error added by the keyswitch, every input coefficient at `key_bound`.
+pub fn max_error_keyswitch(
+    input_lwe_dimension: u64,
+    log2_base: u64,
+    level: u64,
+    ciphertext_modulus_log: u32,
+    variance_ksk: f64,
+    key_bound: f64,
 ) -> f64 {
     input_lwe_dimension as f64
-        * variance_keyswitch_one_bit(log2_base, level, ciphertext_modulus_log, variance_ksk)
+        * max_error_keyswitch_one_bit(
+            log2_base,
+            level,
+            ciphertext_modulus_log,
+            variance_ksk,
+            key_bound,
+        )
 }
 
 #[cfg(test)]
@@ -37,7 +104,8 @@ mod tests {
                 internal_ks_output_lwe_dimension,
                 ciphertext_modulus_log,
                 security,
-            ),
+            )
+            .unwrap(),
         );
 
         approx::assert_relative_eq!(
@@ -65,7 +133,8 @@ mod tests {
                 internal_ks_output_lwe_dimension,
                 ciphertext_modulus_log,
                 security,
-            ),
+            )
+            .unwrap(),
         );
 
         approx::assert_relative_eq!(
@@ -1,3 +1,7 @@
+use concrete_security_curves::key_distribution::KeyMoments;
+
+use crate::gaussian_noise::breakdown::{NoiseBreakdown, NoiseSource};
+use crate::gaussian_noise::worst_case::{decomposition_rounding_max_error, gaussian_max_error};
 use crate::{gaussian_noise::conversion::modular_variance_to_variance, utils::square};
 
 /// Additional noise generated by the bit multiplication
@@ -7,11 +11,45 @@ pub fn variance_keyswitch_one_bit(
     ciphertext_modulus_log: u32,
     variance_ksk: f64,
 ) -> f64 {
-    let variance_key_coefficient_binary: f64 =
-        modular_variance_to_variance(1. / 4., ciphertext_modulus_log);
+    variance_keyswitch_one_bit_with_key(
+        log2_base,
+        level,
+        ciphertext_modulus_log,
+        variance_ksk,
+        KeyMoments::BINARY,
+    )
+}
 
-    let square_expectation_key_coefficient_binary: f64 =
-        modular_variance_to_variance(square(1. / 2.), ciphertext_modulus_log);
+/// Additional noise generated by the multiplication of one coefficient of `input_key`
+pub fn variance_keyswitch_one_bit_with_key(
+    log2_base: u64,
+    level: u64,
+    ciphertext_modulus_log: u32,
+    variance_ksk: f64,
+    input_key: KeyMoments,
+) -> f64 {
+    breakdown_keyswitch_one_bit_with_key(
+        log2_base,
+        level,
+        ciphertext_modulus_log,
+        variance_ksk,
+        input_key,
+    )
+    .total()
+}
+
+pub fn breakdown_keyswitch_one_bit_with_key(
+    log2_base: u64,
+    level: u64,
+    ciphertext_modulus_log: u32,
+    variance_ksk: f64,
+    input_key: KeyMoments,
+) -> NoiseBreakdown {
+    let variance_key_coefficient: f64 =
+        modular_variance_to_variance(input_key.variance, ciphertext_modulus_log);
+
+    let square_expectation_key_coefficient: f64 =
+        modular_variance_to_variance(square(input_key.mean), ciphertext_modulus_log);
 
     let base = 2_f64.powi(log2_base as i32);
     let b2l = 2_f64.powi((log2_base * 2 * level) as i32);
@@ -19,13 +57,36 @@ pub fn variance_keyswitch_one_bit(
 
     // res 2
     let res_2 = (q_square / (12. * b2l) - 1. / 12.)
-        * (variance_key_coefficient_binary + square_expectation_key_coefficient_binary);
+        * (variance_key_coefficient + square_expectation_key_coefficient);
 
     // res 3
-    let res_3 = 1. / 4. * variance_key_coefficient_binary;
+    let res_3 = 1. / 4. * variance_key_coefficient;
 
     // res 4
     let res_4 = (level as f64) * variance_ksk * (square(base) + 2.) / 12.;
 
-    res_2 + res_3 + res_4
+    NoiseBreakdown::term(
+        "keyswitch",
+        NoiseSource::DecompositionRounding,
+        res_2 + res_3,
+    )
+    .then(NoiseBreakdown::term(
+        "keyswitch",
+        NoiseSource::KeyNoise,
+        res_4,
+    ))
+}
+
+/// Largest error added by the multiplication of one coefficient bounded by `key_bound`: the
+/// decomposition rounding times the coefficient, and `level` balanced digits times the ksk error.
+pub fn max_error_keyswitch_one_bit(
+    log2_base: u64,
+    level: u64,
+    ciphertext_modulus_log: u32,
+    variance_ksk: f64,
+    key_bound: f64,
+) -> f64 {
+    let half_base = 2_f64.powi(log2_base as i32 - 1);
+    key_bound * decomposition_rounding_max_error(log2_base, level, ciphertext_modulus_log)
+        + level as f64 * half_base * gaussian_max_error(variance_ksk)
 }
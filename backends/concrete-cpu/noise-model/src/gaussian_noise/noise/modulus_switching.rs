@@ -1,15 +1,282 @@
+//! Rounding of the mask and body of the blind rotate input to the `2N` modulus.
+//!
+//! Rounding every mask coefficient leaves an error `sum_i d_i s_i`. Its mean given the public
+//! rounding errors `d_i` is `E[s] sum_i d_i`, a drift the [`ModulusSwitchingKind`] variants other
+//! than the standard one remove from the body, at the cost of a pass over the mask.
+
+use concrete_security_curves::key_distribution::KeyMoments;
+use serde::{Deserialize, Serialize};
+
+use crate::gaussian_noise::breakdown::{NoiseBreakdown, NoiseShape, NoiseSource};
 use crate::{gaussian_noise::conversion::modular_variance_to_variance, utils::square};
 
+/// How the body of the ciphertext is corrected for the rounding of the mask.
+///
+/// A key Hamming weight is only used through the key mean. The improved modulus switching that
+/// also uses the Hamming weight to choose the rounding of each coefficient is not supported.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ModulusSwitchingKind {
+    /// Every coefficient is rounded independently.
+    #[default]
+    Standard,
+    /// The body is corrected by `E[s] sum_i d_i`, so that the rounding errors multiply the
+    /// centered key coefficients `s_i - E[s]`. With a fixed weight key, `E[s]` is the exact
+    /// density `h / n` given by the key Hamming weight.
+    CenteredMean,
+    /// The key is shifted by its mean: the body is corrected by `E[s] sum_i a_i` before the
+    /// switch and `E[s] sum_i round(a_i)` is added back after, rounded to the `2N` modulus. It only
+    /// needs the switched mask and not the rounding errors, so that it works around a blind rotate
+    /// doing its own switch, for an additional rounding of the body.
+    ShiftedKey,
+}
+
+impl ModulusSwitchingKind {
+    pub const ALL: [Self; 3] = [Self::Standard, Self::CenteredMean, Self::ShiftedKey];
+
+    /// Variance of the key coefficients multiplying the mask rounding errors.
+    fn rounded_key_second_moment(self, key: KeyMoments) -> f64 {
+        match self {
+            Self::Standard => key.second_moment(),
+            Self::CenteredMean | Self::ShiftedKey => key.variance,
+        }
+    }
+
+    /// Probability for a rounding error of the mask to be multiplied by a non zero coefficient, when
+    /// the coefficients it is multiplied by are 0 or of a single magnitude. Keys with `E[s^2] <= 1`
+    /// are taken in `{-1, 0, 1}`, as binary, ternary and fixed weight keys are.
+    fn rounded_key_density(self, key: KeyMoments) -> Option<f64> {
+        match self {
+            _ if key.second_moment() > 1.0 => None,
+            Self::Standard => Some(key.second_moment()),
+            Self::CenteredMean | Self::ShiftedKey if key.mean == 0.0 => Some(key.second_moment()),
+            // s - 1/2 is -1/2 or 1/2
+            Self::CenteredMean | Self::ShiftedKey if key == KeyMoments::BINARY => Some(1.0),
+            Self::CenteredMean | Self::ShiftedKey => None,
+        }
+    }
+
+    /// Variance of the additional rounding of the body on the switched modulus.
+    fn body_correction_rounding(self, key: KeyMoments) -> f64 {
+        match self {
+            // half a step or nothing for a binary key, below a uniform rounding for other means
+            Self::ShiftedKey if key.mean != 0.0 => 1. / 8.,
+            _ => 0.0,
+        }
+    }
+}
+
 pub fn estimate_modulus_switching_noise_with_binary_key(
     internal_ks_output_lwe_dimension: u64,
     glwe_log2_polynomial_size: u64,
     ciphertext_modulus_log: u32,
+) -> f64 {
+    estimate_modulus_switching_noise_with_key(
+        internal_ks_output_lwe_dimension,
+        glwe_log2_polynomial_size,
+        ciphertext_modulus_log,
+        KeyMoments::BINARY,
+    )
+}
+
+/// The rounding errors of the mask are multiplied by the key coefficients, adding E[s^2] / 12 per
+/// coefficient on the switched modulus.
+pub fn estimate_modulus_switching_noise_with_key(
+    internal_ks_output_lwe_dimension: u64,
+    glwe_log2_polynomial_size: u64,
+    ciphertext_modulus_log: u32,
+    key: KeyMoments,
+) -> f64 {
+    estimate_modulus_switching_noise_with_kind(
+        internal_ks_output_lwe_dimension,
+        glwe_log2_polynomial_size,
+        ciphertext_modulus_log,
+        key,
+        ModulusSwitchingKind::Standard,
+    )
+}
+
+/// The mean correction of `kind` replaces E[s^2] by the key variance, the shifted key adds the
+/// rounding of its correction.
+pub fn estimate_modulus_switching_noise_with_kind(
+    internal_ks_output_lwe_dimension: u64,
+    glwe_log2_polynomial_size: u64,
+    ciphertext_modulus_log: u32,
+    key: KeyMoments,
+    kind: ModulusSwitchingKind,
 ) -> f64 {
     let nb_msb = glwe_log2_polynomial_size + 1;
 
     let w = 2_f64.powi(nb_msb as i32);
     let n = internal_ks_output_lwe_dimension as f64;
 
-    (1. / 12. + n / 24.) / square(w)
-        + modular_variance_to_variance(-1. / 12. + n / 48., ciphertext_modulus_log)
+    (1. / 12. + n * kind.rounded_key_second_moment(key) / 12. + kind.body_correction_rounding(key))
+        / square(w)
+        + modular_variance_to_variance(-1. / 12. + n * key.variance / 12., ciphertext_modulus_log)
+}
+
+pub fn breakdown_modulus_switching_with_key(
+    internal_ks_output_lwe_dimension: u64,
+    glwe_log2_polynomial_size: u64,
+    ciphertext_modulus_log: u32,
+    key: KeyMoments,
+) -> NoiseBreakdown {
+    breakdown_modulus_switching_with_kind(
+        internal_ks_output_lwe_dimension,
+        glwe_log2_polynomial_size,
+        ciphertext_modulus_log,
+        key,
+        ModulusSwitchingKind::Standard,
+    )
+}
+
+/// The rounding of the body is uniform, the rounding of the mask a sum of uniform errors
+/// multiplied by the key coefficients, gaussian when their [`NoiseShape`] is unknown.
+pub fn breakdown_modulus_switching_with_kind(
+    internal_ks_output_lwe_dimension: u64,
+    glwe_log2_polynomial_size: u64,
+    ciphertext_modulus_log: u32,
+    key: KeyMoments,
+    kind: ModulusSwitchingKind,
+) -> NoiseBreakdown {
+    let nb_msb = glwe_log2_polynomial_size + 1;
+
+    let w = 2_f64.powi(nb_msb as i32);
+    let n = internal_ks_output_lwe_dimension as f64;
+
+    let mask_shape = kind
+        .rounded_key_density(key)
+        .map_or(NoiseShape::Gaussian, |density| NoiseShape::SparseUniform {
+            count: internal_ks_output_lwe_dimension,
+            density,
+        });
+    let breakdown = NoiseBreakdown::shaped_term(
+        "modulus switching",
+        NoiseSource::ModulusSwitching,
+        1. / 12. / square(w),
+        NoiseShape::SparseUniform {
+            count: 1,
+            density: 1.0,
+        },
+    )
+    .then(NoiseBreakdown::shaped_term(
+        "modulus switching",
+        NoiseSource::ModulusSwitching,
+        n * kind.rounded_key_second_moment(key) / 12. / square(w)
+            + modular_variance_to_variance(
+                -1. / 12. + n * key.variance / 12.,
+                ciphertext_modulus_log,
+            ),
+        mask_shape,
+    ));
+    let body_correction_rounding = kind.body_correction_rounding(key);
+    if body_correction_rounding == 0.0 {
+        breakdown
+    } else {
+        breakdown.then(NoiseBreakdown::term(
+            "modulus switching",
+            NoiseSource::ModulusSwitching,
+            body_correction_rounding / square(w),
+        ))
+    }
+}
+
+/// Largest error of the rounding of the body and of the mask to the `2N` modulus, each rounding
+/// error of the mask multiplied by a key coefficient at `key_bound`.
+pub fn max_error_modulus_switching(
+    internal_ks_output_lwe_dimension: u64,
+    glwe_log2_polynomial_size: u64,
+    key_bound: f64,
+) -> f64 {
+    let half_step = 2_f64.powi(-(glwe_log2_polynomial_size as i32 + 2));
+    (1. + internal_ks_output_lwe_dimension as f64 * key_bound) * half_step
+}
+
+/// Largest error of the modulus switching of `kind`, for key coefficients in
+/// `[min(0, 2 mean - key_bound), key_bound]` as for binary, ternary and gaussian keys.
+pub fn max_error_modulus_switching_with_kind(
+    internal_ks_output_lwe_dimension: u64,
+    glwe_log2_polynomial_size: u64,
+    key_bound: f64,
+    key_mean: f64,
+    kind: ModulusSwitchingKind,
+) -> f64 {
+    let centered_key_bound = (key_bound - key_mean.abs()).max(key_mean.abs());
+    let half_step = 2_f64.powi(-(glwe_log2_polynomial_size as i32 + 2));
+    match kind {
+        ModulusSwitchingKind::Standard => max_error_modulus_switching(
+            internal_ks_output_lwe_dimension,
+            glwe_log2_polynomial_size,
+            key_bound,
+        ),
+        ModulusSwitchingKind::CenteredMean => max_error_modulus_switching(
+            internal_ks_output_lwe_dimension,
+            glwe_log2_polynomial_size,
+            centered_key_bound,
+        ),
+        ModulusSwitchingKind::ShiftedKey => {
+            let correction_rounding = if key_mean == 0.0 { 0.0 } else { half_step };
+            max_error_modulus_switching(
+                internal_ks_output_lwe_dimension,
+                glwe_log2_polynomial_size,
+                centered_key_bound,
+            ) + correction_rounding
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use concrete_security_curves::key_distribution::KeyDistribution;
+
+    use super::*;
+
+    #[test]
+    fn binary_key_moments_give_the_binary_formula() {
+        let n = 800;
+        let with_binary_key = estimate_modulus_switching_noise_with_binary_key(n, 11, 64);
+        let with_key = estimate_modulus_switching_noise_with_key(
+            n,
+            11,
+            64,
+            KeyDistribution::Binary.moments(n),
+        );
+        approx::assert_relative_eq!(with_binary_key, with_key, max_relative = 1e-12);
+        let with_ternary_key = estimate_modulus_switching_noise_with_key(
+            n,
+            11,
+            64,
+            KeyDistribution::Ternary.moments(n),
+        );
+        assert!(with_ternary_key > with_binary_key);
+    }
+
+    #[test]
+    fn mean_correction_removes_the_drift() {
+        let n = 800;
+        let variance_of = |key: KeyDistribution, kind| {
+            estimate_modulus_switching_noise_with_kind(n, 11, 64, key.moments(n), kind)
+        };
+        let standard = variance_of(KeyDistribution::Binary, ModulusSwitchingKind::Standard);
+        let centered = variance_of(KeyDistribution::Binary, ModulusSwitchingKind::CenteredMean);
+        let shifted = variance_of(KeyDistribution::Binary, ModulusSwitchingKind::ShiftedKey);
+        // (1 + n / 4) / 12 against (1 + n / 2) / 12 on the 2N modulus
+        approx::assert_relative_eq!(standard / centered, 401.0 / 201.0, max_relative = 1e-6);
+        approx::assert_relative_eq!(
+            (shifted - centered) * 4096.0 * 4096.0,
+            1.0 / 8.0,
+            max_relative = 1e-6
+        );
+        // nothing to correct on a centered key
+        for kind in ModulusSwitchingKind::ALL {
+            approx::assert_relative_eq!(
+                variance_of(KeyDistribution::Ternary, kind),
+                variance_of(KeyDistribution::Ternary, ModulusSwitchingKind::Standard),
+            );
+        }
+        let max_error_of =
+            |kind| max_error_modulus_switching_with_kind(n, 11, 1.0, 0.5, kind) * 8192.0;
+        approx::assert_relative_eq!(max_error_of(ModulusSwitchingKind::Standard), 801.0);
+        approx::assert_relative_eq!(max_error_of(ModulusSwitchingKind::CenteredMean), 401.0);
+        approx::assert_relative_eq!(max_error_of(ModulusSwitchingKind::ShiftedKey), 402.0);
+    }
 }
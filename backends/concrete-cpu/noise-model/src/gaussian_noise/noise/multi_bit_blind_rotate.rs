@@ -1,4 +1,10 @@
-use super::multi_bit_external_product_glwe::variance_multi_bit_external_product_glwe;
+use concrete_security_curves::key_distribution::KeyMoments;
+
+use super::multi_bit_external_product_glwe::{
+    breakdown_multi_bit_external_product_glwe_with_key,
+    variance_multi_bit_external_product_glwe_with_key,
+};
+use crate::gaussian_noise::breakdown::NoiseBreakdown;
 
 /// Final reduced noise generated by the final multi bit bootstrap step.
 /// Note that it does not depends from input noise, assuming the bootstrap is successful
@@ -14,6 +20,35 @@ pub fn variance_multi_bit_blind_rotate(
     variance_bsk: f64,
     grouping_factor: u32,
     jit_fft: bool,
+) -> f64 {
+    variance_multi_bit_blind_rotate_with_key(
+        in_lwe_dimension,
+        out_glwe_dimension,
+        out_polynomial_size,
+        log2_base,
+        level,
+        ciphertext_modulus_log,
+        fft_precision,
+        variance_bsk,
+        grouping_factor,
+        jit_fft,
+        KeyMoments::BINARY,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn variance_multi_bit_blind_rotate_with_key(
+    in_lwe_dimension: u64,
+    out_glwe_dimension: u64,
+    out_polynomial_size: u64,
+    log2_base: u64,
+    level: u64,
+    ciphertext_modulus_log: u32,
+    fft_precision: u32,
+    variance_bsk: f64,
+    grouping_factor: u32,
+    jit_fft: bool,
+    out_glwe_key: KeyMoments,
 ) -> f64 {
     assert_eq!(
         in_lwe_dimension % (grouping_factor as u64),
@@ -22,7 +57,7 @@ pub fn variance_multi_bit_blind_rotate(
     to be a multiple of grouping_factor ({grouping_factor})"
     );
     (in_lwe_dimension / (grouping_factor as u64)) as f64
-        * variance_multi_bit_external_product_glwe(
+        * variance_multi_bit_external_product_glwe_with_key(
             out_glwe_dimension,
             out_polynomial_size,
             log2_base,
@@ -32,5 +67,42 @@ pub fn variance_multi_bit_blind_rotate(
             variance_bsk,
             grouping_factor,
             jit_fft,
+            out_glwe_key,
         )
 }
+
+#[allow(clippy::too_many_arguments)]
+pub fn breakdown_multi_bit_blind_rotate_with_key(
+    in_lwe_dimension: u64,
+    out_glwe_dimension: u64,
+    out_polynomial_size: u64,
+    log2_base: u64,
+    level: u64,
+    ciphertext_modulus_log: u32,
+    fft_precision: u32,
+    variance_bsk: f64,
+    grouping_factor: u32,
+    jit_fft: bool,
+    out_glwe_key: KeyMoments,
+) -> NoiseBreakdown {
+    assert_eq!(
+        in_lwe_dimension % (grouping_factor as u64),
+        0,
+        "in_lwe_dimension ({in_lwe_dimension}) has \
+    to be a multiple of grouping_factor ({grouping_factor})"
+    );
+    breakdown_multi_bit_external_product_glwe_with_key(
+        out_glwe_dimension,
+        out_polynomial_size,
+        log2_base,
+        level,
+        ciphertext_modulus_log,
+        fft_precision,
+        variance_bsk,
+        grouping_factor,
+        jit_fft,
+        out_glwe_key,
+    )
+    .renamed("multi bit blind rotate")
+    .scaled((in_lwe_dimension / (grouping_factor as u64)) as f64)
+}
@@ -1,3 +1,6 @@
+use concrete_security_curves::key_distribution::KeyMoments;
+
+use crate::gaussian_noise::breakdown::{NoiseBreakdown, NoiseSource};
 use crate::gaussian_noise::conversion::modular_variance_to_variance;
 use crate::utils::square;
 
@@ -13,27 +16,89 @@ pub fn variance_multi_bit_external_product_glwe(
     grouping_factor: u32,
     jit_fft: bool,
 ) -> f64 {
-    theoretical_variance_multi_bit_external_product_glwe(
+    variance_multi_bit_external_product_glwe_with_key(
         glwe_dimension,
         polynomial_size,
         log2_base,
         level,
         ciphertext_modulus_log,
+        fft_precision,
         variance_ggsw,
         grouping_factor,
-    ) + fft_noise_variance_multi_bit_external_product_glwe(
+        jit_fft,
+        KeyMoments::BINARY,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn variance_multi_bit_external_product_glwe_with_key(
+    glwe_dimension: u64,
+    polynomial_size: u64,
+    log2_base: u64,
+    level: u64,
+    ciphertext_modulus_log: u32,
+    fft_precision: u32,
+    variance_ggsw: f64,
+    grouping_factor: u32,
+    jit_fft: bool,
+    glwe_key: KeyMoments,
+) -> f64 {
+    breakdown_multi_bit_external_product_glwe_with_key(
         glwe_dimension,
         polynomial_size,
         log2_base,
         level,
         ciphertext_modulus_log,
         fft_precision,
+        variance_ggsw,
         grouping_factor,
         jit_fft,
+        glwe_key,
+    )
+    .total()
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn breakdown_multi_bit_external_product_glwe_with_key(
+    glwe_dimension: u64,
+    polynomial_size: u64,
+    log2_base: u64,
+    level: u64,
+    ciphertext_modulus_log: u32,
+    fft_precision: u32,
+    variance_ggsw: f64,
+    grouping_factor: u32,
+    jit_fft: bool,
+    glwe_key: KeyMoments,
+) -> NoiseBreakdown {
+    theoretical_breakdown_multi_bit_external_product_glwe(
+        glwe_dimension,
+        polynomial_size,
+        log2_base,
+        level,
+        ciphertext_modulus_log,
+        variance_ggsw,
+        grouping_factor,
+        glwe_key,
     )
+    .then(NoiseBreakdown::term(
+        "multi bit external product",
+        NoiseSource::Fft,
+        fft_noise_variance_multi_bit_external_product_glwe(
+            glwe_dimension,
+            polynomial_size,
+            log2_base,
+            level,
+            ciphertext_modulus_log,
+            fft_precision,
+            grouping_factor,
+            jit_fft,
+        ),
+    ))
 }
 
-fn theoretical_variance_multi_bit_external_product_glwe(
+#[allow(clippy::too_many_arguments)]
+fn theoretical_breakdown_multi_bit_external_product_glwe(
     glwe_dimension: u64,
     polynomial_size: u64,
     log2_base: u64,
@@ -41,12 +106,13 @@ fn theoretical_variance_multi_bit_external_product_glwe(
     ciphertext_modulus_log: u32,
     variance_ggsw: f64,
     grouping_factor: u32,
-) -> f64 {
-    let variance_key_coefficient_binary: f64 =
-        modular_variance_to_variance(1. / 4., ciphertext_modulus_log);
+    glwe_key: KeyMoments,
+) -> NoiseBreakdown {
+    let variance_key_coefficient: f64 =
+        modular_variance_to_variance(glwe_key.variance, ciphertext_modulus_log);
 
-    let square_expectation_key_coefficient_binary: f64 =
-        modular_variance_to_variance(square(1. / 2.), ciphertext_modulus_log);
+    let square_expectation_key_coefficient: f64 =
+        modular_variance_to_variance(square(glwe_key.mean), ciphertext_modulus_log);
 
     let k = glwe_dimension as f64;
     let b = 2_f64.powi(log2_base as i32);
@@ -60,12 +126,16 @@ fn theoretical_variance_multi_bit_external_product_glwe(
         * 2.0f64.powi(grouping_factor as i32);
     let res_2 = (q_square - b2l) / (24. * b2l)
         * (modular_variance_to_variance(1., ciphertext_modulus_log)
-            + k * big_n
-                * (variance_key_coefficient_binary + square_expectation_key_coefficient_binary))
-        + k * big_n / 8. * variance_key_coefficient_binary
-        + 1. / 16. * square(1. - k * big_n) * square_expectation_key_coefficient_binary;
+            + k * big_n * (variance_key_coefficient + square_expectation_key_coefficient))
+        + k * big_n / 8. * variance_key_coefficient
+        + 1. / 16. * square(1. - k * big_n) * square_expectation_key_coefficient;
 
-    res_1 + res_2
+    let primitive = "multi bit external product";
+    NoiseBreakdown::term(primitive, NoiseSource::KeyNoise, res_1).then(NoiseBreakdown::term(
+        primitive,
+        NoiseSource::DecompositionRounding,
+        res_2,
+    ))
 }
 
 const FFT_SCALING_WEIGHTS: [(u32, f64); 3] = [
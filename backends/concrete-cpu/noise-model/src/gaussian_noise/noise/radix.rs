@@ -0,0 +1,111 @@
+//! Noise and cost of the radix integer operations of concrete-cpu.
+//!
+//! Every operation is a sequence of keyswitch + bootstrap pairs. The variances below are the
+//! variance of the ciphertexts entering the keyswitch of each pair, the caller then adds the
+//! keyswitch and modulus switching noise to check the bootstrap input.
+
+/// Variance of two blocks packed as `lhs * message_modulus + rhs` for a bivariate lookup table.
+pub fn variance_bivariate_packing(
+    message_modulus: u64,
+    variance_lhs: f64,
+    variance_rhs: f64,
+) -> f64 {
+    let message_modulus = message_modulus as f64;
+    message_modulus * message_modulus * variance_lhs + variance_rhs
+}
+
+/// Worst bootstrap input variance of a sign extraction, the most significant block is bootstrapped
+/// as is.
+pub const fn variance_sign_extraction(variance_block: f64) -> f64 {
+    variance_block
+}
+
+/// Worst bootstrap input variance of a comparison.
+///
+/// Input blocks are packed pairwise, then the block comparisons, which are bootstrap outputs, are
+/// packed pairwise during the reduction.
+pub fn variance_comparison(
+    block_count: u64,
+    message_modulus: u64,
+    variance_block: f64,
+    variance_bootstrap: f64,
+) -> f64 {
+    let blocks = variance_bivariate_packing(message_modulus, variance_block, variance_block);
+    if block_count <= 1 {
+        return blocks;
+    }
+    let reduction =
+        variance_bivariate_packing(message_modulus, variance_bootstrap, variance_bootstrap);
+    blocks.max(reduction)
+}
+
+/// Worst bootstrap input variance of a carry propagation, a block receives the carry of the
+/// previous block, a bootstrap output.
+pub fn variance_carry_propagation(
+    block_count: u64,
+    variance_block: f64,
+    variance_bootstrap: f64,
+) -> f64 {
+    if block_count <= 1 {
+        return variance_block;
+    }
+    variance_block + variance_bootstrap
+}
+
+/// Worst bootstrap input variance of a bivariate lookup table.
+pub fn variance_bivariate_lut(message_modulus: u64, variance_lhs: f64, variance_rhs: f64) -> f64 {
+    variance_bivariate_packing(message_modulus, variance_lhs, variance_rhs)
+}
+
+/// Number of keyswitch + bootstrap pairs of a sign extraction: one on the most significant block,
+/// none on an empty integer.
+pub const fn pbs_count_sign_extraction(block_count: u64) -> u64 {
+    if block_count == 0 {
+        0
+    } else {
+        1
+    }
+}
+
+/// Number of keyswitch + bootstrap pairs of a comparison: one per block and one per reduction step.
+pub const fn pbs_count_comparison(block_count: u64) -> u64 {
+    (2 * block_count).saturating_sub(1)
+}
+
+/// Number of keyswitch + bootstrap pairs of a carry propagation: a message extraction per block and
+/// a carry extraction per block but the last.
+pub const fn pbs_count_carry_propagation(block_count: u64) -> u64 {
+    (2 * block_count).saturating_sub(1)
+}
+
+/// Number of keyswitch + bootstrap pairs of a bivariate lookup table.
+pub const fn pbs_count_bivariate_lut() -> u64 {
+    1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn comparison_reduction_dominates_fresh_blocks() {
+        let variance_block = 1e-12;
+        let variance_bootstrap = 4e-12;
+
+        let single = variance_comparison(1, 4, variance_block, variance_bootstrap);
+        let multi = variance_comparison(4, 4, variance_block, variance_bootstrap);
+
+        approx::assert_relative_eq!(single, 17. * variance_block);
+        approx::assert_relative_eq!(multi, 17. * variance_bootstrap);
+        assert_eq!(pbs_count_comparison(4), 7);
+        assert_eq!(pbs_count_carry_propagation(1), 1);
+    }
+
+    #[test]
+    fn empty_integers_need_no_bootstrap() {
+        assert_eq!(pbs_count_sign_extraction(0), 0);
+        assert_eq!(pbs_count_sign_extraction(4), 1);
+        assert_eq!(pbs_count_comparison(0), 0);
+        assert_eq!(pbs_count_carry_propagation(0), 0);
+    }
+}
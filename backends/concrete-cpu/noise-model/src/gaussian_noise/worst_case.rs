@@ -0,0 +1,182 @@
+//! Worst-case error bounds, alongside the variances of the average case.
+//!
+//! The variance formulas of [`super::noise`] assume the errors are independent, so that their sum
+//! is close to a gaussian. The `max_error_*` functions bound the absolute error on the torus
+//! instead, adding the largest contribution of every decomposition digit and rounding, with the
+//! key coefficients at their largest value. Gaussian samples (key material encryption noise and
+//! FFT error) are unbounded, they are bounded by their tail of probability
+//! 2^[`GAUSSIAN_TAIL_LOG2_PROBABILITY`].
+
+use std::ops::Add;
+
+use concrete_security_curves::key_distribution::KeyDistribution;
+
+/// Which of the variance or the maximal error decides whether a ciphertext can be decrypted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum NoiseMode {
+    /// The variance gives the probability of a decryption error.
+    #[default]
+    AverageCase,
+    /// The maximal error must stay within the padding, decryption never fails.
+    WorstCase,
+}
+
+/// Probability for a gaussian sample to exceed its bound, as negligible as the security level.
+pub const GAUSSIAN_TAIL_LOG2_PROBABILITY: i32 = -128;
+
+/// Bound on the absolute value of a centered gaussian sample of variance `variance`.
+///
+/// `P(|X| > t) <= 2 exp(-t^2 / (2 variance))`, so `t = sqrt(2 variance ln(2 / p))`.
+pub fn gaussian_max_error(variance: f64) -> f64 {
+    let ln_2_over_p = std::f64::consts::LN_2 * (1 - GAUSSIAN_TAIL_LOG2_PROBABILITY) as f64;
+    (2.0 * variance * ln_2_over_p).sqrt()
+}
+
+/// Bound on the absolute value of a key coefficient.
+pub fn key_coefficient_bound(key_distribution: KeyDistribution) -> f64 {
+    key_distribution
+        .max_abs_coefficient()
+        .unwrap_or_else(|| gaussian_max_error(key_distribution.variance(1)))
+}
+
+/// Rounding error of a torus coefficient decomposed on `level` digits of `log2_base` bits.
+pub(crate) fn decomposition_rounding_max_error(
+    log2_base: u64,
+    level: u64,
+    ciphertext_modulus_log: u32,
+) -> f64 {
+    if log2_base * level >= ciphertext_modulus_log as u64 {
+        0.0
+    } else {
+        0.5 * 2_f64.powi(-((log2_base * level) as i32))
+    }
+}
+
+/// The variance and the maximal error of the same noise.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct NoiseBound {
+    pub variance: f64,
+    pub max_error: f64,
+}
+
+impl NoiseBound {
+    /// A gaussian noise, bounded by its tail.
+    pub fn gaussian(variance: f64) -> Self {
+        Self {
+            variance,
+            max_error: gaussian_max_error(variance),
+        }
+    }
+
+    /// The noise multiplied by `weight`.
+    #[must_use]
+    pub fn weighted(self, weight: f64) -> Self {
+        Self {
+            variance: self.variance * weight * weight,
+            max_error: self.max_error * weight.abs(),
+        }
+    }
+
+    /// The quantity the noise `mode` works with.
+    pub const fn value(self, mode: NoiseMode) -> f64 {
+        match mode {
+            NoiseMode::AverageCase => self.variance,
+            NoiseMode::WorstCase => self.max_error,
+        }
+    }
+}
+
+impl Add for NoiseBound {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self {
+            variance: self.variance + rhs.variance,
+            max_error: self.max_error + rhs.max_error,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use concrete_security_curves::gaussian::security::{
+        minimal_variance_glwe, minimal_variance_lwe,
+    };
+
+    use super::*;
+    use crate::gaussian_noise::noise::blind_rotate::{
+        max_error_blind_rotate, variance_blind_rotate,
+    };
+    use crate::gaussian_noise::noise::keyswitch::{max_error_keyswitch, variance_keyswitch};
+    use crate::gaussian_noise::noise::modulus_switching::max_error_modulus_switching;
+
+    #[test]
+    fn gaussian_tail_bound() {
+        // sqrt(2 * 129 * ln 2) standard deviations
+        approx::assert_relative_eq!(gaussian_max_error(1.0), 13.372_807_2, max_relative = 1e-8);
+        approx::assert_relative_eq!(
+            gaussian_max_error(4.0),
+            2.0 * gaussian_max_error(1.0),
+            max_relative = 1e-12
+        );
+        approx::assert_relative_eq!(key_coefficient_bound(KeyDistribution::Ternary), 1.0);
+        approx::assert_relative_eq!(
+            key_coefficient_bound(KeyDistribution::Gaussian { std_dev: 3.2 }),
+            3.2 * gaussian_max_error(1.0),
+            max_relative = 1e-12
+        );
+    }
+
+    #[test]
+    fn bounds_compose_linearly() {
+        let bound = NoiseBound::gaussian(1.0) + NoiseBound::gaussian(1.0).weighted(-2.0);
+        approx::assert_relative_eq!(bound.variance, 5.0);
+        approx::assert_relative_eq!(bound.max_error, 3.0 * gaussian_max_error(1.0));
+        approx::assert_relative_eq!(bound.value(NoiseMode::AverageCase), bound.variance);
+        approx::assert_relative_eq!(bound.value(NoiseMode::WorstCase), bound.max_error);
+        assert!(decomposition_rounding_max_error(8, 8, 64).abs() < f64::MIN_POSITIVE);
+        approx::assert_relative_eq!(decomposition_rounding_max_error(4, 2, 64), 1.0 / 512.0);
+    }
+
+    #[test]
+    fn max_errors_exceed_the_standard_deviations() {
+        let ciphertext_modulus_log = 64;
+        let variance_ksk = minimal_variance_lwe(800, ciphertext_modulus_log, 128).unwrap();
+        let bsk_variance = minimal_variance_glwe(1, 1 << 11, ciphertext_modulus_log, 128).unwrap();
+
+        let keyswitch = variance_keyswitch(2048, 4, 5, ciphertext_modulus_log, variance_ksk);
+        let max_error_ks =
+            max_error_keyswitch(2048, 4, 5, ciphertext_modulus_log, variance_ksk, 1.0);
+        assert!(max_error_ks > gaussian_max_error(keyswitch));
+
+        let blind_rotate = variance_blind_rotate(
+            800,
+            1,
+            1 << 11,
+            23,
+            1,
+            ciphertext_modulus_log,
+            53,
+            bsk_variance,
+        );
+        let max_error_br = max_error_blind_rotate(
+            800,
+            1,
+            1 << 11,
+            23,
+            1,
+            ciphertext_modulus_log,
+            53,
+            bsk_variance,
+            1.0,
+        );
+        assert!(max_error_br > gaussian_max_error(blind_rotate));
+
+        // the rounding bound dominates coarse decompositions
+        let coarse = max_error_keyswitch(2048, 4, 2, ciphertext_modulus_log, variance_ksk, 1.0);
+        let finer = max_error_keyswitch(2048, 4, 3, ciphertext_modulus_log, variance_ksk, 1.0);
+        assert!(coarse > 4.0);
+        assert!(finer < coarse);
+        approx::assert_relative_eq!(max_error_modulus_switching(800, 11, 1.0), 801.0 / 8192.0);
+    }
+}
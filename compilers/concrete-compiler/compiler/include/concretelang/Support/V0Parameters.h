@@ -81,6 +81,19 @@ constexpr bool DEFAULT_CACHE_ON_DISK = true;
 constexpr uint32_t DEFAULT_CIPHERTEXT_MODULUS_LOG = 64;
 constexpr uint32_t DEFAULT_FFT_PRECISION = 53;
 constexpr bool DEFAULT_COMPOSABLE = false;
+constexpr concrete_optimizer::NoiseTracking DEFAULT_NOISE_TRACKING =
+    concrete_optimizer::NoiseTracking::PerTensor;
+/// The exact tail is only supported by the V0 and DAG_MONO strategies
+constexpr concrete_optimizer::ErrorProbabilityModel
+    DEFAULT_ERROR_PROBABILITY_MODEL =
+        concrete_optimizer::ErrorProbabilityModel::Gaussian;
+/// The empty path selects the security curves compiled in the optimizer
+constexpr const char *DEFAULT_SECURITY_CURVES_FILE = "";
+/// Simplifying the dag makes the solution refer to the simplified dag it gives
+constexpr bool DEFAULT_SIMPLIFY_DAG = false;
+/// Refreshing the compositions is only done by the DAG_MULTI strategy, the
+/// solution then gives the refreshed dag
+constexpr bool DEFAULT_REFRESH_COMPOSITIONS = false;
 
 /// The strategy of the crypto optimization
 enum Strategy {
@@ -116,6 +129,11 @@ struct Config {
   uint32_t ciphertext_modulus_log;
   uint32_t fft_precision;
   bool composable;
+  concrete_optimizer::NoiseTracking noise_tracking;
+  concrete_optimizer::ErrorProbabilityModel error_probability_model;
+  const char *security_curves_file;
+  bool simplify_dag;
+  bool refresh_compositions;
 };
 
 constexpr Config DEFAULT_CONFIG = {
@@ -133,6 +151,11 @@ constexpr Config DEFAULT_CONFIG = {
     DEFAULT_CIPHERTEXT_MODULUS_LOG,
     DEFAULT_FFT_PRECISION,
     DEFAULT_COMPOSABLE,
+    DEFAULT_NOISE_TRACKING,
+    DEFAULT_ERROR_PROBABILITY_MODEL,
+    DEFAULT_SECURITY_CURVES_FILE,
+    DEFAULT_SIMPLIFY_DAG,
+    DEFAULT_REFRESH_COMPOSITIONS,
 };
 
 using Dag = rust::Box<concrete_optimizer::Dag>;
@@ -946,12 +946,14 @@ void memref_wop_pbs_crt_buffer(
   assert(lut_ct_size1 == lut_size);
 
   // Vertical packing
+  size_t worker_count =
+      concrete_cpu_circuit_bootstrap_boolean_vertical_packing_worker_count();
   size_t scratch_size;
   size_t scratch_align;
   concrete_cpu_circuit_bootstrap_boolean_vertical_packing_lwe_ciphertext_u64_scratch(
       &scratch_size, &scratch_align, ct_out_count, lwe_small_dim, ct_in_count,
       lut_size, lut_count, glwe_dim, polynomial_size, polynomial_size,
-      cbs_level_count, fft);
+      cbs_level_count, Parallelism::Rayon, worker_count, fft);
 
   auto *scratch = (uint8_t *)aligned_alloc(scratch_align, scratch_size);
 
@@ -963,8 +965,8 @@ void memref_wop_pbs_crt_buffer(
       lwe_big_dim, ct_out_count, lwe_small_dim, ct_in_count, lut_size,
       lut_count, bsk_level_count, bsk_base_log, glwe_dim, polynomial_size,
       lwe_small_dim, fpksk_level_count, fpksk_base_log, lwe_big_dim, glwe_dim,
-      polynomial_size, glwe_dim + 1, cbs_level_count, cbs_base_log, fft,
-      scratch, scratch_size);
+      polynomial_size, glwe_dim + 1, cbs_level_count, cbs_base_log,
+      Parallelism::Rayon, worker_count, fft, scratch, scratch_size);
 
   free(scratch);
 }
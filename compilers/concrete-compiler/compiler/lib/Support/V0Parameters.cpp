@@ -36,7 +36,14 @@ concrete_optimizer::Options options_from_config(optimizer::Config config) {
       /* .cache_on_disk = */ config.cache_on_disk,
       /* .ciphertext_modulus_log = */ config.ciphertext_modulus_log,
       /* .fft_precision = */ config.fft_precision,
-      /* .composable = */ config.composable};
+      /* .composable = */ config.composable,
+      /* .noise_tracking = */ config.noise_tracking,
+      /* .error_probability_model = */ config.error_probability_model,
+      /* .security_curves_file = */ config.security_curves_file,
+      /* .simplify_dag = */ config.simplify_dag,
+      /* .refresh_compositions = */ config.refresh_compositions,
+      // the runtime bootstraps with the standard modulus switching only
+      /* .modulus_switching_kinds = */ {}};
   return options;
 }
 
@@ -282,7 +289,10 @@ template <> std::string getErrorMessage(optimizer::CircuitSolution sol) {
 }
 
 template <> std::string getErrorMessage(optimizer::DagSolution sol) {
-  return "NoParametersFound";
+  if (sol.error_msg.empty()) {
+    return "NoParametersFound";
+  }
+  return sol.error_msg.c_str();
 }
 
 /// Check if the solution p_error and global_p_error of the given `solution`
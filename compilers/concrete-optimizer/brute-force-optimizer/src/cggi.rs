@@ -9,7 +9,7 @@ use concrete_optimizer::parameters::{
     AtomicPatternParameters, BrDecompositionParameters, GlweParameters, KsDecompositionParameters,
     LweDimension,
 };
-use concrete_security_curves::gaussian::security::{minimal_variance_glwe, minimal_variance_lwe};
+use concrete_security_curves::gaussian::curves::SecurityCurves;
 use rayon::prelude::{IntoParallelIterator, ParallelIterator};
 use std::io::Write;
 // use rayon_cond::CondIterator;
@@ -32,20 +32,23 @@ impl CGGIParams {
     }
 }
 
-struct CGGIConstraint {
+struct CGGIConstraint<'a> {
     variance_constraint: f64,
     log_norm2: u64,
+    security_curves: &'a SecurityCurves,
     security_level: u64,
     sum_size: u64,
 }
 
-impl Problem for CGGIConstraint {
+impl Problem for CGGIConstraint<'_> {
     type Param = CGGIParams;
 
     fn verify(&self, param: Self::Param) -> bool {
         let poly_size = 1 << param.log_poly_size;
 
-        let variance_ksk = minimal_variance_lwe(param.small_lwe_dim, 64, self.security_level);
+        let variance_ksk =
+            self.security_curves
+                .minimal_variance_lwe(param.small_lwe_dim, 64, self.security_level);
 
         let v_ks = variance_keyswitch(
             param.big_lwe_dim(),
@@ -55,8 +58,12 @@ impl Problem for CGGIConstraint {
             variance_ksk,
         );
 
-        let variance_bsk =
-            minimal_variance_glwe(param.glwe_dim, poly_size, 64, self.security_level);
+        let variance_bsk = self.security_curves.minimal_variance_glwe(
+            param.glwe_dim,
+            poly_size,
+            64,
+            self.security_level,
+        );
         let v_pbs = variance_blind_rotate(
             param.small_lwe_dim,
             param.glwe_dim,
@@ -131,7 +138,11 @@ struct CGGISearchSpace {
 }
 
 impl CGGISearchSpace {
-    fn to_tighten(&self, security_level: u64) -> CGGISearchSpaceTighten {
+    fn to_tighten(
+        &self,
+        security_curves: &SecurityCurves,
+        security_level: u64,
+    ) -> CGGISearchSpaceTighten {
         // Keyswitch
         let mut ks_decomp = vec![];
         for log_N in self.range_log_poly_size.to_std_range() {
@@ -142,7 +153,8 @@ impl CGGISearchSpace {
                         let mut current_minimal_noise_for_a_given_level = current_minimal_noise;
                         let mut current_pair = (0, 0);
                         for baselog in self.range_base_log_ks.to_std_range() {
-                            let variance_ksk = minimal_variance_lwe(n, 64, security_level);
+                            let variance_ksk =
+                                security_curves.minimal_variance_lwe(n, 64, security_level);
 
                             let v_ks = variance_keyswitch(
                                 (1 << log_N) * k,
@@ -174,8 +186,12 @@ impl CGGISearchSpace {
                         let mut current_minimal_noise_for_a_given_level = current_minimal_noise;
                         let mut current_pair = (0, 0);
                         for baselog in self.range_base_log_ks.to_std_range() {
-                            let variance_bsk =
-                                minimal_variance_glwe(k, 1 << log_N, 64, security_level);
+                            let variance_bsk = security_curves.minimal_variance_glwe(
+                                k,
+                                1 << log_N,
+                                64,
+                                security_level,
+                            );
                             let v_pbs = variance_blind_rotate(
                                 n,
                                 k,
@@ -275,7 +291,7 @@ impl CGGISearchSpaceTighten {
     }
 }
 
-pub fn solve_all_cggi(p_fail: f64, writer: impl Write) {
+pub fn solve_all_cggi(p_fail: f64, security_curves: &SecurityCurves, writer: impl Write) {
     // -> Vec<(u64, u64, Option<(V0Params, f64)>)> {
     // let p_fail = 1.0 - 0.999_936_657_516;
 
@@ -296,7 +312,7 @@ pub fn solve_all_cggi(p_fail: f64, writer: impl Write) {
         range_small_lwe_dim: MyRange(500, 1500),
     };
 
-    let a_tighten = a.to_tighten(128);
+    let a_tighten = a.to_tighten(security_curves, 128);
     let res: Vec<Solution<CGGIParams>> = precisions
         .into_par_iter()
         .flat_map(|precision| {
@@ -309,6 +325,7 @@ pub fn solve_all_cggi(p_fail: f64, writer: impl Write) {
                             precision, 64, p_fail,
                         ), //5.960464477539063e-08, // 0.0009765625006088146,
                         log_norm2: log_norm,
+                        security_curves,
                         security_level: 128,
                         sum_size: 4096,
                     };
@@ -9,7 +9,7 @@ use concrete_optimizer::parameters::{
     AtomicPatternParameters, BrDecompositionParameters, GlweParameters, KsDecompositionParameters,
     LweDimension,
 };
-use concrete_security_curves::gaussian::security::{minimal_variance_glwe, minimal_variance_lwe};
+use concrete_security_curves::gaussian::curves::SecurityCurves;
 use rayon::prelude::{IntoParallelIterator, ParallelIterator};
 
 use std::io::Write;
@@ -33,20 +33,23 @@ impl CJPParams {
     }
 }
 
-struct CJPConstraint {
+struct CJPConstraint<'a> {
     variance_constraint: f64,
     log_norm2: u64,
+    security_curves: &'a SecurityCurves,
     security_level: u64,
     sum_size: u64,
 }
 
-impl Problem for CJPConstraint {
+impl Problem for CJPConstraint<'_> {
     type Param = CJPParams;
 
     fn verify(&self, param: Self::Param) -> bool {
         let poly_size = 1 << param.log_poly_size;
 
-        let variance_ksk = minimal_variance_lwe(param.small_lwe_dim, 64, self.security_level);
+        let variance_ksk =
+            self.security_curves
+                .minimal_variance_lwe(param.small_lwe_dim, 64, self.security_level);
 
         let v_ks = variance_keyswitch(
             param.big_lwe_dim(),
@@ -56,8 +59,12 @@ impl Problem for CJPConstraint {
             variance_ksk,
         );
 
-        let variance_bsk =
-            minimal_variance_glwe(param.glwe_dim, poly_size, 64, self.security_level);
+        let variance_bsk = self.security_curves.minimal_variance_glwe(
+            param.glwe_dim,
+            poly_size,
+            64,
+            self.security_level,
+        );
         let v_pbs = variance_blind_rotate(
             param.small_lwe_dim,
             param.glwe_dim,
@@ -132,7 +139,11 @@ struct CJPSearchSpace {
 }
 
 impl CJPSearchSpace {
-    fn to_tighten(&self, security_level: u64) -> CJPSearchSpaceTighten {
+    fn to_tighten(
+        &self,
+        security_curves: &SecurityCurves,
+        security_level: u64,
+    ) -> CJPSearchSpaceTighten {
         // Keyswitch
         let mut ks_decomp = vec![];
         for log_N in self.range_log_poly_size.to_std_range() {
@@ -143,7 +154,8 @@ impl CJPSearchSpace {
                         let mut current_minimal_noise_for_a_given_level = current_minimal_noise;
                         let mut current_pair = (0, 0);
                         for baselog in self.range_base_log_ks.to_std_range() {
-                            let variance_ksk = minimal_variance_lwe(n, 64, security_level);
+                            let variance_ksk =
+                                security_curves.minimal_variance_lwe(n, 64, security_level);
 
                             let v_ks = variance_keyswitch(
                                 (1 << log_N) * k,
@@ -175,8 +187,12 @@ impl CJPSearchSpace {
                         let mut current_minimal_noise_for_a_given_level = current_minimal_noise;
                         let mut current_pair = (0, 0);
                         for baselog in self.range_base_log_ks.to_std_range() {
-                            let variance_bsk =
-                                minimal_variance_glwe(k, 1 << log_N, 64, security_level);
+                            let variance_bsk = security_curves.minimal_variance_glwe(
+                                k,
+                                1 << log_N,
+                                64,
+                                security_level,
+                            );
                             let v_pbs = variance_blind_rotate(
                                 n,
                                 k,
@@ -278,7 +294,7 @@ impl CJPSearchSpaceTighten {
     }
 }
 
-pub fn solve_all_cjp(p_fail: f64, writer: impl Write) {
+pub fn solve_all_cjp(p_fail: f64, security_curves: &SecurityCurves, writer: impl Write) {
     let start = Instant::now();
 
     let precisions = 1..24;
@@ -310,7 +326,7 @@ pub fn solve_all_cjp(p_fail: f64, writer: impl Write) {
     //     range_log_poly_size: MyRange(8, 16),
     //     range_small_lwe_dim: MyRange(500, 1000),
     // };
-    let a_tighten = a.to_tighten(128);
+    let a_tighten = a.to_tighten(security_curves, 128);
     let res: Vec<Solution<CJPParams>> = precisions
         .into_par_iter()
         .flat_map(|precision| {
@@ -323,6 +339,7 @@ pub fn solve_all_cjp(p_fail: f64, writer: impl Write) {
                             precision, 64, p_fail,
                         ), //5.960464477539063e-08, // 0.0009765625006088146,
                         log_norm2: log_norm,
+                        security_curves,
                         security_level: 128,
                         sum_size: 4096,
                     };
@@ -12,7 +12,7 @@ use concrete_optimizer::parameters::{
     BrDecompositionParameters, GlweParameters, KeyswitchParameters, KsDecompositionParameters,
     LweDimension, PbsParameters,
 };
-use concrete_security_curves::gaussian::security::{minimal_variance_glwe, minimal_variance_lwe};
+use concrete_security_curves::gaussian::curves::SecurityCurves;
 use rayon::prelude::{IntoParallelIterator, ParallelIterator};
 
 use std::io::Write;
@@ -39,22 +39,25 @@ impl GBAParams {
     }
 }
 
-struct GBAConstraint {
+struct GBAConstraint<'a> {
     variance_constraint: f64,
     log_norm2: u64,
+    security_curves: &'a SecurityCurves,
     security_level: u64,
     sum_size: u64,
     precision: u64,
     nb_inputs: u64,
 }
 
-impl Problem for GBAConstraint {
+impl Problem for GBAConstraint<'_> {
     type Param = GBAParams;
 
     fn verify(&self, param: Self::Param) -> bool {
         let poly_size = 1 << param.log_poly_size;
 
-        let variance_ksk = minimal_variance_lwe(param.small_lwe_dim, 64, self.security_level);
+        let variance_ksk =
+            self.security_curves
+                .minimal_variance_lwe(param.small_lwe_dim, 64, self.security_level);
 
         let v_ks = variance_keyswitch(
             param.big_lwe_dim(),
@@ -64,8 +67,12 @@ impl Problem for GBAConstraint {
             variance_ksk,
         );
 
-        let variance_bsk =
-            minimal_variance_glwe(param.glwe_dim, poly_size, 64, self.security_level);
+        let variance_bsk = self.security_curves.minimal_variance_glwe(
+            param.glwe_dim,
+            poly_size,
+            64,
+            self.security_level,
+        );
         let v_pbs = variance_blind_rotate(
             param.small_lwe_dim,
             param.glwe_dim,
@@ -194,7 +201,11 @@ struct GBASearchSpace {
 }
 
 impl GBASearchSpace {
-    fn to_tighten(&self, security_level: u64) -> GBASearchSpaceTighten {
+    fn to_tighten(
+        &self,
+        security_curves: &SecurityCurves,
+        security_level: u64,
+    ) -> GBASearchSpaceTighten {
         // Keyswitch
         let mut ks_decomp = vec![];
         for log_N in self.range_log_poly_size.to_std_range() {
@@ -205,7 +216,8 @@ impl GBASearchSpace {
                         let mut current_minimal_noise_for_a_given_level = current_minimal_noise;
                         let mut current_pair = (0, 0);
                         for baselog in self.range_base_log_ks.to_std_range() {
-                            let variance_ksk = minimal_variance_lwe(n, 64, security_level);
+                            let variance_ksk =
+                                security_curves.minimal_variance_lwe(n, 64, security_level);
 
                             let v_ks = variance_keyswitch(
                                 (1 << log_N) * k,
@@ -237,8 +249,12 @@ impl GBASearchSpace {
                         let mut current_minimal_noise_for_a_given_level = current_minimal_noise;
                         let mut current_pair = (0, 0);
                         for baselog in self.range_base_log_pbs.to_std_range() {
-                            let variance_bsk =
-                                minimal_variance_glwe(k, 1 << log_N, 64, security_level);
+                            let variance_bsk = security_curves.minimal_variance_glwe(
+                                k,
+                                1 << log_N,
+                                64,
+                                security_level,
+                            );
                             let v_pbs = variance_blind_rotate(
                                 n,
                                 k,
@@ -273,8 +289,12 @@ impl GBASearchSpace {
                         let mut current_minimal_noise_for_a_given_level = current_minimal_noise;
                         let mut current_pair = (0, 0);
                         for baselog in self.range_base_log_fpks.to_std_range() {
-                            let variance_bsk =
-                                minimal_variance_glwe(k, 1 << log_N, 64, security_level);
+                            let variance_bsk = security_curves.minimal_variance_glwe(
+                                k,
+                                1 << log_N,
+                                64,
+                                security_level,
+                            );
                             let v_pp_keyswitch = (1 << log_N) as f64
                                 * variance_keyswitch(
                                     (1 << log_N) * k,
@@ -387,7 +407,7 @@ impl GBASearchSpaceTighten {
     }
 }
 
-pub fn solve_all_gba(p_fail: f64, writer: impl Write) {
+pub fn solve_all_gba(p_fail: f64, security_curves: &SecurityCurves, writer: impl Write) {
     let nb_inputs = 2;
     let start = Instant::now();
 
@@ -412,7 +432,7 @@ pub fn solve_all_gba(p_fail: f64, writer: impl Write) {
     )
     .sqrt()
     .ceil() as u64;
-    let a_tighten = a.to_tighten(128);
+    let a_tighten = a.to_tighten(security_curves, 128);
     let res: Vec<Solution<GBAParams>> = precisions
         .into_par_iter()
         .flat_map(|precision| {
@@ -427,6 +447,7 @@ pub fn solve_all_gba(p_fail: f64, writer: impl Write) {
                             gba_p_fail_per_pbs(precision, nb_inputs, p_fail),
                         ),
                         log_norm2: log_norm,
+                        security_curves,
                         security_level: 128,
                         sum_size: 4096,
                         precision,
@@ -8,7 +8,7 @@ use concrete_optimizer::noise_estimator::error;
 use concrete_optimizer::parameters::{
     BrDecompositionParameters, GlweParameters, LweDimension, PbsParameters,
 };
-use concrete_security_curves::gaussian::security::minimal_variance_glwe;
+use concrete_security_curves::gaussian::curves::SecurityCurves;
 use rayon::prelude::{IntoParallelIterator, ParallelIterator};
 use std::io::Write;
 // use rayon_cond::CondIterator;
@@ -28,21 +28,26 @@ impl KSFreeParams {
     }
 }
 
-struct KSFreeConstraint {
+struct KSFreeConstraint<'a> {
     variance_constraint: f64,
     log_norm2: u64,
+    security_curves: &'a SecurityCurves,
     security_level: u64,
     sum_size: u64,
 }
 
-impl Problem for KSFreeConstraint {
+impl Problem for KSFreeConstraint<'_> {
     type Param = KSFreeParams;
 
     fn verify(&self, param: Self::Param) -> bool {
         let poly_size = 1 << param.log_poly_size;
 
-        let variance_bsk =
-            minimal_variance_glwe(param.glwe_dim, poly_size, 64, self.security_level);
+        let variance_bsk = self.security_curves.minimal_variance_glwe(
+            param.glwe_dim,
+            poly_size,
+            64,
+            self.security_level,
+        );
         let v_pbs = variance_blind_rotate(
             param.big_lwe_dim(),
             param.glwe_dim,
@@ -132,7 +137,7 @@ impl KSFreeSearchSpace {
     }
 }
 
-pub fn solve_all_ksfree(p_fail: f64, writer: impl Write) {
+pub fn solve_all_ksfree(p_fail: f64, security_curves: &SecurityCurves, writer: impl Write) {
     let precisions = 1..9;
     let log_norms = vec![4, 6, 8, 10];
 
@@ -155,6 +160,7 @@ pub fn solve_all_ksfree(p_fail: f64, writer: impl Write) {
                             precision, 64, p_fail,
                         ),
                         log_norm2: log_norm,
+                        security_curves,
                         security_level: 128,
                         sum_size: 4096,
                     };
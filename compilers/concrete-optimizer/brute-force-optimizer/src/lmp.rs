@@ -9,7 +9,7 @@ use concrete_optimizer::parameters::{
     AtomicPatternParameters, BrDecompositionParameters, GlweParameters, KsDecompositionParameters,
     LweDimension,
 };
-use concrete_security_curves::gaussian::security::{minimal_variance_glwe, minimal_variance_lwe};
+use concrete_security_curves::gaussian::curves::SecurityCurves;
 use rayon::prelude::{IntoParallelIterator, ParallelIterator};
 use std::io::Write;
 use std::time::Instant;
@@ -35,19 +35,22 @@ impl LMPParams {
     }
 }
 
-struct LMPConstraint {
+struct LMPConstraint<'a> {
     variance_constraint: f64,
     log_norm2: u64,
+    security_curves: &'a SecurityCurves,
     security_level: u64,
     sum_size: u64,
 }
 
-impl Problem for LMPConstraint {
+impl Problem for LMPConstraint<'_> {
     type Param = LMPParams;
 
     fn verify(&self, param: Self::Param) -> bool {
         let poly_size = 1 << param.log_poly_size;
-        let variance_ksk = minimal_variance_lwe(param.small_lwe_dim, 64, self.security_level);
+        let variance_ksk =
+            self.security_curves
+                .minimal_variance_lwe(param.small_lwe_dim, 64, self.security_level);
 
         let v_ks = variance_keyswitch(
             param.big_lwe_dim(),
@@ -57,8 +60,12 @@ impl Problem for LMPConstraint {
             variance_ksk,
         );
 
-        let variance_bsk =
-            minimal_variance_glwe(param.glwe_dim, poly_size, 64, self.security_level);
+        let variance_bsk = self.security_curves.minimal_variance_glwe(
+            param.glwe_dim,
+            poly_size,
+            64,
+            self.security_level,
+        );
         let v_pbs = variance_blind_rotate(
             param.small_lwe_dim,
             param.glwe_dim,
@@ -227,7 +234,7 @@ impl LMPSearchSpace {
     }
 }
 
-pub fn solve_all_lmp(p_fail: f64, writer: impl Write) {
+pub fn solve_all_lmp(p_fail: f64, security_curves: &SecurityCurves, writer: impl Write) {
     let start = Instant::now();
     let precisions = 1..24;
     let log_norms = vec![4, 6, 8, 10];
@@ -259,6 +266,7 @@ pub fn solve_all_lmp(p_fail: f64, writer: impl Write) {
                             p_fail_per_pbs,
                         ), //5.960464477539063e-08, // 0.0009765625006088146,
                         log_norm2: log_norm,
+                        security_curves,
                         security_level: 128,
                         sum_size: 4096,
                     };
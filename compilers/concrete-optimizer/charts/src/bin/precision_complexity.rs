@@ -3,9 +3,10 @@ use concrete_optimizer::computing_cost::cpu::CpuComplexity;
 use concrete_optimizer::config;
 use concrete_optimizer::global_parameters::DEFAUT_DOMAINS;
 use concrete_optimizer::optimization::atomic_pattern::{self as optimize_atomic_pattern};
-use concrete_optimizer::optimization::config::{Config, SearchSpace};
+use concrete_optimizer::optimization::config::{Config, NoiseTracking, SearchSpace};
 use concrete_optimizer::optimization::decomposition;
 use concrete_optimizer::optimization::wop_atomic_pattern::optimize as optimize_wop_atomic_pattern;
+use concrete_optimizer::{ErrorProbabilityModel, ModulusSwitchingKind, NoiseMode, SecurityCurves};
 
 pub const _4_SIGMA: f64 = 1.0 - 0.999_936_657_516;
 const MIN_LOG_POLY_SIZE: u64 = DEFAUT_DOMAINS
@@ -38,6 +39,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         glwe_dimensions,
         internal_lwe_dimensions,
         levelled_only_lwe_dimensions: DEFAUT_DOMAINS.free_lwe,
+        modulus_switching_kinds: vec![ModulusSwitchingKind::Standard],
     };
 
     let config = Config {
@@ -48,16 +50,24 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         fft_precision,
         complexity_model: &CpuComplexity::default(),
         composable: false,
+        security_curves: SecurityCurves::compiled(),
+        noise_mode: NoiseMode::AverageCase,
+        error_probability_model: ErrorProbabilityModel::Gaussian,
+        dag_passes: &[],
+        refresh_compositions: false,
+        noise_tracking: NoiseTracking::PerTensor,
     };
 
     let cache = decomposition::cache(
         security_level,
+        SecurityCurves::compiled(),
         processing_unit,
         None,
         true,
         ciphertext_modulus_log,
         53,
-    );
+        NoiseMode::AverageCase,
+    )?;
 
     let solutions: Vec<_> = precisions
         .clone()
@@ -2,10 +2,13 @@ use concrete_optimizer::computing_cost::cpu::CpuComplexity;
 use concrete_optimizer::config;
 use concrete_optimizer::config::ProcessingUnit;
 use concrete_optimizer::dag::operator::{
-    self, FunctionTable, LevelledComplexity, OperatorIndex, Precision, Shape,
+    self, FunctionTable, Indices, LevelledComplexity, OperatorIndex, Precision, Shape,
 };
+use concrete_optimizer::dag::rewrite::expand::DEFAULT_ROUND_GROUP_BITS;
+use concrete_optimizer::dag::rewrite::passes::{Pass, ALL_PASSES};
 use concrete_optimizer::dag::unparametrized;
-use concrete_optimizer::optimization::config::{Config, SearchSpace};
+use concrete_optimizer::optimization;
+use concrete_optimizer::optimization::config::{Config, NoiseTracking, SearchSpace};
 use concrete_optimizer::optimization::dag::multi_parameters::keys_spec;
 use concrete_optimizer::optimization::dag::multi_parameters::keys_spec::CircuitSolution;
 use concrete_optimizer::optimization::dag::multi_parameters::partition_cut::PartitionCut;
@@ -13,25 +16,41 @@ use concrete_optimizer::optimization::dag::solo_key::optimize_generic::{
     Encoding, Solution as DagSolution,
 };
 use concrete_optimizer::optimization::decomposition;
+use concrete_optimizer::optimization::Err::InvalidDag;
 use concrete_optimizer::parameters::{BrDecompositionParameters, KsDecompositionParameters};
 use concrete_optimizer::utils::cache::persistent::default_cache_dir;
 use concrete_optimizer::utils::viz::Viz;
+use concrete_optimizer::{
+    CurvesError, ErrorProbabilityModel, ModulusSwitchingKind, NoiseMode, SecurityCurves,
+};
 
-fn no_solution() -> ffi::Solution {
+fn no_solution(error_msg: String) -> ffi::Solution {
     ffi::Solution {
         p_error: 1.0, // error probability to signal an impossible solution
+        error_msg,
         ..ffi::Solution::default()
     }
 }
 
-fn no_dag_solution() -> ffi::DagSolution {
+fn no_dag_solution(error_msg: String) -> ffi::DagSolution {
     ffi::DagSolution {
         p_error: 1.0, // error probability to signal an impossible solution
+        error_msg,
         ..ffi::DagSolution::default()
     }
 }
 
-fn caches_from(options: ffi::Options) -> decomposition::PersistDecompCaches {
+fn security_curves_from(options: &ffi::Options) -> Result<SecurityCurves, CurvesError> {
+    if options.security_curves_file.is_empty() {
+        return Ok(SecurityCurves::compiled().clone());
+    }
+    SecurityCurves::from_json_file(&options.security_curves_file)
+}
+
+fn caches_from(
+    options: &ffi::Options,
+    security_curves: &SecurityCurves,
+) -> Result<decomposition::PersistDecompCaches, optimization::Err> {
     if !options.cache_on_disk {
         println!("optimizer: Using stateless cache.");
         let cache_dir = default_cache_dir();
@@ -40,17 +59,23 @@ fn caches_from(options: ffi::Options) -> decomposition::PersistDecompCaches {
     let processing_unit = processing_unit(options);
     decomposition::cache(
         options.security_level,
+        security_curves,
         processing_unit,
         Some(ProcessingUnit::Cpu.complexity_model()),
         options.cache_on_disk,
         options.ciphertext_modulus_log,
         options.fft_precision,
+        NoiseMode::AverageCase,
     )
 }
 
 fn optimize_bootstrap(precision: u64, noise_factor: f64, options: ffi::Options) -> ffi::Solution {
     // Support composable since there is no dag
-    let processing_unit = processing_unit(options);
+    let processing_unit = processing_unit(&options);
+    let security_curves = match security_curves_from(&options) {
+        Ok(security_curves) => security_curves,
+        Err(err) => return no_solution(err.to_string()),
+    };
 
     let config = Config {
         security_level: options.security_level,
@@ -60,6 +85,20 @@ fn optimize_bootstrap(precision: u64, noise_factor: f64, options: ffi::Options)
         fft_precision: options.fft_precision,
         complexity_model: &CpuComplexity::default(),
         composable: options.composable,
+        security_curves: &security_curves,
+        noise_mode: NoiseMode::AverageCase,
+        error_probability_model: options.error_probability_model.into(),
+        dag_passes: &[],
+        refresh_compositions: options.refresh_compositions,
+        noise_tracking: options.noise_tracking.into(),
+    };
+    if let Err(err) = config.check_security() {
+        return no_solution(err.to_string());
+    }
+
+    let caches = match caches_from(&options, &security_curves) {
+        Ok(caches) => caches,
+        Err(err) => return no_solution(err.to_string()),
     };
 
     let sum_size = 1;
@@ -72,11 +111,12 @@ fn optimize_bootstrap(precision: u64, noise_factor: f64, options: ffi::Options)
         config,
         noise_factor,
         &search_space,
-        &caches_from(options),
+        &caches,
     );
-    result
-        .best_solution
-        .map_or_else(no_solution, |solution| solution.into())
+    result.best_solution.map_or_else(
+        || no_solution(optimization::Err::NoParametersFound.to_string()),
+        |solution| solution.into(),
+    )
 }
 
 fn convert_to_dag_solution(sol: &ffi::Solution) -> ffi::DagSolution {
@@ -104,12 +144,16 @@ impl From<&ffi::Solution> for ffi::DagSolution {
             pp_decomposition_level_count: 0,
             pp_decomposition_base_log: 0,
             crt_decomposition: vec![],
+            error_msg: sol.error_msg.clone(),
         }
     }
 }
 
 impl From<&ffi::CircuitSolution> for ffi::DagSolution {
     fn from(sol: &ffi::CircuitSolution) -> Self {
+        if !sol.is_feasible {
+            return no_dag_solution(sol.error_msg.clone());
+        }
         assert!(sol.circuit_keys.secret_keys.len() == 2);
         let keys = &sol.circuit_keys;
         let big_key = &keys.secret_keys[0];
@@ -153,6 +197,7 @@ impl From<&ffi::CircuitSolution> for ffi::DagSolution {
             pp_decomposition_level_count,
             pp_decomposition_base_log,
             crt_decomposition: sol.crt_decomposition.clone(),
+            error_msg: String::new(),
         }
     }
 }
@@ -171,6 +216,7 @@ impl From<concrete_optimizer::optimization::atomic_pattern::Solution> for ffi::S
             complexity: a.complexity,
             noise_max: a.noise_max,
             p_error: a.p_error,
+            error_msg: String::new(),
         }
     }
 }
@@ -197,6 +243,7 @@ impl From<DagSolution> for ffi::DagSolution {
                 pp_decomposition_level_count: 0,
                 pp_decomposition_base_log: 0,
                 crt_decomposition: vec![],
+                error_msg: String::new(),
             },
             DagSolution::WopSolution(sol) => Self {
                 input_lwe_dimension: sol.input_lwe_dimension,
@@ -217,6 +264,7 @@ impl From<DagSolution> for ffi::DagSolution {
                 pp_decomposition_level_count: sol.pp_decomposition_level_count,
                 pp_decomposition_base_log: sol.pp_decomposition_base_log,
                 crt_decomposition: sol.crt_decomposition,
+                error_msg: String::new(),
             },
         }
     }
@@ -253,6 +301,7 @@ fn convert_to_circuit_solution(sol: &ffi::DagSolution, dag: &Dag) -> ffi::Circui
             level: sol.br_decomposition_level_count,
             log2_base: sol.br_decomposition_base_log,
         },
+        modulus_switching: ffi::ModulusSwitching::Standard,
         description: "tlu bootstrap".into(),
     };
     let circuit_bootstrap_keys = if sol.use_wop_pbs {
@@ -295,6 +344,12 @@ fn convert_to_circuit_solution(sol: &ffi::DagSolution, dag: &Dag) -> ffi::Circui
         extra_conversion_keys: vec![],
     };
     let instructions_keys = vec![instruction_keys; dag.0.len()];
+    // the wop-pbs does not expand the rounds
+    let round_group_bits = if sol.use_wop_pbs {
+        vec![0; dag.0.len()]
+    } else {
+        keys_spec::round_group_bits(&dag.0, DEFAULT_ROUND_GROUP_BITS)
+    };
     let circuit_keys = ffi::CircuitKeys {
         secret_keys: [big_key, small_key].into(),
         keyswitch_keys: [keyswitch_key].into(),
@@ -305,14 +360,18 @@ fn convert_to_circuit_solution(sol: &ffi::DagSolution, dag: &Dag) -> ffi::Circui
     };
     let is_feasible = sol.p_error < 1.0;
     let error_msg = if is_feasible {
-        ""
+        String::new()
+    } else if sol.error_msg.is_empty() {
+        "No crypto-parameters for the given constraints".into()
     } else {
-        "No crypto-parameters for the given constraints"
-    }
-    .into();
+        sol.error_msg.clone()
+    };
     ffi::CircuitSolution {
         circuit_keys,
         instructions_keys,
+        round_group_bits,
+        simplified_dag: String::new(),
+        simplified_instructions: vec![],
         crt_decomposition: sol.crt_decomposition.clone(),
         complexity: sol.complexity,
         p_error: sol.p_error,
@@ -327,6 +386,9 @@ impl From<CircuitSolution> for ffi::CircuitSolution {
         Self {
             circuit_keys: v.circuit_keys.into(),
             instructions_keys: vec_into(v.instructions_keys),
+            round_group_bits: v.round_group_bits,
+            simplified_dag: v.simplified_dag,
+            simplified_instructions: v.simplified_instructions,
             crt_decomposition: v.crt_decomposition,
             complexity: v.complexity,
             p_error: v.p_error,
@@ -341,6 +403,8 @@ impl ffi::CircuitSolution {
     fn short_dump(&self) -> String {
         let mut new = self.clone();
         new.instructions_keys = vec![];
+        new.round_group_bits = vec![];
+        new.simplified_instructions = vec![];
         new.dump()
     }
     fn dump(&self) -> String {
@@ -409,6 +473,7 @@ impl From<keys_spec::BootstrapKey> for ffi::BootstrapKey {
             input_key: v.input_key.into(),
             output_key: v.output_key.into(),
             br_decomposition_parameter: v.br_decomposition_parameter.into(),
+            modulus_switching: v.modulus_switching.into(),
             description: v.description,
         }
     }
@@ -490,7 +555,11 @@ impl Dag {
     }
 
     fn optimize(&self, options: ffi::Options) -> ffi::DagSolution {
-        let processing_unit = processing_unit(options);
+        let processing_unit = processing_unit(&options);
+        let security_curves = match security_curves_from(&options) {
+            Ok(security_curves) => security_curves,
+            Err(err) => return no_dag_solution(err.to_string()),
+        };
         let config = Config {
             security_level: options.security_level,
             maximum_acceptable_error_probability: options.maximum_acceptable_error_probability,
@@ -499,9 +568,19 @@ impl Dag {
             fft_precision: options.fft_precision,
             complexity_model: &CpuComplexity::default(),
             composable: options.composable,
+            security_curves: &security_curves,
+            noise_mode: NoiseMode::AverageCase,
+            error_probability_model: options.error_probability_model.into(),
+            dag_passes: dag_passes(&options),
+            refresh_compositions: options.refresh_compositions,
+            noise_tracking: options.noise_tracking.into(),
         };
 
         let search_space = SearchSpace::default(processing_unit);
+        let caches = match caches_from(&options, &security_curves) {
+            Ok(caches) => caches,
+            Err(err) => return no_dag_solution(err.to_string()),
+        };
 
         let encoding = options.encoding.into();
         if options.composable {
@@ -512,7 +591,7 @@ impl Dag {
                     &search_space,
                     encoding,
                     options.default_log_norm2_woppbs,
-                    &caches_from(options),
+                    &caches,
                     &Some(PartitionCut::empty()),
                 );
             let circuit_sol: ffi::CircuitSolution = circuit_sol.into();
@@ -525,9 +604,9 @@ impl Dag {
                     &search_space,
                     encoding,
                     options.default_log_norm2_woppbs,
-                    &caches_from(options),
+                    &caches,
                 );
-            result.map_or_else(no_dag_solution, |solution| solution.into())
+            result.map_or_else(|err| no_dag_solution(err.to_string()), Into::into)
         }
     }
 
@@ -536,7 +615,14 @@ impl Dag {
     }
 
     fn optimize_multi(&self, options: ffi::Options) -> ffi::CircuitSolution {
-        let processing_unit = processing_unit(options);
+        // the partitionning analyses the dag, it must be valid
+        if let Err(err) = self.0.validate() {
+            return CircuitSolution::no_solution(InvalidDag(err).to_string()).into();
+        }
+        let security_curves = match security_curves_from(&options) {
+            Ok(security_curves) => security_curves,
+            Err(err) => return CircuitSolution::no_solution(err.to_string()).into(),
+        };
         let config = Config {
             security_level: options.security_level,
             maximum_acceptable_error_probability: options.maximum_acceptable_error_probability,
@@ -545,8 +631,18 @@ impl Dag {
             fft_precision: options.fft_precision,
             complexity_model: &CpuComplexity::default(),
             composable: options.composable,
+            security_curves: &security_curves,
+            noise_mode: NoiseMode::AverageCase,
+            error_probability_model: options.error_probability_model.into(),
+            dag_passes: dag_passes(&options),
+            refresh_compositions: options.refresh_compositions,
+            noise_tracking: options.noise_tracking.into(),
+        };
+        let search_space = multi_search_space(&options);
+        let caches = match caches_from(&options, &security_curves) {
+            Ok(caches) => caches,
+            Err(err) => return CircuitSolution::no_solution(err.to_string()).into(),
         };
-        let search_space = SearchSpace::default(processing_unit);
 
         let encoding = options.encoding.into();
         #[allow(clippy::wildcard_in_or_patterns)]
@@ -563,7 +659,7 @@ impl Dag {
                 &search_space,
                 encoding,
                 options.default_log_norm2_woppbs,
-                &caches_from(options),
+                &caches,
                 &Some(p_cut),
             );
         circuit_sol.into()
@@ -646,6 +742,122 @@ impl<'dag> DagBuilder<'dag> {
         self.0.add_unsafe_cast(input.into(), new_precision).into()
     }
 
+    fn add_bivariate_lut(
+        &mut self,
+        lhs: ffi::OperatorIndex,
+        rhs: ffi::OperatorIndex,
+        table: &[u64],
+        out_precision: Precision,
+    ) -> ffi::OperatorIndex {
+        let table = FunctionTable {
+            values: table.to_owned(),
+        };
+
+        self.0
+            .add_bivariate_lut(lhs.into(), rhs.into(), table, out_precision)
+            .into()
+    }
+
+    fn add_mul(
+        &mut self,
+        lhs: ffi::OperatorIndex,
+        rhs: ffi::OperatorIndex,
+        out_precision: Precision,
+    ) -> ffi::OperatorIndex {
+        self.0.add_mul(lhs.into(), rhs.into(), out_precision).into()
+    }
+
+    fn add_max_pool(&mut self, input: ffi::OperatorIndex, window: &[u64]) -> ffi::OperatorIndex {
+        let window = Shape {
+            dimensions_size: window.to_owned(),
+        };
+
+        self.0.add_max_pool(input.into(), window).into()
+    }
+
+    fn add_reshape(&mut self, input: ffi::OperatorIndex, out_shape: &[u64]) -> ffi::OperatorIndex {
+        let out_shape = Shape {
+            dimensions_size: out_shape.to_owned(),
+        };
+
+        self.0.add_reshape(input.into(), out_shape).into()
+    }
+
+    fn add_transpose(&mut self, input: ffi::OperatorIndex, axes: &[usize]) -> ffi::OperatorIndex {
+        self.0.add_transpose(input.into(), axes).into()
+    }
+
+    fn add_concatenate(
+        &mut self,
+        inputs: &[ffi::OperatorIndex],
+        axis: usize,
+    ) -> ffi::OperatorIndex {
+        let inputs: Vec<OperatorIndex> = inputs.iter().copied().map(Into::into).collect();
+
+        self.0.add_concatenate(inputs, axis).into()
+    }
+
+    fn add_slice(
+        &mut self,
+        input: ffi::OperatorIndex,
+        offsets: &[u64],
+        sizes: &[u64],
+        strides: &[u64],
+    ) -> ffi::OperatorIndex {
+        self.0
+            .add_slice(input.into(), offsets, sizes, strides)
+            .into()
+    }
+
+    fn add_gather(
+        &mut self,
+        input: ffi::OperatorIndex,
+        axis: usize,
+        indices_shape: &[u64],
+        indices: &[u64],
+    ) -> ffi::OperatorIndex {
+        let indices = Indices {
+            shape: Shape {
+                dimensions_size: indices_shape.to_owned(),
+            },
+            values: indices.to_owned(),
+        };
+
+        self.0.add_gather(input.into(), axis, indices).into()
+    }
+
+    fn add_broadcast(
+        &mut self,
+        input: ffi::OperatorIndex,
+        out_shape: &[u64],
+    ) -> ffi::OperatorIndex {
+        let out_shape = Shape {
+            dimensions_size: out_shape.to_owned(),
+        };
+
+        self.0.add_broadcast(input.into(), out_shape).into()
+    }
+
+    #[allow(clippy::boxed_local)]
+    fn add_conv2d(
+        &mut self,
+        input: ffi::OperatorIndex,
+        weights: Box<Weights>,
+        strides: &[u64],
+        padding: &[u64],
+        dilations: &[u64],
+        groups: u64,
+    ) -> ffi::OperatorIndex {
+        // attributes of the wrong length are replaced by values the validation reports
+        let strides = strides.try_into().unwrap_or([0; 2]);
+        let padding = padding.try_into().unwrap_or([u64::MAX; 4]);
+        let dilations = dilations.try_into().unwrap_or([0; 2]);
+
+        self.0
+            .add_conv2d(input.into(), weights.0, strides, padding, dilations, groups)
+            .into()
+    }
+
     fn tag_operator_as_output(&mut self, op: ffi::OperatorIndex) {
         self.0.tag_operator_as_output(op.into());
     }
@@ -665,6 +877,15 @@ fn number(weight: i64) -> Box<Weights> {
     Box::new(Weights(operator::Weights::number(weight)))
 }
 
+fn tensor(shape: &[u64], weights: &[i64]) -> Box<Weights> {
+    Box::new(Weights(operator::Weights {
+        shape: Shape {
+            dimensions_size: shape.to_owned(),
+        },
+        values: weights.to_owned(),
+    }))
+}
+
 impl From<OperatorIndex> for ffi::OperatorIndex {
     fn from(oi: OperatorIndex) -> Self {
         Self { index: oi.0 }
@@ -690,6 +911,50 @@ impl Into<Encoding> for ffi::Encoding {
     }
 }
 
+#[allow(clippy::from_over_into)]
+impl Into<NoiseTracking> for ffi::NoiseTracking {
+    fn into(self) -> NoiseTracking {
+        match self {
+            Self::PerTensor => NoiseTracking::PerTensor,
+            Self::PerElementClass => NoiseTracking::PerElementClass,
+            _ => unreachable!("Internal error: Invalid noise tracking"),
+        }
+    }
+}
+
+#[allow(clippy::from_over_into)]
+impl Into<ModulusSwitchingKind> for ffi::ModulusSwitching {
+    fn into(self) -> ModulusSwitchingKind {
+        match self {
+            Self::Standard => ModulusSwitchingKind::Standard,
+            Self::CenteredMean => ModulusSwitchingKind::CenteredMean,
+            Self::ShiftedKey => ModulusSwitchingKind::ShiftedKey,
+            _ => unreachable!("Internal error: Invalid modulus switching"),
+        }
+    }
+}
+
+#[allow(clippy::from_over_into)]
+impl Into<ErrorProbabilityModel> for ffi::ErrorProbabilityModel {
+    fn into(self) -> ErrorProbabilityModel {
+        match self {
+            Self::Gaussian => ErrorProbabilityModel::Gaussian,
+            Self::ExactTail => ErrorProbabilityModel::ExactTail,
+            _ => unreachable!("Internal error: Invalid error probability model"),
+        }
+    }
+}
+
+impl From<ModulusSwitchingKind> for ffi::ModulusSwitching {
+    fn from(v: ModulusSwitchingKind) -> Self {
+        match v {
+            ModulusSwitchingKind::Standard => Self::Standard,
+            ModulusSwitchingKind::CenteredMean => Self::CenteredMean,
+            ModulusSwitchingKind::ShiftedKey => Self::ShiftedKey,
+        }
+    }
+}
+
 #[allow(unused_must_use, clippy::needless_lifetimes)]
 #[cxx::bridge]
 mod ffi {
@@ -759,6 +1024,77 @@ mod ffi {
             rounded_precision: u8,
         ) -> OperatorIndex;
 
+        unsafe fn add_bivariate_lut(
+            self: &mut DagBuilder<'_>,
+            lhs: OperatorIndex,
+            rhs: OperatorIndex,
+            table: &[u64],
+            out_precision: u8,
+        ) -> OperatorIndex;
+
+        unsafe fn add_mul(
+            self: &mut DagBuilder<'_>,
+            lhs: OperatorIndex,
+            rhs: OperatorIndex,
+            out_precision: u8,
+        ) -> OperatorIndex;
+
+        unsafe fn add_max_pool(
+            self: &mut DagBuilder<'_>,
+            input: OperatorIndex,
+            window: &[u64],
+        ) -> OperatorIndex;
+
+        unsafe fn add_reshape(
+            self: &mut DagBuilder<'_>,
+            input: OperatorIndex,
+            out_shape: &[u64],
+        ) -> OperatorIndex;
+
+        unsafe fn add_transpose(
+            self: &mut DagBuilder<'_>,
+            input: OperatorIndex,
+            axes: &[usize],
+        ) -> OperatorIndex;
+
+        unsafe fn add_concatenate(
+            self: &mut DagBuilder<'_>,
+            inputs: &[OperatorIndex],
+            axis: usize,
+        ) -> OperatorIndex;
+
+        unsafe fn add_slice(
+            self: &mut DagBuilder<'_>,
+            input: OperatorIndex,
+            offsets: &[u64],
+            sizes: &[u64],
+            strides: &[u64],
+        ) -> OperatorIndex;
+
+        unsafe fn add_gather(
+            self: &mut DagBuilder<'_>,
+            input: OperatorIndex,
+            axis: usize,
+            indices_shape: &[u64],
+            indices: &[u64],
+        ) -> OperatorIndex;
+
+        unsafe fn add_broadcast(
+            self: &mut DagBuilder<'_>,
+            input: OperatorIndex,
+            out_shape: &[u64],
+        ) -> OperatorIndex;
+
+        unsafe fn add_conv2d(
+            self: &mut DagBuilder<'_>,
+            input: OperatorIndex,
+            weights: Box<Weights>,
+            strides: &[u64],
+            padding: &[u64],
+            dilations: &[u64],
+            groups: u64,
+        ) -> OperatorIndex;
+
         unsafe fn tag_operator_as_output(self: &mut DagBuilder<'_>, op: OperatorIndex);
 
         fn optimize(self: &Dag, options: Options) -> DagSolution;
@@ -777,6 +1113,9 @@ mod ffi {
         #[namespace = "concrete_optimizer::weights"]
         fn number(weight: i64) -> Box<Weights>;
 
+        #[namespace = "concrete_optimizer::weights"]
+        fn tensor(shape: &[u64], weights: &[i64]) -> Box<Weights>;
+
         fn get_circuit_count(self: &Dag) -> usize;
 
         fn optimize_multi(self: &Dag, options: Options) -> CircuitSolution;
@@ -799,7 +1138,7 @@ mod ffi {
     }
 
     #[namespace = "concrete_optimizer::v0"]
-    #[derive(Debug, Clone, Copy, Default)]
+    #[derive(Debug, Clone, Default)]
     pub struct Solution {
         pub input_lwe_dimension: u64,              //n_big
         pub internal_ks_output_lwe_dimension: u64, //n_small
@@ -812,6 +1151,7 @@ mod ffi {
         pub complexity: f64,
         pub noise_max: f64,
         pub p_error: f64, // error probability
+        pub error_msg: String,
     }
 
     #[namespace = "concrete_optimizer::dag"]
@@ -835,6 +1175,7 @@ mod ffi {
         pub pp_decomposition_level_count: u64,
         pub pp_decomposition_base_log: u64,
         pub crt_decomposition: Vec<u64>,
+        pub error_msg: String,
     }
 
     #[derive(Debug, Clone, Copy)]
@@ -844,8 +1185,30 @@ mod ffi {
         ByPrecisionAndNorm2,
     }
 
+    #[derive(Debug, Clone, Copy)]
+    #[namespace = "concrete_optimizer"]
+    pub enum NoiseTracking {
+        PerTensor,
+        PerElementClass,
+    }
+
+    #[derive(Debug, Clone, Copy)]
     #[namespace = "concrete_optimizer"]
+    pub enum ModulusSwitching {
+        Standard,
+        CenteredMean,
+        ShiftedKey,
+    }
+
     #[derive(Debug, Clone, Copy)]
+    #[namespace = "concrete_optimizer"]
+    pub enum ErrorProbabilityModel {
+        Gaussian,
+        ExactTail,
+    }
+
+    #[namespace = "concrete_optimizer"]
+    #[derive(Debug, Clone)]
     pub struct Options {
         pub security_level: u64,
         pub maximum_acceptable_error_probability: f64,
@@ -858,6 +1221,15 @@ mod ffi {
         pub ciphertext_modulus_log: u32,
         pub fft_precision: u32,
         pub composable: bool,
+        pub noise_tracking: NoiseTracking,
+        /* the exact tail is only supported by optimize_bootstrap and the solo key optimization */
+        pub error_probability_model: ErrorProbabilityModel,
+        pub security_curves_file: String,
+        pub simplify_dag: bool,
+        /* identity luts making the compositions refreshed, only for the multi parameters optimization */
+        pub refresh_compositions: bool,
+        /* modulus switchings optimize_multi chooses from, empty for the standard one only */
+        pub modulus_switching_kinds: Vec<ModulusSwitching>,
     }
 
     #[namespace = "concrete_optimizer::dag"]
@@ -891,6 +1263,7 @@ mod ffi {
         pub input_key: SecretLweKey,
         pub output_key: SecretLweKey,
         pub br_decomposition_parameter: BrDecompositionParameters,
+        pub modulus_switching: ModulusSwitching,
         pub description: String,
     }
 
@@ -960,6 +1333,9 @@ mod ffi {
     pub struct CircuitSolution {
         pub circuit_keys: CircuitKeys,
         pub instructions_keys: Vec<InstructionKeys>,
+        pub round_group_bits: Vec<u8>,
+        pub simplified_dag: String,
+        pub simplified_instructions: Vec<u64>,
         pub crt_decomposition: Vec<u64>,
         pub complexity: f64,
         pub p_error: f64,
@@ -969,7 +1345,27 @@ mod ffi {
     }
 }
 
-fn processing_unit(options: ffi::Options) -> ProcessingUnit {
+fn dag_passes(options: &ffi::Options) -> &'static [Pass] {
+    if options.simplify_dag {
+        &ALL_PASSES
+    } else {
+        &[]
+    }
+}
+
+fn multi_search_space(options: &ffi::Options) -> SearchSpace {
+    let mut search_space = SearchSpace::default(processing_unit(options));
+    if !options.modulus_switching_kinds.is_empty() {
+        search_space.modulus_switching_kinds = options
+            .modulus_switching_kinds
+            .iter()
+            .map(|&kind| kind.into())
+            .collect();
+    }
+    search_space
+}
+
+fn processing_unit(options: &ffi::Options) -> ProcessingUnit {
     if options.use_gpu_constraints {
         config::ProcessingUnit::Gpu {
             pbs_type: config::GpuPbsType::Amortized,
@@ -928,6 +928,13 @@ struct operator_new<T, decltype(T::operator new(sizeof(T)))> {
 };
 } // namespace detail
 
+template <typename T>
+union ManuallyDrop {
+  T value;
+  ManuallyDrop(T &&value) : value(::std::move(value)) {}
+  ~ManuallyDrop() {}
+};
+
 template <typename T>
 union MaybeUninit {
   T value;
@@ -946,6 +953,9 @@ namespace concrete_optimizer {
   struct Weights;
   enum class Encoding : ::std::uint8_t;
   enum class MultiParamStrategy : ::std::uint8_t;
+  enum class NoiseTracking : ::std::uint8_t;
+  enum class ModulusSwitching : ::std::uint8_t;
+  enum class ErrorProbabilityModel : ::std::uint8_t;
   struct Options;
   namespace dag {
     struct OperatorIndex;
@@ -995,6 +1005,16 @@ struct DagBuilder final : public ::rust::Opaque {
   ::concrete_optimizer::dag::OperatorIndex add_levelled_op(::rust::Slice<::concrete_optimizer::dag::OperatorIndex const> inputs, double lwe_dim_cost_factor, double fixed_cost, double manp, ::rust::Slice<::std::uint64_t const> out_shape, ::rust::Str comment) noexcept;
   ::concrete_optimizer::dag::OperatorIndex add_round_op(::concrete_optimizer::dag::OperatorIndex input, ::std::uint8_t rounded_precision) noexcept;
   ::concrete_optimizer::dag::OperatorIndex add_unsafe_cast_op(::concrete_optimizer::dag::OperatorIndex input, ::std::uint8_t rounded_precision) noexcept;
+  ::concrete_optimizer::dag::OperatorIndex add_bivariate_lut(::concrete_optimizer::dag::OperatorIndex lhs, ::concrete_optimizer::dag::OperatorIndex rhs, ::rust::Slice<::std::uint64_t const> table, ::std::uint8_t out_precision) noexcept;
+  ::concrete_optimizer::dag::OperatorIndex add_mul(::concrete_optimizer::dag::OperatorIndex lhs, ::concrete_optimizer::dag::OperatorIndex rhs, ::std::uint8_t out_precision) noexcept;
+  ::concrete_optimizer::dag::OperatorIndex add_max_pool(::concrete_optimizer::dag::OperatorIndex input, ::rust::Slice<::std::uint64_t const> window) noexcept;
+  ::concrete_optimizer::dag::OperatorIndex add_reshape(::concrete_optimizer::dag::OperatorIndex input, ::rust::Slice<::std::uint64_t const> out_shape) noexcept;
+  ::concrete_optimizer::dag::OperatorIndex add_transpose(::concrete_optimizer::dag::OperatorIndex input, ::rust::Slice<::std::size_t const> axes) noexcept;
+  ::concrete_optimizer::dag::OperatorIndex add_concatenate(::rust::Slice<::concrete_optimizer::dag::OperatorIndex const> inputs, ::std::size_t axis) noexcept;
+  ::concrete_optimizer::dag::OperatorIndex add_slice(::concrete_optimizer::dag::OperatorIndex input, ::rust::Slice<::std::uint64_t const> offsets, ::rust::Slice<::std::uint64_t const> sizes, ::rust::Slice<::std::uint64_t const> strides) noexcept;
+  ::concrete_optimizer::dag::OperatorIndex add_gather(::concrete_optimizer::dag::OperatorIndex input, ::std::size_t axis, ::rust::Slice<::std::uint64_t const> indices_shape, ::rust::Slice<::std::uint64_t const> indices) noexcept;
+  ::concrete_optimizer::dag::OperatorIndex add_broadcast(::concrete_optimizer::dag::OperatorIndex input, ::rust::Slice<::std::uint64_t const> out_shape) noexcept;
+  ::concrete_optimizer::dag::OperatorIndex add_conv2d(::concrete_optimizer::dag::OperatorIndex input, ::rust::Box<::concrete_optimizer::Weights> weights, ::rust::Slice<::std::uint64_t const> strides, ::rust::Slice<::std::uint64_t const> padding, ::rust::Slice<::std::uint64_t const> dilations, ::std::uint64_t groups) noexcept;
   void tag_operator_as_output(::concrete_optimizer::dag::OperatorIndex op) noexcept;
   ~DagBuilder() = delete;
 
@@ -1056,6 +1076,7 @@ struct Solution final {
   double complexity;
   double noise_max;
   double p_error;
+  ::rust::String error_msg;
 
   using IsRelocatable = ::std::true_type;
 };
@@ -1084,6 +1105,7 @@ struct DagSolution final {
   ::std::uint64_t pp_decomposition_level_count;
   ::std::uint64_t pp_decomposition_base_log;
   ::rust::Vec<::std::uint64_t> crt_decomposition;
+  ::rust::String error_msg;
 
   using IsRelocatable = ::std::true_type;
 };
@@ -1098,6 +1120,31 @@ enum class MultiParamStrategy : ::std::uint8_t {
 };
 #endif // CXXBRIDGE1_ENUM_concrete_optimizer$MultiParamStrategy
 
+#ifndef CXXBRIDGE1_ENUM_concrete_optimizer$NoiseTracking
+#define CXXBRIDGE1_ENUM_concrete_optimizer$NoiseTracking
+enum class NoiseTracking : ::std::uint8_t {
+  PerTensor = 0,
+  PerElementClass = 1,
+};
+#endif // CXXBRIDGE1_ENUM_concrete_optimizer$NoiseTracking
+
+#ifndef CXXBRIDGE1_ENUM_concrete_optimizer$ModulusSwitching
+#define CXXBRIDGE1_ENUM_concrete_optimizer$ModulusSwitching
+enum class ModulusSwitching : ::std::uint8_t {
+  Standard = 0,
+  CenteredMean = 1,
+  ShiftedKey = 2,
+};
+#endif // CXXBRIDGE1_ENUM_concrete_optimizer$ModulusSwitching
+
+#ifndef CXXBRIDGE1_ENUM_concrete_optimizer$ErrorProbabilityModel
+#define CXXBRIDGE1_ENUM_concrete_optimizer$ErrorProbabilityModel
+enum class ErrorProbabilityModel : ::std::uint8_t {
+  Gaussian = 0,
+  ExactTail = 1,
+};
+#endif // CXXBRIDGE1_ENUM_concrete_optimizer$ErrorProbabilityModel
+
 #ifndef CXXBRIDGE1_STRUCT_concrete_optimizer$Options
 #define CXXBRIDGE1_STRUCT_concrete_optimizer$Options
 struct Options final {
@@ -1112,6 +1159,12 @@ struct Options final {
   ::std::uint32_t ciphertext_modulus_log;
   ::std::uint32_t fft_precision;
   bool composable;
+  ::concrete_optimizer::NoiseTracking noise_tracking;
+  ::concrete_optimizer::ErrorProbabilityModel error_probability_model;
+  ::rust::String security_curves_file;
+  bool simplify_dag;
+  bool refresh_compositions;
+  ::rust::Vec<::concrete_optimizer::ModulusSwitching> modulus_switching_kinds;
 
   using IsRelocatable = ::std::true_type;
 };
@@ -1157,6 +1210,7 @@ struct BootstrapKey final {
   ::concrete_optimizer::dag::SecretLweKey input_key;
   ::concrete_optimizer::dag::SecretLweKey output_key;
   ::concrete_optimizer::dag::BrDecompositionParameters br_decomposition_parameter;
+  ::concrete_optimizer::ModulusSwitching modulus_switching;
   ::rust::String description;
 
   using IsRelocatable = ::std::true_type;
@@ -1252,6 +1306,9 @@ struct InstructionKeys final {
 struct CircuitSolution final {
   ::CircuitKeys circuit_keys;
   ::rust::Vec<::concrete_optimizer::dag::InstructionKeys> instructions_keys;
+  ::rust::Vec<::std::uint8_t> round_group_bits;
+  ::rust::String simplified_dag;
+  ::rust::Vec<::std::uint64_t> simplified_instructions;
   ::rust::Vec<::std::uint64_t> crt_decomposition;
   double complexity;
   double p_error;
@@ -1268,7 +1325,7 @@ struct CircuitSolution final {
 
 namespace v0 {
 extern "C" {
-::concrete_optimizer::v0::Solution concrete_optimizer$v0$cxxbridge1$optimize_bootstrap(::std::uint64_t precision, double noise_factor, ::concrete_optimizer::Options options) noexcept;
+void concrete_optimizer$v0$cxxbridge1$optimize_bootstrap(::std::uint64_t precision, double noise_factor, ::concrete_optimizer::Options *options, ::concrete_optimizer::v0::Solution *return$) noexcept;
 } // extern "C"
 } // namespace v0
 
@@ -1312,9 +1369,29 @@ void concrete_optimizer$cxxbridge1$DagBuilder$dump(::concrete_optimizer::DagBuil
 
 ::concrete_optimizer::dag::OperatorIndex concrete_optimizer$cxxbridge1$DagBuilder$add_unsafe_cast_op(::concrete_optimizer::DagBuilder &self, ::concrete_optimizer::dag::OperatorIndex input, ::std::uint8_t rounded_precision) noexcept;
 
+::concrete_optimizer::dag::OperatorIndex concrete_optimizer$cxxbridge1$DagBuilder$add_bivariate_lut(::concrete_optimizer::DagBuilder &self, ::concrete_optimizer::dag::OperatorIndex lhs, ::concrete_optimizer::dag::OperatorIndex rhs, ::rust::Slice<::std::uint64_t const> table, ::std::uint8_t out_precision) noexcept;
+
+::concrete_optimizer::dag::OperatorIndex concrete_optimizer$cxxbridge1$DagBuilder$add_mul(::concrete_optimizer::DagBuilder &self, ::concrete_optimizer::dag::OperatorIndex lhs, ::concrete_optimizer::dag::OperatorIndex rhs, ::std::uint8_t out_precision) noexcept;
+
+::concrete_optimizer::dag::OperatorIndex concrete_optimizer$cxxbridge1$DagBuilder$add_max_pool(::concrete_optimizer::DagBuilder &self, ::concrete_optimizer::dag::OperatorIndex input, ::rust::Slice<::std::uint64_t const> window) noexcept;
+
+::concrete_optimizer::dag::OperatorIndex concrete_optimizer$cxxbridge1$DagBuilder$add_reshape(::concrete_optimizer::DagBuilder &self, ::concrete_optimizer::dag::OperatorIndex input, ::rust::Slice<::std::uint64_t const> out_shape) noexcept;
+
+::concrete_optimizer::dag::OperatorIndex concrete_optimizer$cxxbridge1$DagBuilder$add_transpose(::concrete_optimizer::DagBuilder &self, ::concrete_optimizer::dag::OperatorIndex input, ::rust::Slice<::std::size_t const> axes) noexcept;
+
+::concrete_optimizer::dag::OperatorIndex concrete_optimizer$cxxbridge1$DagBuilder$add_concatenate(::concrete_optimizer::DagBuilder &self, ::rust::Slice<::concrete_optimizer::dag::OperatorIndex const> inputs, ::std::size_t axis) noexcept;
+
+::concrete_optimizer::dag::OperatorIndex concrete_optimizer$cxxbridge1$DagBuilder$add_slice(::concrete_optimizer::DagBuilder &self, ::concrete_optimizer::dag::OperatorIndex input, ::rust::Slice<::std::uint64_t const> offsets, ::rust::Slice<::std::uint64_t const> sizes, ::rust::Slice<::std::uint64_t const> strides) noexcept;
+
+::concrete_optimizer::dag::OperatorIndex concrete_optimizer$cxxbridge1$DagBuilder$add_gather(::concrete_optimizer::DagBuilder &self, ::concrete_optimizer::dag::OperatorIndex input, ::std::size_t axis, ::rust::Slice<::std::uint64_t const> indices_shape, ::rust::Slice<::std::uint64_t const> indices) noexcept;
+
+::concrete_optimizer::dag::OperatorIndex concrete_optimizer$cxxbridge1$DagBuilder$add_broadcast(::concrete_optimizer::DagBuilder &self, ::concrete_optimizer::dag::OperatorIndex input, ::rust::Slice<::std::uint64_t const> out_shape) noexcept;
+
+::concrete_optimizer::dag::OperatorIndex concrete_optimizer$cxxbridge1$DagBuilder$add_conv2d(::concrete_optimizer::DagBuilder &self, ::concrete_optimizer::dag::OperatorIndex input, ::concrete_optimizer::Weights *weights, ::rust::Slice<::std::uint64_t const> strides, ::rust::Slice<::std::uint64_t const> padding, ::rust::Slice<::std::uint64_t const> dilations, ::std::uint64_t groups) noexcept;
+
 void concrete_optimizer$cxxbridge1$DagBuilder$tag_operator_as_output(::concrete_optimizer::DagBuilder &self, ::concrete_optimizer::dag::OperatorIndex op) noexcept;
 
-void concrete_optimizer$cxxbridge1$Dag$optimize(::concrete_optimizer::Dag const &self, ::concrete_optimizer::Options options, ::concrete_optimizer::dag::DagSolution *return$) noexcept;
+void concrete_optimizer$cxxbridge1$Dag$optimize(::concrete_optimizer::Dag const &self, ::concrete_optimizer::Options *options, ::concrete_optimizer::dag::DagSolution *return$) noexcept;
 } // extern "C"
 
 namespace dag {
@@ -1335,20 +1412,25 @@ extern "C" {
 ::concrete_optimizer::Weights *concrete_optimizer$weights$cxxbridge1$vector(::rust::Slice<::std::int64_t const> weights) noexcept;
 
 ::concrete_optimizer::Weights *concrete_optimizer$weights$cxxbridge1$number(::std::int64_t weight) noexcept;
+
+::concrete_optimizer::Weights *concrete_optimizer$weights$cxxbridge1$tensor(::rust::Slice<::std::uint64_t const> shape, ::rust::Slice<::std::int64_t const> weights) noexcept;
 } // extern "C"
 } // namespace weights
 
 extern "C" {
 ::std::size_t concrete_optimizer$cxxbridge1$Dag$get_circuit_count(::concrete_optimizer::Dag const &self) noexcept;
 
-void concrete_optimizer$cxxbridge1$Dag$optimize_multi(::concrete_optimizer::Dag const &self, ::concrete_optimizer::Options options, ::concrete_optimizer::dag::CircuitSolution *return$) noexcept;
+void concrete_optimizer$cxxbridge1$Dag$optimize_multi(::concrete_optimizer::Dag const &self, ::concrete_optimizer::Options *options, ::concrete_optimizer::dag::CircuitSolution *return$) noexcept;
 
 ::std::uint64_t concrete_optimizer$cxxbridge1$NO_KEY_ID() noexcept;
 } // extern "C"
 
 namespace v0 {
 ::concrete_optimizer::v0::Solution optimize_bootstrap(::std::uint64_t precision, double noise_factor, ::concrete_optimizer::Options options) noexcept {
-  return concrete_optimizer$v0$cxxbridge1$optimize_bootstrap(precision, noise_factor, options);
+  ::rust::ManuallyDrop<::concrete_optimizer::Options> options$(::std::move(options));
+  ::rust::MaybeUninit<::concrete_optimizer::v0::Solution> return$;
+  concrete_optimizer$v0$cxxbridge1$optimize_bootstrap(precision, noise_factor, &options$.value, &return$.value);
+  return ::std::move(return$.value);
 }
 } // namespace v0
 
@@ -1428,13 +1510,54 @@ namespace dag {
   return concrete_optimizer$cxxbridge1$DagBuilder$add_unsafe_cast_op(*this, input, rounded_precision);
 }
 
+::concrete_optimizer::dag::OperatorIndex DagBuilder::add_bivariate_lut(::concrete_optimizer::dag::OperatorIndex lhs, ::concrete_optimizer::dag::OperatorIndex rhs, ::rust::Slice<::std::uint64_t const> table, ::std::uint8_t out_precision) noexcept {
+  return concrete_optimizer$cxxbridge1$DagBuilder$add_bivariate_lut(*this, lhs, rhs, table, out_precision);
+}
+
+::concrete_optimizer::dag::OperatorIndex DagBuilder::add_mul(::concrete_optimizer::dag::OperatorIndex lhs, ::concrete_optimizer::dag::OperatorIndex rhs, ::std::uint8_t out_precision) noexcept {
+  return concrete_optimizer$cxxbridge1$DagBuilder$add_mul(*this, lhs, rhs, out_precision);
+}
+
+::concrete_optimizer::dag::OperatorIndex DagBuilder::add_max_pool(::concrete_optimizer::dag::OperatorIndex input, ::rust::Slice<::std::uint64_t const> window) noexcept {
+  return concrete_optimizer$cxxbridge1$DagBuilder$add_max_pool(*this, input, window);
+}
+
+::concrete_optimizer::dag::OperatorIndex DagBuilder::add_reshape(::concrete_optimizer::dag::OperatorIndex input, ::rust::Slice<::std::uint64_t const> out_shape) noexcept {
+  return concrete_optimizer$cxxbridge1$DagBuilder$add_reshape(*this, input, out_shape);
+}
+
+::concrete_optimizer::dag::OperatorIndex DagBuilder::add_transpose(::concrete_optimizer::dag::OperatorIndex input, ::rust::Slice<::std::size_t const> axes) noexcept {
+  return concrete_optimizer$cxxbridge1$DagBuilder$add_transpose(*this, input, axes);
+}
+
+::concrete_optimizer::dag::OperatorIndex DagBuilder::add_concatenate(::rust::Slice<::concrete_optimizer::dag::OperatorIndex const> inputs, ::std::size_t axis) noexcept {
+  return concrete_optimizer$cxxbridge1$DagBuilder$add_concatenate(*this, inputs, axis);
+}
+
+::concrete_optimizer::dag::OperatorIndex DagBuilder::add_slice(::concrete_optimizer::dag::OperatorIndex input, ::rust::Slice<::std::uint64_t const> offsets, ::rust::Slice<::std::uint64_t const> sizes, ::rust::Slice<::std::uint64_t const> strides) noexcept {
+  return concrete_optimizer$cxxbridge1$DagBuilder$add_slice(*this, input, offsets, sizes, strides);
+}
+
+::concrete_optimizer::dag::OperatorIndex DagBuilder::add_gather(::concrete_optimizer::dag::OperatorIndex input, ::std::size_t axis, ::rust::Slice<::std::uint64_t const> indices_shape, ::rust::Slice<::std::uint64_t const> indices) noexcept {
+  return concrete_optimizer$cxxbridge1$DagBuilder$add_gather(*this, input, axis, indices_shape, indices);
+}
+
+::concrete_optimizer::dag::OperatorIndex DagBuilder::add_broadcast(::concrete_optimizer::dag::OperatorIndex input, ::rust::Slice<::std::uint64_t const> out_shape) noexcept {
+  return concrete_optimizer$cxxbridge1$DagBuilder$add_broadcast(*this, input, out_shape);
+}
+
+::concrete_optimizer::dag::OperatorIndex DagBuilder::add_conv2d(::concrete_optimizer::dag::OperatorIndex input, ::rust::Box<::concrete_optimizer::Weights> weights, ::rust::Slice<::std::uint64_t const> strides, ::rust::Slice<::std::uint64_t const> padding, ::rust::Slice<::std::uint64_t const> dilations, ::std::uint64_t groups) noexcept {
+  return concrete_optimizer$cxxbridge1$DagBuilder$add_conv2d(*this, input, weights.into_raw(), strides, padding, dilations, groups);
+}
+
 void DagBuilder::tag_operator_as_output(::concrete_optimizer::dag::OperatorIndex op) noexcept {
   concrete_optimizer$cxxbridge1$DagBuilder$tag_operator_as_output(*this, op);
 }
 
 ::concrete_optimizer::dag::DagSolution Dag::optimize(::concrete_optimizer::Options options) const noexcept {
+  ::rust::ManuallyDrop<::concrete_optimizer::Options> options$(::std::move(options));
   ::rust::MaybeUninit<::concrete_optimizer::dag::DagSolution> return$;
-  concrete_optimizer$cxxbridge1$Dag$optimize(*this, options, &return$.value);
+  concrete_optimizer$cxxbridge1$Dag$optimize(*this, &options$.value, &return$.value);
   return ::std::move(return$.value);
 }
 
@@ -1468,6 +1591,10 @@ namespace weights {
 ::rust::Box<::concrete_optimizer::Weights> number(::std::int64_t weight) noexcept {
   return ::rust::Box<::concrete_optimizer::Weights>::from_raw(concrete_optimizer$weights$cxxbridge1$number(weight));
 }
+
+::rust::Box<::concrete_optimizer::Weights> tensor(::rust::Slice<::std::uint64_t const> shape, ::rust::Slice<::std::int64_t const> weights) noexcept {
+  return ::rust::Box<::concrete_optimizer::Weights>::from_raw(concrete_optimizer$weights$cxxbridge1$tensor(shape, weights));
+}
 } // namespace weights
 
 ::std::size_t Dag::get_circuit_count() const noexcept {
@@ -1475,8 +1602,9 @@ namespace weights {
 }
 
 ::concrete_optimizer::dag::CircuitSolution Dag::optimize_multi(::concrete_optimizer::Options options) const noexcept {
+  ::rust::ManuallyDrop<::concrete_optimizer::Options> options$(::std::move(options));
   ::rust::MaybeUninit<::concrete_optimizer::dag::CircuitSolution> return$;
-  concrete_optimizer$cxxbridge1$Dag$optimize_multi(*this, options, &return$.value);
+  concrete_optimizer$cxxbridge1$Dag$optimize_multi(*this, &options$.value, &return$.value);
   return ::std::move(return$.value);
 }
 
@@ -1498,6 +1626,15 @@ void cxxbridge1$box$concrete_optimizer$DagBuilder$drop(::rust::Box<::concrete_op
 void cxxbridge1$box$concrete_optimizer$Weights$dealloc(::concrete_optimizer::Weights *) noexcept;
 void cxxbridge1$box$concrete_optimizer$Weights$drop(::rust::Box<::concrete_optimizer::Weights> *ptr) noexcept;
 
+void cxxbridge1$rust_vec$concrete_optimizer$ModulusSwitching$new(::rust::Vec<::concrete_optimizer::ModulusSwitching> const *ptr) noexcept;
+void cxxbridge1$rust_vec$concrete_optimizer$ModulusSwitching$drop(::rust::Vec<::concrete_optimizer::ModulusSwitching> *ptr) noexcept;
+::std::size_t cxxbridge1$rust_vec$concrete_optimizer$ModulusSwitching$len(::rust::Vec<::concrete_optimizer::ModulusSwitching> const *ptr) noexcept;
+::std::size_t cxxbridge1$rust_vec$concrete_optimizer$ModulusSwitching$capacity(::rust::Vec<::concrete_optimizer::ModulusSwitching> const *ptr) noexcept;
+::concrete_optimizer::ModulusSwitching const *cxxbridge1$rust_vec$concrete_optimizer$ModulusSwitching$data(::rust::Vec<::concrete_optimizer::ModulusSwitching> const *ptr) noexcept;
+void cxxbridge1$rust_vec$concrete_optimizer$ModulusSwitching$reserve_total(::rust::Vec<::concrete_optimizer::ModulusSwitching> *ptr, ::std::size_t new_cap) noexcept;
+void cxxbridge1$rust_vec$concrete_optimizer$ModulusSwitching$set_len(::rust::Vec<::concrete_optimizer::ModulusSwitching> *ptr, ::std::size_t len) noexcept;
+void cxxbridge1$rust_vec$concrete_optimizer$ModulusSwitching$truncate(::rust::Vec<::concrete_optimizer::ModulusSwitching> *ptr, ::std::size_t len) noexcept;
+
 void cxxbridge1$rust_vec$concrete_optimizer$dag$SecretLweKey$new(::rust::Vec<::concrete_optimizer::dag::SecretLweKey> const *ptr) noexcept;
 void cxxbridge1$rust_vec$concrete_optimizer$dag$SecretLweKey$drop(::rust::Vec<::concrete_optimizer::dag::SecretLweKey> *ptr) noexcept;
 ::std::size_t cxxbridge1$rust_vec$concrete_optimizer$dag$SecretLweKey$len(::rust::Vec<::concrete_optimizer::dag::SecretLweKey> const *ptr) noexcept;
@@ -1601,6 +1738,38 @@ void Box<::concrete_optimizer::Weights>::drop() noexcept {
   cxxbridge1$box$concrete_optimizer$Weights$drop(this);
 }
 template <>
+Vec<::concrete_optimizer::ModulusSwitching>::Vec() noexcept {
+  cxxbridge1$rust_vec$concrete_optimizer$ModulusSwitching$new(this);
+}
+template <>
+void Vec<::concrete_optimizer::ModulusSwitching>::drop() noexcept {
+  return cxxbridge1$rust_vec$concrete_optimizer$ModulusSwitching$drop(this);
+}
+template <>
+::std::size_t Vec<::concrete_optimizer::ModulusSwitching>::size() const noexcept {
+  return cxxbridge1$rust_vec$concrete_optimizer$ModulusSwitching$len(this);
+}
+template <>
+::std::size_t Vec<::concrete_optimizer::ModulusSwitching>::capacity() const noexcept {
+  return cxxbridge1$rust_vec$concrete_optimizer$ModulusSwitching$capacity(this);
+}
+template <>
+::concrete_optimizer::ModulusSwitching const *Vec<::concrete_optimizer::ModulusSwitching>::data() const noexcept {
+  return cxxbridge1$rust_vec$concrete_optimizer$ModulusSwitching$data(this);
+}
+template <>
+void Vec<::concrete_optimizer::ModulusSwitching>::reserve_total(::std::size_t new_cap) noexcept {
+  return cxxbridge1$rust_vec$concrete_optimizer$ModulusSwitching$reserve_total(this, new_cap);
+}
+template <>
+void Vec<::concrete_optimizer::ModulusSwitching>::set_len(::std::size_t len) noexcept {
+  return cxxbridge1$rust_vec$concrete_optimizer$ModulusSwitching$set_len(this, len);
+}
+template <>
+void Vec<::concrete_optimizer::ModulusSwitching>::truncate(::std::size_t len) {
+  return cxxbridge1$rust_vec$concrete_optimizer$ModulusSwitching$truncate(this, len);
+}
+template <>
 Vec<::concrete_optimizer::dag::SecretLweKey>::Vec() noexcept {
   cxxbridge1$rust_vec$concrete_optimizer$dag$SecretLweKey$new(this);
 }
@@ -927,6 +927,9 @@ namespace concrete_optimizer {
   struct Weights;
   enum class Encoding : ::std::uint8_t;
   enum class MultiParamStrategy : ::std::uint8_t;
+  enum class NoiseTracking : ::std::uint8_t;
+  enum class ModulusSwitching : ::std::uint8_t;
+  enum class ErrorProbabilityModel : ::std::uint8_t;
   struct Options;
   namespace dag {
     struct OperatorIndex;
@@ -976,6 +979,16 @@ struct DagBuilder final : public ::rust::Opaque {
   ::concrete_optimizer::dag::OperatorIndex add_levelled_op(::rust::Slice<::concrete_optimizer::dag::OperatorIndex const> inputs, double lwe_dim_cost_factor, double fixed_cost, double manp, ::rust::Slice<::std::uint64_t const> out_shape, ::rust::Str comment) noexcept;
   ::concrete_optimizer::dag::OperatorIndex add_round_op(::concrete_optimizer::dag::OperatorIndex input, ::std::uint8_t rounded_precision) noexcept;
   ::concrete_optimizer::dag::OperatorIndex add_unsafe_cast_op(::concrete_optimizer::dag::OperatorIndex input, ::std::uint8_t rounded_precision) noexcept;
+  ::concrete_optimizer::dag::OperatorIndex add_bivariate_lut(::concrete_optimizer::dag::OperatorIndex lhs, ::concrete_optimizer::dag::OperatorIndex rhs, ::rust::Slice<::std::uint64_t const> table, ::std::uint8_t out_precision) noexcept;
+  ::concrete_optimizer::dag::OperatorIndex add_mul(::concrete_optimizer::dag::OperatorIndex lhs, ::concrete_optimizer::dag::OperatorIndex rhs, ::std::uint8_t out_precision) noexcept;
+  ::concrete_optimizer::dag::OperatorIndex add_max_pool(::concrete_optimizer::dag::OperatorIndex input, ::rust::Slice<::std::uint64_t const> window) noexcept;
+  ::concrete_optimizer::dag::OperatorIndex add_reshape(::concrete_optimizer::dag::OperatorIndex input, ::rust::Slice<::std::uint64_t const> out_shape) noexcept;
+  ::concrete_optimizer::dag::OperatorIndex add_transpose(::concrete_optimizer::dag::OperatorIndex input, ::rust::Slice<::std::size_t const> axes) noexcept;
+  ::concrete_optimizer::dag::OperatorIndex add_concatenate(::rust::Slice<::concrete_optimizer::dag::OperatorIndex const> inputs, ::std::size_t axis) noexcept;
+  ::concrete_optimizer::dag::OperatorIndex add_slice(::concrete_optimizer::dag::OperatorIndex input, ::rust::Slice<::std::uint64_t const> offsets, ::rust::Slice<::std::uint64_t const> sizes, ::rust::Slice<::std::uint64_t const> strides) noexcept;
+  ::concrete_optimizer::dag::OperatorIndex add_gather(::concrete_optimizer::dag::OperatorIndex input, ::std::size_t axis, ::rust::Slice<::std::uint64_t const> indices_shape, ::rust::Slice<::std::uint64_t const> indices) noexcept;
+  ::concrete_optimizer::dag::OperatorIndex add_broadcast(::concrete_optimizer::dag::OperatorIndex input, ::rust::Slice<::std::uint64_t const> out_shape) noexcept;
+  ::concrete_optimizer::dag::OperatorIndex add_conv2d(::concrete_optimizer::dag::OperatorIndex input, ::rust::Box<::concrete_optimizer::Weights> weights, ::rust::Slice<::std::uint64_t const> strides, ::rust::Slice<::std::uint64_t const> padding, ::rust::Slice<::std::uint64_t const> dilations, ::std::uint64_t groups) noexcept;
   void tag_operator_as_output(::concrete_optimizer::dag::OperatorIndex op) noexcept;
   ~DagBuilder() = delete;
 
@@ -1037,6 +1050,7 @@ struct Solution final {
   double complexity;
   double noise_max;
   double p_error;
+  ::rust::String error_msg;
 
   using IsRelocatable = ::std::true_type;
 };
@@ -1065,6 +1079,7 @@ struct DagSolution final {
   ::std::uint64_t pp_decomposition_level_count;
   ::std::uint64_t pp_decomposition_base_log;
   ::rust::Vec<::std::uint64_t> crt_decomposition;
+  ::rust::String error_msg;
 
   using IsRelocatable = ::std::true_type;
 };
@@ -1079,6 +1094,31 @@ enum class MultiParamStrategy : ::std::uint8_t {
 };
 #endif // CXXBRIDGE1_ENUM_concrete_optimizer$MultiParamStrategy
 
+#ifndef CXXBRIDGE1_ENUM_concrete_optimizer$NoiseTracking
+#define CXXBRIDGE1_ENUM_concrete_optimizer$NoiseTracking
+enum class NoiseTracking : ::std::uint8_t {
+  PerTensor = 0,
+  PerElementClass = 1,
+};
+#endif // CXXBRIDGE1_ENUM_concrete_optimizer$NoiseTracking
+
+#ifndef CXXBRIDGE1_ENUM_concrete_optimizer$ModulusSwitching
+#define CXXBRIDGE1_ENUM_concrete_optimizer$ModulusSwitching
+enum class ModulusSwitching : ::std::uint8_t {
+  Standard = 0,
+  CenteredMean = 1,
+  ShiftedKey = 2,
+};
+#endif // CXXBRIDGE1_ENUM_concrete_optimizer$ModulusSwitching
+
+#ifndef CXXBRIDGE1_ENUM_concrete_optimizer$ErrorProbabilityModel
+#define CXXBRIDGE1_ENUM_concrete_optimizer$ErrorProbabilityModel
+enum class ErrorProbabilityModel : ::std::uint8_t {
+  Gaussian = 0,
+  ExactTail = 1,
+};
+#endif // CXXBRIDGE1_ENUM_concrete_optimizer$ErrorProbabilityModel
+
 #ifndef CXXBRIDGE1_STRUCT_concrete_optimizer$Options
 #define CXXBRIDGE1_STRUCT_concrete_optimizer$Options
 struct Options final {
@@ -1093,6 +1133,12 @@ struct Options final {
   ::std::uint32_t ciphertext_modulus_log;
   ::std::uint32_t fft_precision;
   bool composable;
+  ::concrete_optimizer::NoiseTracking noise_tracking;
+  ::concrete_optimizer::ErrorProbabilityModel error_probability_model;
+  ::rust::String security_curves_file;
+  bool simplify_dag;
+  bool refresh_compositions;
+  ::rust::Vec<::concrete_optimizer::ModulusSwitching> modulus_switching_kinds;
 
   using IsRelocatable = ::std::true_type;
 };
@@ -1138,6 +1184,7 @@ struct BootstrapKey final {
   ::concrete_optimizer::dag::SecretLweKey input_key;
   ::concrete_optimizer::dag::SecretLweKey output_key;
   ::concrete_optimizer::dag::BrDecompositionParameters br_decomposition_parameter;
+  ::concrete_optimizer::ModulusSwitching modulus_switching;
   ::rust::String description;
 
   using IsRelocatable = ::std::true_type;
@@ -1233,6 +1280,9 @@ struct InstructionKeys final {
 struct CircuitSolution final {
   ::CircuitKeys circuit_keys;
   ::rust::Vec<::concrete_optimizer::dag::InstructionKeys> instructions_keys;
+  ::rust::Vec<::std::uint8_t> round_group_bits;
+  ::rust::String simplified_dag;
+  ::rust::Vec<::std::uint64_t> simplified_instructions;
   ::rust::Vec<::std::uint64_t> crt_decomposition;
   double complexity;
   double p_error;
@@ -1265,6 +1315,8 @@ namespace weights {
 ::rust::Box<::concrete_optimizer::Weights> vector(::rust::Slice<::std::int64_t const> weights) noexcept;
 
 ::rust::Box<::concrete_optimizer::Weights> number(::std::int64_t weight) noexcept;
+
+::rust::Box<::concrete_optimizer::Weights> tensor(::rust::Slice<::std::uint64_t const> shape, ::rust::Slice<::std::int64_t const> weights) noexcept;
 } // namespace weights
 
 ::std::uint64_t NO_KEY_ID() noexcept;
@@ -12,6 +12,7 @@ rust::cxxbridge1::Slice<const T> slice(std::vector<T> &vec) {
 const uint64_t SECURITY_128B = 128;
 const double P_ERROR = 0.05;
 const double PRECISION_1B = 1;
+const double PRECISION_4B = 4;
 const double PRECISION_8B = 8;
 const double PRECISION_16B = 16;
 const double WOP_FALLBACK_LOG_NORM = 8;
@@ -30,7 +31,14 @@ concrete_optimizer::Options default_options() {
       .cache_on_disk = true,
       .ciphertext_modulus_log = CIPHERTEXT_MODULUS_LOG,
       .fft_precision = 53,
-      .composable = false
+      .composable = false,
+      .noise_tracking = concrete_optimizer::NoiseTracking::PerTensor,
+      .error_probability_model =
+          concrete_optimizer::ErrorProbabilityModel::Gaussian,
+      .security_curves_file = "",
+      .simplify_dag = false,
+      .refresh_compositions = false,
+      .modulus_switching_kinds = {}
   };
 }
 
@@ -44,6 +52,23 @@ TEST test_v0() {
           PRECISION_1B, NOISE_DEVIATION_COEFF, options);
 
   assert(solution.glwe_polynomial_size == 256);
+  assert(solution.error_msg.empty());
+}
+
+TEST test_v0_errors() {
+  auto options = default_options();
+  options.security_level = 1;
+  auto solution = concrete_optimizer::v0::optimize_bootstrap(
+      PRECISION_1B, NOISE_DEVIATION_COEFF, options);
+  assert(solution.p_error == 1.0);
+  assert(!solution.error_msg.empty());
+
+  options = default_options();
+  options.security_curves_file = "missing-curves.json";
+  solution = concrete_optimizer::v0::optimize_bootstrap(
+      PRECISION_1B, NOISE_DEVIATION_COEFF, options);
+  assert(solution.p_error == 1.0);
+  assert(!solution.error_msg.empty());
 }
 
 TEST test_dag_no_lut() {
@@ -70,6 +95,30 @@ TEST test_dag_no_lut() {
   assert(solution.glwe_dimension == 555);
 }
 
+TEST test_dag_invalid() {
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+
+  std::vector<uint64_t> shape = {3};
+
+  concrete_optimizer::dag::OperatorIndex node1 =
+      builder->add_input(PRECISION_8B, slice(shape));
+
+  std::vector<concrete_optimizer::dag::OperatorIndex> inputs = {node1};
+
+  std::vector<int64_t> weight_vec = {1, 1};
+
+  rust::cxxbridge1::Box<concrete_optimizer::Weights> weights =
+      concrete_optimizer::weights::vector(slice(weight_vec));
+
+  auto id = builder->add_dot(slice(inputs), std::move(weights));
+  builder->tag_operator_as_output(id);
+
+  auto solution = dag->optimize(default_options());
+  assert(solution.p_error == 1.0);
+  assert(!solution.error_msg.empty());
+}
+
 TEST test_dag_lut() {
   auto dag = concrete_optimizer::dag::empty();
   auto builder = dag->builder("test");
@@ -89,6 +138,48 @@ TEST test_dag_lut() {
   assert(!solution.use_wop_pbs);
 }
 
+TEST test_dag_lut_per_element_class() {
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+
+  std::vector<uint64_t> shape = {3};
+
+  concrete_optimizer::dag::OperatorIndex input =
+      builder->add_input(PRECISION_8B, slice(shape));
+
+  std::vector<u_int64_t> table = {};
+  auto id = builder->add_lut(input, slice(table), PRECISION_8B);
+  builder->tag_operator_as_output(id);
+
+  auto options = default_options();
+  options.noise_tracking = concrete_optimizer::NoiseTracking::PerElementClass;
+  auto solution = dag->optimize(options);
+  // All the elements have the same noise, the parameters are unchanged
+  assert(solution.glwe_dimension == 1);
+  assert(solution.glwe_polynomial_size == 8192);
+  assert(!solution.use_wop_pbs);
+}
+
+TEST test_dag_lut_missing_curves_file() {
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+
+  std::vector<uint64_t> shape = {3};
+
+  concrete_optimizer::dag::OperatorIndex input =
+      builder->add_input(PRECISION_8B, slice(shape));
+
+  std::vector<u_int64_t> table = {};
+  auto id = builder->add_lut(input, slice(table), PRECISION_8B);
+  builder->tag_operator_as_output(id);
+
+  auto options = default_options();
+  options.security_curves_file = "missing-curves.json";
+  auto solution = dag->optimize(options);
+  assert(solution.p_error == 1.0);
+  assert(!solution.error_msg.empty());
+}
+
 TEST test_dag_lut_wop() {
   auto dag = concrete_optimizer::dag::empty();
   auto builder = dag->builder("test");
@@ -155,6 +246,227 @@ TEST test_multi_parameters_1_precision() {
   assert(circuit_solution.circuit_keys.conversion_keyswitch_keys.size() == 0);
 }
 
+TEST test_multi_parameters_round() {
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+
+  std::vector<uint64_t> shape = {3};
+
+  concrete_optimizer::dag::OperatorIndex input =
+      builder->add_input(PRECISION_8B, slice(shape));
+
+  std::vector<u_int64_t> table = {};
+  auto id1 = builder->add_lut(input, slice(table), PRECISION_8B);
+  auto round = builder->add_round_op(input, PRECISION_4B);
+  auto id2 = builder->add_lut(round, slice(table), PRECISION_4B);
+  builder->tag_operator_as_output(id1);
+  builder->tag_operator_as_output(id2);
+
+  auto options = default_options();
+  auto circuit_solution = dag->optimize_multi(options);
+  assert(circuit_solution.is_feasible);
+  assert(circuit_solution.round_group_bits.size() == 4);
+  assert(circuit_solution.round_group_bits[input.index] == 0);
+  assert(circuit_solution.round_group_bits[id1.index] == 0);
+  auto group_bits = circuit_solution.round_group_bits[round.index];
+  assert(1 <= group_bits && group_bits <= 4);
+  assert(circuit_solution.round_group_bits[id2.index] == 0);
+}
+
+TEST test_multi_parameters_simplified_dag() {
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+
+  std::vector<uint64_t> shape = {3};
+
+  concrete_optimizer::dag::OperatorIndex input =
+      builder->add_input(PRECISION_8B, slice(shape));
+
+  std::vector<u_int64_t> table = {};
+  auto id1 = builder->add_lut(input, slice(table), PRECISION_8B);
+  auto id2 = builder->add_lut(id1, slice(table), PRECISION_8B);
+  builder->tag_operator_as_output(id2);
+
+  auto options = default_options();
+  auto circuit_solution = dag->optimize_multi(options);
+  assert(circuit_solution.is_feasible);
+  assert(circuit_solution.simplified_dag.empty());
+  assert(circuit_solution.simplified_instructions.empty());
+
+  options.simplify_dag = true;
+  circuit_solution = dag->optimize_multi(options);
+  assert(circuit_solution.is_feasible);
+  assert(!circuit_solution.simplified_dag.empty());
+  // the first lut is fused in the second one
+  auto instructions = circuit_solution.simplified_instructions;
+  assert(instructions.size() == 3);
+  assert(instructions[id1.index] == UINT64_MAX);
+  assert(instructions[id2.index] == 1);
+}
+
+TEST test_multi_parameters_refresh_compositions() {
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+
+  std::vector<uint64_t> shape = {};
+
+  concrete_optimizer::dag::OperatorIndex input =
+      builder->add_input(PRECISION_4B, slice(shape));
+
+  std::vector<u_int64_t> table = {};
+  auto lut = builder->add_lut(input, slice(table), PRECISION_4B);
+  // the output keeps the input noise, which grows through the composition
+  std::vector<concrete_optimizer::dag::OperatorIndex> inputs = {input, lut};
+  std::vector<int64_t> weight_vec = {4, 1};
+  rust::cxxbridge1::Box<concrete_optimizer::Weights> weights =
+      concrete_optimizer::weights::vector(slice(weight_vec));
+  auto output = builder->add_dot(slice(inputs), std::move(weights));
+  builder->tag_operator_as_output(output);
+
+  auto options = default_options();
+  options.encoding = concrete_optimizer::Encoding::Native;
+  options.composable = true;
+  auto circuit_solution = dag->optimize_multi(options);
+  assert(!circuit_solution.is_feasible);
+
+  options.refresh_compositions = true;
+  circuit_solution = dag->optimize_multi(options);
+  assert(circuit_solution.is_feasible);
+  assert(!circuit_solution.simplified_dag.empty());
+  assert(circuit_solution.simplified_instructions.size() == 3);
+}
+
+TEST test_multi_parameters_modulus_switching() {
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+
+  std::vector<uint64_t> shape = {};
+
+  concrete_optimizer::dag::OperatorIndex input =
+      builder->add_input(PRECISION_8B, slice(shape));
+
+  std::vector<u_int64_t> table = {};
+  auto lut = builder->add_lut(input, slice(table), PRECISION_8B);
+
+  std::vector<concrete_optimizer::dag::OperatorIndex> inputs = {lut};
+  std::vector<int64_t> weight_vec = {8};
+  rust::cxxbridge1::Box<concrete_optimizer::Weights> weights =
+      concrete_optimizer::weights::vector(slice(weight_vec));
+  auto id = builder->add_dot(slice(inputs), std::move(weights));
+  builder->tag_operator_as_output(id);
+
+  auto options = default_options();
+  auto standard = dag->optimize_multi(options);
+  assert(standard.is_feasible);
+  assert(standard.circuit_keys.bootstrap_keys[0].modulus_switching ==
+         concrete_optimizer::ModulusSwitching::Standard);
+
+  options.modulus_switching_kinds.push_back(
+      concrete_optimizer::ModulusSwitching::Standard);
+  options.modulus_switching_kinds.push_back(
+      concrete_optimizer::ModulusSwitching::CenteredMean);
+  options.modulus_switching_kinds.push_back(
+      concrete_optimizer::ModulusSwitching::ShiftedKey);
+  auto corrected = dag->optimize_multi(options);
+  assert(corrected.is_feasible);
+  assert(corrected.complexity < standard.complexity);
+  auto bootstrap_key = corrected.circuit_keys.bootstrap_keys[0];
+  assert(bootstrap_key.modulus_switching ==
+         concrete_optimizer::ModulusSwitching::CenteredMean);
+  assert(bootstrap_key.input_key.polynomial_size <
+         standard.circuit_keys.bootstrap_keys[0].input_key.polynomial_size);
+}
+
+TEST test_multi_parameters_bivariate_mul_max_pool() {
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+
+  std::vector<uint64_t> shape = {4, 4};
+
+  concrete_optimizer::dag::OperatorIndex lhs =
+      builder->add_input(PRECISION_4B, slice(shape));
+  concrete_optimizer::dag::OperatorIndex rhs =
+      builder->add_input(PRECISION_4B, slice(shape));
+
+  std::vector<u_int64_t> table = {};
+  auto bivariate = builder->add_bivariate_lut(lhs, rhs, slice(table),
+                                              PRECISION_4B);
+  auto mul = builder->add_mul(lhs, rhs, PRECISION_4B);
+  std::vector<uint64_t> window = {2, 2};
+  auto max_pool = builder->add_max_pool(bivariate, slice(window));
+  builder->tag_operator_as_output(mul);
+  builder->tag_operator_as_output(max_pool);
+
+  std::string dump(builder->dump());
+  assert(dump.find("2 <- Bivariate") != std::string::npos);
+  assert(dump.find("3 <- Mul") != std::string::npos);
+  assert(dump.find("4 <- MaxPool") != std::string::npos);
+
+  auto circuit_solution = dag->optimize_multi(default_options());
+  assert(circuit_solution.is_feasible);
+  assert(!circuit_solution.circuit_keys.bootstrap_keys.empty());
+}
+
+TEST test_multi_parameters_tensor_operators() {
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+
+  std::vector<uint64_t> image_shape = {1, 2, 4, 4};
+  concrete_optimizer::dag::OperatorIndex input =
+      builder->add_input(PRECISION_4B, slice(image_shape));
+
+  std::vector<u_int64_t> table = {};
+  auto lut1 = builder->add_lut(input, slice(table), PRECISION_4B);
+
+  std::vector<uint64_t> weights_shape = {2, 2, 2, 2};
+  std::vector<int64_t> weight_vec(16, 1);
+  std::vector<uint64_t> strides = {1, 1};
+  std::vector<uint64_t> padding = {0, 0, 0, 0};
+  std::vector<uint64_t> dilations = {1, 1};
+  auto conv = builder->add_conv2d(
+      lut1, concrete_optimizer::weights::tensor(slice(weights_shape),
+                                                slice(weight_vec)),
+      slice(strides), slice(padding), slice(dilations), 1);
+
+  std::vector<uint64_t> matrix_shape = {2, 9};
+  auto reshaped = builder->add_reshape(conv, slice(matrix_shape));
+  std::vector<size_t> axes = {1, 0};
+  auto transposed = builder->add_transpose(reshaped, slice(axes));
+  std::vector<concrete_optimizer::dag::OperatorIndex> parts = {transposed,
+                                                               transposed};
+  auto concatenated = builder->add_concatenate(slice(parts), 0);
+  std::vector<uint64_t> offsets = {0, 0};
+  std::vector<uint64_t> sizes = {9, 1};
+  std::vector<uint64_t> slice_strides = {2, 1};
+  auto sliced = builder->add_slice(concatenated, slice(offsets), slice(sizes),
+                                   slice(slice_strides));
+  std::vector<uint64_t> indices_shape = {2};
+  std::vector<uint64_t> indices = {0, 8};
+  auto gathered =
+      builder->add_gather(sliced, 0, slice(indices_shape), slice(indices));
+  std::vector<uint64_t> broadcast_shape = {3, 2, 1};
+  auto broadcasted = builder->add_broadcast(gathered, slice(broadcast_shape));
+  auto lut2 = builder->add_lut(broadcasted, slice(table), PRECISION_4B);
+  builder->tag_operator_as_output(lut2);
+
+  auto circuit_solution = dag->optimize_multi(default_options());
+  assert(circuit_solution.is_feasible);
+
+  auto invalid_dag = concrete_optimizer::dag::empty();
+  auto invalid_builder = invalid_dag->builder("test");
+  input = invalid_builder->add_input(PRECISION_4B, slice(image_shape));
+  std::vector<uint64_t> short_padding = {0, 0};
+  conv = invalid_builder->add_conv2d(
+      input, concrete_optimizer::weights::tensor(slice(weights_shape),
+                                                 slice(weight_vec)),
+      slice(strides), slice(short_padding), slice(dilations), 1);
+  invalid_builder->tag_operator_as_output(conv);
+
+  circuit_solution = invalid_dag->optimize_multi(default_options());
+  assert(!circuit_solution.is_feasible);
+  assert(!circuit_solution.error_msg.empty());
+}
+
 TEST test_multi_parameters_2_precision() {
   auto dag = concrete_optimizer::dag::empty();
   auto builder = dag->builder("test");
@@ -309,11 +621,21 @@ TEST test_non_composable_dag_mono_fallback_on_woppbs() {
 int main() {
 
   test_v0();
+  test_v0_errors();
   test_dag_no_lut();
+  test_dag_invalid();
   test_dag_lut();
+  test_dag_lut_per_element_class();
+  test_dag_lut_missing_curves_file();
   test_dag_lut_wop();
   test_dag_lut_force_wop();
   test_multi_parameters_1_precision();
+  test_multi_parameters_round();
+  test_multi_parameters_simplified_dag();
+  test_multi_parameters_refresh_compositions();
+  test_multi_parameters_modulus_switching();
+  test_multi_parameters_bivariate_mul_max_pool();
+  test_multi_parameters_tensor_operators();
   test_multi_parameters_2_precision();
   test_multi_parameters_2_precision_crt();
   test_composable_dag_mono_fallback_on_dag_multi();
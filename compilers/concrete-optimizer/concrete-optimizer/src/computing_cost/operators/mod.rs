@@ -1,4 +1,5 @@
 pub mod cmux;
 pub(super) mod keyswitch_lwe;
+pub mod modulus_switching;
 pub mod multi_bit_pbs;
 pub(super) mod pbs;
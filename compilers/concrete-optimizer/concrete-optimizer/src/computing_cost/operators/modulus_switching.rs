@@ -0,0 +1,14 @@
+use super::super::complexity::Complexity;
+use concrete_cpu_noise_model::gaussian_noise::noise::modulus_switching::ModulusSwitchingKind;
+
+/// Additional cost of the body correction of `kind` before a blind rotate.
+///
+/// Each mask coefficient is switched, then its rounding error (or itself) is summed.
+pub fn correction_complexity(kind: ModulusSwitchingKind, lwe_dimension: u64) -> Complexity {
+    match kind {
+        ModulusSwitchingKind::Standard => 0.0,
+        ModulusSwitchingKind::CenteredMean | ModulusSwitchingKind::ShiftedKey => {
+            2.0 * lwe_dimension as Complexity
+        }
+    }
+}
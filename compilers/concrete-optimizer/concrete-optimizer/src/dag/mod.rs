@@ -1,3 +1,5 @@
 pub mod operator;
 pub mod rewrite;
+pub mod text;
 pub mod unparametrized;
+pub mod validate;
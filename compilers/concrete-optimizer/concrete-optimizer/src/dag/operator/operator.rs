@@ -1,3 +1,4 @@
+use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::iter::{empty, once};
 use std::ops::Deref;
@@ -5,8 +6,9 @@ use std::ops::Deref;
 use crate::dag::operator::tensor::{ClearTensor, Shape};
 
 pub type Weights = ClearTensor<i64>;
+pub type Indices = ClearTensor<u64>;
 
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub struct FunctionTable {
     pub values: Vec<u64>,
 }
@@ -15,7 +17,7 @@ impl FunctionTable {
     pub const UNKWOWN: Self = Self { values: vec![] };
 }
 
-#[derive(Clone, Copy, PartialEq, Debug)]
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
 pub struct LevelledComplexity {
     pub lwe_dim_cost_factor: f64,
     pub fixed_cost: f64,
@@ -67,8 +69,12 @@ impl std::ops::Mul<u64> for LevelledComplexity {
 }
 pub type Precision = u8;
 pub const MIN_PRECISION: Precision = 1;
+// A message and its two padding bits fit in a 64 bits ciphertext.
+pub const MAX_PRECISION: Precision = 62;
+// The widest lut input, as for the frontend table lookups and the crt decomposition.
+pub const MAX_LUT_PRECISION: Precision = 16;
 
-#[derive(Clone, PartialEq, Debug)]
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub enum Operator {
     Input {
         out_precision: Precision,
@@ -101,6 +107,72 @@ pub enum Operator {
         input: OperatorIndex,
         out_precision: Precision,
     },
+    // A lut on two inputs of the same shape, the table is indexed by the lhs bits followed by the rhs bits.
+    // It is expanded to a lut on both inputs packed in a single ciphertext.
+    Bivariate {
+        inputs: [OperatorIndex; 2],
+        table: FunctionTable,
+        out_precision: Precision,
+    },
+    // Product of two inputs of the same shape.
+    // It is expanded with the squares trick, x * y = ((x + y)^2 - (x - y)^2) / 4, as two luts.
+    Mul {
+        inputs: [OperatorIndex; 2],
+        out_precision: Precision,
+    },
+    // Max on non overlapping windows of the last dimensions of the input.
+    // It is expanded to a tree of max on pairs, max(x, y) = y + relu(x - y), one lut per pair.
+    MaxPool {
+        input: OperatorIndex,
+        window: Shape,
+    },
+    // The shape operators only move ciphertexts, each output element has the noise of an input element.
+    // As tensor noises are tracked per operator, an output mixing inputs is bounded by its worst input.
+    // The same elements in another shape of the same size.
+    Reshape {
+        input: OperatorIndex,
+        out_shape: Shape,
+    },
+    // Permutation of the dimensions, the output dimension i is the input dimension axes[i].
+    Transpose {
+        input: OperatorIndex,
+        axes: Vec<usize>,
+    },
+    // Inputs of the same shape except on the concatenation axis.
+    // Its noise is the worst input noise, not exact for the elements of the other inputs.
+    Concatenate {
+        inputs: Vec<OperatorIndex>,
+        axis: usize,
+    },
+    // For each dimension, the elements at offsets + i * strides for i < sizes.
+    Slice {
+        input: OperatorIndex,
+        offsets: Vec<u64>,
+        sizes: Vec<u64>,
+        strides: Vec<u64>,
+    },
+    // The elements at clear indices along an axis, the indices shape replaces the axis dimension.
+    Gather {
+        input: OperatorIndex,
+        axis: usize,
+        indices: Indices,
+    },
+    // Repeats the input to a bigger shape, following numpy broadcasting rules.
+    Broadcast {
+        input: OperatorIndex,
+        out_shape: Shape,
+    },
+    // 2d convolution of a [N, C, H, W] input by [F, C / groups, KH, KW] weights.
+    // The padding is [top, left, bottom, right].
+    // Its noise is the worst filter square norm times the input noise, padded borders included.
+    Conv2d {
+        input: OperatorIndex,
+        weights: Weights,
+        strides: [u64; 2],
+        padding: [u64; 4],
+        dilations: [u64; 2],
+        groups: u64,
+    },
 }
 
 impl Operator {
@@ -108,15 +180,25 @@ impl Operator {
     pub(crate) fn get_inputs_iter(&self) -> Box<dyn Iterator<Item = &OperatorIndex> + '_> {
         match self {
             Self::Input { .. } => Box::new(empty()),
-            Self::LevelledOp { inputs, .. } | Self::Dot { inputs, .. } => Box::new(inputs.iter()),
+            Self::LevelledOp { inputs, .. }
+            | Self::Dot { inputs, .. }
+            | Self::Concatenate { inputs, .. } => Box::new(inputs.iter()),
+            Self::Bivariate { inputs, .. } | Self::Mul { inputs, .. } => Box::new(inputs.iter()),
             Self::UnsafeCast { input, .. }
             | Self::Lut { input, .. }
-            | Self::Round { input, .. } => Box::new(once(input)),
+            | Self::Round { input, .. }
+            | Self::MaxPool { input, .. }
+            | Self::Reshape { input, .. }
+            | Self::Transpose { input, .. }
+            | Self::Slice { input, .. }
+            | Self::Gather { input, .. }
+            | Self::Broadcast { input, .. }
+            | Self::Conv2d { input, .. } => Box::new(once(input)),
         }
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub struct OperatorIndex(pub usize);
 
 impl Deref for OperatorIndex {
@@ -134,6 +216,7 @@ impl fmt::Display for OperatorIndex {
 }
 
 impl fmt::Display for Operator {
+    #[allow(clippy::too_many_lines)]
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "")?;
         match self {
@@ -185,6 +268,59 @@ impl fmt::Display for Operator {
             } => {
                 write!(f, "ROUND[%{}] : u{out_precision}", input.0)?;
             }
+            Self::Bivariate {
+                inputs,
+                out_precision,
+                ..
+            } => {
+                write!(
+                    f,
+                    "LUT[%{}, %{}] : u{out_precision}",
+                    inputs[0].0, inputs[1].0
+                )?;
+            }
+            Self::Mul {
+                inputs,
+                out_precision,
+            } => {
+                write!(f, "%{} x %{} : u{out_precision}", inputs[0].0, inputs[1].0)?;
+            }
+            Self::MaxPool { input, window } => {
+                write!(f, "MAXPOOL[%{}] : {window:?}", input.0)?;
+            }
+            Self::Reshape { input, out_shape } => {
+                write!(f, "RESHAPE[%{}] : {out_shape:?}", input.0)?;
+            }
+            Self::Transpose { input, axes } => {
+                write!(f, "TRANSPOSE[%{}] : {axes:?}", input.0)?;
+            }
+            Self::Concatenate { inputs, axis } => {
+                write!(f, "CONCAT[")?;
+                for (i, input) in inputs.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "%{}", input.0)?;
+                }
+                write!(f, "] : axis={axis}")?;
+            }
+            Self::Slice {
+                input,
+                offsets,
+                sizes,
+                strides,
+            } => {
+                write!(f, "SLICE[%{}] : {offsets:?} {sizes:?} {strides:?}", input.0)?;
+            }
+            Self::Gather { input, axis, .. } => {
+                write!(f, "GATHER[%{}] : axis={axis}", input.0)?;
+            }
+            Self::Broadcast { input, out_shape } => {
+                write!(f, "BROADCAST[%{}] : {out_shape:?}", input.0)?;
+            }
+            Self::Conv2d { input, weights, .. } => {
+                write!(f, "CONV2D[%{}] : {:?}", input.0, weights.shape)?;
+            }
         }
         Ok(())
     }
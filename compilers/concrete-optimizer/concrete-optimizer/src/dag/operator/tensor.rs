@@ -1,9 +1,10 @@
+use serde::{Deserialize, Serialize};
 use std::iter::Sum;
 use std::ops::Mul;
 
 use crate::utils::square_ref;
 
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub struct Shape {
     pub dimensions_size: Vec<u64>,
 }
@@ -25,6 +26,13 @@ impl Shape {
         product
     }
 
+    /// The number of values, or `None` if it does not fit in a `u64`.
+    pub fn checked_flat_size(&self) -> Option<u64> {
+        self.dimensions_size
+            .iter()
+            .try_fold(1_u64, |product, &dim_size| product.checked_mul(dim_size))
+    }
+
     pub fn number() -> Self {
         Self {
             dimensions_size: vec![],
@@ -57,9 +65,57 @@ impl Shape {
             dimensions_size: self.dimensions_size[1..].to_vec(),
         }
     }
+
+    // The shape after pooling the last dimensions by non overlapping windows
+    pub fn pooled(&self, window: &Self) -> Self {
+        let first_pooled = self.rank().saturating_sub(window.rank());
+        let mut dimensions_size = self.dimensions_size.clone();
+        for (dim_size, window_size) in dimensions_size[first_pooled..]
+            .iter_mut()
+            .zip(&window.dimensions_size)
+        {
+            *dim_size /= window_size;
+        }
+        Self { dimensions_size }
+    }
+
+    pub fn can_be_pooled(&self, window: &Self) -> bool {
+        window.rank() <= self.rank()
+            && self.dimensions_size[self.rank() - window.rank()..]
+                .iter()
+                .zip(&window.dimensions_size)
+                .all(|(dim_size, window_size)| *window_size > 0 && dim_size % window_size == 0)
+    }
+
+    pub fn is_permutation(axes: &[usize]) -> bool {
+        let mut seen = vec![false; axes.len()];
+        axes.iter()
+            .all(|&axis| axis < seen.len() && !std::mem::replace(&mut seen[axis], true))
+    }
+
+    // The output dimension i is the dimension axes[i], axes must be a permutation
+    pub fn transposed(&self, axes: &[usize]) -> Self {
+        Self {
+            dimensions_size: axes
+                .iter()
+                .filter_map(|&axis| self.dimensions_size.get(axis).copied())
+                .collect(),
+        }
+    }
+
+    // Numpy broadcasting, trailing dimensions are aligned and a dimension of size 1 is repeated
+    pub fn can_be_broadcast_to(&self, other: &Self) -> bool {
+        self.rank() <= other.rank()
+            && self
+                .dimensions_size
+                .iter()
+                .rev()
+                .zip(other.dimensions_size.iter().rev())
+                .all(|(dim_size, other_size)| *dim_size == 1 || dim_size == other_size)
+    }
 }
 
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub struct ClearTensor<W> {
     pub shape: Shape,
     pub values: Vec<W>,
@@ -103,6 +159,29 @@ where
     pub fn square_norm2(&self) -> W {
         self.values.iter().map(square_ref).sum()
     }
+
+    // The square norms of the sub tensors along the first dimension
+    pub fn first_dim_square_norm2s(&self) -> Vec<W> {
+        let first_dim_size = self.shape.dimensions_size.first().copied().unwrap_or(1);
+        let chunk_size = (self.values.len() as u64 / first_dim_size.max(1)).max(1);
+        self.values
+            .chunks(chunk_size as usize)
+            .map(|chunk| chunk.iter().map(square_ref).sum())
+            .collect()
+    }
+}
+
+impl<W> ClearTensor<W>
+where
+    W: Copy + Mul<Output = W> + Sum<W> + Ord,
+{
+    // The biggest square norm of the sub tensors along the first dimension
+    pub fn max_first_dim_square_norm2(&self) -> W {
+        self.first_dim_square_norm2s()
+            .into_iter()
+            .max()
+            .unwrap_or_else(|| std::iter::empty().sum())
+    }
 }
 
 // helps using shared shapes
@@ -0,0 +1,51 @@
+use crate::dag::operator::{Operator, OperatorIndex, Precision};
+use crate::dag::unparametrized::{Dag, DagBuilder};
+
+use super::regen::regen;
+
+/// The number of bits truncated per pbs the multi parameters optimizer tries for round operators.
+///
+/// Each one is a full optimization of the dag with all its rounds expanded with that number of
+/// bits, the cheapest solution is kept.
+pub const ROUND_GROUPS_BITS: [Precision; 4] = [1, 2, 3, 4];
+
+/// The number of bits truncated per pbs when the round operators are expanded only once.
+pub const DEFAULT_ROUND_GROUP_BITS: Precision = 1;
+
+/// Expands the operators the analyses do not handle directly to their native patterns.
+fn regen_expanded(
+    round_group_bits: Precision,
+) -> impl FnMut(usize, &Operator, &mut DagBuilder<'_>) -> Option<OperatorIndex> {
+    move |_, op, dag| match op {
+        Operator::Round {
+            input,
+            out_precision,
+        } => Some(dag.add_expanded_round_by_groups(*input, *out_precision, round_group_bits)),
+        Operator::Bivariate {
+            inputs: [lhs, rhs],
+            table,
+            out_precision,
+        } => Some(dag.add_expanded_bivariate_lut(*lhs, *rhs, table.clone(), *out_precision)),
+        Operator::Mul {
+            inputs: [lhs, rhs],
+            out_precision,
+        } => Some(dag.add_expanded_mul(*lhs, *rhs, *out_precision)),
+        Operator::MaxPool { input, window } => Some(dag.add_expanded_max_pool(*input, window)),
+        _ => None,
+    }
+}
+
+pub(crate) fn expand_operators(dag: &Dag) -> Dag {
+    regen(dag, &mut regen_expanded(DEFAULT_ROUND_GROUP_BITS)).0
+}
+
+pub(crate) fn expand_operators_and_index_map(dag: &Dag) -> (Dag, Vec<Vec<OperatorIndex>>) {
+    expand_operators_with_round_groups(dag, DEFAULT_ROUND_GROUP_BITS)
+}
+
+pub(crate) fn expand_operators_with_round_groups(
+    dag: &Dag,
+    round_group_bits: Precision,
+) -> (Dag, Vec<Vec<OperatorIndex>>) {
+    regen(dag, &mut regen_expanded(round_group_bits))
+}
@@ -1,2 +1,4 @@
+pub mod expand;
+pub mod passes;
+pub mod refresh;
 pub mod regen;
-pub mod round;
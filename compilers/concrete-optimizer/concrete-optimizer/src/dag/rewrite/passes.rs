@@ -0,0 +1,482 @@
+use std::collections::HashMap;
+
+use crate::dag::operator::{FunctionTable, Operator, OperatorIndex, Weights};
+use crate::dag::unparametrized::Dag;
+
+use super::regen::{compose_instructions_maps, regen};
+
+/// A simplification of the dag, run by [`run_passes`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Pass {
+    /// Merges the casts of a cast and removes the casts to the same precision.
+    CastFolding,
+    /// Merges the operators computing the same value from the same inputs.
+    /// Levelled operators and luts of unknown tables are never merged, they may compute different
+    /// functions.
+    CommonSubexpression,
+    /// Merges a lut of a lut into one lut, composing their tables.
+    LutFusion,
+    /// Merges a dot of numbers into the dot of numbers using it.
+    DotFolding,
+    /// Removes the operators that are neither used nor returned.
+    DeadCode,
+}
+
+/// All the passes, each one cleaning up after the previous ones.
+pub const ALL_PASSES: [Pass; 5] = [
+    Pass::CastFolding,
+    Pass::CommonSubexpression,
+    Pass::LutFusion,
+    Pass::DotFolding,
+    Pass::DeadCode,
+];
+
+/// Runs the passes in order.
+///
+/// Returns the simplified dag and, for each instruction of `dag`, the instructions computing it in
+/// the simplified dag. A merged instruction has the instructions of the one it is merged into, a
+/// removed one has none.
+pub fn run_passes(dag: &Dag, passes: &[Pass]) -> (Dag, Vec<Vec<OperatorIndex>>) {
+    let mut dag = dag.clone();
+    let mut instrs_map: Vec<_> = (0..dag.len()).map(|i| vec![OperatorIndex(i)]).collect();
+    for pass in passes {
+        let mut consumers = consumers_count(&dag);
+        let pass_map = match pass {
+            Pass::CastFolding => fold_casts(&mut dag, &mut consumers),
+            Pass::CommonSubexpression => eliminate_common_subexpressions(&mut dag, &mut consumers),
+            Pass::LutFusion => fuse_luts(&mut dag, &mut consumers),
+            Pass::DotFolding => fold_dots(&mut dag, &mut consumers),
+            Pass::DeadCode => {
+                let (new_dag, pass_map) = remove_dead_code(&dag);
+                dag = new_dag;
+                pass_map
+            }
+        };
+        instrs_map = compose_instructions_maps(&instrs_map, &pass_map);
+    }
+    (dag, instrs_map)
+}
+
+/// The instructions map of a rewrite in place, where operator `i` is computed by `forward[i]`.
+fn forward_map(forward: impl IntoIterator<Item = usize>) -> Vec<Vec<OperatorIndex>> {
+    forward
+        .into_iter()
+        .map(|i| vec![OperatorIndex(i)])
+        .collect()
+}
+
+fn consumers_count(dag: &Dag) -> Vec<usize> {
+    let mut consumers = vec![0; dag.len()];
+    for op in &dag.operators {
+        for input in op.get_inputs_iter() {
+            consumers[input.0] += 1;
+        }
+    }
+    consumers
+}
+
+/// An operator can be merged into its consumer if nothing else sees its result.
+fn mergeable(dag: &Dag, consumers: &[usize], i: usize) -> bool {
+    consumers[i] == 1 && !dag.output_state[i].is_output()
+}
+
+/// Forgets a use of operator `i`, an operator left dead no longer uses its own inputs.
+fn release(dag: &Dag, consumers: &mut [usize], i: usize) {
+    consumers[i] -= 1;
+    if consumers[i] == 0 && !dag.output_state[i].is_output() {
+        for input in dag.operators[i].get_inputs_iter() {
+            release(dag, consumers, input.0);
+        }
+    }
+}
+
+fn replace_operator(dag: &mut Dag, consumers: &mut [usize], i: usize, op: Operator) {
+    for input in op.get_inputs_iter() {
+        consumers[input.0] += 1;
+        dag.output_state[input.0].transition_use();
+    }
+    let old = std::mem::replace(&mut dag.operators[i], op);
+    for input in old.get_inputs_iter() {
+        release(dag, consumers, input.0);
+    }
+}
+
+fn inputs_mut(op: &mut Operator) -> &mut [OperatorIndex] {
+    match op {
+        Operator::Input { .. } => &mut [],
+        Operator::Lut { input, .. }
+        | Operator::UnsafeCast { input, .. }
+        | Operator::Round { input, .. }
+        | Operator::MaxPool { input, .. }
+        | Operator::Reshape { input, .. }
+        | Operator::Transpose { input, .. }
+        | Operator::Slice { input, .. }
+        | Operator::Gather { input, .. }
+        | Operator::Broadcast { input, .. }
+        | Operator::Conv2d { input, .. } => std::slice::from_mut(input),
+        Operator::Dot { inputs, .. }
+        | Operator::LevelledOp { inputs, .. }
+        | Operator::Concatenate { inputs, .. } => inputs,
+        Operator::Bivariate { inputs, .. } | Operator::Mul { inputs, .. } => inputs,
+    }
+}
+
+/// Makes the consumers of operator `from` use operator `to` instead.
+fn replace_uses(dag: &mut Dag, consumers: &mut [usize], from: usize, to: usize) {
+    for i in from + 1..dag.len() {
+        if dag.operators[i]
+            .get_inputs_iter()
+            .all(|input| input.0 != from)
+        {
+            continue;
+        }
+        let mut op = dag.operators[i].clone();
+        for input in inputs_mut(&mut op) {
+            if input.0 == from {
+                input.0 = to;
+            }
+        }
+        replace_operator(dag, consumers, i, op);
+    }
+}
+
+fn fold_casts(dag: &mut Dag, consumers: &mut [usize]) -> Vec<Vec<OperatorIndex>> {
+    let mut forward: Vec<_> = (0..dag.len()).collect();
+    for (i, forwarded) in forward.iter_mut().enumerate() {
+        let Operator::UnsafeCast {
+            input,
+            out_precision,
+        } = dag.operators[i]
+        else {
+            continue;
+        };
+        let input = match dag.operators[input.0] {
+            Operator::UnsafeCast {
+                input: cast_input, ..
+            } if mergeable(dag, consumers, input.0) => {
+                let folded = Operator::UnsafeCast {
+                    input: cast_input,
+                    out_precision,
+                };
+                replace_operator(dag, consumers, i, folded);
+                cast_input
+            }
+            _ => input,
+        };
+        if dag.out_precisions[input.0] == out_precision && !dag.output_state[i].is_output() {
+            replace_uses(dag, consumers, i, input.0);
+            *forwarded = input.0;
+        }
+    }
+    forward_map(forward)
+}
+
+fn eliminate_common_subexpressions(
+    dag: &mut Dag,
+    consumers: &mut [usize],
+) -> Vec<Vec<OperatorIndex>> {
+    let mut forward: Vec<_> = (0..dag.len()).collect();
+    // operators by inputs, the consumers of a merged operator use the first one
+    let mut known: HashMap<Vec<usize>, Vec<usize>> = HashMap::new();
+    for i in 0..dag.len() {
+        let comparable = match &dag.operators[i] {
+            Operator::Input { .. } | Operator::LevelledOp { .. } => false,
+            Operator::Lut { table, .. } | Operator::Bivariate { table, .. } => {
+                !table.values.is_empty()
+            }
+            Operator::Dot { .. }
+            | Operator::UnsafeCast { .. }
+            | Operator::Round { .. }
+            | Operator::Mul { .. }
+            | Operator::MaxPool { .. }
+            | Operator::Reshape { .. }
+            | Operator::Transpose { .. }
+            | Operator::Concatenate { .. }
+            | Operator::Slice { .. }
+            | Operator::Gather { .. }
+            | Operator::Broadcast { .. }
+            | Operator::Conv2d { .. } => true,
+        };
+        if !comparable || consumers[i] == 0 || dag.output_state[i].is_output() {
+            continue;
+        }
+        let inputs: Vec<usize> = dag.operators[i]
+            .get_inputs_iter()
+            .map(|input| input.0)
+            .collect();
+        let same = known.get(&inputs).and_then(|candidates| {
+            candidates.iter().copied().find(|&k| {
+                dag.operators[k] == dag.operators[i] && dag.circuit_tags[k] == dag.circuit_tags[i]
+            })
+        });
+        if let Some(k) = same {
+            replace_uses(dag, consumers, i, k);
+            forward[i] = k;
+        } else {
+            known.entry(inputs).or_default().push(i);
+        }
+    }
+    forward_map(forward)
+}
+
+/// The table of `outer` applied on the results of `inner`, unknown if any of them is.
+fn compose_tables(outer: &FunctionTable, inner: &FunctionTable) -> FunctionTable {
+    inner
+        .values
+        .iter()
+        .map(|&value| outer.values.get(value as usize).copied())
+        .collect::<Option<_>>()
+        .map_or(FunctionTable::UNKWOWN, |values| FunctionTable { values })
+}
+
+fn fuse_luts(dag: &mut Dag, consumers: &mut [usize]) -> Vec<Vec<OperatorIndex>> {
+    for i in 0..dag.len() {
+        let Operator::Lut {
+            input,
+            table,
+            out_precision,
+        } = &dag.operators[i]
+        else {
+            continue;
+        };
+        let Operator::Lut {
+            input: lut_input,
+            table: lut_table,
+            ..
+        } = &dag.operators[input.0]
+        else {
+            continue;
+        };
+        if !mergeable(dag, consumers, input.0) {
+            continue;
+        }
+        let fused = Operator::Lut {
+            input: *lut_input,
+            table: compose_tables(table, lut_table),
+            out_precision: *out_precision,
+        };
+        replace_operator(dag, consumers, i, fused);
+    }
+    forward_map(0..dag.len())
+}
+
+/// The inputs and weights of a dot of numbers.
+fn numbers_dot(dag: &Dag, i: usize) -> Option<(Vec<OperatorIndex>, Vec<i64>)> {
+    match &dag.operators[i] {
+        Operator::Dot { inputs, weights }
+            if weights.rank() <= 1
+                && weights.values.len() == inputs.len()
+                && inputs
+                    .iter()
+                    .all(|input| dag.out_shapes[input.0].is_number()) =>
+        {
+            Some((inputs.clone(), weights.values.clone()))
+        }
+        _ => None,
+    }
+}
+
+fn fold_dots(dag: &mut Dag, consumers: &mut [usize]) -> Vec<Vec<OperatorIndex>> {
+    for i in 0..dag.len() {
+        let Some((inputs, weights)) = numbers_dot(dag, i) else {
+            continue;
+        };
+        let mut folded_inputs = vec![];
+        let mut folded_weights = vec![];
+        for (input, weight) in inputs.iter().zip(&weights) {
+            let folded = numbers_dot(dag, input.0)
+                .filter(|_| mergeable(dag, consumers, input.0))
+                .and_then(|(dot_inputs, dot_weights)| {
+                    let dot_weights: Option<Vec<_>> =
+                        dot_weights.iter().map(|w| w.checked_mul(*weight)).collect();
+                    dot_weights.map(|dot_weights| (dot_inputs, dot_weights))
+                });
+            if let Some((dot_inputs, dot_weights)) = folded {
+                folded_inputs.extend(dot_inputs);
+                folded_weights.extend(dot_weights);
+            } else {
+                folded_inputs.push(*input);
+                folded_weights.push(*weight);
+            }
+        }
+        if folded_inputs != inputs {
+            let folded = Operator::Dot {
+                inputs: folded_inputs,
+                weights: Weights::vector(folded_weights),
+            };
+            replace_operator(dag, consumers, i, folded);
+        }
+    }
+    forward_map(0..dag.len())
+}
+
+fn remove_dead_code(dag: &Dag) -> (Dag, Vec<Vec<OperatorIndex>>) {
+    let mut live = vec![false; dag.len()];
+    for i in (0..dag.len()).rev() {
+        live[i] |=
+            matches!(dag.operators[i], Operator::Input { .. }) || dag.output_state[i].is_output();
+        if live[i] {
+            for input in dag.operators[i].get_inputs_iter() {
+                live[input.0] = true;
+            }
+        }
+    }
+    // A dead operator is replaced by its first input, only dead operators use it
+    regen(dag, &mut |i, op, _| {
+        if live[i] {
+            None
+        } else {
+            op.get_inputs_iter().next().copied()
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dag::operator::{LevelledComplexity, Shape};
+
+    fn instrs(indexes: &[usize]) -> Vec<OperatorIndex> {
+        indexes.iter().copied().map(OperatorIndex).collect()
+    }
+
+    #[test]
+    fn fold_casts_chain() {
+        let mut dag = Dag::new();
+        let input = dag.add_input(3, Shape::number());
+        let cast = dag.add_unsafe_cast(input, 5);
+        let cast_back = dag.add_unsafe_cast(cast, 3);
+        let _ = dag.add_lut(cast_back, FunctionTable::UNKWOWN, 3);
+        let (simplified, instrs_map) = run_passes(&dag, &ALL_PASSES);
+        assert_eq!(simplified.len(), 2);
+        assert_eq!(
+            simplified.operators[1],
+            Operator::Lut {
+                input,
+                table: FunctionTable::UNKWOWN,
+                out_precision: 3
+            }
+        );
+        assert_eq!(
+            instrs_map,
+            vec![instrs(&[0]), instrs(&[]), instrs(&[0]), instrs(&[1])]
+        );
+    }
+
+    #[test]
+    fn eliminate_common_subexpressions() {
+        let mut dag = Dag::new();
+        let input = dag.add_input(3, Shape::number());
+        let dot_1 = dag.add_dot([input], [2]);
+        let dot_2 = dag.add_dot([input], [2]);
+        let lut_1 = dag.add_lut(dot_1, FunctionTable::UNKWOWN, 3);
+        let lut_2 = dag.add_lut(dot_2, FunctionTable::UNKWOWN, 3);
+        let _ = dag.add_dot([lut_1, lut_2], [1, 1]);
+        let (simplified, instrs_map) = run_passes(&dag, &ALL_PASSES);
+        // the dots are merged, but not the luts of unknown tables
+        assert_eq!(simplified.len(), 5);
+        assert_eq!(
+            instrs_map,
+            vec![
+                instrs(&[0]),
+                instrs(&[1]),
+                instrs(&[1]),
+                instrs(&[2]),
+                instrs(&[3]),
+                instrs(&[4])
+            ]
+        );
+        let table = FunctionTable {
+            values: vec![0, 1, 0, 1, 0, 1, 0, 1],
+        };
+        let mut dag = Dag::new();
+        let input = dag.add_input(3, Shape::number());
+        let lut_1 = dag.add_lut(input, table.clone(), 3);
+        let lut_2 = dag.add_lut(input, table, 3);
+        let _ = dag.add_dot([lut_1, lut_2], [1, 1]);
+        assert_eq!(run_passes(&dag, &ALL_PASSES).0.len(), 3);
+    }
+
+    #[test]
+    fn fuse_luts() {
+        let mut dag = Dag::new();
+        let input = dag.add_input(2, Shape::number());
+        let inc = dag.add_lut(
+            input,
+            FunctionTable {
+                values: vec![1, 2, 3, 0],
+            },
+            2,
+        );
+        let _ = dag.add_lut(
+            inc,
+            FunctionTable {
+                values: vec![0, 2, 4, 6],
+            },
+            3,
+        );
+        let (simplified, instrs_map) = run_passes(&dag, &ALL_PASSES);
+        assert_eq!(
+            simplified.operators,
+            vec![
+                dag.operators[input.0].clone(),
+                Operator::Lut {
+                    input,
+                    table: FunctionTable {
+                        values: vec![2, 4, 6, 0],
+                    },
+                    out_precision: 3,
+                },
+            ]
+        );
+        assert_eq!(instrs_map, vec![instrs(&[0]), instrs(&[]), instrs(&[1])]);
+        // a returned lut is kept
+        dag.tag_operator_as_output(inc);
+        assert_eq!(run_passes(&dag, &ALL_PASSES).0.len(), 3);
+    }
+
+    #[test]
+    fn fold_dots() {
+        let mut dag = Dag::new();
+        let input_1 = dag.add_input(3, Shape::number());
+        let input_2 = dag.add_input(3, Shape::number());
+        let sum = dag.add_dot([input_1, input_2], [1, 2]);
+        let _ = dag.add_dot([sum, input_1], [3, 1]);
+        let (simplified, _) = run_passes(&dag, &ALL_PASSES);
+        assert_eq!(
+            simplified.operators[2],
+            Operator::Dot {
+                inputs: vec![input_1, input_2, input_1],
+                weights: Weights::vector([3, 6, 1]),
+            }
+        );
+        assert!(simplified.validate().is_ok());
+    }
+
+    #[test]
+    fn remove_dead_code() {
+        let mut dag = Dag::new();
+        let input = dag.add_input(3, Shape::number());
+        let lut = dag.add_lut(input, FunctionTable::UNKWOWN, 3);
+        let dead = dag.add_levelled_op(
+            [lut],
+            LevelledComplexity::ADDITION,
+            1.0,
+            Shape::number(),
+            "dead",
+        );
+        let output = dag.add_lut(lut, FunctionTable::UNKWOWN, 3);
+        // the dead operator was used by a removed operator
+        dag.output_state[dead.0].transition_use();
+        let (simplified, instrs_map) = run_passes(&dag, &[Pass::DeadCode]);
+        assert_eq!(
+            instrs_map,
+            vec![instrs(&[0]), instrs(&[1]), instrs(&[]), instrs(&[2])]
+        );
+        assert_eq!(
+            simplified.get_output_operators_iter().next().unwrap().id,
+            OperatorIndex(instrs_map[output.0][0].0)
+        );
+        assert!(simplified.validate().is_ok());
+    }
+}
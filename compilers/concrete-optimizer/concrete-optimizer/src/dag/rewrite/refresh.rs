@@ -0,0 +1,254 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::dag::operator::{FunctionTable, Operator, OperatorIndex, Precision};
+use crate::dag::unparametrized::{Dag, DagBuilder, OutputState};
+
+use super::regen::regen;
+
+/// An identity lut inserted by [`insert_refreshes`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Refresh {
+    /// The refreshed operator, an output or an input of the original dag
+    pub refreshed: OperatorIndex,
+    /// The identity lut, in the rewritten dag
+    pub lut: OperatorIndex,
+    /// The number of bootstraps of the lut, i.e. the size of the refreshed tensor
+    pub nb_bootstraps: u64,
+    /// The cost of the bootstraps at the refreshed precision
+    pub cost: f64,
+}
+
+/// Tells, for each operator, whether it carries the noise of an input fed by a composition, and
+/// not yet refreshed.
+fn carries_composed_noise(dag: &Dag, noisy_inputs: &HashSet<usize>) -> Vec<bool> {
+    let mut noisy = Vec::with_capacity(dag.len());
+    for (i, op) in dag.operators.iter().enumerate() {
+        let carries = match op {
+            Operator::Input { .. } => noisy_inputs.contains(&i),
+            // the product is a difference of luts
+            Operator::Lut { .. } | Operator::Bivariate { .. } | Operator::Mul { .. } => false,
+            _ => op.get_inputs_iter().any(|input| noisy[input.0]),
+        };
+        noisy.push(carries);
+    }
+    noisy
+}
+
+/// The cost of refreshing operator `i`, a bootstrap per element at its precision.
+/// The dag is validated before its rewrite, an empty tensor would make the cost 0 or NaN.
+fn refresh_cost(dag: &Dag, i: usize, pbs_cost: &impl Fn(Precision) -> f64) -> f64 {
+    let size = dag.out_shapes[i].flat_size();
+    debug_assert_ne!(size, 0, "empty tensors are rejected by the dag validation");
+    size as f64 * pbs_cost(dag.out_precisions[i])
+}
+
+/// Chooses the operators to refresh so that no composition feeds an input with a noise that
+/// depends on a composed input. Each step refreshes the output or input resolving the most
+/// offending compositions per cost.
+/// This is conservative: an acyclic chain of circuits is refreshed as a cycle would be.
+fn refresh_points(
+    dag: &Dag,
+    compositions: &[(OperatorIndex, OperatorIndex)],
+    pbs_cost: &impl Fn(Precision) -> f64,
+) -> Vec<usize> {
+    let mut refreshed = HashSet::new();
+    let mut noisy_inputs: HashSet<usize> = compositions.iter().map(|(_, input)| input.0).collect();
+    loop {
+        let noisy = carries_composed_noise(dag, &noisy_inputs);
+        let offending: Vec<_> = compositions
+            .iter()
+            .filter(|(output, input)| {
+                noisy[output.0] && !refreshed.contains(&output.0) && noisy_inputs.contains(&input.0)
+            })
+            .collect();
+        let candidates = offending
+            .iter()
+            .map(|(output, _)| (output.0, false))
+            .chain(offending.iter().map(|(_, input)| (input.0, true)));
+        let mut best: Option<(usize, bool, f64)> = None;
+        for (candidate, is_input) in candidates {
+            let resolved = offending
+                .iter()
+                .filter(|(output, input)| candidate == if is_input { input.0 } else { output.0 })
+                .count();
+            let score = resolved as f64 / refresh_cost(dag, candidate, pbs_cost);
+            if !matches!(best, Some((_, _, best_score)) if best_score >= score) {
+                best = Some((candidate, is_input, score));
+            }
+        }
+        let Some((candidate, is_input, _)) = best else {
+            break;
+        };
+        if is_input {
+            let _ = noisy_inputs.remove(&candidate);
+        }
+        let _ = refreshed.insert(candidate);
+    }
+    let mut refreshed: Vec<_> = refreshed.into_iter().collect();
+    refreshed.sort_unstable();
+    refreshed
+}
+
+/// Inserts identity luts on composed outputs or inputs so that the noise of a circuit output no
+/// longer grows through the compositions, i.e. so that the circuit becomes composable.
+///
+/// Compositions are the declared ones, or all outputs to all inputs when `composable`.
+/// `pbs_cost` gives the cost of a bootstrap at a precision, to refresh the cheapest operators.
+/// Returns the rewritten dag, for each operator the operators computing it in the rewritten dag, a
+/// refreshed one being computed by its lut last, and the inserted luts in the order of the
+/// original operators.
+pub fn insert_refreshes(
+    dag: &Dag,
+    composable: bool,
+    pbs_cost: impl Fn(Precision) -> f64,
+) -> (Dag, Vec<Vec<OperatorIndex>>, Vec<Refresh>) {
+    let points = refresh_points(dag, &dag.get_compositions(composable), &pbs_cost);
+    if points.is_empty() {
+        let instrs_map = (0..dag.len()).map(|i| vec![OperatorIndex(i)]).collect();
+        return (dag.clone(), instrs_map, vec![]);
+    }
+    let mut refresh = |i: usize, op: &Operator, builder: &mut DagBuilder<'_>| {
+        if points.binary_search(&i).is_err() {
+            return None;
+        }
+        let precision = dag.out_precisions[i];
+        let refreshed = builder.add_operator(op.clone());
+        let lut = builder.add_lut(refreshed, FunctionTable::UNKWOWN, precision);
+        if dag.output_state[i] == OutputState::Tagged {
+            builder.tag_operator_as_output(lut);
+        }
+        Some(lut)
+    };
+    let (new_dag, instrs_map) = regen(dag, &mut refresh);
+    let refreshes = points
+        .into_iter()
+        .map(|i| Refresh {
+            refreshed: OperatorIndex(i),
+            lut: *instrs_map[i].last().unwrap(),
+            nb_bootstraps: dag.out_shapes[i].flat_size(),
+            cost: refresh_cost(dag, i, &pbs_cost),
+        })
+        .collect();
+    (new_dag, instrs_map, refreshes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dag::operator::{LevelledComplexity, Shape, Weights};
+
+    fn unit_cost(_: Precision) -> f64 {
+        1.0
+    }
+
+    #[test]
+    fn refresh_output() {
+        let mut dag = Dag::new();
+        let mut builder = dag.builder("main");
+        let input = builder.add_input(3, Shape::number());
+        let lut = builder.add_lut(input, FunctionTable::UNKWOWN, 3);
+        let output = builder.add_dot([input, lut], Weights::vector([1, 1]));
+        builder.tag_operator_as_output(lut);
+        dag.add_composition(output, input);
+        let (new_dag, _, refreshes) = insert_refreshes(&dag, false, unit_cost);
+        assert_eq!(
+            refreshes,
+            vec![Refresh {
+                refreshed: output,
+                lut: OperatorIndex(3),
+                nb_bootstraps: 1,
+                cost: 1.0,
+            }]
+        );
+        assert_eq!(new_dag.compositions, vec![(OperatorIndex(3), input)]);
+        assert_eq!(new_dag.get_output_operators_iter().count(), 2);
+        assert!(new_dag.validate().is_ok());
+        assert!(insert_refreshes(&new_dag, false, unit_cost).2.is_empty());
+    }
+
+    #[test]
+    fn refresh_input_fed_by_several_outputs() {
+        let mut dag = Dag::new();
+        let mut builder = dag.builder("main");
+        let input = builder.add_input(3, Shape::number());
+        let output_1 = builder.add_dot([input], Weights::number(2));
+        let output_2 = builder.add_dot([input], Weights::number(3));
+        dag.add_composition(output_1, input);
+        dag.add_composition(output_2, input);
+        let (new_dag, _, refreshes) = insert_refreshes(&dag, false, unit_cost);
+        assert_eq!(
+            refreshes,
+            vec![Refresh {
+                refreshed: input,
+                lut: OperatorIndex(1),
+                nb_bootstraps: 1,
+                cost: 1.0,
+            }]
+        );
+        // The compositions still feed the input, not its refresh
+        assert_eq!(
+            new_dag.compositions,
+            vec![(OperatorIndex(2), input), (OperatorIndex(3), input)]
+        );
+        assert!(new_dag.validate().is_ok());
+        assert!(insert_refreshes(&new_dag, false, unit_cost).2.is_empty());
+    }
+
+    #[test]
+    fn refresh_smaller_tensors() {
+        let mut dag = Dag::new();
+        let mut builder = dag.builder("main");
+        let small = builder.add_input(3, Shape::number());
+        let large = builder.add_input(3, Shape::vector(8));
+        let sum = builder.add_dot([small], Weights::number(8));
+        let output = builder.add_levelled_op(
+            [sum, large],
+            LevelledComplexity::ZERO,
+            1.0,
+            Shape::vector(8),
+            "add",
+        );
+        // All outputs are composed to all inputs, the small input is the cheapest refresh, then
+        // the output and the large input cost the same
+        let (_, _, refreshes) = insert_refreshes(&dag, true, unit_cost);
+        let refreshed: Vec<_> = refreshes.iter().map(|refresh| refresh.refreshed).collect();
+        assert_eq!(refreshed, vec![small, output]);
+    }
+
+    #[test]
+    fn refresh_lower_precisions() {
+        let mut dag = Dag::new();
+        let mut builder = dag.builder("main");
+        let input = builder.add_input(2, Shape::number());
+        let cast = builder.add_unsafe_cast(input, 6);
+        let output = builder.add_dot([cast], Weights::number(2));
+        let (_, _, refreshes) = insert_refreshes(&dag, true, unit_cost);
+        assert_eq!(refreshes[0].refreshed, output);
+        // the input resolves the same composition with a cheaper bootstrap
+        let (_, _, refreshes) =
+            insert_refreshes(&dag, true, |precision| f64::from(1_u32 << precision));
+        assert_eq!(
+            refreshes,
+            vec![Refresh {
+                refreshed: input,
+                lut: OperatorIndex(1),
+                nb_bootstraps: 1,
+                cost: 4.0,
+            }]
+        );
+    }
+
+    #[test]
+    fn nothing_to_refresh() {
+        let mut dag = Dag::new();
+        let mut builder = dag.builder("main");
+        let input = builder.add_input(3, Shape::number());
+        let output = builder.add_lut(input, FunctionTable::UNKWOWN, 3);
+        dag.add_composition(output, input);
+        let (new_dag, _, refreshes) = insert_refreshes(&dag, true, unit_cost);
+        assert!(refreshes.is_empty());
+        assert_eq!(new_dag.len(), dag.len());
+    }
+}
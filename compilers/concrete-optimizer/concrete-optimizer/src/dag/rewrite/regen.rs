@@ -8,8 +8,22 @@ fn reindex_op_inputs(op: &Operator, old_index_to_new: &[usize]) -> Operator {
         Operator::Input { .. } => (),
         Operator::Lut { input, .. }
         | Operator::UnsafeCast { input, .. }
-        | Operator::Round { input, .. } => input.0 = old_index_to_new[input.0],
-        Operator::Dot { inputs, .. } | Operator::LevelledOp { inputs, .. } => {
+        | Operator::Round { input, .. }
+        | Operator::MaxPool { input, .. }
+        | Operator::Reshape { input, .. }
+        | Operator::Transpose { input, .. }
+        | Operator::Slice { input, .. }
+        | Operator::Gather { input, .. }
+        | Operator::Broadcast { input, .. }
+        | Operator::Conv2d { input, .. } => input.0 = old_index_to_new[input.0],
+        Operator::Dot { inputs, .. }
+        | Operator::LevelledOp { inputs, .. }
+        | Operator::Concatenate { inputs, .. } => {
+            for input in inputs {
+                input.0 = old_index_to_new[input.0];
+            }
+        }
+        Operator::Bivariate { inputs, .. } | Operator::Mul { inputs, .. } => {
             for input in inputs {
                 input.0 = old_index_to_new[input.0];
             }
@@ -41,7 +55,22 @@ pub(crate) fn regen(
                 .for_each(|n| regen_dag.output_state[n.0].transition_use());
         }
     }
-    (regen_dag, instructions_multi_map(&old_index_to_new))
+    let multi_map = instructions_multi_map(&old_index_to_new);
+    // An output is replaced by the last instruction generated for it, an input by the first one,
+    // any instruction generated after it consumes it.
+    let new_output = |index: &OperatorIndex| OperatorIndex(old_index_to_new[index.0]);
+    let new_input = |index: &OperatorIndex| {
+        multi_map[index.0]
+            .first()
+            .copied()
+            .unwrap_or_else(|| new_output(index))
+    };
+    regen_dag.compositions = dag
+        .compositions
+        .iter()
+        .map(|(output, input)| (new_output(output), new_input(input)))
+        .collect();
+    (regen_dag, multi_map)
 }
 
 fn instructions_multi_map(old_index_to_new: &[usize]) -> Vec<Vec<OperatorIndex>> {
@@ -49,13 +78,30 @@ fn instructions_multi_map(old_index_to_new: &[usize]) -> Vec<Vec<OperatorIndex>>
     let mut result = vec![];
     result.reserve_exact(old_index_to_new.len());
     for &new_instr in old_index_to_new {
-        let start_from = last_new_instr.map_or(new_instr, |v: usize| v + 1);
+        let start_from = last_new_instr.map_or(0, |v: usize| v + 1);
         if start_from <= new_instr {
             result.push((start_from..=new_instr).map(OperatorIndex).collect());
         } else {
             result.push(vec![]);
         }
-        last_new_instr = Some(new_instr);
+        // an operator replaced by a previous one must not rewind the next ranges
+        last_new_instr = Some(last_new_instr.map_or(new_instr, |v| v.max(new_instr)));
     }
     result
 }
+
+/// Chains the instructions maps of two successive rewrites.
+pub(crate) fn compose_instructions_maps(
+    first: &[Vec<OperatorIndex>],
+    second: &[Vec<OperatorIndex>],
+) -> Vec<Vec<OperatorIndex>> {
+    first
+        .iter()
+        .map(|instrs| {
+            instrs
+                .iter()
+                .flat_map(|instr| second[instr.0].iter().copied())
+                .collect()
+        })
+        .collect()
+}
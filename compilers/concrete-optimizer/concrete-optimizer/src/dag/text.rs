@@ -0,0 +1,1235 @@
+//! Textual representation of a [`Dag`], printed by its `Display` and parsed by its `FromStr`.
+//!
+//! Each line is a statement, `//` starts a comment:
+//!
+//! ```text
+//! circuit "main"
+//! %0 = input u4 shape [2]
+//! %1 = input u4 shape [2]
+//! %2 = dot [%0, %1] weights [1, -2]
+//! %3 = lut %2 u4 table [0, 1, 4, 9, 16, 25, 36, 49, 64, 81, 100, 121, 144, 169, 196, 225]
+//! output %3
+//! %4 = levelled [%3] manp 2.5 complexity [1.0, 0.0] shape [2] comment "neg"
+//! %5 = cast %4 u5
+//! %6 = round %5 u3
+//! %7 = bivariate [%0, %1] u3
+//! %8 = mul [%0, %1] u8
+//! %9 = maxpool %8 window [2]
+//! %10 = reshape %9 shape [1, 1]
+//! %11 = broadcast %10 shape [2, 3]
+//! %12 = transpose %11 axes [1, 0]
+//! %13 = concat [%12, %12] axis 1
+//! %14 = slice %13 offsets [1, 0] sizes [2, 2] strides [1, 2]
+//! %15 = gather %14 axis 0 indices [1, 0, 1]
+//! %16 = reshape %15 shape [1, 1, 3, 2]
+//! %17 = conv2d %16 weights [1, -1, 2, 0] shape [2, 1, 2, 1] padding [1, 0, 0, 0]
+//! ```
+//!
+//! Operators are numbered in order, `circuit` starts the operators of a circuit (the anonymous
+//! `"_"` one before any `circuit`), `output` tags an operator as an output even if it is used and
+//! `compose %3 to %0` feeds an output to an input, see [`Dag::add_composition`].
+//! Omitted attributes default to a number shape, a vector of weights or indices, an unknown table,
+//! a zero complexity, an empty comment, zero offsets and padding, unit strides and dilations and a
+//! single convolution group.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::dag::operator::{
+    FunctionTable, Indices, LevelledComplexity, Operator, OperatorIndex, Precision, Shape, Weights,
+};
+use crate::dag::unparametrized::{Dag, OutputState, DEFAULT_CIRCUIT};
+
+impl fmt::Display for Dag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut circuit = None;
+        for op in self.get_operators_iter() {
+            if circuit != Some(op.circuit_tag) {
+                writeln!(f, "circuit {:?}", op.circuit_tag)?;
+                circuit = Some(op.circuit_tag);
+            }
+            writeln!(f, "%{} = {}", op.id, Statement(op.operator))?;
+            if *op.output_state == OutputState::Tagged {
+                writeln!(f, "output %{}", op.id)?;
+            }
+        }
+        for (output, input) in &self.compositions {
+            writeln!(f, "compose %{output} to %{input}")?;
+        }
+        Ok(())
+    }
+}
+
+/// An operator as written in the text, `Operator`'s `Display` being a lossy summary.
+struct Statement<'op>(&'op Operator);
+
+struct Ref(OperatorIndex);
+
+impl fmt::Display for Ref {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "%{}", self.0)
+    }
+}
+
+fn write_list<T: fmt::Display>(
+    f: &mut fmt::Formatter,
+    items: impl IntoIterator<Item = T>,
+) -> fmt::Result {
+    write!(f, "[")?;
+    for (i, item) in items.into_iter().enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+        write!(f, "{item}")?;
+    }
+    write!(f, "]")
+}
+
+fn write_shape(f: &mut fmt::Formatter, shape: &Shape) -> fmt::Result {
+    if shape.is_number() {
+        return Ok(());
+    }
+    write!(f, " shape ")?;
+    write_list(f, &shape.dimensions_size)
+}
+
+/// Writes ` name [values]`, unless the values are the attribute default.
+fn write_attribute<T: fmt::Display + PartialEq>(
+    f: &mut fmt::Formatter,
+    name: &str,
+    values: &[T],
+    default: &T,
+) -> fmt::Result {
+    if values.iter().all(|value| value == default) {
+        return Ok(());
+    }
+    write!(f, " {name} ")?;
+    write_list(f, values)
+}
+
+/// Writes the values of a clear tensor, followed by its shape if it is not a vector.
+fn write_tensor<T: fmt::Display>(
+    f: &mut fmt::Formatter,
+    name: &str,
+    values: &[T],
+    shape: &Shape,
+) -> fmt::Result {
+    write!(f, " {name} ")?;
+    write_list(f, values)?;
+    if *shape == Shape::vector(values.len() as u64) {
+        return Ok(());
+    }
+    write!(f, " shape ")?;
+    write_list(f, &shape.dimensions_size)
+}
+
+impl fmt::Display for Statement<'_> {
+    #[allow(clippy::too_many_lines)]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            Operator::Input {
+                out_precision,
+                out_shape,
+            } => {
+                write!(f, "input u{out_precision}")?;
+                write_shape(f, out_shape)
+            }
+            Operator::Lut {
+                input,
+                table,
+                out_precision,
+            } => {
+                write!(f, "lut %{input} u{out_precision}")?;
+                if table.values.is_empty() {
+                    return Ok(());
+                }
+                write!(f, " table ")?;
+                write_list(f, &table.values)
+            }
+            Operator::Dot { inputs, weights } => {
+                write!(f, "dot ")?;
+                write_list(f, inputs.iter().copied().map(Ref))?;
+                write_tensor(f, "weights", &weights.values, &weights.shape)
+            }
+            Operator::LevelledOp {
+                inputs,
+                complexity,
+                manp,
+                out_shape,
+                comment,
+            } => {
+                write!(f, "levelled ")?;
+                write_list(f, inputs.iter().copied().map(Ref))?;
+                write!(f, " manp {manp:?}")?;
+                if *complexity != LevelledComplexity::ZERO {
+                    write!(
+                        f,
+                        " complexity [{:?}, {:?}]",
+                        complexity.lwe_dim_cost_factor, complexity.fixed_cost
+                    )?;
+                }
+                write_shape(f, out_shape)?;
+                if comment.is_empty() {
+                    return Ok(());
+                }
+                write!(f, " comment {comment:?}")
+            }
+            Operator::UnsafeCast {
+                input,
+                out_precision,
+            } => write!(f, "cast %{input} u{out_precision}"),
+            Operator::Round {
+                input,
+                out_precision,
+            } => write!(f, "round %{input} u{out_precision}"),
+            Operator::Bivariate {
+                inputs,
+                table,
+                out_precision,
+            } => {
+                write!(f, "bivariate ")?;
+                write_list(f, inputs.iter().copied().map(Ref))?;
+                write!(f, " u{out_precision}")?;
+                if table.values.is_empty() {
+                    return Ok(());
+                }
+                write!(f, " table ")?;
+                write_list(f, &table.values)
+            }
+            Operator::Mul {
+                inputs,
+                out_precision,
+            } => {
+                write!(f, "mul ")?;
+                write_list(f, inputs.iter().copied().map(Ref))?;
+                write!(f, " u{out_precision}")
+            }
+            Operator::MaxPool { input, window } => {
+                write!(f, "maxpool %{input} window ")?;
+                write_list(f, &window.dimensions_size)
+            }
+            Operator::Reshape { input, out_shape } => {
+                write!(f, "reshape %{input}")?;
+                write_shape(f, out_shape)
+            }
+            Operator::Transpose { input, axes } => {
+                write!(f, "transpose %{input} axes ")?;
+                write_list(f, axes)
+            }
+            Operator::Concatenate { inputs, axis } => {
+                write!(f, "concat ")?;
+                write_list(f, inputs.iter().copied().map(Ref))?;
+                write!(f, " axis {axis}")
+            }
+            Operator::Slice {
+                input,
+                offsets,
+                sizes,
+                strides,
+            } => {
+                write!(f, "slice %{input}")?;
+                write_attribute(f, "offsets", offsets, &0)?;
+                write!(f, " sizes ")?;
+                write_list(f, sizes)?;
+                write_attribute(f, "strides", strides, &1)
+            }
+            Operator::Gather {
+                input,
+                axis,
+                indices,
+            } => {
+                write!(f, "gather %{input} axis {axis}")?;
+                write_tensor(f, "indices", &indices.values, &indices.shape)
+            }
+            Operator::Broadcast { input, out_shape } => {
+                write!(f, "broadcast %{input}")?;
+                write_shape(f, out_shape)
+            }
+            Operator::Conv2d {
+                input,
+                weights,
+                strides,
+                padding,
+                dilations,
+                groups,
+            } => {
+                write!(f, "conv2d %{input}")?;
+                write_tensor(f, "weights", &weights.values, &weights.shape)?;
+                write_attribute(f, "strides", strides, &1)?;
+                write_attribute(f, "padding", padding, &0)?;
+                write_attribute(f, "dilations", dilations, &1)?;
+                if *groups == 1 {
+                    return Ok(());
+                }
+                write!(f, " groups {groups}")
+            }
+        }
+    }
+}
+
+/// An error in a dag text, with the 1-based line and column where it was found.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Token<'text> {
+    Word(&'text str),
+    Str(String),
+    Open,
+    Close,
+    Comma,
+    Equal,
+}
+
+impl fmt::Display for Token<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Word(word) => write!(f, "`{word}`"),
+            Self::Str(string) => write!(f, "{string:?}"),
+            Self::Open => write!(f, "`[`"),
+            Self::Close => write!(f, "`]`"),
+            Self::Comma => write!(f, "`,`"),
+            Self::Equal => write!(f, "`=`"),
+        }
+    }
+}
+
+/// The tokens of a line, with their column.
+struct Tokens<'text> {
+    line: usize,
+    end_column: usize,
+    items: Vec<(usize, Token<'text>)>,
+    position: usize,
+    attributes: Vec<&'text str>,
+}
+
+fn is_delimiter(c: char) -> bool {
+    c.is_whitespace() || matches!(c, '[' | ']' | ',' | '=' | '"')
+}
+
+impl<'text> Tokens<'text> {
+    fn new(line: usize, text: &'text str) -> Result<Self, ParseError> {
+        let mut tokens = Self {
+            line,
+            end_column: text.chars().count() + 1,
+            items: vec![],
+            position: 0,
+            attributes: vec![],
+        };
+        let mut chars = text.char_indices().zip(1..).peekable();
+        while let Some(((start, c), column)) = chars.next() {
+            let token = match c {
+                _ if c.is_whitespace() => continue,
+                '[' => Token::Open,
+                ']' => Token::Close,
+                ',' => Token::Comma,
+                '=' => Token::Equal,
+                '"' => Token::Str(tokens.string(column, &mut chars.by_ref().map(|(c, _)| c.1))?),
+                _ => {
+                    let mut end = start + c.len_utf8();
+                    while let Some(((i, c), _)) = chars.next_if(|((_, c), _)| !is_delimiter(*c)) {
+                        end = i + c.len_utf8();
+                    }
+                    let word = &text[start..end];
+                    if word.starts_with("//") {
+                        break;
+                    }
+                    Token::Word(word)
+                }
+            };
+            tokens.items.push((column, token));
+        }
+        Ok(tokens)
+    }
+
+    /// Reads a string after its opening quote, with the escapes of `{:?}`.
+    fn string(
+        &self,
+        column: usize,
+        chars: &mut impl Iterator<Item = char>,
+    ) -> Result<String, ParseError> {
+        let mut string = String::new();
+        loop {
+            match chars.next() {
+                None => return Err(self.error(column, "unterminated string")),
+                Some('"') => return Ok(string),
+                Some('\\') => {
+                    let escaped = match chars.next() {
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('0') => '\0',
+                        Some(c @ ('\\' | '"' | '\'')) => c,
+                        Some('u') => {
+                            let code: String = chars.take_while(|&c| c != '}').collect();
+                            code.strip_prefix('{')
+                                .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                                .and_then(char::from_u32)
+                                .ok_or_else(|| {
+                                    self.error(column, format!("invalid escape `\\u{code}}}`"))
+                                })?
+                        }
+                        c => {
+                            let c = c.map(String::from).unwrap_or_default();
+                            return Err(self.error(column, format!("invalid escape `\\{c}`")));
+                        }
+                    };
+                    string.push(escaped);
+                }
+                Some(c) => string.push(c),
+            }
+        }
+    }
+
+    fn error(&self, column: usize, message: impl Into<String>) -> ParseError {
+        ParseError {
+            line: self.line,
+            column,
+            message: message.into(),
+        }
+    }
+
+    fn missing(&self, column: usize, operator: &str, attribute: &str) -> ParseError {
+        self.error(
+            column,
+            format!("missing attribute `{attribute}` of `{operator}`"),
+        )
+    }
+
+    fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    fn peek(&self) -> Option<&Token<'text>> {
+        self.items.get(self.position).map(|(_, token)| token)
+    }
+
+    fn next(&mut self, expected: &str) -> Result<(usize, Token<'text>), ParseError> {
+        let token = self.items.get(self.position).cloned().ok_or_else(|| {
+            self.error(
+                self.end_column,
+                format!("expected {expected}, found end of line"),
+            )
+        })?;
+        self.position += 1;
+        Ok(token)
+    }
+
+    fn unexpected(&self, column: usize, expected: &str, found: &Token) -> ParseError {
+        self.error(column, format!("expected {expected}, found {found}"))
+    }
+
+    fn word(&mut self, expected: &str) -> Result<(usize, &'text str), ParseError> {
+        match self.next(expected)? {
+            (column, Token::Word(word)) => Ok((column, word)),
+            (column, token) => Err(self.unexpected(column, expected, &token)),
+        }
+    }
+
+    fn punctuation(&mut self, punctuation: &Token) -> Result<(), ParseError> {
+        let expected = punctuation.to_string();
+        match self.next(&expected)? {
+            (_, token) if token == *punctuation => Ok(()),
+            (column, token) => Err(self.unexpected(column, &expected, &token)),
+        }
+    }
+
+    fn end(&self) -> Result<(), ParseError> {
+        match self.items.get(self.position) {
+            None => Ok(()),
+            Some((column, token)) => Err(self.unexpected(*column, "end of line", token)),
+        }
+    }
+
+    fn string_value(&mut self, expected: &str) -> Result<String, ParseError> {
+        match self.next(expected)? {
+            (_, Token::Str(string)) => Ok(string),
+            (column, token) => Err(self.unexpected(column, expected, &token)),
+        }
+    }
+
+    fn number<T: FromStr>(&mut self, expected: &str) -> Result<T, ParseError> {
+        let (column, word) = self.word(expected)?;
+        word.parse()
+            .map_err(|_| self.unexpected(column, expected, &Token::Word(word)))
+    }
+
+    fn precision(&mut self) -> Result<Precision, ParseError> {
+        const EXPECTED: &str = "a precision like `u4`";
+        let (column, word) = self.word(EXPECTED)?;
+        word.strip_prefix('u')
+            .and_then(|precision| precision.parse().ok())
+            .ok_or_else(|| self.unexpected(column, EXPECTED, &Token::Word(word)))
+    }
+
+    fn list<T>(
+        &mut self,
+        mut item: impl FnMut(&mut Self) -> Result<T, ParseError>,
+    ) -> Result<(usize, Vec<T>), ParseError> {
+        let column = self
+            .items
+            .get(self.position)
+            .map_or(self.end_column, |t| t.0);
+        self.punctuation(&Token::Open)?;
+        let mut items = vec![];
+        if self.peek() == Some(&Token::Close) {
+            self.position += 1;
+            return Ok((column, items));
+        }
+        loop {
+            items.push(item(self)?);
+            match self.next("`,` or `]`")? {
+                (_, Token::Comma) => {}
+                (_, Token::Close) => return Ok((column, items)),
+                (column, token) => return Err(self.unexpected(column, "`,` or `]`", &token)),
+            }
+        }
+    }
+
+    fn shape(&mut self) -> Result<Shape, ParseError> {
+        let (_, dimensions_size) = self.list(|tokens| tokens.number("a dimension size"))?;
+        Ok(Shape { dimensions_size })
+    }
+
+    /// Reads a list of exactly `N` values.
+    fn array<const N: usize>(&mut self, expected: &str) -> Result<[u64; N], ParseError> {
+        let (column, values) = self.list(|tokens| tokens.number(expected))?;
+        values
+            .try_into()
+            .map_err(|_| self.error(column, format!("expected {N} values")))
+    }
+
+    /// Returns the next attribute name of `operator`, among `allowed` and not already given.
+    fn attribute(
+        &mut self,
+        operator: &str,
+        allowed: &[&str],
+    ) -> Result<Option<&'text str>, ParseError> {
+        if self.peek().is_none() {
+            return Ok(None);
+        }
+        let (column, name) = self.word("an attribute")?;
+        if !allowed.contains(&name) {
+            let allowed = allowed.join("`, `");
+            return Err(self.error(
+                column,
+                format!("unknown attribute `{name}` of `{operator}`, expected one of `{allowed}`"),
+            ));
+        }
+        if self.attributes.contains(&name) {
+            return Err(self.error(column, format!("duplicate attribute `{name}`")));
+        }
+        self.attributes.push(name);
+        Ok(Some(name))
+    }
+}
+
+fn operator_index(tokens: &Tokens, column: usize, word: &str) -> Result<OperatorIndex, ParseError> {
+    word.strip_prefix('%')
+        .and_then(|index| index.parse().ok())
+        .map(OperatorIndex)
+        .ok_or_else(|| tokens.unexpected(column, "an operator like `%0`", &Token::Word(word)))
+}
+
+struct Parser {
+    dag: Dag,
+    circuit: String,
+}
+
+impl Parser {
+    /// Reads a reference to an operator defined before, in any circuit.
+    fn defined<'text>(
+        &self,
+        tokens: &mut Tokens<'text>,
+    ) -> Result<(usize, &'text str, OperatorIndex), ParseError> {
+        let (column, word) = tokens.word("an operator like `%0`")?;
+        let index = operator_index(tokens, column, word)?;
+        if index.0 >= self.dag.len() {
+            return Err(tokens.error(column, format!("operator {word} is not defined")));
+        }
+        Ok((column, word, index))
+    }
+
+    /// Reads a reference to an operator defined before in the same circuit.
+    fn operand(&self, tokens: &mut Tokens) -> Result<OperatorIndex, ParseError> {
+        let (column, word, index) = self.defined(tokens)?;
+        let circuit = &self.dag.circuit_tags[index.0];
+        if *circuit != self.circuit {
+            return Err(tokens.error(
+                column,
+                format!(
+                    "operator {word} belongs to circuit {circuit:?}, not {:?}",
+                    self.circuit
+                ),
+            ));
+        }
+        Ok(index)
+    }
+
+    fn operands(
+        &self,
+        tokens: &mut Tokens,
+        operator: &str,
+    ) -> Result<Vec<OperatorIndex>, ParseError> {
+        let (column, inputs) = tokens.list(|tokens| self.operand(tokens))?;
+        if inputs.is_empty() {
+            return Err(tokens.error(column, format!("`{operator}` needs at least one input")));
+        }
+        Ok(inputs)
+    }
+
+    fn operand_pair(
+        &self,
+        tokens: &mut Tokens,
+        operator: &str,
+    ) -> Result<[OperatorIndex; 2], ParseError> {
+        let (column, inputs) = tokens.list(|tokens| self.operand(tokens))?;
+        let [lhs, rhs] = inputs[..] else {
+            return Err(tokens.error(column, format!("`{operator}` needs two inputs")));
+        };
+        Ok([lhs, rhs])
+    }
+
+    fn statement(&mut self, tokens: &mut Tokens) -> Result<(), ParseError> {
+        let (column, word) = tokens.word("a statement")?;
+        match word {
+            "circuit" => {
+                self.circuit = tokens.string_value("a circuit name")?;
+            }
+            "output" => {
+                let index = self.operand(tokens)?;
+                self.dag.tag_operator_as_output(index);
+            }
+            "compose" => {
+                let (_, _, output) = self.defined(tokens)?;
+                let (to_column, to) = tokens.word("`to`")?;
+                if to != "to" {
+                    return Err(tokens.unexpected(to_column, "`to`", &Token::Word(to)));
+                }
+                let (_, _, input) = self.defined(tokens)?;
+                self.dag
+                    .try_add_composition(output, input)
+                    .map_err(|err| tokens.error(column, err.to_string()))?;
+            }
+            _ if word.starts_with('%') => {
+                let index = operator_index(tokens, column, word)?;
+                if index.0 != self.dag.len() {
+                    return Err(tokens.error(
+                        column,
+                        format!("expected operator %{}, found {word}", self.dag.len()),
+                    ));
+                }
+                tokens.punctuation(&Token::Equal)?;
+                let operator = self.operator(tokens)?;
+                let _ = self
+                    .dag
+                    .builder(&self.circuit)
+                    .try_add_operator(operator)
+                    .map_err(|err| tokens.error(column, err.to_string()))?;
+            }
+            _ => {
+                return Err(tokens.unexpected(
+                    column,
+                    "`circuit`, `output`, `compose` or an operator like `%0 = ...`",
+                    &Token::Word(word),
+                ));
+            }
+        }
+        tokens.end()
+    }
+
+    fn dot(&self, tokens: &mut Tokens, column: usize) -> Result<Operator, ParseError> {
+        let name = "dot";
+        let inputs = self.operands(tokens, name)?;
+        let mut values = None;
+        let mut shape = None;
+        while let Some(attribute) = tokens.attribute(name, &["weights", "shape"])? {
+            if attribute == "weights" {
+                values = Some(tokens.list(|tokens| tokens.number("a weight"))?.1);
+            } else {
+                shape = Some(tokens.shape()?);
+            }
+        }
+        let values: Vec<i64> = values.ok_or_else(|| tokens.missing(column, name, "weights"))?;
+        let shape = shape.unwrap_or_else(|| Shape::vector(values.len() as u64));
+        let weights = Weights { shape, values };
+        Ok(Operator::Dot { inputs, weights })
+    }
+
+    fn levelled(&self, tokens: &mut Tokens, column: usize) -> Result<Operator, ParseError> {
+        let name = "levelled";
+        let inputs = self.operands(tokens, name)?;
+        let mut manp = None;
+        let mut complexity = LevelledComplexity::ZERO;
+        let mut out_shape = Shape::number();
+        let mut comment = String::new();
+        let allowed = ["manp", "complexity", "shape", "comment"];
+        while let Some(attribute) = tokens.attribute(name, &allowed)? {
+            match attribute {
+                "manp" => manp = Some(tokens.number("a norm")?),
+                "complexity" => {
+                    let (column, costs) = tokens.list(|tokens| tokens.number("a cost"))?;
+                    let [lwe_dim_cost_factor, fixed_cost] = costs[..] else {
+                        return Err(tokens.error(
+                            column,
+                            "expected the `[lwe_dim_cost_factor, fixed_cost]` costs",
+                        ));
+                    };
+                    complexity = LevelledComplexity {
+                        lwe_dim_cost_factor,
+                        fixed_cost,
+                    };
+                }
+                "shape" => out_shape = tokens.shape()?,
+                _ => comment = tokens.string_value("a comment")?,
+            }
+        }
+        Ok(Operator::LevelledOp {
+            inputs,
+            complexity,
+            manp: manp.ok_or_else(|| tokens.missing(column, name, "manp"))?,
+            out_shape,
+            comment,
+        })
+    }
+
+    fn slice(&self, tokens: &mut Tokens, column: usize) -> Result<Operator, ParseError> {
+        let name = "slice";
+        let input = self.operand(tokens)?;
+        let mut offsets = None;
+        let mut sizes = None;
+        let mut strides = None;
+        let allowed = ["offsets", "sizes", "strides"];
+        while let Some(attribute) = tokens.attribute(name, &allowed)? {
+            let (_, values) = tokens.list(|tokens| tokens.number("an index"))?;
+            match attribute {
+                "offsets" => offsets = Some(values),
+                "sizes" => sizes = Some(values),
+                _ => strides = Some(values),
+            }
+        }
+        let sizes: Vec<u64> = sizes.ok_or_else(|| tokens.missing(column, name, "sizes"))?;
+        Ok(Operator::Slice {
+            input,
+            offsets: offsets.unwrap_or_else(|| vec![0; sizes.len()]),
+            strides: strides.unwrap_or_else(|| vec![1; sizes.len()]),
+            sizes,
+        })
+    }
+
+    fn gather(&self, tokens: &mut Tokens, column: usize) -> Result<Operator, ParseError> {
+        let name = "gather";
+        let input = self.operand(tokens)?;
+        let mut axis = None;
+        let mut values = None;
+        let mut shape = None;
+        while let Some(attribute) = tokens.attribute(name, &["axis", "indices", "shape"])? {
+            match attribute {
+                "axis" => axis = Some(tokens.number("an axis")?),
+                "indices" => values = Some(tokens.list(|tokens| tokens.number("an index"))?.1),
+                _ => shape = Some(tokens.shape()?),
+            }
+        }
+        let values: Vec<u64> = values.ok_or_else(|| tokens.missing(column, name, "indices"))?;
+        let shape = shape.unwrap_or_else(|| Shape::vector(values.len() as u64));
+        Ok(Operator::Gather {
+            input,
+            axis: axis.ok_or_else(|| tokens.missing(column, name, "axis"))?,
+            indices: Indices { shape, values },
+        })
+    }
+
+    fn conv2d(&self, tokens: &mut Tokens, column: usize) -> Result<Operator, ParseError> {
+        let name = "conv2d";
+        let input = self.operand(tokens)?;
+        let mut values = None;
+        let mut shape = None;
+        let mut strides = [1; 2];
+        let mut padding = [0; 4];
+        let mut dilations = [1; 2];
+        let mut groups = 1;
+        let allowed = [
+            "weights",
+            "shape",
+            "strides",
+            "padding",
+            "dilations",
+            "groups",
+        ];
+        while let Some(attribute) = tokens.attribute(name, &allowed)? {
+            match attribute {
+                "weights" => values = Some(tokens.list(|tokens| tokens.number("a weight"))?.1),
+                "shape" => shape = Some(tokens.shape()?),
+                "strides" => strides = tokens.array("a stride")?,
+                "padding" => padding = tokens.array("a padding")?,
+                "dilations" => dilations = tokens.array("a dilation")?,
+                _ => groups = tokens.number("a number of groups")?,
+            }
+        }
+        let values: Vec<i64> = values.ok_or_else(|| tokens.missing(column, name, "weights"))?;
+        let shape = shape.ok_or_else(|| tokens.missing(column, name, "shape"))?;
+        Ok(Operator::Conv2d {
+            input,
+            weights: Weights { shape, values },
+            strides,
+            padding,
+            dilations,
+            groups,
+        })
+    }
+
+    #[allow(clippy::too_many_lines)]
+    fn operator(&self, tokens: &mut Tokens) -> Result<Operator, ParseError> {
+        let (column, name) = tokens.word("an operator name")?;
+        match name {
+            "input" => {
+                let out_precision = tokens.precision()?;
+                let mut out_shape = Shape::number();
+                while tokens.attribute(name, &["shape"])?.is_some() {
+                    out_shape = tokens.shape()?;
+                }
+                Ok(Operator::Input {
+                    out_precision,
+                    out_shape,
+                })
+            }
+            "lut" => {
+                let input = self.operand(tokens)?;
+                let out_precision = tokens.precision()?;
+                let mut table = FunctionTable::UNKWOWN;
+                while tokens.attribute(name, &["table"])?.is_some() {
+                    let (_, values) = tokens.list(|tokens| tokens.number("a table value"))?;
+                    table = FunctionTable { values };
+                }
+                Ok(Operator::Lut {
+                    input,
+                    table,
+                    out_precision,
+                })
+            }
+            "dot" => self.dot(tokens, column),
+            "levelled" => self.levelled(tokens, column),
+            "cast" => {
+                let input = self.operand(tokens)?;
+                let out_precision = tokens.precision()?;
+                Ok(Operator::UnsafeCast {
+                    input,
+                    out_precision,
+                })
+            }
+            "round" => {
+                let input = self.operand(tokens)?;
+                let out_precision = tokens.precision()?;
+                Ok(Operator::Round {
+                    input,
+                    out_precision,
+                })
+            }
+            "bivariate" => {
+                let inputs = self.operand_pair(tokens, name)?;
+                let out_precision = tokens.precision()?;
+                let mut table = FunctionTable::UNKWOWN;
+                while tokens.attribute(name, &["table"])?.is_some() {
+                    let (_, values) = tokens.list(|tokens| tokens.number("a table value"))?;
+                    table = FunctionTable { values };
+                }
+                Ok(Operator::Bivariate {
+                    inputs,
+                    table,
+                    out_precision,
+                })
+            }
+            "mul" => {
+                let inputs = self.operand_pair(tokens, name)?;
+                let out_precision = tokens.precision()?;
+                Ok(Operator::Mul {
+                    inputs,
+                    out_precision,
+                })
+            }
+            "maxpool" => {
+                let input = self.operand(tokens)?;
+                let mut window = None;
+                while tokens.attribute(name, &["window"])?.is_some() {
+                    window = Some(tokens.shape()?);
+                }
+                Ok(Operator::MaxPool {
+                    input,
+                    window: window.ok_or_else(|| tokens.missing(column, name, "window"))?,
+                })
+            }
+            "reshape" | "broadcast" => {
+                let input = self.operand(tokens)?;
+                let mut out_shape = Shape::number();
+                while tokens.attribute(name, &["shape"])?.is_some() {
+                    out_shape = tokens.shape()?;
+                }
+                Ok(if name == "reshape" {
+                    Operator::Reshape { input, out_shape }
+                } else {
+                    Operator::Broadcast { input, out_shape }
+                })
+            }
+            "transpose" => {
+                let input = self.operand(tokens)?;
+                let mut axes = None;
+                while tokens.attribute(name, &["axes"])?.is_some() {
+                    axes = Some(tokens.list(|tokens| tokens.number("an axis"))?.1);
+                }
+                Ok(Operator::Transpose {
+                    input,
+                    axes: axes.ok_or_else(|| tokens.missing(column, name, "axes"))?,
+                })
+            }
+            "concat" => {
+                let inputs = self.operands(tokens, name)?;
+                let mut axis = None;
+                while tokens.attribute(name, &["axis"])?.is_some() {
+                    axis = Some(tokens.number("an axis")?);
+                }
+                Ok(Operator::Concatenate {
+                    inputs,
+                    axis: axis.ok_or_else(|| tokens.missing(column, name, "axis"))?,
+                })
+            }
+            "slice" => self.slice(tokens, column),
+            "gather" => self.gather(tokens, column),
+            "conv2d" => self.conv2d(tokens, column),
+            _ => Err(tokens.unexpected(
+                column,
+                "`input`, `lut`, `dot`, `levelled`, `cast`, `round`, `bivariate`, `mul`, \
+                 `maxpool`, `reshape`, `transpose`, `concat`, `slice`, `gather`, `broadcast` or \
+                 `conv2d`",
+                &Token::Word(name),
+            )),
+        }
+    }
+}
+
+impl FromStr for Dag {
+    type Err = ParseError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            dag: Self::new(),
+            circuit: DEFAULT_CIRCUIT.into(),
+        };
+        for (line, text) in (1..).zip(text.lines()) {
+            let mut tokens = Tokens::new(line, text)?;
+            if !tokens.is_empty() {
+                parser.statement(&mut tokens)?;
+            }
+        }
+        Ok(parser.dag)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(dag: &Dag) {
+        let text = dag.to_string();
+        let parsed: Dag = text.parse().unwrap_or_else(|err| panic!("{err}\n{text}"));
+        assert_eq!(*dag, parsed, "\n{text}");
+        assert_eq!(text, parsed.to_string());
+    }
+
+    #[test]
+    fn round_trip_all_operators() {
+        let mut dag = Dag::new();
+        let mut builder = dag.builder("main \"1\"");
+        let lhs = builder.add_input(4, Shape::vector(2));
+        let rhs = builder.add_input(4, Shape::vector(2));
+        let sum = builder.add_dot([lhs, rhs], [1, -2]);
+        let table = FunctionTable {
+            values: (0..16).map(|x| x * x).collect(),
+        };
+        let squared = builder.add_lut(sum, table, 4);
+        builder.tag_operator_as_output(squared);
+        let complexity = LevelledComplexity {
+            lwe_dim_cost_factor: 1.0,
+            fixed_cost: 1e-7,
+        };
+        let negated =
+            builder.add_levelled_op([squared], complexity, 2.5, Shape::vector(2), "neg\tone");
+        let cast = builder.add_unsafe_cast(negated, 5);
+        let _ = builder.add_round_op(cast, 3);
+        let xor = FunctionTable {
+            values: (0..256).map(|x| (x >> 4) ^ (x & 15)).collect(),
+        };
+        let _ = builder.add_bivariate_lut(lhs, rhs, xor, 4);
+        let product = builder.add_mul(lhs, rhs, 8);
+        let _ = builder.add_max_pool(product, Shape::vector(2));
+        let matrix = builder.add_reshape(product, Shape::vector(2));
+        let matrix = builder.add_broadcast(
+            matrix,
+            Shape {
+                dimensions_size: vec![3, 2],
+            },
+        );
+        let transposed = builder.add_transpose(matrix, [1, 0]);
+        let _ = builder.add_concatenate([transposed, transposed], 1);
+        let _ = builder.add_slice(matrix, [0, 0], [2, 2], [1, 1]);
+        let _ = builder.add_slice(matrix, [1, 0], [1, 1], [1, 2]);
+        let _ = builder.add_gather(matrix, 1, [1, 1, 0]);
+        let indices = Indices {
+            shape: Shape {
+                dimensions_size: vec![2, 1],
+            },
+            values: vec![2, 0],
+        };
+        let _ = builder.add_gather(matrix, 0, indices);
+        let image = builder.add_reshape(
+            matrix,
+            Shape {
+                dimensions_size: vec![1, 1, 3, 2],
+            },
+        );
+        let weights = Weights {
+            shape: Shape {
+                dimensions_size: vec![2, 1, 2, 2],
+            },
+            values: vec![1, -1, 0, 2, 3, 0, 0, -3],
+        };
+        let _ = builder.add_conv2d(image, &weights, [1, 1], [0; 4], [1, 1], 1);
+        let _ = builder.add_conv2d(image, weights, [2, 1], [1, 0, 1, 1], [1, 1], 1);
+        let mut builder = dag.builder("main2");
+        let input = builder.add_input(6, Shape::number());
+        let tripled = builder.add_dot([input], Weights::number(3));
+        let combined = builder.add_levelled_op(
+            [input, tripled],
+            LevelledComplexity::ZERO,
+            1.0,
+            Shape::number(),
+            "",
+        );
+        let rounded = builder.add_expanded_rounded_lut(combined, FunctionTable::UNKWOWN, 3, 6);
+        let _ = dag.add_input(1, Shape::number());
+        dag.add_composition(squared, lhs);
+        dag.add_composition(rounded, input);
+        round_trip(&dag);
+    }
+
+    #[test]
+    fn parse_with_defaults() {
+        let text = "
+            // the anonymous circuit
+            %0 = input u3
+            %1 = input u3   // shape [] by default
+            %2 = dot [%0, %1] weights [1, 1]
+            %3 = lut %2 u3
+            output %2
+            %4 = levelled [%3] manp 1.0
+        ";
+        let mut dag = Dag::new();
+        let a = dag.add_input(3, Shape::number());
+        let b = dag.add_input(3, Shape::number());
+        let c = dag.add_dot([a, b], [1, 1]);
+        let d = dag.add_lut(c, FunctionTable::UNKWOWN, 3);
+        dag.tag_operator_as_output(c);
+        let _ = dag.add_levelled_op([d], LevelledComplexity::ZERO, 1.0, Shape::number(), "");
+        assert_eq!(dag, text.parse().unwrap());
+        round_trip(&dag);
+    }
+
+    fn assert_parse_errors(errors: &[(&str, usize, usize, &str)]) {
+        for &(text, line, column, message) in errors {
+            let expected = ParseError {
+                line,
+                column,
+                message: message.into(),
+            };
+            assert_eq!(text.parse::<Dag>().unwrap_err(), expected, "{text}");
+        }
+    }
+
+    #[test]
+    fn parse_syntax_errors() {
+        assert_parse_errors(&[
+            (
+                "%0 = input",
+                1,
+                11,
+                "expected a precision like `u4`, found end of line",
+            ),
+            (
+                "%0 = input 4",
+                1,
+                12,
+                "expected a precision like `u4`, found `4`",
+            ),
+            (
+                "%0 = input u4 shape [2, x]",
+                1,
+                25,
+                "expected a dimension size, found `x`",
+            ),
+            (
+                "%0 = input u4 shape [2",
+                1,
+                23,
+                "expected `,` or `]`, found end of line",
+            ),
+            (
+                "%0 = input u4 table []",
+                1,
+                15,
+                "unknown attribute `table` of `input`, expected one of `shape`",
+            ),
+            (
+                "%0 = input u4 shape [] shape []",
+                1,
+                24,
+                "duplicate attribute `shape`",
+            ),
+            (
+                "%0 = input u4\n%1 = dot [%0]",
+                2,
+                6,
+                "missing attribute `weights` of `dot`",
+            ),
+            (
+                "%0 = input u4\n%1 = levelled [%0] manp 1 complexity [1]",
+                2,
+                38,
+                "expected the `[lwe_dim_cost_factor, fixed_cost]` costs",
+            ),
+            (
+                "%0 = input u4\n%1 = maxpool %0",
+                2,
+                6,
+                "missing attribute `window` of `maxpool`",
+            ),
+            (
+                "%0 = input u4\n%1 = conv2d %0 weights [1] shape [1, 1, 1, 1] padding [1]",
+                2,
+                55,
+                "expected 4 values",
+            ),
+            (
+                "%0 = noop",
+                1,
+                6,
+                "expected `input`, `lut`, `dot`, `levelled`, `cast`, `round`, `bivariate`, `mul`, \
+                 `maxpool`, `reshape`, `transpose`, `concat`, `slice`, `gather`, `broadcast` or \
+                 `conv2d`, found `noop`",
+            ),
+        ]);
+    }
+
+    #[test]
+    fn parse_operator_errors() {
+        assert_parse_errors(&[
+            ("%1 = input u4", 1, 1, "expected operator %0, found %1"),
+            (
+                "%0 = input u4\n%1 = lut %2 u4",
+                2,
+                10,
+                "operator %2 is not defined",
+            ),
+            (
+                "%0 = input u4\n%1 = dot [] weights []",
+                2,
+                10,
+                "`dot` needs at least one input",
+            ),
+            (
+                "%0 = input u4\n%1 = dot [%0] weights [1, 2] shape [3]",
+                2,
+                1,
+                "operator %1 has 2 weights for a shape [3] of 3 values",
+            ),
+            (
+                "%0 = input u4 shape [2]\n%1 = dot [%0] weights [1, 2, 3]",
+                2,
+                1,
+                "operator %1 is an unsupported dot of 1 inputs of shape [2] with weights of shape [3]",
+            ),
+            (
+                "%0 = input u4\n%1 = round %0 u5",
+                2,
+                1,
+                "operator %1 rounds a u4 to a u5",
+            ),
+            (
+                "%0 = input u4\n%1 = mul [%0] u4",
+                2,
+                10,
+                "`mul` needs two inputs",
+            ),
+            (
+                "%0 = input u4 shape [3]\n%1 = slice %0 offsets [2] sizes [2]",
+                2,
+                1,
+                "operator %1 reads outside of an input of shape [3]",
+            ),
+            (
+                "%0 = input u4 shape [0]",
+                1,
+                1,
+                "operator %0 has an empty shape [0]",
+            ),
+            (
+                "%0 = input u4\n%1 = lut %0 u4 table [1, 2]",
+                2,
+                1,
+                "operator %1 has a table of 2 values for an input of precision 4",
+            ),
+        ]);
+    }
+
+    #[test]
+    fn parse_circuit_errors() {
+        assert_parse_errors(&[
+            (
+                "%0 = input u4\noutput %0 %0",
+                2,
+                11,
+                "expected end of line, found `%0`",
+            ),
+            (
+                "%0 = input u4\ncircuit \"b\"\n%1 = lut %0 u4",
+                3,
+                10,
+                "operator %0 belongs to circuit \"_\", not \"b\"",
+            ),
+            (
+                "%0 = input u4\n%1 = lut %0 u4\ncompose %1 into %0",
+                3,
+                12,
+                "expected `to`, found `into`",
+            ),
+            (
+                "%0 = input u4\n%1 = lut %0 u3\ncompose %1 to %0",
+                3,
+                1,
+                "operator %0 is not an input of the precision and shape of the output %1 composed \
+                 into it",
+            ),
+            ("circuit \"main", 1, 9, "unterminated string"),
+            (
+                "input u4",
+                1,
+                1,
+                "expected `circuit`, `output`, `compose` or an operator like `%0 = ...`, found `input`",
+            ),
+        ]);
+    }
+}
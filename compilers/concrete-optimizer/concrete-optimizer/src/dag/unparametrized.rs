@@ -1,15 +1,17 @@
 use crate::dag::operator::{
-    dot_kind, DotKind, FunctionTable, LevelledComplexity, Operator, OperatorIndex, Precision,
-    Shape, Weights,
+    dot_kind, DotKind, FunctionTable, Indices, LevelledComplexity, Operator, OperatorIndex,
+    Precision, Shape, Weights,
 };
+use crate::dag::validate::{check_composition, check_operator, DagError};
+use serde::{Deserialize, Serialize};
 use std::{collections::HashSet, fmt};
 
 /// The name of the default. Used when adding operations directly on the dag instead of via a
 /// builder.
-const DEFAULT_CIRCUIT: &str = "_";
+pub(crate) const DEFAULT_CIRCUIT: &str = "_";
 
 /// A state machine to define if an operator is used as output to a circuit.
-#[derive(Debug, Clone, PartialEq, Copy)]
+#[derive(Debug, Clone, PartialEq, Copy, Serialize, Deserialize)]
 pub(crate) enum OutputState {
     /// The operator was created and neither used as input to another operator, nor tagged as output
     /// explicitly. It is considered an output.
@@ -126,7 +128,7 @@ pub struct DagBuilder<'dag> {
 }
 
 impl<'dag> DagBuilder<'dag> {
-    fn add_operator(&mut self, operator: Operator) -> OperatorIndex {
+    pub(crate) fn add_operator(&mut self, operator: Operator) -> OperatorIndex {
         debug_assert!(operator
             .get_inputs_iter()
             .all(|id| self.dag.circuit_tags[id.0] == self.circuit));
@@ -220,32 +222,457 @@ impl<'dag> DagBuilder<'dag> {
         input: OperatorIndex,
         rounded_precision: Precision,
     ) -> OperatorIndex {
-        let in_precision = self.dag.out_precisions[input.0];
-        assert!(rounded_precision <= in_precision);
         self.add_operator(Operator::Round {
             input,
             out_precision: rounded_precision,
         })
     }
 
-    fn add_shift_left_lsb_to_msb_no_padding(&mut self, input: OperatorIndex) -> OperatorIndex {
-        // Convert any input to simple 1bit msb replacing the padding
+    pub(crate) fn try_add_operator(
+        &mut self,
+        operator: Operator,
+    ) -> Result<OperatorIndex, DagError> {
+        let index = OperatorIndex(self.dag.len());
+        check_operator(self.dag, index, &operator, &self.circuit)?;
+        Ok(self.add_operator(operator))
+    }
+
+    /// Like [`Self::add_input`], but fails instead of adding a malformed operator.
+    ///
+    /// # Errors
+    /// The [`DagError`] of the operator, which is then not added.
+    pub fn try_add_input(
+        &mut self,
+        out_precision: Precision,
+        out_shape: impl Into<Shape>,
+    ) -> Result<OperatorIndex, DagError> {
+        let out_shape = out_shape.into();
+        self.try_add_operator(Operator::Input {
+            out_precision,
+            out_shape,
+        })
+    }
+
+    /// Like [`Self::add_lut`], but fails instead of adding a malformed operator.
+    ///
+    /// # Errors
+    /// The [`DagError`] of the operator, which is then not added.
+    pub fn try_add_lut(
+        &mut self,
+        input: OperatorIndex,
+        table: FunctionTable,
+        out_precision: Precision,
+    ) -> Result<OperatorIndex, DagError> {
+        self.try_add_operator(Operator::Lut {
+            input,
+            table,
+            out_precision,
+        })
+    }
+
+    /// Like [`Self::add_dot`], but fails instead of adding a malformed operator.
+    ///
+    /// # Errors
+    /// The [`DagError`] of the operator, which is then not added.
+    pub fn try_add_dot(
+        &mut self,
+        inputs: impl Into<Vec<OperatorIndex>>,
+        weights: impl Into<Weights>,
+    ) -> Result<OperatorIndex, DagError> {
+        let inputs = inputs.into();
+        let weights = weights.into();
+        self.try_add_operator(Operator::Dot { inputs, weights })
+    }
+
+    /// Like [`Self::add_levelled_op`], but fails instead of adding a malformed operator.
+    ///
+    /// # Errors
+    /// The [`DagError`] of the operator, which is then not added.
+    pub fn try_add_levelled_op(
+        &mut self,
+        inputs: impl Into<Vec<OperatorIndex>>,
+        complexity: LevelledComplexity,
+        manp: f64,
+        out_shape: impl Into<Shape>,
+        comment: impl Into<String>,
+    ) -> Result<OperatorIndex, DagError> {
+        self.try_add_operator(Operator::LevelledOp {
+            inputs: inputs.into(),
+            complexity,
+            manp,
+            out_shape: out_shape.into(),
+            comment: comment.into(),
+        })
+    }
+
+    /// Like [`Self::add_unsafe_cast`], but fails instead of adding a malformed operator.
+    ///
+    /// # Errors
+    /// The [`DagError`] of the operator, which is then not added.
+    pub fn try_add_unsafe_cast(
+        &mut self,
+        input: OperatorIndex,
+        out_precision: Precision,
+    ) -> Result<OperatorIndex, DagError> {
+        let operator = Operator::UnsafeCast {
+            input,
+            out_precision,
+        };
+        check_operator(
+            self.dag,
+            OperatorIndex(self.dag.len()),
+            &operator,
+            &self.circuit,
+        )?;
+        Ok(self.add_unsafe_cast(input, out_precision))
+    }
+
+    /// Like [`Self::add_round_op`], but fails instead of adding a malformed operator.
+    ///
+    /// # Errors
+    /// The [`DagError`] of the operator, which is then not added.
+    pub fn try_add_round_op(
+        &mut self,
+        input: OperatorIndex,
+        rounded_precision: Precision,
+    ) -> Result<OperatorIndex, DagError> {
+        self.try_add_operator(Operator::Round {
+            input,
+            out_precision: rounded_precision,
+        })
+    }
+
+    /// Adds a lut on two inputs of the same shape, its table is indexed by the `lhs` bits followed
+    /// by the `rhs` bits.
+    pub fn add_bivariate_lut(
+        &mut self,
+        lhs: OperatorIndex,
+        rhs: OperatorIndex,
+        table: FunctionTable,
+        out_precision: Precision,
+    ) -> OperatorIndex {
+        self.add_operator(Operator::Bivariate {
+            inputs: [lhs, rhs],
+            table,
+            out_precision,
+        })
+    }
+
+    /// Adds the product of two encrypted inputs of the same shape.
+    pub fn add_mul(
+        &mut self,
+        lhs: OperatorIndex,
+        rhs: OperatorIndex,
+        out_precision: Precision,
+    ) -> OperatorIndex {
+        self.add_operator(Operator::Mul {
+            inputs: [lhs, rhs],
+            out_precision,
+        })
+    }
+
+    /// Adds the max on non overlapping windows of the last dimensions of the input.
+    pub fn add_max_pool(
+        &mut self,
+        input: OperatorIndex,
+        window: impl Into<Shape>,
+    ) -> OperatorIndex {
+        self.add_operator(Operator::MaxPool {
+            input,
+            window: window.into(),
+        })
+    }
+
+    /// Like [`Self::add_bivariate_lut`], but fails instead of adding a malformed operator.
+    ///
+    /// # Errors
+    /// The [`DagError`] of the operator, which is then not added.
+    pub fn try_add_bivariate_lut(
+        &mut self,
+        lhs: OperatorIndex,
+        rhs: OperatorIndex,
+        table: FunctionTable,
+        out_precision: Precision,
+    ) -> Result<OperatorIndex, DagError> {
+        self.try_add_operator(Operator::Bivariate {
+            inputs: [lhs, rhs],
+            table,
+            out_precision,
+        })
+    }
+
+    /// Like [`Self::add_mul`], but fails instead of adding a malformed operator.
+    ///
+    /// # Errors
+    /// The [`DagError`] of the operator, which is then not added.
+    pub fn try_add_mul(
+        &mut self,
+        lhs: OperatorIndex,
+        rhs: OperatorIndex,
+        out_precision: Precision,
+    ) -> Result<OperatorIndex, DagError> {
+        self.try_add_operator(Operator::Mul {
+            inputs: [lhs, rhs],
+            out_precision,
+        })
+    }
+
+    /// Like [`Self::add_max_pool`], but fails instead of adding a malformed operator.
+    ///
+    /// # Errors
+    /// The [`DagError`] of the operator, which is then not added.
+    pub fn try_add_max_pool(
+        &mut self,
+        input: OperatorIndex,
+        window: impl Into<Shape>,
+    ) -> Result<OperatorIndex, DagError> {
+        self.try_add_operator(Operator::MaxPool {
+            input,
+            window: window.into(),
+        })
+    }
+
+    /// Adds the input elements in another shape of the same flat size.
+    pub fn add_reshape(
+        &mut self,
+        input: OperatorIndex,
+        out_shape: impl Into<Shape>,
+    ) -> OperatorIndex {
+        self.add_operator(Operator::Reshape {
+            input,
+            out_shape: out_shape.into(),
+        })
+    }
+
+    /// Adds the input with its dimensions permuted, the output dimension `i` is the input
+    /// dimension `axes[i]`.
+    pub fn add_transpose(
+        &mut self,
+        input: OperatorIndex,
+        axes: impl Into<Vec<usize>>,
+    ) -> OperatorIndex {
+        self.add_operator(Operator::Transpose {
+            input,
+            axes: axes.into(),
+        })
+    }
+
+    /// Adds the concatenation of inputs having the same shape except on `axis`.
+    pub fn add_concatenate(
+        &mut self,
+        inputs: impl Into<Vec<OperatorIndex>>,
+        axis: usize,
+    ) -> OperatorIndex {
+        self.add_operator(Operator::Concatenate {
+            inputs: inputs.into(),
+            axis,
+        })
+    }
+
+    /// Adds the `sizes` elements starting at `offsets` and spaced by `strides` in each dimension.
+    pub fn add_slice(
+        &mut self,
+        input: OperatorIndex,
+        offsets: impl Into<Vec<u64>>,
+        sizes: impl Into<Vec<u64>>,
+        strides: impl Into<Vec<u64>>,
+    ) -> OperatorIndex {
+        self.add_operator(Operator::Slice {
+            input,
+            offsets: offsets.into(),
+            sizes: sizes.into(),
+            strides: strides.into(),
+        })
+    }
+
+    /// Adds the elements at clear `indices` along `axis`, the dimension `axis` is replaced by the
+    /// indices shape.
+    pub fn add_gather(
+        &mut self,
+        input: OperatorIndex,
+        axis: usize,
+        indices: impl Into<Indices>,
+    ) -> OperatorIndex {
+        self.add_operator(Operator::Gather {
+            input,
+            axis,
+            indices: indices.into(),
+        })
+    }
+
+    /// Adds the input repeated to `out_shape` with numpy broadcasting rules.
+    pub fn add_broadcast(
+        &mut self,
+        input: OperatorIndex,
+        out_shape: impl Into<Shape>,
+    ) -> OperatorIndex {
+        self.add_operator(Operator::Broadcast {
+            input,
+            out_shape: out_shape.into(),
+        })
+    }
+
+    /// Adds the 2d convolution of a `[N, C, H, W]` input by `[F, C / groups, KH, KW]` clear
+    /// weights, the padding being `[top, left, bottom, right]`.
+    pub fn add_conv2d(
+        &mut self,
+        input: OperatorIndex,
+        weights: impl Into<Weights>,
+        strides: [u64; 2],
+        padding: [u64; 4],
+        dilations: [u64; 2],
+        groups: u64,
+    ) -> OperatorIndex {
+        self.add_operator(Operator::Conv2d {
+            input,
+            weights: weights.into(),
+            strides,
+            padding,
+            dilations,
+            groups,
+        })
+    }
+
+    /// Like [`Self::add_reshape`], but fails instead of adding a malformed operator.
+    ///
+    /// # Errors
+    /// The [`DagError`] of the operator, which is then not added.
+    pub fn try_add_reshape(
+        &mut self,
+        input: OperatorIndex,
+        out_shape: impl Into<Shape>,
+    ) -> Result<OperatorIndex, DagError> {
+        self.try_add_operator(Operator::Reshape {
+            input,
+            out_shape: out_shape.into(),
+        })
+    }
+
+    /// Like [`Self::add_transpose`], but fails instead of adding a malformed operator.
+    ///
+    /// # Errors
+    /// The [`DagError`] of the operator, which is then not added.
+    pub fn try_add_transpose(
+        &mut self,
+        input: OperatorIndex,
+        axes: impl Into<Vec<usize>>,
+    ) -> Result<OperatorIndex, DagError> {
+        self.try_add_operator(Operator::Transpose {
+            input,
+            axes: axes.into(),
+        })
+    }
+
+    /// Like [`Self::add_concatenate`], but fails instead of adding a malformed operator.
+    ///
+    /// # Errors
+    /// The [`DagError`] of the operator, which is then not added.
+    pub fn try_add_concatenate(
+        &mut self,
+        inputs: impl Into<Vec<OperatorIndex>>,
+        axis: usize,
+    ) -> Result<OperatorIndex, DagError> {
+        self.try_add_operator(Operator::Concatenate {
+            inputs: inputs.into(),
+            axis,
+        })
+    }
+
+    /// Like [`Self::add_slice`], but fails instead of adding a malformed operator.
+    ///
+    /// # Errors
+    /// The [`DagError`] of the operator, which is then not added.
+    pub fn try_add_slice(
+        &mut self,
+        input: OperatorIndex,
+        offsets: impl Into<Vec<u64>>,
+        sizes: impl Into<Vec<u64>>,
+        strides: impl Into<Vec<u64>>,
+    ) -> Result<OperatorIndex, DagError> {
+        self.try_add_operator(Operator::Slice {
+            input,
+            offsets: offsets.into(),
+            sizes: sizes.into(),
+            strides: strides.into(),
+        })
+    }
+
+    /// Like [`Self::add_gather`], but fails instead of adding a malformed operator.
+    ///
+    /// # Errors
+    /// The [`DagError`] of the operator, which is then not added.
+    pub fn try_add_gather(
+        &mut self,
+        input: OperatorIndex,
+        axis: usize,
+        indices: impl Into<Indices>,
+    ) -> Result<OperatorIndex, DagError> {
+        self.try_add_operator(Operator::Gather {
+            input,
+            axis,
+            indices: indices.into(),
+        })
+    }
+
+    /// Like [`Self::add_broadcast`], but fails instead of adding a malformed operator.
+    ///
+    /// # Errors
+    /// The [`DagError`] of the operator, which is then not added.
+    pub fn try_add_broadcast(
+        &mut self,
+        input: OperatorIndex,
+        out_shape: impl Into<Shape>,
+    ) -> Result<OperatorIndex, DagError> {
+        self.try_add_operator(Operator::Broadcast {
+            input,
+            out_shape: out_shape.into(),
+        })
+    }
+
+    /// Like [`Self::add_conv2d`], but fails instead of adding a malformed operator.
+    ///
+    /// # Errors
+    /// The [`DagError`] of the operator, which is then not added.
+    pub fn try_add_conv2d(
+        &mut self,
+        input: OperatorIndex,
+        weights: impl Into<Weights>,
+        strides: [u64; 2],
+        padding: [u64; 4],
+        dilations: [u64; 2],
+        groups: u64,
+    ) -> Result<OperatorIndex, DagError> {
+        self.try_add_operator(Operator::Conv2d {
+            input,
+            weights: weights.into(),
+            strides,
+            padding,
+            dilations,
+            groups,
+        })
+    }
+
+    fn add_shift_left_lsb_to_msb_no_padding(
+        &mut self,
+        input: OperatorIndex,
+        window_precision: Precision,
+    ) -> OperatorIndex {
+        // Convert any input to its lowest bits as msbs, replacing the padding
         // For now encoding is not explicit, so 1 bit content without padding <=> 0 bit content with padding.
         let in_precision = self.dag.out_precisions[input.0];
-        let shift_factor = Weights::number(1 << (in_precision as i64));
+        assert!(1 <= window_precision && window_precision <= in_precision);
+        let shift_factor = Weights::number(1 << ((in_precision - window_precision + 1) as i64));
         let lsb_as_msb = self.add_dot([input], shift_factor);
-        self.add_unsafe_cast(lsb_as_msb, 0 as Precision)
+        self.add_unsafe_cast(lsb_as_msb, window_precision - 1)
     }
 
-    fn add_lut_1bit_no_padding(
+    fn add_lut_no_padding(
         &mut self,
         input: OperatorIndex,
         table: FunctionTable,
         out_precision: Precision,
     ) -> OperatorIndex {
-        // For now encoding is not explicit, so 1 bit content without padding <=> 0 bit content with padding.
-        let in_precision = self.dag.out_precisions[input.0];
-        assert!(in_precision == 0);
+        // For now encoding is not explicit, so n bits content without padding <=> n-1 bits content with padding.
         // An add after with a clear constant is skipped here as it doesn't change noise handling.
         self.add_lut(input, table, out_precision)
     }
@@ -255,42 +682,72 @@ impl<'dag> DagBuilder<'dag> {
         input: OperatorIndex,
         out_precision: Precision,
     ) -> OperatorIndex {
-        // Convert simple 1 bit msb to a nbit with zero padding
+        // Convert msbs without padding to a nbit with zero padding
         let to_nbits_padded = FunctionTable::UNKWOWN;
-        self.add_lut_1bit_no_padding(input, to_nbits_padded, out_precision)
+        self.add_lut_no_padding(input, to_nbits_padded, out_precision)
     }
 
-    fn add_isolate_lowest_bit(&mut self, input: OperatorIndex) -> OperatorIndex {
-        // The lowest bit is converted to a ciphertext of same precision as input.
-        // Introduce a pbs of input precision but this precision is only used on 1 levelled op and converted to lower precision
+    fn add_isolate_lowest_bits(
+        &mut self,
+        input: OperatorIndex,
+        window_precision: Precision,
+    ) -> OperatorIndex {
+        // The lowest bits are converted to a ciphertext of same precision as input.
+        // Introduce a pbs on the `window_precision` lowest bits, its table keeps only the bits to
+        // isolate, but this precision is only used on 1 levelled op and converted to lower precision
         // Noise is reduced by a pbs.
         let out_precision = self.dag.out_precisions[input.0];
-        let lsb_as_msb = self.add_shift_left_lsb_to_msb_no_padding(input);
+        let lsb_as_msb = self.add_shift_left_lsb_to_msb_no_padding(input, window_precision);
         self.add_shift_right_msb_no_padding_to_lsb(lsb_as_msb, out_precision)
     }
 
+    /// Clears the `nb_bits` lowest bits, with one pbs on the `window_precision` lowest bits.
+    pub fn add_truncate_bits(
+        &mut self,
+        input: OperatorIndex,
+        nb_bits: Precision,
+        window_precision: Precision,
+    ) -> OperatorIndex {
+        // ex: 10110 is truncated by 2 bits to 101, 10111 is truncated by 2 bits to 101
+        let in_precision = self.dag.out_precisions[input.0];
+        assert!(nb_bits <= window_precision);
+        let lowest_bits = self.add_isolate_lowest_bits(input, window_precision);
+        let bits_cleared = self.add_dot([input, lowest_bits], [1, -1]);
+        self.add_unsafe_cast(bits_cleared, in_precision - nb_bits)
+    }
+
     pub fn add_truncate_1_bit(&mut self, input: OperatorIndex) -> OperatorIndex {
         // Reset a bit.
         // ex: 10110 is truncated to 1011, 10111 is truncated to 1011
-        let in_precision = self.dag.out_precisions[input.0];
-        let lowest_bit = self.add_isolate_lowest_bit(input);
-        let bit_cleared = self.add_dot([input, lowest_bit], [1, -1]);
-        self.add_unsafe_cast(bit_cleared, in_precision - 1)
+        self.add_truncate_bits(input, 1, 1)
     }
 
     pub fn add_expanded_round(
         &mut self,
         input: OperatorIndex,
         rounded_precision: Precision,
+    ) -> OperatorIndex {
+        self.add_expanded_round_by_groups(input, rounded_precision, 1)
+    }
+
+    /// Like [`Self::add_expanded_round`], but truncates `group_bits` bits per pbs.
+    ///
+    /// Larger groups need fewer pbs, but of a higher precision, so with a smaller noise after the
+    /// keyswitch and the modulus switching. The multi parameters optimizer chooses the group size
+    /// of round operators, see `ROUND_GROUPS_BITS`.
+    pub fn add_expanded_round_by_groups(
+        &mut self,
+        input: OperatorIndex,
+        rounded_precision: Precision,
+        group_bits: Precision,
     ) -> OperatorIndex {
         // Round such that the output has precision out_precision.
         // We round by adding 2**(removed_precision - 1) to the last remaining bit to clear (this step is a no-op).
-        // Than all lower bits are cleared.
+        // Than all lower bits are cleared, by groups.
         // Note: this is a simplified graph, some constant additions are missing without consequence on crypto parameter choice.
-        // Note: reset and rounds could be done by 4, 3, 2 and 1 bits groups for efficiency.
-        //       bit efficiency is better for 4 precision then 3, but the feasibility is lower for high noise
         let in_precision = self.dag.out_precisions[input.0];
         assert!(rounded_precision <= in_precision);
+        assert!(group_bits >= 1);
         if in_precision == rounded_precision {
             return input;
         }
@@ -298,8 +755,16 @@ impl<'dag> DagBuilder<'dag> {
         let mut rounded = input;
         // The rounded is in high precision with garbage lowest bits
         let bits_to_truncate = in_precision - rounded_precision;
-        for _ in 1..=bits_to_truncate as i64 {
-            rounded = self.add_truncate_1_bit(rounded);
+        // All pbs of a round use the same window, so they can share their keys: the smaller
+        // remainder group is truncated first, while the window still fits the precision.
+        let window_precision = group_bits.min(in_precision);
+        let mut remainder = bits_to_truncate % group_bits;
+        if remainder == 0 {
+            remainder = group_bits.min(bits_to_truncate);
+        }
+        rounded = self.add_truncate_bits(rounded, remainder, window_precision);
+        for _ in 0..(bits_to_truncate - remainder) / group_bits {
+            rounded = self.add_truncate_bits(rounded, group_bits, window_precision);
         }
         rounded
     }
@@ -327,6 +792,83 @@ impl<'dag> DagBuilder<'dag> {
         self.add_lut(rounded, table, out_precision)
     }
 
+    pub fn add_expanded_bivariate_lut(
+        &mut self,
+        lhs: OperatorIndex,
+        rhs: OperatorIndex,
+        table: FunctionTable,
+        out_precision: Precision,
+    ) -> OperatorIndex {
+        // Both inputs are packed in one ciphertext, lhs bits first, and a single lut is applied.
+        let rhs_precision = self.dag.out_precisions[rhs.0];
+        let packed_precision = self.dag.out_precisions[lhs.0] + rhs_precision;
+        let lhs = self.add_unsafe_cast(lhs, packed_precision);
+        let rhs = self.add_unsafe_cast(rhs, packed_precision);
+        let packed = self.add_dot([lhs, rhs], [1 << rhs_precision, 1]);
+        self.add_lut(packed, table, out_precision)
+    }
+
+    pub fn add_expanded_mul(
+        &mut self,
+        lhs: OperatorIndex,
+        rhs: OperatorIndex,
+        out_precision: Precision,
+    ) -> OperatorIndex {
+        // x * y = (x + y)^2 / 4 - (x - y)^2 / 4, each square is a lut.
+        // The sum and the difference need one more bit than the inputs.
+        let precision = self.dag.out_precisions[lhs.0].max(self.dag.out_precisions[rhs.0]) + 1;
+        let lhs = self.add_unsafe_cast(lhs, precision);
+        let rhs = self.add_unsafe_cast(rhs, precision);
+        let sum = self.add_dot([lhs, rhs], [1, 1]);
+        let diff = self.add_dot([lhs, rhs], [1, -1]);
+        let quarter_square = FunctionTable::UNKWOWN;
+        let sum_square = self.add_lut(sum, quarter_square.clone(), out_precision);
+        let diff_square = self.add_lut(diff, quarter_square, out_precision);
+        self.add_dot([sum_square, diff_square], [1, -1])
+    }
+
+    pub fn add_expanded_max_pool(&mut self, input: OperatorIndex, window: &Shape) -> OperatorIndex {
+        // The window elements are selected by strided slices, then reduced by a tree of
+        // max(x, y) = y + relu(x - y), one lut per pair of elements.
+        // The difference sign needs one more bit, the whole tree is computed with it.
+        let in_precision = self.dag.out_precisions[input.0];
+        let out_shape = self.dag.out_shapes[input.0].pooled(window);
+        let first_pooled = out_shape.rank() - window.rank();
+        let mut strides = vec![1; out_shape.rank()];
+        strides[first_pooled..].copy_from_slice(&window.dimensions_size);
+        let input = self.add_unsafe_cast(input, in_precision + 1);
+        let mut maxs: Vec<_> = (0..window.flat_size())
+            .map(|element| {
+                let mut offsets = vec![0; out_shape.rank()];
+                let mut rest = element;
+                for (offset, window_size) in offsets[first_pooled..]
+                    .iter_mut()
+                    .zip(&window.dimensions_size)
+                    .rev()
+                {
+                    *offset = rest % window_size;
+                    rest /= window_size;
+                }
+                let sizes = out_shape.dimensions_size.clone();
+                self.add_slice(input, offsets, sizes, strides.clone())
+            })
+            .collect();
+        while maxs.len() > 1 {
+            maxs = maxs
+                .chunks(2)
+                .map(|pair| match *pair {
+                    [lhs, rhs] => {
+                        let diff = self.add_dot([lhs, rhs], [1, -1]);
+                        let relu = self.add_lut(diff, FunctionTable::UNKWOWN, in_precision + 1);
+                        self.add_dot([rhs, relu], [1, 1])
+                    }
+                    _ => pair[0],
+                })
+                .collect();
+        }
+        self.add_unsafe_cast(maxs[0], in_precision)
+    }
+
     /// Marks an operator as being an output of the circuit.
     ///
     /// # Note:
@@ -346,34 +888,87 @@ impl<'dag> DagBuilder<'dag> {
 
     fn infer_out_shape(&self, op: &Operator) -> Shape {
         match op {
-            Operator::Input { out_shape, .. } | Operator::LevelledOp { out_shape, .. } => {
-                out_shape.clone()
-            }
+            Operator::Input { out_shape, .. }
+            | Operator::LevelledOp { out_shape, .. }
+            | Operator::Reshape { out_shape, .. }
+            | Operator::Broadcast { out_shape, .. } => out_shape.clone(),
             Operator::Lut { input, .. }
             | Operator::UnsafeCast { input, .. }
             | Operator::Round { input, .. } => self.dag.out_shapes[input.0].clone(),
+            Operator::Bivariate { inputs, .. } | Operator::Mul { inputs, .. } => {
+                self.dag.out_shapes[inputs[0].0].clone()
+            }
+            Operator::MaxPool { input, window } => self.dag.out_shapes[input.0].pooled(window),
+            Operator::Transpose { input, axes } => self.dag.out_shapes[input.0].transposed(axes),
+            Operator::Concatenate { inputs, axis } => {
+                let mut out_shape = inputs
+                    .first()
+                    .map_or_else(Shape::number, |input| self.dag.out_shapes[input.0].clone());
+                if let Some(out_dim_size) = out_shape.dimensions_size.get_mut(*axis) {
+                    *out_dim_size = inputs
+                        .iter()
+                        .filter_map(|input| self.dag.out_shapes[input.0].dimensions_size.get(*axis))
+                        .sum();
+                }
+                out_shape
+            }
+            Operator::Slice { sizes, .. } => Shape {
+                dimensions_size: sizes.clone(),
+            },
+            Operator::Gather {
+                input,
+                axis,
+                indices,
+            } => {
+                let input_dims = &self.dag.out_shapes[input.0].dimensions_size;
+                let axis = (*axis).min(input_dims.len());
+                let mut dimensions_size = input_dims[..axis].to_vec();
+                dimensions_size.extend_from_slice(&indices.shape.dimensions_size);
+                dimensions_size.extend(input_dims.iter().skip(axis + 1));
+                Shape { dimensions_size }
+            }
+            Operator::Conv2d {
+                input,
+                weights,
+                strides,
+                padding,
+                dilations,
+                ..
+            } => {
+                // an invalid convolution is reported by the validation
+                let (&[n, _, h, w], &[f, _, kh, kw]) = (
+                    self.dag.out_shapes[input.0].dimensions_size.as_slice(),
+                    weights.shape.dimensions_size.as_slice(),
+                ) else {
+                    return Shape::number();
+                };
+                let out_size = |size: u64, before: u64, after: u64, k: u64, i: usize| {
+                    let padded = size.checked_add(before)?.checked_add(after)?;
+                    let span = dilations[i].checked_mul(k.saturating_sub(1))?;
+                    let last_start = padded.checked_sub(span)?.checked_sub(1)?;
+                    Some(last_start.checked_div(strides[i])? + 1)
+                };
+                Shape {
+                    dimensions_size: vec![
+                        n,
+                        f,
+                        out_size(h, padding[0], padding[2], kh, 0).unwrap_or(0),
+                        out_size(w, padding[1], padding[3], kw, 1).unwrap_or(0),
+                    ],
+                }
+            }
             Operator::Dot {
                 inputs, weights, ..
             } => {
                 let input_shape = self.dag.out_shapes[inputs[0].0].clone();
                 let kind = dot_kind(inputs.len() as u64, &input_shape, weights);
                 match kind {
-                    DotKind::Simple | DotKind::Tensor | DotKind::CompatibleTensor => {
-                        Shape::number()
-                    }
+                    // an unsupported dot is reported by the validation
+                    DotKind::Simple
+                    | DotKind::Tensor
+                    | DotKind::CompatibleTensor
+                    | DotKind::Unsupported => Shape::number(),
                     DotKind::Broadcast { shape } => shape,
-                    DotKind::Unsupported { .. } => {
-                        let weights_shape = &weights.shape;
-
-                        println!();
-                        println!();
-                        println!("Error diagnostic on dot operation:");
-                        println!(
-                            "Incompatible operands: <{input_shape:?}> DOT <{weights_shape:?}>"
-                        );
-                        println!();
-                        panic!("Unsupported or invalid dot operation")
-                    }
                 }
             }
         }
@@ -384,10 +979,22 @@ impl<'dag> DagBuilder<'dag> {
             Operator::Input { out_precision, .. }
             | Operator::Lut { out_precision, .. }
             | Operator::UnsafeCast { out_precision, .. }
-            | Operator::Round { out_precision, .. } => *out_precision,
+            | Operator::Round { out_precision, .. }
+            | Operator::Bivariate { out_precision, .. }
+            | Operator::Mul { out_precision, .. } => *out_precision,
             Operator::Dot { inputs, .. } | Operator::LevelledOp { inputs, .. } => {
                 self.dag.out_precisions[inputs[0].0]
             }
+            Operator::Concatenate { inputs, .. } => inputs
+                .first()
+                .map_or(0, |input| self.dag.out_precisions[input.0]),
+            Operator::MaxPool { input, .. }
+            | Operator::Reshape { input, .. }
+            | Operator::Transpose { input, .. }
+            | Operator::Slice { input, .. }
+            | Operator::Gather { input, .. }
+            | Operator::Broadcast { input, .. }
+            | Operator::Conv2d { input, .. } => self.dag.out_precisions[input.0],
         }
     }
 }
@@ -405,7 +1012,10 @@ impl<'dag> DagBuilder<'dag> {
 ///
 /// For ease of use in tests, it is also possible to add operators on an anonymous circuit (`_`)
 /// directly on a [`Dag`] object itself, using the `Dag::add_*` methods.
-#[derive(Clone, PartialEq, Debug)]
+///
+/// The outputs of a circuit can be fed back to the inputs of the same or another circuit, see
+/// [`Dag::add_composition`].
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
 #[must_use]
 pub struct Dag {
     pub(crate) operators: Vec<Operator>,
@@ -417,15 +1027,8 @@ pub struct Dag {
     pub(crate) output_state: Vec<OutputState>,
     // Collect the circuit the operators are associated with
     pub(crate) circuit_tags: Vec<String>,
-}
-
-impl fmt::Display for Dag {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        for op in self.get_operators_iter() {
-            writeln!(f, "{} <- {:?}", op.id, op.operator)?;
-        }
-        Ok(())
-    }
+    // Collect the (output, input) pairs of outputs fed to inputs
+    pub(crate) compositions: Vec<(OperatorIndex, OperatorIndex)>,
 }
 
 impl Default for Dag {
@@ -443,6 +1046,7 @@ impl Dag {
             out_precisions: vec![],
             output_state: vec![],
             circuit_tags: vec![],
+            compositions: vec![],
         }
     }
 
@@ -511,21 +1115,121 @@ impl Dag {
             .add_round_op(input, rounded_precision)
     }
 
+    pub fn add_bivariate_lut(
+        &mut self,
+        lhs: OperatorIndex,
+        rhs: OperatorIndex,
+        table: FunctionTable,
+        out_precision: Precision,
+    ) -> OperatorIndex {
+        self.builder(DEFAULT_CIRCUIT)
+            .add_bivariate_lut(lhs, rhs, table, out_precision)
+    }
+
+    pub fn add_mul(
+        &mut self,
+        lhs: OperatorIndex,
+        rhs: OperatorIndex,
+        out_precision: Precision,
+    ) -> OperatorIndex {
+        self.builder(DEFAULT_CIRCUIT)
+            .add_mul(lhs, rhs, out_precision)
+    }
+
+    pub fn add_max_pool(
+        &mut self,
+        input: OperatorIndex,
+        window: impl Into<Shape>,
+    ) -> OperatorIndex {
+        self.builder(DEFAULT_CIRCUIT).add_max_pool(input, window)
+    }
+
+    pub fn add_reshape(
+        &mut self,
+        input: OperatorIndex,
+        out_shape: impl Into<Shape>,
+    ) -> OperatorIndex {
+        self.builder(DEFAULT_CIRCUIT).add_reshape(input, out_shape)
+    }
+
+    pub fn add_transpose(
+        &mut self,
+        input: OperatorIndex,
+        axes: impl Into<Vec<usize>>,
+    ) -> OperatorIndex {
+        self.builder(DEFAULT_CIRCUIT).add_transpose(input, axes)
+    }
+
+    pub fn add_concatenate(
+        &mut self,
+        inputs: impl Into<Vec<OperatorIndex>>,
+        axis: usize,
+    ) -> OperatorIndex {
+        self.builder(DEFAULT_CIRCUIT).add_concatenate(inputs, axis)
+    }
+
+    pub fn add_slice(
+        &mut self,
+        input: OperatorIndex,
+        offsets: impl Into<Vec<u64>>,
+        sizes: impl Into<Vec<u64>>,
+        strides: impl Into<Vec<u64>>,
+    ) -> OperatorIndex {
+        self.builder(DEFAULT_CIRCUIT)
+            .add_slice(input, offsets, sizes, strides)
+    }
+
+    pub fn add_gather(
+        &mut self,
+        input: OperatorIndex,
+        axis: usize,
+        indices: impl Into<Indices>,
+    ) -> OperatorIndex {
+        self.builder(DEFAULT_CIRCUIT)
+            .add_gather(input, axis, indices)
+    }
+
+    pub fn add_broadcast(
+        &mut self,
+        input: OperatorIndex,
+        out_shape: impl Into<Shape>,
+    ) -> OperatorIndex {
+        self.builder(DEFAULT_CIRCUIT)
+            .add_broadcast(input, out_shape)
+    }
+
+    pub fn add_conv2d(
+        &mut self,
+        input: OperatorIndex,
+        weights: impl Into<Weights>,
+        strides: [u64; 2],
+        padding: [u64; 4],
+        dilations: [u64; 2],
+        groups: u64,
+    ) -> OperatorIndex {
+        self.builder(DEFAULT_CIRCUIT)
+            .add_conv2d(input, weights, strides, padding, dilations, groups)
+    }
+
     #[allow(unused)]
-    fn add_shift_left_lsb_to_msb_no_padding(&mut self, input: OperatorIndex) -> OperatorIndex {
+    fn add_shift_left_lsb_to_msb_no_padding(
+        &mut self,
+        input: OperatorIndex,
+        window_precision: Precision,
+    ) -> OperatorIndex {
         self.builder(DEFAULT_CIRCUIT)
-            .add_shift_left_lsb_to_msb_no_padding(input)
+            .add_shift_left_lsb_to_msb_no_padding(input, window_precision)
     }
 
     #[allow(unused)]
-    fn add_lut_1bit_no_padding(
+    fn add_lut_no_padding(
         &mut self,
         input: OperatorIndex,
         table: FunctionTable,
         out_precision: Precision,
     ) -> OperatorIndex {
         self.builder(DEFAULT_CIRCUIT)
-            .add_lut_1bit_no_padding(input, table, out_precision)
+            .add_lut_no_padding(input, table, out_precision)
     }
 
     #[allow(unused)]
@@ -539,8 +1243,23 @@ impl Dag {
     }
 
     #[allow(unused)]
-    fn add_isolate_lowest_bit(&mut self, input: OperatorIndex) -> OperatorIndex {
-        self.builder(DEFAULT_CIRCUIT).add_isolate_lowest_bit(input)
+    fn add_isolate_lowest_bits(
+        &mut self,
+        input: OperatorIndex,
+        window_precision: Precision,
+    ) -> OperatorIndex {
+        self.builder(DEFAULT_CIRCUIT)
+            .add_isolate_lowest_bits(input, window_precision)
+    }
+
+    pub fn add_truncate_bits(
+        &mut self,
+        input: OperatorIndex,
+        nb_bits: Precision,
+        window_precision: Precision,
+    ) -> OperatorIndex {
+        self.builder(DEFAULT_CIRCUIT)
+            .add_truncate_bits(input, nb_bits, window_precision)
     }
 
     pub fn add_truncate_1_bit(&mut self, input: OperatorIndex) -> OperatorIndex {
@@ -556,6 +1275,19 @@ impl Dag {
             .add_expanded_round(input, rounded_precision)
     }
 
+    pub fn add_expanded_round_by_groups(
+        &mut self,
+        input: OperatorIndex,
+        rounded_precision: Precision,
+        group_bits: Precision,
+    ) -> OperatorIndex {
+        self.builder(DEFAULT_CIRCUIT).add_expanded_round_by_groups(
+            input,
+            rounded_precision,
+            group_bits,
+        )
+    }
+
     pub fn add_expanded_rounded_lut(
         &mut self,
         input: OperatorIndex,
@@ -586,6 +1318,32 @@ impl Dag {
         )
     }
 
+    pub fn add_expanded_bivariate_lut(
+        &mut self,
+        lhs: OperatorIndex,
+        rhs: OperatorIndex,
+        table: FunctionTable,
+        out_precision: Precision,
+    ) -> OperatorIndex {
+        self.builder(DEFAULT_CIRCUIT)
+            .add_expanded_bivariate_lut(lhs, rhs, table, out_precision)
+    }
+
+    pub fn add_expanded_mul(
+        &mut self,
+        lhs: OperatorIndex,
+        rhs: OperatorIndex,
+        out_precision: Precision,
+    ) -> OperatorIndex {
+        self.builder(DEFAULT_CIRCUIT)
+            .add_expanded_mul(lhs, rhs, out_precision)
+    }
+
+    pub fn add_expanded_max_pool(&mut self, input: OperatorIndex, window: &Shape) -> OperatorIndex {
+        self.builder(DEFAULT_CIRCUIT)
+            .add_expanded_max_pool(input, window)
+    }
+
     /// Returns an iterator over the operator indices.
     pub(crate) fn get_indices_iter(&self) -> impl Iterator<Item = OperatorIndex> {
         (0..self.len()).map(OperatorIndex)
@@ -677,6 +1435,46 @@ impl Dag {
         self.output_state[operator.0].transition_tag();
     }
 
+    /// Declares that the `output` operator of a circuit is fed to the `input` operator of the same
+    /// or another circuit, so that the `output` noise is propagated to `input`.
+    ///
+    /// # Note:
+    ///
+    /// Compositions can form cycles, whose noise is bounded only if it is refreshed by a lut on the
+    /// way. A malformed composition is reported by [`Dag::validate`].
+    pub fn add_composition(&mut self, output: OperatorIndex, input: OperatorIndex) {
+        self.compositions.push((output, input));
+    }
+
+    /// Like [`Self::add_composition`], but fails instead of adding a malformed composition.
+    ///
+    /// # Errors
+    /// The [`DagError`] of the composition, which is then not added.
+    pub fn try_add_composition(
+        &mut self,
+        output: OperatorIndex,
+        input: OperatorIndex,
+    ) -> Result<(), DagError> {
+        check_composition(self, output, input)?;
+        self.add_composition(output, input);
+        Ok(())
+    }
+
+    /// Returns the (output, input) compositions, every output being fed to every input if
+    /// `all_composed`.
+    pub(crate) fn get_compositions(
+        &self,
+        all_composed: bool,
+    ) -> Vec<(OperatorIndex, OperatorIndex)> {
+        if !all_composed {
+            return self.compositions.clone();
+        }
+        let outputs: Vec<_> = self.get_output_operators_iter().map(|op| op.id).collect();
+        self.get_input_operators_iter()
+            .flat_map(|input| outputs.iter().map(move |&output| (output, input.id)))
+            .collect()
+    }
+
     /// Returns the number of circuits in the dag.
     pub fn get_circuit_count(&self) -> usize {
         self.get_circuits_iter().count()
@@ -788,6 +1586,173 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_round_by_groups() {
+        let mut graph = Dag::new();
+        let input1 = graph.add_input(8, Shape::number());
+        let rounded = graph.add_expanded_round_by_groups(input1, 3, 2);
+        assert_eq!(graph.out_precisions[rounded.0], 3);
+        // 5 bits are truncated by 1 then 2 and 2 bits, all with a 2 bits window
+        let mut truncated_precisions = vec![];
+        for op in &graph.operators {
+            match op {
+                Operator::Lut { input, .. } => assert_eq!(graph.out_precisions[input.0], 1),
+                Operator::UnsafeCast {
+                    out_precision,
+                    input,
+                } if matches!(graph.operators[input.0], Operator::Dot { ref inputs, .. } if inputs.len() == 2) =>
+                {
+                    truncated_precisions.push(*out_precision);
+                }
+                _ => (),
+            }
+        }
+        assert_eq!(truncated_precisions, vec![7, 5, 3]);
+        assert_eq!(
+            graph.operators[1],
+            Operator::Dot {
+                inputs: vec![input1],
+                weights: Weights::number(1 << 7),
+            }
+        );
+        // the window does not exceed the precision
+        let input2 = graph.add_input(2, Shape::number());
+        let rounded = graph.add_expanded_round_by_groups(input2, 1, 4);
+        assert_eq!(graph.out_precisions[rounded.0], 1);
+    }
+
+    #[test]
+    fn test_composite_operators() {
+        let mut graph = Dag::new();
+        let input1 = graph.add_input(2, Shape::vector(6));
+        let input2 = graph.add_input(3, Shape::vector(6));
+        let bivariate = graph.add_bivariate_lut(input1, input2, FunctionTable::UNKWOWN, 4);
+        let mul = graph.add_mul(input1, input2, 5);
+        let tensor = graph.add_input(
+            3,
+            Shape {
+                dimensions_size: vec![3, 4, 6],
+            },
+        );
+        let max_pool = graph.add_max_pool(
+            tensor,
+            Shape {
+                dimensions_size: vec![2, 3],
+            },
+        );
+        assert_eq!(graph.out_precisions[bivariate.0], 4);
+        assert_eq!(graph.out_shapes[bivariate.0], Shape::vector(6));
+        assert_eq!(graph.out_precisions[mul.0], 5);
+        assert_eq!(graph.out_precisions[max_pool.0], 3);
+        assert_eq!(graph.out_shapes[max_pool.0].dimensions_size, vec![3, 2, 2]);
+
+        let mut expanded = Dag::new();
+        let input1 = expanded.add_input(2, Shape::vector(6));
+        let input2 = expanded.add_input(3, Shape::vector(6));
+        let packed_lut =
+            expanded.add_expanded_bivariate_lut(input1, input2, FunctionTable::UNKWOWN, 4);
+        // both inputs are packed in 5 bits
+        let Operator::Lut { input: packed, .. } = expanded.operators[packed_lut.0] else {
+            panic!()
+        };
+        assert_eq!(expanded.out_precisions[packed.0], 5);
+        assert_eq!(
+            expanded.operators[packed.0],
+            Operator::Dot {
+                inputs: vec![OperatorIndex(2), OperatorIndex(3)],
+                weights: Weights::vector([8, 1]),
+            }
+        );
+        let nb_ops = expanded.len();
+        let product = expanded.add_expanded_mul(input1, input2, 5);
+        assert_eq!(expanded.out_precisions[product.0], 5);
+        let squares = expanded.operators[nb_ops..]
+            .iter()
+            .filter(|op| matches!(op, Operator::Lut { input, .. } if expanded.out_precisions[input.0] == 4))
+            .count();
+        assert_eq!(squares, 2);
+        let tensor = expanded.add_input(3, Shape::vector(6));
+        let nb_ops = expanded.len();
+        // a window of 3 elements needs 2 max of pairs
+        let max = expanded.add_expanded_max_pool(tensor, &Shape::vector(3));
+        assert_eq!(expanded.out_precisions[max.0], 3);
+        assert_eq!(expanded.out_shapes[max.0], Shape::vector(2));
+        let relus: Vec<_> = expanded.operators[nb_ops..]
+            .iter()
+            .enumerate()
+            .filter(|(_, op)| matches!(op, Operator::Lut { .. }))
+            .map(|(i, _)| expanded.out_shapes[nb_ops + i].clone())
+            .collect();
+        assert_eq!(relus, vec![Shape::vector(2); 2]);
+        let offsets: Vec<_> = expanded.operators[nb_ops..]
+            .iter()
+            .filter_map(|op| match op {
+                Operator::Slice {
+                    offsets, strides, ..
+                } => {
+                    assert_eq!(*strides, vec![3]);
+                    Some(offsets[0])
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(offsets, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_shape_operators() {
+        let mut graph = Dag::new();
+        let input = graph.add_input(
+            3,
+            Shape {
+                dimensions_size: vec![2, 3, 4],
+            },
+        );
+        let reshape = graph.add_reshape(input, Shape::vector(24));
+        assert_eq!(graph.out_shapes[reshape.0], Shape::vector(24));
+        let transpose = graph.add_transpose(input, [2, 0, 1]);
+        assert_eq!(graph.out_shapes[transpose.0].dimensions_size, vec![4, 2, 3]);
+        let concat = graph.add_concatenate([input, input, input], 1);
+        assert_eq!(graph.out_shapes[concat.0].dimensions_size, vec![2, 9, 4]);
+        let slice = graph.add_slice(input, [1, 0, 1], [1, 2, 2], [1, 2, 2]);
+        assert_eq!(graph.out_shapes[slice.0].dimensions_size, vec![1, 2, 2]);
+        let indices = Indices {
+            shape: Shape {
+                dimensions_size: vec![2, 2],
+            },
+            values: vec![0, 3, 3, 1],
+        };
+        let gather = graph.add_gather(input, 2, indices);
+        assert_eq!(graph.out_shapes[gather.0].dimensions_size, vec![2, 3, 2, 2]);
+        let broadcast = graph.add_broadcast(
+            input,
+            Shape {
+                dimensions_size: vec![5, 2, 3, 4],
+            },
+        );
+        assert_eq!(graph.out_shapes[broadcast.0].flat_size(), 120);
+        let image = graph.add_input(
+            4,
+            Shape {
+                dimensions_size: vec![1, 4, 7, 7],
+            },
+        );
+        let weights = Weights {
+            shape: Shape {
+                dimensions_size: vec![6, 2, 3, 3],
+            },
+            values: vec![1; 108],
+        };
+        let conv = graph.add_conv2d(image, weights, [2, 1], [1, 1, 1, 0], [1, 2], 2);
+        // height: (7 + 2 - 3) / 2 + 1, width: (7 + 1 - 5) / 1 + 1
+        assert_eq!(graph.out_shapes[conv.0].dimensions_size, vec![1, 6, 4, 4]);
+        assert_eq!(graph.out_precisions[conv.0], 4);
+        for op in [reshape, transpose, concat, slice, gather, broadcast] {
+            assert_eq!(graph.out_precisions[op.0], 3);
+        }
+        assert_eq!(graph.validate(), Ok(()));
+    }
+
     #[test]
     fn test_rounded_lut() {
         let mut graph = Dag::new();
@@ -893,4 +1858,23 @@ mod tests {
             assert_eq!(expected, actual, "{i}-th operation");
         }
     }
+
+    #[test]
+    fn serialization_round_trip() {
+        let mut graph = Dag::new();
+        let mut builder = graph.builder("main1");
+        let a = builder.add_input(4, Shape::vector(2));
+        let b = builder.add_dot([a], [1, 3]);
+        let c = builder.add_lut(b, FunctionTable { values: vec![3; 16] }, 4);
+        let _ = builder.add_expanded_round(c, 2);
+        let mut builder = graph.builder("main2");
+        let d = builder.add_input(2, Shape::number());
+        builder.tag_operator_as_output(d);
+        let _ = builder.add_lut(d, FunctionTable::UNKWOWN, 2);
+
+        let json = serde_json::to_string(&graph).unwrap();
+        assert_eq!(graph, serde_json::from_str(&json).unwrap());
+        let bytes = bincode::serialize(&graph).unwrap();
+        assert_eq!(graph, bincode::deserialize(&bytes).unwrap());
+    }
 }
@@ -0,0 +1,1036 @@
+//! Checks that a [`Dag`] can be analyzed, reporting the first malformed operator.
+
+use std::fmt;
+
+use crate::dag::operator::{
+    dot_kind, DotKind, Operator, OperatorIndex, Precision, Shape, MAX_LUT_PRECISION, MAX_PRECISION,
+};
+use crate::dag::unparametrized::Dag;
+
+/// Why an operator makes its dag malformed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DagError {
+    /// The operator has no recorded shape, precision, output state or circuit.
+    Incomplete { operator: OperatorIndex },
+    /// An input is not an operator defined before this one.
+    UndefinedInput {
+        operator: OperatorIndex,
+        input: OperatorIndex,
+    },
+    /// An input belongs to another circuit.
+    InputOfOtherCircuit {
+        operator: OperatorIndex,
+        input: OperatorIndex,
+        circuit: String,
+        input_circuit: String,
+    },
+    /// A dot, levelled operator or concatenation without inputs.
+    NoInputs { operator: OperatorIndex },
+    /// The inputs of a dot, levelled operator or concatenation have different precisions.
+    MixedPrecisions {
+        operator: OperatorIndex,
+        precisions: Vec<Precision>,
+    },
+    /// The inputs of a dot, bivariate lut or multiplication have different shapes, or the inputs
+    /// of a concatenation differ outside of its axis.
+    MixedShapes {
+        operator: OperatorIndex,
+        shapes: Vec<Shape>,
+    },
+    /// The number of weights of a dot does not match their shape.
+    InvalidWeights {
+        operator: OperatorIndex,
+        count: usize,
+        shape: Shape,
+    },
+    /// The weights shape of a dot does not fit its inputs, or the analysis does not handle it.
+    UnsupportedDot {
+        operator: OperatorIndex,
+        nb_inputs: usize,
+        input_shape: Shape,
+        weights_shape: Shape,
+    },
+    /// A round to a higher precision than its input.
+    RoundToHigherPrecision {
+        operator: OperatorIndex,
+        in_precision: Precision,
+        out_precision: Precision,
+    },
+    /// A max pooling window that does not split the last dimensions of its input.
+    InvalidPoolWindow {
+        operator: OperatorIndex,
+        input_shape: Shape,
+        window: Shape,
+    },
+    /// A reshape changing the number of elements, or a broadcast to an incompatible shape.
+    IncompatibleShapes {
+        operator: OperatorIndex,
+        input_shape: Shape,
+        out_shape: Shape,
+    },
+    /// Transpose axes that are not a permutation, or a concatenation or gather axis out of rank.
+    InvalidAxes {
+        operator: OperatorIndex,
+        rank: usize,
+        axes: Vec<usize>,
+    },
+    /// A slice or gather reading elements outside of its input.
+    OutOfBounds {
+        operator: OperatorIndex,
+        input_shape: Shape,
+    },
+    /// A convolution whose weights, groups, strides, dilations or padding do not fit its input.
+    InvalidConvolution {
+        operator: OperatorIndex,
+        input_shape: Shape,
+        weights_shape: Shape,
+    },
+    /// An output, weights or indices shape without any value.
+    EmptyShape {
+        operator: OperatorIndex,
+        shape: Shape,
+    },
+    /// An output, weights or indices shape with more values than a `u64` can count.
+    ShapeOverflow {
+        operator: OperatorIndex,
+        shape: Shape,
+    },
+    /// A lut or bivariate lut table without one value per input value.
+    InvalidTable {
+        operator: OperatorIndex,
+        count: usize,
+        in_precision: Precision,
+    },
+    /// An output precision above [`MAX_PRECISION`], or a lut input precision above
+    /// [`MAX_LUT_PRECISION`], packed inputs included.
+    UnsupportedPrecision {
+        operator: OperatorIndex,
+        precision: u32,
+        max: Precision,
+    },
+    /// A composition that does not feed an input with an output of the same precision and shape.
+    InvalidComposition {
+        operator: OperatorIndex,
+        output: OperatorIndex,
+    },
+}
+
+impl DagError {
+    /// The malformed operator.
+    pub fn operator(&self) -> OperatorIndex {
+        match self {
+            Self::Incomplete { operator }
+            | Self::UndefinedInput { operator, .. }
+            | Self::InputOfOtherCircuit { operator, .. }
+            | Self::NoInputs { operator }
+            | Self::MixedPrecisions { operator, .. }
+            | Self::MixedShapes { operator, .. }
+            | Self::InvalidWeights { operator, .. }
+            | Self::UnsupportedDot { operator, .. }
+            | Self::RoundToHigherPrecision { operator, .. }
+            | Self::InvalidPoolWindow { operator, .. }
+            | Self::IncompatibleShapes { operator, .. }
+            | Self::InvalidAxes { operator, .. }
+            | Self::OutOfBounds { operator, .. }
+            | Self::InvalidConvolution { operator, .. }
+            | Self::EmptyShape { operator, .. }
+            | Self::ShapeOverflow { operator, .. }
+            | Self::InvalidTable { operator, .. }
+            | Self::UnsupportedPrecision { operator, .. }
+            | Self::InvalidComposition { operator, .. } => *operator,
+        }
+    }
+}
+
+impl fmt::Display for DagError {
+    #[allow(clippy::too_many_lines)]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "operator %{} ", self.operator())?;
+        match self {
+            Self::Incomplete { .. } => {
+                write!(f, "has no recorded shape, precision, output state or circuit")
+            }
+            Self::UndefinedInput { input, .. } => {
+                write!(f, "uses %{input}, which is not defined before it")
+            }
+            Self::InputOfOtherCircuit {
+                input,
+                circuit,
+                input_circuit,
+                ..
+            } => write!(f, "of circuit {circuit:?} uses %{input} of circuit {input_circuit:?}"),
+            Self::NoInputs { .. } => write!(f, "has no inputs"),
+            Self::MixedPrecisions { precisions, .. } => {
+                write!(f, "has inputs of different precisions {precisions:?}")
+            }
+            Self::MixedShapes { shapes, .. } => {
+                let shapes: Vec<_> = shapes.iter().map(|shape| &shape.dimensions_size).collect();
+                write!(f, "has inputs of different shapes {shapes:?}")
+            }
+            Self::InvalidWeights { count, shape, .. } => write!(
+                f,
+                "has {count} weights for a shape {:?} of {} values",
+                shape.dimensions_size,
+                shape.flat_size()
+            ),
+            Self::UnsupportedDot {
+                nb_inputs,
+                input_shape,
+                weights_shape,
+                ..
+            } => write!(
+                f,
+                "is an unsupported dot of {nb_inputs} inputs of shape {:?} with weights of shape {:?}",
+                input_shape.dimensions_size, weights_shape.dimensions_size
+            ),
+            Self::RoundToHigherPrecision {
+                in_precision,
+                out_precision,
+                ..
+            } => write!(f, "rounds a u{in_precision} to a u{out_precision}"),
+            Self::InvalidPoolWindow {
+                input_shape,
+                window,
+                ..
+            } => write!(
+                f,
+                "pools an input of shape {:?} by windows of shape {:?}",
+                input_shape.dimensions_size, window.dimensions_size
+            ),
+            Self::IncompatibleShapes {
+                input_shape,
+                out_shape,
+                ..
+            } => write!(
+                f,
+                "cannot give the shape {:?} to an input of shape {:?}",
+                out_shape.dimensions_size, input_shape.dimensions_size
+            ),
+            Self::InvalidAxes { rank, axes, .. } => {
+                write!(f, "has axes {axes:?} for inputs of rank {rank}")
+            }
+            Self::OutOfBounds { input_shape, .. } => write!(
+                f,
+                "reads outside of an input of shape {:?}",
+                input_shape.dimensions_size
+            ),
+            Self::InvalidConvolution {
+                input_shape,
+                weights_shape,
+                ..
+            } => write!(
+                f,
+                "convolves an input of shape {:?} by weights of shape {:?}",
+                input_shape.dimensions_size, weights_shape.dimensions_size
+            ),
+            Self::EmptyShape { shape, .. } => {
+                write!(f, "has an empty shape {:?}", shape.dimensions_size)
+            }
+            Self::ShapeOverflow { shape, .. } => write!(
+                f,
+                "has a shape {:?} of more than 2^64 values",
+                shape.dimensions_size
+            ),
+            Self::InvalidTable {
+                count,
+                in_precision,
+                ..
+            } => write!(
+                f,
+                "has a table of {count} values for an input of precision {in_precision}"
+            ),
+            Self::UnsupportedPrecision { precision, max, .. } => {
+                write!(f, "needs a precision of {precision}, above the maximum {max}")
+            }
+            Self::InvalidComposition { output, .. } => write!(
+                f,
+                "is not an input of the precision and shape of the output %{output} composed into it"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DagError {}
+
+fn all_same<Property: PartialEq + Clone>(
+    inputs: &[OperatorIndex],
+    properties: &[Property],
+) -> Result<(), Vec<Property>> {
+    let first = &properties[inputs[0].0];
+    if inputs.iter().all(|input| properties[input.0] == *first) {
+        Ok(())
+    } else {
+        Err(inputs
+            .iter()
+            .map(|input| properties[input.0].clone())
+            .collect())
+    }
+}
+
+/// Checks that `shape` has values, and not more than a `u64` can count.
+fn check_shape(operator: OperatorIndex, shape: &Shape) -> Result<(), DagError> {
+    match shape.checked_flat_size() {
+        Some(0) => Err(DagError::EmptyShape {
+            operator,
+            shape: shape.clone(),
+        }),
+        Some(_) => Ok(()),
+        None => Err(DagError::ShapeOverflow {
+            operator,
+            shape: shape.clone(),
+        }),
+    }
+}
+
+fn check_precision(
+    operator: OperatorIndex,
+    precision: impl Into<u32>,
+    max: Precision,
+) -> Result<(), DagError> {
+    let precision = precision.into();
+    if precision > u32::from(max) {
+        return Err(DagError::UnsupportedPrecision {
+            operator,
+            precision,
+            max,
+        });
+    }
+    Ok(())
+}
+
+/// Checks a lut on an input of `in_precision`, with an unknown table or one value per input value.
+fn check_lut(
+    operator: OperatorIndex,
+    in_precision: u32,
+    table: &[u64],
+    out_precision: Precision,
+) -> Result<(), DagError> {
+    check_precision(operator, in_precision, MAX_LUT_PRECISION)?;
+    check_precision(operator, out_precision, MAX_PRECISION)?;
+    if !table.is_empty() && table.len() as u64 != 1 << in_precision {
+        return Err(DagError::InvalidTable {
+            operator,
+            count: table.len(),
+            // in_precision <= MAX_LUT_PRECISION
+            in_precision: in_precision as Precision,
+        });
+    }
+    Ok(())
+}
+
+/// Checks `op`, to be added as `operator` in `circuit`, against the operators already in `dag`.
+#[allow(clippy::too_many_lines)]
+pub(crate) fn check_operator(
+    dag: &Dag,
+    operator: OperatorIndex,
+    op: &Operator,
+    circuit: &str,
+) -> Result<(), DagError> {
+    for &input in op.get_inputs_iter() {
+        if input.0 >= operator.0 {
+            return Err(DagError::UndefinedInput { operator, input });
+        }
+        let input_circuit = &dag.circuit_tags[input.0];
+        if input_circuit != circuit {
+            return Err(DagError::InputOfOtherCircuit {
+                operator,
+                input,
+                circuit: circuit.into(),
+                input_circuit: input_circuit.clone(),
+            });
+        }
+    }
+    match op {
+        Operator::Input {
+            out_precision,
+            out_shape,
+        } => {
+            check_precision(operator, *out_precision, MAX_PRECISION)?;
+            check_shape(operator, out_shape)
+        }
+        Operator::UnsafeCast { out_precision, .. } => {
+            check_precision(operator, *out_precision, MAX_PRECISION)
+        }
+        Operator::Lut {
+            input,
+            table,
+            out_precision,
+        } => check_lut(
+            operator,
+            dag.out_precisions[input.0].into(),
+            &table.values,
+            *out_precision,
+        ),
+        Operator::Round {
+            input,
+            out_precision,
+        } => {
+            let in_precision = dag.out_precisions[input.0];
+            if *out_precision > in_precision {
+                return Err(DagError::RoundToHigherPrecision {
+                    operator,
+                    in_precision,
+                    out_precision: *out_precision,
+                });
+            }
+            Ok(())
+        }
+        Operator::Bivariate { inputs, .. } | Operator::Mul { inputs, .. } => {
+            all_same(inputs, &dag.out_shapes)
+                .map_err(|shapes| DagError::MixedShapes { operator, shapes })?;
+            let [lhs, rhs] = inputs.map(|input| u32::from(dag.out_precisions[input.0]));
+            match op {
+                // both inputs are packed in the input of a single lut
+                Operator::Bivariate {
+                    table,
+                    out_precision,
+                    ..
+                } => check_lut(operator, lhs + rhs, &table.values, *out_precision),
+                // the squares are luts on one more bit than the inputs
+                Operator::Mul { out_precision, .. } => {
+                    check_lut(operator, lhs.max(rhs) + 1, &[], *out_precision)
+                }
+                _ => Ok(()),
+            }
+        }
+        Operator::MaxPool { input, window } => {
+            let input_shape = &dag.out_shapes[input.0];
+            // the pairwise max are luts on one more bit than the input
+            check_precision(
+                operator,
+                u32::from(dag.out_precisions[input.0]) + 1,
+                MAX_LUT_PRECISION,
+            )?;
+            if !input_shape.can_be_pooled(window) {
+                return Err(DagError::InvalidPoolWindow {
+                    operator,
+                    input_shape: input_shape.clone(),
+                    window: window.clone(),
+                });
+            }
+            Ok(())
+        }
+        Operator::Reshape { .. }
+        | Operator::Transpose { .. }
+        | Operator::Concatenate { .. }
+        | Operator::Broadcast { .. } => check_shape_operator(dag, operator, op),
+        Operator::Slice { .. } | Operator::Gather { .. } => check_bounds(dag, operator, op),
+        Operator::Conv2d { .. } => check_conv2d(dag, operator, op),
+        Operator::LevelledOp { inputs, .. } | Operator::Dot { inputs, .. } => {
+            if inputs.is_empty() {
+                return Err(DagError::NoInputs { operator });
+            }
+            all_same(inputs, &dag.out_precisions).map_err(|precisions| {
+                DagError::MixedPrecisions {
+                    operator,
+                    precisions,
+                }
+            })?;
+            if let Operator::LevelledOp { out_shape, .. } = op {
+                return check_shape(operator, out_shape);
+            }
+            let Operator::Dot { weights, .. } = op else {
+                return Ok(());
+            };
+            all_same(inputs, &dag.out_shapes)
+                .map_err(|shapes| DagError::MixedShapes { operator, shapes })?;
+            check_shape(operator, &weights.shape)?;
+            if weights.values.len() as u64 != weights.shape.flat_size() {
+                return Err(DagError::InvalidWeights {
+                    operator,
+                    count: weights.values.len(),
+                    shape: weights.shape.clone(),
+                });
+            }
+            let input_shape = &dag.out_shapes[inputs[0].0];
+            match dot_kind(inputs.len() as u64, input_shape, weights) {
+                DotKind::Simple | DotKind::Tensor | DotKind::Broadcast { .. } => Ok(()),
+                // not handled by the analysis
+                DotKind::CompatibleTensor | DotKind::Unsupported => Err(DagError::UnsupportedDot {
+                    operator,
+                    nb_inputs: inputs.len(),
+                    input_shape: input_shape.clone(),
+                    weights_shape: weights.shape.clone(),
+                }),
+            }
+        }
+    }
+}
+
+/// Checks a reshape, transpose, concatenation or broadcast.
+fn check_shape_operator(dag: &Dag, operator: OperatorIndex, op: &Operator) -> Result<(), DagError> {
+    match op {
+        Operator::Reshape { input, out_shape } | Operator::Broadcast { input, out_shape } => {
+            check_shape(operator, out_shape)?;
+            let input_shape = &dag.out_shapes[input.0];
+            let compatible = if matches!(op, Operator::Reshape { .. }) {
+                input_shape.flat_size() == out_shape.flat_size()
+            } else {
+                input_shape.can_be_broadcast_to(out_shape)
+            };
+            if !compatible {
+                return Err(DagError::IncompatibleShapes {
+                    operator,
+                    input_shape: input_shape.clone(),
+                    out_shape: out_shape.clone(),
+                });
+            }
+            Ok(())
+        }
+        Operator::Transpose { input, axes } => {
+            let rank = dag.out_shapes[input.0].rank();
+            if axes.len() != rank || !Shape::is_permutation(axes) {
+                return Err(DagError::InvalidAxes {
+                    operator,
+                    rank,
+                    axes: axes.clone(),
+                });
+            }
+            Ok(())
+        }
+        Operator::Concatenate { inputs, axis } => {
+            if inputs.is_empty() {
+                return Err(DagError::NoInputs { operator });
+            }
+            all_same(inputs, &dag.out_precisions).map_err(|precisions| {
+                DagError::MixedPrecisions {
+                    operator,
+                    precisions,
+                }
+            })?;
+            let first = &dag.out_shapes[inputs[0].0];
+            if *axis >= first.rank() {
+                return Err(DagError::InvalidAxes {
+                    operator,
+                    rank: first.rank(),
+                    axes: vec![*axis],
+                });
+            }
+            let same_outside_axis = |shape: &Shape| {
+                shape.rank() == first.rank()
+                    && (0..shape.rank())
+                        .all(|i| i == *axis || shape.dimensions_size[i] == first.dimensions_size[i])
+            };
+            if !inputs
+                .iter()
+                .all(|input| same_outside_axis(&dag.out_shapes[input.0]))
+            {
+                return Err(DagError::MixedShapes {
+                    operator,
+                    shapes: inputs
+                        .iter()
+                        .map(|input| dag.out_shapes[input.0].clone())
+                        .collect(),
+                });
+            }
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Checks that a slice or gather reads inside its input.
+fn check_bounds(dag: &Dag, operator: OperatorIndex, op: &Operator) -> Result<(), DagError> {
+    match op {
+        Operator::Slice {
+            input,
+            offsets,
+            sizes,
+            strides,
+        } => {
+            check_shape(
+                operator,
+                &Shape {
+                    dimensions_size: sizes.clone(),
+                },
+            )?;
+            let input_shape = &dag.out_shapes[input.0];
+            let rank = input_shape.rank();
+            let in_bounds = offsets.len() == rank
+                && sizes.len() == rank
+                && strides.len() == rank
+                && (0..rank).all(|i| {
+                    let last = (sizes[i].saturating_sub(1))
+                        .checked_mul(strides[i])
+                        .and_then(|span| span.checked_add(offsets[i]));
+                    strides[i] > 0 && last.is_some_and(|last| last < input_shape.dimensions_size[i])
+                });
+            if !in_bounds {
+                return Err(DagError::OutOfBounds {
+                    operator,
+                    input_shape: input_shape.clone(),
+                });
+            }
+            Ok(())
+        }
+        Operator::Gather {
+            input,
+            axis,
+            indices,
+        } => {
+            let input_shape = &dag.out_shapes[input.0];
+            let Some(&dim_size) = input_shape.dimensions_size.get(*axis) else {
+                return Err(DagError::InvalidAxes {
+                    operator,
+                    rank: input_shape.rank(),
+                    axes: vec![*axis],
+                });
+            };
+            check_shape(operator, &indices.shape)?;
+            if indices.values.len() as u64 != indices.flat_size()
+                || indices.values.iter().any(|&index| index >= dim_size)
+            {
+                return Err(DagError::OutOfBounds {
+                    operator,
+                    input_shape: input_shape.clone(),
+                });
+            }
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Checks that the convolution weights and attributes fit its input.
+fn check_conv2d(dag: &Dag, operator: OperatorIndex, op: &Operator) -> Result<(), DagError> {
+    match op {
+        Operator::Conv2d {
+            input,
+            weights,
+            strides,
+            padding,
+            dilations,
+            groups,
+        } => {
+            check_shape(operator, &weights.shape)?;
+            let input_shape = &dag.out_shapes[input.0];
+            let valid = match (
+                input_shape.dimensions_size.as_slice(),
+                weights.shape.dimensions_size.as_slice(),
+            ) {
+                (&[_, c, h, w], &[f, filter_c, kh, kw]) => {
+                    let fits = |size: u64, before: u64, after: u64, k: u64, i: usize| {
+                        let padded = size.checked_add(before).and_then(|s| s.checked_add(after));
+                        let span = k.checked_sub(1).and_then(|k| dilations[i].checked_mul(k));
+                        matches!((padded, span), (Some(padded), Some(span)) if padded > span)
+                    };
+                    *groups > 0
+                        && c % groups == 0
+                        && f % groups == 0
+                        && filter_c == c / groups
+                        && weights.values.len() as u64 == weights.flat_size()
+                        && strides.iter().chain(dilations).all(|&step| step > 0)
+                        && fits(h, padding[0], padding[2], kh, 0)
+                        && fits(w, padding[1], padding[3], kw, 1)
+                }
+                _ => false,
+            };
+            if !valid {
+                return Err(DagError::InvalidConvolution {
+                    operator,
+                    input_shape: input_shape.clone(),
+                    weights_shape: weights.shape.clone(),
+                });
+            }
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Checks that `output` is an output that can be fed to the `input` operator.
+pub(crate) fn check_composition(
+    dag: &Dag,
+    output: OperatorIndex,
+    input: OperatorIndex,
+) -> Result<(), DagError> {
+    let valid = output.0 < dag.len()
+        && input.0 < dag.len()
+        && dag.output_state[output.0].is_output()
+        && matches!(dag.operators[input.0], Operator::Input { .. })
+        && dag.out_precisions[output.0] == dag.out_precisions[input.0]
+        && dag.out_shapes[output.0] == dag.out_shapes[input.0];
+    if valid {
+        Ok(())
+    } else {
+        Err(DagError::InvalidComposition {
+            operator: input,
+            output,
+        })
+    }
+}
+
+impl Dag {
+    /// Checks that every operator and composition is well formed, as the analysis and optimization
+    /// expect.
+    ///
+    /// # Errors
+    /// The [`DagError`] of the first malformed operator or composition.
+    pub fn validate(&self) -> Result<(), DagError> {
+        for (i, op) in self.operators.iter().enumerate() {
+            let operator = OperatorIndex(i);
+            let complete = [
+                self.out_shapes.len(),
+                self.out_precisions.len(),
+                self.output_state.len(),
+                self.circuit_tags.len(),
+            ]
+            .iter()
+            .all(|&len| i < len);
+            if !complete {
+                return Err(DagError::Incomplete { operator });
+            }
+            check_shape(operator, &self.out_shapes[i])?;
+            check_operator(self, operator, op, &self.circuit_tags[i])?;
+        }
+        for &(output, input) in &self.compositions {
+            check_composition(self, output, input)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dag::operator::{FunctionTable, Indices, LevelledComplexity, Weights};
+
+    #[test]
+    fn valid_dag() {
+        let mut dag = Dag::new();
+        let mut builder = dag.builder("main");
+        let lhs = builder.add_input(4, Shape::vector(2));
+        let rhs = builder.add_input(4, Shape::vector(2));
+        let sum = builder.add_dot([lhs, rhs], [1, 2]);
+        let rounded = builder.add_expanded_rounded_lut(sum, FunctionTable::UNKWOWN, 2, 4);
+        let rounded_lut = builder.add_rounded_lut(rounded, FunctionTable::UNKWOWN, 2, 4);
+        let _ = builder.add_levelled_op(
+            [lhs, rounded_lut],
+            LevelledComplexity::ZERO,
+            1.0,
+            Shape::vector(4),
+            "concat",
+        );
+        assert_eq!(dag.validate(), Ok(()));
+    }
+
+    #[test]
+    fn invalid_operators() {
+        let mut dag = Dag::new();
+        let small = dag.add_input(2, Shape::number());
+        let big = dag.add_input(4, Shape::number());
+        let vector = dag.add_input(4, Shape::vector(3));
+        let other_circuit = dag.builder("other").add_input(4, Shape::number());
+        let next = OperatorIndex(dag.len());
+
+        let mut builder = dag.builder("_");
+        assert_eq!(
+            builder.try_add_lut(next, FunctionTable::UNKWOWN, 2),
+            Err(DagError::UndefinedInput {
+                operator: next,
+                input: next
+            })
+        );
+        let error = builder
+            .try_add_dot([big, other_circuit], [1, 1])
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "operator %4 of circuit \"_\" uses %3 of circuit \"other\""
+        );
+        assert_eq!(
+            builder.try_add_dot([], Weights::vector([])),
+            Err(DagError::NoInputs {
+                operator: OperatorIndex(4)
+            })
+        );
+        let error = builder
+            .try_add_levelled_op(
+                [small, big],
+                LevelledComplexity::ZERO,
+                1.0,
+                Shape::number(),
+                "",
+            )
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "operator %4 has inputs of different precisions [2, 4]"
+        );
+        let error = builder.try_add_dot([big, vector], [1, 1]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "operator %4 has inputs of different shapes [[], [3]]"
+        );
+        let weights = Weights {
+            shape: Shape::vector(3),
+            values: vec![1, 2],
+        };
+        let error = builder.try_add_dot([vector], weights).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "operator %4 has 2 weights for a shape [3] of 3 values"
+        );
+        let error = builder.try_add_dot([vector], [1, 2]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "operator %4 is an unsupported dot of 1 inputs of shape [3] with weights of shape [2]"
+        );
+        let error = builder.try_add_mul(big, vector, 4).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "operator %4 has inputs of different shapes [[], [3]]"
+        );
+        let error = builder
+            .try_add_max_pool(vector, Shape::vector(2))
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "operator %4 pools an input of shape [3] by windows of shape [2]"
+        );
+        let error = builder.try_add_max_pool(big, Shape::vector(1)).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "operator %4 pools an input of shape [] by windows of shape [1]"
+        );
+        let error = builder.try_add_round_op(small, 3).unwrap_err();
+        assert_eq!(error.to_string(), "operator %4 rounds a u2 to a u3");
+        assert_eq!(error.operator(), OperatorIndex(4));
+        assert_eq!(dag.len(), 4);
+        assert_eq!(dag.validate(), Ok(()));
+
+        // the infallible builder adds them anyway, to be reported by the validation
+        let rounded = dag.builder("_").add_round_op(small, 3);
+        assert_eq!(dag.validate(), Err(error));
+        assert_eq!(rounded, OperatorIndex(4));
+    }
+
+    #[test]
+    fn invalid_shape_operators() {
+        let mut dag = Dag::new();
+        let matrix_shape = Shape {
+            dimensions_size: vec![2, 3],
+        };
+        let matrix = dag.add_input(4, &matrix_shape);
+        let vector = dag.add_input(4, Shape::vector(3));
+        let small = dag.add_input(2, Shape::vector(3));
+        let image_shape = Shape {
+            dimensions_size: vec![1, 1, 3, 3],
+        };
+        let image = dag.add_input(4, &image_shape);
+        let weights = Weights {
+            shape: Shape {
+                dimensions_size: vec![1, 1, 2, 2],
+            },
+            values: vec![1; 4],
+        };
+        let mut builder = dag.builder("_");
+        let errors = [
+            (
+                builder.try_add_reshape(matrix, Shape::vector(5)),
+                "cannot give the shape [5] to an input of shape [2, 3]",
+            ),
+            (
+                builder.try_add_broadcast(matrix, Shape::vector(3)),
+                "cannot give the shape [3] to an input of shape [2, 3]",
+            ),
+            (
+                builder.try_add_transpose(matrix, [1, 1]),
+                "has axes [1, 1] for inputs of rank 2",
+            ),
+            (
+                builder.try_add_concatenate([matrix, vector], 0),
+                "has inputs of different shapes [[2, 3], [3]]",
+            ),
+            (
+                builder.try_add_concatenate([vector, small], 0),
+                "has inputs of different precisions [4, 2]",
+            ),
+            (
+                builder.try_add_concatenate([vector], 1),
+                "has axes [1] for inputs of rank 1",
+            ),
+            (
+                builder.try_add_slice(matrix, [1, 1], [1, 2], [1, 2]),
+                "reads outside of an input of shape [2, 3]",
+            ),
+            (
+                builder.try_add_slice(matrix, [1, 1], [1, 2], [1, u64::MAX]),
+                "reads outside of an input of shape [2, 3]",
+            ),
+            (
+                builder.try_add_gather(vector, 0, [0, 3]),
+                "reads outside of an input of shape [3]",
+            ),
+            (
+                builder.try_add_conv2d(matrix, [1], [1, 1], [0; 4], [1, 1], 1),
+                "convolves an input of shape [2, 3] by weights of shape [1]",
+            ),
+            (
+                builder.try_add_conv2d(image, &weights, [1, 1], [u64::MAX, 0, 1, 0], [1, 1], 1),
+                "convolves an input of shape [1, 1, 3, 3] by weights of shape [1, 1, 2, 2]",
+            ),
+            (
+                builder.try_add_conv2d(image, &weights, [1, 1], [0; 4], [1, u64::MAX], 1),
+                "convolves an input of shape [1, 1, 3, 3] by weights of shape [1, 1, 2, 2]",
+            ),
+        ];
+        for (result, message) in errors {
+            assert_eq!(
+                result.unwrap_err().to_string(),
+                format!("operator %4 {message}")
+            );
+        }
+        assert!(builder.try_add_concatenate([matrix, matrix], 1).is_ok());
+        assert!(builder
+            .try_add_slice(matrix, [1, 0], [1, 2], [1, 2])
+            .is_ok());
+        assert!(builder.try_add_broadcast(vector, &matrix_shape).is_ok());
+        assert_eq!(dag.validate(), Ok(()));
+    }
+
+    #[test]
+    fn invalid_sizes_tables_and_precisions() {
+        let mut dag = Dag::new();
+        let input = dag.add_input(4, Shape::vector(2));
+        let wide = dag.add_input(MAX_LUT_PRECISION, Shape::vector(2));
+        let half = dag.add_input(30, Shape::vector(2));
+        let huge = Shape {
+            dimensions_size: vec![1 << 32, 1 << 32, 2],
+        };
+        let mut builder = dag.builder("_");
+        let errors = [
+            (
+                builder.try_add_input(4, Shape::vector(0)),
+                "has an empty shape [0]",
+            ),
+            (
+                builder.try_add_input(4, &huge),
+                "has a shape [4294967296, 4294967296, 2] of more than 2^64 values",
+            ),
+            (
+                builder.try_add_broadcast(
+                    input,
+                    Shape {
+                        dimensions_size: vec![0, 2],
+                    },
+                ),
+                "has an empty shape [0, 2]",
+            ),
+            (
+                builder.try_add_reshape(input, &huge),
+                "has a shape [4294967296, 4294967296, 2] of more than 2^64 values",
+            ),
+            (
+                builder.try_add_slice(input, [0], [0], [1]),
+                "has an empty shape [0]",
+            ),
+            (
+                builder.try_add_gather(input, 0, Indices::vector([])),
+                "has an empty shape [0]",
+            ),
+            (
+                builder.try_add_lut(input, FunctionTable { values: vec![1, 2] }, 4),
+                "has a table of 2 values for an input of precision 4",
+            ),
+            (
+                builder.try_add_bivariate_lut(
+                    input,
+                    input,
+                    FunctionTable {
+                        values: vec![0; 16],
+                    },
+                    4,
+                ),
+                "has a table of 16 values for an input of precision 8",
+            ),
+            (
+                builder.try_add_input(70, Shape::number()),
+                "needs a precision of 70, above the maximum 62",
+            ),
+            (
+                builder.try_add_lut(input, FunctionTable::UNKWOWN, 70),
+                "needs a precision of 70, above the maximum 62",
+            ),
+            (
+                builder.try_add_bivariate_lut(half, half, FunctionTable::UNKWOWN, 4),
+                "needs a precision of 60, above the maximum 16",
+            ),
+            (
+                builder.try_add_mul(wide, wide, 4),
+                "needs a precision of 17, above the maximum 16",
+            ),
+            (
+                builder.try_add_max_pool(wide, Shape::vector(2)),
+                "needs a precision of 17, above the maximum 16",
+            ),
+        ];
+        for (result, message) in errors {
+            assert_eq!(
+                result.unwrap_err().to_string(),
+                format!("operator %3 {message}")
+            );
+        }
+        assert!(builder.try_add_lut(wide, FunctionTable::UNKWOWN, 4).is_ok());
+        assert_eq!(dag.validate(), Ok(()));
+
+        // the infallible builder adds them anyway, to be reported by the validation
+        let empty = dag.add_input(4, Shape::vector(0));
+        assert_eq!(
+            dag.validate(),
+            Err(DagError::EmptyShape {
+                operator: empty,
+                shape: Shape::vector(0)
+            })
+        );
+    }
+
+    #[test]
+    fn incomplete_dag() {
+        let mut dag = Dag::new();
+        let _ = dag.add_input(2, Shape::number());
+        let _ = dag.out_shapes.pop();
+        assert_eq!(
+            dag.validate(),
+            Err(DagError::Incomplete {
+                operator: OperatorIndex(0)
+            })
+        );
+    }
+
+    #[test]
+    fn invalid_compositions() {
+        let mut dag = Dag::new();
+        let input = dag.builder("a").add_input(3, Shape::number());
+        let lut = dag.builder("a").add_lut(input, FunctionTable::UNKWOWN, 3);
+        let other_input = dag.builder("b").add_input(3, Shape::number());
+        let wide_input = dag.builder("b").add_input(4, Shape::number());
+        let used = dag.builder("b").add_dot([other_input], [2]);
+        let _ = dag.builder("b").add_lut(used, FunctionTable::UNKWOWN, 3);
+        assert_eq!(dag.try_add_composition(lut, other_input), Ok(()));
+        assert_eq!(dag.try_add_composition(lut, input), Ok(()));
+        let errors = [
+            (lut, wide_input),
+            (lut, used),
+            (used, other_input),
+            (lut, OperatorIndex(9)),
+        ];
+        for (output, input) in errors {
+            assert_eq!(
+                dag.try_add_composition(output, input),
+                Err(DagError::InvalidComposition {
+                    operator: input,
+                    output
+                })
+            );
+        }
+        assert_eq!(dag.compositions.len(), 2);
+        assert_eq!(dag.validate(), Ok(()));
+
+        dag.add_composition(lut, wide_input);
+        assert_eq!(
+            dag.validate().unwrap_err().to_string(),
+            "operator %3 is not an input of the precision and shape of the output %1 composed into it"
+        );
+    }
+}
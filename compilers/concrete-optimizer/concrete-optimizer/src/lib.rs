@@ -28,4 +28,9 @@ pub mod parameters;
 pub mod utils;
 pub mod weight;
 
+pub use concrete_cpu_noise_model::gaussian_noise::error_probability::ErrorProbabilityModel;
+pub use concrete_cpu_noise_model::gaussian_noise::noise::modulus_switching::ModulusSwitchingKind;
+pub use concrete_cpu_noise_model::gaussian_noise::worst_case::NoiseMode;
+pub use concrete_security_curves::gaussian::curves::{CurvesError, SecurityCurves};
 pub use concrete_security_curves::gaussian::security::supported_security_levels;
+pub use concrete_security_curves::key_distribution::KeyDistribution;
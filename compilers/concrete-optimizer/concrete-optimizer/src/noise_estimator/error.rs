@@ -50,6 +50,13 @@ pub fn safe_variance_bound_2padbits(
     )
 }
 
+/// Largest error on the torus a ciphertext of `precision` bits keeps within its padding, the bound
+/// of the worst-case noise mode.
+pub fn max_error_bound_2padbits(precision: u64) -> f64 {
+    let padding_bits = LEFT_PADDING_BITS + RIGHT_PADDING_BITS;
+    fatal_variance_limit(padding_bits, precision, 0)
+}
+
 pub fn safe_variance_bound_product_1padbit(
     precision: f64,
     ciphertext_modulus_log: u32,
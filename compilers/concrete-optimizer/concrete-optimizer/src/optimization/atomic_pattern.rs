@@ -1,4 +1,11 @@
-use concrete_cpu_noise_model::gaussian_noise::noise::modulus_switching::estimate_modulus_switching_noise_with_binary_key;
+use std::collections::HashMap;
+
+use concrete_cpu_noise_model::gaussian_noise::error_probability::{
+    error_probability_with_gaussian, gaussian_noise_budget, ErrorProbabilityModel,
+};
+use concrete_cpu_noise_model::gaussian_noise::noise::modulus_switching::{
+    breakdown_modulus_switching_with_key, estimate_modulus_switching_noise_with_key,
+};
 
 use super::config::{Config, SearchSpace};
 use super::decomposition::cmux::CmuxComplexityNoise;
@@ -49,22 +56,61 @@ pub struct Caches {
     pub cb_pbs: circuit_bootstrap::Cache,
 }
 
+/// The keyswitch and blind rotate noises are sums of many independent errors, taken gaussian.
+/// With the exact tail, the modulus switching keeps its own distribution: the safe variance is the
+/// room it leaves for the gaussian noise at `max_error`, plus its own variance.
+fn safe_variance_with_modulus_switching(
+    consts: &OptimizationDecompositionsConsts,
+    max_error: f64,
+    internal_dim: u64,
+    glwe_log2_polynomial_size: u64,
+) -> Option<f64> {
+    match consts.config.error_probability_model {
+        ErrorProbabilityModel::Gaussian => Some(consts.safe_variance),
+        ErrorProbabilityModel::ExactTail => {
+            let modulus_switching = breakdown_modulus_switching_with_key(
+                internal_dim,
+                glwe_log2_polynomial_size,
+                consts.config.ciphertext_modulus_log,
+                consts
+                    .config
+                    .security_curves
+                    .key_distribution()
+                    .moments(internal_dim),
+            );
+            gaussian_noise_budget(
+                &modulus_switching,
+                max_error,
+                consts.config.maximum_acceptable_error_probability,
+            )
+            .map(|budget| budget + modulus_switching.total())
+        }
+    }
+}
+
 #[allow(clippy::too_many_lines)]
 fn update_state_with_best_decompositions(
     state: &mut OptimizationState,
     consts: &OptimizationDecompositionsConsts,
+    safe_variance: f64,
+    max_error: f64,
     internal_dim: u64,
     glwe_params: GlweParameters,
     cmux_quantities: &[CmuxComplexityNoise],
     ks_quantities: &[KsComplexityNoise],
 ) {
     let input_lwe_dimension = glwe_params.sample_extract_lwe_dimension();
-    let noise_modulus_switching = estimate_modulus_switching_noise_with_binary_key(
+    let key_moments = consts
+        .config
+        .security_curves
+        .key_distribution()
+        .moments(internal_dim);
+    let noise_modulus_switching = estimate_modulus_switching_noise_with_key(
         internal_dim,
         glwe_params.log2_polynomial_size,
         consts.config.ciphertext_modulus_log,
+        key_moments,
     );
-    let safe_variance = consts.safe_variance;
     if CUTS && noise_modulus_switching > safe_variance {
         return;
     }
@@ -103,7 +149,21 @@ fn update_state_with_best_decompositions(
             }
             // feasible and at least as good complexity
             if complexity < best_complexity || noise_max < best_variance {
-                let p_error = find_p_error(consts.kappa, safe_variance, noise_max);
+                let p_error = match consts.config.error_probability_model {
+                    ErrorProbabilityModel::Gaussian => {
+                        find_p_error(consts.kappa, safe_variance, noise_max)
+                    }
+                    ErrorProbabilityModel::ExactTail => error_probability_with_gaussian(
+                        &breakdown_modulus_switching_with_key(
+                            internal_dim,
+                            glwe_params.log2_polynomial_size,
+                            consts.config.ciphertext_modulus_log,
+                            key_moments,
+                        ),
+                        noise_max - noise_modulus_switching,
+                        max_error,
+                    ),
+                };
 
                 let BrDecompositionParameters {
                     level: br_l,
@@ -162,6 +222,7 @@ pub fn optimize_one(
     );
     let kappa =
         error::sigma_scale_of_error_probability(config.maximum_acceptable_error_probability);
+    let max_error = error::max_error_bound_2padbits(precision);
 
     let consts = OptimizationDecompositionsConsts {
         config,
@@ -174,20 +235,36 @@ pub fn optimize_one(
     let mut state = OptimizationState {
         best_solution: None,
     };
+    if config.check_average_case().is_err() {
+        return state;
+    }
 
     // cut only on glwe_poly_size based of modulus switching noise
     // assume this noise is increasing with lwe_intern_dim
     let min_internal_lwe_dimensions = search_space.internal_lwe_dimensions[0];
     let lower_bound_cut = |glwe_log_poly_size| {
         // TODO: cut if min complexity is higher than current best
-        CUTS && estimate_modulus_switching_noise_with_binary_key(
+        CUTS && !safe_variance_with_modulus_switching(
+            &consts,
+            max_error,
             min_internal_lwe_dimensions,
             glwe_log_poly_size,
-            ciphertext_modulus_log,
-        ) > consts.safe_variance
+        )
+        .is_some_and(|safe_variance| {
+            estimate_modulus_switching_noise_with_key(
+                min_internal_lwe_dimensions,
+                glwe_log_poly_size,
+                ciphertext_modulus_log,
+                config
+                    .security_curves
+                    .key_distribution()
+                    .moments(min_internal_lwe_dimensions),
+            ) <= safe_variance
+        })
     };
 
     let mut caches = persistent_caches.caches();
+    let mut safe_variances = HashMap::new();
 
     for &glwe_dim in &search_space.glwe_dimensions {
         for &glwe_log_poly_size in &search_space.glwe_log_polynomial_sizes {
@@ -207,11 +284,28 @@ pub fn optimize_one(
             for &internal_dim in &search_space.internal_lwe_dimensions {
                 assert!(256 < internal_dim);
 
+                // the exact tail of the modulus switching does not depend on the glwe dimension
+                let safe_variance = *safe_variances
+                    .entry((glwe_log_poly_size, internal_dim))
+                    .or_insert_with(|| {
+                        safe_variance_with_modulus_switching(
+                            &consts,
+                            max_error,
+                            internal_dim,
+                            glwe_log_poly_size,
+                        )
+                    });
+                let Some(safe_variance) = safe_variance else {
+                    continue;
+                };
+
                 let ks_quantities = caches.keyswitch.pareto_quantities(internal_dim);
 
                 update_state_with_best_decompositions(
                     &mut state,
                     &consts,
+                    safe_variance,
+                    max_error,
                     internal_dim,
                     glwe_params,
                     cmux_quantities,
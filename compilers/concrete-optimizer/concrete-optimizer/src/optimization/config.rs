@@ -1,15 +1,41 @@
 use crate::computing_cost::complexity_model::ComplexityModel;
 use crate::config;
 use crate::config::GpuPbsType;
+use crate::dag::rewrite::passes::Pass;
 use crate::global_parameters::{Range, DEFAUT_DOMAINS};
+use crate::optimization;
+use concrete_cpu_noise_model::gaussian_noise::error_probability::ErrorProbabilityModel;
+use concrete_cpu_noise_model::gaussian_noise::noise::modulus_switching::ModulusSwitchingKind;
+use concrete_cpu_noise_model::gaussian_noise::worst_case::NoiseMode;
+use concrete_security_curves::gaussian::curves::SecurityCurves;
+
+/// How finely the dag analysis tracks the noise of the elements of a tensor.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NoiseTracking {
+    /// All the elements of a tensor have the noise of its worst element.
+    PerTensor,
+    /// The elements are grouped in classes of the same noise, e.g. the output channels of a
+    /// convolution or the columns of a matmul, each class being constrained with its own count of
+    /// elements. The class of each element is kept through the shape operators and the dots.
+    PerElementClass,
+}
 
 #[derive(Clone, Copy, Debug)]
 pub struct NoiseBoundConfig {
     pub security_level: u64,
     pub maximum_acceptable_error_probability: f64,
     pub ciphertext_modulus_log: u32,
+    pub noise_tracking: NoiseTracking,
 }
 
+/// The optimization configuration.
+///
+/// With round operators, the multi parameters optimizer runs the whole optimization once per
+/// number of bits truncated per pbs in [`ROUND_GROUPS_BITS`], i.e. up to 4 times the cost of a dag
+/// without rounds. The chosen number of bits is the same for all the rounds of the dag, it is not
+/// chosen per round operator.
+///
+/// [`ROUND_GROUPS_BITS`]: crate::dag::rewrite::expand::ROUND_GROUPS_BITS
 #[derive(Clone, Copy)]
 pub struct Config<'a> {
     pub security_level: u64,
@@ -19,6 +45,71 @@ pub struct Config<'a> {
     pub fft_precision: u32,
     pub complexity_model: &'a dyn ComplexityModel,
     pub composable: bool,
+    pub security_curves: &'a SecurityCurves,
+    /// Whether `maximum_acceptable_error_probability` bounds the error probability or the
+    /// maximal errors must never exceed the padding, only for the multi parameters optimizer
+    pub noise_mode: NoiseMode,
+    /// Whether the error probability is the tail of a gaussian of the noise variance or the exact
+    /// tail of the noise terms distributions, only for the atomic pattern and solo key optimizers:
+    /// the multi parameters optimizer reports `UnsupportedErrorProbabilityModel`
+    pub error_probability_model: ErrorProbabilityModel,
+    /// Simplifications of the dag run before the multi parameters optimization, the solution
+    /// instructions keys still follow the original instructions, the removed ones having none, and
+    /// the circuit solution gives the simplified dag
+    pub dag_passes: &'a [Pass],
+    /// Whether identity luts are inserted after the dag passes on the cheapest composed outputs or
+    /// inputs, so that the compositions stop carrying a growing noise, only for the multi
+    /// parameters optimizer
+    pub refresh_compositions: bool,
+    /// Whether the error probability of a tensor counts all its elements at the worst noise or
+    /// per class of elements of the same noise
+    pub noise_tracking: NoiseTracking,
+}
+
+impl Config<'_> {
+    /// Rejects security levels and moduli the security curves cannot handle, before searching.
+    /// Fixed weight keys are rejected too: the decomposition caches assume the keyswitch noise is
+    /// linear in the input dimension.
+    ///
+    /// # Errors
+    /// `UnsupportedSecurityLevel`, `UnsupportedCiphertextModulus` or `UnsupportedKeyDistribution`.
+    pub fn check_security(&self) -> optimization::Result<()> {
+        let key_distribution = self.security_curves.key_distribution();
+        if key_distribution.has_fixed_weight() {
+            return Err(optimization::Err::UnsupportedKeyDistribution(format!(
+                "{key_distribution:?}"
+            )));
+        }
+        Ok(self
+            .security_curves
+            .check(self.security_level, self.ciphertext_modulus_log)?)
+    }
+
+    /// The worst-case noise mode is only implemented by the multi parameters optimizer.
+    ///
+    /// # Errors
+    /// `UnsupportedNoiseMode`.
+    pub fn check_average_case(&self) -> optimization::Result<()> {
+        match self.noise_mode {
+            NoiseMode::AverageCase => Ok(()),
+            NoiseMode::WorstCase => Err(optimization::Err::UnsupportedNoiseMode(self.noise_mode)),
+        }
+    }
+
+    /// The exact tail is only implemented by the atomic pattern and solo key optimizers. The multi
+    /// parameters optimizer returns this error, the wop optimizer, which returns no error, finds no
+    /// solution.
+    ///
+    /// # Errors
+    /// `UnsupportedErrorProbabilityModel`.
+    pub fn check_gaussian_tail(&self) -> optimization::Result<()> {
+        match self.error_probability_model {
+            ErrorProbabilityModel::Gaussian => Ok(()),
+            ErrorProbabilityModel::ExactTail => Err(
+                optimization::Err::UnsupportedErrorProbabilityModel(self.error_probability_model),
+            ),
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -27,6 +118,8 @@ pub struct SearchSpace {
     pub glwe_dimensions: Vec<u64>,
     pub internal_lwe_dimensions: Vec<u64>,
     pub levelled_only_lwe_dimensions: Range,
+    /// Modulus switchings the backend bootstrap implements, one is chosen per partition
+    pub modulus_switching_kinds: Vec<ModulusSwitchingKind>,
 }
 
 impl SearchSpace {
@@ -43,6 +136,7 @@ impl SearchSpace {
             glwe_dimensions,
             internal_lwe_dimensions,
             levelled_only_lwe_dimensions,
+            modulus_switching_kinds: vec![ModulusSwitchingKind::Standard],
         }
     }
 
@@ -59,6 +153,7 @@ impl SearchSpace {
             glwe_dimensions,
             internal_lwe_dimensions,
             levelled_only_lwe_dimensions,
+            modulus_switching_kinds: vec![ModulusSwitchingKind::Standard],
         }
     }
 
@@ -75,6 +170,7 @@ impl SearchSpace {
             glwe_dimensions,
             internal_lwe_dimensions,
             levelled_only_lwe_dimensions,
+            modulus_switching_kinds: vec![ModulusSwitchingKind::Standard],
         }
     }
     pub fn default(processing_unit: config::ProcessingUnit) -> Self {
@@ -0,0 +1,387 @@
+use crate::dag::operator::{dot_kind, DotKind, Operator, OperatorIndex, Shape};
+use crate::utils::square;
+
+// Above this number of classes, the classes are collapsed to the worst one
+pub const MAX_ELEMENT_CLASSES: usize = 256;
+
+/// A symbolic variance of one of the dag analyses.
+pub trait ClassVariance:
+    Clone + PartialEq + std::ops::Mul<f64, Output = Self> + std::ops::AddAssign
+{
+    // A variance bounding both
+    fn worst(&self, other: &Self) -> Self;
+}
+
+/// The variances of the elements of a tensor, grouped in classes of elements of the same variance.
+#[derive(Clone, Debug)]
+pub struct ElementVariances<V> {
+    // (number of elements, variance of each element)
+    pub classes: Vec<(u64, V)>,
+    // The class of each element in row major order, empty when there is a single class.
+    // A class fits in a byte as there are at most MAX_ELEMENT_CLASSES classes.
+    element_classes: Vec<u8>,
+}
+
+impl<V: ClassVariance> ElementVariances<V> {
+    pub fn uniform(count: u64, variance: V) -> Self {
+        Self {
+            classes: vec![(count, variance)],
+            element_classes: vec![],
+        }
+    }
+
+    // The classes of the given element variances, in row major order
+    fn from_variances(variances: impl IntoIterator<Item = V>) -> Self {
+        let mut classes: Vec<(u64, V)> = vec![];
+        let mut element_classes = vec![];
+        let mut variances = variances.into_iter();
+        for variance in variances.by_ref() {
+            let class = if let Some(class) = classes.iter().position(|(_, v)| *v == variance) {
+                class
+            } else if classes.len() < MAX_ELEMENT_CLASSES {
+                classes.push((0, variance));
+                classes.len() - 1
+            } else {
+                // Too many classes, the elements are collapsed to the worst one
+                let mut count = element_classes.len() as u64 + 1;
+                let mut worst = classes
+                    .iter()
+                    .fold(variance, |worst, (_, v)| worst.worst(v));
+                for variance in variances {
+                    worst = worst.worst(&variance);
+                    count += 1;
+                }
+                return Self::uniform(count, worst);
+            };
+            classes[class].0 += 1;
+            element_classes.push(class as u8);
+        }
+        if classes.len() <= 1 {
+            element_classes.clear();
+        }
+        Self {
+            classes,
+            element_classes,
+        }
+    }
+
+    // The elements of the inputs at the given (input, flat index) sources, in row major order
+    fn moved(inputs: &[Self], sources: impl IntoIterator<Item = (usize, usize)>) -> Self {
+        // The output class of each input class, assigned on first use
+        let mut out_class: Vec<Vec<Option<u8>>> = inputs
+            .iter()
+            .map(|input| vec![None; input.classes.len()])
+            .collect();
+        let mut classes: Vec<(u64, V)> = vec![];
+        let mut element_classes = vec![];
+        for (input, index) in sources {
+            let in_class = inputs[input].class_of(index);
+            let class = if let Some(class) = out_class[input][in_class] {
+                class as usize
+            } else {
+                let variance = &inputs[input].classes[in_class].1;
+                let class = if let Some(class) = classes.iter().position(|(_, v)| v == variance) {
+                    class
+                } else if classes.len() < MAX_ELEMENT_CLASSES {
+                    classes.push((0, variance.clone()));
+                    classes.len() - 1
+                } else {
+                    let count = inputs.iter().map(Self::flat_size).sum();
+                    let worst = inputs
+                        .iter()
+                        .map(Self::worst)
+                        .reduce(|worst, variance| worst.worst(&variance))
+                        .unwrap();
+                    return Self::uniform(count, worst);
+                };
+                out_class[input][in_class] = Some(class as u8);
+                class
+            };
+            classes[class].0 += 1;
+            element_classes.push(class as u8);
+        }
+        if classes.len() <= 1 {
+            element_classes.clear();
+        }
+        Self {
+            classes,
+            element_classes,
+        }
+    }
+
+    fn class_of(&self, index: usize) -> usize {
+        self.element_classes
+            .get(index)
+            .map_or(0, |&class| class as usize)
+    }
+
+    fn variance_of(&self, index: usize) -> &V {
+        &self.classes[self.class_of(index)].1
+    }
+
+    pub fn flat_size(&self) -> u64 {
+        self.classes.iter().map(|(count, _)| count).sum()
+    }
+
+    pub fn worst(&self) -> V {
+        self.classes
+            .iter()
+            .map(|(_, variance)| variance.clone())
+            .reduce(|worst, variance| worst.worst(&variance))
+            .unwrap()
+    }
+
+    pub fn is_uniform(&self) -> bool {
+        self.classes.len() <= 1
+    }
+
+    pub fn map(&self, f: impl Fn(&V) -> V) -> Self {
+        let mapped: Vec<V> = self
+            .classes
+            .iter()
+            .map(|(_, variance)| f(variance))
+            .collect();
+        if self.is_uniform() {
+            return Self::uniform(self.flat_size(), mapped[0].clone());
+        }
+        Self::from_variances(
+            self.element_classes
+                .iter()
+                .map(|&class| mapped[class as usize].clone()),
+        )
+    }
+}
+
+// The source of each element of a tensor, in row major order, from its multi-dimensional index
+fn flat_sources<T>(shape: &Shape, source: impl Fn(&[u64]) -> T) -> Vec<T> {
+    let dims = &shape.dimensions_size;
+    let size = shape.flat_size();
+    let mut index = vec![0; dims.len()];
+    let mut sources = Vec::with_capacity(size as usize);
+    for _ in 0..size {
+        sources.push(source(&index));
+        for (i, dim_size) in index.iter_mut().zip(dims).rev() {
+            *i += 1;
+            if *i < *dim_size {
+                break;
+            }
+            *i = 0;
+        }
+    }
+    sources
+}
+
+fn row_major_strides(shape: &Shape) -> Vec<u64> {
+    let mut strides = vec![1; shape.rank()];
+    for i in (0..shape.rank().saturating_sub(1)).rev() {
+        strides[i] = strides[i + 1] * shape.dimensions_size[i + 1];
+    }
+    strides
+}
+
+fn flat_index(index: impl Iterator<Item = u64>, strides: &[u64]) -> u64 {
+    index.zip(strides).map(|(i, stride)| i * stride).sum()
+}
+
+// The input flat index of each output element of a shape operator
+fn shape_op_sources(op: &Operator, out_shapes: &[Shape], out_shape: &Shape) -> Vec<usize> {
+    match op {
+        Operator::Transpose { input, axes } => {
+            let in_strides = row_major_strides(&out_shapes[input.0]);
+            let strides: Vec<_> = axes.iter().map(|&axis| in_strides[axis]).collect();
+            flat_sources(out_shape, |index| {
+                flat_index(index.iter().copied(), &strides) as usize
+            })
+        }
+        Operator::Broadcast { input, .. } => {
+            let in_shape = &out_shapes[input.0];
+            let in_strides = row_major_strides(in_shape);
+            let first = out_shape.rank() - in_shape.rank();
+            flat_sources(out_shape, |index| {
+                flat_index(
+                    index[first..]
+                        .iter()
+                        .zip(&in_shape.dimensions_size)
+                        .map(|(&i, &dim_size)| if dim_size == 1 { 0 } else { i }),
+                    &in_strides,
+                ) as usize
+            })
+        }
+        Operator::Slice {
+            input,
+            offsets,
+            strides,
+            ..
+        } => {
+            let in_strides = row_major_strides(&out_shapes[input.0]);
+            flat_sources(out_shape, |index| {
+                flat_index(
+                    index
+                        .iter()
+                        .zip(offsets.iter().zip(strides))
+                        .map(|(i, (offset, stride))| offset + i * stride),
+                    &in_strides,
+                ) as usize
+            })
+        }
+        Operator::Gather {
+            input,
+            axis,
+            indices,
+        } => {
+            let in_strides = row_major_strides(&out_shapes[input.0]);
+            let indices_strides = row_major_strides(&indices.shape);
+            let indices_rank = indices.shape.rank();
+            flat_sources(out_shape, |index| {
+                let gathered = flat_index(
+                    index[*axis..*axis + indices_rank].iter().copied(),
+                    &indices_strides,
+                );
+                let in_index = index[..*axis]
+                    .iter()
+                    .copied()
+                    .chain([indices.values[gathered as usize]])
+                    .chain(index[*axis + indices_rank..].iter().copied());
+                flat_index(in_index, &in_strides) as usize
+            })
+        }
+        _ => unreachable!("{op} is not a shape operator"),
+    }
+}
+
+/// The element classes of an operator output.
+///
+/// `worst` is the variance of the output worst element as computed by the analysis, it is the
+/// variance of all the elements when the operator mixes the input elements in an unknown way.
+#[allow(clippy::too_many_lines)]
+pub fn out_element_variances<V: ClassVariance>(
+    op: &Operator,
+    out_shapes: &[Shape],
+    out_shape: &Shape,
+    worst: &V,
+    in_element_variances: impl Fn(OperatorIndex) -> ElementVariances<V>,
+) -> ElementVariances<V> {
+    let out_size = out_shape.flat_size();
+    let uniform = || ElementVariances::uniform(out_size, worst.clone());
+    match op {
+        Operator::UnsafeCast { input, .. } | Operator::Reshape { input, .. } => {
+            in_element_variances(*input)
+        }
+        Operator::Transpose { input, .. }
+        | Operator::Broadcast { input, .. }
+        | Operator::Slice { input, .. }
+        | Operator::Gather { input, .. } => {
+            let classes = in_element_variances(*input);
+            if classes.is_uniform() {
+                return ElementVariances::uniform(out_size, classes.worst());
+            }
+            let sources = shape_op_sources(op, out_shapes, out_shape);
+            ElementVariances::moved(&[classes], sources.into_iter().map(|index| (0, index)))
+        }
+        Operator::Concatenate { inputs, axis } => {
+            let classes: Vec<_> = inputs.iter().map(|i| in_element_variances(*i)).collect();
+            let in_shapes: Vec<_> = inputs.iter().map(|i| &out_shapes[i.0]).collect();
+            let in_strides: Vec<_> = in_shapes.iter().map(|s| row_major_strides(s)).collect();
+            let sources = flat_sources(out_shape, |index| {
+                let mut offset = index[*axis];
+                let mut input = 0;
+                while offset >= in_shapes[input].dimensions_size[*axis] {
+                    offset -= in_shapes[input].dimensions_size[*axis];
+                    input += 1;
+                }
+                let in_index = index
+                    .iter()
+                    .enumerate()
+                    .map(|(d, &i)| if d == *axis { offset } else { i });
+                (input, flat_index(in_index, &in_strides[input]) as usize)
+            });
+            ElementVariances::moved(&classes, sources)
+        }
+        Operator::Conv2d { input, weights, .. } => {
+            // One class per filter, i.e. per output channel of a [N, F, H, W] output
+            let in_worst = in_element_variances(*input).worst();
+            let filter_variances: Vec<V> = weights
+                .first_dim_square_norm2s()
+                .into_iter()
+                .map(|norm| in_worst.clone() * norm as f64)
+                .collect();
+            let channel_size = out_shape.dimensions_size[2..]
+                .iter()
+                .product::<u64>()
+                .max(1);
+            let nb_filters = filter_variances.len() as u64;
+            ElementVariances::from_variances((0..out_size).map(|index| {
+                filter_variances[((index / channel_size) % nb_filters) as usize].clone()
+            }))
+        }
+        Operator::Dot { inputs, weights } => {
+            let in_shape = &out_shapes[inputs[0].0];
+            let kind = dot_kind(inputs.len() as u64, in_shape, weights);
+            let classes: Vec<_> = inputs.iter().map(|i| in_element_variances(*i)).collect();
+            if classes.iter().all(ElementVariances::is_uniform) {
+                return uniform();
+            }
+            let sq_weights: Vec<f64> = weights
+                .values
+                .iter()
+                .map(|&weight| square(weight as f64))
+                .collect();
+            // The variance of a weighted sum of (input, flat index) elements
+            let weighted_sum = |terms: &mut dyn Iterator<Item = (usize, usize, f64)>| {
+                terms
+                    .map(|(input, index, sq_weight)| {
+                        classes[input].variance_of(index).clone() * sq_weight
+                    })
+                    .reduce(|mut sum, variance| {
+                        sum += variance;
+                        sum
+                    })
+                    .unwrap()
+            };
+            match kind {
+                // A single output element, the weighted sum of all the input elements
+                DotKind::Simple | DotKind::Tensor => {
+                    let mut terms = sq_weights.iter().enumerate().map(|(j, &sq_weight)| {
+                        if inputs.len() > 1 {
+                            (j, 0, sq_weight)
+                        } else {
+                            (0, j, sq_weight)
+                        }
+                    });
+                    ElementVariances::uniform(out_size, weighted_sum(&mut terms))
+                }
+                // Each row of the input matrix by the weights
+                DotKind::Broadcast { .. }
+                    if inputs.len() == 1 && in_shape.erase_first_dim() == weights.shape =>
+                {
+                    let row_size = sq_weights.len();
+                    ElementVariances::from_variances((0..out_size as usize).map(|row| {
+                        let mut terms = sq_weights
+                            .iter()
+                            .enumerate()
+                            .map(|(j, &sq_weight)| (0, row * row_size + j, sq_weight));
+                        weighted_sum(&mut terms)
+                    }))
+                }
+                // Elementwise weighted sum of the inputs, or the input times a number
+                DotKind::Broadcast { .. } => {
+                    ElementVariances::from_variances((0..out_size as usize).map(|index| {
+                        let mut terms = sq_weights
+                            .iter()
+                            .enumerate()
+                            .map(|(j, &sq_weight)| (j.min(inputs.len() - 1), index, sq_weight));
+                        weighted_sum(&mut terms)
+                    }))
+                }
+                DotKind::CompatibleTensor | DotKind::Unsupported => uniform(),
+            }
+        }
+        Operator::Input { .. } | Operator::Lut { .. } | Operator::LevelledOp { .. } => uniform(),
+        Operator::Round { .. }
+        | Operator::Bivariate { .. }
+        | Operator::Mul { .. }
+        | Operator::MaxPool { .. } => {
+            unreachable!("Round, Bivariate, Mul and MaxPool should have been expanded")
+        }
+    }
+}
@@ -1,2 +1,3 @@
+pub mod element_variances;
 pub mod multi_parameters;
 pub mod solo_key;
@@ -1,9 +1,10 @@
 use crate::dag::operator::{
     dot_kind, DotKind, LevelledComplexity, Operator, OperatorIndex, Precision, Shape,
 };
-use crate::dag::rewrite::round::expand_round_and_index_map;
+use crate::dag::rewrite::expand::{expand_operators_and_index_map, DEFAULT_ROUND_GROUP_BITS};
 use crate::dag::unparametrized;
-use crate::optimization::config::NoiseBoundConfig;
+use crate::optimization::config::{NoiseBoundConfig, NoiseTracking};
+use crate::optimization::dag::element_variances::{self, ElementVariances};
 use crate::optimization::dag::multi_parameters::partition_cut::PartitionCut;
 use crate::optimization::dag::multi_parameters::partitionning::partitionning_with_preferred;
 use crate::optimization::dag::multi_parameters::partitions::{
@@ -20,6 +21,7 @@ use super::operations_value::OperationsValue;
 use super::variance_constraint::VarianceConstraint;
 
 use crate::utils::square;
+use std::collections::{HashMap, HashSet};
 
 // private short convention
 use DotKind as DK;
@@ -42,6 +44,8 @@ pub struct AnalyzedDag {
     pub operations_count_per_instrs: Vec<OperationsCount>,
     pub operations_count: OperationsCount,
     pub instruction_rewrite_index: Vec<Vec<OperatorIndex>>,
+    // Bits truncated per pbs by the expanded rounds
+    pub round_group_bits: Precision,
     pub p_cut: PartitionCut,
 }
 
@@ -52,7 +56,7 @@ pub fn analyze(
     default_partition: PartitionIndex,
     composable: bool,
 ) -> Result<AnalyzedDag> {
-    let (dag, instruction_rewrite_index) = expand_round_and_index_map(dag);
+    let (dag, instruction_rewrite_index) = expand_operators_and_index_map(dag);
     let levelled_complexity = LevelledComplexity::ZERO;
     // The precision cut is chosen to work well with rounded pbs
     // Note: this is temporary
@@ -64,31 +68,30 @@ pub fn analyze(
     let partitions = partitionning_with_preferred(&dag, &p_cut, default_partition, composable);
     let instrs_partition = partitions.instrs_partition;
     let nb_partitions = partitions.nb_partitions;
-    let mut out_variances = self::out_variances(&dag, nb_partitions, &instrs_partition, &None);
-    if composable {
-        // Verify that there is no input symbol in the symbolic variances of the outputs.
-        check_composability(&dag, &out_variances, nb_partitions)?;
-        // Get the largest output out_variance
-        let largest_output_variances = dag
-            .get_output_operators_iter()
-            .map(|op| out_variances[op.id.0].clone())
-            .reduce(|lhs, rhs| {
-                lhs.into_iter()
-                    .zip(rhs)
-                    .map(|(lhsi, rhsi)| lhsi.max(&rhsi))
-                    .collect()
-            })
-            .expect("Failed to get the largest output variance.");
-        // Re-compute the out variances with input variances overriden by input variances
-        out_variances = self::out_variances(
-            &dag,
-            nb_partitions,
-            &instrs_partition,
-            &Some(largest_output_variances),
-        );
-    }
-    let variance_constraints =
-        collect_all_variance_constraints(&dag, noise_config, &instrs_partition, &out_variances);
+    let out_variances =
+        self::out_variances(&dag, nb_partitions, &instrs_partition, &HashMap::new());
+    let compositions = dag.get_compositions(composable);
+    let out_variances = propagate_compositions(
+        &dag,
+        nb_partitions,
+        &instrs_partition,
+        &compositions,
+        out_variances,
+    )?;
+    let out_element_variances = out_element_variances(
+        &dag,
+        nb_partitions,
+        &instrs_partition,
+        &out_variances,
+        noise_config.noise_tracking,
+    );
+    let variance_constraints = collect_all_variance_constraints(
+        &dag,
+        noise_config,
+        &instrs_partition,
+        &out_variances,
+        &out_element_variances,
+    );
     let undominated_variance_constraints =
         VarianceConstraint::remove_dominated(&variance_constraints);
     let operations_count_per_instrs =
@@ -97,6 +100,7 @@ pub fn analyze(
     Ok(AnalyzedDag {
         operators: dag.operators,
         instruction_rewrite_index,
+        round_group_bits: DEFAULT_ROUND_GROUP_BITS,
         nb_partitions,
         instrs_partition,
         out_variances,
@@ -109,38 +113,74 @@ pub fn analyze(
     })
 }
 
-fn check_composability(
+/// The variances of the composed inputs, the maximum of the outputs fed to them.
+fn composed_inputs_variances(
+    compositions: &[(OperatorIndex, OperatorIndex)],
+    out_variances: &[Vec<SymbolicVariance>],
+) -> HashMap<usize, Vec<SymbolicVariance>> {
+    let mut inputs_variances: HashMap<usize, Vec<SymbolicVariance>> = HashMap::new();
+    for (output, input) in compositions {
+        let output_variances = &out_variances[output.0];
+        _ = inputs_variances
+            .entry(input.0)
+            .and_modify(|variances| {
+                for (variance, output_variance) in variances.iter_mut().zip(output_variances) {
+                    *variance = variance.max(output_variance);
+                }
+            })
+            .or_insert_with(|| output_variances.clone());
+    }
+    inputs_variances
+}
+
+#[allow(clippy::float_cmp)]
+fn same_variances(lhs: &[SymbolicVariance], rhs: &[SymbolicVariance]) -> bool {
+    lhs.iter().zip(rhs).all(|(lhs, rhs)| {
+        lhs.coeffs
+            .iter()
+            .zip(rhs.coeffs.iter())
+            .all(|(l, r)| l == r || (l.is_nan() && r.is_nan()))
+    })
+}
+
+/// Propagates the outputs variances to the inputs they are fed to, until the variances are stable.
+/// Without cycles, this takes at most one propagation per composed input. A cycle is stable only if
+/// its noise is refreshed, otherwise the variances grow at each propagation.
+fn propagate_compositions(
     dag: &unparametrized::Dag,
-    symbolic_variances: &[Vec<SymbolicVariance>],
     nb_partitions: usize,
-) -> Result<()> {
-    // If the circuit only contains inputs, then it is composable.
-    let only_inputs = dag
-        .operators
+    instrs_partition: &[InstructionPartition],
+    compositions: &[(OperatorIndex, OperatorIndex)],
+    mut out_variances: Vec<Vec<SymbolicVariance>>,
+) -> Result<Vec<Vec<SymbolicVariance>>> {
+    let nb_composed_inputs = compositions
         .iter()
-        .all(|node| matches!(node, Operator::Input { .. }));
-    if only_inputs {
-        return Ok(());
-    }
-
-    // If the circuit outputs are free from input variances, it means that every outputs are
-    // refreshed, and the function can be composed.
-    let in_var_in_out_var = dag
-        .get_output_operators_iter()
-        .flat_map(|op| symbolic_variances[op.id.0].iter().map(move |v| (op.id, v)))
-        .find_map(|(output_index, sym_var)| {
-            (0..nb_partitions)
-                .find(|partition| {
-                    let coeff = sym_var.coeff_input(*partition);
-                    coeff != 0.0f64 && !coeff.is_nan()
-                })
-                .map(|_| (output_index, sym_var))
+        .map(|(_, input)| input.0)
+        .collect::<HashSet<_>>()
+        .len();
+    let mut inputs_variances: HashMap<usize, Vec<SymbolicVariance>> = HashMap::new();
+    let mut nb_propagations = 0;
+    loop {
+        let new_inputs_variances = composed_inputs_variances(compositions, &out_variances);
+        let unstable = compositions.iter().find(|(_, input)| {
+            !inputs_variances
+                .get(&input.0)
+                .is_some_and(|previous| same_variances(previous, &new_inputs_variances[&input.0]))
         });
-    match in_var_in_out_var {
-        Some((output_id, sym_var)) => Err(NotComposable(format!(
-            "Output {output_id} has variance {sym_var}."
-        ))),
-        None => Ok(()),
+        let Some((output, input)) = unstable else {
+            return Ok(out_variances);
+        };
+        if nb_propagations > nb_composed_inputs {
+            let partition = instrs_partition[input.0].instruction_partition;
+            return Err(NotComposable(format!(
+                "Output {output} fed to input {input} has a growing variance {}.",
+                out_variances[output.0][partition]
+            )));
+        }
+        inputs_variances = new_inputs_variances;
+        out_variances =
+            self::out_variances(dag, nb_partitions, instrs_partition, &inputs_variances);
+        nb_propagations += 1;
     }
 }
 
@@ -156,6 +196,19 @@ pub fn original_instrs_partition(
     result.reserve_exact(dag.instruction_rewrite_index.len());
     let unknown = keys_spec::Id::MAX;
     for new_instructions in &dag.instruction_rewrite_index {
+        if new_instructions.is_empty() {
+            // removed by a dag pass, nothing is computed
+            result.push(keys_spec::InstructionKeys {
+                input_key: unknown,
+                tlu_keyswitch_key: unknown,
+                tlu_bootstrap_key: unknown,
+                output_key: unknown,
+                extra_conversion_keys: vec![],
+                tlu_circuit_bootstrap_key: keys_spec::NO_KEY_ID,
+                tlu_private_functional_packing_key: keys_spec::NO_KEY_ID,
+            });
+            continue;
+        }
         let mut partition = None;
         let mut input_partition = None;
         let mut tlu_keyswitch_key = None;
@@ -223,11 +276,11 @@ fn out_variance(
     out_variances: &[Vec<SymbolicVariance>],
     nb_partitions: usize,
     instr_partition: &InstructionPartition,
-    input_override: Option<Vec<SymbolicVariance>>,
+    input_override: Option<&Vec<SymbolicVariance>>,
 ) -> Vec<SymbolicVariance> {
     // If an override is given for input and we have an input node, we override.
     if let (Some(overr), Op::Input { .. }) = (input_override, op) {
-        return overr;
+        return overr.clone();
     }
     // one variance per partition, in case the result is converted
     let partition = instr_partition.instruction_partition;
@@ -274,10 +327,26 @@ fn out_variance(
                 DK::Unsupported { .. } => panic!("Unsupported"),
             }
         }
-        Op::UnsafeCast { input, .. } => out_variance_of(input),
+        Op::UnsafeCast { input, .. }
+        | Op::Reshape { input, .. }
+        | Op::Transpose { input, .. }
+        | Op::Slice { input, .. }
+        | Op::Gather { input, .. }
+        | Op::Broadcast { input, .. } => out_variance_of(input),
+        Op::Concatenate { inputs, .. } => inputs
+            .iter()
+            .map(out_variance_of)
+            .reduce(max_variance)
+            .unwrap(),
+        Op::Conv2d { input, weights, .. } => {
+            out_variance_of(input) * weights.max_first_dim_square_norm2() as f64
+        }
         Op::Round { .. } => {
             unreachable!("Round should have been either expanded or integrated to a lut")
         }
+        Op::Bivariate { .. } | Op::Mul { .. } | Op::MaxPool { .. } => {
+            unreachable!("Bivariate, Mul and MaxPool should have been expanded")
+        }
     };
     // Injecting NAN in unused symbolic variance to detect bad use
     let unused = SymbolicVariance::nan(nb_partitions);
@@ -296,33 +365,84 @@ fn out_variances(
     dag: &unparametrized::Dag,
     nb_partitions: usize,
     instrs_partition: &[InstructionPartition],
-    input_override: &Option<Vec<SymbolicVariance>>,
+    inputs_override: &HashMap<usize, Vec<SymbolicVariance>>,
 ) -> Vec<Vec<SymbolicVariance>> {
     let nb_ops = dag.operators.len();
     let mut out_variances = Vec::with_capacity(nb_ops);
-    for (op, instr_partition) in dag.operators.iter().zip(instrs_partition) {
+    for (i, (op, instr_partition)) in dag.operators.iter().zip(instrs_partition).enumerate() {
         let vf = out_variance(
             op,
             &dag.out_shapes,
             &out_variances,
             nb_partitions,
             instr_partition,
-            input_override.clone(),
+            inputs_override.get(&i),
         );
         out_variances.push(vf);
     }
     out_variances
 }
 
-fn variance_constraint(
+/// The element classes of the operators outputs, one per partition like the variances.
+/// Only the instruction partition classes are propagated, the classes of the converted outputs are
+/// deduced from them.
+fn out_element_variances(
     dag: &unparametrized::Dag,
+    nb_partitions: usize,
+    instrs_partition: &[InstructionPartition],
+    out_variances: &[Vec<SymbolicVariance>],
+    noise_tracking: NoiseTracking,
+) -> Vec<Vec<ElementVariances<SymbolicVariance>>> {
+    let mut out_element_variances: Vec<Vec<ElementVariances<SymbolicVariance>>> =
+        Vec::with_capacity(dag.operators.len());
+    for (i, (op, instr_partition)) in dag.operators.iter().zip(instrs_partition).enumerate() {
+        let out_shape = &dag.out_shapes[i];
+        let partition = instr_partition.instruction_partition;
+        let classes = match noise_tracking {
+            NoiseTracking::PerTensor => ElementVariances::uniform(
+                out_shape.flat_size(),
+                out_variances[i][partition].clone(),
+            ),
+            NoiseTracking::PerElementClass => element_variances::out_element_variances(
+                op,
+                &dag.out_shapes,
+                out_shape,
+                &out_variances[i][partition],
+                |input| out_element_variances[input.0][partition].clone(),
+            ),
+        };
+        let op_classes = (0..nb_partitions)
+            .map(|dst_partition| {
+                if dst_partition == partition {
+                    classes.clone()
+                } else if classes.is_uniform()
+                    || !instr_partition
+                        .alternative_output_representation
+                        .contains(&dst_partition)
+                {
+                    ElementVariances::uniform(
+                        out_shape.flat_size(),
+                        out_variances[i][dst_partition].clone(),
+                    )
+                } else {
+                    classes.map(|variance| {
+                        variance.after_partition_keyswitch_to_big(partition, dst_partition)
+                    })
+                }
+            })
+            .collect();
+        out_element_variances.push(op_classes);
+    }
+    out_element_variances
+}
+
+fn variance_constraint(
     noise_config: &NoiseBoundConfig,
     partition: PartitionIndex,
-    op_i: usize,
+    nb_constraints: u64,
     precision: Precision,
     variance: SymbolicVariance,
 ) -> VarianceConstraint {
-    let nb_constraints = dag.out_shapes[op_i].flat_size();
     let safe_variance_bound = safe_noise_bound(precision, noise_config);
     VarianceConstraint {
         precision,
@@ -340,6 +460,7 @@ fn collect_all_variance_constraints(
     noise_config: &NoiseBoundConfig,
     instrs_partition: &[InstructionPartition],
     out_variances: &[Vec<SymbolicVariance>],
+    out_element_variances: &[Vec<ElementVariances<SymbolicVariance>>],
 ) -> Vec<VarianceConstraint> {
     let mut constraints = vec![];
     for op in dag.get_operators_iter() {
@@ -363,30 +484,30 @@ fn collect_all_variance_constraints(
                     dst_partition
                 }
             };
-            let variance = &out_variances[input.0][src_partition].clone();
-            let variance = variance
-                .after_partition_keyswitch_to_small(src_partition, dst_partition)
-                .after_modulus_switching(partition);
-            constraints.push(variance_constraint(
-                dag,
-                noise_config,
-                partition,
-                op.id.0,
-                precision,
-                variance,
-            ));
+            for (count, variance) in &out_element_variances[input.0][src_partition].classes {
+                let variance = variance
+                    .after_partition_keyswitch_to_small(src_partition, dst_partition)
+                    .after_modulus_switching(partition);
+                constraints.push(variance_constraint(
+                    noise_config,
+                    partition,
+                    *count,
+                    precision,
+                    variance,
+                ));
+            }
         }
         if op.is_output() {
             let precision = dag.out_precisions[op.id.0];
-            let variance = out_variances[op.id.0][partition].clone();
-            constraints.push(variance_constraint(
-                dag,
-                noise_config,
-                partition,
-                op.id.0,
-                precision,
-                variance,
-            ));
+            for (count, variance) in &out_element_variances[op.id.0][partition].classes {
+                constraints.push(variance_constraint(
+                    noise_config,
+                    partition,
+                    *count,
+                    precision,
+                    variance.clone(),
+                ));
+            }
         }
     }
     constraints
@@ -439,7 +560,7 @@ fn sum_operations_count(all_counts: &[OperationsCount]) -> OperationsCount {
 #[cfg(test)]
 pub mod tests {
     use super::*;
-    use crate::dag::operator::{FunctionTable, Shape};
+    use crate::dag::operator::{FunctionTable, Shape, Weights};
     use crate::dag::unparametrized;
     use crate::optimization::dag::multi_parameters::partitionning::tests::{
         show_partitionning, HIGH_PRECISION_PARTITION, LOW_PRECISION_PARTITION,
@@ -552,6 +673,59 @@ pub mod tests {
         assert!(actual_constraint_strings == expected_constraint_strings);
     }
 
+    /// Two circuits of a 4 bits input, "levelled" forwards its input noise, "refresh" bootstraps
+    /// it.
+    pub struct CircuitsToCompose {
+        pub dag: unparametrized::Dag,
+        pub levelled_input: OperatorIndex,
+        pub levelled_output: OperatorIndex,
+        pub refresh_input: OperatorIndex,
+        pub refresh_output: OperatorIndex,
+    }
+
+    pub fn circuits_to_compose() -> CircuitsToCompose {
+        let mut dag = unparametrized::Dag::new();
+        let levelled_input = dag.builder("levelled").add_input(4, Shape::number());
+        let levelled_output = dag.builder("levelled").add_dot([levelled_input], [4]);
+        let refresh_input = dag.builder("refresh").add_input(4, Shape::number());
+        let refresh_output =
+            dag.builder("refresh")
+                .add_lut(refresh_input, FunctionTable::UNKWOWN, 4);
+        CircuitsToCompose {
+            dag,
+            levelled_input,
+            levelled_output,
+            refresh_input,
+            refresh_output,
+        }
+    }
+
+    #[test]
+    fn test_composition_between_circuits() {
+        let CircuitsToCompose {
+            mut dag,
+            levelled_input,
+            levelled_output,
+            refresh_input,
+            refresh_output,
+        } = circuits_to_compose();
+        dag.add_composition(levelled_output, refresh_input);
+        dag.add_composition(refresh_output, levelled_input);
+        let dag = super::analyze(&dag, &CONFIG, &None, LOW_PRECISION_PARTITION, false).unwrap();
+        let actual_constraint_strings = dag
+            .variance_constraints
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<String>>();
+        // the fed inputs have the noise of the outputs instead of a fresh noise
+        let expected_constraint_strings = vec![
+            "16σ²Br[0] < (2²)**-8 (4bits partition:0 count:1, dom=20)",
+            "16σ²Br[0] + 1σ²K[0] + 1σ²M[0] < (2²)**-8 (4bits partition:0 count:1, dom=20)",
+            "1σ²Br[0] < (2²)**-8 (4bits partition:0 count:1, dom=16)",
+        ];
+        assert!(actual_constraint_strings == expected_constraint_strings);
+    }
+
     #[test]
     fn test_composition_2_partitions() {
         let mut dag = unparametrized::Dag::new();
@@ -969,6 +1143,46 @@ pub mod tests {
         }
     }
 
+    #[test]
+    fn test_per_element_class_constraints() {
+        let mut dag = unparametrized::Dag::new();
+        let image_shape = Shape {
+            dimensions_size: vec![1, 2, 4, 4],
+        };
+        let input = dag.add_input(3, &image_shape);
+        let lut1 = dag.add_lut(input, FunctionTable::UNKWOWN, 3);
+        let weights = Weights {
+            shape: Shape {
+                dimensions_size: vec![2, 2, 1, 1],
+            },
+            values: vec![1, 0, 3, 0],
+        };
+        let conv = dag.add_conv2d(lut1, weights, [1, 1], [0; 4], [1, 1], 1);
+        _ = dag.add_lut(conv, FunctionTable::UNKWOWN, 3);
+        let counted_br_coeffs = |noise_tracking| {
+            let config = NoiseBoundConfig {
+                noise_tracking,
+                ..CONFIG
+            };
+            let p_cut = PartitionCut::for_each_precision(&dag);
+            let analyzed = super::analyze(&dag, &config, &Some(p_cut), 0, false).unwrap();
+            analyzed
+                .variance_constraints
+                .iter()
+                .map(|c| (c.nb_constraints, c.variance.coeff_pbs(0)))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            counted_br_coeffs(NoiseTracking::PerTensor),
+            vec![(32, 0.0), (32, 9.0), (32, 1.0)]
+        );
+        // the constraint on the second lut is split per output channel of the convolution
+        assert_eq!(
+            counted_br_coeffs(NoiseTracking::PerElementClass),
+            vec![(32, 0.0), (16, 1.0), (16, 9.0), (32, 1.0)]
+        );
+    }
+
     #[test]
     fn test_rounded_v3_classic_first_layer_second_layer_complexity() {
         let acc_precision = 7;
@@ -1,8 +1,10 @@
 // TODO: move to cache with pareto check
 
-use concrete_cpu_noise_model::gaussian_noise::conversion::modular_variance_to_variance;
+use concrete_cpu_noise_model::gaussian_noise::noise::fast_keyswitch::{
+    fast_keyswitch_input_polynomial_count, fft_noise_variance_fast_keyswitch,
+};
+use concrete_cpu_noise_model::gaussian_noise::worst_case::{NoiseBound, NoiseMode};
 
-// TODO: move to concrete-cpu
 use crate::optimization::decomposition::keyswitch::KsComplexityNoise;
 use crate::parameters::{GlweParameters, KsDecompositionParameters};
 
@@ -20,33 +22,6 @@ pub struct FksComplexityNoise {
     pub dst_glwe_param: GlweParameters,
 }
 
-// Copy & paste from concrete-cpu
-const FFT_SCALING_WEIGHT: f64 = -2.577_224_94;
-fn fft_noise_variance_external_product_glwe(
-    glwe_dimension: u64,
-    polynomial_size: u64,
-    log2_base: u64,
-    level: u64,
-    ciphertext_modulus_log: u32,
-    fft_precision: u32,
-) -> f64 {
-    // https://github.com/zama-ai/concrete-optimizer/blob/prototype/python/optimizer/noise_formulas/bootstrap.py#L25
-    let b = 2_f64.powi(log2_base as i32);
-    let l = level as f64;
-    let big_n = polynomial_size as f64;
-    let k = glwe_dimension;
-    assert!(k > 0, "k = {k}");
-
-    #[allow(clippy::cast_possible_wrap)]
-    let lost_bits = ciphertext_modulus_log as i32 - fft_precision as i32;
-
-    let scale_margin = 2_f64.powi(2 * lost_bits);
-
-    let res =
-        f64::exp2(FFT_SCALING_WEIGHT) * scale_margin * l * b * b * big_n.powi(2) * (k as f64 + 1.);
-    modular_variance_to_variance(res, ciphertext_modulus_log)
-}
-
 #[allow(non_snake_case)]
 fn upper_k0(input_glwe: &GlweParameters, output_glwe: &GlweParameters) -> u64 {
     let k1 = input_glwe.glwe_dimension;
@@ -54,11 +29,7 @@ fn upper_k0(input_glwe: &GlweParameters, output_glwe: &GlweParameters) -> u64 {
     let k0 = output_glwe.glwe_dimension;
     let N0 = output_glwe.polynomial_size();
     assert!(k1 * N1 >= k0 * N0);
-    // candidate * N0 >= k1 * N1
-    let f_upper_k0 = (k1 * N1) as f64 / N0 as f64;
-    #[allow(clippy::cast_sign_loss)]
-    let upper_k0 = f_upper_k0.ceil() as u64;
-    upper_k0
+    fast_keyswitch_input_polynomial_count(k1, N1, N0)
 }
 
 #[allow(non_snake_case)]
@@ -74,23 +45,25 @@ pub fn complexity(input_glwe: &GlweParameters, output_glwe: &GlweParameters, lev
     (add_count + mul_count) as f64
 }
 
-#[allow(non_snake_case)]
+/// The keyswitch noise of `ks` from the sample extracted input key, plus the fft error.
+///
+/// `ks` holds maximal errors in worst-case `noise_mode`, the fft error is then bounded by its tail.
 pub fn noise(
     ks: &KsComplexityNoise,
     input_glwe: &GlweParameters,
     output_glwe: &GlweParameters,
     ciphertext_modulus_log: u32,
     fft_precision: u32,
+    noise_mode: NoiseMode,
 ) -> f64 {
-    let N0 = output_glwe.polynomial_size();
-    let upper_k0 = upper_k0(input_glwe, output_glwe);
-    ks.noise(input_glwe.sample_extract_lwe_dimension())
-        + fft_noise_variance_external_product_glwe(
-            upper_k0,
-            N0,
-            ks.decomp.log2_base,
-            ks.decomp.level,
-            ciphertext_modulus_log,
-            fft_precision,
-        )
+    let fft = NoiseBound::gaussian(fft_noise_variance_fast_keyswitch(
+        input_glwe.glwe_dimension,
+        input_glwe.polynomial_size(),
+        output_glwe.polynomial_size(),
+        ks.decomp.log2_base,
+        ks.decomp.level,
+        ciphertext_modulus_log,
+        fft_precision,
+    ));
+    ks.noise(input_glwe.sample_extract_lwe_dimension()) + fft.value(noise_mode)
 }
@@ -1,3 +1,6 @@
+use concrete_cpu_noise_model::gaussian_noise::worst_case::NoiseMode;
+
+use crate::noise_estimator::error;
 use crate::noise_estimator::p_error::{combine_errors, repeat_p_error};
 use crate::optimization::dag::multi_parameters::variance_constraint::VarianceConstraint;
 use crate::optimization::dag::solo_key::analyze::p_error_from_relative_variance;
@@ -6,6 +9,12 @@ use crate::utils::f64::f64_dot;
 use super::operations_value::OperationsValue;
 use super::partitions::PartitionIndex;
 
+/// In worst-case mode, the operations "variances" are their maximal errors and each constraint
+/// bounds their sum by the largest error the padding absorbs.
+///
+/// The constraints coefficients are squared weights, they also bound the absolute weights the
+/// maximal errors are multiplied by as long as the weights are integers. The optimizer rejects
+/// worst-case dags with levelled ops whose squared norm is not a non-zero integer.
 #[derive(Clone)]
 pub struct Feasible {
     // TODO: move kappa here
@@ -13,6 +22,7 @@ pub struct Feasible {
     pub undominated_constraints: Vec<VarianceConstraint>,
     pub kappa: f64, // to convert variance to local probabilities
     pub global_p_error: Option<f64>,
+    pub noise_mode: NoiseMode,
 }
 
 impl Feasible {
@@ -23,6 +33,35 @@ impl Feasible {
             constraints: constraints.into(),
             undominated_constraints,
             global_p_error,
+            noise_mode: NoiseMode::AverageCase,
+        }
+    }
+
+    /// Constraints on the maximal errors, met solutions never fail to decrypt.
+    pub fn worst_case(constraints: &[VarianceConstraint], global_p_error: Option<f64>) -> Self {
+        let constraints: Vec<_> = constraints
+            .iter()
+            .map(|constraint| VarianceConstraint {
+                safe_variance_bound: error::max_error_bound_2padbits(u64::from(
+                    constraint.precision,
+                )),
+                ..constraint.clone()
+            })
+            .collect();
+        // no variance to convert to a probability
+        let kappa = f64::NAN;
+        Self {
+            noise_mode: NoiseMode::WorstCase,
+            ..Self::of(&constraints, kappa, global_p_error)
+        }
+    }
+
+    fn p_error_from_relative_variance(&self, relative_variance: f64) -> f64 {
+        match self.noise_mode {
+            NoiseMode::AverageCase => p_error_from_relative_variance(relative_variance, self.kappa),
+            NoiseMode::WorstCase if relative_variance <= 1.0 => 0.0,
+            // not a probability, only ranks unfeasible solutions by how far they are from the bound
+            NoiseMode::WorstCase => 1.0 - 1.0 / relative_variance,
         }
     }
 
@@ -152,7 +191,7 @@ impl Feasible {
 
     pub fn p_error(&self, operations_variance: &OperationsValue) -> f64 {
         let (_, relative_variance, _) = self.worst_constraint(operations_variance);
-        p_error_from_relative_variance(relative_variance, self.kappa)
+        self.p_error_from_relative_variance(relative_variance)
     }
 
     fn global_p_error_with_cut(
@@ -164,7 +203,7 @@ impl Feasible {
         for constraint in &self.constraints {
             let variance = f64_dot(operations_variance, &constraint.variance.coeffs);
             let relative_variance = variance / constraint.safe_variance_bound;
-            let p_error = p_error_from_relative_variance(relative_variance, self.kappa);
+            let p_error = self.p_error_from_relative_variance(relative_variance);
             global_p_error = combine_errors(
                 global_p_error,
                 repeat_p_error(p_error, constraint.nb_constraints),
@@ -199,7 +238,10 @@ impl Feasible {
             })
             .cloned()
             .collect();
-        Self::of(&partition_constraints, self.kappa, self.global_p_error)
+        Self {
+            noise_mode: self.noise_mode,
+            ..Self::of(&partition_constraints, self.kappa, self.global_p_error)
+        }
     }
 
     pub fn compressed(self) -> Self {
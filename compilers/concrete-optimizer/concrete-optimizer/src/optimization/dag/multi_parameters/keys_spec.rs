@@ -1,5 +1,12 @@
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use concrete_cpu_noise_model::gaussian_noise::noise::modulus_switching::ModulusSwitchingKind;
+
+use crate::dag::operator::{Operator, Precision};
+use crate::dag::rewrite::expand::DEFAULT_ROUND_GROUP_BITS;
+use crate::dag::rewrite::refresh::Refresh;
+use crate::dag::unparametrized::Dag;
 use crate::optimization::{atomic_pattern, wop_atomic_pattern};
 use crate::parameters::{BrDecompositionParameters, KsDecompositionParameters};
 
@@ -14,8 +21,9 @@ pub type ConversionKeySwitchKeyId = Id;
 pub type CircuitBoostrapKeyId = Id;
 pub type PrivateFunctionalPackingBoostrapKeyId = Id;
 pub const NO_KEY_ID: Id = Id::MAX;
+pub const NO_INSTRUCTION: u64 = u64::MAX;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SecretLweKey {
     /* Big and small secret keys */
     pub identifier: SecretLweKeyId,
@@ -24,17 +32,19 @@ pub struct SecretLweKey {
     pub description: String,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct BootstrapKey {
     /* Public TLU bootstrap keys */
     pub identifier: BootstrapKeyId,
     pub input_key: SecretLweKey,
     pub output_key: SecretLweKey,
     pub br_decomposition_parameter: BrDecompositionParameters,
+    /// Body correction to apply before the blind rotate
+    pub modulus_switching: ModulusSwitchingKind,
     pub description: String,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct KeySwitchKey {
     /* Public TLU keyswitch keys */
     pub identifier: KeySwitchKeyId,
@@ -44,7 +54,7 @@ pub struct KeySwitchKey {
     pub description: String,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ConversionKeySwitchKey {
     /* Public conversion to make compatible ciphertext with incompatible keys.
     It's currently only between two big secret keys. */
@@ -56,7 +66,7 @@ pub struct ConversionKeySwitchKey {
     pub description: String,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct CircuitBoostrapKey {
     pub identifier: ConversionKeySwitchKeyId,
     pub representation_key: SecretLweKey,
@@ -64,7 +74,7 @@ pub struct CircuitBoostrapKey {
     pub description: String,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct PrivateFunctionalPackingBoostrapKey {
     pub identifier: PrivateFunctionalPackingBoostrapKeyId,
     pub representation_key: SecretLweKey,
@@ -72,7 +82,7 @@ pub struct PrivateFunctionalPackingBoostrapKey {
     pub description: String,
 }
 
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct CircuitKeys {
     /* All keys used in a circuit, sorted by Id for each key type */
     pub secret_keys: Vec<SecretLweKey>,
@@ -83,7 +93,7 @@ pub struct CircuitKeys {
     pub private_functional_packing_keys: Vec<PrivateFunctionalPackingBoostrapKey>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct InstructionKeys {
     /* Describe for each instructions what is the key of inputs/outputs.
        For tlus, it gives the internal keyswitch/pbs keys.
@@ -98,7 +108,7 @@ pub struct InstructionKeys {
     pub extra_conversion_keys: Vec<ConversionKeySwitchKeyId>,
 }
 
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CircuitSolution {
     pub circuit_keys: CircuitKeys,
     /* instructions keys ordered by instructions index of the original dag original (i.e. in same order):
@@ -108,6 +118,16 @@ pub struct CircuitSolution {
        sol.instructions_keys[instr_0.index] gives instr_0_keys
     */
     pub instructions_keys: Vec<InstructionKeys>,
+    /* bits truncated per pbs by each round instruction, 0 for the other instructions */
+    pub round_group_bits: Vec<Precision>,
+    /* the dag rewritten by the dag passes and the refreshes in the text format, empty without
+    rewrite */
+    pub simplified_dag: String,
+    /* for each instruction, the rewritten dag instruction computing it or NO_INSTRUCTION,
+    empty without rewrite */
+    pub simplified_instructions: Vec<u64>,
+    /* identity luts inserted to make the circuit composable, in the rewritten dag */
+    pub refreshes: Vec<Refresh>,
     /* complexity of the full circuit */
     pub complexity: f64,
     /* highest p_error attained in a TLU */
@@ -162,6 +182,7 @@ impl CircuitSolution {
                 level: sol.br_decomposition_level_count,
                 log2_base: sol.br_decomposition_base_log,
             },
+            modulus_switching: ModulusSwitchingKind::Standard,
             description: "tlu bootstrap".into(),
         };
         let circuit_bootstrap_key = CircuitBoostrapKey {
@@ -210,6 +231,10 @@ impl CircuitSolution {
         Self {
             circuit_keys,
             instructions_keys,
+            round_group_bits: vec![0; nb_instr],
+            simplified_dag: String::new(),
+            simplified_instructions: vec![],
+            refreshes: vec![],
             complexity: sol.complexity,
             p_error: sol.p_error,
             global_p_error: sol.p_error,
@@ -219,7 +244,8 @@ impl CircuitSolution {
         }
     }
 
-    pub fn from_native_solution(sol: atomic_pattern::Solution, nb_instr: usize) -> Self {
+    pub fn from_native_solution(sol: atomic_pattern::Solution, dag: &Dag) -> Self {
+        let round_group_bits = round_group_bits(dag, DEFAULT_ROUND_GROUP_BITS);
         let is_feasible = sol.p_error < 1.0;
         let error_msg = if is_feasible {
             ""
@@ -253,13 +279,14 @@ impl CircuitSolution {
             };
             return Self {
                 circuit_keys,
-                instructions_keys: vec![instruction_keys; nb_instr],
-                crt_decomposition: vec![],
+                instructions_keys: vec![instruction_keys; dag.len()],
+                round_group_bits,
                 complexity: sol.complexity,
                 p_error: sol.p_error,
                 global_p_error: sol.global_p_error,
                 is_feasible,
                 error_msg,
+                ..Self::default()
             };
         }
         let small_key = SecretLweKey {
@@ -286,6 +313,7 @@ impl CircuitSolution {
                 level: sol.br_decomposition_level_count,
                 log2_base: sol.br_decomposition_base_log,
             },
+            modulus_switching: ModulusSwitchingKind::Standard,
             description: "tlu bootstrap".into(),
         };
         let instruction_keys = InstructionKeys {
@@ -297,7 +325,7 @@ impl CircuitSolution {
             output_key: big_key.identifier,
             extra_conversion_keys: vec![],
         };
-        let instructions_keys = vec![instruction_keys; nb_instr];
+        let instructions_keys = vec![instruction_keys; dag.len()];
         let circuit_keys = CircuitKeys {
             secret_keys: [big_key, small_key].into(),
             keyswitch_keys: [keyswitch_key].into(),
@@ -309,16 +337,32 @@ impl CircuitSolution {
         Self {
             circuit_keys,
             instructions_keys,
-            crt_decomposition: vec![],
+            round_group_bits,
             complexity: sol.complexity,
             p_error: sol.p_error,
             global_p_error: sol.global_p_error,
             is_feasible,
             error_msg,
+            ..Self::default()
         }
     }
 }
 
+/// Bits truncated per pbs by each instruction of `dag` once its rounds are expanded by groups of
+/// `group_bits`.
+pub fn round_group_bits(dag: &Dag, group_bits: Precision) -> Vec<Precision> {
+    dag.operators
+        .iter()
+        .map(|op| {
+            if matches!(op, Operator::Round { .. }) {
+                group_bits
+            } else {
+                0
+            }
+        })
+        .collect()
+}
+
 pub struct ExpandedCircuitKeys {
     pub big_secret_keys: Vec<SecretLweKey>,
     pub small_secret_keys: Vec<SecretLweKey>,
@@ -379,6 +423,7 @@ impl ExpandedCircuitKeys {
                     input_key: small_secret_keys[i].clone(),
                     output_key: big_secret_keys[i].clone(),
                     br_decomposition_parameter,
+                    modulus_switching: params.macro_params[i].unwrap().modulus_switching,
                     description: format!("pbs[{i}]"),
                 }
             })
@@ -692,11 +737,19 @@ impl InstructionKeys {
         } else {
             sharing.bootstrap_keys[&self.tlu_bootstrap_key].identifier
         };
+        // instructions removed by a dag pass have no keys
+        let secret_key = |key: SecretLweKeyId| {
+            if key == NO_KEY_ID {
+                NO_KEY_ID
+            } else {
+                sharing.secret_keys[&key].identifier
+            }
+        };
         Self {
-            input_key: sharing.secret_keys[&self.input_key].identifier,
+            input_key: secret_key(self.input_key),
             tlu_bootstrap_key,
             tlu_keyswitch_key,
-            output_key: sharing.secret_keys[&self.output_key].identifier,
+            output_key: secret_key(self.output_key),
             extra_conversion_keys: self
                 .extra_conversion_keys
                 .iter()
@@ -1,9 +1,26 @@
 // OPT: cache for fks and verified pareto
-use concrete_cpu_noise_model::gaussian_noise::noise::modulus_switching::estimate_modulus_switching_noise_with_binary_key;
-
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use concrete_cpu_noise_model::gaussian_noise::noise::modulus_switching::{
+    estimate_modulus_switching_noise_with_kind, max_error_modulus_switching_with_kind,
+    ModulusSwitchingKind,
+};
+use concrete_cpu_noise_model::gaussian_noise::worst_case::{
+    key_coefficient_bound, NoiseBound, NoiseMode,
+};
+use concrete_security_curves::gaussian::curves::SecurityCurves;
+
+use crate::computing_cost::operators::modulus_switching::correction_complexity;
+use crate::dag::operator::{Operator, OperatorIndex, Precision};
+use crate::dag::rewrite::expand::{expand_operators_with_round_groups, ROUND_GROUPS_BITS};
+use crate::dag::rewrite::passes::run_passes;
+use crate::dag::rewrite::refresh::{insert_refreshes, Refresh};
+use crate::dag::rewrite::regen::compose_instructions_maps;
 use crate::dag::unparametrized::Dag;
 use crate::noise_estimator::error;
 use crate::optimization;
+use crate::optimization::atomic_pattern::optimize_one;
 use crate::optimization::config::{Config, NoiseBoundConfig, SearchSpace};
 use crate::optimization::dag::multi_parameters::analyze::{analyze, AnalyzedDag};
 use crate::optimization::dag::multi_parameters::fast_keyswitch;
@@ -21,7 +38,9 @@ use crate::optimization::dag::multi_parameters::feasible::Feasible;
 use crate::optimization::dag::multi_parameters::partition_cut::PartitionCut;
 use crate::optimization::dag::multi_parameters::partitions::PartitionIndex;
 use crate::optimization::dag::multi_parameters::{analyze, keys_spec};
-use crate::optimization::Err::{NoParametersFound, NotComposable};
+use crate::optimization::Err::{
+    InvalidDag, NoParametersFound, NoiseModeMismatch, NotComposable, UnboundedWorstCaseNoise,
+};
 
 use super::keys_spec::InstructionKeys;
 
@@ -49,6 +68,15 @@ struct PartialMicroParameters {
 pub struct MacroParameters {
     pub glwe_params: GlweParameters,
     pub internal_dim: u64,
+    pub modulus_switching: ModulusSwitchingKind,
+}
+
+impl MacroParameters {
+    /// Cost of the blind rotate of `pbs` with the modulus switching of the partition.
+    fn complexity_br(self, pbs: &CmuxComplexityNoise) -> f64 {
+        pbs.complexity_br(self.internal_dim)
+            + correction_complexity(self.modulus_switching, self.internal_dim)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -201,6 +229,7 @@ fn optimize_1_fks_and_all_compatible_ks(
                 &output_glwe,
                 ciphertext_modulus_log,
                 fft_precision,
+                feasible.noise_mode,
             );
             let complexity =
                 fast_keyswitch::complexity(&input_glwe, &output_glwe, ks_quantity.decomp.level);
@@ -355,7 +384,7 @@ fn optimize_1_cmux_and_dst_exclusive_fks_subset_and_all_ks(
         // increasing complexity, decreasing variance
 
         // Lower bounds cuts
-        let pbs_cost = cmux_quantity.complexity_br(internal_dim);
+        let pbs_cost = macro_parameters[partition].complexity_br(&cmux_quantity);
         *operations.cost.pbs(partition) = pbs_cost;
         let lower_cost = complexity.complexity(&operations.cost);
         if lower_cost > best_sol_complexity {
@@ -454,6 +483,7 @@ fn apply_fks_variance_and_cost_or_lower_bound(
     operations: &mut OperationsCV,
     ciphertext_modulus_log: u32,
     fft_precision: u32,
+    noise_mode: NoiseMode,
 ) {
     for (src, dst) in cross_partition(nb_partitions) {
         if !used_conversion_keyswitch[src][dst] {
@@ -498,6 +528,7 @@ fn apply_fks_variance_and_cost_or_lower_bound(
                     output_glwe,
                     ciphertext_modulus_log,
                     fft_precision,
+                    noise_mode,
                 );
                 variance_min = variance_min.min(variance);
             }
@@ -509,9 +540,54 @@ fn apply_fks_variance_and_cost_or_lower_bound(
     }
 }
 
+/// Variance and maximal error of the fresh ciphertexts encrypted with `glwe_params`.
+fn input_noise(
+    glwe_params: GlweParameters,
+    ciphertext_modulus_log: u32,
+    security_curves: &SecurityCurves,
+    security_level: u64,
+) -> NoiseBound {
+    NoiseBound::gaussian(glwe_params.minimal_variance(
+        security_curves,
+        ciphertext_modulus_log,
+        security_level,
+    ))
+}
+
+/// Variance and maximal error of the modulus switching before the blind rotate.
+fn modulus_switching_noise(
+    glwe_log2_poly_size: u64,
+    internal_lwe_dimension: u64,
+    modulus_switching: ModulusSwitchingKind,
+    ciphertext_modulus_log: u32,
+    security_curves: &SecurityCurves,
+) -> NoiseBound {
+    let key_distribution = security_curves.key_distribution();
+    let key = key_distribution.moments(internal_lwe_dimension);
+    NoiseBound {
+        variance: estimate_modulus_switching_noise_with_kind(
+            internal_lwe_dimension,
+            glwe_log2_poly_size,
+            ciphertext_modulus_log,
+            key,
+            modulus_switching,
+        ),
+        max_error: max_error_modulus_switching_with_kind(
+            internal_lwe_dimension,
+            glwe_log2_poly_size,
+            key_coefficient_bound(key_distribution),
+            key.mean,
+            modulus_switching,
+        ),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 fn apply_partitions_input_and_modulus_variance_and_cost(
     ciphertext_modulus_log: u32,
+    security_curves: &SecurityCurves,
     security_level: u64,
+    noise_mode: NoiseMode,
     nb_partitions: usize,
     macro_parameters: &[MacroParameters],
     partition: PartitionIndex,
@@ -524,15 +600,23 @@ fn apply_partitions_input_and_modulus_variance_and_cost(
             if macro_parameters[i] == macro_parameters[partition] {
                 (input_variance, variance_modulus_switching)
             } else {
-                let input_variance = macro_parameters[i]
-                    .glwe_params
-                    .minimal_variance(ciphertext_modulus_log, security_level);
-                let variance_modulus_switching = estimate_modulus_switching_noise_with_binary_key(
-                    macro_parameters[i].internal_dim,
+                let input_variance = input_noise(
+                    macro_parameters[i].glwe_params,
+                    ciphertext_modulus_log,
+                    security_curves,
+                    security_level,
+                );
+                let variance_modulus_switching = modulus_switching_noise(
                     macro_parameters[i].glwe_params.log2_polynomial_size,
+                    macro_parameters[i].internal_dim,
+                    macro_parameters[i].modulus_switching,
                     ciphertext_modulus_log,
+                    security_curves,
                 );
-                (input_variance, variance_modulus_switching)
+                (
+                    input_variance.value(noise_mode),
+                    variance_modulus_switching.value(noise_mode),
+                )
             };
         *operations.variance.input(i) = input_variance;
         *operations.variance.modulus_switching(i) = variance_modulus_switching;
@@ -552,7 +636,7 @@ fn apply_pbs_variance_and_cost_or_lower_bounds(
         if let Some(pbs) = pbs {
             let internal_dim = macro_parameters[i].internal_dim;
             *operations.variance.pbs(i) = pbs.noise_br(internal_dim);
-            *operations.cost.pbs(i) = pbs.complexity_br(internal_dim);
+            *operations.cost.pbs(i) = macro_parameters[i].complexity_br(pbs);
         } else {
             // OPT: Most values could be shared on first optimize_macro
             let in_internal_dim = macro_parameters[i].internal_dim;
@@ -617,6 +701,7 @@ pub const REAL_FAST_KS: bool = false;
 #[allow(clippy::cognitive_complexity)]
 #[allow(clippy::too_many_lines)]
 fn optimize_macro(
+    security_curves: &SecurityCurves,
     security_level: u64,
     ciphertext_modulus_log: u32,
     fft_precision: u32,
@@ -634,13 +719,18 @@ fn optimize_macro(
     let nb_partitions = init_parameters.macro_params.len();
     assert!(partition < nb_partitions);
 
-    let variance_modulus_switching_of = |glwe_log2_poly_size, internal_lwe_dimensions| {
-        estimate_modulus_switching_noise_with_binary_key(
-            internal_lwe_dimensions,
-            glwe_log2_poly_size,
-            ciphertext_modulus_log,
-        )
-    };
+    let noise_mode = feasible.noise_mode;
+    let variance_modulus_switching_of =
+        |glwe_log2_poly_size, internal_lwe_dimensions, modulus_switching| {
+            modulus_switching_noise(
+                glwe_log2_poly_size,
+                internal_lwe_dimensions,
+                modulus_switching,
+                ciphertext_modulus_log,
+                security_curves,
+            )
+            .value(noise_mode)
+        };
 
     let mut best_parameters = init_parameters.clone();
     let mut best_complexity = best_complexity;
@@ -667,224 +757,244 @@ fn optimize_macro(
             glwe_dimension,
         };
 
-        let input_variance = glwe_params.minimal_variance(ciphertext_modulus_log, security_level);
+        let input_noise = input_noise(
+            glwe_params,
+            ciphertext_modulus_log,
+            security_curves,
+            security_level,
+        );
         if glwe_dimension == 1 && log2_polynomial_size == 8 {
             // this is insecure and so minimal variance will be above 1
-            assert!(input_variance > 1.0);
+            assert!(input_noise.variance > 1.0);
             continue;
         }
+        let input_variance = input_noise.value(noise_mode);
 
         for &internal_dim in &search_space.internal_lwe_dimensions {
-            let mut operations = operations.clone();
-            // OPT: fast linear noise_modulus_switching
-            let variance_modulus_switching =
-                variance_modulus_switching_of(log2_polynomial_size, internal_dim);
-
-            let macro_param_partition = MacroParameters {
-                glwe_params,
-                internal_dim,
-            };
-
-            // Heuristic to fill missing macro parameters
-            let macros: Vec<_> = (0..nb_partitions)
-                .map(|i| {
-                    if i == partition {
-                        macro_param_partition
-                    } else {
-                        init_parameters.macro_params[i].unwrap_or(macro_param_partition)
-                    }
-                })
-                .collect();
-
-            // OPT: could be done once and than partially updated
-            apply_partitions_input_and_modulus_variance_and_cost(
-                ciphertext_modulus_log,
-                security_level,
-                nb_partitions,
-                &macros,
-                partition,
-                input_variance,
-                variance_modulus_switching,
-                &mut operations,
-            );
+            // the noise is increasing with internal_dim, the cut needs every modulus switching
+            let mut cut_internal_dim = true;
+            for &modulus_switching in &search_space.modulus_switching_kinds {
+                let mut operations = operations.clone();
+                // OPT: fast linear noise_modulus_switching
+                let variance_modulus_switching = variance_modulus_switching_of(
+                    log2_polynomial_size,
+                    internal_dim,
+                    modulus_switching,
+                );
 
-            if best_parameters.is_feasible && !feasible.feasible(&operations.variance) {
-                // noise_modulus_switching is increasing with internal_dim so we can cut
-                // but as long as nothing feasible as been found we don't break to improve feasibility
-                break;
-            }
+                let macro_param_partition = MacroParameters {
+                    glwe_params,
+                    internal_dim,
+                    modulus_switching,
+                };
 
-            if complexity.complexity(&operations.cost) > best_complexity {
-                continue;
-            }
+                // Heuristic to fill missing macro parameters
+                let macros: Vec<_> = (0..nb_partitions)
+                    .map(|i| {
+                        if i == partition {
+                            macro_param_partition
+                        } else {
+                            init_parameters.macro_params[i].unwrap_or(macro_param_partition)
+                        }
+                    })
+                    .collect();
+
+                // OPT: could be done once and than partially updated
+                apply_partitions_input_and_modulus_variance_and_cost(
+                    ciphertext_modulus_log,
+                    security_curves,
+                    security_level,
+                    noise_mode,
+                    nb_partitions,
+                    &macros,
+                    partition,
+                    input_variance,
+                    variance_modulus_switching,
+                    &mut operations,
+                );
 
-            // setting already chosen pbs and lower bounds
-            // OPT: could be done once and than partially updated
-            apply_pbs_variance_and_cost_or_lower_bounds(
-                &mut caches.cmux,
-                &macros,
-                &init_parameters.micro_params.pbs,
-                partition,
-                &mut operations,
-            );
+                if best_parameters.is_feasible && !feasible.feasible(&operations.variance) {
+                    // noise_modulus_switching is increasing with internal_dim so we can cut
+                    // but as long as nothing feasible as been found we don't break to improve feasibility
+                    continue;
+                }
+                cut_internal_dim = false;
 
-            // OPT: could be done once and than partially updated
-            apply_all_ks_lower_bound(
-                &mut caches.keyswitch,
-                nb_partitions,
-                &macros,
-                used_tlu_keyswitch,
-                &mut operations,
-            );
-            // OPT: could be done once and than partially updated
-            apply_fks_variance_and_cost_or_lower_bound(
-                &mut caches.keyswitch,
-                nb_partitions,
-                &macros,
-                &init_parameters.micro_params.fks,
-                &fks_to_optimize,
-                used_conversion_keyswitch,
-                &mut operations,
-                ciphertext_modulus_log,
-                fft_precision,
-            );
+                if complexity.complexity(&operations.cost) > best_complexity {
+                    continue;
+                }
 
-            let non_feasible = !feasible.feasible(&operations.variance);
-            if best_parameters.is_feasible && non_feasible {
-                continue;
-            }
+                // setting already chosen pbs and lower bounds
+                // OPT: could be done once and than partially updated
+                apply_pbs_variance_and_cost_or_lower_bounds(
+                    &mut caches.cmux,
+                    &macros,
+                    &init_parameters.micro_params.pbs,
+                    partition,
+                    &mut operations,
+                );
 
-            if complexity.complexity(&operations.cost) > best_complexity {
-                continue;
-            }
+                // OPT: could be done once and than partially updated
+                apply_all_ks_lower_bound(
+                    &mut caches.keyswitch,
+                    nb_partitions,
+                    &macros,
+                    used_tlu_keyswitch,
+                    &mut operations,
+                );
+                // OPT: could be done once and than partially updated
+                apply_fks_variance_and_cost_or_lower_bound(
+                    &mut caches.keyswitch,
+                    nb_partitions,
+                    &macros,
+                    &init_parameters.micro_params.fks,
+                    &fks_to_optimize,
+                    used_conversion_keyswitch,
+                    &mut operations,
+                    ciphertext_modulus_log,
+                    fft_precision,
+                    noise_mode,
+                );
 
-            let cmux_pareto = caches.cmux.pareto_quantities(glwe_params);
+                let non_feasible = !feasible.feasible(&operations.variance);
+                if best_parameters.is_feasible && non_feasible {
+                    continue;
+                }
 
-            if non_feasible {
-                lb_message = Some("Non feasible");
-                // here we optimize for feasibility only
-                // if nothing is feasible, it will give improves feasability for later iterations
-                let mut macro_params = init_parameters.macro_params.clone();
-                macro_params[partition] = Some(MacroParameters {
-                    glwe_params,
-                    internal_dim,
-                });
-                // optimize the feasibility only, takes all lower bounds on variance
-                // this selects both macro parameters and pbs (lowest variance) for this partition
-                let complexity = f64::INFINITY;
-                let cmux_params = cmux::lowest_noise(cmux_pareto);
-                let partition_p_error = partition_feasible.p_error(&operations.variance);
-                if partition_p_error >= best_partition_p_error {
+                if complexity.complexity(&operations.cost) > best_complexity {
                     continue;
                 }
-                best_partition_p_error = partition_p_error;
-                let p_error = feasible.p_error(&operations.variance);
-                let global_p_error = feasible.global_p_error(&operations.variance);
-                let mut pbs = init_parameters.micro_params.pbs.clone();
-                pbs[partition] = Some(cmux_params);
-                let micro_params = MicroParameters {
-                    pbs,
-                    ks: vec![vec![None; nb_partitions]; nb_partitions],
-                    fks: vec![vec![None; nb_partitions]; nb_partitions],
-                };
-                best_parameters = Parameters {
-                    p_error,
-                    global_p_error,
-                    complexity,
-                    micro_params,
-                    macro_params,
-                    is_lower_bound: true,
-                    is_feasible: false,
-                };
-                continue;
-            }
 
-            if complexity.complexity(&operations.cost) > best_complexity {
-                continue;
-            }
+                let cmux_pareto = caches.cmux.pareto_quantities(glwe_params);
+
+                if non_feasible {
+                    lb_message = Some("Non feasible");
+                    // here we optimize for feasibility only
+                    // if nothing is feasible, it will give improves feasability for later iterations
+                    let mut macro_params = init_parameters.macro_params.clone();
+                    macro_params[partition] = Some(macro_param_partition);
+                    // optimize the feasibility only, takes all lower bounds on variance
+                    // this selects both macro parameters and pbs (lowest variance) for this partition
+                    let complexity = f64::INFINITY;
+                    let cmux_params = cmux::lowest_noise(cmux_pareto);
+                    let partition_p_error = partition_feasible.p_error(&operations.variance);
+                    if partition_p_error >= best_partition_p_error {
+                        continue;
+                    }
+                    best_partition_p_error = partition_p_error;
+                    let p_error = feasible.p_error(&operations.variance);
+                    let global_p_error = feasible.global_p_error(&operations.variance);
+                    let mut pbs = init_parameters.micro_params.pbs.clone();
+                    pbs[partition] = Some(cmux_params);
+                    let micro_params = MicroParameters {
+                        pbs,
+                        ks: vec![vec![None; nb_partitions]; nb_partitions],
+                        fks: vec![vec![None; nb_partitions]; nb_partitions],
+                    };
+                    best_parameters = Parameters {
+                        p_error,
+                        global_p_error,
+                        complexity,
+                        micro_params,
+                        macro_params,
+                        is_lower_bound: true,
+                        is_feasible: false,
+                    };
+                    continue;
+                }
 
-            let micro_opt = optimize_1_cmux_and_dst_exclusive_fks_subset_and_all_ks(
-                partition,
-                &macros,
-                internal_dim,
-                cmux_pareto,
-                &fks_to_optimize,
-                used_tlu_keyswitch,
-                &operations,
-                feasible,
-                complexity,
-                &mut caches.keyswitch,
-                best_complexity,
-                best_p_error,
-                ciphertext_modulus_log,
-                fft_precision,
-            );
-            if let Some(some_micro_params) = micro_opt {
-                // erase macros and all fks that can't be real
-                // set global is_lower_bound here, if any parameter is missing this is lower bound
-                // optimize_micro has already checked for best-ness
-                lb_message = None;
-                let mut macro_params = init_parameters.macro_params.clone();
-                macro_params[partition] = Some(macro_param_partition);
-                let mut is_lower_bound = macro_params.iter().any(Option::is_none);
-                if is_lower_bound {
-                    lb_message = Some("is_lower_bound due to missing macro parameter");
+                if complexity.complexity(&operations.cost) > best_complexity {
+                    continue;
                 }
-                // copy back pbs from other partition
-                let mut all_pbs = init_parameters.micro_params.pbs.clone();
-                all_pbs[partition] = Some(some_micro_params.pbs);
-                let mut all_fks = init_parameters.micro_params.fks.clone();
-                for (dst_partition, maybe_fks) in fks_to_optimize.iter().enumerate() {
-                    if let &Some(src_partition) = maybe_fks {
-                        all_fks[src_partition][dst_partition] =
-                            some_micro_params.fks[src_partition][dst_partition];
-                        assert!(used_conversion_keyswitch[src_partition][dst_partition]);
-                        assert!(all_fks[src_partition][dst_partition].is_some());
+
+                let micro_opt = optimize_1_cmux_and_dst_exclusive_fks_subset_and_all_ks(
+                    partition,
+                    &macros,
+                    internal_dim,
+                    cmux_pareto,
+                    &fks_to_optimize,
+                    used_tlu_keyswitch,
+                    &operations,
+                    feasible,
+                    complexity,
+                    &mut caches.keyswitch,
+                    best_complexity,
+                    best_p_error,
+                    ciphertext_modulus_log,
+                    fft_precision,
+                );
+                if let Some(some_micro_params) = micro_opt {
+                    // erase macros and all fks that can't be real
+                    // set global is_lower_bound here, if any parameter is missing this is lower bound
+                    // optimize_micro has already checked for best-ness
+                    lb_message = None;
+                    let mut macro_params = init_parameters.macro_params.clone();
+                    macro_params[partition] = Some(macro_param_partition);
+                    let mut is_lower_bound = macro_params.iter().any(Option::is_none);
+                    if is_lower_bound {
+                        lb_message = Some("is_lower_bound due to missing macro parameter");
                     }
-                }
-                // As all fks cannot be re-optimized in some case, we need to check previous ones are still valid.
-                for (src_partition, dst_partition) in cross_partition(nb_partitions) {
-                    if !used_conversion_keyswitch[src_partition][dst_partition] {
-                        continue;
+                    // copy back pbs from other partition
+                    let mut all_pbs = init_parameters.micro_params.pbs.clone();
+                    all_pbs[partition] = Some(some_micro_params.pbs);
+                    let mut all_fks = init_parameters.micro_params.fks.clone();
+                    for (dst_partition, maybe_fks) in fks_to_optimize.iter().enumerate() {
+                        if let &Some(src_partition) = maybe_fks {
+                            all_fks[src_partition][dst_partition] =
+                                some_micro_params.fks[src_partition][dst_partition];
+                            assert!(used_conversion_keyswitch[src_partition][dst_partition]);
+                            assert!(all_fks[src_partition][dst_partition].is_some());
+                        }
                     }
-                    let fks = &all_fks[src_partition][dst_partition];
-                    if !is_lower_bound && fks.is_none() {
-                        lb_message = Some("is_lower_bound due to missing fast keyswitch parameter");
+                    // As all fks cannot be re-optimized in some case, we need to check previous ones are still valid.
+                    for (src_partition, dst_partition) in cross_partition(nb_partitions) {
+                        if !used_conversion_keyswitch[src_partition][dst_partition] {
+                            continue;
+                        }
+                        let fks = &all_fks[src_partition][dst_partition];
+                        if !is_lower_bound && fks.is_none() {
+                            lb_message =
+                                Some("is_lower_bound due to missing fast keyswitch parameter");
+                            is_lower_bound = true;
+                        }
+                        let src_glwe_param = macro_params[src_partition].map(|p| p.glwe_params);
+                        let dst_glwe_param = macro_params[dst_partition].map(|p| p.glwe_params);
+                        let src_glwe_param_stable = src_glwe_param == fks.map(|p| p.src_glwe_param);
+                        let dst_glwe_param_stable = dst_glwe_param == fks.map(|p| p.dst_glwe_param);
+                        if src_glwe_param_stable && dst_glwe_param_stable {
+                            continue;
+                        }
+                        if !is_lower_bound {
+                            lb_message =
+                                Some("is_lower_bound due to changing others fks macro param");
+                        }
+                        all_fks[src_partition][dst_partition] = None;
                         is_lower_bound = true;
                     }
-                    let src_glwe_param = macro_params[src_partition].map(|p| p.glwe_params);
-                    let dst_glwe_param = macro_params[dst_partition].map(|p| p.glwe_params);
-                    let src_glwe_param_stable = src_glwe_param == fks.map(|p| p.src_glwe_param);
-                    let dst_glwe_param_stable = dst_glwe_param == fks.map(|p| p.dst_glwe_param);
-                    if src_glwe_param_stable && dst_glwe_param_stable {
-                        continue;
-                    }
-                    if !is_lower_bound {
-                        lb_message = Some("is_lower_bound due to changing others fks macro param");
-                    }
-                    all_fks[src_partition][dst_partition] = None;
-                    is_lower_bound = true;
+                    let micro_params = MicroParameters {
+                        pbs: all_pbs,
+                        ks: some_micro_params.ks,
+                        fks: all_fks,
+                    };
+                    best_complexity = some_micro_params.complexity;
+                    best_p_error = some_micro_params.p_error;
+                    best_parameters = Parameters {
+                        p_error: best_p_error,
+                        global_p_error: some_micro_params.global_p_error,
+                        complexity: best_complexity,
+                        micro_params,
+                        macro_params,
+                        is_lower_bound,
+                        is_feasible: true,
+                    };
+                } else {
+                    // the macro parameters are feasible
+                    // but the complexity is not good enough due to previous feasible solution
+                    assert!(best_parameters.is_feasible);
                 }
-                let micro_params = MicroParameters {
-                    pbs: all_pbs,
-                    ks: some_micro_params.ks,
-                    fks: all_fks,
-                };
-                best_complexity = some_micro_params.complexity;
-                best_p_error = some_micro_params.p_error;
-                best_parameters = Parameters {
-                    p_error: best_p_error,
-                    global_p_error: some_micro_params.global_p_error,
-                    complexity: best_complexity,
-                    micro_params,
-                    macro_params,
-                    is_lower_bound,
-                    is_feasible: true,
-                };
-            } else {
-                // the macro parameters are feasible
-                // but the complexity is not good enough due to previous feasible solution
-                assert!(best_parameters.is_feasible);
+            }
+            if cut_internal_dim {
+                break;
             }
         }
     }
@@ -898,7 +1008,43 @@ fn cross_partition(nb_partitions: usize) -> impl Iterator<Item = (usize, usize)>
     (0..nb_partitions).flat_map(move |a: usize| (0..nb_partitions).map(move |b: usize| (a, b)))
 }
 
-#[allow(clippy::too_many_lines, clippy::missing_errors_doc)]
+/// Rewrites `dag` by the dag passes then, if asked, by the refreshes making it composable, a
+/// refresh costing the atomic pattern of its precision.
+/// Returns the rewritten dag, the rewritten instructions computing each instruction of `dag`, and
+/// the refreshes with both their instructions in the rewritten dag.
+fn rewrite_dag(
+    dag: &Dag,
+    config: Config,
+    search_space: &SearchSpace,
+    persistent_caches: &PersistDecompCaches,
+) -> (Dag, Vec<Vec<OperatorIndex>>, Vec<Refresh>) {
+    let (simplified_dag, passes_index) = run_passes(dag, config.dag_passes);
+    if !config.refresh_compositions {
+        return (simplified_dag, passes_index, vec![]);
+    }
+    let costs = RefCell::new(HashMap::new());
+    let pbs_cost = |precision: Precision| {
+        *costs.borrow_mut().entry(precision).or_insert_with(|| {
+            let precision = u64::from(precision.max(1));
+            optimize_one(1, precision, config, 1.0, search_space, persistent_caches)
+                .best_solution
+                .map_or(f64::INFINITY, |sol| sol.complexity)
+        })
+    };
+    let (refreshed_dag, refresh_index, refreshes) =
+        insert_refreshes(&simplified_dag, config.composable, pbs_cost);
+    let refreshes = refreshes
+        .into_iter()
+        .map(|refresh| Refresh {
+            refreshed: refresh_index[refresh.refreshed.0][0],
+            ..refresh
+        })
+        .collect();
+    let index = compose_instructions_maps(&passes_index, &refresh_index);
+    (refreshed_dag, index, refreshes)
+}
+
+#[allow(clippy::missing_errors_doc)]
 pub fn optimize(
     dag: &Dag,
     config: Config,
@@ -906,6 +1052,78 @@ pub fn optimize(
     persistent_caches: &PersistDecompCaches,
     p_cut: &Option<PartitionCut>,
     default_partition: PartitionIndex,
+) -> optimization::Result<(AnalyzedDag, Parameters)> {
+    config.check_security()?;
+    dag.validate()?;
+    config.check_gaussian_tail()?;
+    let (rewritten_dag, rewrite_index, _) =
+        rewrite_dag(dag, config, search_space, persistent_caches);
+    // Each number of bits truncated per pbs gives another expansion of all the rounds, the cheapest
+    // one is kept. The same number is used for all the rounds, choosing it per round would multiply
+    // the optimizations by the number of rounds.
+    let has_round = rewritten_dag
+        .operators
+        .iter()
+        .any(|op| matches!(op, Operator::Round { .. }));
+    let groups_bits: &[Precision] = if has_round { &ROUND_GROUPS_BITS } else { &[1] };
+    let mut best: Option<(AnalyzedDag, Parameters)> = None;
+    let mut first_err = None;
+    for &group_bits in groups_bits {
+        let (expanded_dag, round_index) =
+            expand_operators_with_round_groups(&rewritten_dag, group_bits);
+        match optimize_expanded(
+            &expanded_dag,
+            config,
+            search_space,
+            persistent_caches,
+            p_cut,
+            default_partition,
+        ) {
+            Ok((mut analyzed_dag, params)) => {
+                if matches!(&best, Some((_, best)) if best.complexity <= params.complexity) {
+                    continue;
+                }
+                // the solution refers to the instructions of the given dag
+                let index = compose_instructions_maps(&rewrite_index, &round_index);
+                analyzed_dag.instruction_rewrite_index =
+                    compose_instructions_maps(&index, &analyzed_dag.instruction_rewrite_index);
+                analyzed_dag.round_group_bits = group_bits;
+                best = Some((analyzed_dag, params));
+            }
+            Err(err) => {
+                let _ = first_err.get_or_insert(err);
+            }
+        }
+    }
+    best.ok_or_else(|| first_err.unwrap_or(NoParametersFound))
+}
+
+/// The constraints coefficients are squared weights, they bound the maximal errors only when
+/// multiplied by integer weights. Levelled ops only give the norm of their weights, their squared
+/// norm must then be a non-zero integer.
+fn check_worst_case_weights(operators: &[Operator]) -> optimization::Result<()> {
+    for (i, op) in operators.iter().enumerate() {
+        if let Operator::LevelledOp { manp, .. } = op {
+            let squared_norm = manp * manp;
+            if squared_norm < 1.0 || (squared_norm - squared_norm.round()).abs() > 1e-6 {
+                return Err(UnboundedWorstCaseNoise(format!(
+                    "levelled op %{i} has fractional weights (manp {manp})"
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+// same arguments as `optimize`
+#[allow(clippy::too_many_lines, clippy::ref_option)]
+fn optimize_expanded(
+    dag: &Dag,
+    config: Config,
+    search_space: &SearchSpace,
+    persistent_caches: &PersistDecompCaches,
+    p_cut: &Option<PartitionCut>,
+    default_partition: PartitionIndex,
 ) -> optimization::Result<(AnalyzedDag, Parameters)> {
     let ciphertext_modulus_log = config.ciphertext_modulus_log;
     let fft_precision = config.fft_precision;
@@ -915,15 +1133,29 @@ pub fn optimize(
         security_level,
         maximum_acceptable_error_probability: config.maximum_acceptable_error_probability,
         ciphertext_modulus_log,
+        noise_tracking: config.noise_tracking,
     };
 
+    if persistent_caches.noise_mode != config.noise_mode {
+        return Err(NoiseModeMismatch {
+            caches: persistent_caches.noise_mode,
+            config: config.noise_mode,
+        });
+    }
+    if config.noise_mode == NoiseMode::WorstCase {
+        check_worst_case_weights(&dag.operators)?;
+    }
     let dag = analyze(dag, &noise_config, p_cut, default_partition, composable)?;
     let kappa =
         error::sigma_scale_of_error_probability(config.maximum_acceptable_error_probability);
 
     let mut caches = persistent_caches.caches();
 
-    let feasible = Feasible::of(&dag.variance_constraints, kappa, None).compressed();
+    let feasible = match config.noise_mode {
+        NoiseMode::AverageCase => Feasible::of(&dag.variance_constraints, kappa, None),
+        NoiseMode::WorstCase => Feasible::worst_case(&dag.variance_constraints, None),
+    }
+    .compressed();
     let complexity = Complexity::of(&dag.operations_count).compressed();
     let used_tlu_keyswitch = used_tlu_keyswitch(&dag);
     let used_conversion_keyswitch = used_conversion_keyswitch(&dag);
@@ -952,6 +1184,7 @@ pub fn optimize(
     for iter in 0..=10 {
         for partition in (0..nb_partitions).rev() {
             let new_params = optimize_macro(
+                config.security_curves,
                 security_level,
                 ciphertext_modulus_log,
                 fft_precision,
@@ -1029,6 +1262,7 @@ pub fn optimize(
         &used_conversion_keyswitch,
         &used_tlu_keyswitch,
         ciphertext_modulus_log,
+        config.security_curves,
         security_level,
         &feasible,
         &complexity,
@@ -1076,6 +1310,7 @@ fn sanity_check(
     used_conversion_keyswitch: &[Vec<bool>],
     used_tlu_keyswitch: &[Vec<bool>],
     ciphertext_modulus_log: u32,
+    security_curves: &SecurityCurves,
     security_level: u64,
     feasible: &Feasible,
     complexity: &Complexity,
@@ -1095,17 +1330,25 @@ fn sanity_check(
         let partition_macro = params.macro_params[partition].unwrap();
         let glwe_param = partition_macro.glwe_params;
         let internal_dim = partition_macro.internal_dim;
-        let input_variance = glwe_param.minimal_variance(ciphertext_modulus_log, security_level);
-        let variance_modulus_switching = estimate_modulus_switching_noise_with_binary_key(
-            internal_dim,
+        let input_noise = input_noise(
+            glwe_param,
+            ciphertext_modulus_log,
+            security_curves,
+            security_level,
+        );
+        let modulus_switching_noise = modulus_switching_noise(
             glwe_param.log2_polynomial_size,
+            internal_dim,
+            partition_macro.modulus_switching,
             ciphertext_modulus_log,
+            security_curves,
         );
-        *operations.variance.input(partition) = input_variance;
-        *operations.variance.modulus_switching(partition) = variance_modulus_switching;
+        *operations.variance.input(partition) = input_noise.value(feasible.noise_mode);
+        *operations.variance.modulus_switching(partition) =
+            modulus_switching_noise.value(feasible.noise_mode);
         if let Some(pbs) = micro_params.pbs[partition] {
             *operations.variance.pbs(partition) = pbs.noise_br(internal_dim);
-            *operations.cost.pbs(partition) = pbs.complexity_br(internal_dim);
+            *operations.cost.pbs(partition) = partition_macro.complexity_br(&pbs);
         } else {
             *operations.variance.pbs(partition) = f64::MAX;
             *operations.cost.pbs(partition) = f64::MAX;
@@ -1155,6 +1398,37 @@ fn sanity_check(
     }
 }
 
+/// The dag rewritten by the dag passes and the refreshes in the text format, the rewritten
+/// instruction computing each instruction, a refreshed one being computed by its refresh, and the
+/// refreshes. Nothing without rewrite.
+fn simplified_dag(
+    dag: &Dag,
+    config: Config,
+    search_space: &SearchSpace,
+    persistent_caches: &PersistDecompCaches,
+) -> (String, Vec<u64>, Vec<Refresh>) {
+    if config.dag_passes.is_empty() && !config.refresh_compositions {
+        return (String::new(), vec![], vec![]);
+    }
+    let (rewritten_dag, rewrite_index, refreshes) =
+        rewrite_dag(dag, config, search_space, persistent_caches);
+    let simplified_instructions = rewrite_index
+        .iter()
+        .map(|new_instructions| {
+            new_instructions
+                .last()
+                .map_or(keys_spec::NO_INSTRUCTION, |new_instruction| {
+                    new_instruction.0 as u64
+                })
+        })
+        .collect();
+    (
+        rewritten_dag.to_string(),
+        simplified_instructions,
+        refreshes,
+    )
+}
+
 pub fn optimize_to_circuit_solution(
     dag: &Dag,
     config: Config,
@@ -1162,17 +1436,26 @@ pub fn optimize_to_circuit_solution(
     persistent_caches: &PersistDecompCaches,
     p_cut: &Option<PartitionCut>,
 ) -> keys_spec::CircuitSolution {
+    if let Err(err) = config.check_security() {
+        return keys_spec::CircuitSolution::no_solution(err.to_string());
+    }
+    if let Err(err) = dag.validate() {
+        return keys_spec::CircuitSolution::no_solution(InvalidDag(err).to_string());
+    }
     if lut_count_from_dag(dag) == 0 {
         // If there are no lut in the dag the noise is never refresh so the dag cannot be composable
-        if config.composable {
+        // Note: the mono parameter optimization below would ignore the compositions
+        if config.composable || !dag.compositions.is_empty() {
             return keys_spec::CircuitSolution::no_solution(
                 NotComposable("No luts in the circuit.".into()).to_string(),
             );
         }
-        let nb_instr = dag.operators.len();
+        if let Err(err) = config.check_average_case() {
+            return keys_spec::CircuitSolution::no_solution(err.to_string());
+        }
         if let Some(sol) = optimize_mono(dag, config, search_space, persistent_caches).best_solution
         {
-            return keys_spec::CircuitSolution::from_native_solution(sol, nb_instr);
+            return keys_spec::CircuitSolution::from_native_solution(sol, dag);
         }
         return keys_spec::CircuitSolution::no_solution(NoParametersFound.to_string());
     }
@@ -1188,9 +1471,11 @@ pub fn optimize_to_circuit_solution(
     #[allow(clippy::option_if_let_else)]
     match dag_and_params {
         Err(e) => keys_spec::CircuitSolution::no_solution(e.to_string()),
-        Ok((dag, params)) => {
+        Ok((analyzed_dag, params)) => {
+            let (simplified_dag, simplified_instructions, refreshes) =
+                simplified_dag(dag, config, search_space, persistent_caches);
             let ext_keys = keys_spec::ExpandedCircuitKeys::of(&params);
-            let instructions_keys = analyze::original_instrs_partition(&dag, &ext_keys);
+            let instructions_keys = analyze::original_instrs_partition(&analyzed_dag, &ext_keys);
             let (ext_keys, instructions_keys) = if config.key_sharing {
                 let (ext_keys, key_sharing) = ext_keys.shared_keys();
                 let instructions_keys =
@@ -1203,6 +1488,10 @@ pub fn optimize_to_circuit_solution(
             keys_spec::CircuitSolution {
                 circuit_keys,
                 instructions_keys,
+                round_group_bits: keys_spec::round_group_bits(dag, analyzed_dag.round_group_bits),
+                simplified_dag,
+                simplified_instructions,
+                refreshes,
                 crt_decomposition: vec![],
                 complexity: params.complexity,
                 p_error: params.p_error,
@@ -5,11 +5,19 @@ use once_cell::sync::Lazy;
 use super::*;
 use crate::computing_cost::cpu::CpuComplexity;
 use crate::config;
-use crate::dag::operator::{FunctionTable, LevelledComplexity, Shape};
+use crate::dag::operator::{FunctionTable, LevelledComplexity, Shape, Weights};
+use crate::dag::rewrite::passes::ALL_PASSES;
 use crate::dag::unparametrized;
+use crate::optimization::config::NoiseTracking;
+use crate::optimization::dag::multi_parameters::analyze::tests::{
+    circuits_to_compose, CircuitsToCompose,
+};
 use crate::optimization::dag::solo_key;
 use crate::optimization::dag::solo_key::optimize::{add_v0_dag, v0_dag};
 use crate::optimization::decomposition;
+use crate::{
+    ErrorProbabilityModel, KeyDistribution, ModulusSwitchingKind, NoiseMode, SecurityCurves,
+};
 
 const CIPHERTEXT_MODULUS_LOG: u32 = 64;
 const FFT_PRECISION: u32 = 53;
@@ -18,12 +26,15 @@ static SHARED_CACHES: Lazy<PersistDecompCaches> = Lazy::new(|| {
     let processing_unit = config::ProcessingUnit::Cpu;
     decomposition::cache(
         128,
+        SecurityCurves::compiled(),
         processing_unit,
         None,
         true,
         CIPHERTEXT_MODULUS_LOG,
         FFT_PRECISION,
+        NoiseMode::AverageCase,
     )
+    .unwrap()
 });
 
 const _4_SIGMA: f64 = 0.000_063_342_483_999_973;
@@ -42,6 +53,12 @@ fn default_config() -> Config<'static> {
         fft_precision: 53,
         complexity_model,
         composable: false,
+        security_curves: SecurityCurves::compiled(),
+        noise_mode: NoiseMode::AverageCase,
+        error_probability_model: ErrorProbabilityModel::Gaussian,
+        dag_passes: &[],
+        refresh_compositions: false,
+        noise_tracking: NoiseTracking::PerTensor,
     }
 }
 
@@ -425,6 +442,113 @@ fn optimize_v3_direct_round() {
     );
 }
 
+#[test]
+fn optimize_round_by_groups() {
+    let mut dag_1_bit = unparametrized::Dag::new();
+    let input1 = dag_1_bit.add_input(16, Shape::number());
+    _ = dag_1_bit.add_expanded_rounded_lut(input1, FunctionTable::UNKWOWN, 8, 16);
+    let mut dag = unparametrized::Dag::new();
+    let input1 = dag.add_input(16, Shape::number());
+    let round = dag.add_round_op(input1, 8);
+    _ = dag.add_lut(round, FunctionTable::UNKWOWN, 16);
+    let sol_1_bit = optimize(&dag_1_bit, &None, 0).unwrap();
+    let sol = optimize(&dag, &None, 0).unwrap();
+    // the 1 bit groups are among the tried expansions
+    assert!(sol.complexity <= sol_1_bit.complexity);
+    let mut dag_4_bits = unparametrized::Dag::new();
+    let input1 = dag_4_bits.add_input(16, Shape::number());
+    let rounded = dag_4_bits.add_expanded_round_by_groups(input1, 8, 4);
+    _ = dag_4_bits.add_lut(rounded, FunctionTable::UNKWOWN, 16);
+    let sol_4_bits = optimize(&dag_4_bits, &None, 0).unwrap();
+    assert!(sol.complexity <= sol_4_bits.complexity);
+    // all the pbs of the round share their keys
+    let circuit_sol = super::optimize_to_circuit_solution(
+        &dag,
+        default_config(),
+        &SearchSpace::default_cpu(),
+        &SHARED_CACHES,
+        &None,
+    );
+    assert!(circuit_sol.is_feasible, "{}", circuit_sol.error_msg);
+    assert_eq!(circuit_sol.instructions_keys.len(), dag.len());
+    // the solution gives the bits of the cheapest expansion
+    let group_bits = circuit_sol.round_group_bits[round.0];
+    assert_eq!(circuit_sol.round_group_bits, [0, group_bits, 0]);
+    let mut dag_chosen = unparametrized::Dag::new();
+    let input1 = dag_chosen.add_input(16, Shape::number());
+    let rounded = dag_chosen.add_expanded_round_by_groups(input1, 8, group_bits);
+    _ = dag_chosen.add_lut(rounded, FunctionTable::UNKWOWN, 16);
+    let sol_chosen = optimize(&dag_chosen, &None, 0).unwrap();
+    assert!(sol_chosen.complexity == sol.complexity);
+}
+
+#[test]
+fn optimize_composite_operators() {
+    let shape = Shape::vector(4);
+    let mut dag = unparametrized::Dag::new();
+    let input1 = dag.add_input(3, &shape);
+    let input2 = dag.add_input(3, &shape);
+    let product = dag.add_mul(input1, input2, 3);
+    let compared = dag.add_bivariate_lut(product, input2, FunctionTable::UNKWOWN, 3);
+    _ = dag.add_max_pool(compared, Shape::vector(2));
+    let mut expanded = unparametrized::Dag::new();
+    let input1 = expanded.add_input(3, &shape);
+    let input2 = expanded.add_input(3, &shape);
+    let product = expanded.add_expanded_mul(input1, input2, 3);
+    let compared = expanded.add_expanded_bivariate_lut(product, input2, FunctionTable::UNKWOWN, 3);
+    _ = expanded.add_expanded_max_pool(compared, &Shape::vector(2));
+    let sol = optimize(&dag, &None, 0).unwrap();
+    let sol_expanded = optimize(&expanded, &None, 0).unwrap();
+    assert!(sol.complexity == sol_expanded.complexity);
+    let circuit_sol = super::optimize_to_circuit_solution(
+        &dag,
+        default_config(),
+        &SearchSpace::default_cpu(),
+        &SHARED_CACHES,
+        &None,
+    );
+    assert!(circuit_sol.is_feasible, "{}", circuit_sol.error_msg);
+    assert_eq!(circuit_sol.instructions_keys.len(), dag.len());
+}
+
+#[test]
+fn optimize_shape_operators() {
+    let image_shape = Shape {
+        dimensions_size: vec![1, 1, 2, 2],
+    };
+    let mut dag = unparametrized::Dag::new();
+    let input1 = dag.add_input(4, &image_shape);
+    let lut1 = dag.add_lut(input1, FunctionTable::UNKWOWN, 4);
+    let weights = Weights {
+        shape: Shape {
+            dimensions_size: vec![1, 1, 1, 1],
+        },
+        values: vec![3],
+    };
+    let conv = dag.add_conv2d(lut1, weights, [1, 1], [0; 4], [1, 1], 1);
+    let transposed = dag.add_transpose(conv, [0, 1, 3, 2]);
+    let flat = dag.add_reshape(transposed, Shape::vector(4));
+    _ = dag.add_lut(flat, FunctionTable::UNKWOWN, 4);
+    // the same noise and bootstraps with a dot
+    let mut dot_dag = unparametrized::Dag::new();
+    let input1 = dot_dag.add_input(4, Shape::vector(4));
+    let lut1 = dot_dag.add_lut(input1, FunctionTable::UNKWOWN, 4);
+    let scaled = dot_dag.add_dot([lut1], Weights::number(3));
+    _ = dot_dag.add_lut(scaled, FunctionTable::UNKWOWN, 4);
+    let sol = optimize(&dag, &None, 0).unwrap();
+    let sol_dot = optimize(&dot_dag, &None, 0).unwrap();
+    assert!(sol.complexity == sol_dot.complexity);
+    let circuit_sol = super::optimize_to_circuit_solution(
+        &dag,
+        default_config(),
+        &SearchSpace::default_cpu(),
+        &SHARED_CACHES,
+        &None,
+    );
+    assert!(circuit_sol.is_feasible, "{}", circuit_sol.error_msg);
+    assert_eq!(circuit_sol.instructions_keys.len(), dag.len());
+}
+
 #[test]
 fn optimize_sign_extract() {
     let precision = 8;
@@ -637,6 +761,74 @@ fn test_levelled_only() {
     assert!(sol.circuit_keys.secret_keys[0].polynomial_size == sol_mono.glwe_polynomial_size);
 }
 
+#[test]
+fn test_circuit_solution_of_simplified_dag() {
+    let table = FunctionTable {
+        values: (0..8).collect(),
+    };
+    let mut dag = unparametrized::Dag::new();
+    let input = dag.add_input(3, Shape::number());
+    let lut_1 = dag.add_lut(input, table.clone(), 3);
+    let lut_2 = dag.add_lut(input, table, 3);
+    let sum = dag.add_dot([lut_1, lut_2], [1, 1]);
+    let lut_3 = dag.add_lut(sum, FunctionTable::UNKWOWN, 4);
+    let _ = dag.add_lut(lut_3, FunctionTable::UNKWOWN, 4);
+    let search_space = SearchSpace::default_cpu();
+    let solution = |dag_passes| {
+        let config = Config {
+            dag_passes,
+            ..default_config()
+        };
+        super::optimize_to_circuit_solution(&dag, config, &search_space, &SHARED_CACHES, &None)
+    };
+    let sol = solution(&[]);
+    let simplified_sol = solution(&ALL_PASSES);
+    assert!(simplified_sol.is_feasible);
+    assert!(simplified_sol.complexity < sol.complexity);
+    assert_eq!(simplified_sol.instructions_keys.len(), dag.len());
+    // the merged lut has the keys of the first one, the lut fused in the next one has none
+    let keys = &simplified_sol.instructions_keys;
+    assert_eq!(keys[lut_2.0], keys[lut_1.0]);
+    assert_eq!(keys[lut_3.0].tlu_bootstrap_key, keys_spec::NO_KEY_ID);
+    // the solution gives the simplified dag to compute
+    assert!(sol.simplified_dag.is_empty() && sol.simplified_instructions.is_empty());
+    let simplified: unparametrized::Dag = simplified_sol.simplified_dag.parse().unwrap();
+    assert_eq!(simplified.len(), 4);
+    let instructions = &simplified_sol.simplified_instructions;
+    assert_eq!(instructions.len(), dag.len());
+    assert_eq!(instructions[lut_2.0], instructions[lut_1.0]);
+    assert_eq!(instructions[lut_3.0], keys_spec::NO_INSTRUCTION);
+    let fused = instructions[dag.len() - 1] as usize;
+    assert!(matches!(
+        simplified.operators[fused],
+        Operator::Lut { input, .. } if input.0 == instructions[sum.0] as usize
+    ));
+}
+
+#[test]
+fn test_circuit_solution_serialization() {
+    let mut dag = unparametrized::Dag::new();
+    let input1 = dag.add_input(3, Shape::number());
+    let input2 = dag.add_input(6, Shape::number());
+    let lut1 = dag.add_lut(input1, FunctionTable::UNKWOWN, 6);
+    let lut2 = dag.add_lut(input2, FunctionTable::UNKWOWN, 6);
+    let _ = dag.add_dot([lut1, lut2], [1, 1]);
+    let search_space = SearchSpace::default_cpu();
+    let sol = super::optimize_to_circuit_solution(
+        &dag,
+        default_config(),
+        &search_space,
+        &SHARED_CACHES,
+        &None,
+    );
+    assert!(sol.is_feasible);
+    assert!(!sol.circuit_keys.bootstrap_keys.is_empty());
+    let json = serde_json::to_string(&sol).unwrap();
+    assert_eq!(sol, serde_json::from_str(&json).unwrap());
+    let bytes = bincode::serialize(&sol).unwrap();
+    assert_eq!(sol, bincode::deserialize(&bytes).unwrap());
+}
+
 #[test]
 fn test_big_secret_key_sharing() {
     let mut dag = unparametrized::Dag::new();
@@ -654,6 +846,12 @@ fn test_big_secret_key_sharing() {
         fft_precision: 53,
         complexity_model: &CpuComplexity::default(),
         composable: false,
+        security_curves: SecurityCurves::compiled(),
+        noise_mode: NoiseMode::AverageCase,
+        error_probability_model: ErrorProbabilityModel::Gaussian,
+        dag_passes: &[],
+        refresh_compositions: false,
+        noise_tracking: NoiseTracking::PerTensor,
     };
     let config_no_sharing = Config {
         key_sharing: false,
@@ -704,6 +902,12 @@ fn test_big_and_small_secret_key() {
         fft_precision: 53,
         complexity_model: &CpuComplexity::default(),
         composable: false,
+        security_curves: SecurityCurves::compiled(),
+        noise_mode: NoiseMode::AverageCase,
+        error_probability_model: ErrorProbabilityModel::Gaussian,
+        dag_passes: &[],
+        refresh_compositions: false,
+        noise_tracking: NoiseTracking::PerTensor,
     };
     let config_no_sharing = Config {
         key_sharing: false,
@@ -795,6 +999,263 @@ fn test_composition_1_partition_not_composable() {
     assert!(composed_sol.is_err());
 }
 
+#[test]
+fn test_unsupported_security_is_an_error() {
+    let mut dag = unparametrized::Dag::new();
+    let input = dag.add_input(8, Shape::number());
+    let _ = dag.add_lut(input, FunctionTable::UNKWOWN, 8);
+    let search_space = SearchSpace::default_cpu();
+    let optimize_with =
+        |config| super::optimize(&dag, config, &search_space, &SHARED_CACHES, &None, 0);
+    let unsupported_level = Config {
+        security_level: 96,
+        ..default_config()
+    };
+    assert!(matches!(
+        optimize_with(unsupported_level),
+        Err(optimization::Err::UnsupportedSecurityLevel(96))
+    ));
+    let unsupported_modulus = Config {
+        ciphertext_modulus_log: 8,
+        ..default_config()
+    };
+    assert!(matches!(
+        optimize_with(unsupported_modulus),
+        Err(optimization::Err::UnsupportedCiphertextModulus(8))
+    ));
+    let sol = super::optimize_to_circuit_solution(
+        &dag,
+        unsupported_level,
+        &search_space,
+        &SHARED_CACHES,
+        &None,
+    );
+    assert!(!sol.is_feasible);
+    assert_eq!(sol.error_msg, "96 bits of security is not supported");
+}
+
+#[test]
+fn test_fixed_weight_keys_are_rejected() {
+    let mut dag = unparametrized::Dag::new();
+    let input = dag.add_input(8, Shape::number());
+    let _ = dag.add_lut(input, FunctionTable::UNKWOWN, 8);
+    let sparse_curves = SecurityCurves::compiled()
+        .clone()
+        .for_key_distribution(KeyDistribution::Sparse { hamming_weight: 64 });
+    let config = Config {
+        security_curves: &sparse_curves,
+        ..default_config()
+    };
+    let search_space = SearchSpace::default_cpu();
+    assert!(matches!(
+        super::optimize(&dag, config, &search_space, &SHARED_CACHES, &None, 0),
+        Err(optimization::Err::UnsupportedKeyDistribution(_))
+    ));
+    assert!(matches!(
+        decomposition::cache(
+            128,
+            &sparse_curves,
+            config::ProcessingUnit::Cpu,
+            None,
+            false,
+            CIPHERTEXT_MODULUS_LOG,
+            FFT_PRECISION,
+            NoiseMode::AverageCase,
+        ),
+        Err(optimization::Err::UnsupportedKeyDistribution(_))
+    ));
+}
+
+#[test]
+fn test_exact_tail_is_rejected() {
+    let config = Config {
+        error_probability_model: ErrorProbabilityModel::ExactTail,
+        ..default_config()
+    };
+    let search_space = SearchSpace::default_cpu();
+    let mut dag = unparametrized::Dag::new();
+    let input = dag.add_input(4, Shape::number());
+    let _ = dag.add_lut(input, FunctionTable::UNKWOWN, 4);
+    assert!(matches!(
+        super::optimize(&dag, config, &search_space, &SHARED_CACHES, &None, 0),
+        Err(optimization::Err::UnsupportedErrorProbabilityModel(
+            ErrorProbabilityModel::ExactTail
+        ))
+    ));
+
+    let sol =
+        super::optimize_to_circuit_solution(&dag, config, &search_space, &SHARED_CACHES, &None);
+    assert!(!sol.is_feasible);
+    assert_eq!(
+        sol.error_msg,
+        "ExactTail error probabilities are not supported by this optimizer"
+    );
+
+    // the levelled dag goes to the solo key optimizer, which supports the exact tail
+    let mut levelled_dag = unparametrized::Dag::new();
+    let input = levelled_dag.add_input(4, Shape::number());
+    let _ = levelled_dag.add_dot([input], [2]);
+    let sol = super::optimize_to_circuit_solution(
+        &levelled_dag,
+        config,
+        &search_space,
+        &SHARED_CACHES,
+        &None,
+    );
+    assert!(sol.is_feasible, "{}", sol.error_msg);
+}
+
+#[test]
+fn test_modulus_switching_trades_cost_for_dimension() {
+    let mut dag = unparametrized::Dag::new();
+    let input = dag.add_input(6, Shape::number());
+    let lut = dag.add_lut(input, FunctionTable::UNKWOWN, 6);
+    let _ = dag.add_dot([lut], [8]);
+    let standard_space = SearchSpace::default_cpu();
+    let corrected_space = SearchSpace {
+        modulus_switching_kinds: ModulusSwitchingKind::ALL.to_vec(),
+        ..SearchSpace::default_cpu()
+    };
+    let (_, standard) = super::optimize(
+        &dag,
+        default_config(),
+        &standard_space,
+        &SHARED_CACHES,
+        &None,
+        0,
+    )
+    .unwrap();
+    let (_, corrected) = super::optimize(
+        &dag,
+        default_config(),
+        &corrected_space,
+        &SHARED_CACHES,
+        &None,
+        0,
+    )
+    .unwrap();
+    let standard_macro = standard.macro_params[0].unwrap();
+    let corrected_macro = corrected.macro_params[0].unwrap();
+    assert_eq!(
+        standard_macro.modulus_switching,
+        ModulusSwitchingKind::Standard
+    );
+    assert!(corrected.complexity < standard.complexity);
+    assert_eq!(
+        corrected_macro.modulus_switching,
+        ModulusSwitchingKind::CenteredMean
+    );
+    assert!(corrected_macro.internal_dim < standard_macro.internal_dim);
+    let keys = keys_spec::ExpandedCircuitKeys::of(&corrected);
+    assert_eq!(
+        keys.bootstrap_keys[0].modulus_switching,
+        corrected_macro.modulus_switching
+    );
+}
+
+#[test]
+fn test_worst_case_parameters_never_fail() {
+    let mut dag = unparametrized::Dag::new();
+    let input = dag.add_input(4, Shape::number());
+    let lut = dag.add_lut(input, FunctionTable::UNKWOWN, 4);
+    let _ = dag.add_dot([lut], [4]);
+    let search_space = SearchSpace::default_cpu();
+    let worst_case_caches = decomposition::cache(
+        128,
+        SecurityCurves::compiled(),
+        config::ProcessingUnit::Cpu,
+        None,
+        false,
+        CIPHERTEXT_MODULUS_LOG,
+        FFT_PRECISION,
+        NoiseMode::WorstCase,
+    )
+    .unwrap();
+    let worst_case_config = Config {
+        noise_mode: NoiseMode::WorstCase,
+        ..default_config()
+    };
+    let (_, average_case) = super::optimize(
+        &dag,
+        default_config(),
+        &search_space,
+        &SHARED_CACHES,
+        &None,
+        0,
+    )
+    .unwrap();
+    let (_, worst_case) = super::optimize(
+        &dag,
+        worst_case_config,
+        &search_space,
+        &worst_case_caches,
+        &None,
+        0,
+    )
+    .unwrap();
+    assert!(worst_case.p_error == 0.0);
+    assert!(worst_case.global_p_error == 0.0);
+    assert!(worst_case.complexity > average_case.complexity);
+
+    let mismatch = super::optimize(
+        &dag,
+        worst_case_config,
+        &search_space,
+        &SHARED_CACHES,
+        &None,
+        0,
+    );
+    assert_eq!(
+        mismatch.unwrap_err(),
+        optimization::Err::NoiseModeMismatch {
+            caches: NoiseMode::AverageCase,
+            config: NoiseMode::WorstCase
+        }
+    );
+
+    // a 0.5 weight halves the maximal error but quarters its squared coefficient
+    let mut fractional_dag = unparametrized::Dag::new();
+    let input = fractional_dag.add_input(4, Shape::number());
+    let lut = fractional_dag.add_lut(input, FunctionTable::UNKWOWN, 4);
+    let half = fractional_dag.add_levelled_op(
+        [lut],
+        LevelledComplexity::ZERO,
+        0.5,
+        Shape::number(),
+        "half",
+    );
+    let _ = fractional_dag.add_lut(half, FunctionTable::UNKWOWN, 4);
+    let fractional = super::optimize(
+        &fractional_dag,
+        worst_case_config,
+        &search_space,
+        &worst_case_caches,
+        &None,
+        0,
+    );
+    assert!(matches!(
+        fractional,
+        Err(optimization::Err::UnboundedWorstCaseNoise(_))
+    ));
+
+    // only the multi parameters optimizer bounds the maximal errors
+    let mut levelled_dag = unparametrized::Dag::new();
+    let input = levelled_dag.add_input(4, Shape::number());
+    let _ = levelled_dag.add_dot([input], [2]);
+    let sol = super::optimize_to_circuit_solution(
+        &levelled_dag,
+        worst_case_config,
+        &search_space,
+        &worst_case_caches,
+        &None,
+    );
+    assert!(!sol.is_feasible);
+    assert_eq!(
+        sol.error_msg,
+        "WorstCase noise mode is not supported by this optimizer"
+    );
+}
+
 #[test]
 fn test_maximal_multi() {
     let config = default_config();
@@ -845,3 +1306,114 @@ fn test_bug_with_zero_noise() {
     let sol = optimize(&dag, &None, 0);
     assert!(sol.is_some());
 }
+
+#[test]
+fn test_text_dag_2_circuits() {
+    let dag: unparametrized::Dag = r#"
+        circuit "small"
+        %0 = input u3
+        %1 = lut %0 u3
+        circuit "big"
+        %2 = input u6
+        %3 = dot [%2] weights [4]
+        %4 = lut %3 u6
+    "#
+    .parse()
+    .unwrap();
+    assert_eq!(dag.get_circuit_count(), 2);
+    let p_cut = PartitionCut::for_each_precision(&dag);
+    let sol = optimize(&dag, &Some(p_cut), LOW_PARTITION).unwrap();
+    assert_eq!(sol.macro_params.len(), 2);
+    assert!(sol.macro_params.iter().all(Option::is_some));
+}
+
+#[test]
+fn test_invalid_dag_error() {
+    let mut dag = unparametrized::Dag::new();
+    let input = dag.add_input(3, Shape::vector(2));
+    let lut = dag.add_lut(input, FunctionTable::UNKWOWN, 3);
+    let _ = dag.add_dot([lut], [1, 2, 3]);
+    let search_space = SearchSpace::default_cpu();
+    let result = super::optimize(
+        &dag,
+        default_config(),
+        &search_space,
+        &SHARED_CACHES,
+        &None,
+        LOW_PARTITION,
+    );
+    assert!(matches!(result, Err(InvalidDag(_))));
+    let sol = super::optimize_to_circuit_solution(
+        &dag,
+        default_config(),
+        &search_space,
+        &SHARED_CACHES,
+        &None,
+    );
+    assert!(!sol.is_feasible);
+    assert_eq!(
+        sol.error_msg,
+        "Invalid dag: operator %2 is an unsupported dot of 1 inputs of shape [2] with weights of shape [3]"
+    );
+}
+
+#[test]
+fn test_composition_between_circuits() {
+    let CircuitsToCompose {
+        dag,
+        levelled_input,
+        levelled_output,
+        refresh_input,
+        refresh_output,
+    } = circuits_to_compose();
+    let search_space = SearchSpace::default_cpu();
+    let optimize_composed = |dag: &unparametrized::Dag, composable| {
+        let config = Config {
+            composable,
+            ..default_config()
+        };
+        super::optimize(dag, config, &search_space, &SHARED_CACHES, &None, 0).map(|v| v.1)
+    };
+    // every output fed to every input: the levelled output noise grows through its own input
+    assert!(matches!(
+        optimize_composed(&dag, true),
+        Err(NotComposable(_))
+    ));
+
+    // only the refreshed paths are composed, possibly in a cycle
+    let mut cycle = dag.clone();
+    cycle.add_composition(levelled_output, refresh_input);
+    cycle.add_composition(refresh_output, levelled_input);
+    assert!(optimize_composed(&cycle, false).unwrap().is_feasible);
+
+    let mut levelled_cycle = dag.clone();
+    levelled_cycle.add_composition(levelled_output, levelled_input);
+    let err = optimize_composed(&levelled_cycle, false).unwrap_err();
+    assert!(
+        err.to_string().starts_with(
+            "Program can not be composed: Output 1 fed to input 0 has a growing variance"
+        ),
+        "{err}"
+    );
+
+    // refreshing the levelled output makes both composable
+    for (dag, composable) in [(dag, true), (levelled_cycle, false)] {
+        let config = Config {
+            composable,
+            refresh_compositions: true,
+            ..default_config()
+        };
+        let sol =
+            super::optimize_to_circuit_solution(&dag, config, &search_space, &SHARED_CACHES, &None);
+        assert!(sol.is_feasible, "{}", sol.error_msg);
+        assert_eq!(sol.refreshes.len(), 1);
+        let refresh = &sol.refreshes[0];
+        assert_eq!(refresh.refreshed, levelled_output);
+        // the consumers of the levelled output read its refresh
+        assert_eq!(
+            sol.simplified_instructions[levelled_output.0],
+            refresh.lut.0 as u64
+        );
+        assert!(refresh.cost > 0.0 && refresh.cost.is_finite());
+    }
+}
@@ -6,6 +6,7 @@ use crate::optimization::dag::solo_key::analyze;
 use crate::optimization::dag::solo_key::optimize_generic::{max_precision, Encoding};
 use crate::optimization::decomposition::PersistDecompCaches;
 use crate::optimization::wop_atomic_pattern::optimize::optimize_to_circuit_solution as crt_optimize_no_dag;
+use crate::optimization::Err::{InvalidDag, NotComposable};
 
 use super::partition_cut::PartitionCut;
 
@@ -32,11 +33,21 @@ fn crt_optimize(
     default_log_norm2_woppbs: f64,
     caches: &PersistDecompCaches,
 ) -> CircuitSolution {
-    if analyze::has_round(dag) || analyze::has_unsafe_cast(dag) {
+    if analyze::has_round(dag)
+        || analyze::has_unsafe_cast(dag)
+        || analyze::has_composite_operator(dag)
+    {
         return CircuitSolution::no_solution(
-            "Crt does not support round/reinterpret_precision operator",
+            "Crt does not support round/reinterpret_precision/bivariate/mul/maxpool operator",
         );
     } // TODO: dag to params
+      // The noise of the declared compositions is not analyzed, a composable circuit only
+      // relies on the wop refreshing every lut output
+    if !dag.compositions.is_empty() {
+        return CircuitSolution::no_solution(
+            NotComposable("Crt does not support compositions.".into()).to_string(),
+        );
+    }
     let max_precision = max_precision(dag);
     let nb_luts = analyze::lut_count_from_dag(dag);
     let worst_log_norm = analyze::worst_log_norm_for_wop(dag);
@@ -62,6 +73,12 @@ pub fn optimize(
     caches: &PersistDecompCaches,
     p_cut: &Option<PartitionCut>,
 ) -> CircuitSolution {
+    if let Err(err) = config.check_security() {
+        return CircuitSolution::no_solution(err.to_string());
+    }
+    if let Err(err) = dag.validate() {
+        return CircuitSolution::no_solution(InvalidDag(err).to_string());
+    }
     let native = || native_optimize(dag, config, search_space, caches, p_cut);
     let crt = || crt_optimize(dag, config, search_space, default_log_norm2_woppbs, caches);
     match encoding {
@@ -3,7 +3,7 @@ use std::collections::{HashMap, HashSet};
 use ordered_float::OrderedFloat;
 
 use crate::dag::operator::{Operator, OperatorIndex, Precision};
-use crate::dag::rewrite::round::expand_round_and_index_map;
+use crate::dag::rewrite::expand::expand_operators_and_index_map;
 use crate::dag::unparametrized;
 use crate::optimization::dag::multi_parameters::partitions::PartitionIndex;
 use crate::optimization::dag::solo_key::analyze::out_variances;
@@ -76,7 +76,7 @@ impl PartitionCut {
     }
 
     pub fn for_each_precision(dag: &unparametrized::Dag) -> Self {
-        let (dag, _) = expand_round_and_index_map(dag);
+        let (dag, _) = expand_operators_and_index_map(dag);
         let mut lut_in_precisions: HashSet<_> = HashSet::default();
         for op in &dag.operators {
             if let Operator::Lut { input, .. } = op {
@@ -87,10 +87,11 @@ impl PartitionCut {
         Self::from_precisions(&precisions)
     }
 
+    #[allow(clippy::too_many_lines)]
     pub fn maximal_partitionning(original_dag: &unparametrized::Dag) -> Self {
         // Note: only keep one 0-bits, partition as the compiler will not support multi-parameter round
         // partition based on input precision and output log norm2
-        let (dag, rewrited) = expand_round_and_index_map(original_dag);
+        let (dag, rewrited) = expand_operators_and_index_map(original_dag);
         let mut round_index: HashMap<usize, usize> = HashMap::default();
         for (round_i, op) in original_dag.operators.iter().enumerate() {
             if let Operator::Round { .. } = op {
@@ -109,7 +110,9 @@ impl PartitionCut {
         for (op_i, op) in dag.operators.iter().enumerate() {
             match op {
                 // propagate
-                Operator::Dot { inputs, .. } | Operator::LevelledOp { inputs, .. } => {
+                Operator::Dot { inputs, .. }
+                | Operator::LevelledOp { inputs, .. }
+                | Operator::Concatenate { inputs, .. } => {
                     let mut origins = HashSet::default();
                     for input in inputs {
                         origins.extend(&noise_origins[input.0]);
@@ -117,7 +120,13 @@ impl PartitionCut {
                     noise_origins[op_i] = origins;
                 }
                 #[allow(clippy::assigning_clones)]
-                Operator::UnsafeCast { input, .. } => {
+                Operator::UnsafeCast { input, .. }
+                | Operator::Reshape { input, .. }
+                | Operator::Transpose { input, .. }
+                | Operator::Slice { input, .. }
+                | Operator::Gather { input, .. }
+                | Operator::Broadcast { input, .. }
+                | Operator::Conv2d { input, .. } => {
                     noise_origins[op_i] = noise_origins[input.0].clone();
                 }
                 // origins
@@ -129,7 +138,10 @@ impl PartitionCut {
                     noise_origins[op_i] = std::iter::once(op_i).collect();
                 }
                 // unreachable
-                Operator::Round { .. } => panic!("expand_round failed"),
+                Operator::Round { .. }
+                | Operator::Bivariate { .. }
+                | Operator::Mul { .. }
+                | Operator::MaxPool { .. } => panic!("expand_operators failed"),
             }
         }
         let out_norm2 = |i: usize| out_variances[i].lut_coeff + out_variances[i].input_coeff;
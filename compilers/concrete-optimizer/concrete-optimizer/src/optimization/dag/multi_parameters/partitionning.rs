@@ -55,24 +55,29 @@ fn extract_levelled_block(dag: &unparametrized::Dag, composable: bool) -> Blocks
             // Block entry point and pre-exit point
             Op::Lut { .. } => (),
             // Connectors
-            Op::UnsafeCast { input, .. } => uf.union(input.0, op_i),
-            Op::LevelledOp { inputs, .. } | Op::Dot { inputs, .. } => {
+            Op::UnsafeCast { input, .. }
+            | Op::Reshape { input, .. }
+            | Op::Transpose { input, .. }
+            | Op::Slice { input, .. }
+            | Op::Gather { input, .. }
+            | Op::Broadcast { input, .. }
+            | Op::Conv2d { input, .. } => uf.union(input.0, op_i),
+            Op::LevelledOp { inputs, .. }
+            | Op::Dot { inputs, .. }
+            | Op::Concatenate { inputs, .. } => {
                 for input in inputs {
                     uf.union(input.0, op_i);
                 }
             }
             Op::Round { .. } => unreachable!("Round should have been expanded"),
+            Op::Bivariate { .. } | Op::Mul { .. } | Op::MaxPool { .. } => {
+                unreachable!("Bivariate, Mul and MaxPool should have been expanded")
+            }
         };
     }
-    if composable {
-        // Without knowledge of how outputs are forwarded to inputs, we can't do better than putting
-        // all inputs and outputs in the same partition.
-        let mut input_iter = dag.get_input_operators_iter().map(|op| op.id.0);
-        let first_inp = input_iter.next().unwrap();
-        dag.get_output_operators_iter()
-            .map(|op| op.id.0)
-            .chain(input_iter)
-            .for_each(|ind| uf.union(first_inp, ind));
+    // An output and the inputs it is fed to share their partition.
+    for (output, input) in dag.get_compositions(composable) {
+        uf.union(output.0, input.0);
     }
     Blocks::from(uf)
 }
@@ -120,14 +125,25 @@ fn only_1_partition(dag: &unparametrized::Dag) -> Partitions {
     let mut instrs_partition = vec![InstructionPartition::new(0); dag.operators.len()];
     for (op_i, op) in dag.operators.iter().enumerate() {
         match op {
-            Op::Dot { inputs, .. } | Op::LevelledOp { inputs, .. } => {
+            Op::Dot { inputs, .. }
+            | Op::LevelledOp { inputs, .. }
+            | Op::Concatenate { inputs, .. } => {
                 instrs_partition[op_i].inputs_transition = vec![None; inputs.len()];
             }
-            Op::Lut { .. } | Op::UnsafeCast { .. } => {
+            Op::Lut { .. }
+            | Op::UnsafeCast { .. }
+            | Op::Reshape { .. }
+            | Op::Transpose { .. }
+            | Op::Slice { .. }
+            | Op::Gather { .. }
+            | Op::Broadcast { .. }
+            | Op::Conv2d { .. } => {
                 instrs_partition[op_i].inputs_transition = vec![None];
             }
             Op::Input { .. } => (),
-            Op::Round { .. } => unreachable!(),
+            Op::Round { .. } | Op::Bivariate { .. } | Op::Mul { .. } | Op::MaxPool { .. } => {
+                unreachable!()
+            }
         }
     }
     Partitions {
@@ -136,6 +152,7 @@ fn only_1_partition(dag: &unparametrized::Dag) -> Partitions {
     }
 }
 
+#[allow(clippy::too_many_lines)]
 fn resolve_by_levelled_block(
     dag: &unparametrized::Dag,
     p_cut: &PartitionCut,
@@ -210,7 +227,9 @@ fn resolve_by_levelled_block(
                         HashSet::from([group_partition]);
                 }
             }
-            Op::LevelledOp { inputs, .. } | Op::Dot { inputs, .. } => {
+            Op::LevelledOp { inputs, .. }
+            | Op::Dot { inputs, .. }
+            | Op::Concatenate { inputs, .. } => {
                 instrs_p[op_i].instruction_partition = group_partition;
                 instrs_p[op_i].inputs_transition = vec![None; inputs.len()];
                 for (i, input) in inputs.iter().enumerate() {
@@ -222,7 +241,13 @@ fn resolve_by_levelled_block(
                     }
                 }
             }
-            Op::UnsafeCast { input, .. } => {
+            Op::UnsafeCast { input, .. }
+            | Op::Reshape { input, .. }
+            | Op::Transpose { input, .. }
+            | Op::Slice { input, .. }
+            | Op::Gather { input, .. }
+            | Op::Broadcast { input, .. }
+            | Op::Conv2d { input, .. } => {
                 instrs_p[op_i].instruction_partition = group_partition;
                 let input_partition = instrs_p[input.0].instruction_partition;
                 instrs_p[op_i].inputs_transition = if group_partition == input_partition {
@@ -235,6 +260,9 @@ fn resolve_by_levelled_block(
             }
             Operator::Input { .. } => instrs_p[op_i].instruction_partition = group_partition,
             Op::Round { .. } => unreachable!("Round should have been expanded"),
+            Op::Bivariate { .. } | Op::Mul { .. } | Op::MaxPool { .. } => {
+                unreachable!("Bivariate, Mul and MaxPool should have been expanded")
+            }
         }
     }
     Partitions {
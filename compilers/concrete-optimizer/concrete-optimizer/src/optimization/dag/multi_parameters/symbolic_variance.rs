@@ -1,5 +1,6 @@
 use std::fmt;
 
+use crate::optimization::dag::element_variances::ClassVariance;
 use crate::optimization::dag::multi_parameters::operations_value::OperationsValue;
 
 /**
@@ -186,6 +187,12 @@ impl SymbolicVariance {
     }
 }
 
+impl ClassVariance for SymbolicVariance {
+    fn worst(&self, other: &Self) -> Self {
+        self.max(other)
+    }
+}
+
 impl fmt::Display for SymbolicVariance {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         if self == &Self::ZERO {
@@ -2,12 +2,17 @@ use super::symbolic_variance::{SymbolicVariance, VarianceOrigin};
 use crate::dag::operator::{
     dot_kind, LevelledComplexity, Operator, OperatorIndex, Precision, Shape,
 };
-use crate::dag::rewrite::round::expand_round;
+use crate::dag::rewrite::expand::expand_operators;
 use crate::dag::unparametrized::Dag;
 use crate::noise_estimator::error;
 use crate::noise_estimator::p_error::{combine_errors, repeat_p_error};
-use crate::optimization::config::NoiseBoundConfig;
+use crate::optimization::config::{NoiseBoundConfig, NoiseTracking};
+use crate::optimization::dag::element_variances::{self, ElementVariances};
 use crate::utils::square;
+use concrete_cpu_noise_model::gaussian_noise::breakdown::NoiseBreakdown;
+use concrete_cpu_noise_model::gaussian_noise::error_probability::{
+    error_probability_with_gaussian, gaussian_noise_budget,
+};
 use dot_kind::DotKind;
 use std::collections::{HashMap, HashSet};
 
@@ -15,53 +20,9 @@ pub fn first<'a, Property>(inputs: &[OperatorIndex], properties: &'a [Property])
     &properties[inputs[0].0]
 }
 
-fn assert_all_same<Property: PartialEq + std::fmt::Debug>(
-    inputs: &[OperatorIndex],
-    properties: &[Property],
-) {
-    let first = first(inputs, properties);
-    for input in inputs.iter().skip(1) {
-        assert_eq!(first, &properties[input.0]);
-    }
-}
-
-fn assert_inputs_uniform_precisions(op: &Operator, out_precisions: &[Precision]) {
-    if let Operator::Dot { inputs, .. } | Operator::LevelledOp { inputs, .. } = op {
-        assert_all_same(inputs, out_precisions);
-    }
-}
-
-fn assert_dot_uniform_inputs_shape(op: &Operator, out_shapes: &[Shape]) {
-    if let Operator::Dot { inputs, .. } = op {
-        assert_all_same(inputs, out_shapes);
-    }
-}
-
-fn assert_non_empty_inputs(op: &Operator) {
-    if let Operator::Dot { inputs, .. } | Operator::LevelledOp { inputs, .. } = op {
-        assert!(!inputs.is_empty());
-    }
-}
-
-fn assert_inputs_index(op: &Operator, first_bad_index: usize) {
-    let valid = match op {
-        Operator::Input { .. } => true,
-        Operator::Lut { input, .. }
-        | Operator::UnsafeCast { input, .. }
-        | Operator::Round { input, .. } => input.0 < first_bad_index,
-        Operator::LevelledOp { inputs, .. } | Operator::Dot { inputs, .. } => {
-            inputs.iter().all(|input| input.0 < first_bad_index)
-        }
-    };
-    assert!(valid, "Invalid dag, bad index in op: {op:?}");
-}
-
 fn assert_dag_correctness(dag: &Dag) {
-    for (i, op) in dag.operators.iter().enumerate() {
-        assert_non_empty_inputs(op);
-        assert_inputs_uniform_precisions(op, &dag.out_precisions);
-        assert_dot_uniform_inputs_shape(op, &dag.out_shapes);
-        assert_inputs_index(op, i);
+    if let Err(err) = dag.validate() {
+        panic!("Invalid dag, {err}");
     }
 }
 
@@ -74,6 +35,15 @@ pub fn has_round(dag: &Dag) -> bool {
     false
 }
 
+pub fn has_composite_operator(dag: &Dag) -> bool {
+    dag.operators.iter().any(|op| {
+        matches!(
+            op,
+            Operator::Bivariate { .. } | Operator::Mul { .. } | Operator::MaxPool { .. }
+        )
+    })
+}
+
 pub fn has_unsafe_cast(dag: &Dag) -> bool {
     for op in &dag.operators {
         if matches!(op, Operator::UnsafeCast { .. }) {
@@ -180,10 +150,25 @@ fn out_variance(
                 DotKind::Unsupported { .. } => panic!("Unsupported"),
             }
         }
-        Operator::UnsafeCast { input, .. } => out_variances[input.0],
+        Operator::UnsafeCast { input, .. }
+        | Operator::Reshape { input, .. }
+        | Operator::Transpose { input, .. }
+        | Operator::Slice { input, .. }
+        | Operator::Gather { input, .. }
+        | Operator::Broadcast { input, .. } => out_variances[input.0],
+        Operator::Concatenate { inputs, .. } => inputs
+            .iter()
+            .map(|input| out_variances[input.0])
+            .fold(SymbolicVariance::ZERO, |max, variance| max.max(&variance)),
+        Operator::Conv2d { input, weights, .. } => {
+            out_variances[input.0] * weights.max_first_dim_square_norm2() as f64
+        }
         Operator::Round { .. } => {
             unreachable!("Round should have been either expanded or integrated to a lut")
         }
+        Operator::Bivariate { .. } | Operator::Mul { .. } | Operator::MaxPool { .. } => {
+            unreachable!("Bivariate, Mul and MaxPool should have been expanded")
+        }
     }
 }
 
@@ -197,27 +182,68 @@ pub fn out_variances(dag: &Dag) -> Vec<SymbolicVariance> {
     out_variances
 }
 
+pub fn out_element_variances(
+    dag: &Dag,
+    out_variances: &[SymbolicVariance],
+    noise_tracking: NoiseTracking,
+) -> Vec<ElementVariances<SymbolicVariance>> {
+    let mut out_element_variances: Vec<ElementVariances<SymbolicVariance>> =
+        Vec::with_capacity(dag.operators.len());
+    for (i, op) in dag.operators.iter().enumerate() {
+        let out_shape = &dag.out_shapes[i];
+        let classes = match noise_tracking {
+            NoiseTracking::PerTensor => {
+                ElementVariances::uniform(out_shape.flat_size(), out_variances[i])
+            }
+            NoiseTracking::PerElementClass => element_variances::out_element_variances(
+                op,
+                &dag.out_shapes,
+                out_shape,
+                &out_variances[i],
+                |input| out_element_variances[input.0].clone(),
+            ),
+        };
+        out_element_variances.push(classes);
+    }
+    out_element_variances
+}
+
 fn extra_final_variances(
     dag: &Dag,
+    out_element_variances: &[ElementVariances<SymbolicVariance>],
     out_variances: &[SymbolicVariance],
-) -> Vec<(Precision, Shape, SymbolicVariance)> {
+) -> Vec<(
+    Precision,
+    ElementVariances<SymbolicVariance>,
+    SymbolicVariance,
+)> {
     dag.get_output_operators_iter()
-        .map(|op| (*op.precision, op.shape.to_owned(), out_variances[op.id.0]))
+        .map(|op| {
+            (
+                *op.precision,
+                out_element_variances[op.id.0].clone(),
+                out_variances[op.id.0],
+            )
+        })
         .collect()
 }
 
 fn in_luts_variance(
     dag: &Dag,
+    out_element_variances: &[ElementVariances<SymbolicVariance>],
     out_variances: &[SymbolicVariance],
-) -> Vec<(Precision, Shape, SymbolicVariance)> {
+) -> Vec<(
+    Precision,
+    ElementVariances<SymbolicVariance>,
+    SymbolicVariance,
+)> {
     dag.operators
         .iter()
-        .enumerate()
-        .filter_map(|(i, op)| {
+        .filter_map(|op| {
             if let &Operator::Lut { input, .. } = op {
                 Some((
                     dag.out_precisions[input.0],
-                    dag.out_shapes[i].clone(),
+                    out_element_variances[input.0].clone(),
                     out_variances[input.0],
                 ))
             } else {
@@ -227,7 +253,11 @@ fn in_luts_variance(
         .collect()
 }
 
-fn op_levelled_complexity(op: &Operator, out_shapes: &[Shape]) -> LevelledComplexity {
+fn op_levelled_complexity(
+    op: &Operator,
+    op_out_shape: &Shape,
+    out_shapes: &[Shape],
+) -> LevelledComplexity {
     match op {
         Operator::Dot {
             inputs, weights, ..
@@ -244,20 +274,34 @@ fn op_levelled_complexity(op: &Operator, out_shapes: &[Shape]) -> LevelledComple
                 DotKind::Unsupported { .. } => panic!("Unsupported"),
             }
         }
-        Operator::LevelledOp { complexity, .. } => *complexity,
-        Operator::Input { .. } | Operator::Lut { .. } | Operator::UnsafeCast { .. } => {
-            LevelledComplexity::ZERO
+        Operator::Conv2d { weights, .. } => {
+            let filter_size = weights.shape.erase_first_dim().flat_size();
+            let out_size = op_out_shape.flat_size();
+            LevelledComplexity::ADDITION * out_size * filter_size
         }
+        Operator::LevelledOp { complexity, .. } => *complexity,
+        Operator::Input { .. }
+        | Operator::Lut { .. }
+        | Operator::UnsafeCast { .. }
+        | Operator::Reshape { .. }
+        | Operator::Transpose { .. }
+        | Operator::Concatenate { .. }
+        | Operator::Slice { .. }
+        | Operator::Gather { .. }
+        | Operator::Broadcast { .. } => LevelledComplexity::ZERO,
         Operator::Round { .. } => {
             unreachable!("Round should have been either expanded or integrated to a lut")
         }
+        Operator::Bivariate { .. } | Operator::Mul { .. } | Operator::MaxPool { .. } => {
+            unreachable!("Bivariate, Mul and MaxPool should have been expanded")
+        }
     }
 }
 
 pub fn levelled_complexity(dag: &Dag) -> LevelledComplexity {
     let mut levelled_complexity = LevelledComplexity::ZERO;
-    for op in &dag.operators {
-        levelled_complexity += op_levelled_complexity(op, &dag.out_shapes);
+    for (op, out_shape) in dag.operators.iter().zip(&dag.out_shapes) {
+        levelled_complexity += op_levelled_complexity(op, out_shape, &dag.out_shapes);
     }
     levelled_complexity
 }
@@ -269,6 +313,12 @@ pub fn lut_count_from_dag(dag: &Dag) -> u64 {
             count += dag.out_shapes[i].flat_size();
         } else if let Operator::Round { out_precision, .. } = op {
             count += dag.out_shapes[i].flat_size() * (dag.out_precisions[i] - out_precision) as u64;
+        } else if let Operator::Bivariate { .. } = op {
+            count += dag.out_shapes[i].flat_size();
+        } else if let Operator::Mul { .. } = op {
+            count += 2 * dag.out_shapes[i].flat_size();
+        } else if let Operator::MaxPool { window, .. } = op {
+            count += dag.out_shapes[i].flat_size() * (window.flat_size() - 1);
         }
     }
     count
@@ -284,8 +334,16 @@ pub fn safe_noise_bound(precision: Precision, noise_config: &NoiseBoundConfig) -
 
 fn constraints_by_precisions(
     out_precisions: &[Precision],
-    final_variances: &[(Precision, Shape, SymbolicVariance)],
-    in_luts_variance: &[(Precision, Shape, SymbolicVariance)],
+    final_variances: &[(
+        Precision,
+        ElementVariances<SymbolicVariance>,
+        SymbolicVariance,
+    )],
+    in_luts_variance: &[(
+        Precision,
+        ElementVariances<SymbolicVariance>,
+        SymbolicVariance,
+    )],
     noise_config: &NoiseBoundConfig,
 ) -> Vec<VariancesAndBound> {
     let precisions: HashSet<Precision> = out_precisions.iter().copied().collect();
@@ -319,18 +377,19 @@ fn select_precision<T1: Clone, T2: Copy>(
 }
 
 fn counted_symbolic_variance(
-    symbolic_variances: &[(Shape, SymbolicVariance)],
+    symbolic_variances: &[(ElementVariances<SymbolicVariance>, SymbolicVariance)],
 ) -> Vec<(u64, SymbolicVariance)> {
     pub fn exact_key(v: &SymbolicVariance) -> (u64, u64) {
         (v.lut_coeff.to_bits(), v.input_coeff.to_bits())
     }
+    let all_classes = || symbolic_variances.iter().flat_map(|(e, _)| &e.classes);
     let mut count: HashMap<(u64, u64), u64> = HashMap::new();
-    for (s, v) in symbolic_variances {
-        *count.entry(exact_key(v)).or_insert(0) += s.flat_size();
+    for (c, v) in all_classes() {
+        *count.entry(exact_key(v)).or_insert(0) += c;
     }
     let mut res = Vec::new();
     res.reserve_exact(count.len());
-    for (_s, v) in symbolic_variances {
+    for (_c, v) in all_classes() {
         if let Some(c) = count.remove(&exact_key(v)) {
             res.push((c, *v));
         }
@@ -340,8 +399,16 @@ fn counted_symbolic_variance(
 
 fn constraint_for_one_precision(
     target_precision: Precision,
-    extra_final_variances: &[(Precision, Shape, SymbolicVariance)],
-    in_luts_variance: &[(Precision, Shape, SymbolicVariance)],
+    extra_final_variances: &[(
+        Precision,
+        ElementVariances<SymbolicVariance>,
+        SymbolicVariance,
+    )],
+    in_luts_variance: &[(
+        Precision,
+        ElementVariances<SymbolicVariance>,
+        SymbolicVariance,
+    )],
     safe_noise_bound: f64,
 ) -> VariancesAndBound {
     let extra_finals_variance = select_precision(target_precision, extra_final_variances);
@@ -349,9 +416,9 @@ fn constraint_for_one_precision(
     let nb_luts = in_luts_variance.len() as u64;
     let all_output = counted_symbolic_variance(&extra_finals_variance);
     let all_in_lut = counted_symbolic_variance(&in_luts_variance);
-    let remove_shape = |t: &(Shape, SymbolicVariance)| t.1;
-    let extra_finals_variance = extra_finals_variance.iter().map(remove_shape).collect();
-    let in_luts_variance = in_luts_variance.iter().map(remove_shape).collect();
+    let remove_classes = |t: &(ElementVariances<SymbolicVariance>, SymbolicVariance)| t.1;
+    let extra_finals_variance = extra_finals_variance.iter().map(remove_classes).collect();
+    let in_luts_variance = in_luts_variance.iter().map(remove_classes).collect();
     let pareto_vfs_final = SymbolicVariance::reduce_to_pareto_front(extra_finals_variance);
     let pareto_vfs_in_lut = SymbolicVariance::reduce_to_pareto_front(in_luts_variance);
     VariancesAndBound {
@@ -369,10 +436,12 @@ pub fn worst_log_norm_for_wop(dag: &Dag) -> f64 {
     assert_dag_correctness(dag);
     assert_no_round(dag);
     let out_variances = out_variances(dag);
-    let in_luts_variance = in_luts_variance(dag, &out_variances);
+    let out_element_variances =
+        out_element_variances(dag, &out_variances, NoiseTracking::PerTensor);
+    let in_luts_variance = in_luts_variance(dag, &out_element_variances, &out_variances);
     let coeffs = in_luts_variance
         .iter()
-        .map(|(_precision, _shape, symbolic_variance)| {
+        .map(|(_precision, _classes, symbolic_variance)| {
             symbolic_variance.lut_coeff + symbolic_variance.input_coeff
         })
         .filter(|v| *v >= 1.0);
@@ -382,12 +451,14 @@ pub fn worst_log_norm_for_wop(dag: &Dag) -> f64 {
 
 pub fn analyze(dag: &Dag, noise_config: &NoiseBoundConfig) -> SoloKeyDag {
     assert_dag_correctness(dag);
-    let dag = &expand_round(dag);
+    let dag = &expand_operators(dag);
     assert_no_round(dag);
     let out_variances = out_variances(dag);
-    let in_luts_variance = in_luts_variance(dag, &out_variances);
+    let out_element_variances =
+        out_element_variances(dag, &out_variances, noise_config.noise_tracking);
+    let in_luts_variance = in_luts_variance(dag, &out_element_variances, &out_variances);
     let nb_luts = lut_count_from_dag(dag);
-    let extra_final_variances = extra_final_variances(dag, &out_variances);
+    let extra_final_variances = extra_final_variances(dag, &out_element_variances, &out_variances);
     let levelled_complexity = levelled_complexity(dag);
     let constraints_by_precisions = constraints_by_precisions(
         &dag.out_precisions,
@@ -412,15 +483,57 @@ fn max_update(current: &mut f64, candidate: f64) {
     }
 }
 
-// Compute the maximum attained variance for the full dag
-// TODO take a noise summary => peek_error or global error
+/// The modulus switching noise added in the luts.
+#[derive(Clone, Debug)]
+pub struct ModulusSwitchingNoise {
+    pub variance: f64,
+    /// With the exact tail, the modulus switching terms and, per constraint, the largest variance
+    /// in its luts, modulus switching included, keeping its error probability below the bound.
+    exact_tail: Option<(NoiseBreakdown, Vec<f64>)>,
+}
+
+impl ModulusSwitchingNoise {
+    /// A gaussian noise of `variance`.
+    pub fn gaussian(variance: f64) -> Self {
+        Self {
+            variance,
+            exact_tail: None,
+        }
+    }
+
+    fn lut_variance_bound(&self, i_constraint: usize, constraint: &VariancesAndBound) -> f64 {
+        self.exact_tail
+            .as_ref()
+            .map_or(constraint.safe_variance_bound, |(_, bounds)| {
+                bounds[i_constraint]
+            })
+    }
+
+    /// Error probability of a variance in a lut, modulus switching included.
+    fn lut_p_error(&self, constraint: &VariancesAndBound, variance: f64, kappa: f64) -> f64 {
+        match &self.exact_tail {
+            None => {
+                p_error_from_relative_variance(variance / constraint.safe_variance_bound, kappa)
+            }
+            Some((modulus_switching, _)) => error_probability_with_gaussian(
+                modulus_switching,
+                variance - self.variance,
+                error::max_error_bound_2padbits(constraint.precision as u64),
+            ),
+        }
+    }
+}
+
+// Compute the maximum attained variance for the full dag, relative to its bound
+// Returns the relative variance, the variance and whether it is in a lut
 fn peak_variance_per_constraint(
     constraint: &VariancesAndBound,
+    lut_variance_bound: f64,
     input_noise_out: f64,
     blind_rotate_noise_out: f64,
     noise_keyswitch: f64,
     noise_modulus_switching: f64,
-) -> f64 {
+) -> (f64, f64, bool) {
     assert!(input_noise_out < blind_rotate_noise_out || blind_rotate_noise_out == 0.0);
     // the maximal variance encountered as an output that can be decrypted
     let mut variance_output = 0.0;
@@ -430,8 +543,9 @@ fn peak_variance_per_constraint(
             vf.eval(input_noise_out, blind_rotate_noise_out),
         );
     }
+    let relative_output = variance_output / constraint.safe_variance_bound;
     if constraint.pareto_in_lut.is_empty() {
-        return variance_output;
+        return (relative_output, variance_output, false);
     }
     // the maximal variance encountered during a lut computation
     let mut variance_in_lut = 0.0;
@@ -442,36 +556,12 @@ fn peak_variance_per_constraint(
         );
     }
     let peek_in_lut = variance_in_lut + noise_keyswitch + noise_modulus_switching;
-    peek_in_lut.max(variance_output)
-}
-
-// Compute the maximum attained relative variance for the full dag
-fn peak_relative_variance(
-    dag: &SoloKeyDag,
-    input_noise_out: f64,
-    blind_rotate_noise_out: f64,
-    noise_keyswitch: f64,
-    noise_modulus_switching: f64,
-) -> (f64, f64) {
-    assert!(!dag.constraints_by_precisions.is_empty());
-    assert!(input_noise_out <= blind_rotate_noise_out);
-    let mut max_relative_var = 0.0;
-    let mut safe_noise = 0.0;
-    for ns in &dag.constraints_by_precisions {
-        let variance_max = peak_variance_per_constraint(
-            ns,
-            input_noise_out,
-            blind_rotate_noise_out,
-            noise_keyswitch,
-            noise_modulus_switching,
-        );
-        let relative_var = variance_max / ns.safe_variance_bound;
-        if max_relative_var < relative_var {
-            max_relative_var = relative_var;
-            safe_noise = ns.safe_variance_bound;
-        }
+    let relative_in_lut = peek_in_lut / lut_variance_bound;
+    if relative_in_lut >= relative_output {
+        (relative_in_lut, peek_in_lut, true)
+    } else {
+        (relative_output, variance_output, false)
     }
-    (max_relative_var, safe_noise)
 }
 
 pub fn p_error_from_relative_variance(relative_variance: f64, kappa: f64) -> f64 {
@@ -484,7 +574,7 @@ fn p_error_per_constraint(
     input_noise_out: f64,
     blind_rotate_noise_out: f64,
     noise_keyswitch: f64,
-    noise_modulus_switching: f64,
+    modulus_switching: &ModulusSwitchingNoise,
     kappa: f64,
 ) -> f64 {
     // Note: no log probability to keep accuracy near 0, 0 is a fine answer when p_success is very small.
@@ -501,9 +591,11 @@ fn p_error_per_constraint(
     for &(count, vf) in &constraint.all_in_lut {
         assert!(0 < count);
         let variance = vf.eval(input_noise_out, blind_rotate_noise_out);
-        let relative_variance =
-            (variance + noise_keyswitch + noise_modulus_switching) / constraint.safe_variance_bound;
-        let vf_p_error = p_error_from_relative_variance(relative_variance, kappa);
+        let vf_p_error = modulus_switching.lut_p_error(
+            constraint,
+            variance + noise_keyswitch + modulus_switching.variance,
+            kappa,
+        );
 
         p_error = combine_errors(p_error, repeat_p_error(vf_p_error, count));
     }
@@ -511,32 +603,92 @@ fn p_error_per_constraint(
 }
 
 impl SoloKeyDag {
+    /// The modulus switching noise of the exact tail of `modulus_switching`, `None` when it alone
+    /// exceeds the error probability bound in a lut.
+    pub fn exact_tail_modulus_switching(
+        &self,
+        modulus_switching: NoiseBreakdown,
+        maximum_acceptable_error_probability: f64,
+    ) -> Option<ModulusSwitchingNoise> {
+        let variance = modulus_switching.total();
+        let lut_variance_bounds = self
+            .constraints_by_precisions
+            .iter()
+            .map(|constraint| {
+                if constraint.pareto_in_lut.is_empty() {
+                    return Some(f64::INFINITY);
+                }
+                gaussian_noise_budget(
+                    &modulus_switching,
+                    error::max_error_bound_2padbits(constraint.precision as u64),
+                    maximum_acceptable_error_probability,
+                )
+                .map(|budget| budget + variance)
+            })
+            .collect::<Option<_>>()?;
+        Some(ModulusSwitchingNoise {
+            variance,
+            exact_tail: Some((modulus_switching, lut_variance_bounds)),
+        })
+    }
+
+    // The maximum relative variance of the full dag, its variance and error probability
+    fn peak(
+        &self,
+        input_noise_out: f64,
+        blind_rotate_noise_out: f64,
+        noise_keyswitch: f64,
+        modulus_switching: &ModulusSwitchingNoise,
+        kappa: f64,
+    ) -> (f64, f64, f64) {
+        assert!(!self.constraints_by_precisions.is_empty());
+        assert!(input_noise_out <= blind_rotate_noise_out);
+        let mut peak = (0.0, 0.0, f64::NAN);
+        for (i, ns) in self.constraints_by_precisions.iter().enumerate() {
+            let (relative_variance, variance, in_lut) = peak_variance_per_constraint(
+                ns,
+                modulus_switching.lut_variance_bound(i, ns),
+                input_noise_out,
+                blind_rotate_noise_out,
+                noise_keyswitch,
+                modulus_switching.variance,
+            );
+            if peak.0 < relative_variance {
+                let p_error = if in_lut {
+                    modulus_switching.lut_p_error(ns, variance, kappa)
+                } else {
+                    p_error_from_relative_variance(relative_variance, kappa)
+                };
+                peak = (relative_variance, variance, p_error);
+            }
+        }
+        peak
+    }
+
     pub fn peek_p_error(
         &self,
         input_noise_out: f64,
         blind_rotate_noise_out: f64,
         noise_keyswitch: f64,
-        noise_modulus_switching: f64,
+        modulus_switching: &ModulusSwitchingNoise,
         kappa: f64,
     ) -> (f64, f64) {
-        let (relative_var, variance_bound) = peak_relative_variance(
-            self,
+        let (_, variance, p_error) = self.peak(
             input_noise_out,
             blind_rotate_noise_out,
             noise_keyswitch,
-            noise_modulus_switching,
+            modulus_switching,
+            kappa,
         );
-        (
-            p_error_from_relative_variance(relative_var, kappa),
-            relative_var * variance_bound,
-        )
+        (p_error, variance)
     }
+
     pub fn global_p_error(
         &self,
         input_noise_out: f64,
         blind_rotate_noise_out: f64,
         noise_keyswitch: f64,
-        noise_modulus_switching: f64,
+        modulus_switching: &ModulusSwitchingNoise,
         kappa: f64,
     ) -> f64 {
         let mut p_error = 0.0;
@@ -546,7 +698,7 @@ impl SoloKeyDag {
                 input_noise_out,
                 blind_rotate_noise_out,
                 noise_keyswitch,
-                noise_modulus_switching,
+                modulus_switching,
                 kappa,
             );
 
@@ -561,17 +713,18 @@ impl SoloKeyDag {
         input_noise_out: f64,
         blind_rotate_noise_out: f64,
         noise_keyswitch: f64,
-        noise_modulus_switching: f64,
+        modulus_switching: &ModulusSwitchingNoise,
     ) -> bool {
-        for ns in &self.constraints_by_precisions {
-            if peak_variance_per_constraint(
+        for (i, ns) in self.constraints_by_precisions.iter().enumerate() {
+            let (relative_variance, _, _) = peak_variance_per_constraint(
                 ns,
+                modulus_switching.lut_variance_bound(i, ns),
                 input_noise_out,
                 blind_rotate_noise_out,
                 noise_keyswitch,
-                noise_modulus_switching,
-            ) > ns.safe_variance_bound
-            {
+                modulus_switching.variance,
+            );
+            if relative_variance > 1.0 {
                 return false;
             }
         }
@@ -614,6 +767,7 @@ pub mod tests {
         security_level: 128,
         ciphertext_modulus_log: 64,
         maximum_acceptable_error_probability: _4_SIGMA,
+        noise_tracking: NoiseTracking::PerTensor,
     };
 
     fn analyze(dag: &Dag) -> super::SoloKeyDag {
@@ -844,6 +998,223 @@ pub mod tests {
         }
     }
 
+    #[test]
+    fn test_mul() {
+        let mut graph = Dag::new();
+        let input1 = graph.add_input(3, Shape::vector(2));
+        let input2 = graph.add_input(3, Shape::vector(2));
+        let lut2 = graph.add_lut(input2, FunctionTable::UNKWOWN, 3);
+        let product = graph.add_mul(input1, lut2, 6);
+        let analysis = analyze(&graph);
+        // the squares of the sum and the difference
+        assert_eq!(analysis.nb_luts, 2 * 2 + 2);
+        assert_eq!(graph.out_precisions[product.0], 6);
+        let out_variance = analysis.out_variances.last().unwrap();
+        assert_f64_eq(out_variance.input_coeff, 0.0);
+        assert_f64_eq(out_variance.lut_coeff, 2.0);
+        let in_lut_4_bits = analysis
+            .constraints_by_precisions
+            .iter()
+            .find(|constraint| constraint.precision == 4)
+            .unwrap();
+        assert_eq!(in_lut_4_bits.pareto_in_lut.len(), 1);
+        assert_f64_eq(in_lut_4_bits.pareto_in_lut[0].input_coeff, 1.0);
+        assert_f64_eq(in_lut_4_bits.pareto_in_lut[0].lut_coeff, 1.0);
+    }
+
+    #[test]
+    fn test_bivariate_lut() {
+        let mut graph = Dag::new();
+        let input1 = graph.add_input(3, Shape::number());
+        let input2 = graph.add_input(2, Shape::number());
+        let _ = graph.add_bivariate_lut(input1, input2, FunctionTable::UNKWOWN, 3);
+        let analysis = analyze(&graph);
+        assert_eq!(analysis.nb_luts, 1);
+        // the packed lut input
+        let in_lut_5_bits = analysis
+            .constraints_by_precisions
+            .iter()
+            .find(|constraint| constraint.precision == 5)
+            .unwrap();
+        assert_f64_eq(in_lut_5_bits.pareto_in_lut[0].input_coeff, 16.0 + 1.0);
+        assert_f64_eq(in_lut_5_bits.pareto_in_lut[0].lut_coeff, 0.0);
+    }
+
+    #[test]
+    fn test_max_pool() {
+        let mut graph = Dag::new();
+        let input1 = graph.add_input(
+            3,
+            Shape {
+                dimensions_size: vec![4, 4],
+            },
+        );
+        let max_pool = graph.add_max_pool(
+            input1,
+            Shape {
+                dimensions_size: vec![2, 2],
+            },
+        );
+        assert_eq!(graph.out_shapes[max_pool.0].dimensions_size, vec![2, 2]);
+        let analysis = analyze(&graph);
+        // 3 max of pairs per window
+        assert_eq!(analysis.nb_luts, 4 * 3);
+        // each level adds the noise of a relu
+        let out_variance = analysis.out_variances.last().unwrap();
+        assert_f64_eq(out_variance.input_coeff, 1.0);
+        assert_f64_eq(out_variance.lut_coeff, 2.0);
+        let in_lut_4_bits = analysis
+            .constraints_by_precisions
+            .iter()
+            .find(|constraint| constraint.precision == 4)
+            .unwrap();
+        let worst_in_lut = in_lut_4_bits.pareto_in_lut.last().unwrap();
+        assert_f64_eq(worst_in_lut.input_coeff, 2.0);
+        assert_f64_eq(worst_in_lut.lut_coeff, 2.0);
+    }
+
+    #[test]
+    fn test_max_pool_keeps_selected_noise() {
+        let mut graph = Dag::new();
+        let input1 = graph.add_input(3, Shape::vector(4));
+        let scaled = graph.add_dot([input1], Weights::number(3));
+        let _ = graph.add_max_pool(scaled, Shape::vector(2));
+        let analysis = analyze(&graph);
+        // the selected elements keep the noise of the scaled input
+        let out_variance = analysis.out_variances.last().unwrap();
+        assert_f64_eq(out_variance.input_coeff, 9.0);
+        assert_f64_eq(out_variance.lut_coeff, 1.0);
+    }
+
+    #[test]
+    fn test_shape_operators() {
+        let mut graph = Dag::new();
+        let image_shape = Shape {
+            dimensions_size: vec![1, 2, 4, 4],
+        };
+        let input1 = graph.add_input(3, &image_shape);
+        let lut = graph.add_lut(input1, FunctionTable::UNKWOWN, 3);
+        let scaled = graph.add_dot([input1], Weights::number(2));
+        let concat = graph.add_concatenate([lut, scaled], 1);
+        let transposed = graph.add_transpose(concat, [0, 1, 3, 2]);
+        let weights = Weights {
+            shape: Shape {
+                dimensions_size: vec![2, 4, 1, 1],
+            },
+            values: vec![1, 1, 0, 0, 3, 0, 0, 1],
+        };
+        let conv = graph.add_conv2d(transposed, weights, [1, 1], [0; 4], [1, 1], 1);
+        let analysis = analyze(&graph);
+        let concat_variance = analysis.out_variances[concat.0];
+        assert_f64_eq(concat_variance.input_coeff, 4.0);
+        assert_f64_eq(concat_variance.lut_coeff, 1.0);
+        assert_eq!(analysis.out_variances[transposed.0], concat_variance);
+        // the second filter has the biggest norm
+        let conv_variance = analysis.out_variances[conv.0];
+        assert_f64_eq(conv_variance.input_coeff, 40.0);
+        assert_f64_eq(conv_variance.lut_coeff, 10.0);
+        assert_eq!(graph.out_shapes[conv.0].dimensions_size, vec![1, 2, 4, 4]);
+        // a dot on the input and the 4 additions of each convolution output
+        assert_f64_eq(
+            analysis.levelled_complexity.lwe_dim_cost_factor,
+            (image_shape.flat_size() + 32 * 4) as f64,
+        );
+    }
+
+    #[test]
+    fn test_per_element_class_noise() {
+        let mut graph = Dag::new();
+        let image_shape = Shape {
+            dimensions_size: vec![1, 2, 4, 4],
+        };
+        let input1 = graph.add_input(3, &image_shape);
+        let lut1 = graph.add_lut(input1, FunctionTable::UNKWOWN, 3);
+        let weights = Weights {
+            shape: Shape {
+                dimensions_size: vec![2, 2, 1, 1],
+            },
+            values: vec![1, 0, 3, 0],
+        };
+        let conv = graph.add_conv2d(lut1, weights, [1, 1], [0; 4], [1, 1], 1);
+        let shifted = graph.add_dot([conv, lut1], Weights::vector([1, 2]));
+        _ = graph.add_lut(shifted, FunctionTable::UNKWOWN, 3);
+        let lut_variance = |lut_coeff| SymbolicVariance {
+            lut_coeff,
+            input_coeff: 0.0,
+        };
+        let per_tensor = analyze(&graph).constraint();
+        assert_eq!(
+            per_tensor.all_in_lut,
+            vec![(32, SymbolicVariance::INPUT), (32, lut_variance(13.0))]
+        );
+        let config = NoiseBoundConfig {
+            noise_tracking: NoiseTracking::PerElementClass,
+            ..CONFIG
+        };
+        let per_class = super::analyze(&graph, &config).constraint();
+        // each output channel of the convolution has the noise of its own filter
+        assert_eq!(
+            per_class.all_in_lut,
+            vec![
+                (32, SymbolicVariance::INPUT),
+                (16, lut_variance(5.0)),
+                (16, lut_variance(13.0))
+            ]
+        );
+        assert_eq!(per_class.all_output, per_tensor.all_output);
+        assert_eq!(per_class.pareto_in_lut, per_tensor.pareto_in_lut);
+        assert_eq!(per_class.nb_luts, per_tensor.nb_luts);
+    }
+
+    #[test]
+    fn test_per_element_class_matmul() {
+        let mut graph = Dag::new();
+        let input1 = graph.add_input(3, Shape::vector(6));
+        let lut1 = graph.add_lut(input1, FunctionTable::UNKWOWN, 3);
+        let matrix = graph.add_reshape(
+            lut1,
+            Shape {
+                dimensions_size: vec![2, 3],
+            },
+        );
+        // matrix @ [[1, 1], [0, 2], [0, 2]], one column at a time
+        let column_shape = Shape {
+            dimensions_size: vec![2, 1],
+        };
+        let column0 = graph.add_dot([matrix], Weights::vector([1, 0, 0]));
+        let column0 = graph.add_reshape(column0, column_shape.clone());
+        let column1 = graph.add_dot([matrix], Weights::vector([1, 2, 2]));
+        let column1 = graph.add_reshape(column1, column_shape);
+        let matmul = graph.add_concatenate([column0, column1], 1);
+        let transposed = graph.add_transpose(matmul, [1, 0]);
+        let row_sums = graph.add_dot([transposed], Weights::vector([1, 1]));
+        let sliced = graph.add_slice(matmul, [0, 0], [2, 1], [1, 1]);
+        let gathered = graph.add_gather(matmul, 1, [1, 0, 1]);
+        let out_variances = super::out_variances(&graph);
+        let classes =
+            super::out_element_variances(&graph, &out_variances, NoiseTracking::PerElementClass);
+        let lut_variance = |lut_coeff| SymbolicVariance {
+            lut_coeff,
+            input_coeff: 0.0,
+        };
+        let classes_of = |op: OperatorIndex| classes[op.0].classes.clone();
+        // each output column has the noise of its own weights column
+        assert_eq!(
+            classes_of(matmul),
+            vec![(2, lut_variance(1.0)), (2, lut_variance(9.0))]
+        );
+        assert_eq!(out_variances[matmul.0], lut_variance(9.0));
+        assert_eq!(
+            classes_of(row_sums),
+            vec![(1, lut_variance(2.0)), (1, lut_variance(18.0))]
+        );
+        assert_eq!(classes_of(sliced), vec![(2, lut_variance(1.0))]);
+        assert_eq!(
+            classes_of(gathered),
+            vec![(4, lut_variance(9.0)), (2, lut_variance(1.0))]
+        );
+    }
+
     #[test]
     fn test_broadcast_dot_multiply_by_number() {
         let mut graph = Dag::new();
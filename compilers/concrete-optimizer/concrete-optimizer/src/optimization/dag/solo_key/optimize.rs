@@ -1,7 +1,12 @@
-use concrete_cpu_noise_model::gaussian_noise::noise::modulus_switching::estimate_modulus_switching_noise_with_binary_key;
-use concrete_security_curves::gaussian::security::minimal_variance_lwe;
+use std::collections::HashMap;
+
+use concrete_cpu_noise_model::gaussian_noise::error_probability::ErrorProbabilityModel;
+use concrete_cpu_noise_model::gaussian_noise::noise::modulus_switching::{
+    breakdown_modulus_switching_with_key, estimate_modulus_switching_noise_with_key,
+};
 
 use super::analyze;
+use super::analyze::ModulusSwitchingNoise;
 use crate::dag::operator::{LevelledComplexity, Precision};
 use crate::dag::unparametrized;
 use crate::dag::unparametrized::Dag;
@@ -27,7 +32,7 @@ fn update_best_solution_with_best_decompositions(
     internal_dim: u64,
     glwe_params: GlweParameters,
     input_noise_out: f64,
-    noise_modulus_switching: f64,
+    modulus_switching: &ModulusSwitchingNoise,
     cmux_pareto: &[CmuxComplexityNoise],
     ks_pareto: &[KsComplexityNoise],
 ) {
@@ -54,8 +59,7 @@ fn update_best_solution_with_best_decompositions(
 
         let br_variance = cmux_quantity.noise_br(internal_dim);
 
-        let not_feasible =
-            !dag.feasible(input_noise_out, br_variance, 0.0, noise_modulus_switching);
+        let not_feasible = !dag.feasible(input_noise_out, br_variance, 0.0, modulus_switching);
         if not_feasible {
             continue;
         }
@@ -70,12 +74,8 @@ fn update_best_solution_with_best_decompositions(
             }
             let ks_variance = ks_quantity.noise(input_lwe_dimension);
 
-            let not_feasible = !dag.feasible(
-                input_noise_out,
-                br_variance,
-                ks_variance,
-                noise_modulus_switching,
-            );
+            let not_feasible =
+                !dag.feasible(input_noise_out, br_variance, ks_variance, modulus_switching);
             if not_feasible {
                 continue;
             }
@@ -84,7 +84,7 @@ fn update_best_solution_with_best_decompositions(
                 input_noise_out,
                 br_variance,
                 ks_variance,
-                noise_modulus_switching,
+                modulus_switching,
                 consts.kappa,
             );
             #[allow(clippy::float_cmp)]
@@ -124,7 +124,7 @@ fn update_best_solution_with_best_decompositions(
                 input_noise_out,
                 br_variance,
                 ks_variance,
-                noise_modulus_switching,
+                modulus_switching,
                 consts.kappa,
             ),
             noise_max: best_variance,
@@ -160,11 +160,12 @@ fn update_no_luts_solution(
         return;
     }
 
+    let ignored_modulus_switching = ModulusSwitchingNoise::gaussian(CHECKED_IGNORED_NOISE);
     let (p_error, variance) = dag.peek_p_error(
         input_noise_out,
         CHECKED_IGNORED_NOISE,
         CHECKED_IGNORED_NOISE,
-        CHECKED_IGNORED_NOISE,
+        &ignored_modulus_switching,
         consts.kappa,
     );
 
@@ -189,7 +190,7 @@ fn update_no_luts_solution(
             input_noise_out,
             CHECKED_IGNORED_NOISE,
             CHECKED_IGNORED_NOISE,
-            CHECKED_IGNORED_NOISE,
+            &ignored_modulus_switching,
             consts.kappa,
         ),
         noise_max: variance,
@@ -197,7 +198,11 @@ fn update_no_luts_solution(
 }
 
 fn minimal_variance(config: &Config, glwe_params: GlweParameters) -> f64 {
-    glwe_params.minimal_variance(config.ciphertext_modulus_log, config.security_level)
+    glwe_params.minimal_variance(
+        config.security_curves,
+        config.ciphertext_modulus_log,
+        config.security_level,
+    )
 }
 
 fn optimize_no_luts(
@@ -206,11 +211,15 @@ fn optimize_no_luts(
     dag: &analyze::SoloKeyDag,
     search_space: &SearchSpace,
 ) -> OptimizationState {
-    let not_feasible = |input_noise_out| !dag.feasible(input_noise_out, 0.0, 0.0, 0.0);
+    let no_modulus_switching = ModulusSwitchingNoise::gaussian(0.0);
+    let not_feasible =
+        |input_noise_out| !dag.feasible(input_noise_out, 0.0, 0.0, &no_modulus_switching);
     let modulus_log = consts.config.ciphertext_modulus_log;
     let security_level = consts.config.security_level;
+    let security_curves = consts.config.security_curves;
     for lwe in &search_space.levelled_only_lwe_dimensions {
-        let input_noise_out = minimal_variance_lwe(lwe, modulus_log, security_level);
+        let input_noise_out =
+            security_curves.minimal_variance_lwe(lwe, modulus_log, security_level);
         if not_feasible(input_noise_out) {
             continue;
         }
@@ -224,7 +233,7 @@ fn not_feasible_macro_parameters(
     dag: &analyze::SoloKeyDag,
     internal_dim: u64,
     input_noise_out: f64,
-    noise_modulus_switching: f64,
+    modulus_switching: &ModulusSwitchingNoise,
     cmux_pareto: &[CmuxComplexityNoise],
     ks_pareto: &[KsComplexityNoise],
 ) -> bool {
@@ -234,7 +243,7 @@ fn not_feasible_macro_parameters(
         input_noise_out,
         lowest_noise_br,
         lowest_noise_ks,
-        noise_modulus_switching,
+        modulus_switching,
     )
 }
 
@@ -272,7 +281,13 @@ pub fn optimize(
         security_level,
         maximum_acceptable_error_probability: config.maximum_acceptable_error_probability,
         ciphertext_modulus_log,
+        noise_tracking: config.noise_tracking,
     };
+    if dag.validate().is_err() {
+        return OptimizationState {
+            best_solution: None,
+        };
+    }
     let &min_precision = dag.out_precisions.iter().min().unwrap();
 
     let dag = analyze::analyze(dag, &noise_config);
@@ -296,23 +311,41 @@ pub fn optimize(
     let mut state = OptimizationState {
         best_solution: None,
     };
+    if config.check_average_case().is_err() {
+        return state;
+    }
 
     if dag.nb_luts == 0 {
         return optimize_no_luts(state, &consts, &dag, search_space);
     }
     let mut caches = persistent_caches.caches();
 
-    let noise_modulus_switching = |glwe_log2_poly_size, internal_lwe_dimensions| {
-        estimate_modulus_switching_noise_with_binary_key(
-            internal_lwe_dimensions,
-            glwe_log2_poly_size,
-            ciphertext_modulus_log,
-        )
-    };
-
-    let not_feasible = |input_noise_out, noise_modulus_switching| {
-        !dag.feasible(input_noise_out, 0.0, 0.0, noise_modulus_switching)
+    let key_distribution = config.security_curves.key_distribution();
+    // `None` when the modulus switching alone exceeds the error probability bound in a lut
+    let modulus_switching = |glwe_log2_poly_size, internal_lwe_dimensions| {
+        let key = key_distribution.moments(internal_lwe_dimensions);
+        match config.error_probability_model {
+            ErrorProbabilityModel::Gaussian => Some(ModulusSwitchingNoise::gaussian(
+                estimate_modulus_switching_noise_with_key(
+                    internal_lwe_dimensions,
+                    glwe_log2_poly_size,
+                    ciphertext_modulus_log,
+                    key,
+                ),
+            )),
+            ErrorProbabilityModel::ExactTail => dag.exact_tail_modulus_switching(
+                breakdown_modulus_switching_with_key(
+                    internal_lwe_dimensions,
+                    glwe_log2_poly_size,
+                    ciphertext_modulus_log,
+                    key,
+                ),
+                config.maximum_acceptable_error_probability,
+            ),
+        }
     };
+    // the exact tail of the modulus switching does not depend on the glwe dimension
+    let mut modulus_switchings = HashMap::new();
 
     for &glwe_dim in &search_space.glwe_dimensions {
         for &glwe_log_poly_size in &search_space.glwe_log_polynomial_sizes {
@@ -327,12 +360,17 @@ pub fn optimize(
             for &internal_dim in &search_space.internal_lwe_dimensions {
                 let ks_pareto = caches.keyswitch.pareto_quantities(internal_dim);
 
-                let noise_modulus_switching =
-                    noise_modulus_switching(glwe_log_poly_size, internal_dim);
-                if not_feasible(input_noise_out, noise_modulus_switching) {
-                    // noise_modulus_switching is increasing with internal_dim
+                let Some(modulus_switching) = modulus_switchings
+                    .entry((glwe_log_poly_size, internal_dim))
+                    .or_insert_with(|| modulus_switching(glwe_log_poly_size, internal_dim))
+                    .as_ref()
+                    .filter(|modulus_switching| {
+                        dag.feasible(input_noise_out, 0.0, 0.0, modulus_switching)
+                    })
+                else {
+                    // the modulus switching noise is increasing with internal_dim
                     break;
-                }
+                };
                 if too_complex_macro_parameters(
                     &state,
                     &dag,
@@ -347,7 +385,7 @@ pub fn optimize(
                     &dag,
                     internal_dim,
                     input_noise_out,
-                    noise_modulus_switching,
+                    modulus_switching,
                     cmux_pareto,
                     ks_pareto,
                 ) {
@@ -360,7 +398,7 @@ pub fn optimize(
                     internal_dim,
                     glwe_params,
                     input_noise_out,
-                    noise_modulus_switching,
+                    modulus_switching,
                     cmux_pareto,
                     ks_pareto,
                 );
@@ -431,10 +469,11 @@ pub(crate) mod tests {
     use crate::config;
     use crate::dag::operator::{FunctionTable, Shape, Weights};
     use crate::noise_estimator::p_error::repeat_p_error;
-    use crate::optimization::config::SearchSpace;
+    use crate::optimization::config::{NoiseTracking, SearchSpace};
     use crate::optimization::dag::solo_key::symbolic_variance::VarianceOrigin;
     use crate::optimization::{atomic_pattern, decomposition};
     use crate::utils::square;
+    use crate::{ErrorProbabilityModel, NoiseMode, SecurityCurves};
 
     fn small_relative_diff(v1: f64, v2: f64) -> bool {
         f64::abs(v1 - v2) / f64::max(v1, v2) <= 0.000_000_1
@@ -464,12 +503,15 @@ pub(crate) mod tests {
         let processing_unit = config::ProcessingUnit::Cpu;
         decomposition::cache(
             128,
+            SecurityCurves::compiled(),
             processing_unit,
             None,
             true,
             CIPHERTEXT_MODULUS_LOG,
             FFT_PRECISION,
+            NoiseMode::AverageCase,
         )
+        .unwrap()
     });
 
     pub fn optimize(dag: &unparametrized::Dag) -> OptimizationState {
@@ -481,6 +523,12 @@ pub(crate) mod tests {
             fft_precision: 53,
             complexity_model: &CpuComplexity::default(),
             composable: false,
+            security_curves: SecurityCurves::compiled(),
+            noise_mode: NoiseMode::AverageCase,
+            error_probability_model: ErrorProbabilityModel::Gaussian,
+            dag_passes: &[],
+            refresh_compositions: false,
+            noise_tracking: NoiseTracking::PerTensor,
         };
 
         let search_space = SearchSpace::default_cpu();
@@ -488,6 +536,93 @@ pub(crate) mod tests {
         super::optimize(dag, config, &search_space, &SHARED_CACHES)
     }
 
+    #[test]
+    fn test_exact_tail_atomic_pattern() {
+        // every exact safe variance is a search in itself
+        let search_space = SearchSpace {
+            glwe_log_polynomial_sizes: vec![10, 11],
+            glwe_dimensions: vec![1, 2],
+            internal_lwe_dimensions: (600..900).step_by(4).collect(),
+            ..SearchSpace::default_cpu()
+        };
+        let complexity_model = CpuComplexity::default();
+        let config = |error_probability_model| Config {
+            security_level: 128,
+            maximum_acceptable_error_probability: 2_f64.powi(-40),
+            key_sharing: true,
+            ciphertext_modulus_log: 64,
+            fft_precision: 53,
+            complexity_model: &complexity_model,
+            composable: false,
+            security_curves: SecurityCurves::compiled(),
+            noise_mode: NoiseMode::AverageCase,
+            error_probability_model,
+            dag_passes: &[],
+            refresh_compositions: false,
+            noise_tracking: NoiseTracking::PerTensor,
+        };
+        for precision in [1, 4] {
+            let optimize = |model| {
+                atomic_pattern::optimize_one(
+                    1,
+                    precision,
+                    config(model),
+                    1.0,
+                    &search_space,
+                    &SHARED_CACHES,
+                )
+                .best_solution
+                .unwrap()
+            };
+            let gaussian = optimize(ErrorProbabilityModel::Gaussian);
+            let exact = optimize(ErrorProbabilityModel::ExactTail);
+            // the modulus switching is a small part of the noise, its tail barely moves the error
+            // probability
+            approx::assert_relative_eq!(exact.p_error, gaussian.p_error, max_relative = 0.05);
+            assert!(exact.complexity <= gaussian.complexity * 1.05);
+        }
+
+        // just above the gaussian error probability of the gaussian choice, its exact tail exceeds
+        // the bound and a more expensive solution is chosen
+        let gaussian = atomic_pattern::optimize_one(
+            1,
+            4,
+            config(ErrorProbabilityModel::Gaussian),
+            1.0,
+            &search_space,
+            &SHARED_CACHES,
+        )
+        .best_solution
+        .unwrap();
+        let bounded = |model| {
+            let config = Config {
+                maximum_acceptable_error_probability: gaussian.p_error * 1.001,
+                ..config(model)
+            };
+            atomic_pattern::optimize_one(1, 4, config, 1.0, &search_space, &SHARED_CACHES)
+                .best_solution
+                .unwrap()
+        };
+        approx::assert_relative_eq!(
+            bounded(ErrorProbabilityModel::Gaussian).complexity,
+            gaussian.complexity
+        );
+        let exact = bounded(ErrorProbabilityModel::ExactTail);
+        assert!(exact.p_error <= gaussian.p_error * 1.001);
+        assert!(exact.complexity > gaussian.complexity);
+
+        // the solo key optimizer takes the same exact tail on the equivalent dag
+        let config = Config {
+            maximum_acceptable_error_probability: gaussian.p_error * 1.001,
+            ..config(ErrorProbabilityModel::ExactTail)
+        };
+        let solo_key = optimize_v0(1, 4, config, 1.0, &search_space, &SHARED_CACHES)
+            .best_solution
+            .unwrap();
+        approx::assert_relative_eq!(solo_key.complexity, exact.complexity);
+        approx::assert_relative_eq!(solo_key.p_error, exact.p_error, max_relative = 1e-6);
+    }
+
     struct Times {
         worst_time: u128,
         dag_time: u128,
@@ -527,6 +662,12 @@ pub(crate) mod tests {
             fft_precision: 53,
             complexity_model: &CpuComplexity::default(),
             composable: false,
+            security_curves: SecurityCurves::compiled(),
+            noise_mode: NoiseMode::AverageCase,
+            error_probability_model: ErrorProbabilityModel::Gaussian,
+            dag_passes: &[],
+            refresh_compositions: false,
+            noise_tracking: NoiseTracking::PerTensor,
         };
 
         _ = optimize_v0(
@@ -604,6 +745,7 @@ pub(crate) mod tests {
                     security_level,
                     maximum_acceptable_error_probability: _4_SIGMA,
                     ciphertext_modulus_log: 64,
+                    noise_tracking: NoiseTracking::PerTensor,
                 },
             );
             let constraint = dag2.constraint();
@@ -626,6 +768,12 @@ pub(crate) mod tests {
             fft_precision: 53,
             complexity_model: &CpuComplexity::default(),
             composable: false,
+            security_curves: SecurityCurves::compiled(),
+            noise_mode: NoiseMode::AverageCase,
+            error_probability_model: ErrorProbabilityModel::Gaussian,
+            dag_passes: &[],
+            refresh_compositions: false,
+            noise_tracking: NoiseTracking::PerTensor,
         };
 
         let state = optimize(&dag);
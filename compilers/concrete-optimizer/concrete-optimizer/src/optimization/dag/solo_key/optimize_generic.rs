@@ -7,8 +7,9 @@ use crate::optimization::dag::solo_key::{analyze, optimize};
 use crate::optimization::decomposition::PersistDecompCaches;
 use crate::optimization::wop_atomic_pattern::optimize::optimize_one as wop_optimize;
 use crate::optimization::wop_atomic_pattern::Solution as WopSolution;
+use crate::optimization::{Err, Result};
 
-use super::analyze::{has_round, has_unsafe_cast};
+use super::analyze::{has_composite_operator, has_round, has_unsafe_cast};
 
 pub enum Solution {
     WpSolution(WpSolution),
@@ -69,7 +70,7 @@ fn optimize_with_wop_pbs(
     default_log_norm2_woppbs: f64,
     caches: &PersistDecompCaches,
 ) -> Option<WopSolution> {
-    if has_round(dag) || has_unsafe_cast(dag) {
+    if has_round(dag) || has_unsafe_cast(dag) || has_composite_operator(dag) {
         return None;
     }
     let max_precision = max_precision(dag);
@@ -81,6 +82,9 @@ fn optimize_with_wop_pbs(
         .map(|sol| updated_global_p_error_and_complexity(nb_luts, sol))
 }
 
+/// # Errors
+/// `InvalidDag`, the errors of `Config::check_security`, `NotComposable` for a composable
+/// configuration or a dag with compositions, which are not analyzed, or `NoParametersFound`.
 pub fn optimize(
     dag: &Dag,
     config: Config,
@@ -88,7 +92,14 @@ pub fn optimize(
     encoding: Encoding,
     default_log_norm2_woppbs: f64,
     caches: &PersistDecompCaches,
-) -> Option<Solution> {
+) -> Result<Solution> {
+    config.check_security()?;
+    dag.validate()?;
+    if config.composable || !dag.compositions.is_empty() {
+        return Err(Err::NotComposable(
+            "The solo key optimizer does not support compositions.".into(),
+        ));
+    }
     let native = || {
         optimize::optimize(dag, config, search_space, caches)
             .best_solution
@@ -98,9 +109,10 @@ pub fn optimize(
         optimize_with_wop_pbs(dag, config, search_space, default_log_norm2_woppbs, caches)
             .map(Solution::WopSolution)
     };
-    match encoding {
+    let solution = match encoding {
         Encoding::Auto => best_complexity_solution(native(), crt()),
         Encoding::Native => native(),
         Encoding::Crt => crt(),
-    }
+    };
+    solution.ok_or(Err::NoParametersFound)
 }
@@ -1,3 +1,4 @@
+use crate::optimization::dag::element_variances::ClassVariance;
 use std::iter::Sum;
 
 /**
@@ -79,6 +80,12 @@ impl std::ops::Mul<u64> for SymbolicVariance {
     }
 }
 
+impl ClassVariance for SymbolicVariance {
+    fn worst(&self, other: &Self) -> Self {
+        self.max(other)
+    }
+}
+
 impl SymbolicVariance {
     pub const ZERO: Self = Self {
         input_coeff: 0.0,
@@ -107,6 +114,14 @@ impl SymbolicVariance {
         manp * manp
     }
 
+    // A variance bounding both, coefficient by coefficient
+    pub fn max(&self, other: &Self) -> Self {
+        Self {
+            lut_coeff: self.lut_coeff.max(other.lut_coeff),
+            input_coeff: self.input_coeff.max(other.input_coeff),
+        }
+    }
+
     pub fn dominate_or_equal(&self, other: &Self) -> bool {
         let extra_other_minimal_base_noise = 0.0_f64.max(other.input_coeff - self.input_coeff);
         other.lut_coeff + extra_other_minimal_base_noise <= self.lut_coeff
@@ -1,6 +1,8 @@
 use std::sync::Arc;
 
-use concrete_cpu_noise_model::gaussian_noise::noise::cmux::variance_cmux;
+use concrete_cpu_noise_model::gaussian_noise::noise::cmux::variance_cmux_with_key;
+use concrete_security_curves::gaussian::curves::SecurityCurves;
+use concrete_security_curves::key_distribution::KeyDistribution;
 use serde::{Deserialize, Serialize};
 
 use crate::computing_cost::complexity_model::ComplexityModel;
@@ -32,8 +34,10 @@ pub fn pareto_quantities(
     complexity_model: &dyn ComplexityModel,
     ciphertext_modulus_log: u32,
     fft_precision: u32,
+    key_distribution: KeyDistribution,
     glwe_params: GlweParameters,
 ) -> Vec<CbComplexityNoise> {
+    let glwe_key = key_distribution.moments(glwe_params.sample_extract_lwe_dimension());
     let cmux_param = |level, log2_base| {
         let br_decomposition_parameter = BrDecompositionParameters { level, log2_base };
         CmuxParameters {
@@ -61,7 +65,7 @@ pub fn pareto_quantities(
 
             // Compute bias and slove for variance_one_external_product_for_cmux_tree_bias
             let variance = |variance_bsk| {
-                variance_cmux(
+                variance_cmux_with_key(
                     glwe_params.glwe_dimension,
                     glwe_params.polynomial_size(),
                     log2_base,
@@ -69,6 +73,7 @@ pub fn pareto_quantities(
                     ciphertext_modulus_log,
                     fft_precision,
                     variance_bsk,
+                    glwe_key,
                 )
             };
             let variance_at_0 = variance(0.0);
@@ -109,6 +114,7 @@ pub struct CbPareto {
 
 pub fn cache(
     security_level: u64,
+    security_curves: &SecurityCurves,
     processing_unit: config::ProcessingUnit,
     complexity_model: Arc<dyn ComplexityModel>,
     ciphertext_modulus_log: u32,
@@ -116,13 +122,16 @@ pub fn cache(
 ) -> PersistDecompCache {
     let cache_dir: String = default_cache_dir();
     let hardware = processing_unit.br_to_string();
+    let curves = security_curves.cache_suffix();
+    let key_distribution = security_curves.key_distribution();
     let path =
-        format!("{cache_dir}/cb-decomp-{hardware}-{ciphertext_modulus_log}-{fft_precision}-{security_level}");
+        format!("{cache_dir}/cb-decomp-{hardware}-{ciphertext_modulus_log}-{fft_precision}-{security_level}{curves}");
     let function = move |glwe_params| {
         let pareto = pareto_quantities(
             complexity_model.as_ref(),
             ciphertext_modulus_log,
             fft_precision,
+            key_distribution,
             glwe_params,
         );
 
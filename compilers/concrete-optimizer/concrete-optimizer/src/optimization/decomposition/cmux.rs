@@ -3,16 +3,21 @@ use crate::config;
 use crate::parameters::{BrDecompositionParameters, CmuxParameters, GlweParameters};
 use crate::utils::cache::ephemeral::{CacheHashMap, EphemeralCache};
 use crate::utils::cache::persistent::{default_cache_dir, PersistentCacheHashMap};
-use concrete_cpu_noise_model::gaussian_noise::noise::cmux::variance_cmux;
+use concrete_cpu_noise_model::gaussian_noise::noise::cmux::{
+    max_error_cmux, variance_cmux_with_key,
+};
+use concrete_cpu_noise_model::gaussian_noise::worst_case::{key_coefficient_bound, NoiseMode};
+use concrete_security_curves::gaussian::curves::SecurityCurves;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
-use super::common::VERSION;
+use super::common::{noise_mode_cache_suffix, VERSION};
 
 #[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct CmuxComplexityNoise {
     pub decomp: BrDecompositionParameters,
     pub complexity: f64,
+    /// The variance per cmux, or the maximal error in worst-case mode
     pub noise: f64,
 }
 
@@ -30,10 +35,17 @@ pub fn pareto_quantities(
     complexity_model: &dyn ComplexityModel,
     ciphertext_modulus_log: u32,
     fft_precision: u32,
+    security_curves: &SecurityCurves,
     security_level: u64,
+    noise_mode: NoiseMode,
     glwe_params: GlweParameters,
 ) -> Vec<CmuxComplexityNoise> {
-    let variance_bsk = glwe_params.minimal_variance(ciphertext_modulus_log, security_level);
+    let variance_bsk =
+        glwe_params.minimal_variance(security_curves, ciphertext_modulus_log, security_level);
+    let glwe_key = security_curves
+        .key_distribution()
+        .moments(glwe_params.sample_extract_lwe_dimension());
+    let glwe_key_bound = key_coefficient_bound(security_curves.key_distribution());
 
     let mut quantities = Vec::with_capacity(ciphertext_modulus_log as usize);
     let mut increasing_complexity = 0.0;
@@ -52,15 +64,28 @@ pub fn pareto_quantities(
         let range = (1..=prev_best_log2_base).rev();
 
         for log2_base in range {
-            let base_noise = variance_cmux(
-                glwe_params.glwe_dimension,
-                glwe_params.polynomial_size(),
-                log2_base,
-                level,
-                ciphertext_modulus_log,
-                fft_precision,
-                variance_bsk,
-            );
+            let base_noise = match noise_mode {
+                NoiseMode::AverageCase => variance_cmux_with_key(
+                    glwe_params.glwe_dimension,
+                    glwe_params.polynomial_size(),
+                    log2_base,
+                    level,
+                    ciphertext_modulus_log,
+                    fft_precision,
+                    variance_bsk,
+                    glwe_key,
+                ),
+                NoiseMode::WorstCase => max_error_cmux(
+                    glwe_params.glwe_dimension,
+                    glwe_params.polynomial_size(),
+                    log2_base,
+                    level,
+                    ciphertext_modulus_log,
+                    fft_precision,
+                    variance_bsk,
+                    glwe_key_bound,
+                ),
+            };
             if base_noise > level_decreasing_base_noise {
                 break;
             }
@@ -126,22 +151,29 @@ pub type PersistDecompCache = PersistentCacheHashMap<GlweParameters, Vec<CmuxCom
 
 pub fn cache(
     security_level: u64,
+    security_curves: &SecurityCurves,
     processing_unit: config::ProcessingUnit,
     complexity_model: Arc<dyn ComplexityModel>,
     ciphertext_modulus_log: u32,
     fft_precision: u32,
+    noise_mode: NoiseMode,
 ) -> PersistDecompCache {
     let cache_dir: String = default_cache_dir();
+    let curves = security_curves.cache_suffix();
+    let mode = noise_mode_cache_suffix(noise_mode);
+    let security_curves = security_curves.clone();
     let hardware = processing_unit.br_to_string();
     let path =
-        format!("{cache_dir}/cmux-decomp-{hardware}-{ciphertext_modulus_log}-{fft_precision}-{security_level}");
+        format!("{cache_dir}/cmux-decomp-{hardware}-{ciphertext_modulus_log}-{fft_precision}-{security_level}{curves}{mode}");
 
     let function = move |glwe_params: GlweParameters| {
         pareto_quantities(
             complexity_model.as_ref(),
             ciphertext_modulus_log,
             fft_precision,
+            &security_curves,
             security_level,
+            noise_mode,
             glwe_params,
         )
     };
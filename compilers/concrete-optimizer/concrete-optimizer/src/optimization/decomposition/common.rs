@@ -1,5 +1,15 @@
+use concrete_cpu_noise_model::gaussian_noise::worst_case::NoiseMode;
+
 use crate::parameters::GlweParameters;
 
 pub type MacroParam = (GlweParameters, u64);
 
 pub const VERSION: u64 = 3;
+
+/// Tells apart the caches storing maximal errors instead of variances.
+pub const fn noise_mode_cache_suffix(noise_mode: NoiseMode) -> &'static str {
+    match noise_mode {
+        NoiseMode::AverageCase => "",
+        NoiseMode::WorstCase => "-worst-case",
+    }
+}
@@ -1,11 +1,16 @@
-use super::common::VERSION;
+use super::common::{noise_mode_cache_suffix, VERSION};
 use crate::computing_cost::complexity_model::ComplexityModel;
 use crate::config;
+use crate::optimization;
 use crate::parameters::{KeyswitchParameters, KsDecompositionParameters, LweDimension};
 use crate::utils::cache::ephemeral::{CacheHashMap, EphemeralCache};
 use crate::utils::cache::persistent::{default_cache_dir, PersistentCacheHashMap};
-use concrete_cpu_noise_model::gaussian_noise::noise::keyswitch_one_bit::variance_keyswitch_one_bit;
-use concrete_security_curves::gaussian::security::minimal_variance_lwe;
+use concrete_cpu_noise_model::gaussian_noise::noise::keyswitch_one_bit::{
+    max_error_keyswitch_one_bit, variance_keyswitch_one_bit_with_key,
+};
+use concrete_cpu_noise_model::gaussian_noise::worst_case::{key_coefficient_bound, NoiseMode};
+use concrete_security_curves::gaussian::curves::SecurityCurves;
+use concrete_security_curves::key_distribution::KeyMoments;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
@@ -14,6 +19,7 @@ pub struct KsComplexityNoise {
     pub decomp: KsDecompositionParameters,
     pub complexity_bias: f64,
     pub complexity_slope: f64,
+    /// The variance per input coefficient, or the maximal error in worst-case mode
     pub noise: f64,
 }
 
@@ -27,13 +33,19 @@ impl KsComplexityNoise {
 }
 
 /* This is strictly variance decreasing and strictly complexity increasing */
+#[allow(clippy::too_many_arguments)]
 pub fn pareto_quantities(
     complexity_model: &dyn ComplexityModel,
     ciphertext_modulus_log: u32,
+    security_curves: &SecurityCurves,
     security_level: u64,
+    noise_mode: NoiseMode,
+    input_key: KeyMoments,
     internal_dim: u64,
 ) -> Vec<KsComplexityNoise> {
-    let variance_ksk = minimal_variance_lwe(internal_dim, ciphertext_modulus_log, security_level);
+    let variance_ksk =
+        security_curves.minimal_variance_lwe(internal_dim, ciphertext_modulus_log, security_level);
+    let input_key_bound = key_coefficient_bound(security_curves.key_distribution());
 
     let mut quantities = Vec::with_capacity(ciphertext_modulus_log as usize);
     let mut increasing_complexity_slope = 0.0;
@@ -52,8 +64,22 @@ pub fn pareto_quantities(
         let range = (1..=prev_best_log2_base).rev();
 
         for log2_base in range {
-            let noise_keyswitch =
-                variance_keyswitch_one_bit(log2_base, level, ciphertext_modulus_log, variance_ksk);
+            let noise_keyswitch = match noise_mode {
+                NoiseMode::AverageCase => variance_keyswitch_one_bit_with_key(
+                    log2_base,
+                    level,
+                    ciphertext_modulus_log,
+                    variance_ksk,
+                    input_key,
+                ),
+                NoiseMode::WorstCase => max_error_keyswitch_one_bit(
+                    log2_base,
+                    level,
+                    ciphertext_modulus_log,
+                    variance_ksk,
+                    input_key_bound,
+                ),
+            };
             if noise_keyswitch > level_decreasing_base_noise {
                 break;
             }
@@ -126,24 +152,42 @@ impl Cache {
 
 pub type PersistDecompCache = PersistentCacheHashMap<u64, Vec<KsComplexityNoise>>;
 
+/// # Errors
+/// `UnsupportedKeyDistribution` for fixed weight keys.
 pub fn cache(
     security_level: u64,
+    security_curves: &SecurityCurves,
     processing_unit: config::ProcessingUnit,
     complexity_model: Arc<dyn ComplexityModel>,
     ciphertext_modulus_log: u32,
-) -> PersistDecompCache {
+    noise_mode: NoiseMode,
+) -> optimization::Result<PersistDecompCache> {
+    // the noise is linear in the input dimension only if the key moments do not depend on it
+    let key_distribution = security_curves.key_distribution();
+    let input_key = key_distribution.coefficient_moments().ok_or_else(|| {
+        optimization::Err::UnsupportedKeyDistribution(format!("{key_distribution:?}"))
+    })?;
     let cache_dir: String = default_cache_dir();
+    let curves = security_curves.cache_suffix();
+    let mode = noise_mode_cache_suffix(noise_mode);
+    let security_curves = security_curves.clone();
     let hardware = processing_unit.ks_to_string();
-    let path =
-        format!("{cache_dir}/ks-decomp-{hardware}-{ciphertext_modulus_log}-{security_level}");
+    let path = format!(
+        "{cache_dir}/ks-decomp-{hardware}-{ciphertext_modulus_log}-{security_level}{curves}{mode}"
+    );
 
     let function = move |internal_dim: u64| {
         pareto_quantities(
             complexity_model.as_ref(),
             ciphertext_modulus_log,
+            &security_curves,
             security_level,
+            noise_mode,
+            input_key,
             internal_dim,
         )
     };
-    PersistentCacheHashMap::new_no_read(&path, VERSION, function)
+    Ok(PersistentCacheHashMap::new_no_read(
+        &path, VERSION, function,
+    ))
 }
@@ -8,6 +8,9 @@ pub use common::MacroParam;
 
 use crate::computing_cost::complexity_model::ComplexityModel;
 use crate::config;
+use crate::optimization;
+use concrete_cpu_noise_model::gaussian_noise::worst_case::NoiseMode;
+use concrete_security_curves::gaussian::curves::SecurityCurves;
 
 use std::sync::Arc;
 
@@ -17,6 +20,8 @@ pub struct PersistDecompCaches {
     pub pp: pp_switch::PersistDecompCache,
     pub cb: circuit_bootstrap::PersistDecompCache,
     pub cache_on_disk: bool,
+    /// Whether the ks and cmux noises are variances or maximal errors
+    pub noise_mode: NoiseMode,
 }
 
 pub struct DecompCaches {
@@ -26,63 +31,85 @@ pub struct DecompCaches {
     pub cb_pbs: circuit_bootstrap::Cache,
 }
 
+/// # Errors
+/// The errors of [`PersistDecompCaches::new`].
+#[allow(clippy::too_many_arguments)]
 pub fn cache(
     security_level: u64,
+    security_curves: &SecurityCurves,
     processing_unit: config::ProcessingUnit,
     complexity_model: Option<Arc<dyn ComplexityModel>>,
     cache_on_disk: bool,
     ciphertext_modulus_log: u32,
     fft_precision: u32,
-) -> PersistDecompCaches {
+    noise_mode: NoiseMode,
+) -> optimization::Result<PersistDecompCaches> {
     PersistDecompCaches::new(
         security_level,
+        security_curves,
         processing_unit,
         complexity_model,
         cache_on_disk,
         ciphertext_modulus_log,
         fft_precision,
+        noise_mode,
     )
 }
 
 impl PersistDecompCaches {
+    /// The caches compute the noises lazily, the curves are checked once here.
+    ///
+    /// # Errors
+    /// `UnsupportedSecurityLevel`, `UnsupportedCiphertextModulus` or `UnsupportedKeyDistribution`.
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         security_level: u64,
+        security_curves: &SecurityCurves,
         processing_unit: config::ProcessingUnit,
         complexity_model: Option<Arc<dyn ComplexityModel>>,
         cache_on_disk: bool,
         ciphertext_modulus_log: u32,
         fft_precision: u32,
-    ) -> Self {
+        noise_mode: NoiseMode,
+    ) -> optimization::Result<Self> {
+        security_curves.check(security_level, ciphertext_modulus_log)?;
         let complexity_model =
             complexity_model.unwrap_or_else(|| processing_unit.complexity_model());
         let res = Self {
             ks: keyswitch::cache(
                 security_level,
+                security_curves,
                 processing_unit,
                 complexity_model.clone(),
                 ciphertext_modulus_log,
-            ),
+                noise_mode,
+            )?,
             cmux: cmux::cache(
                 security_level,
+                security_curves,
                 processing_unit,
                 complexity_model.clone(),
                 ciphertext_modulus_log,
                 fft_precision,
+                noise_mode,
             ),
             pp: pp_switch::cache(
                 security_level,
+                security_curves,
                 processing_unit,
                 complexity_model.clone(),
                 ciphertext_modulus_log,
             ),
             cb: circuit_bootstrap::cache(
                 security_level,
+                security_curves,
                 processing_unit,
                 complexity_model,
                 ciphertext_modulus_log,
                 fft_precision,
             ),
             cache_on_disk,
+            noise_mode,
         };
         if cache_on_disk {
             res.ks.read();
@@ -90,7 +117,7 @@ impl PersistDecompCaches {
             res.pp.read();
             res.cb.read();
         }
-        res
+        Ok(res)
     }
 
     pub fn backport(&self, cache: DecompCaches) {
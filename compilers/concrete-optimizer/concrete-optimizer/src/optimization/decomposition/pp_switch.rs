@@ -7,7 +7,8 @@ use crate::parameters::{
 };
 use crate::utils::cache::ephemeral::{CacheHashMap, EphemeralCache};
 use crate::utils::cache::persistent::{default_cache_dir, PersistentCacheHashMap};
-use concrete_cpu_noise_model::gaussian_noise::noise::private_packing_keyswitch::estimate_packing_private_keyswitch;
+use concrete_cpu_noise_model::gaussian_noise::noise::private_packing_keyswitch::estimate_packing_private_keyswitch_with_key;
+use concrete_security_curves::gaussian::curves::SecurityCurves;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
@@ -31,10 +32,15 @@ pub type PersistDecompCache = PersistentCacheHashMap<GlweParameters, Vec<PpSwitc
 pub fn pareto_quantities(
     complexity_model: &dyn ComplexityModel,
     ciphertext_modulus_log: u32,
+    security_curves: &SecurityCurves,
     security_level: u64,
     glwe_params: GlweParameters,
 ) -> Vec<PpSwitchComplexityNoise> {
-    let variance_bsk = glwe_params.minimal_variance(ciphertext_modulus_log, security_level);
+    let variance_bsk =
+        glwe_params.minimal_variance(security_curves, ciphertext_modulus_log, security_level);
+    let glwe_key = security_curves
+        .key_distribution()
+        .moments(glwe_params.sample_extract_lwe_dimension());
     let mut quantities = Vec::with_capacity(ciphertext_modulus_log as usize);
     let mut increasing_complexity = 0.0;
     let mut decreasing_variance = f64::INFINITY;
@@ -53,7 +59,7 @@ pub fn pareto_quantities(
         // so we start on prev_best_log2_base
         let max_log2_base = prev_best_log2_base.min(max_level / level);
         for log2_base in (1..=max_log2_base).rev() {
-            let variance_private_packing_ks = estimate_packing_private_keyswitch(
+            let variance_private_packing_ks = estimate_packing_private_keyswitch_with_key(
                 0.,
                 variance_bsk,
                 log2_base,
@@ -61,6 +67,7 @@ pub fn pareto_quantities(
                 glwe_params.glwe_dimension,
                 glwe_params.polynomial_size(),
                 ciphertext_modulus_log,
+                glwe_key,
             );
             if variance_private_packing_ks > level_decreasing_base_noise {
                 break;
@@ -108,19 +115,24 @@ pub fn pareto_quantities(
 
 pub fn cache(
     security_level: u64,
+    security_curves: &SecurityCurves,
     processing_unit: config::ProcessingUnit,
     complexity_model: Arc<dyn ComplexityModel>,
     ciphertext_modulus_log: u32,
 ) -> PersistDecompCache {
     let cache_dir: String = default_cache_dir();
+    let curves = security_curves.cache_suffix();
+    let security_curves = security_curves.clone();
     let hardware = processing_unit.br_to_string();
-    let path =
-        format!("{cache_dir}/pp-decomp-{hardware}-{ciphertext_modulus_log}-{security_level}");
+    let path = format!(
+        "{cache_dir}/pp-decomp-{hardware}-{ciphertext_modulus_log}-{security_level}{curves}"
+    );
 
     let function = move |glwe_params: GlweParameters| {
         pareto_quantities(
             complexity_model.as_ref(),
             ciphertext_modulus_log,
+            &security_curves,
             security_level,
             glwe_params,
         )
@@ -2,21 +2,97 @@ pub mod atomic_pattern;
 pub mod config;
 pub mod dag;
 pub mod decomposition;
+pub mod security_check;
 pub mod wop_atomic_pattern;
 
+use concrete_cpu_noise_model::gaussian_noise::error_probability::ErrorProbabilityModel;
+use concrete_cpu_noise_model::gaussian_noise::worst_case::NoiseMode;
+use concrete_security_curves::gaussian::curves::SecurityError;
+use concrete_security_curves::key_distribution::KeyDistribution;
+
+use crate::dag::validate::DagError;
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Err {
+    InvalidDag(DagError),
     NotComposable(String),
     NoParametersFound,
+    UnsupportedSecurityLevel(u64),
+    UnsupportedCiphertextModulus(u32),
+    UnsupportedKeyDistribution(String),
+    UnsupportedNoiseMode(NoiseMode),
+    UnsupportedErrorProbabilityModel(ErrorProbabilityModel),
+    UnboundedWorstCaseNoise(String),
+    NoiseModeMismatch {
+        caches: NoiseMode,
+        config: NoiseMode,
+    },
 }
 
 impl std::fmt::Display for Err {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
+            Self::InvalidDag(err) => write!(f, "Invalid dag: {err}"),
             Self::NotComposable(details) => write!(f, "Program can not be composed: {details}"),
             Self::NoParametersFound => write!(f, "No crypto parameters could be found"),
+            Self::UnsupportedSecurityLevel(level) => {
+                write!(f, "{level} bits of security is not supported")
+            }
+            Self::UnsupportedCiphertextModulus(ciphertext_modulus_log) => write!(
+                f,
+                "A {ciphertext_modulus_log} bits ciphertext modulus is not supported"
+            ),
+            Self::UnsupportedKeyDistribution(key_distribution) => {
+                write!(f, "{key_distribution} keys are not supported")
+            }
+            Self::UnsupportedNoiseMode(noise_mode) => {
+                write!(
+                    f,
+                    "{noise_mode:?} noise mode is not supported by this optimizer"
+                )
+            }
+            Self::UnsupportedErrorProbabilityModel(model) => {
+                write!(
+                    f,
+                    "{model:?} error probabilities are not supported by this optimizer"
+                )
+            }
+            Self::UnboundedWorstCaseNoise(details) => {
+                write!(f, "Worst-case noise can not be bounded: {details}")
+            }
+            Self::NoiseModeMismatch { caches, config } => write!(
+                f,
+                "The decomposition caches are computed for {caches:?} noise mode, not {config:?}"
+            ),
+        }
+    }
+}
+
+impl From<SecurityError> for Err {
+    fn from(err: SecurityError) -> Self {
+        match err {
+            SecurityError::UnsupportedSecurityLevel(level) => Self::UnsupportedSecurityLevel(level),
+            SecurityError::ModulusOutOfRange(ciphertext_modulus_log) => {
+                Self::UnsupportedCiphertextModulus(ciphertext_modulus_log)
+            }
+            // insecure dimensions are pruned from the search space, never reported
+            SecurityError::DimensionBelowMinimum { .. } => Self::NoParametersFound,
+            SecurityError::UntabulatedSparseKey { hamming_weight } => {
+                Self::UnsupportedKeyDistribution(format!(
+                    "{:?}",
+                    KeyDistribution::Sparse { hamming_weight }
+                ))
+            }
         }
     }
 }
 
+impl std::error::Error for Err {}
+
+impl From<DagError> for Err {
+    fn from(err: DagError) -> Self {
+        Self::InvalidDag(err)
+    }
+}
+
 type Result<T> = std::result::Result<T, Err>;
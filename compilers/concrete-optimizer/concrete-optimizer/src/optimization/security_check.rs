@@ -0,0 +1,66 @@
+pub use concrete_security_curves::estimator::TOLERANCE;
+use concrete_security_curves::estimator::{estimate, LweInstance, ReductionCost, SecurityEstimate};
+use concrete_security_curves::gaussian::curves::SecurityCurves;
+
+#[derive(Clone, Copy, Debug)]
+pub struct SecurityCheck {
+    pub lwe_dimension: u64,
+    pub log2_std: f64,
+    pub estimate: SecurityEstimate,
+}
+
+impl SecurityCheck {
+    /// Whether the curve noise at this dimension gives less security than the curve claims.
+    pub fn curve_is_over_optimistic(&self, security_level: u64) -> bool {
+        self.estimate.falls_short_of(security_level, TOLERANCE)
+    }
+}
+
+/// Estimates the security of a key of `lwe_dimension`, drawn from the curves key distribution, used
+/// with the minimal noise allowed by the curves.
+pub fn check_secret_key(
+    security_curves: &SecurityCurves,
+    security_level: u64,
+    ciphertext_modulus_log: u32,
+    lwe_dimension: u64,
+) -> SecurityCheck {
+    let variance =
+        security_curves.minimal_variance_lwe(lwe_dimension, ciphertext_modulus_log, security_level);
+    let log2_std = 0.5 * variance.log2();
+    let estimate = estimate(
+        LweInstance {
+            lwe_dimension,
+            ciphertext_modulus_log,
+            log2_std,
+            key_distribution: security_curves.key_distribution(),
+        },
+        ReductionCost::Bdgl16,
+    );
+    SecurityCheck {
+        lwe_dimension,
+        log2_std,
+        estimate,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compiled_curve_keys_pass_and_optimistic_curve_keys_fail() {
+        let check = check_secret_key(SecurityCurves::compiled(), 128, 64, 1024);
+        assert!(!check.curve_is_over_optimistic(128));
+        assert!(check.estimate.security_level() >= 128.0 - TOLERANCE);
+
+        // far less noise than the 128 bits curve at the same dimension
+        let optimistic = SecurityCurves::from_json_str(
+            r#"[{"slope": -0.06, "bias": 2.0, "security_level": 128, "minimal_lwe_dimension": 450}]"#,
+        )
+        .unwrap();
+        let check = check_secret_key(&optimistic, 128, 64, 1024);
+        assert!(check.log2_std < -50.0);
+        assert!(check.curve_is_over_optimistic(128));
+        assert!(check.estimate.security_level() < 128.0 - TOLERANCE);
+    }
+}
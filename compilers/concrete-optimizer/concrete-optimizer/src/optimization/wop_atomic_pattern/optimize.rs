@@ -1,5 +1,5 @@
 use concrete_cpu_noise_model::gaussian_noise::conversion::variance_to_std_dev;
-use concrete_cpu_noise_model::gaussian_noise::noise::modulus_switching::estimate_modulus_switching_noise_with_binary_key;
+use concrete_cpu_noise_model::gaussian_noise::noise::modulus_switching::estimate_modulus_switching_noise_with_key;
 
 use super::crt_decomposition;
 use crate::dag::operator::Precision;
@@ -209,10 +209,15 @@ fn update_state_with_best_decompositions(
     let safe_variance_bound = consts.safe_variance;
     let norm = consts.noise_factor;
 
-    let variance_modulus_switching = estimate_modulus_switching_noise_with_binary_key(
+    let variance_modulus_switching = estimate_modulus_switching_noise_with_key(
         internal_dim,
         glwe_params.log2_polynomial_size,
         ciphertext_modulus_log,
+        consts
+            .config
+            .security_curves
+            .key_distribution()
+            .moments(internal_dim),
     );
 
     if variance_modulus_switching > consts.safe_variance {
@@ -437,6 +442,9 @@ fn optimize_raw(
     let mut state = OptimizationState {
         best_solution: None,
     };
+    if config.check_average_case().is_err() || config.check_gaussian_tail().is_err() {
+        return state;
+    }
 
     let consts = OptimizationDecompositionsConsts {
         config,
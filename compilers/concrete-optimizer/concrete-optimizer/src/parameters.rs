@@ -3,7 +3,7 @@ pub use individual::*;
 pub use range::*;
 
 mod individual {
-    use concrete_security_curves::gaussian::security::minimal_variance_glwe;
+    use concrete_security_curves::gaussian::curves::SecurityCurves;
     use serde::{Deserialize, Serialize};
 
     #[derive(Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord, Debug, Serialize, Deserialize)]
@@ -32,8 +32,13 @@ mod individual {
             self.glwe_dimension << self.log2_polynomial_size
         }
 
-        pub fn minimal_variance(self, ciphertext_modulus_log: u32, security_level: u64) -> f64 {
-            minimal_variance_glwe(
+        pub fn minimal_variance(
+            self,
+            security_curves: &SecurityCurves,
+            ciphertext_modulus_log: u32,
+            security_level: u64,
+        ) -> f64 {
+            security_curves.minimal_variance_glwe(
                 self.glwe_dimension,
                 self.polynomial_size(),
                 ciphertext_modulus_log,
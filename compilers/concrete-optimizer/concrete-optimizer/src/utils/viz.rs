@@ -99,6 +99,37 @@ impl<'dag> Viz for crate::dag::unparametrized::DagOperator<'dag> {
             Operator::Round { out_precision, .. } => {
                 format!("{index} [label = \"{{%{index} = Round({input_string}) |{{out_precision:|{out_precision:?}}}}}\" fillcolor={color}];",)
             }
+            Operator::Bivariate { out_precision, .. } => {
+                format!("{index} [label = \"{{%{index} = Bivariate({input_string}) |{{out_precision:|{out_precision:?}}}}}\" fillcolor={color}];")
+            }
+            Operator::Mul { out_precision, .. } => {
+                format!("{index} [label = \"{{%{index} = Mul({input_string}) |{{out_precision:|{out_precision:?}}}}}\" fillcolor={color}];")
+            }
+            Operator::MaxPool { window, .. } => {
+                let window = &window.dimensions_size;
+                format!("{index} [label = \"{{%{index} = MaxPool({input_string}) |{{window:|{window:?}}}}}\" fillcolor={color}];")
+            }
+            Operator::Reshape { .. } => {
+                format!("{index} [label = \"{{%{index} = Reshape({input_string})}}\" fillcolor={color}];")
+            }
+            Operator::Transpose { axes, .. } => {
+                format!("{index} [label = \"{{%{index} = Transpose({input_string}) |{{axes:|{axes:?}}}}}\" fillcolor={color}];")
+            }
+            Operator::Concatenate { axis, .. } => {
+                format!("{index} [label = \"{{%{index} = Concatenate({input_string}) |{{axis:|{axis:?}}}}}\" fillcolor={color}];")
+            }
+            Operator::Slice { .. } => {
+                format!("{index} [label = \"{{%{index} = Slice({input_string})}}\" fillcolor={color}];")
+            }
+            Operator::Gather { axis, .. } => {
+                format!("{index} [label = \"{{%{index} = Gather({input_string}) |{{axis:|{axis:?}}}}}\" fillcolor={color}];")
+            }
+            Operator::Broadcast { .. } => {
+                format!("{index} [label = \"{{%{index} = Broadcast({input_string})}}\" fillcolor={color}];")
+            }
+            Operator::Conv2d { .. } => {
+                format!("{index} [label = \"{{%{index} = Conv2d({input_string})}}\" fillcolor={color}];")
+            }
         }
     }
 
@@ -1,5 +1,7 @@
+use concrete_optimizer::SecurityCurves;
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
-use v0_parameters::{all_results, Args, MAX_LWE_DIM, MIN_LWE_DIM, _4_SIGMA};
+use v0_parameters::optimize_file::ErrorProbability;
+use v0_parameters::{all_results, Args, _4_SIGMA, MAX_LWE_DIM, MIN_LWE_DIM};
 
 fn v0_pbs_optimization(c: &mut Criterion) {
     let args: Args = Args {
@@ -21,10 +23,13 @@ fn v0_pbs_optimization(c: &mut Criterion) {
         ciphertext_modulus_log: 64,
         fft_precision: 53,
         composable: false,
+        error_probability: ErrorProbability::Gaussian,
+        check_security: false,
+        security_curves: None,
     };
 
     c.bench_function("v0 PBS table generation", |b| {
-        b.iter(|| black_box(all_results(&args)))
+        b.iter(|| black_box(all_results(&args, SecurityCurves::compiled())))
     });
 }
 
@@ -48,10 +53,13 @@ fn v0_pbs_optimization_simulate_graph(c: &mut Criterion) {
         ciphertext_modulus_log: 64,
         fft_precision: 53,
         composable: false,
+        error_probability: ErrorProbability::Gaussian,
+        check_security: false,
+        security_curves: None,
     };
 
     c.bench_function("v0 PBS simulate dag table generation", |b| {
-        b.iter(|| black_box(all_results(&args)))
+        b.iter(|| black_box(all_results(&args, SecurityCurves::compiled())))
     });
 }
 
@@ -75,10 +83,13 @@ fn v0_wop_pbs_optimization(c: &mut Criterion) {
         ciphertext_modulus_log: 64,
         fft_precision: 53,
         composable: false,
+        error_probability: ErrorProbability::Gaussian,
+        check_security: false,
+        security_curves: None,
     };
 
     c.bench_function("v0 WoP-PBS table generation", |b| {
-        b.iter(|| black_box(all_results(&args)))
+        b.iter(|| black_box(all_results(&args, SecurityCurves::compiled())))
     });
 }
 
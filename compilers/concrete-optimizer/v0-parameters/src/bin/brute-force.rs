@@ -7,7 +7,8 @@ use brute_force_optimizer::lmp::solve_all_lmp;
 use brute_force_optimizer::multi_bit_cjp::solve_all_multi_bit_cjp;
 use clap::Parser;
 use std::fs::File;
-use v0_parameters::_4_SIGMA;
+use std::path::PathBuf;
+use v0_parameters::{load_security_curves, _4_SIGMA};
 
 /// Find parameters for a variety of atomic patterns using a brute force algorithm
 #[derive(Parser, Debug)]
@@ -22,10 +23,17 @@ pub struct BruteForceArgs {
         help = "Supported atomic patterns: CJP, KSfree, CGGI, GBA and LMP and MBCJP"
     )]
     pub atomic_pattern: String,
+
+    #[clap(
+        long,
+        help = "Security curves file in the curves.json format, the compiled curves by default"
+    )]
+    pub security_curves: Option<PathBuf>,
 }
 
 fn main() {
     let args = BruteForceArgs::parse();
+    let security_curves = load_security_curves(args.security_curves.as_deref()).unwrap();
 
     let filename = format!(
         "exp/{}-pfail-{}.txt",
@@ -35,12 +43,12 @@ fn main() {
     let file = File::create(filename).unwrap();
 
     match args.atomic_pattern.as_str() {
-        "CJP" => solve_all_cjp(args.p_fail, file),
-        "CGGI" => solve_all_cggi(args.p_fail, file),
-        "KSfree" => solve_all_ksfree(args.p_fail, file),
-        "LMP" => solve_all_lmp(args.p_fail, file),
-        "GBA" => solve_all_gba(args.p_fail, file),
-        "MBCJP" => solve_all_multi_bit_cjp(args.p_fail, file),
+        "CJP" => solve_all_cjp(args.p_fail, &security_curves, file),
+        "CGGI" => solve_all_cggi(args.p_fail, &security_curves, file),
+        "KSfree" => solve_all_ksfree(args.p_fail, &security_curves, file),
+        "LMP" => solve_all_lmp(args.p_fail, &security_curves, file),
+        "GBA" => solve_all_gba(args.p_fail, &security_curves, file),
+        "MBCJP" => solve_all_multi_bit_cjp(args.p_fail, &security_curves, file),
         _ => {
             panic!(
                 "The resquested AP is not supported ({})",
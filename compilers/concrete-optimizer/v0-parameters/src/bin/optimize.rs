@@ -0,0 +1,15 @@
+#![warn(clippy::nursery)]
+#![warn(clippy::pedantic)]
+#![warn(clippy::style)]
+
+use clap::Parser;
+use v0_parameters::optimize_file::{run, OptimizeArgs};
+
+fn main() {
+    let args = OptimizeArgs::parse();
+
+    if let Err(err) = run(&args) {
+        eprintln!("optimize: {err}");
+        std::process::exit(1);
+    }
+}
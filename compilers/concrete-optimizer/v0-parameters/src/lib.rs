@@ -12,16 +12,24 @@ use clap::Parser;
 use concrete_optimizer::computing_cost::cpu::CpuComplexity;
 use concrete_optimizer::config;
 use concrete_optimizer::global_parameters::DEFAUT_DOMAINS;
-use concrete_optimizer::optimization::config::{Config, SearchSpace};
+use concrete_optimizer::optimization::config::{Config, NoiseTracking, SearchSpace};
 use concrete_optimizer::optimization::dag::solo_key::optimize::{self as optimize_dag};
 use concrete_optimizer::optimization::dag::solo_key::optimize_generic::Solution;
 use concrete_optimizer::optimization::dag::solo_key::optimize_generic::Solution::{
     WopSolution, WpSolution,
 };
+use concrete_optimizer::optimization::security_check::check_secret_key;
 use concrete_optimizer::optimization::wop_atomic_pattern::optimize as optimize_wop_atomic_pattern;
-use concrete_optimizer::optimization::{atomic_pattern as optimize_atomic_pattern, decomposition};
+use concrete_optimizer::optimization::{
+    self, atomic_pattern as optimize_atomic_pattern, decomposition,
+};
+use concrete_optimizer::{CurvesError, ModulusSwitchingKind, NoiseMode, SecurityCurves};
+use optimize_file::ErrorProbability;
 use rayon_cond::CondIterator;
 use std::io::Write;
+use std::path::{Path, PathBuf};
+
+pub mod optimize_file;
 
 pub const _4_SIGMA: f64 = 1.0 - 0.999_936_657_516;
 const MIN_LOG_POLY_SIZE: u64 = DEFAUT_DOMAINS
@@ -50,7 +58,7 @@ pub struct Args {
     #[clap(
         long,
         default_value_t = 128,
-        help = "Supported values: 80, 96, 112, 128, 192"
+        help = "Supported values: 80, 112, 128, 192"
     )]
     pub security_level: u64,
 
@@ -95,9 +103,35 @@ pub struct Args {
 
     #[clap(long)]
     pub composable: bool,
+
+    #[clap(long, value_enum, default_value_t = ErrorProbability::Gaussian)]
+    pub error_probability: ErrorProbability,
+
+    #[clap(
+        long,
+        help = "Cross-check the keys of each solution with the native lattice estimator"
+    )]
+    pub check_security: bool,
+
+    #[clap(
+        long,
+        help = "Security curves file in the curves.json format, the compiled curves by default"
+    )]
+    pub security_curves: Option<PathBuf>,
+}
+
+/// The curves of a `curves.json` file, or the compiled curves without a file.
+pub fn load_security_curves(path: Option<&Path>) -> Result<SecurityCurves, CurvesError> {
+    path.map_or_else(
+        || Ok(SecurityCurves::compiled().clone()),
+        SecurityCurves::from_json_file,
+    )
 }
 
-pub fn all_results(args: &Args) -> Vec<Vec<Option<Solution>>> {
+pub fn all_results(
+    args: &Args,
+    security_curves: &SecurityCurves,
+) -> Result<Vec<Vec<Option<Solution>>>, optimization::Err> {
     let processing_unit = config::ProcessingUnit::Cpu;
     let sum_size = args.sum_size;
     let maximum_acceptable_error_probability = args.p_error;
@@ -110,6 +144,7 @@ pub fn all_results(args: &Args) -> Vec<Vec<Option<Solution>>> {
         glwe_dimensions: (args.min_glwe_dim..=args.max_glwe_dim).collect(),
         internal_lwe_dimensions: (args.min_intern_lwe_dim..=args.max_intern_lwe_dim).collect(),
         levelled_only_lwe_dimensions: DEFAUT_DOMAINS.free_lwe,
+        modulus_switching_kinds: vec![ModulusSwitchingKind::Standard],
     };
 
     let precisions = args.min_precision..=args.max_precision;
@@ -127,18 +162,26 @@ pub fn all_results(args: &Args) -> Vec<Vec<Option<Solution>>> {
         fft_precision: args.fft_precision,
         complexity_model: &CpuComplexity::default(),
         composable,
+        security_curves,
+        noise_mode: NoiseMode::AverageCase,
+        error_probability_model: args.error_probability.into(),
+        dag_passes: &[],
+        refresh_compositions: false,
+        noise_tracking: NoiseTracking::PerTensor,
     };
 
     let cache = decomposition::cache(
         security_level,
+        security_curves,
         processing_unit,
         None,
         cache_on_disk,
         args.ciphertext_modulus_log,
         args.fft_precision,
-    );
+        NoiseMode::AverageCase,
+    )?;
 
-    precisions_iter
+    let results = precisions_iter
         .map(|precision| {
             log_norms2
                 .iter()
@@ -181,11 +224,15 @@ pub fn all_results(args: &Args) -> Vec<Vec<Option<Solution>>> {
                 })
                 .collect::<Vec<_>>()
         })
-        .collect::<Vec<_>>()
+        .collect::<Vec<_>>();
+    Ok(results)
 }
 
 pub fn compute_print_results(mut writer: impl Write, args: &Args) -> Result<(), std::io::Error> {
-    let all_results = all_results(args);
+    let security_curves = load_security_curves(args.security_curves.as_deref())
+        .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidInput, err))?;
+    let all_results = all_results(args, &security_curves)
+        .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidInput, err))?;
 
     let p_error = args.p_error;
     let security_level = args.security_level;
@@ -220,6 +267,15 @@ pub fn compute_print_results(mut writer: impl Write, args: &Args) -> Result<(),
                             (solution.complexity / (1024.0 * 1024.0)) as u64,
                             solution.p_error
                         )?;
+                        if args.check_security {
+                            write_security_checks(
+                                &mut writer,
+                                args,
+                                &security_curves,
+                                solution.internal_ks_output_lwe_dimension,
+                                solution.glwe_dimension * solution.glwe_polynomial_size,
+                            )?;
+                        }
                     }
                     WopSolution(solution) => {
                         if manp_i == 0 {
@@ -239,6 +295,15 @@ pub fn compute_print_results(mut writer: impl Write, args: &Args) -> Result<(),
                             (solution.complexity / (1024.0 * 1024.0)) as u64,
                             solution.p_error
                         )?;
+                        if args.check_security {
+                            write_security_checks(
+                                &mut writer,
+                                args,
+                                &security_curves,
+                                solution.internal_ks_output_lwe_dimension,
+                                solution.glwe_dimension * solution.glwe_polynomial_size,
+                            )?;
+                        }
                     }
                 }
             } else if no_solution_at.is_none() {
@@ -259,9 +324,36 @@ pub fn compute_print_results(mut writer: impl Write, args: &Args) -> Result<(),
     Ok(())
 }
 
+/// Reports the keys for which the curves promise more security than the lattice estimate.
+fn write_security_checks(
+    mut writer: impl Write,
+    args: &Args,
+    security_curves: &SecurityCurves,
+    small_lwe_dimension: u64,
+    big_lwe_dimension: u64,
+) -> Result<(), std::io::Error> {
+    for lwe_dimension in [small_lwe_dimension, big_lwe_dimension] {
+        let check = check_secret_key(
+            security_curves,
+            args.security_level,
+            args.ciphertext_modulus_log,
+            lwe_dimension,
+        );
+        if check.curve_is_over_optimistic(args.security_level) {
+            writeln!(
+                writer,
+                "    #   n = {lwe_dimension}: estimated {:.1} bits of security",
+                check.estimate.security_level()
+            )?;
+        }
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use concrete_optimizer::optimization::security_check::TOLERANCE;
     use concrete_optimizer::supported_security_levels;
 
     #[test]
@@ -301,6 +393,9 @@ mod tests {
                 ciphertext_modulus_log: 64,
                 fft_precision: 53,
                 composable: false,
+                error_probability: ErrorProbability::Gaussian,
+                check_security: false,
+                security_curves: None,
             };
 
             let mut actual_output = Vec::<u8>::new();
@@ -316,6 +411,39 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_security_checks_report_optimistic_curves() {
+        let args = Args::parse_from(["v0-parameters", "--check-security"]);
+        let check = |security_curves: &SecurityCurves| {
+            let mut output = Vec::<u8>::new();
+            write_security_checks(&mut output, &args, security_curves, 700, 2048).unwrap();
+            String::from_utf8(output).unwrap()
+        };
+
+        assert_eq!(check(SecurityCurves::compiled()), "");
+
+        // far less noise than the 128 bits curve at the same dimensions
+        let optimistic = SecurityCurves::from_json_str(
+            r#"[{"slope": -0.06, "bias": 2.0, "security_level": 128, "minimal_lwe_dimension": 450}]"#,
+        )
+        .unwrap();
+        let report = check(&optimistic);
+        let lines: Vec<_> = report.lines().collect();
+        assert_eq!(lines.len(), 2, "{report}");
+        assert!(lines[0].starts_with("    #   n = 700: estimated "));
+        assert!(lines[1].starts_with("    #   n = 2048: estimated "));
+        for line in lines {
+            let bits: f64 = line
+                .trim_end_matches(" bits of security")
+                .rsplit(' ')
+                .next()
+                .unwrap()
+                .parse()
+                .unwrap();
+            assert!(bits < 128.0 - TOLERANCE, "{line}");
+        }
+    }
+
     #[test]
     fn test_reference_wop_output() {
         check_reference_wop_output_on_levels(supported_security_levels());
@@ -346,6 +474,9 @@ mod tests {
                 ciphertext_modulus_log: 64,
                 fft_precision: 53,
                 composable: false,
+                error_probability: ErrorProbability::Gaussian,
+                check_security: false,
+                security_curves: None,
             };
 
             let mut actual_output = Vec::<u8>::new();
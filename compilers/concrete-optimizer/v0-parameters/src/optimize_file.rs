@@ -0,0 +1,436 @@
+//! Optimization of a serialized dag, to reproduce a compilation without the compiler.
+
+use clap::{Parser, ValueEnum};
+use concrete_optimizer::computing_cost::cpu::CpuComplexity;
+use concrete_optimizer::config::{GpuPbsType, ProcessingUnit};
+use concrete_optimizer::dag::rewrite::passes::ALL_PASSES;
+use concrete_optimizer::dag::unparametrized::Dag;
+use concrete_optimizer::optimization::config::{Config, NoiseTracking, SearchSpace};
+use concrete_optimizer::optimization::dag::multi_parameters::keys_spec::CircuitSolution;
+use concrete_optimizer::optimization::dag::multi_parameters::optimize_generic as optimize_multi;
+use concrete_optimizer::optimization::dag::multi_parameters::partition_cut::PartitionCut;
+use concrete_optimizer::optimization::dag::solo_key::optimize_generic::{
+    self as optimize_solo, Solution,
+};
+use concrete_optimizer::optimization::decomposition;
+use concrete_optimizer::{ErrorProbabilityModel, NoiseMode, SecurityCurves};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::PathBuf;
+
+use crate::{load_security_curves, _4_SIGMA};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    Json,
+    Bincode,
+}
+
+impl Format {
+    pub fn read<T: DeserializeOwned>(self, reader: impl Read) -> Result<T, Box<dyn Error>> {
+        Ok(match self {
+            Self::Json => serde_json::from_reader(reader)?,
+            Self::Bincode => bincode::deserialize_from(reader)?,
+        })
+    }
+
+    pub fn write<T: Serialize>(
+        self,
+        mut writer: impl Write,
+        value: &T,
+    ) -> Result<(), Box<dyn Error>> {
+        match self {
+            Self::Json => {
+                serde_json::to_writer_pretty(&mut writer, value)?;
+                writeln!(writer)?;
+            }
+            Self::Bincode => bincode::serialize_into(&mut writer, value)?,
+        }
+        Ok(())
+    }
+}
+
+/// How the circuit is split into partitions, each with its own parameters.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Strategy {
+    /// A single set of parameters for the whole circuit
+    SoloKey,
+    ByPrecision,
+    ByPrecisionAndNorm2,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Encoding {
+    Auto,
+    Native,
+    Crt,
+}
+
+impl From<Encoding> for optimize_solo::Encoding {
+    fn from(encoding: Encoding) -> Self {
+        match encoding {
+            Encoding::Auto => Self::Auto,
+            Encoding::Native => Self::Native,
+            Encoding::Crt => Self::Crt,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Noise {
+    AverageCase,
+    WorstCase,
+}
+
+impl From<Noise> for NoiseMode {
+    fn from(noise: Noise) -> Self {
+        match noise {
+            Noise::AverageCase => Self::AverageCase,
+            Noise::WorstCase => Self::WorstCase,
+        }
+    }
+}
+
+/// How the error probability of a noise is computed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ErrorProbability {
+    /// The tail of a gaussian of the noise variance
+    Gaussian,
+    /// The exact tail of the noise terms, for the atomic pattern and solo key optimizers
+    ExactTail,
+}
+
+impl From<ErrorProbability> for ErrorProbabilityModel {
+    fn from(error_probability: ErrorProbability) -> Self {
+        match error_probability {
+            ErrorProbability::Gaussian => Self::Gaussian,
+            ErrorProbability::ExactTail => Self::ExactTail,
+        }
+    }
+}
+
+/// Find the parameters of a serialized circuit and write its keys
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct OptimizeArgs {
+    #[clap(help = "Serialized dag")]
+    pub dag: PathBuf,
+
+    #[clap(long, help = "Solution file, the standard output by default")]
+    pub output: Option<PathBuf>,
+
+    #[clap(long, value_enum, default_value_t = Format::Json)]
+    pub input_format: Format,
+
+    #[clap(long, value_enum, default_value_t = Format::Json)]
+    pub output_format: Format,
+
+    #[clap(long, value_enum, default_value_t = Strategy::ByPrecision)]
+    pub strategy: Strategy,
+
+    #[clap(long, value_enum, default_value_t = Encoding::Auto)]
+    pub encoding: Encoding,
+
+    #[clap(long, default_value_t = _4_SIGMA)]
+    pub p_error: f64,
+
+    #[clap(
+        long,
+        default_value_t = 128,
+        help = "Supported values: 80, 112, 128, 192"
+    )]
+    pub security_level: u64,
+
+    #[clap(long)]
+    pub no_key_sharing: bool,
+
+    #[clap(long)]
+    pub composable: bool,
+
+    #[clap(long, help = "Simplify the dag before a multi parameters optimization")]
+    pub simplify: bool,
+
+    #[clap(
+        long,
+        help = "Refresh the composed outputs or inputs carrying a growing noise before a multi parameters optimization"
+    )]
+    pub refresh: bool,
+
+    #[clap(
+        long,
+        help = "Track the noise per class of tensor elements instead of per tensor"
+    )]
+    pub per_element_noise: bool,
+
+    #[clap(long, default_value_t = 8.0)]
+    pub default_log_norm2_woppbs: f64,
+
+    #[clap(long)]
+    pub use_gpu_constraints: bool,
+
+    #[clap(long, value_enum, default_value_t = Noise::AverageCase)]
+    pub noise_mode: Noise,
+
+    #[clap(long, value_enum, default_value_t = ErrorProbability::Gaussian)]
+    pub error_probability: ErrorProbability,
+
+    #[clap(long)]
+    pub cache_on_disk: bool,
+
+    #[clap(long, default_value_t = 64)]
+    pub ciphertext_modulus_log: u32,
+
+    #[clap(long, default_value_t = 53)]
+    pub fft_precision: u32,
+
+    #[clap(
+        long,
+        help = "Security curves file in the curves.json format, the compiled curves by default"
+    )]
+    pub security_curves: Option<PathBuf>,
+}
+
+impl OptimizeArgs {
+    const fn processing_unit(&self) -> ProcessingUnit {
+        if self.use_gpu_constraints {
+            ProcessingUnit::Gpu {
+                pbs_type: GpuPbsType::Amortized,
+                number_of_sm: 1,
+            }
+        } else {
+            ProcessingUnit::Cpu
+        }
+    }
+}
+
+pub fn optimize(
+    dag: &Dag,
+    args: &OptimizeArgs,
+    security_curves: &SecurityCurves,
+) -> CircuitSolution {
+    let processing_unit = args.processing_unit();
+    let noise_mode = args.noise_mode.into();
+    let config = Config {
+        security_level: args.security_level,
+        maximum_acceptable_error_probability: args.p_error,
+        key_sharing: !args.no_key_sharing,
+        ciphertext_modulus_log: args.ciphertext_modulus_log,
+        fft_precision: args.fft_precision,
+        complexity_model: &CpuComplexity::default(),
+        composable: args.composable,
+        security_curves,
+        noise_mode,
+        error_probability_model: args.error_probability.into(),
+        dag_passes: if args.simplify { &ALL_PASSES } else { &[] },
+        refresh_compositions: args.refresh,
+        noise_tracking: if args.per_element_noise {
+            NoiseTracking::PerElementClass
+        } else {
+            NoiseTracking::PerTensor
+        },
+    };
+    let search_space = SearchSpace::default(processing_unit);
+    let caches = match decomposition::cache(
+        args.security_level,
+        security_curves,
+        processing_unit,
+        Some(ProcessingUnit::Cpu.complexity_model()),
+        args.cache_on_disk,
+        args.ciphertext_modulus_log,
+        args.fft_precision,
+        noise_mode,
+    ) {
+        Ok(caches) => caches,
+        Err(err) => return CircuitSolution::no_solution(err.to_string()),
+    };
+    let p_cut = match args.strategy {
+        Strategy::SoloKey => {
+            let nb_instr = dag.len();
+            return match optimize_solo::optimize(
+                dag,
+                config,
+                &search_space,
+                args.encoding.into(),
+                args.default_log_norm2_woppbs,
+                &caches,
+            ) {
+                Ok(Solution::WpSolution(sol)) => CircuitSolution::from_native_solution(sol, dag),
+                Ok(Solution::WopSolution(sol)) => CircuitSolution::from_wop_solution(sol, nb_instr),
+                Err(err) => CircuitSolution::no_solution(err.to_string()),
+            };
+        }
+        Strategy::ByPrecision => PartitionCut::for_each_precision(dag),
+        Strategy::ByPrecisionAndNorm2 => PartitionCut::maximal_partitionning(dag),
+    };
+    optimize_multi::optimize(
+        dag,
+        config,
+        &search_space,
+        args.encoding.into(),
+        args.default_log_norm2_woppbs,
+        &caches,
+        &Some(p_cut),
+    )
+}
+
+pub fn run(args: &OptimizeArgs) -> Result<(), Box<dyn Error>> {
+    let dag: Dag = args
+        .input_format
+        .read(BufReader::new(File::open(&args.dag)?))?;
+    let security_curves = load_security_curves(args.security_curves.as_deref())?;
+    let solution = optimize(&dag, args, &security_curves);
+    match &args.output {
+        Some(path) => args
+            .output_format
+            .write(BufWriter::new(File::create(path)?), &solution),
+        None => args
+            .output_format
+            .write(std::io::stdout().lock(), &solution),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use concrete_optimizer::dag::operator::{FunctionTable, Shape};
+
+    #[test]
+    fn test_optimize_dag_file() {
+        let mut dag = Dag::new();
+        let input1 = dag.add_input(3, Shape::number());
+        let input2 = dag.add_input(5, Shape::number());
+        let lut1 = dag.add_lut(input1, FunctionTable::UNKWOWN, 5);
+        let lut2 = dag.add_lut(input2, FunctionTable::UNKWOWN, 5);
+        let _ = dag.add_dot([lut1, lut2], [1, 1]);
+
+        let dir = std::env::temp_dir().join(format!("optimize-file-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let dag_path = dir.join("dag.json");
+        Format::Json
+            .write(File::create(&dag_path).unwrap(), &dag)
+            .unwrap();
+
+        for (strategy, nb_secret_keys) in [("solo-key", 2), ("by-precision", 4)] {
+            let solution_path = dir.join(format!("{strategy}.bin"));
+            let args = OptimizeArgs::parse_from([
+                "optimize".as_ref(),
+                dag_path.as_os_str(),
+                "--output".as_ref(),
+                solution_path.as_os_str(),
+                "--output-format=bincode".as_ref(),
+                format!("--strategy={strategy}").as_ref(),
+                "--encoding=native".as_ref(),
+            ]);
+            run(&args).unwrap();
+            let solution: CircuitSolution = Format::Bincode
+                .read(File::open(&solution_path).unwrap())
+                .unwrap();
+            assert!(solution.is_feasible, "{strategy}: {}", solution.error_msg);
+            assert_eq!(solution.instructions_keys.len(), dag.len());
+            assert_eq!(solution.circuit_keys.secret_keys.len(), nb_secret_keys);
+            assert_eq!(solution, optimize(&dag, &args, SecurityCurves::compiled()));
+        }
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_optimize_with_curves_file() {
+        let mut dag = Dag::new();
+        let input = dag.add_input(3, Shape::number());
+        let _ = dag.add_lut(input, FunctionTable::UNKWOWN, 3);
+
+        let dir = std::env::temp_dir().join(format!("optimize-curves-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let dag_path = dir.join("dag.json");
+        Format::Json
+            .write(File::create(&dag_path).unwrap(), &dag)
+            .unwrap();
+        // only the 80 bits curve
+        let curves_path = dir.join("curves.json");
+        std::fs::write(
+            &curves_path,
+            r#"[{"slope": -0.04045822621883835, "bias": 1.7183812000404686,
+                "security_level": 80, "minimal_lwe_dimension": 450}]"#,
+        )
+        .unwrap();
+        let solution_path = dir.join("solution.bin");
+        let args_with_curves = |curves: &PathBuf| {
+            OptimizeArgs::parse_from([
+                "optimize".as_ref(),
+                dag_path.as_os_str(),
+                "--output".as_ref(),
+                solution_path.as_os_str(),
+                "--output-format=bincode".as_ref(),
+                "--security-curves".as_ref(),
+                curves.as_os_str(),
+            ])
+        };
+
+        run(&args_with_curves(&curves_path)).unwrap();
+        let solution: CircuitSolution = Format::Bincode
+            .read(File::open(&solution_path).unwrap())
+            .unwrap();
+        assert!(!solution.is_feasible);
+        assert_eq!(solution.error_msg, "128 bits of security is not supported");
+
+        assert!(run(&args_with_curves(&dir.join("missing.json"))).is_err());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_optimize_with_refresh() {
+        let mut dag = Dag::new();
+        let input = dag.add_input(4, Shape::number());
+        let lut = dag.add_lut(input, FunctionTable::UNKWOWN, 4);
+        // the output keeps the input noise, which grows through the composition
+        let output = dag.add_dot([input, lut], [4, 1]);
+        let args = |refresh: bool| {
+            let mut args = vec!["optimize", "dag.json", "--encoding=native", "--composable"];
+            if refresh {
+                args.push("--refresh");
+            }
+            OptimizeArgs::parse_from(args)
+        };
+
+        let solution = optimize(&dag, &args(false), SecurityCurves::compiled());
+        assert!(!solution.is_feasible);
+        let solution = optimize(&dag, &args(true), SecurityCurves::compiled());
+        assert!(solution.is_feasible, "{}", solution.error_msg);
+        assert_eq!(solution.refreshes.len(), 1);
+        assert_eq!(solution.refreshes[0].refreshed, output);
+    }
+
+    #[test]
+    fn test_growing_composition_is_infeasible() {
+        let mut dag = Dag::new();
+        let input = dag.add_input(4, Shape::number());
+        let output = dag.add_dot([input], [2]);
+        let _ = dag.add_lut(output, FunctionTable::UNKWOWN, 4);
+        dag.tag_operator_as_output(output);
+        dag.add_composition(output, input);
+
+        for args in [
+            vec!["optimize", "dag.json", "--encoding=native"],
+            vec!["optimize", "dag.json"],
+            vec!["optimize", "dag.json", "--strategy=solo-key"],
+        ] {
+            let solution = optimize(
+                &dag,
+                &OptimizeArgs::parse_from(&args),
+                SecurityCurves::compiled(),
+            );
+            assert!(!solution.is_feasible, "{args:?}");
+            assert!(
+                solution
+                    .error_msg
+                    .starts_with("Program can not be composed"),
+                "{args:?}: {}",
+                solution.error_msg
+            );
+        }
+    }
+}
@@ -0,0 +1,250 @@
+//! Core-SVP style estimate of lattice attacks on LWE, to cross-check the linear security curves
+//! without running the Sage lattice estimator.
+//!
+//! Two attacks are covered, both using BKZ and choosing the number of samples freely: the primal
+//! attack through unique-SVP (Alkim et al. 2016) and the dual distinguishing attack with sieving
+//! amortization. The secret is rescaled to the error size in both embeddings. Hybrid and
+//! combinatorial attacks are not covered, so the estimate only upper-bounds the security reported
+//! by the lattice estimator scripts in `lattice-scripts`.
+
+use std::f64::consts::{E, PI};
+
+use crate::key_distribution::KeyDistribution;
+
+/// Margin in bits under which an estimate and a curve are considered to agree.
+pub const TOLERANCE: f64 = 2.0;
+
+/// Below this block size the root hermite factor model is not meaningful.
+const MIN_BLOCK_SIZE: u64 = 40;
+
+/// log2 of the number of short vectors a sieve outputs in block size beta, per unit of beta.
+const SIEVE_OUTPUT_LOG2_PER_BLOCK: f64 = 0.2075;
+
+/// Cost of running BKZ in a given block size.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReductionCost {
+    /// One SVP call with classical sieving, 2^(0.292 beta)
+    CoreSvp,
+    /// The lattice estimator `RC.BDGL16` model, used to generate the curves
+    Bdgl16,
+}
+
+impl ReductionCost {
+    fn log2_cost(self, block_size: f64, dimension: f64) -> f64 {
+        match self {
+            Self::CoreSvp => 0.292 * block_size,
+            Self::Bdgl16 => 0.292 * block_size + 16.4 + (8.0 * dimension).log2(),
+        }
+    }
+}
+
+/// An LWE problem, the error being a centered gaussian.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LweInstance {
+    pub lwe_dimension: u64,
+    pub ciphertext_modulus_log: u32,
+    /// log2 of the error standard deviation on the torus (i.e. relative to q)
+    pub log2_std: f64,
+    pub key_distribution: KeyDistribution,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AttackCost {
+    /// log2 of the number of operations, infinite when no block size succeeds
+    pub log2_cost: f64,
+    pub block_size: u64,
+    pub samples: u64,
+}
+
+impl AttackCost {
+    const INFEASIBLE: Self = Self {
+        log2_cost: f64::INFINITY,
+        block_size: 0,
+        samples: 0,
+    };
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SecurityEstimate {
+    pub primal_usvp: AttackCost,
+    pub dual: AttackCost,
+}
+
+impl SecurityEstimate {
+    /// Bits of security, i.e. the cost of the cheapest attack.
+    pub fn security_level(&self) -> f64 {
+        f64::min(self.primal_usvp.log2_cost, self.dual.log2_cost)
+    }
+
+    /// Whether the estimate is below `security_level` by more than `tolerance` bits.
+    pub fn falls_short_of(&self, security_level: u64, tolerance: f64) -> bool {
+        self.security_level() < security_level as f64 - tolerance
+    }
+}
+
+/// log2 of the root hermite factor reached by BKZ with `block_size`.
+fn log2_root_hermite_factor(block_size: f64) -> f64 {
+    let beta = block_size;
+    ((beta / (2.0 * PI * E)).log2() + (PI * beta).log2() / beta) / (2.0 * (beta - 1.0))
+}
+
+/// Noise and secret sizes, as log2 on the integer scale.
+struct Scales {
+    n: f64,
+    log2_q: f64,
+    log2_error_std: f64,
+    log2_secret_std: f64,
+}
+
+impl Scales {
+    fn new(instance: LweInstance) -> Self {
+        let log2_q = f64::from(instance.ciphertext_modulus_log);
+        Self {
+            n: instance.lwe_dimension as f64,
+            log2_q,
+            log2_error_std: instance.log2_std + log2_q,
+            log2_secret_std: 0.5
+                * instance
+                    .key_distribution
+                    .variance(instance.lwe_dimension)
+                    .log2(),
+        }
+    }
+
+    /// Largest block size tried, far above any block size that can succeed.
+    fn max_block_size(&self) -> u64 {
+        4 * self.n as u64 + 64
+    }
+}
+
+/// Primal attack: the secret and error form an unusually short vector of the Kannan embedding
+/// lattice, found once the BKZ projected norm falls below the Gram-Schmidt norm at index d - beta.
+fn primal_usvp(scales: &Scales, cost: ReductionCost) -> AttackCost {
+    let Scales {
+        n,
+        log2_q,
+        log2_error_std,
+        log2_secret_std,
+    } = *scales;
+    // the secret coordinates are scaled to match the error
+    let log2_scaling = log2_error_std - log2_secret_std;
+
+    for block_size in MIN_BLOCK_SIZE..=scales.max_block_size() {
+        let beta = block_size as f64;
+        let log2_delta = log2_root_hermite_factor(beta);
+        let lhs = log2_error_std + 0.5 * beta.log2();
+        let rhs = |d: f64| {
+            let log2_volume = (d - n - 1.0) * log2_q + n * log2_scaling;
+            (2.0 * beta - d - 1.0) * log2_delta + log2_volume / d
+        };
+
+        let best_dimension = (((n + 1.0) * log2_q - n * log2_scaling) / log2_delta).sqrt();
+        let min_dimension = f64::max(n + 2.0, beta);
+        for d in [best_dimension.floor(), best_dimension.ceil()] {
+            let d = d.max(min_dimension);
+            if lhs <= rhs(d) {
+                return AttackCost {
+                    log2_cost: cost.log2_cost(beta, d),
+                    block_size,
+                    samples: (d - n - 1.0) as u64,
+                };
+            }
+        }
+    }
+    AttackCost::INFEASIBLE
+}
+
+/// Dual attack: short vectors of the scaled dual lattice turn LWE samples into samples biased
+/// towards 0, each BKZ run giving as many short vectors as the final sieve outputs.
+fn dual(scales: &Scales, cost: ReductionCost) -> AttackCost {
+    let Scales {
+        n,
+        log2_q,
+        log2_error_std,
+        log2_secret_std,
+    } = *scales;
+    // the secret part of the dual vector is scaled so that both parts contribute the same noise
+    let log2_scaling = log2_secret_std - log2_error_std;
+    let log2_volume = n * (log2_q + log2_scaling);
+
+    let mut best = AttackCost::INFEASIBLE;
+    for block_size in MIN_BLOCK_SIZE..=scales.max_block_size() {
+        let beta = block_size as f64;
+        let log2_delta = log2_root_hermite_factor(beta);
+
+        let best_dimension = (log2_volume.max(0.0) / log2_delta).sqrt().round();
+        let d = best_dimension.max(n + 1.0).max(beta);
+        let log2_length = d * log2_delta + log2_volume / d;
+
+        // the advantage is exp(-2 pi^2 (sigma * length / q)^2), needing 1 / advantage^2 samples
+        let log2_relative_std = log2_error_std + log2_length - log2_q;
+        let log2_repetitions =
+            4.0 * PI * PI * f64::exp2(2.0 * log2_relative_std) / std::f64::consts::LN_2;
+        let log2_cost = cost.log2_cost(beta, d)
+            + f64::max(0.0, log2_repetitions - SIEVE_OUTPUT_LOG2_PER_BLOCK * beta);
+
+        if log2_cost < best.log2_cost {
+            best = AttackCost {
+                log2_cost,
+                block_size,
+                samples: (d - n) as u64,
+            };
+        }
+    }
+    best
+}
+
+pub fn estimate(instance: LweInstance, cost: ReductionCost) -> SecurityEstimate {
+    let scales = Scales::new(instance);
+    SecurityEstimate {
+        primal_usvp: primal_usvp(&scales, cost),
+        dual: dual(&scales, cost),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gaussian::security::security_weight;
+
+    fn binary_instance(lwe_dimension: u64, log2_std: f64) -> LweInstance {
+        LweInstance {
+            lwe_dimension,
+            ciphertext_modulus_log: 64,
+            log2_std,
+            key_distribution: KeyDistribution::Binary,
+        }
+    }
+
+    #[test]
+    fn curve_points_are_close_to_their_level() {
+        // Without the hybrid attacks the estimate only upper-bounds the curves security: it may
+        // exceed the level by a few bits but must not fall short of it by more than the tolerance
+        // the cross-check flags.
+        let max_overestimate = 8.0;
+        for (security_level, lwe_dimension) in [(128, 1024), (128, 2048), (192, 2048)] {
+            let weight = security_weight(security_level).unwrap();
+            let log2_std = weight.secure_log2_std(lwe_dimension, 64.0);
+            let estimate = estimate(
+                binary_instance(lwe_dimension, log2_std),
+                ReductionCost::Bdgl16,
+            );
+            let bits = estimate.security_level();
+            assert!(
+                !estimate.falls_short_of(security_level, TOLERANCE)
+                    && bits <= security_level as f64 + max_overestimate,
+                "{security_level} bits curve at n = {lwe_dimension}: estimated {bits}"
+            );
+        }
+    }
+
+    #[test]
+    fn security_grows_with_noise_and_dimension() {
+        let base = estimate(binary_instance(1024, -30.0), ReductionCost::CoreSvp);
+        let noisier = estimate(binary_instance(1024, -20.0), ReductionCost::CoreSvp);
+        let larger = estimate(binary_instance(2048, -30.0), ReductionCost::CoreSvp);
+        assert!(noisier.security_level() > base.security_level());
+        assert!(larger.security_level() > base.security_level());
+        assert!(base.falls_short_of(128, 0.0));
+    }
+}
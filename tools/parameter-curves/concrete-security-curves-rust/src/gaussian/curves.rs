@@ -0,0 +1,630 @@
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use serde::Deserialize;
+
+use super::curves_gen::SECURITY_WEIGHTS_ARRAY;
+use super::security_weights::SecurityWeights;
+use crate::key_distribution::KeyDistribution;
+
+/// One entry of `curves.json`, as produced by the lattice-estimator scripts.
+#[derive(Deserialize)]
+struct CurveRecord {
+    security_level: u64,
+    slope: f64,
+    bias: f64,
+    minimal_lwe_dimension: u64,
+    #[serde(default)]
+    key_distribution: KeyDistribution,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum CurvesError {
+    Io(String),
+    Parse(String),
+    Empty,
+    DuplicateSecurityLevel(u64),
+    InvalidCurve { security_level: u64, reason: String },
+}
+
+impl fmt::Display for CurvesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "cannot read security curves: {err}"),
+            Self::Parse(err) => write!(f, "cannot parse security curves: {err}"),
+            Self::Empty => write!(f, "no security curve"),
+            Self::DuplicateSecurityLevel(level) => {
+                write!(f, "security level {level} is defined more than once")
+            }
+            Self::InvalidCurve {
+                security_level,
+                reason,
+            } => write!(
+                f,
+                "invalid curve for security level {security_level}: {reason}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CurvesError {}
+
+/// Ciphertext moduli the curves can be used with. They were fitted for 64 bits, the noise model on
+/// the torus stays meaningful as long as the modulus is large compared to the noise.
+pub const SUPPORTED_CIPHERTEXT_MODULUS_LOGS: RangeInclusive<u32> = 16..=128;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SecurityError {
+    UnsupportedSecurityLevel(u64),
+    DimensionBelowMinimum {
+        lwe_dimension: u64,
+        minimal_lwe_dimension: u64,
+    },
+    ModulusOutOfRange(u32),
+    UntabulatedSparseKey {
+        hamming_weight: u64,
+    },
+}
+
+impl fmt::Display for SecurityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnsupportedSecurityLevel(level) => {
+                write!(f, "{level} bits of security is not supported")
+            }
+            Self::DimensionBelowMinimum {
+                lwe_dimension,
+                minimal_lwe_dimension,
+            } => write!(
+                f,
+                "lwe dimension {lwe_dimension} is below the minimal secure dimension {minimal_lwe_dimension}"
+            ),
+            Self::ModulusOutOfRange(ciphertext_modulus_log) => write!(
+                f,
+                "a {ciphertext_modulus_log} bits ciphertext modulus is not supported, expected {}..={}",
+                SUPPORTED_CIPHERTEXT_MODULUS_LOGS.start(),
+                SUPPORTED_CIPHERTEXT_MODULUS_LOGS.end()
+            ),
+            Self::UntabulatedSparseKey { hamming_weight } => write!(
+                f,
+                "sparse keys of hamming weight {hamming_weight} need their own security curves"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SecurityError {}
+
+type Curves = Vec<(u64, SecurityWeights)>;
+
+/// The security curves of every supported security level, sorted by level.
+///
+/// Curves are fitted for binary keys. Other key distributions use their own curves when some are
+/// tabulated, otherwise the binary curves shifted by the ratio of the key standard deviations: the
+/// primal and dual attacks rescale the secret to the error size, so the cost only depends on the
+/// error to secret ratio. Sparse keys are rejected unless tabulated, the hybrid attacks making them
+/// weaker than this ratio tells.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SecurityCurves {
+    curves: Curves,
+    key_curves: Vec<(KeyDistribution, Curves)>,
+    key_distribution: KeyDistribution,
+    interpolate: bool,
+}
+
+impl Default for SecurityCurves {
+    fn default() -> Self {
+        Self::compiled().clone()
+    }
+}
+
+impl SecurityCurves {
+    /// The curves compiled in this crate, generated from `tools/parameter-curves/json/curves.json`.
+    pub fn compiled() -> &'static Self {
+        static COMPILED: OnceLock<SecurityCurves> = OnceLock::new();
+        COMPILED.get_or_init(|| Self {
+            curves: SECURITY_WEIGHTS_ARRAY.to_vec(),
+            key_curves: vec![],
+            key_distribution: KeyDistribution::Binary,
+            interpolate: false,
+        })
+    }
+
+    /// Curves of binary keys.
+    pub fn new(
+        curves: impl IntoIterator<Item = (u64, SecurityWeights)>,
+    ) -> Result<Self, CurvesError> {
+        Ok(Self {
+            curves: validated(curves)?,
+            key_curves: vec![],
+            key_distribution: KeyDistribution::Binary,
+            interpolate: false,
+        })
+    }
+
+    /// Adds or replaces the curves of `key_distribution`.
+    pub fn with_key_distribution_curves(
+        mut self,
+        key_distribution: KeyDistribution,
+        curves: impl IntoIterator<Item = (u64, SecurityWeights)>,
+    ) -> Result<Self, CurvesError> {
+        let curves = validated(curves)?;
+        if key_distribution == KeyDistribution::Binary {
+            self.curves = curves;
+        } else if let Some(entry) = self
+            .key_curves
+            .iter_mut()
+            .find(|(distribution, _)| *distribution == key_distribution)
+        {
+            entry.1 = curves;
+        } else {
+            self.key_curves.push((key_distribution, curves));
+        }
+        Ok(self)
+    }
+
+    /// Selects the distribution of the secret keys the queries are made for, binary by default.
+    #[must_use]
+    pub fn for_key_distribution(mut self, key_distribution: KeyDistribution) -> Self {
+        self.key_distribution = key_distribution;
+        self
+    }
+
+    pub fn key_distribution(&self) -> KeyDistribution {
+        self.key_distribution
+    }
+
+    /// Whether the selected key distribution has its own curves.
+    pub fn is_tabulated(&self) -> bool {
+        self.key_distribution == KeyDistribution::Binary
+            || self
+                .key_curves
+                .iter()
+                .any(|(distribution, _)| *distribution == self.key_distribution)
+    }
+
+    /// The curves of the selected key distribution, or the binary ones it is derived from.
+    fn active_curves(&self) -> &Curves {
+        self.key_curves
+            .iter()
+            .find(|(distribution, _)| *distribution == self.key_distribution)
+            .map_or(&self.curves, |(_, curves)| curves)
+    }
+
+    /// log2 of the std a key of `lwe_dimension` gives compared to a binary key.
+    fn log2_key_std_ratio(&self, lwe_dimension: u64) -> f64 {
+        if self.is_tabulated() {
+            return 0.0;
+        }
+        let binary_variance = KeyDistribution::Binary.variance(lwe_dimension);
+        0.5 * (self.key_distribution.variance(lwe_dimension) / binary_variance).log2()
+    }
+
+    /// Accepts security levels between two tabulated levels, the slope and bias being interpolated
+    /// linearly and the minimal dimension taken from the upper level.
+    #[must_use]
+    pub fn with_interpolation(mut self, interpolate: bool) -> Self {
+        self.interpolate = interpolate;
+        self
+    }
+
+    /// Parses and validates curves in the `curves.json` format, records without a
+    /// `key_distribution` being for binary keys.
+    pub fn from_json_str(json: &str) -> Result<Self, CurvesError> {
+        let records: Vec<CurveRecord> =
+            serde_json::from_str(json).map_err(|err| CurvesError::Parse(err.to_string()))?;
+        let mut by_distribution: Vec<(KeyDistribution, Curves)> = vec![];
+        for record in records {
+            let weights = SecurityWeights {
+                slope: record.slope,
+                bias: record.bias,
+                minimal_lwe_dimension: record.minimal_lwe_dimension,
+            };
+            match by_distribution
+                .iter_mut()
+                .find(|(distribution, _)| *distribution == record.key_distribution)
+            {
+                Some((_, curves)) => curves.push((record.security_level, weights)),
+                None => by_distribution.push((
+                    record.key_distribution,
+                    vec![(record.security_level, weights)],
+                )),
+            }
+        }
+        let binary = by_distribution
+            .iter()
+            .position(|(distribution, _)| *distribution == KeyDistribution::Binary)
+            .ok_or(CurvesError::Empty)?;
+        let (_, binary_curves) = by_distribution.swap_remove(binary);
+        by_distribution.into_iter().try_fold(
+            Self::new(binary_curves)?,
+            |curves, (key_distribution, key_curves)| {
+                curves.with_key_distribution_curves(key_distribution, key_curves)
+            },
+        )
+    }
+
+    pub fn from_json_file(path: impl AsRef<Path>) -> Result<Self, CurvesError> {
+        let json = std::fs::read_to_string(path).map_err(|err| CurvesError::Io(err.to_string()))?;
+        Self::from_json_str(&json)
+    }
+
+    pub fn is_compiled(&self) -> bool {
+        self == Self::compiled()
+    }
+
+    /// Identifies the curves in cache keys, empty for the compiled curves.
+    pub fn cache_suffix(&self) -> String {
+        if self.is_compiled() {
+            return String::new();
+        }
+        let curve_words = |curves: &Curves| {
+            curves
+                .iter()
+                .flat_map(|(level, w)| {
+                    [
+                        *level,
+                        w.slope.to_bits(),
+                        w.bias.to_bits(),
+                        w.minimal_lwe_dimension,
+                    ]
+                })
+                .collect::<Vec<_>>()
+        };
+        let hash = curve_words(&self.curves)
+            .into_iter()
+            .chain(self.key_curves.iter().flat_map(|(distribution, curves)| {
+                distribution
+                    .fingerprint()
+                    .into_iter()
+                    .chain(curve_words(curves))
+            }))
+            .chain(self.key_distribution.fingerprint())
+            .chain([u64::from(self.interpolate)])
+            .flat_map(u64::to_le_bytes)
+            .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+                (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+            });
+        format!("-curves{hash:016x}")
+    }
+
+    pub fn supported_security_levels(&self) -> impl Iterator<Item = u64> + '_ {
+        self.active_curves()
+            .iter()
+            .map(|(security_level, _)| *security_level)
+    }
+
+    /// The tabulated weights of the selected key distribution, or of binary keys when it is derived.
+    pub fn security_weight(&self, security_level: u64) -> Option<SecurityWeights> {
+        let curves = self.active_curves();
+        let index = curves
+            .binary_search_by_key(&security_level, |(security_level, _weights)| {
+                *security_level
+            })
+            .ok()?;
+
+        Some(curves[index].1)
+    }
+
+    /// The weights of a tabulated level, or interpolated between its neighbours when enabled.
+    pub fn try_security_weight(
+        &self,
+        security_level: u64,
+    ) -> Result<SecurityWeights, SecurityError> {
+        let curves = self.active_curves();
+        match curves.binary_search_by_key(&security_level, |(security_level, _weights)| {
+            *security_level
+        }) {
+            Ok(index) => Ok(curves[index].1),
+            Err(index) if self.interpolate && 0 < index && index < curves.len() => {
+                let (low_level, low) = curves[index - 1];
+                let (high_level, high) = curves[index];
+                let t = (security_level - low_level) as f64 / (high_level - low_level) as f64;
+                Ok(SecurityWeights {
+                    slope: low.slope + t * (high.slope - low.slope),
+                    bias: low.bias + t * (high.bias - low.bias),
+                    minimal_lwe_dimension: high.minimal_lwe_dimension,
+                })
+            }
+            Err(_) => Err(SecurityError::UnsupportedSecurityLevel(security_level)),
+        }
+    }
+
+    /// Checks that the curves can be used for this level and modulus, whatever the dimension.
+    pub fn check(
+        &self,
+        security_level: u64,
+        ciphertext_modulus_log: u32,
+    ) -> Result<(), SecurityError> {
+        self.checked_security_weight(security_level, ciphertext_modulus_log)
+            .map(|_| ())
+    }
+
+    fn checked_security_weight(
+        &self,
+        security_level: u64,
+        ciphertext_modulus_log: u32,
+    ) -> Result<SecurityWeights, SecurityError> {
+        if !SUPPORTED_CIPHERTEXT_MODULUS_LOGS.contains(&ciphertext_modulus_log) {
+            return Err(SecurityError::ModulusOutOfRange(ciphertext_modulus_log));
+        }
+        if let KeyDistribution::Sparse { hamming_weight } = self.key_distribution {
+            if !self.is_tabulated() {
+                return Err(SecurityError::UntabulatedSparseKey { hamming_weight });
+            }
+        }
+        self.try_security_weight(security_level)
+    }
+
+    /// Noise ensuring security
+    pub fn try_minimal_variance_lwe(
+        &self,
+        lwe_dimension: u64,
+        ciphertext_modulus_log: u32,
+        security_level: u64,
+    ) -> Result<f64, SecurityError> {
+        self.try_minimal_variance_glwe(lwe_dimension, 1, ciphertext_modulus_log, security_level)
+    }
+
+    /// Noise ensuring security
+    pub fn try_minimal_variance_glwe(
+        &self,
+        glwe_dimension: u64,
+        polynomial_size: u64,
+        ciphertext_modulus_log: u32,
+        security_level: u64,
+    ) -> Result<f64, SecurityError> {
+        let lwe_dimension = glwe_dimension * polynomial_size;
+        let security_weights =
+            self.checked_security_weight(security_level, ciphertext_modulus_log)?;
+        let ciphertext_modulus_log = ciphertext_modulus_log as f64;
+        let secure_log2_std =
+            security_weights.try_secure_log2_std(lwe_dimension, ciphertext_modulus_log)?;
+        // a larger secret lets the error shrink by the same factor
+        let secure_log2_std = f64::max(
+            secure_log2_std - self.log2_key_std_ratio(lwe_dimension),
+            SecurityWeights::minimal_log2_std(ciphertext_modulus_log),
+        );
+        Ok(f64::exp2(2.0 * secure_log2_std))
+    }
+
+    /// Noise ensuring security, a variance above 1 when the dimension is too small to be secure.
+    ///
+    /// # Errors
+    /// When the security level, the modulus or the key distribution is not supported.
+    pub fn checked_minimal_variance_glwe(
+        &self,
+        glwe_dimension: u64,
+        polynomial_size: u64,
+        ciphertext_modulus_log: u32,
+        security_level: u64,
+    ) -> Result<f64, SecurityError> {
+        match self.try_minimal_variance_glwe(
+            glwe_dimension,
+            polynomial_size,
+            ciphertext_modulus_log,
+            security_level,
+        ) {
+            Err(SecurityError::DimensionBelowMinimum { .. }) => {
+                Ok(f64::exp2(2.0 * ciphertext_modulus_log as f64))
+            }
+            result => result,
+        }
+    }
+
+    /// Noise ensuring security, a variance above 1 when the dimension is too small to be secure.
+    ///
+    /// # Panics
+    /// When the security level is not supported.
+    pub fn minimal_variance_lwe(
+        &self,
+        lwe_dimension: u64,
+        ciphertext_modulus_log: u32,
+        security_level: u64,
+    ) -> f64 {
+        self.minimal_variance_glwe(lwe_dimension, 1, ciphertext_modulus_log, security_level)
+    }
+
+    /// Noise ensuring security, a variance above 1 when the dimension is too small to be secure.
+    ///
+    /// # Panics
+    /// When the security level, the modulus or the key distribution is not supported.
+    pub fn minimal_variance_glwe(
+        &self,
+        glwe_dimension: u64,
+        polynomial_size: u64,
+        ciphertext_modulus_log: u32,
+        security_level: u64,
+    ) -> f64 {
+        self.checked_minimal_variance_glwe(
+            glwe_dimension,
+            polynomial_size,
+            ciphertext_modulus_log,
+            security_level,
+        )
+        .unwrap_or_else(|err| panic!("{err}"))
+    }
+}
+
+fn validated(
+    curves: impl IntoIterator<Item = (u64, SecurityWeights)>,
+) -> Result<Curves, CurvesError> {
+    let mut curves: Vec<_> = curves.into_iter().collect();
+    if curves.is_empty() {
+        return Err(CurvesError::Empty);
+    }
+    curves.sort_by_key(|(security_level, _)| *security_level);
+    for pair in curves.windows(2) {
+        if pair[0].0 == pair[1].0 {
+            return Err(CurvesError::DuplicateSecurityLevel(pair[0].0));
+        }
+    }
+    for (security_level, weights) in &curves {
+        let invalid = |reason: &str| CurvesError::InvalidCurve {
+            security_level: *security_level,
+            reason: reason.to_string(),
+        };
+        if !weights.slope.is_finite() || !weights.bias.is_finite() {
+            return Err(invalid("slope and bias must be finite"));
+        }
+        if weights.slope >= 0.0 {
+            return Err(invalid("the noise must decrease with the dimension"));
+        }
+        if weights.minimal_lwe_dimension == 0 {
+            return Err(invalid("the minimal lwe dimension must be positive"));
+        }
+    }
+    Ok(curves)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CURVES_JSON: &str = include_str!("../../../json/curves.json");
+
+    #[test]
+    fn json_matches_compiled_curves() {
+        let curves = SecurityCurves::from_json_str(CURVES_JSON).unwrap();
+        assert!(curves.is_compiled());
+        assert_eq!(curves.cache_suffix(), "");
+    }
+
+    #[test]
+    fn invalid_curves_are_rejected() {
+        assert_eq!(SecurityCurves::from_json_str("[]"), Err(CurvesError::Empty));
+        let duplicated = r#"[
+            {"slope": -0.04, "bias": 1.7, "security_level": 80, "minimal_lwe_dimension": 450},
+            {"slope": -0.03, "bias": 2.6, "security_level": 80, "minimal_lwe_dimension": 450}
+        ]"#;
+        assert_eq!(
+            SecurityCurves::from_json_str(duplicated),
+            Err(CurvesError::DuplicateSecurityLevel(80))
+        );
+        let increasing =
+            r#"[{"slope": 0.04, "bias": 1.7, "security_level": 80, "minimal_lwe_dimension": 450}]"#;
+        assert!(matches!(
+            SecurityCurves::from_json_str(increasing),
+            Err(CurvesError::InvalidCurve {
+                security_level: 80,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn custom_curves_get_their_own_cache() {
+        let custom = r#"[{"slope": -0.02, "bias": 3.0, "security_level": 100, "minimal_lwe_dimension": 500}]"#;
+        let curves = SecurityCurves::from_json_str(custom).unwrap();
+        assert_eq!(
+            curves.supported_security_levels().collect::<Vec<_>>(),
+            [100]
+        );
+        assert!(curves.security_weight(128).is_none());
+        assert_ne!(curves.cache_suffix(), "");
+    }
+
+    #[test]
+    fn unsupported_queries_are_errors() {
+        let curves = SecurityCurves::compiled();
+        assert_eq!(
+            curves.try_minimal_variance_lwe(1024, 64, 96),
+            Err(SecurityError::UnsupportedSecurityLevel(96))
+        );
+        assert_eq!(
+            curves.try_minimal_variance_lwe(1024, 8, 128),
+            Err(SecurityError::ModulusOutOfRange(8))
+        );
+        let minimal_lwe_dimension = curves.security_weight(128).unwrap().minimal_lwe_dimension;
+        assert_eq!(
+            curves.try_minimal_variance_lwe(minimal_lwe_dimension - 1, 64, 128),
+            Err(SecurityError::DimensionBelowMinimum {
+                lwe_dimension: minimal_lwe_dimension - 1,
+                minimal_lwe_dimension,
+            })
+        );
+        assert_eq!(
+            curves.try_minimal_variance_glwe(2, 1024, 64, 128),
+            Ok(curves.minimal_variance_glwe(2, 1024, 64, 128))
+        );
+    }
+
+    #[test]
+    fn interpolated_levels_are_between_their_neighbours() {
+        let curves = SecurityCurves::compiled().clone().with_interpolation(true);
+        let variance = |level| curves.try_minimal_variance_lwe(2048, 64, level).unwrap();
+        assert!(variance(112) < variance(120) && variance(120) < variance(128));
+        assert_eq!(
+            variance(128),
+            SecurityCurves::compiled().minimal_variance_lwe(2048, 64, 128)
+        );
+        assert_eq!(
+            curves.try_security_weight(1024),
+            Err(SecurityError::UnsupportedSecurityLevel(1024))
+        );
+        assert_eq!(
+            curves.try_security_weight(64),
+            Err(SecurityError::UnsupportedSecurityLevel(64))
+        );
+    }
+
+    #[test]
+    fn larger_secrets_need_less_noise() {
+        let binary = SecurityCurves::compiled();
+        let variance = |key_distribution| {
+            binary
+                .clone()
+                .for_key_distribution(key_distribution)
+                .try_minimal_variance_lwe(1024, 64, 128)
+                .unwrap()
+        };
+        let binary_variance = variance(KeyDistribution::Binary);
+        assert_eq!(binary_variance, binary.minimal_variance_lwe(1024, 64, 128));
+        assert!(variance(KeyDistribution::Ternary) < binary_variance);
+        assert!(variance(KeyDistribution::Gaussian { std_dev: 3.2 }) < binary_variance);
+    }
+
+    #[test]
+    fn untabulated_sparse_keys_are_rejected() {
+        let sparse = SecurityCurves::compiled()
+            .clone()
+            .for_key_distribution(KeyDistribution::Sparse { hamming_weight: 64 });
+        let rejected = Err(SecurityError::UntabulatedSparseKey { hamming_weight: 64 });
+        assert_eq!(sparse.check(128, 64), rejected);
+        assert_eq!(
+            sparse.try_minimal_variance_lwe(1024, 64, 128).map(|_| ()),
+            rejected
+        );
+    }
+
+    #[test]
+    fn tabulated_key_distributions_use_their_own_curves() {
+        let json = r#"[
+            {"slope": -0.04, "bias": 1.7, "security_level": 80, "minimal_lwe_dimension": 450},
+            {"slope": -0.03, "bias": 2.6, "security_level": 80, "minimal_lwe_dimension": 450, "key_distribution": "ternary"}
+        ]"#;
+        let curves = SecurityCurves::from_json_str(json).unwrap();
+        let ternary = curves
+            .clone()
+            .for_key_distribution(KeyDistribution::Ternary);
+        assert!(ternary.is_tabulated());
+        assert_eq!(ternary.security_weight(80).unwrap().bias, 2.6);
+        assert_ne!(ternary.cache_suffix(), curves.cache_suffix());
+        let gaussian = curves
+            .clone()
+            .for_key_distribution(KeyDistribution::Gaussian { std_dev: 1.0 });
+        assert!(!gaussian.is_tabulated());
+        assert_eq!(gaussian.security_weight(80).unwrap().bias, 1.7);
+        let sparse_json = r#"[
+            {"slope": -0.04, "bias": 1.7, "security_level": 80, "minimal_lwe_dimension": 450},
+            {"slope": -0.02, "bias": 3.1, "security_level": 80, "minimal_lwe_dimension": 450, "key_distribution": {"sparse": {"hamming_weight": 64}}}
+        ]"#;
+        let sparse = SecurityCurves::from_json_str(sparse_json)
+            .unwrap()
+            .for_key_distribution(KeyDistribution::Sparse { hamming_weight: 64 });
+        assert!(sparse.is_tabulated());
+        assert!(sparse.try_minimal_variance_lwe(1024, 64, 80).is_ok());
+    }
+}
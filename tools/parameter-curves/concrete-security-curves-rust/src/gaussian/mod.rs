@@ -1,3 +1,4 @@
+pub mod curves;
 pub mod curves_gen;
-pub mod security_weights;
 pub mod security;
+pub mod security_weights;
@@ -1,46 +1,42 @@
-use super::curves_gen::SECURITY_WEIGHTS_ARRAY;
+use super::curves::{SecurityCurves, SecurityError};
 use super::security_weights::SecurityWeights;
 
 pub fn supported_security_levels() -> impl std::iter::Iterator<Item = u64> {
-    SECURITY_WEIGHTS_ARRAY
-        .iter()
-        .map(|(security_level, _)| *security_level)
+    SecurityCurves::compiled().supported_security_levels()
 }
 
 pub fn security_weight(security_level: u64) -> Option<SecurityWeights> {
-    let index = SECURITY_WEIGHTS_ARRAY
-        .binary_search_by_key(&security_level, |(security_level, _weights)| {
-            *security_level
-        })
-        .ok()?;
-
-    Some(SECURITY_WEIGHTS_ARRAY[index].1)
+    SecurityCurves::compiled().security_weight(security_level)
 }
 
-/// Noise ensuring security
+/// Noise ensuring security, a variance above 1 when the dimension is too small to be secure.
+///
+/// # Errors
+/// When the security level or the modulus is not supported by the compiled curves.
 pub fn minimal_variance_lwe(
     lwe_dimension: u64,
     ciphertext_modulus_log: u32,
     security_level: u64,
-) -> f64 {
+) -> Result<f64, SecurityError> {
     minimal_variance_glwe(lwe_dimension, 1, ciphertext_modulus_log, security_level)
 }
 
-/// Noise ensuring security
+/// Noise ensuring security, a variance above 1 when the dimension is too small to be secure.
+///
+/// # Errors
+/// When the security level or the modulus is not supported by the compiled curves.
 pub fn minimal_variance_glwe(
     glwe_dimension: u64,
     polynomial_size: u64,
     ciphertext_modulus_log: u32,
     security_level: u64,
-) -> f64 {
-    let equiv_lwe_dimension = glwe_dimension * polynomial_size;
-    let security_weights = security_weight(security_level)
-        .unwrap_or_else(|| panic!("{security_level} bits of security is not supported"));
-
-    let secure_log2_std =
-        security_weights.secure_log2_std(equiv_lwe_dimension, ciphertext_modulus_log as f64);
-    let log2_var = 2.0 * secure_log2_std;
-    f64::exp2(log2_var)
+) -> Result<f64, SecurityError> {
+    SecurityCurves::compiled().checked_minimal_variance_glwe(
+        glwe_dimension,
+        polynomial_size,
+        ciphertext_modulus_log,
+        security_level,
+    )
 }
 
 #[cfg(test)]
@@ -62,10 +58,19 @@ mod tests {
         let golden_std_dev = 2.168_404_344_971_009e-19;
         let security_level = 128;
 
-        let actual_var = minimal_variance_glwe(10, 1 << 14, integer_size, security_level);
+        let actual_var = minimal_variance_glwe(10, 1 << 14, integer_size, security_level).unwrap();
         let actual_std_dev = actual_var.sqrt();
         let expected_std_dev = (0.99 * golden_std_dev)..(1.01 * golden_std_dev);
         assert!(expected_std_dev.contains(&actual_std_dev));
     }
 
+    #[test]
+    fn unknown_security_level_is_an_error() {
+        assert_eq!(
+            minimal_variance_lwe(1024, 64, 1),
+            Err(SecurityError::UnsupportedSecurityLevel(1))
+        );
+        // too small to be secure, but not an error
+        assert!(minimal_variance_lwe(1, 64, 128).unwrap() > 1.0);
+    }
 }
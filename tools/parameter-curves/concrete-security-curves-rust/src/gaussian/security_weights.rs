@@ -1,4 +1,6 @@
-#[derive(Clone, Copy)]
+use super::curves::SecurityError;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct SecurityWeights {
     pub(crate) slope: f64,
     pub(crate) bias: f64,
@@ -6,10 +8,10 @@ pub struct SecurityWeights {
 }
 
 impl SecurityWeights {
+    /// log2 of the minimal secure std on the torus, `ciphertext_modulus_log` (i.e. a variance above
+    /// 1) when the dimension is below the minimal one.
     pub fn secure_log2_std(&self, lwe_dimension: u64, ciphertext_modulus_log: f64) -> f64 {
-        // ensure to have a minimal on std deviation covering the 2 lowest bits on modular scale
-        let epsilon_log2_std_modular = 2.0;
-        let epsilon_log2_std = epsilon_log2_std_modular - (ciphertext_modulus_log);
+        let epsilon_log2_std = Self::minimal_log2_std(ciphertext_modulus_log);
         // ensure the requested lwe_dimension is bigger than the minimal lwe dimension
         if self.minimal_lwe_dimension <= lwe_dimension {
             f64::max(
@@ -20,4 +22,24 @@ impl SecurityWeights {
             ciphertext_modulus_log
         }
     }
+
+    /// Whatever the curve, the std covers at least the 2 lowest bits on the modular scale.
+    pub(crate) fn minimal_log2_std(ciphertext_modulus_log: f64) -> f64 {
+        let epsilon_log2_std_modular = 2.0;
+        epsilon_log2_std_modular - ciphertext_modulus_log
+    }
+
+    pub fn try_secure_log2_std(
+        &self,
+        lwe_dimension: u64,
+        ciphertext_modulus_log: f64,
+    ) -> Result<f64, SecurityError> {
+        if lwe_dimension < self.minimal_lwe_dimension {
+            return Err(SecurityError::DimensionBelowMinimum {
+                lwe_dimension,
+                minimal_lwe_dimension: self.minimal_lwe_dimension,
+            });
+        }
+        Ok(self.secure_log2_std(lwe_dimension, ciphertext_modulus_log))
+    }
 }
@@ -0,0 +1,118 @@
+use serde::Deserialize;
+
+/// Distribution of the coefficients of an LWE secret key.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyDistribution {
+    /// Uniform in {0, 1}
+    #[default]
+    Binary,
+    /// Uniform in {-1, 0, 1}
+    Ternary,
+    /// Centered discrete gaussian, `std_dev` on the integer scale
+    Gaussian { std_dev: f64 },
+    /// Binary with exactly `hamming_weight` ones
+    Sparse { hamming_weight: u64 },
+}
+
+/// Mean and variance of one key coefficient, all the noise formulas depend on.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct KeyMoments {
+    pub mean: f64,
+    pub variance: f64,
+}
+
+impl KeyMoments {
+    pub const BINARY: Self = Self {
+        mean: 0.5,
+        variance: 0.25,
+    };
+
+    /// E[s^2]
+    pub fn second_moment(self) -> f64 {
+        self.variance + self.mean * self.mean
+    }
+}
+
+impl KeyDistribution {
+    /// Moments of a coefficient of a key with `lwe_dimension` coefficients.
+    pub fn moments(self, lwe_dimension: u64) -> KeyMoments {
+        match self {
+            Self::Binary => KeyMoments::BINARY,
+            Self::Ternary => KeyMoments {
+                mean: 0.0,
+                variance: 2.0 / 3.0,
+            },
+            Self::Gaussian { std_dev } => KeyMoments {
+                mean: 0.0,
+                variance: std_dev * std_dev,
+            },
+            Self::Sparse { hamming_weight } => {
+                let density = hamming_weight.min(lwe_dimension) as f64 / lwe_dimension as f64;
+                KeyMoments {
+                    mean: density,
+                    variance: density * (1.0 - density),
+                }
+            }
+        }
+    }
+
+    pub fn mean(self, lwe_dimension: u64) -> f64 {
+        self.moments(lwe_dimension).mean
+    }
+
+    pub fn variance(self, lwe_dimension: u64) -> f64 {
+        self.moments(lwe_dimension).variance
+    }
+
+    /// Whether the moments depend on the key dimension, i.e. the key has a fixed weight.
+    pub fn has_fixed_weight(self) -> bool {
+        matches!(self, Self::Sparse { .. })
+    }
+
+    /// Moments of a coefficient whatever the key dimension, `None` for fixed weight keys.
+    pub fn coefficient_moments(self) -> Option<KeyMoments> {
+        (!self.has_fixed_weight()).then(|| self.moments(1))
+    }
+
+    /// Largest absolute value a coefficient can take, `None` for unbounded gaussian keys.
+    pub fn max_abs_coefficient(self) -> Option<f64> {
+        match self {
+            Self::Binary | Self::Ternary | Self::Sparse { .. } => Some(1.0),
+            Self::Gaussian { .. } => None,
+        }
+    }
+
+    /// Identifies the distribution in cache keys.
+    pub(crate) fn fingerprint(self) -> [u64; 2] {
+        match self {
+            Self::Binary => [0, 0],
+            Self::Ternary => [1, 0],
+            Self::Gaussian { std_dev } => [2, std_dev.to_bits()],
+            Self::Sparse { hamming_weight } => [3, hamming_weight],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sparse_moments_follow_the_density() {
+        let sparse = KeyDistribution::Sparse { hamming_weight: 64 };
+        assert_eq!(sparse.mean(256), 0.25);
+        assert_eq!(sparse.variance(256), 0.1875);
+        assert_eq!(
+            KeyDistribution::Sparse {
+                hamming_weight: 128
+            }
+            .moments(256),
+            KeyMoments::BINARY
+        );
+        assert_eq!(
+            KeyDistribution::Ternary.moments(256).second_moment(),
+            2.0 / 3.0
+        );
+    }
+}
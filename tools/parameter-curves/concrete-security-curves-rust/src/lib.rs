@@ -1 +1,3 @@
+pub mod estimator;
 pub mod gaussian;
+pub mod key_distribution;